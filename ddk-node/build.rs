@@ -15,10 +15,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build_client(true)
         .build_server(true)
         .out_dir("./src")
-        .type_attribute("InfoResponse", "#[derive(serde::Serialize, serde::Deserialize)]")
-        .type_attribute("WalletBalanceResponse", "#[derive(serde::Serialize, serde::Deserialize)]")
-        .type_attribute("NewAddressResponse", "#[derive(serde::Serialize, serde::Deserialize)]")
-        .type_attribute("Peer", "#[derive(serde::Serialize, serde::Deserialize)]")
+        // Every message is serde so the JSON-RPC server can use the gRPC types. Fields missing
+        // from a JSON request take their protobuf default.
+        .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
+        .type_attribute(".", "#[serde(default)]")
         .compile(&proto_paths, &[dir])?;
 
     Ok(())
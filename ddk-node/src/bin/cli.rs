@@ -1,25 +1,43 @@
 use core::panic;
 
-use clap::{Parser, Subcommand};
-use ddk::bitcoin::Transaction;
+use clap::{Parser, Subcommand, ValueEnum};
+use ddk::bitcoin::{OutPoint, Transaction};
 use ddk::dlc::{EnumerationPayout, Payout};
-use ddk::dlc_manager::contract::contract_input::ContractInput;
 use ddk::dlc_manager::contract::offered_contract::OfferedContract;
+use ddk::dlc_messages::oracle_msgs::OracleAnnouncement;
+use ddk::dlc_messages::{Message, OfferDlc, WireMessage};
+use ddk::util::{
+    contract_fee_rate, contract_state, decode_dlc_message, deserialize_contract_bytes, simulate_payouts, OutcomeRange,
+};
 use ddk_node::ddkrpc::ddk_rpc_client::DdkRpcClient;
 use ddk_node::ddkrpc::{
-    AcceptOfferRequest, ConnectRequest, GetWalletTransactionsRequest, InfoRequest, ListContractsRequest, ListOffersRequest, ListOraclesRequest, ListPeersRequest, ListUtxosRequest, NewAddressRequest, SendOfferRequest, WalletBalanceRequest
+    ChannelRequest, CloseChannelRequest, ContractSummary, EstimateFeeRequest, ExportNostrKeyRequest, ShowKeysRequest, GetContractHistoryRequest, GetLabelRequest, SetLabelRequest, ListChannelsRequest, OpenChannelRequest, RenewChannelRequest, SettleChannelRequest,
+    AcceptOfferRequest, AddRelayRequest, ListRelaysRequest, RemoveRelayRequest, CancelOfferRequest, ConnectRequest, FillOfferRequest, FindContractsByEventRequest, GetCetListRequest, GetContractRequest, GetContractTransactionsRequest, GetOfferMessageRequest, GetWalletTransactionsRequest, ImportContractRequest, ImportOfferRequest, HealthRequest, HealthResponse, MessageStatsRequest, MessageTypeStats, InfoRequest, InfoResponse, ListContractsRequest, ListOffersRequest, ListOracleEventsRequest, ListOraclesRequest, ListPeersRequest, ListUtxosRequest, NewAddressRequest, PortfolioSummaryRequest, RebroadcastFundingRequest, ForceAttestationRequest, RescanRequest, ResendLastMessageRequest, RetryDeadLetterRequest, SendOfferRequest, SendRequest, StreamContractsRequest, VerifyFundingRequest, WalletBalanceRequest
 };
-use inquire::{Select, Text};
+use ddk_payouts::fee_rate::{parse_contract_input, FeeRate, FeeRateUnit};
+use ddk_payouts::fiat::{create_fiat_contract_input, FiatHedge};
+use inquire::{Confirm, Select, Text};
+use std::ops::RangeInclusive;
+
+const CLI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The major version of a `major.minor.patch` version.
+fn major_version(version: &str) -> &str {
+    version.split('.').next().unwrap_or(version)
+}
 
 #[derive(Debug, Clone, Parser)]
 #[clap(name = "ddk-cli")]
 #[clap(about = "CLI for ddk-node", author = "benny b <ben@bitcoinbay.foundation>")]
-#[clap(version = option_env ! ("CARGO_PKG_VERSION").unwrap_or("unknown"))]
+#[clap(version = CLI_VERSION)]
 struct DdkCliArgs {
     #[arg(short, long)]
     #[arg(help = "ddk-node gRPC server to connect to.")]
     #[arg(default_value = "http://127.0.0.1:3030")]
     pub server: String,
+    #[arg(long = "fee-rate-unit", global = true)]
+    #[arg(help = "Unit of fee rates given without one: sat/vb, sat/kwu or sat/kvb. Without it, fee rates must state their unit.")]
+    pub fee_rate_unit: Option<FeeRateUnit>,
     #[clap(subcommand)]
     pub command: CliCommand,
 }
@@ -27,18 +45,85 @@ struct DdkCliArgs {
 #[derive(Debug, Clone, Subcommand)]
 enum CliCommand {
     /// Gets information about the DDK instance
-    Info,
+    Info {
+        #[arg(short, long, help = "Include derivation paths and the wallet sync height.")]
+        verbose: bool,
+        #[arg(long = "key-format", value_enum, default_value_t = KeyFormat::Hex, help = "How to show the node pubkey.")]
+        key_format: KeyFormat,
+    },
+    /// Versions of the CLI, the node and the DLC protocol it speaks.
+    Version,
+    /// Check that the node can reach its backends and suggest fixes for what it cannot.
+    /// Exits with an error if any check fails.
+    Doctor,
     /// Pass a contract input to send an offer
     OfferContract(Offer),
     /// Retrieve the offers that ddk-node has received.
     Offers,
     /// Accept a DLC offer with the contract id string.
     AcceptOffer(Accept),
+    /// Cancel an offer you sent before the counterparty accepts it.
+    CancelOffer {
+        #[arg(help = "The contract id of the offer or a unique prefix of it.")]
+        contract_id: String,
+    },
+    /// Send a counterparty an offer for part of a partial offer.
+    FillOffer {
+        #[arg(help = "The contract id of the partial offer or a unique prefix of it.")]
+        contract_id: String,
+        #[arg(help = "The counterparty to send the fill to.")]
+        counter_party: String,
+        #[arg(help = "Collateral the counterparty puts up for the fill, in sats.")]
+        accept_collateral: u64,
+        #[arg(help = "Seconds until the offer expires. Defaults to three hours.")]
+        #[arg(short = 'e', long = "expiry")]
+        expiry_secs: Option<u64>,
+    },
+    /// Print the hex encoded offer message of a contract, to hand to the counterparty without
+    /// a transport.
+    ExportOffer {
+        #[arg(help = "The contract id of the offer or a unique prefix of it.")]
+        contract_id: String,
+    },
+    /// Take in an offer exported by a counterparty with `export-offer`.
+    ImportOffer {
+        #[arg(help = "The counterparty that made the offer.")]
+        counter_party: String,
+        #[arg(help = "File with the hex encoded offer message.")]
+        file: std::path::PathBuf,
+    },
     /// List contracts.
     Contracts,
+    /// Show the collateral at risk in signed and confirmed contracts, and their payout at the
+    /// outcome the oracle attested, if it has.
+    Portfolio,
+    /// Contract commands
+    #[clap(subcommand)]
+    Contract(ContractCommand),
+    /// DLC channel commands
+    #[clap(subcommand)]
+    Channel(ChannelCommand),
     /// Wallet commands
     #[clap(subcommand)]
     Wallet(WalletCommand),
+    /// Oracle commands
+    #[clap(subcommand)]
+    Oracle(OracleCommand),
+    /// Show the note of a transaction or contract, or set it if a label is given.
+    Label {
+        #[arg(value_enum, help = "What the id is.")]
+        kind: LabelKind,
+        #[arg(help = "The txid or contract id.")]
+        id: String,
+        #[arg(help = "The note. An empty note removes it.")]
+        label: Option<String>,
+    },
+    /// Nostr identity commands
+    #[clap(subcommand)]
+    Keys(KeysCommand),
+    /// Nostr transport commands
+    #[clap(subcommand)]
+    Nostr(NostrCommand),
     /// Get the peers connected to the node.
     Peers,
     /// Connect to another DDK node.
@@ -46,15 +131,100 @@ enum CliCommand {
         #[arg(help = "The counter party to connect to. <PUBKEY>@<HOST>")]
         connect_string: String
     },
+    /// Developer tools that do not require a running node.
+    #[clap(subcommand)]
+    Debug(DebugCommand),
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum KeysCommand {
+    #[command(about = "Show the node pubkey and the Nostr npub to receive offers on.")]
+    Show,
+    #[command(about = "Print the Nostr nsec. Anyone who sees it can act as this node on Nostr.")]
+    Export {
+        #[arg(long = "i-understand-the-risk", help = "Required to print the secret key.")]
+        i_understand_the_risk: bool,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum NostrCommand {
+    #[command(about = "Show the count and size of the DLC messages sent and received over nostr, by type.")]
+    Stats,
+    #[command(about = "Add a relay to listen and send on. The node keeps it across restarts.")]
+    AddRelay {
+        #[arg(help = "Websocket url of the relay, e.g. wss://relay.damus.io")]
+        relay: String,
+    },
+    #[command(about = "Stop listening and sending on a relay.")]
+    RemoveRelay {
+        #[arg(help = "Websocket url of the relay.")]
+        relay: String,
+    },
+    #[command(about = "List the relays of the node and the state of their connection.")]
+    Relays,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum DebugCommand {
+    #[command(about = "Decode a base64 encoded DLC message, prefixed with its type id.")]
+    DecodeMessage {
+        #[arg(help = "The base64 encoded message.")]
+        message: String,
+    },
 }
 
 #[derive(Parser, Clone, Debug)]
 struct Offer {
-    #[arg(help = "Path to a contract input file. Eventually to be a repl asking contract params")]
+    #[arg(help = "Path to a contract input file, or `-` to read from stdin. Eventually to be a repl asking contract params")]
     #[arg(short = 'f', long = "file")]
     pub contract_input_file: Option<String>,
     #[arg(help = "The contract counterparty to send to.")]
     pub counter_party: String,
+    #[arg(help = "Seconds until the offer expires. Defaults to three hours.")]
+    #[arg(short = 'e', long = "expiry")]
+    pub expiry_secs: Option<u64>,
+    #[arg(help = "Allow the offer to be filled in parts with `fill-offer`. Enum contracts only.")]
+    #[arg(long = "allow-partial")]
+    pub allow_partial: bool,
+    #[arg(help = "Enter the fee rate or use the node's fee estimate.")]
+    #[arg(long = "fee-rate-source", value_enum, default_value_t = FeeRateSource::Manual)]
+    pub fee_rate_source: FeeRateSource,
+    #[arg(help = "Confirmation target of the fee estimate: urgent, normal, or background.")]
+    #[arg(long = "fee-target", default_value = "normal")]
+    pub fee_target: String,
+    #[arg(help = "Blocks the funding transaction should confirm within. The node estimates the fee rate for it.")]
+    #[arg(long = "funding-target")]
+    pub funding_target: Option<u16>,
+    #[arg(help = "Lowest fee rate accepted without a warning, such as 1sat/vb.")]
+    #[arg(long = "min-fee-rate", default_value = "1sat/vb")]
+    pub min_fee_rate: String,
+    #[arg(help = "Highest fee rate accepted without a warning, such as 100sat/vb.")]
+    #[arg(long = "max-fee-rate", default_value = "100sat/vb")]
+    pub max_fee_rate: String,
+    #[arg(help = "A local note to attach to the contract.")]
+    #[arg(long)]
+    pub label: Option<String>,
+    #[arg(help = "Fund the offer from only this utxo, as `txid:vout`. Repeat for more utxos.")]
+    #[arg(long = "utxo")]
+    pub utxos: Vec<OutPoint>,
+}
+
+/// Encodings of the node pubkey. Hex is the compressed key, xonly and npub drop its parity as
+/// Nostr does.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum KeyFormat {
+    Hex,
+    Xonly,
+    Npub,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum FeeRateSource {
+    Manual,
+    Estimate,
+    /// Choose how many blocks the funding transaction should confirm within.
+    Target,
 }
 
 #[derive(Clone, Debug, Subcommand)]
@@ -63,16 +233,210 @@ enum WalletCommand {
     Balance,
     #[command(about = "Generate a new, unused address from the wallet.")]
     NewAddress,
-    #[command(about = "Get the wallet transactions.")]
-    Transactions,
+    #[command(about = "Get the wallet transactions, newest first.")]
+    Transactions {
+        #[arg(long, help = "Show at most this many transactions.")]
+        limit: Option<u32>,
+        #[arg(long, help = "Only show transactions confirmed below this height.")]
+        before_height: Option<u32>,
+    },
     #[command(about = "Get the wallet utxos.")]
     Utxos,
+    #[command(about = "Send an amount to an address.")]
+    Send {
+        #[arg(help = "The address to send to.")]
+        address: String,
+        #[arg(help = "The amount to send, in sats.")]
+        amount: u64,
+        #[arg(long = "fee-rate", default_value = "1sat/vb", help = "Fee rate with its unit, such as 10sat/vb, 2500sat/kwu or 10000sat/kvb.")]
+        fee_rate: String,
+        #[arg(long = "utxo", help = "Spend only this utxo, as `txid:vout`. Repeat for more utxos.")]
+        utxos: Vec<OutPoint>,
+    },
+    #[command(about = "Rescan the chain for wallet transactions after a restore.")]
+    Rescan {
+        #[arg(long, help = "Height to rescan from. Defaults to the wallet birthday, pass 0 to rescan from genesis.")]
+        from_height: Option<u32>,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum OracleCommand {
+    #[command(about = "List the announced events of the oracle. Oracles that cannot list their events show the announcements fetched so far.")]
+    Events {
+        #[arg(long, help = "Only events whose id contains the asset, like BTCUSD.")]
+        asset: Option<String>,
+        #[arg(long, value_parser = parse_date, help = "Only events maturing at or after the date. YYYY-MM-DD or unix seconds.")]
+        from: Option<u32>,
+        #[arg(long, value_parser = parse_date, help = "Only events maturing at or before the date. YYYY-MM-DD or unix seconds.")]
+        to: Option<u32>,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum ContractCommand {
+    #[command(about = "List contract summaries, optionally filtered by state and direction.")]
+    List {
+        #[arg(long, help = "Only contracts in this state, such as `confirmed` or `failed sign`.")]
+        state: Option<String>,
+        #[arg(long, help = "Only `outgoing` contracts we offered or `incoming` contracts we were offered.")]
+        direction: Option<String>,
+        #[arg(long, help = "Print each contract as a line of JSON.")]
+        json: bool,
+        #[arg(long, help = "Print each contract as it arrives instead of after the whole list.")]
+        stream: bool,
+    },
+    #[command(about = "Rebroadcast the funding transaction of an unconfirmed contract.")]
+    Rebroadcast {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+    },
+    #[command(about = "Send the last message of a contract again when the counterparty did not receive it.")]
+    ResendMessage {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+    },
+    #[command(about = "Send a message that ran out of delivery attempts again and clear the delivery failed flag of its contract.")]
+    RetryDeadLetter {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+    },
+    #[command(about = "Rebuild the funding output of a contract from the fund pubkeys and check it against the chain.")]
+    VerifyFunding {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+    },
+    #[command(about = "Show the state of a contract and whether its counterparty is online.")]
+    Show {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+    },
+    #[command(about = "Print a contract as hex, to back it up or import it on another node.")]
+    Export {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+    },
+    #[command(about = "Import a contract written by `contract export`. A stored contract with the same id is never replaced unless it is closed, refunded, rejected or failed and --overwrite is given.")]
+    Import {
+        #[arg(help = "File with the hex encoded contract.")]
+        file: std::path::PathBuf,
+        #[arg(long, help = "Replace a stored contract with the same id if it is terminal.")]
+        overwrite: bool,
+    },
+    #[command(about = "List the CETs of a signed contract with the outcomes they settle and their payouts.")]
+    CetList {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+        #[arg(long, default_value = "0")]
+        #[arg(help = "Number of CETs to skip.")]
+        offset: u32,
+        #[arg(long)]
+        #[arg(help = "Most CETs to list. Lists all of them if not set.")]
+        limit: Option<u32>,
+    },
+    #[command(about = "Get the on-chain transactions of a contract and the fees they paid.")]
+    Txs {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+    },
+    #[command(about = "Find the contracts that settle on an oracle event.")]
+    ByEvent {
+        #[arg(help = "The oracle event id.")]
+        event_id: String,
+    },
+    #[command(about = "Print every state transition of a contract.")]
+    History {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+    },
+    #[command(about = "Print your payout at each oracle outcome of a contract.")]
+    SimulateSettle {
+        #[arg(help = "The contract id.")]
+        contract_id: String,
+        #[arg(long, help = "Numerical outcomes to evaluate as min:max:step. Defaults to where the rounded payout changes.")]
+        range: Option<OutcomeRange>,
+    },
+    #[command(about = "Settle a confirmed contract with an oracle attestation from a JSON file. Needs a node started with --dev.")]
+    ForceAttestation {
+        #[arg(help = "The contract id or a unique prefix of it.")]
+        contract_id: String,
+        #[arg(help = "Path to the oracle attestation JSON file.")]
+        attestation_file: String,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum ChannelCommand {
+    #[command(about = "Offer a channel with a contract input file as its first contract.")]
+    Open {
+        #[arg(help = "The channel counterparty.")]
+        counter_party: String,
+        #[arg(help = "Path to the contract input file.")]
+        contract_input_file: String,
+    },
+    #[command(about = "Accept a channel offer.")]
+    Accept {
+        #[arg(help = "The channel id.")]
+        channel_id: String,
+    },
+    #[command(about = "Offer to settle the contract of a channel off-chain.")]
+    Settle {
+        #[arg(help = "The channel id.")]
+        channel_id: String,
+        #[arg(help = "Sats paid to the counterparty.")]
+        counter_payout: u64,
+    },
+    #[command(about = "Accept an offer to settle the contract of a channel.")]
+    AcceptSettle {
+        #[arg(help = "The channel id.")]
+        channel_id: String,
+    },
+    #[command(about = "Offer a new contract in a channel.")]
+    Renew {
+        #[arg(help = "The channel id.")]
+        channel_id: String,
+        #[arg(help = "Sats paid to the counterparty from the current contract.")]
+        counter_payout: u64,
+        #[arg(help = "Path to the contract input file of the new contract.")]
+        contract_input_file: String,
+    },
+    #[command(about = "Accept an offer of a new contract in a channel.")]
+    AcceptRenew {
+        #[arg(help = "The channel id.")]
+        channel_id: String,
+    },
+    #[command(about = "Offer to close a channel cooperatively.")]
+    Close {
+        #[arg(help = "The channel id.")]
+        channel_id: String,
+        #[arg(help = "Sats paid to the counterparty.")]
+        #[arg(default_value_t = 0)]
+        counter_payout: u64,
+        #[arg(long, help = "Close unilaterally with the latest signed state.")]
+        force: bool,
+    },
+    #[command(about = "Accept an offer to close a channel.")]
+    AcceptClose {
+        #[arg(help = "The channel id.")]
+        channel_id: String,
+    },
+    #[command(about = "List channels.")]
+    List,
 }
 
 #[derive(Parser, Clone, Debug)]
 struct Accept {
-    // The contract id string to accept.
+    // The contract id to accept, or a unique prefix of it.
     pub contract_id: String,
+    #[arg(help = "A local note to attach to the contract.")]
+    #[arg(long)]
+    pub label: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LabelKind {
+    Tx,
+    Contract,
 }
 
 #[derive(Parser, Clone, Debug)]
@@ -85,33 +449,128 @@ struct Connect {
 async fn main() -> anyhow::Result<()> {
     let args = DdkCliArgs::parse();
 
+    if let CliCommand::Debug(debug) = &args.command {
+        return debug_command(debug.clone());
+    }
+
+    if let CliCommand::Doctor = &args.command {
+        if !doctor(args.server).await {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let mut client = DdkRpcClient::connect(args.server).await?;
+    let fee_unit = args.fee_rate_unit;
 
     match args.command {
-        CliCommand::Info => {
-            let info = client.info(InfoRequest::default()).await?.into_inner();
-            print!("{}", serde_json::to_string_pretty(&info)?);
+        CliCommand::Info { verbose, key_format } => {
+            let info = client.info(InfoRequest { verbose }).await?.into_inner();
+            let pubkey = match key_format {
+                KeyFormat::Hex => &info.pubkey,
+                KeyFormat::Xonly => &info.pubkey_xonly,
+                KeyFormat::Npub => &info.pubkey_npub,
+            };
+            println!("pubkey:     {}", pubkey);
+            println!("network:    {}", info.network);
+            println!("transport:  {} ({} connected peers)", info.transport, info.connected_peers);
+            println!("storage:    {}", info.storage);
+            println!("oracle:     {} at {}", info.oracle, info.oracle_endpoint);
+            if info.contracts.is_empty() {
+                println!("contracts:  none");
+            } else {
+                let counts = info.contracts.iter()
+                    .map(|c| format!("{} {}", c.count, c.state))
+                    .collect::<Vec<_>>();
+                println!("contracts:  {}", counts.join(", "));
+            }
+            if let Some(limit) = info.max_active_contracts {
+                println!("limit:      {} active contracts", limit);
+            }
+            if let Some(cap) = info.max_contract_collateral {
+                println!("cap:        {} sats per contract", cap);
+            }
+            if let Some(height) = info.sync_height {
+                println!("synced to:  {}", height);
+            }
+            for path in &info.derivation_paths {
+                println!("path:       {}", path);
+            }
+        }
+        CliCommand::Version => {
+            let info = client.info(InfoRequest { verbose: false }).await?.into_inner();
+            println!("ddk-cli:    {}", CLI_VERSION);
+            println!("ddk-node:   {}", info.node_version);
+            println!("ddk:        {}", info.ddk_version);
+            println!("rust-dlc:   {}", info.rust_dlc_version);
+            println!("protocol:   {}", info.protocol_version);
+            if major_version(CLI_VERSION) != major_version(&info.node_version) {
+                eprintln!(
+                    "warning: ddk-cli {} and ddk-node {} are different major versions and may not work together.",
+                    CLI_VERSION, info.node_version
+                );
+            }
         }
         CliCommand::OfferContract(arg) => {
             // TODO: support multiple oracles
             let oracle = client.list_oracles(ListOraclesRequest::default()).await?.into_inner();
 
-            let contract_input = if let Some(file) = arg.contract_input_file {
-                let contract_string = std::fs::read_to_string(file)?;
-                serde_json::from_str::<ContractInput>(&contract_string)?
+            let from_stdin = arg.contract_input_file.as_deref() == Some("-");
+            let mut funding_target = arg.funding_target;
+            let mut contract_input = if let Some(file) = arg.contract_input_file.as_deref() {
+                let contract_string = if from_stdin {
+                    std::io::read_to_string(std::io::stdin())?
+                } else {
+                    std::fs::read_to_string(file)?
+                };
+                let contract_input = parse_contract_input(&contract_string, fee_unit)?;
+                contract_input
+                    .validate()
+                    .map_err(|e| anyhow::anyhow!("Invalid contract input. {:?}", e))?;
+                ddk_payouts::validate_contract_input(&contract_input)?;
+                let band = fee_rate_band(&arg, fee_unit)?;
+                let fee_rate = FeeRate::from_sat_per_vb(contract_input.fee_rate);
+                // Stdin is the contract input, so there is no one to confirm an out of band rate.
+                if !band.contains(&fee_rate) && (from_stdin || !confirm_fee_rate(fee_rate, &band)?) {
+                    return Err(anyhow::anyhow!("Fee rate {} is outside {}..={}.", fee_rate, band.start(), band.end()));
+                }
+                contract_input
             } else {
-                let contract_type = Select::new("Select type of contract.", vec!["enum", "numerical"]).prompt()?;
+                let contract_type = Select::new("Select type of contract.", vec!["enum", "numerical", "numerical in USD"]).prompt()?;
                 match contract_type {
                     "numerical" => {
                         let offer_collateral: u64 = Text::new("Collateral from you (sats):").prompt()?.parse()?;
                         let accept_collateral: u64 = Text::new("Collateral from counterparty (sats):").prompt()?.parse()?;
-                        let fee_rate: u64 = Text::new("Fee rate (sats/vbyte):").prompt()?.parse()?;
+                        let (fee_rate, target) = prompt_fee_rate(&mut client, &arg, fee_unit).await?;
+                        ddk_payouts::validate_offer_terms(offer_collateral, accept_collateral, fee_rate)?;
+                        funding_target = target;
                         let min_price: u64 = Text::new("Minimum Bitcoin price:").prompt()?.parse()?;
                         let max_price: u64 = Text::new("Maximum Bitcoin price:").prompt()?.parse()?;
                         let num_steps: u64 = Text::new("Number of rounding steps:").prompt()?.parse()?;
                         let oracle_pubkey = Text::new("Oracle public key:").prompt()?;
                         let event_id = Text::new("Oracle event id:").prompt()?;
-                        ddk_payouts::create_contract_input(min_price, max_price, num_steps, offer_collateral, accept_collateral, fee_rate, oracle_pubkey, event_id)
+                        ddk_payouts::create_contract_input(min_price, max_price, num_steps, offer_collateral, accept_collateral, fee_rate, oracle_pubkey, event_id, None)?
+                    }
+                    "numerical in USD" => {
+                        let notional: u64 = Text::new("Dollars your payout is worth:").prompt()?.parse()?;
+                        let reference_price: u64 = Text::new("Bitcoin price now:").prompt()?.parse()?;
+                        let floor_price: u64 = Text::new("Lowest Bitcoin price to hold the dollars at:").prompt()?.parse()?;
+                        let cap_price = Text::new("Highest Bitcoin price to hold the dollars at (empty for no cap):").prompt()?;
+                        let cap_price = match cap_price.trim() {
+                            "" => None,
+                            cap => Some(cap.parse()?),
+                        };
+                        let num_steps: u64 = Text::new("Number of sample prices:").with_default("50").prompt()?.parse()?;
+                        let announcement = prompt_oracle_event(&mut client).await?;
+                        let (fee_rate, target) = prompt_fee_rate(&mut client, &arg, fee_unit).await?;
+                        funding_target = target;
+                        let hedge = FiatHedge { notional, reference_price, floor_price, cap_price };
+                        let contract_input = create_fiat_contract_input(&hedge, &announcement, num_steps, fee_rate)?;
+                        println!(
+                            "You put up {} sats and the counterparty {} sats.",
+                            contract_input.offer_collateral, contract_input.accept_collateral
+                        );
+                        contract_input
                     }
                     "enum" => {
                         let offer_collateral: u64 = Text::new("Collateral from you (sats):").prompt()?.parse()?;
@@ -131,19 +590,43 @@ async fn main() -> anyhow::Result<()> {
                             };
                             outcome_payouts.push(outcome_payout)
                         }
-                        let fee_rate: u64 = Text::new("Fee rate (sats/vbyte):").prompt()?.parse()?; 
+                        let (fee_rate, target) = prompt_fee_rate(&mut client, &arg, fee_unit).await?;
+                        ddk_payouts::validate_offer_terms(offer_collateral, accept_collateral, fee_rate)?;
+                        funding_target = target;
                         // TODO: list possible events.
                         let event_id = Text::new("Oracle event id:").prompt()?;
-                        ddk_payouts::enumeration::create_contract_input(outcome_payouts, offer_collateral, accept_collateral, fee_rate, oracle.pubkey, event_id)
+                        ddk_payouts::enumeration::create_contract_input(outcome_payouts, offer_collateral, accept_collateral, fee_rate, oracle.pubkey, event_id)?
                     }
                     _ => panic!("Invalid contract type.")
                 }
             };
 
+            if let (Some(blocks), Some(_)) = (funding_target, arg.contract_input_file.as_deref()) {
+                // The node funds the offer at the rate of the target, not the rate of the file.
+                contract_input.fee_rate = estimate_fee_for_target(&mut client, blocks).await?;
+            }
+            let cost = ddk_payouts::estimate_total_cost(&contract_input, contract_input.fee_rate);
+            let summary = format!(
+                "Offer costs {} sats: {} collateral, {} funding fee, {} CET fee reserve.",
+                cost.total(), cost.collateral, cost.funding_fee, cost.cet_fee_reserve
+            );
+            if arg.contract_input_file.is_some() {
+                // Stdout is reserved for the offer.
+                eprintln!("{}", summary);
+            } else if !Confirm::new(&format!("{} Send offer?", summary)).with_default(true).prompt()? {
+                return Ok(());
+            }
+
             let contract_input = serde_json::to_vec(&contract_input)?;
-            let offer = client.send_offer(SendOfferRequest { contract_input, counter_party: arg.counter_party}).await?.into_inner();
-            let offer_dlc = serde_json::to_string_pretty(&offer.offer_dlc)?;
-            print!("{}", offer_dlc);
+            let offer = client.send_offer(SendOfferRequest { contract_input, counter_party: arg.counter_party, expiry_secs: arg.expiry_secs, allow_partial: arg.allow_partial, label: arg.label, utxos: arg.utxos.iter().map(|utxo| utxo.to_string()).collect(), funding_target_blocks: funding_target.map(u32::from) }).await?.into_inner();
+            if from_stdin {
+                // Only print the contract id so the output can be piped.
+                println!("{}", offer.contract_id);
+            } else {
+                let offer_dlc: OfferDlc = serde_json::from_slice(&offer.offer_dlc)?;
+                println!("{}", serde_json::to_string_pretty(&offer_dlc)?);
+                println!("Offer sent, contract id: {}", offer.contract_id);
+            }
         }
         CliCommand::Offers => {
             let offers_request = client.list_offers(ListOffersRequest {}).await?.into_inner();
@@ -168,31 +651,307 @@ async fn main() -> anyhow::Result<()> {
             let accept = client
                 .accept_offer(AcceptOfferRequest {
                     contract_id: accept.contract_id,
+                    label: accept.label,
                 })
-                .await?
-                .into_inner();
+                .await;
+            let accept = match accept {
+                Ok(accept) => accept.into_inner(),
+                Err(status) if status.code() == tonic::Code::Unavailable => {
+                    return Err(anyhow::anyhow!("{} The offer is unchanged, try accepting it again later.", status.message()));
+                }
+                Err(status) => return Err(status.into()),
+            };
             let accept_dlc = serde_json::to_string_pretty(&accept.accept_dlc)?;
             println!("{:?}", accept_dlc)
         }
+        CliCommand::CancelOffer { contract_id } => {
+            client
+                .cancel_offer(CancelOfferRequest { contract_id: contract_id.clone() })
+                .await?;
+            println!("Cancelled offer {}", contract_id)
+        }
+        CliCommand::FillOffer { contract_id, counter_party, accept_collateral, expiry_secs } => {
+            let fill = client
+                .fill_offer(FillOfferRequest { contract_id, counter_party, accept_collateral, expiry_secs })
+                .await?
+                .into_inner();
+            let offer_dlc: OfferDlc = serde_json::from_slice(&fill.offer_dlc)?;
+            println!("{}", hex::encode(offer_dlc.temporary_contract_id));
+        }
+        CliCommand::ExportOffer { contract_id } => {
+            let offer = client
+                .get_offer_message(GetOfferMessageRequest { contract_id })
+                .await?
+                .into_inner();
+            println!("{}", offer.offer);
+        }
+        CliCommand::ImportOffer { counter_party, file } => {
+            let offer = std::fs::read_to_string(&file)?;
+            let imported = client
+                .import_offer(ImportOfferRequest { offer, counter_party })
+                .await?
+                .into_inner();
+            println!("Imported offer, contract id: {}", imported.contract_id);
+        }
+        CliCommand::Portfolio => {
+            let portfolio = client.portfolio_summary(PortfolioSummaryRequest {}).await?.into_inner();
+            for risk in &portfolio.contracts {
+                let mut line = format!("{} collateral {} max loss {}", risk.contract_id, risk.collateral, risk.max_loss);
+                if let Some(payout) = risk.mark_to_market {
+                    line.push_str(&format!(" payout {}", payout));
+                }
+                println!("{}", line);
+            }
+            println!("Collateral committed: {} sats", portfolio.total_collateral);
+            println!("Worst case loss: {} sats", portfolio.total_max_loss);
+            println!("Payout of attested contracts: {} sats", portfolio.mark_to_market);
+        }
         CliCommand::Contracts => {
-            let _contracts = client.list_contracts(ListContractsRequest {}).await?.into_inner().contracts;
-            // for contract in contracts {
-            //     let contract = deserialize_contract_bytes(&contract).unwrap();
-            //     match contract {
-            //         Contract::Offered(o) => {
-            //             print!("{:?}", o)
-            //         }
-            //         Contract::Signed(s) => {
-            //             print!("{:?}", s)
-            //         }
-            //         Contract::Accepted(a) => {
-            //             print!("{:?}", a)
-            //         }
-            //         _ => ()
-            //     }
-            // }
+            let contracts = client.list_contracts(ListContractsRequest {}).await?.into_inner();
+            for contract in &contracts.contracts {
+                let contract = deserialize_contract_bytes(contract)?;
+                let contract_id = hex::encode(contract.get_id());
+                let expiry = contracts.offer_expiries.iter().find(|e| e.contract_id == contract_id);
+                let mut line = format!("{} {}", contract_id, contract_state(&contract));
+                if let Some(fee_rate) = contract_fee_rate(&contract) {
+                    line.push_str(&format!(" {} sat/vB", fee_rate));
+                }
+                if let Some(e) = expiry {
+                    line.push_str(&format!(" (expires in {}s)", e.expires_in));
+                }
+                if let Some(label) = contracts.labels.iter().find(|l| l.id == contract_id) {
+                    line.push_str(&format!(" \"{}\"", label.label));
+                }
+                if let Some(conflict) = contracts.funding_conflicts.iter().find(|c| c.contract_id == contract_id) {
+                    line.push_str(&format!(
+                        " FUNDING CONFLICT: input {} spent by {}",
+                        conflict.outpoint, conflict.conflicting_txid
+                    ));
+                }
+                if let Some(dead_letter) = contracts.dead_letters.iter().find(|d| d.contract_id == contract_id) {
+                    line.push_str(&format!(" DELIVERY FAILED after {} attempts", dead_letter.attempts));
+                }
+                println!("{}", line);
+            }
+        }
+        CliCommand::Debug(_) => unreachable!("Debug commands do not connect to a node."),
+        CliCommand::Doctor => unreachable!("The doctor connects to the node itself."),
+        CliCommand::Contract(contract) => match contract {
+            ContractCommand::List { state, direction, json, stream } => {
+                let mut contracts = client
+                    .stream_contracts(StreamContractsRequest { state, direction })
+                    .await?
+                    .into_inner();
+                let line = |summary: &ContractSummary| -> anyhow::Result<String> {
+                    if json {
+                        return Ok(serde_json::to_string(summary)?);
+                    }
+                    Ok(format!(
+                        "{} {} {}",
+                        summary.contract_id,
+                        summary.state,
+                        summary.direction.as_deref().unwrap_or("-")
+                    ))
+                };
+                let mut buffered = Vec::new();
+                while let Some(summary) = contracts.message().await? {
+                    if stream {
+                        println!("{}", line(&summary)?);
+                    } else {
+                        buffered.push(line(&summary)?);
+                    }
+                }
+                for line in buffered {
+                    println!("{}", line);
+                }
+            }
+            ContractCommand::Rebroadcast { contract_id } => {
+                let rebroadcast = client
+                    .rebroadcast_funding(RebroadcastFundingRequest { contract_id })
+                    .await?
+                    .into_inner();
+                if rebroadcast.accepted {
+                    println!("Backend accepted funding transaction {}", rebroadcast.txid);
+                } else {
+                    println!("Backend rejected rebroadcast: {}", rebroadcast.error);
+                }
+            }
+            ContractCommand::ResendMessage { contract_id } => {
+                let resent = client
+                    .resend_last_message(ResendLastMessageRequest { contract_id })
+                    .await?
+                    .into_inner();
+                match resent.acknowledgement.is_empty() {
+                    true => println!("Message resent: {}", resent.receipt),
+                    false => println!("Message resent: {} ({})", resent.receipt, resent.acknowledgement),
+                }
+            }
+            ContractCommand::RetryDeadLetter { contract_id } => {
+                let retried = client
+                    .retry_dead_letter(RetryDeadLetterRequest { contract_id })
+                    .await?
+                    .into_inner();
+                match retried.acknowledgement.is_empty() {
+                    true => println!("Message requeued: {}", retried.receipt),
+                    false => println!("Message requeued: {} ({})", retried.receipt, retried.acknowledgement),
+                }
+            }
+            ContractCommand::Show { contract_id } => {
+                let contract = client
+                    .get_contract(GetContractRequest { contract_id })
+                    .await?
+                    .into_inner();
+                let online = match contract.counterparty_online {
+                    Some(true) => "online",
+                    Some(false) => "offline",
+                    None => "unknown",
+                };
+                println!("state:         {}", contract.state);
+                println!("counterparty:  {} ({})", contract.counter_party, online);
+            }
+            ContractCommand::Export { contract_id } => {
+                let contract = client
+                    .get_contract(GetContractRequest { contract_id })
+                    .await?
+                    .into_inner();
+                println!("{}", hex::encode(contract.contract));
+            }
+            ContractCommand::Import { file, overwrite } => {
+                let contract = std::fs::read_to_string(file)?;
+                let imported = client
+                    .import_contract(ImportContractRequest { contract, overwrite })
+                    .await?
+                    .into_inner();
+                println!("Imported {} contract {}", imported.state, imported.contract_id);
+            }
+            ContractCommand::CetList { contract_id, offset, limit } => {
+                let list = client
+                    .get_cet_list(GetCetListRequest { contract_id, offset, limit })
+                    .await?
+                    .into_inner();
+                for cet in &list.cets {
+                    let outcomes = match cet.first_outcome == cet.last_outcome {
+                        true => cet.first_outcome.clone(),
+                        false => format!("{}-{}", cet.first_outcome, cet.last_outcome),
+                    };
+                    println!(
+                        "{:>5} {} outcomes {} offer {} sats accept {} sats",
+                        cet.index, cet.txid, outcomes, cet.offer_payout, cet.accept_payout
+                    );
+                }
+                println!("{} of {} CETs", list.cets.len(), list.total);
+            }
+            ContractCommand::VerifyFunding { contract_id } => {
+                let funding = client
+                    .verify_funding(VerifyFundingRequest { contract_id })
+                    .await?
+                    .into_inner();
+                println!("Funding output matches the fund pubkeys of the contract.");
+                println!("outpoint:       {}", funding.outpoint);
+                println!("address:        {}", funding.address);
+                println!("value:          {} sats", funding.value);
+                println!("witness script: {}", funding.witness_script);
+            }
+            ContractCommand::Txs { contract_id } => {
+                let txs = client
+                    .get_contract_transactions(GetContractTransactionsRequest { contract_id })
+                    .await?
+                    .into_inner();
+                print!("{}", serde_json::to_string_pretty(&txs)?);
+            }
+            ContractCommand::ByEvent { event_id } => {
+                let found = client
+                    .find_contracts_by_event(FindContractsByEventRequest { event_id })
+                    .await?
+                    .into_inner();
+                for contract in &found.contracts {
+                    let contract = deserialize_contract_bytes(contract)?;
+                    println!("{} {}", hex::encode(contract.get_id()), contract_state(&contract));
+                }
+            }
+            ContractCommand::History { contract_id } => {
+                let history = client
+                    .get_contract_history(GetContractHistoryRequest { contract_id })
+                    .await?
+                    .into_inner();
+                for transition in history.transitions {
+                    let from = transition.from_state.unwrap_or_else(|| "new".to_string());
+                    let txid = transition.txid.unwrap_or_default();
+                    println!("{} {} -> {} {}", transition.timestamp, from, transition.to_state, txid);
+                }
+            }
+            ContractCommand::SimulateSettle { contract_id, range } => {
+                let contracts = client.list_contracts(ListContractsRequest {}).await?.into_inner();
+                let contract = contracts
+                    .contracts
+                    .iter()
+                    .map(deserialize_contract_bytes)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .find(|c| hex::encode(c.get_id()) == contract_id)
+                    .ok_or_else(|| anyhow::anyhow!("Contract {} not found.", contract_id))?;
+                let payouts = simulate_payouts(&contract, range)?;
+                let width = payouts.iter().map(|(o, _)| o.len()).max().unwrap_or(0).max(7);
+                println!("{:<width$} payout", "outcome");
+                for (outcome, payout) in payouts {
+                    println!("{:<width$} {}", outcome, payout);
+                }
+            }
+            ContractCommand::ForceAttestation { contract_id, attestation_file } => {
+                let attestation = std::fs::read(attestation_file)?;
+                let settled = client
+                    .force_attestation(ForceAttestationRequest { contract_id, attestation })
+                    .await?
+                    .into_inner();
+                print!("{}", serde_json::to_string_pretty(&settled)?);
+            }
+        },
+        CliCommand::Channel(channel) => {
+            let channel_id = match channel {
+                ChannelCommand::Open { counter_party, contract_input_file } => {
+                    let contract_input = read_contract_input(&contract_input_file, fee_unit)?;
+                    client.open_channel(OpenChannelRequest { contract_input, counter_party }).await?
+                }
+                ChannelCommand::Accept { channel_id } => {
+                    client.accept_channel(ChannelRequest { channel_id }).await?
+                }
+                ChannelCommand::Settle { channel_id, counter_payout } => {
+                    client.settle_channel(SettleChannelRequest { channel_id, counter_payout }).await?
+                }
+                ChannelCommand::AcceptSettle { channel_id } => {
+                    client.accept_settle_channel(ChannelRequest { channel_id }).await?
+                }
+                ChannelCommand::Renew { channel_id, counter_payout, contract_input_file } => {
+                    let contract_input = read_contract_input(&contract_input_file, fee_unit)?;
+                    client.renew_channel(RenewChannelRequest { channel_id, counter_payout, contract_input }).await?
+                }
+                ChannelCommand::AcceptRenew { channel_id } => {
+                    client.accept_renew_channel(ChannelRequest { channel_id }).await?
+                }
+                ChannelCommand::Close { channel_id, counter_payout, force } => {
+                    client.close_channel(CloseChannelRequest { channel_id, counter_payout, force }).await?
+                }
+                ChannelCommand::AcceptClose { channel_id } => {
+                    client.accept_close_channel(ChannelRequest { channel_id }).await?
+                }
+                ChannelCommand::List => {
+                    let channels = client.list_channels(ListChannelsRequest {}).await?.into_inner();
+                    for channel in channels.channels {
+                        println!("{} {} {}", channel.channel_id, channel.counter_party, channel.state);
+                    }
+                    return Ok(());
+                }
+            };
+            println!("{}", channel_id.into_inner().channel_id);
         }
         CliCommand::Wallet(wallet) => match wallet {
+            WalletCommand::Rescan { from_height } => {
+                let rescan = client
+                    .rescan(RescanRequest { from_height })
+                    .await?
+                    .into_inner();
+                print!("{}", serde_json::to_string_pretty(&rescan)?);
+            }
             WalletCommand::Balance => {
                 let balance = client
                     .wallet_balance(WalletBalanceRequest::default())
@@ -209,17 +968,21 @@ async fn main() -> anyhow::Result<()> {
                 let pretty_string = serde_json::to_string_pretty(&address)?;
                 println!("{}", pretty_string);
             }
-            WalletCommand::Transactions => {
-                let transactions = client
-                    .get_wallet_transactions(GetWalletTransactionsRequest::default())
+            WalletCommand::Transactions { limit, before_height } => {
+                let mut transactions = client
+                    .get_wallet_transactions(GetWalletTransactionsRequest { limit, before_height })
                     .await?
                     .into_inner();
-                let txns = transactions.transactions
-                    .iter()
-                    .map(|txn| serde_json::from_slice(txn).unwrap())
-                    .collect::<Vec<Transaction>>();
-                let txns = serde_json::to_string_pretty(&txns)?;
-                print!("{}", txns)
+                while let Some(record) = transactions.message().await? {
+                    let txn: Transaction = serde_json::from_slice(&record.transaction)?;
+                    let mut value = serde_json::to_value(&txn)?;
+                    value["height"] = record.height.into();
+                    value["time"] = record.time.into();
+                    if let Some(label) = record.label {
+                        value["label"] = label.into();
+                    }
+                    println!("{}", serde_json::to_string_pretty(&value)?);
+                }
             }
             WalletCommand::Utxos => {
                 let utxos = client
@@ -229,7 +992,84 @@ async fn main() -> anyhow::Result<()> {
                 let utxos = serde_json::to_string_pretty(&utxos.utxos)?;
                 print!("{}", utxos)
             }
+            WalletCommand::Send { address, amount, fee_rate, utxos } => {
+                let utxos = utxos.iter().map(|utxo| utxo.to_string()).collect();
+                let fee_rate = FeeRate::parse(&fee_rate, fee_unit)?.sat_per_vb();
+                let sent = client
+                    .send(SendRequest { address, amount, fee_rate, utxos })
+                    .await?
+                    .into_inner();
+                print!("{}", serde_json::to_string_pretty(&sent)?);
+            }
         },
+        CliCommand::Label { kind, id, label } => {
+            let kind = match kind {
+                LabelKind::Tx => "tx",
+                LabelKind::Contract => "contract",
+            }
+            .to_string();
+            match label {
+                Some(label) => {
+                    client.set_label(SetLabelRequest { kind, id, label }).await?;
+                }
+                None => {
+                    let label = client.get_label(GetLabelRequest { kind, id }).await?.into_inner();
+                    println!("{}", label.label.unwrap_or_default());
+                }
+            }
+        }
+        CliCommand::Keys(keys) => match keys {
+            KeysCommand::Show => {
+                let keys = client.show_keys(ShowKeysRequest {}).await?.into_inner();
+                println!("node pubkey: {}", keys.node_pubkey);
+                println!("npub:        {}", keys.npub);
+            }
+            KeysCommand::Export { i_understand_the_risk } => {
+                if !i_understand_the_risk {
+                    return Err(anyhow::anyhow!("Exporting prints the secret key. Pass --i-understand-the-risk to continue."));
+                }
+                if !Confirm::new("Print the Nostr secret key to this terminal?").with_default(false).prompt()? {
+                    return Ok(());
+                }
+                let key = client.export_nostr_key(ExportNostrKeyRequest {}).await?.into_inner();
+                println!("{}", key.nsec);
+            }
+        },
+        CliCommand::Nostr(NostrCommand::Stats) => {
+            let stats = client.message_stats(MessageStatsRequest {}).await?.into_inner();
+            print_message_stats("sent", &stats.sent);
+            print_message_stats("received", &stats.received);
+        }
+        CliCommand::Nostr(NostrCommand::AddRelay { relay }) => {
+            let added = client.add_relay(AddRelayRequest { relay }).await?.into_inner();
+            println!("Added relay {}", added.relay);
+        }
+        CliCommand::Nostr(NostrCommand::RemoveRelay { relay }) => {
+            let removed = client.remove_relay(RemoveRelayRequest { relay }).await?.into_inner();
+            println!("Removed relay {}", removed.relay);
+        }
+        CliCommand::Nostr(NostrCommand::Relays) => {
+            let relays = client.list_relays(ListRelaysRequest {}).await?.into_inner();
+            if relays.relays.is_empty() {
+                println!("No relays.");
+            }
+            for relay in relays.relays {
+                println!("{} {}", relay.url, relay.status);
+            }
+        }
+        CliCommand::Oracle(OracleCommand::Events { asset, from, to }) => {
+            let events = client
+                .list_oracle_events(ListOracleEventsRequest { asset, from, to })
+                .await?
+                .into_inner()
+                .events;
+            if events.is_empty() {
+                println!("No oracle events found.");
+            }
+            for event in events {
+                println!("{} {}", event.maturity, event.event_id);
+            }
+        }
         CliCommand::Peers => {
             let peers_response = client.list_peers(ListPeersRequest::default()).await?.into_inner();
             let peers = serde_json::to_string_pretty(&peers_response.peers)?;
@@ -245,3 +1085,323 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+fn print_message_stats(direction: &str, stats: &[MessageTypeStats]) {
+    println!("{}:", direction);
+    if stats.is_empty() {
+        println!("  no messages");
+    }
+    for stat in stats {
+        println!(
+            "  {}: {} messages, {} bytes, {} bytes encrypted",
+            stat.message_type, stat.messages, stat.plaintext_bytes, stat.encrypted_bytes
+        );
+    }
+}
+
+/// Blocks the wallet may be behind esplora before the doctor warns. The wallet syncs on each
+/// periodic check, so it is often a block or two behind.
+const WALLET_SYNC_LAG_BLOCKS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One line of the `doctor` report.
+struct Check {
+    status: CheckStatus,
+    name: &'static str,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: String) -> Self {
+        Check { status: CheckStatus::Pass, name, detail, fix: None }
+    }
+
+    fn warn(name: &'static str, detail: String, fix: String) -> Self {
+        Check { status: CheckStatus::Warn, name, detail, fix: Some(fix) }
+    }
+
+    fn fail(name: &'static str, detail: String, fix: String) -> Self {
+        Check { status: CheckStatus::Fail, name, detail, fix: Some(fix) }
+    }
+
+    fn print(&self) {
+        let status = match self.status {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "FAIL",
+        };
+        println!("{:<5} {:<9} {}", status, self.name, self.detail);
+        if let Some(fix) = &self.fix {
+            println!("{:<15} fix: {}", "", fix);
+        }
+    }
+}
+
+/// Run the `doctor` checks against the node at `server` and print the report. Returns false if
+/// any check failed.
+async fn doctor(server: String) -> bool {
+    let mut checks = Vec::new();
+    match doctor_node(&server).await {
+        Ok((info, health)) => {
+            checks.push(Check::pass("node", format!("ddk-node {} on {}", info.node_version, info.network)));
+            checks.extend(health_checks(&info, &health));
+        }
+        Err(check) => checks.push(check),
+    }
+
+    for check in &checks {
+        check.print();
+    }
+    let failed = checks.iter().filter(|c| c.status == CheckStatus::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == CheckStatus::Warn).count();
+    println!("{} checks, {} failed, {} warnings", checks.len(), failed, warned);
+    failed == 0
+}
+
+async fn doctor_node(server: &str) -> Result<(InfoResponse, HealthResponse), Check> {
+    let unreachable = |e: String| {
+        Check::fail(
+            "node",
+            format!("cannot reach ddk-node at {}: {}", server, e),
+            "Start ddk-node, or pass its gRPC address with --server.".to_string(),
+        )
+    };
+    let mut client = DdkRpcClient::connect(server.to_string())
+        .await
+        .map_err(|e| unreachable(e.to_string()))?;
+    let info = client
+        .info(InfoRequest { verbose: false })
+        .await
+        .map_err(|e| unreachable(e.message().to_string()))?
+        .into_inner();
+    let health = client.health(HealthRequest {}).await.map_err(|e| {
+        Check::fail(
+            "node",
+            format!("ddk-node {} cannot report its health: {}", info.node_version, e.message()),
+            "Upgrade ddk-node to the version of ddk-cli.".to_string(),
+        )
+    })?;
+    Ok((info, health.into_inner()))
+}
+
+fn health_checks(info: &InfoResponse, health: &HealthResponse) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if major_version(CLI_VERSION) != major_version(&info.node_version) {
+        checks.push(Check::warn(
+            "version",
+            format!("ddk-cli {} and ddk-node {} are different major versions", CLI_VERSION, info.node_version),
+            "Use a ddk-cli of the same major version as the node.".to_string(),
+        ));
+    }
+
+    let network = &health.network;
+    match (&health.esplora_error, health.esplora_height) {
+        (Some(e), _) => checks.push(Check::fail(
+            "esplora",
+            format!("cannot reach {}: {}", health.esplora_host, e),
+            format!("Run ddk-node with --esplora set to a reachable esplora API for {}.", network),
+        )),
+        _ if !health.esplora_network_matches => checks.push(Check::fail(
+            "network",
+            format!("{} does not serve the {} chain", health.esplora_host, network),
+            format!("Point --esplora at a {} esplora API, or run ddk-node with the --network esplora serves.", network),
+        )),
+        (None, height) => {
+            let height = height.unwrap_or_default();
+            checks.push(Check::pass("esplora", format!("{} at height {}", health.esplora_host, height)));
+            let lag = height.saturating_sub(health.wallet_height);
+            if lag > WALLET_SYNC_LAG_BLOCKS {
+                checks.push(Check::warn(
+                    "wallet",
+                    format!("synced to {}, {} blocks behind esplora", health.wallet_height, lag),
+                    "Wait for the next periodic sync, or run `ddk-cli wallet rescan` if it stays behind.".to_string(),
+                ));
+            } else {
+                checks.push(Check::pass("wallet", format!("synced to {}", health.wallet_height)));
+            }
+        }
+    }
+
+    match &health.oracle_error {
+        Some(e) => checks.push(Check::fail(
+            "oracle",
+            format!("cannot reach {}: {}", health.oracle_endpoint, e),
+            "Run ddk-node with --oracle set to a running kormir oracle.".to_string(),
+        )),
+        None if !health.oracle_key_matches => checks.push(Check::warn(
+            "oracle",
+            format!("{} has a different key than when the node started", health.oracle_endpoint),
+            "Restart ddk-node so new offers use the current oracle key.".to_string(),
+        )),
+        None => checks.push(Check::pass("oracle", health.oracle_endpoint.clone())),
+    }
+
+    if health.connected_peers == 0 {
+        checks.push(Check::warn(
+            "peers",
+            "no peers connected".to_string(),
+            "Connect to a counterparty with `ddk-cli connect <PUBKEY>@<HOST>`.".to_string(),
+        ));
+    } else {
+        checks.push(Check::pass("peers", format!("{} connected", health.connected_peers)));
+    }
+    checks
+}
+
+/// Funding confirmation targets offered by the interactive offer builder, in blocks.
+const FUNDING_TARGETS: [&str; 4] = ["1", "3", "6", "144"];
+
+/// Fee rate of an interactive offer, estimated by the node or entered and checked against the
+/// band of the offer arguments. Also returns the funding confirmation target the rate was
+/// estimated for, if there is one.
+async fn prompt_fee_rate(
+    client: &mut DdkRpcClient<tonic::transport::Channel>,
+    arg: &Offer,
+    fee_unit: Option<FeeRateUnit>,
+) -> anyhow::Result<(u64, Option<u16>)> {
+    let target = match (arg.funding_target, arg.fee_rate_source) {
+        (Some(blocks), _) => Some(blocks),
+        (None, FeeRateSource::Target) => {
+            let blocks = Select::new("Confirm the funding transaction within how many blocks?", FUNDING_TARGETS.to_vec()).prompt()?;
+            Some(blocks.parse()?)
+        }
+        (None, _) => None,
+    };
+    if let Some(blocks) = target {
+        let fee_rate = estimate_fee_for_target(client, blocks).await?;
+        println!("Using estimated fee rate of {} sats/vbyte to confirm within {} blocks.", fee_rate, blocks);
+        return Ok((fee_rate, Some(blocks)));
+    }
+
+    if arg.fee_rate_source == FeeRateSource::Estimate {
+        let estimate = client
+            .estimate_fee(EstimateFeeRequest { target: arg.fee_target.clone(), blocks: None })
+            .await?
+            .into_inner();
+        println!("Using estimated fee rate of {} sats/vbyte.", estimate.sats_per_vbyte);
+        return Ok((estimate.sats_per_vbyte, None));
+    }
+
+    let band = fee_rate_band(arg, fee_unit)?;
+    let question = match fee_unit {
+        Some(unit) => format!("Fee rate ({}):", unit),
+        None => "Fee rate (such as 10sat/vb):".to_string(),
+    };
+    loop {
+        let fee_rate = FeeRate::parse(&Text::new(&question).prompt()?, fee_unit)?;
+        if band.contains(&fee_rate) || confirm_fee_rate(fee_rate, &band)? {
+            // Contract inputs take whole sats/vbyte.
+            return Ok((fee_rate.sat_per_vb(), None));
+        }
+    }
+}
+
+/// Pick one of the events the oracle lists.
+async fn prompt_oracle_event(client: &mut DdkRpcClient<tonic::transport::Channel>) -> anyhow::Result<OracleAnnouncement> {
+    let events = client
+        .list_oracle_events(ListOracleEventsRequest { asset: None, from: None, to: None })
+        .await?
+        .into_inner()
+        .events;
+    if events.is_empty() {
+        return Err(anyhow::anyhow!("The oracle lists no events."));
+    }
+    let event_ids = events.iter().map(|event| event.event_id.clone()).collect::<Vec<_>>();
+    let event_id = Select::new("Select the oracle event.", event_ids).prompt()?;
+    let event = events
+        .into_iter()
+        .find(|event| event.event_id == event_id)
+        .expect("selected from the listed events");
+    Ok(serde_json::from_slice(&event.announcement)?)
+}
+
+/// The node's fee rate estimate for confirming within `blocks`.
+async fn estimate_fee_for_target(client: &mut DdkRpcClient<tonic::transport::Channel>, blocks: u16) -> anyhow::Result<u64> {
+    let estimate = client
+        .estimate_fee(EstimateFeeRequest { target: String::new(), blocks: Some(u32::from(blocks)) })
+        .await?
+        .into_inner();
+    Ok(estimate.sats_per_vbyte)
+}
+
+/// Fee rates accepted without a warning, from `--min-fee-rate` and `--max-fee-rate`.
+fn fee_rate_band(arg: &Offer, fee_unit: Option<FeeRateUnit>) -> anyhow::Result<RangeInclusive<FeeRate>> {
+    let min = FeeRate::parse(&arg.min_fee_rate, fee_unit)?;
+    let max = FeeRate::parse(&arg.max_fee_rate, fee_unit)?;
+    if min > max {
+        return Err(anyhow::anyhow!("--min-fee-rate is above --max-fee-rate."));
+    }
+    Ok(min..=max)
+}
+
+fn confirm_fee_rate(fee_rate: FeeRate, band: &RangeInclusive<FeeRate>) -> anyhow::Result<bool> {
+    let question = format!(
+        "Fee rate {} is outside {}..={}. Use it anyway?",
+        fee_rate, band.start(), band.end()
+    );
+    Ok(Confirm::new(&question).with_default(false).prompt()?)
+}
+
+/// Read and validate a contract input file, returning it as the json bytes the node expects.
+fn read_contract_input(file: &str, fee_unit: Option<FeeRateUnit>) -> anyhow::Result<Vec<u8>> {
+    let contract_input = parse_contract_input(&std::fs::read_to_string(file)?, fee_unit)?;
+    contract_input
+        .validate()
+        .map_err(|e| anyhow::anyhow!("Invalid contract input. {:?}", e))?;
+    Ok(serde_json::to_vec(&contract_input)?)
+}
+
+/// Unix seconds of a `YYYY-MM-DD` date at midnight UTC, or of a unix time given as is.
+fn parse_date(date: &str) -> Result<u32, String> {
+    if let Ok(secs) = date.parse::<u32>() {
+        return Ok(secs);
+    }
+    let invalid = || format!("`{}` is not a YYYY-MM-DD date or unix time.", date);
+    let parts = date
+        .split('-')
+        .map(|part| part.parse::<i64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let [year, month, day] = parts[..] else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(invalid());
+    }
+    // Days since the unix epoch of a date in the proleptic Gregorian calendar.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    u32::try_from(days * 86_400).map_err(|_| invalid())
+}
+
+fn debug_command(command: DebugCommand) -> anyhow::Result<()> {
+    match command {
+        DebugCommand::DecodeMessage { message } => {
+            let bytes = base64::decode(message.trim())?;
+            let decoded = match decode_dlc_message(&bytes)? {
+                WireMessage::Message(Message::Offer(offer)) => serde_json::json!({ "offer": offer }),
+                WireMessage::Message(Message::Accept(accept)) => serde_json::json!({ "accept": accept }),
+                WireMessage::Message(Message::Sign(sign)) => serde_json::json!({ "sign": sign }),
+                WireMessage::Message(msg) => serde_json::json!({ "message": format!("{:?}", msg) }),
+                WireMessage::SegmentStart(start) => serde_json::json!({
+                    "segment_start": { "nb_segments": start.nb_segments, "data_len": start.data.len() }
+                }),
+                WireMessage::SegmentChunk(chunk) => serde_json::json!({
+                    "segment_chunk": { "data_len": chunk.data.len() }
+                }),
+            };
+            print!("{}", serde_json::to_string_pretty(&decoded)?);
+        }
+    }
+    Ok(())
+}
@@ -3,17 +3,20 @@ use std::str::FromStr;
 use std::sync::Arc;
 use clap::Parser;
 use ddk::config::{DdkConfig, SeedConfig};
-use ddk::builder::DdkBuilder;
-use ddk::storage::SledStorageProvider;
+use ddk::builder::{mainnet_allowed_by_env, BuilderError, DdkBuilder};
+use ddk::storage::{CachingStorage, SledStorageProvider, StorageFormat, DEFAULT_CONTRACT_CACHE_SIZE};
 use ddk::oracle::KormirOracleClient;
 use ddk::transport::lightning::LightningTransport;
+use ddk::transport::AnyTransport;
 use ddk::bitcoin::Network;
+use ddk::bitcoin::secp256k1::PublicKey;
+use ddk::wallet::{WalletBirthday, WalletDescriptors};
 use ddk_node::ddkrpc::ddk_rpc_server::DdkRpcServer;
 use ddk_node::DdkNode;
 use tonic::transport::Server;
 use tracing::level_filters::LevelFilter;
 
-type DdkServer = ddk::DlcDevKit<LightningTransport, SledStorageProvider, KormirOracleClient>;
+type DdkServer = ddk::DlcDevKit<AnyTransport, CachingStorage<SledStorageProvider>, KormirOracleClient>;
 
 #[derive(Parser, Clone, Debug)]
 #[clap(name = "ddk-node")]
@@ -33,6 +36,15 @@ struct NodeArgs {
     #[arg(short, long)]
     #[arg(help = "The path where ddk-node stores data. ddk-node will try to store in the $HOME directory by default.")]
     storage_dir: Option<PathBuf>,
+    #[arg(long = "storage-format")]
+    #[arg(help = "Format of the records stored next to contracts. Records in the other format are still read.")]
+    #[arg(default_value = "bincode")]
+    #[arg(value_parser = ["json", "bincode"])]
+    storage_format: String,
+    #[arg(long = "contract-cache-size")]
+    #[arg(help = "Active contracts kept in memory in front of the storage. 0 reads every contract from disk.")]
+    #[arg(default_value_t = DEFAULT_CONTRACT_CACHE_SIZE)]
+    contract_cache_size: usize,
     #[arg(short = 'p')]
     #[arg(long = "port")]
     #[arg(default_value = "1776")]
@@ -42,10 +54,12 @@ struct NodeArgs {
     #[arg(default_value = "0.0.0.0:3030")]
     #[arg(help = "Host and port the gRPC server will run on.")]
     grpc_host: String,
+    #[arg(long = "jsonrpc")]
+    #[arg(help = "Host and port to serve JSON-RPC over HTTP on. Disabled unless set.")]
+    jsonrpc_host: Option<String>,
     #[arg(long = "esplora")]
-    #[arg(default_value = "http://127.0.0.1:30000")]
-    #[arg(help = "Esplora server to connect to.")]
-    esplora_host: String,
+    #[arg(help = "Esplora server to connect to. Defaults to a public host for the network.")]
+    esplora_host: Option<String>,
     #[arg(long = "oracle")]
     #[arg(default_value = "http://127.0.0.1:8082")]
     #[arg(help = "Kormir oracle to connect to.")]
@@ -54,7 +68,42 @@ struct NodeArgs {
     #[arg(help = "Seed config strategy.")]
     #[arg(default_value = "file")]
     #[arg(value_parser = ["file", "bytes"])]
-    seed: String
+    seed: String,
+    #[arg(long)]
+    #[arg(help = "Allow running on mainnet with real funds. Also allowed with DDK_ALLOW_MAINNET=1.")]
+    allow_mainnet: bool,
+    #[arg(long = "wallet-birthday")]
+    #[arg(help = "Block height the wallet's history starts at. Speeds up the first sync of a restored wallet.")]
+    wallet_birthday: Option<u32>,
+    #[arg(long = "max-active-contracts")]
+    #[arg(help = "Most contracts that can be offered or open at once. Unlimited if not set.")]
+    max_active_contracts: Option<usize>,
+    #[arg(long = "max-contract-collateral")]
+    #[arg(help = "Most collateral in sats the node commits to a single contract. Uncapped if not set.")]
+    max_contract_collateral: Option<u64>,
+    #[arg(long = "webhook")]
+    #[arg(help = "URL to POST contract events to as JSON.")]
+    webhook_url: Option<String>,
+    #[arg(long = "watch-only")]
+    #[arg(help = "Run without signing keys, tracking the wallet of the public descriptors and the contracts of the node pubkey.")]
+    #[arg(requires_all = ["external_descriptor", "internal_descriptor", "node_pubkey"])]
+    watch_only: bool,
+    #[arg(long = "external-descriptor")]
+    #[arg(help = "Public descriptor of the receive addresses of a watch-only wallet.")]
+    external_descriptor: Option<String>,
+    #[arg(long = "internal-descriptor")]
+    #[arg(help = "Public descriptor of the change addresses of a watch-only wallet.")]
+    internal_descriptor: Option<String>,
+    #[arg(long = "node-pubkey")]
+    #[arg(help = "Public key of the node whose contracts a watch-only node tracks.")]
+    node_pubkey: Option<String>,
+    #[arg(long)]
+    #[arg(help = "Serve methods for regtest and dispute testing, like forcing an attestation. Needs the dev feature.")]
+    dev: bool,
+    #[arg(long = "nostr-relay")]
+    #[arg(help = "Nostr relay to also send and receive DLC messages on. Needs the nostr feature.")]
+    #[arg(conflicts_with = "watch_only")]
+    nostr_relay: Option<String>,
 }
 
 #[tokio::main]
@@ -75,7 +124,14 @@ async fn main() -> anyhow::Result<()> {
     };
     config.storage_path = storage_path.clone();
     config.esplora_host = args.esplora_host;
-    config.network = Network::from_str(&args.network)?;
+    config.network = match args.network.as_str() {
+        "mainnet" => Network::Bitcoin,
+        network => Network::from_str(network)?,
+    };
+    // Refuse before the seed file and storage are created.
+    if config.network == Network::Bitcoin && !args.allow_mainnet && !mainnet_allowed_by_env() {
+        return Err(BuilderError::MainnetNotAllowed.into());
+    }
     config.seed_config = match args.seed.as_str() {
         "bytes" => SeedConfig::Bytes([0u8; 64]),
         _ => SeedConfig::File(storage_path.to_str().unwrap().to_string()),
@@ -85,30 +141,109 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("Starting DDK node.");
 
-    let transport = Arc::new(LightningTransport::new(&config.seed_config, args.listening_port, config.network)?);
-    let storage = Arc::new(SledStorageProvider::new(
-        config.storage_path.join("sled_db").to_str().unwrap(),
-    )?);
+    // A watch-only node has no seed to load or create.
+    let node_pubkey = match args.watch_only {
+        true => Some(PublicKey::from_str(args.node_pubkey.as_deref().unwrap_or_default())?),
+        false => None,
+    };
+    let transport = match node_pubkey {
+        Some(node_pubkey) => Arc::new(LightningTransport::watch_only(node_pubkey, args.listening_port)?),
+        None => Arc::new(LightningTransport::new(&config.seed_config, args.listening_port, config.network)?),
+    };
+    #[cfg(feature = "nostr")]
+    let nostr = match &args.nostr_relay {
+        Some(relay) => Some(Arc::new(
+            ddk::transport::nostr::NostrDlcRelayHandler::new(&config.seed_config, relay, config.network)?
+                .with_relays_file(config.storage_path.join("nostr_relays")),
+        )),
+        None => None,
+    };
+    #[cfg(not(feature = "nostr"))]
+    if args.nostr_relay.is_some() {
+        return Err(anyhow::anyhow!("ddk-node was built without the nostr feature."));
+    }
+    let storage_format = match args.storage_format.as_str() {
+        "json" => StorageFormat::Json,
+        _ => StorageFormat::Bincode,
+    };
+    let storage = Arc::new(CachingStorage::new(
+        SledStorageProvider::new(config.storage_path.join("sled_db").to_str().unwrap())?
+            .with_format(storage_format),
+        args.contract_cache_size,
+    ));
 
     // let oracle = Arc::new(P2PDOracleClient::new(&oracle_host).await?);
     let oracle = Arc::new(KormirOracleClient::new(&args.oracle_host).await?);
 
     let mut builder = DdkBuilder::new();
     builder.set_config(config);
-    builder.set_transport(transport.clone());
+    builder.allow_mainnet(args.allow_mainnet);
+    builder.set_transport(Arc::new(AnyTransport::from(transport)));
+    #[cfg(feature = "nostr")]
+    if let Some(nostr) = &nostr {
+        builder.add_transport(Arc::new(AnyTransport::from(nostr.clone())));
+    }
     builder.set_storage(storage.clone());
     builder.set_oracle(oracle.clone());
+    if let Some(height) = args.wallet_birthday {
+        builder.set_wallet_birthday(WalletBirthday::Height(height));
+    }
+    if let Some(limit) = args.max_active_contracts {
+        builder.set_max_active_contracts(limit);
+    }
+    if let Some(cap) = args.max_contract_collateral {
+        builder.set_max_contract_collateral(cap);
+    }
+    if let Some(url) = &args.webhook_url {
+        builder.set_webhook_url(url.parse()?);
+    }
+    if let Some(node_pubkey) = node_pubkey {
+        let descriptors = WalletDescriptors {
+            external: args.external_descriptor.clone().unwrap_or_default(),
+            internal: args.internal_descriptor.clone().unwrap_or_default(),
+        };
+        tracing::warn!("Running watch-only. Offers, accepts, sends and broadcasts will fail.");
+        builder.watch_only(descriptors, node_pubkey);
+    }
 
     let ddk: DdkServer = builder.finish()?;
 
     ddk.start()?;
 
-    let node = DdkNode::new(ddk);
+    if args.dev {
+        tracing::warn!("Serving dev methods. Do not use with real funds.");
+    }
+    let node = DdkNode::new(ddk).with_dev_mode(args.dev);
+    #[cfg(feature = "nostr")]
+    let node = match nostr {
+        Some(nostr) => node.with_nostr(nostr),
+        None => node,
+    };
+    let node = Arc::new(node);
+    let ddk = node.inner.clone();
+
+    if let Some(jsonrpc_host) = args.jsonrpc_host {
+        let addr = jsonrpc_host.parse()?;
+        tracing::info!(host = jsonrpc_host, "Serving JSON-RPC.");
+        let jsonrpc = ddk_node::jsonrpc::serve(node.clone(), addr, async {
+            let _ = tokio::signal::ctrl_c().await;
+        });
+        tokio::spawn(async move {
+            if let Err(e) = jsonrpc.await {
+                tracing::error!(error=?e, "JSON-RPC server stopped.");
+            }
+        });
+    }
 
     Server::builder()
-        .add_service(DdkRpcServer::new(node))
-        .serve(args.grpc_host.parse()?)
+        .add_service(DdkRpcServer::from_arc(node))
+        .serve_with_shutdown(args.grpc_host.parse()?, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
         .await?;
 
+    tracing::info!("Shutting down DDK node.");
+    ddk.flush()?;
+
     Ok(())
 }
@@ -1,8 +1,14 @@
 // This file is @generated by prost-build.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct InfoRequest {}
+pub struct InfoRequest {
+    #[prost(bool, tag = "1")]
+    pub verbose: bool,
+}
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct InfoResponse {
@@ -12,7 +18,80 @@ pub struct InfoResponse {
     pub transport: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
     pub oracle: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub oracle_endpoint: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub storage: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub network: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "7")]
+    pub connected_peers: u64,
+    #[prost(message, repeated, tag = "8")]
+    pub contracts: ::prost::alloc::vec::Vec<ContractStateCount>,
+    #[prost(string, repeated, tag = "9")]
+    pub derivation_paths: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "10")]
+    pub sync_height: ::core::option::Option<u32>,
+    #[prost(string, tag = "11")]
+    pub pubkey_xonly: ::prost::alloc::string::String,
+    #[prost(string, tag = "12")]
+    pub pubkey_npub: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "13")]
+    pub max_active_contracts: ::core::option::Option<u64>,
+    #[prost(string, tag = "14")]
+    pub node_version: ::prost::alloc::string::String,
+    #[prost(string, tag = "15")]
+    pub ddk_version: ::prost::alloc::string::String,
+    #[prost(string, tag = "16")]
+    pub rust_dlc_version: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "17")]
+    pub protocol_version: u32,
+    #[prost(uint64, optional, tag = "18")]
+    pub max_contract_collateral: ::core::option::Option<u64>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContractStateCount {
+    #[prost(string, tag = "1")]
+    pub state: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub count: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HealthRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HealthResponse {
+    #[prost(string, tag = "1")]
+    pub network: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub esplora_host: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "3")]
+    pub esplora_error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "4")]
+    pub esplora_height: ::core::option::Option<u32>,
+    #[prost(bool, tag = "5")]
+    pub esplora_network_matches: bool,
+    #[prost(string, tag = "6")]
+    pub oracle_endpoint: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "7")]
+    pub oracle_error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag = "8")]
+    pub oracle_key_matches: bool,
+    #[prost(uint64, tag = "9")]
+    pub connected_peers: u64,
+    #[prost(uint32, tag = "10")]
+    pub wallet_height: u32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendOfferRequest {
@@ -20,28 +99,52 @@ pub struct SendOfferRequest {
     pub contract_input: ::prost::alloc::vec::Vec<u8>,
     #[prost(string, tag = "2")]
     pub counter_party: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "3")]
+    pub expiry_secs: ::core::option::Option<u64>,
+    #[prost(bool, tag = "4")]
+    pub allow_partial: bool,
+    #[prost(string, optional, tag = "5")]
+    pub label: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "6")]
+    pub utxos: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "7")]
+    pub funding_target_blocks: ::core::option::Option<u32>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendOfferResponse {
     #[prost(bytes = "vec", tag = "1")]
     pub offer_dlc: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub contract_id: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListOffersRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListOffersResponse {
     #[prost(bytes = "vec", repeated, tag = "1")]
     pub offers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AcceptOfferRequest {
     #[prost(string, tag = "1")]
     pub contract_id: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "2")]
+    pub label: ::core::option::Option<::prost::alloc::string::String>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AcceptOfferResponse {
@@ -52,20 +155,26 @@ pub struct AcceptOfferResponse {
     #[prost(bytes = "vec", tag = "3")]
     pub accept_dlc: ::prost::alloc::vec::Vec<u8>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NewAddressRequest {}
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NewAddressResponse {
     #[prost(string, tag = "1")]
     pub address: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WalletBalanceRequest {}
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WalletBalanceResponse {
@@ -73,28 +182,53 @@ pub struct WalletBalanceResponse {
     pub confirmed: u64,
     #[prost(uint64, tag = "2")]
     pub unconfirmed: u64,
+    #[prost(uint64, tag = "3")]
+    pub reserved: u64,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetWalletTransactionsRequest {}
+pub struct GetWalletTransactionsRequest {
+    #[prost(uint32, optional, tag = "1")]
+    pub limit: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "2")]
+    pub before_height: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetWalletTransactionsResponse {
-    #[prost(bytes = "vec", repeated, tag = "1")]
-    pub transactions: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+pub struct WalletTransaction {
+    #[prost(bytes = "vec", tag = "1")]
+    pub transaction: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint32, optional, tag = "2")]
+    pub height: ::core::option::Option<u32>,
+    #[prost(uint64, tag = "3")]
+    pub time: u64,
+    #[prost(string, optional, tag = "4")]
+    pub label: ::core::option::Option<::prost::alloc::string::String>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListUtxosRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListUtxosResponse {
     #[prost(bytes = "vec", repeated, tag = "1")]
     pub utxos: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListPeersRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListPeersResponse {
@@ -102,6 +236,7 @@ pub struct ListPeersResponse {
     pub peers: ::prost::alloc::vec::Vec<Peer>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Peer {
@@ -110,6 +245,8 @@ pub struct Peer {
     #[prost(string, tag = "2")]
     pub host: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConnectRequest {
@@ -118,12 +255,18 @@ pub struct ConnectRequest {
     #[prost(string, tag = "2")]
     pub host: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConnectResponse {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListOraclesRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListOraclesResponse {
@@ -132,437 +275,2157 @@ pub struct ListOraclesResponse {
     #[prost(string, tag = "2")]
     pub pubkey: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PortfolioSummaryRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PortfolioSummaryResponse {
+    #[prost(uint64, tag = "1")]
+    pub total_collateral: u64,
+    #[prost(uint64, tag = "2")]
+    pub total_max_loss: u64,
+    #[prost(uint64, tag = "3")]
+    pub mark_to_market: u64,
+    #[prost(message, repeated, tag = "4")]
+    pub contracts: ::prost::alloc::vec::Vec<ContractRisk>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContractRisk {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub collateral: u64,
+    #[prost(uint64, tag = "3")]
+    pub max_loss: u64,
+    #[prost(uint64, optional, tag = "4")]
+    pub mark_to_market: ::core::option::Option<u64>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListOracleEventsRequest {
+    #[prost(string, optional, tag = "1")]
+    pub asset: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "2")]
+    pub from: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "3")]
+    pub to: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListOracleEventsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub events: ::prost::alloc::vec::Vec<OracleEvent>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OracleEvent {
+    #[prost(string, tag = "1")]
+    pub event_id: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub maturity: u32,
+    #[prost(bytes = "vec", tag = "3")]
+    pub announcement: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListContractsRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamContractsRequest {
+    #[prost(string, optional, tag = "1")]
+    pub state: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "2")]
+    pub direction: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContractSummary {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub state: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub counter_party: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "4")]
+    pub direction: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "5")]
+    pub total_collateral: ::core::option::Option<u64>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListContractsResponse {
     #[prost(bytes = "vec", repeated, tag = "1")]
     pub contracts: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(message, repeated, tag = "2")]
+    pub offer_expiries: ::prost::alloc::vec::Vec<OfferExpiry>,
+    #[prost(message, repeated, tag = "3")]
+    pub labels: ::prost::alloc::vec::Vec<Label>,
+    #[prost(message, repeated, tag = "4")]
+    pub funding_conflicts: ::prost::alloc::vec::Vec<FundingConflict>,
+    #[prost(message, repeated, tag = "5")]
+    pub dead_letters: ::prost::alloc::vec::Vec<DeadLetter>,
 }
-/// Generated client implementations.
-pub mod ddk_rpc_client {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    use tonic::codegen::http::Uri;
-    #[derive(Debug, Clone)]
-    pub struct DdkRpcClient<T> {
-        inner: tonic::client::Grpc<T>,
-    }
-    impl DdkRpcClient<tonic::transport::Channel> {
-        /// Attempt to create a new client by connecting to a given endpoint.
-        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
-        where
-            D: TryInto<tonic::transport::Endpoint>,
-            D::Error: Into<StdError>,
-        {
-            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
-            Ok(Self::new(conn))
-        }
-    }
-    impl<T> DdkRpcClient<T>
-    where
-        T: tonic::client::GrpcService<tonic::body::BoxBody>,
-        T::Error: Into<StdError>,
-        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
-        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
-    {
-        pub fn new(inner: T) -> Self {
-            let inner = tonic::client::Grpc::new(inner);
-            Self { inner }
-        }
-        pub fn with_origin(inner: T, origin: Uri) -> Self {
-            let inner = tonic::client::Grpc::with_origin(inner, origin);
-            Self { inner }
-        }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> DdkRpcClient<InterceptedService<T, F>>
-        where
-            F: tonic::service::Interceptor,
-            T::ResponseBody: Default,
-            T: tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-                Response = http::Response<
-                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
-                >,
-            >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-            >>::Error: Into<StdError> + Send + Sync,
-        {
-            DdkRpcClient::new(InterceptedService::new(inner, interceptor))
-        }
-        /// Compress requests with the given encoding.
-        ///
-        /// This requires the server to support it otherwise it might respond with an
-        /// error.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.send_compressed(encoding);
-            self
-        }
-        /// Enable decompressing responses.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.accept_compressed(encoding);
-            self
-        }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_decoding_message_size(limit);
-            self
-        }
-        /// Limits the maximum size of an encoded message.
-        ///
-        /// Default: `usize::MAX`
-        #[must_use]
-        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_encoding_message_size(limit);
-            self
-        }
-        pub async fn info(
-            &mut self,
-            request: impl tonic::IntoRequest<super::InfoRequest>,
-        ) -> std::result::Result<tonic::Response<super::InfoResponse>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/Info");
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "Info"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn send_offer(
-            &mut self,
-            request: impl tonic::IntoRequest<super::SendOfferRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::SendOfferResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/SendOffer");
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "SendOffer"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn accept_offer(
-            &mut self,
-            request: impl tonic::IntoRequest<super::AcceptOfferRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::AcceptOfferResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/ddkrpc.DdkRpc/AcceptOffer",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "AcceptOffer"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn list_offers(
-            &mut self,
-            request: impl tonic::IntoRequest<super::ListOffersRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListOffersResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/ListOffers");
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListOffers"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn new_address(
-            &mut self,
-            request: impl tonic::IntoRequest<super::NewAddressRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::NewAddressResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/NewAddress");
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "NewAddress"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn wallet_balance(
-            &mut self,
-            request: impl tonic::IntoRequest<super::WalletBalanceRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::WalletBalanceResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/ddkrpc.DdkRpc/WalletBalance",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "WalletBalance"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn get_wallet_transactions(
-            &mut self,
-            request: impl tonic::IntoRequest<super::GetWalletTransactionsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::GetWalletTransactionsResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/ddkrpc.DdkRpc/GetWalletTransactions",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "GetWalletTransactions"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn list_utxos(
-            &mut self,
-            request: impl tonic::IntoRequest<super::ListUtxosRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListUtxosResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/ListUtxos");
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListUtxos"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn list_peers(
-            &mut self,
-            request: impl tonic::IntoRequest<super::ListPeersRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListPeersResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/ListPeers");
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListPeers"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn connect_peer(
-            &mut self,
-            request: impl tonic::IntoRequest<super::ConnectRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ConnectResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/ddkrpc.DdkRpc/ConnectPeer",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ConnectPeer"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn list_oracles(
-            &mut self,
-            request: impl tonic::IntoRequest<super::ListOraclesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListOraclesResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/ddkrpc.DdkRpc/ListOracles",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListOracles"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn list_contracts(
-            &mut self,
-            request: impl tonic::IntoRequest<super::ListContractsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListContractsResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/ddkrpc.DdkRpc/ListContracts",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListContracts"));
-            self.inner.unary(req, path, codec).await
-        }
-    }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FundingConflict {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub outpoint: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub conflicting_txid: ::prost::alloc::string::String,
 }
-/// Generated server implementations.
-pub mod ddk_rpc_server {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with DdkRpcServer.
-    #[async_trait]
-    pub trait DdkRpc: Send + Sync + 'static {
-        async fn info(
-            &self,
-            request: tonic::Request<super::InfoRequest>,
-        ) -> std::result::Result<tonic::Response<super::InfoResponse>, tonic::Status>;
-        async fn send_offer(
-            &self,
-            request: tonic::Request<super::SendOfferRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::SendOfferResponse>,
-            tonic::Status,
-        >;
-        async fn accept_offer(
-            &self,
-            request: tonic::Request<super::AcceptOfferRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::AcceptOfferResponse>,
-            tonic::Status,
-        >;
-        async fn list_offers(
-            &self,
-            request: tonic::Request<super::ListOffersRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListOffersResponse>,
-            tonic::Status,
-        >;
-        async fn new_address(
-            &self,
-            request: tonic::Request<super::NewAddressRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::NewAddressResponse>,
-            tonic::Status,
-        >;
-        async fn wallet_balance(
-            &self,
-            request: tonic::Request<super::WalletBalanceRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::WalletBalanceResponse>,
-            tonic::Status,
-        >;
-        async fn get_wallet_transactions(
-            &self,
-            request: tonic::Request<super::GetWalletTransactionsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::GetWalletTransactionsResponse>,
-            tonic::Status,
-        >;
-        async fn list_utxos(
-            &self,
-            request: tonic::Request<super::ListUtxosRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListUtxosResponse>,
-            tonic::Status,
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeadLetter {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub counter_party: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "3")]
+    pub attempts: u32,
+    #[prost(uint64, tag = "4")]
+    pub failed_at: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OfferExpiry {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub expires_in: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RebroadcastFundingRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RebroadcastFundingResponse {
+    #[prost(bool, tag = "1")]
+    pub accepted: bool,
+    #[prost(string, tag = "2")]
+    pub txid: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub error: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyFundingRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyFundingResponse {
+    #[prost(string, tag = "1")]
+    pub outpoint: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub witness_script: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub value: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResendLastMessageRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResendLastMessageResponse {
+    #[prost(string, tag = "1")]
+    pub receipt: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub acknowledgement: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetryDeadLetterRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetryDeadLetterResponse {
+    #[prost(string, tag = "1")]
+    pub receipt: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub acknowledgement: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContractRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContractResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub contract: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub state: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub counter_party: ::prost::alloc::string::String,
+    #[prost(bool, optional, tag = "4")]
+    pub counterparty_online: ::core::option::Option<bool>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetOfferMessageRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetOfferMessageResponse {
+    #[prost(string, tag = "1")]
+    pub offer: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportOfferRequest {
+    #[prost(string, tag = "1")]
+    pub offer: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub counter_party: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportOfferResponse {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportContractRequest {
+    #[prost(string, tag = "1")]
+    pub contract: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub overwrite: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportContractResponse {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub state: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetCetListRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub offset: u32,
+    #[prost(uint32, optional, tag = "3")]
+    pub limit: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetCetListResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub cets: ::prost::alloc::vec::Vec<Cet>,
+    #[prost(uint32, tag = "2")]
+    pub total: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Cet {
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    #[prost(string, tag = "2")]
+    pub txid: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub first_outcome: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub last_outcome: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "5")]
+    pub offer_payout: u64,
+    #[prost(uint64, tag = "6")]
+    pub accept_payout: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContractTransactionsRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContractTransactionsResponse {
+    #[prost(string, tag = "1")]
+    pub funding_outpoint: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub transactions: ::prost::alloc::vec::Vec<ContractTransaction>,
+    #[prost(message, optional, tag = "3")]
+    pub fees: ::core::option::Option<ContractFees>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContractFees {
+    #[prost(uint64, tag = "1")]
+    pub funding_fee: u64,
+    #[prost(uint64, tag = "2")]
+    pub own_fee: u64,
+    #[prost(uint64, tag = "3")]
+    pub fund_output_value: u64,
+    #[prost(uint64, optional, tag = "4")]
+    pub closing_fee: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "5")]
+    pub fee_rate_per_vb: u64,
+    #[prost(uint32, optional, tag = "6")]
+    pub funding_target_blocks: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContractTransaction {
+    #[prost(string, tag = "1")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub txid: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "3")]
+    pub confirmations: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RescanRequest {
+    #[prost(uint32, optional, tag = "1")]
+    pub from_height: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RescanResponse {
+    #[prost(uint64, tag = "1")]
+    pub transactions: u64,
+    #[prost(uint64, tag = "2")]
+    pub confirmed: u64,
+    #[prost(uint64, tag = "3")]
+    pub unconfirmed: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelOfferRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelOfferResponse {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FillOfferRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub counter_party: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub accept_collateral: u64,
+    #[prost(uint64, optional, tag = "4")]
+    pub expiry_secs: ::core::option::Option<u64>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FillOfferResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub offer_dlc: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FindContractsByEventRequest {
+    #[prost(string, tag = "1")]
+    pub event_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FindContractsByEventResponse {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub contracts: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OpenChannelRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub contract_input: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub counter_party: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelRequest {
+    #[prost(string, tag = "1")]
+    pub channel_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SettleChannelRequest {
+    #[prost(string, tag = "1")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub counter_payout: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RenewChannelRequest {
+    #[prost(string, tag = "1")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub counter_payout: u64,
+    #[prost(bytes = "vec", tag = "3")]
+    pub contract_input: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CloseChannelRequest {
+    #[prost(string, tag = "1")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub counter_payout: u64,
+    #[prost(bool, tag = "3")]
+    pub force: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelResponse {
+    #[prost(string, tag = "1")]
+    pub channel_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListChannelsRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListChannelsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub channels: ::prost::alloc::vec::Vec<ChannelInfo>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelInfo {
+    #[prost(string, tag = "1")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub counter_party: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub state: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EstimateFeeRequest {
+    #[prost(string, tag = "1")]
+    pub target: ::prost::alloc::string::String,
+    #[prost(uint32, optional, tag = "2")]
+    pub blocks: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EstimateFeeResponse {
+    #[prost(uint64, tag = "1")]
+    pub sats_per_vbyte: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Label {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub label: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetLabelRequest {
+    #[prost(string, tag = "1")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub label: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetLabelResponse {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetLabelRequest {
+    #[prost(string, tag = "1")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetLabelResponse {
+    #[prost(string, optional, tag = "1")]
+    pub label: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContractHistoryRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContractHistoryResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub transitions: ::prost::alloc::vec::Vec<ContractTransition>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContractTransition {
+    #[prost(string, optional, tag = "1")]
+    pub from_state: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, tag = "2")]
+    pub to_state: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub timestamp: u64,
+    #[prost(string, optional, tag = "4")]
+    pub txid: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShowKeysRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShowKeysResponse {
+    #[prost(string, tag = "1")]
+    pub node_pubkey: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub npub: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportNostrKeyRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportNostrKeyResponse {
+    #[prost(string, tag = "1")]
+    pub nsec: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendRequest {
+    #[prost(string, tag = "1")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub amount: u64,
+    #[prost(uint64, tag = "3")]
+    pub fee_rate: u64,
+    #[prost(string, repeated, tag = "4")]
+    pub utxos: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendResponse {
+    #[prost(string, tag = "1")]
+    pub txid: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ForceAttestationRequest {
+    #[prost(string, tag = "1")]
+    pub contract_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub attestation: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ForceAttestationResponse {
+    #[prost(string, tag = "1")]
+    pub state: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageStatsRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageStatsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub sent: ::prost::alloc::vec::Vec<MessageTypeStats>,
+    #[prost(message, repeated, tag = "2")]
+    pub received: ::prost::alloc::vec::Vec<MessageTypeStats>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageTypeStats {
+    #[prost(string, tag = "1")]
+    pub message_type: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub messages: u64,
+    #[prost(uint64, tag = "3")]
+    pub plaintext_bytes: u64,
+    #[prost(uint64, tag = "4")]
+    pub encrypted_bytes: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddRelayRequest {
+    #[prost(string, tag = "1")]
+    pub relay: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddRelayResponse {
+    #[prost(string, tag = "1")]
+    pub relay: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RemoveRelayRequest {
+    #[prost(string, tag = "1")]
+    pub relay: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RemoveRelayResponse {
+    #[prost(string, tag = "1")]
+    pub relay: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListRelaysRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListRelaysResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub relays: ::prost::alloc::vec::Vec<Relay>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Relay {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub status: ::prost::alloc::string::String,
+}
+/// Generated client implementations.
+pub mod ddk_rpc_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    #[derive(Debug, Clone)]
+    pub struct DdkRpcClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl DdkRpcClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> DdkRpcClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> DdkRpcClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            DdkRpcClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        pub async fn info(
+            &mut self,
+            request: impl tonic::IntoRequest<super::InfoRequest>,
+        ) -> std::result::Result<tonic::Response<super::InfoResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/Info");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "Info"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn send_offer(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SendOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SendOfferResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/SendOffer");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "SendOffer"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn accept_offer(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AcceptOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AcceptOfferResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/AcceptOffer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "AcceptOffer"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_offers(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListOffersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListOffersResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/ListOffers");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListOffers"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn new_address(
+            &mut self,
+            request: impl tonic::IntoRequest<super::NewAddressRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::NewAddressResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/NewAddress");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "NewAddress"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn wallet_balance(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WalletBalanceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WalletBalanceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/WalletBalance",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "WalletBalance"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_wallet_transactions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetWalletTransactionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::WalletTransaction>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/GetWalletTransactions",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "GetWalletTransactions"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn list_utxos(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListUtxosRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListUtxosResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/ListUtxos");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListUtxos"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_peers(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListPeersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListPeersResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/ListPeers");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListPeers"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn connect_peer(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ConnectRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ConnectResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ConnectPeer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ConnectPeer"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_oracles(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListOraclesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListOraclesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ListOracles",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListOracles"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_oracle_events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListOracleEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListOracleEventsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ListOracleEvents",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListOracleEvents"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_contracts(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListContractsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListContractsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ListContracts",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListContracts"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn stream_contracts(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamContractsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ContractSummary>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/StreamContracts",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "StreamContracts"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn portfolio_summary(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PortfolioSummaryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PortfolioSummaryResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/PortfolioSummary",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "PortfolioSummary"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn rebroadcast_funding(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RebroadcastFundingRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RebroadcastFundingResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/RebroadcastFunding",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "RebroadcastFunding"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_contract_transactions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetContractTransactionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContractTransactionsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/GetContractTransactions",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "GetContractTransactions"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn rescan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RescanRequest>,
+        ) -> std::result::Result<tonic::Response<super::RescanResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/Rescan");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "Rescan"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn cancel_offer(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CancelOfferResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/CancelOffer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "CancelOffer"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn fill_offer(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FillOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FillOfferResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/FillOffer");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "FillOffer"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn find_contracts_by_event(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FindContractsByEventRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FindContractsByEventResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/FindContractsByEvent",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "FindContractsByEvent"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn open_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::OpenChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/OpenChannel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "OpenChannel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn accept_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/AcceptChannel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "AcceptChannel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn settle_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SettleChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/SettleChannel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "SettleChannel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn accept_settle_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/AcceptSettleChannel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "AcceptSettleChannel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn renew_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RenewChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/RenewChannel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "RenewChannel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn accept_renew_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/AcceptRenewChannel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "AcceptRenewChannel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn close_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CloseChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/CloseChannel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "CloseChannel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn accept_close_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/AcceptCloseChannel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "AcceptCloseChannel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_channels(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListChannelsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListChannelsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ListChannels",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListChannels"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn estimate_fee(
+            &mut self,
+            request: impl tonic::IntoRequest<super::EstimateFeeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EstimateFeeResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/EstimateFee",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "EstimateFee"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn set_label(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetLabelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SetLabelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/SetLabel");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "SetLabel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_label(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetLabelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetLabelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/GetLabel");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "GetLabel"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_contract_history(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetContractHistoryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContractHistoryResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/GetContractHistory",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "GetContractHistory"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn show_keys(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ShowKeysRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ShowKeysResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/ShowKeys");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ShowKeys"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn export_nostr_key(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExportNostrKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExportNostrKeyResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ExportNostrKey",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "ExportNostrKey"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn send(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SendRequest>,
+        ) -> std::result::Result<tonic::Response<super::SendResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/Send");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "Send"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn force_attestation(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ForceAttestationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ForceAttestationResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ForceAttestation",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "ForceAttestation"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn verify_funding(
+            &mut self,
+            request: impl tonic::IntoRequest<super::VerifyFundingRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::VerifyFundingResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/VerifyFunding",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "VerifyFunding"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn resend_last_message(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResendLastMessageRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ResendLastMessageResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ResendLastMessage",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "ResendLastMessage"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn retry_dead_letter(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RetryDeadLetterRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RetryDeadLetterResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/RetryDeadLetter",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "RetryDeadLetter"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_cet_list(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetCetListRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetCetListResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/GetCetList");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "GetCetList"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_contract(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetContractRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContractResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/GetContract",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "GetContract"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_offer_message(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetOfferMessageRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetOfferMessageResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/GetOfferMessage",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "GetOfferMessage"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn import_offer(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImportOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportOfferResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ImportOffer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ImportOffer"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn import_contract(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImportContractRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportContractResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/ImportContract",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "ImportContract"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn health(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HealthRequest>,
+        ) -> std::result::Result<tonic::Response<super::HealthResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/Health");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "Health"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn message_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MessageStatsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::MessageStatsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/MessageStats",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ddkrpc.DdkRpc", "MessageStats"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn add_relay(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AddRelayRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AddRelayResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/AddRelay");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "AddRelay"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn remove_relay(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RemoveRelayRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RemoveRelayResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ddkrpc.DdkRpc/RemoveRelay",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "RemoveRelay"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_relays(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListRelaysRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListRelaysResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/ddkrpc.DdkRpc/ListRelays");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("ddkrpc.DdkRpc", "ListRelays"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod ddk_rpc_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with DdkRpcServer.
+    #[async_trait]
+    pub trait DdkRpc: Send + Sync + 'static {
+        async fn info(
+            &self,
+            request: tonic::Request<super::InfoRequest>,
+        ) -> std::result::Result<tonic::Response<super::InfoResponse>, tonic::Status>;
+        async fn send_offer(
+            &self,
+            request: tonic::Request<super::SendOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SendOfferResponse>,
+            tonic::Status,
+        >;
+        async fn accept_offer(
+            &self,
+            request: tonic::Request<super::AcceptOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AcceptOfferResponse>,
+            tonic::Status,
+        >;
+        async fn list_offers(
+            &self,
+            request: tonic::Request<super::ListOffersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListOffersResponse>,
+            tonic::Status,
+        >;
+        async fn new_address(
+            &self,
+            request: tonic::Request<super::NewAddressRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::NewAddressResponse>,
+            tonic::Status,
+        >;
+        async fn wallet_balance(
+            &self,
+            request: tonic::Request<super::WalletBalanceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WalletBalanceResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the GetWalletTransactions method.
+        type GetWalletTransactionsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::WalletTransaction, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn get_wallet_transactions(
+            &self,
+            request: tonic::Request<super::GetWalletTransactionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::GetWalletTransactionsStream>,
+            tonic::Status,
+        >;
+        async fn list_utxos(
+            &self,
+            request: tonic::Request<super::ListUtxosRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListUtxosResponse>,
+            tonic::Status,
         >;
         async fn list_peers(
             &self,
@@ -582,6 +2445,13 @@ pub mod ddk_rpc_server {
             tonic::Response<super::ListOraclesResponse>,
             tonic::Status,
         >;
+        async fn list_oracle_events(
+            &self,
+            request: tonic::Request<super::ListOracleEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListOracleEventsResponse>,
+            tonic::Status,
+        >;
         async fn list_contracts(
             &self,
             request: tonic::Request<super::ListContractsRequest>,
@@ -589,6 +2459,245 @@ pub mod ddk_rpc_server {
             tonic::Response<super::ListContractsResponse>,
             tonic::Status,
         >;
+        /// Server streaming response type for the StreamContracts method.
+        type StreamContractsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::ContractSummary, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn stream_contracts(
+            &self,
+            request: tonic::Request<super::StreamContractsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamContractsStream>,
+            tonic::Status,
+        >;
+        async fn portfolio_summary(
+            &self,
+            request: tonic::Request<super::PortfolioSummaryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PortfolioSummaryResponse>,
+            tonic::Status,
+        >;
+        async fn rebroadcast_funding(
+            &self,
+            request: tonic::Request<super::RebroadcastFundingRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RebroadcastFundingResponse>,
+            tonic::Status,
+        >;
+        async fn get_contract_transactions(
+            &self,
+            request: tonic::Request<super::GetContractTransactionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContractTransactionsResponse>,
+            tonic::Status,
+        >;
+        async fn rescan(
+            &self,
+            request: tonic::Request<super::RescanRequest>,
+        ) -> std::result::Result<tonic::Response<super::RescanResponse>, tonic::Status>;
+        async fn cancel_offer(
+            &self,
+            request: tonic::Request<super::CancelOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CancelOfferResponse>,
+            tonic::Status,
+        >;
+        async fn fill_offer(
+            &self,
+            request: tonic::Request<super::FillOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FillOfferResponse>,
+            tonic::Status,
+        >;
+        async fn find_contracts_by_event(
+            &self,
+            request: tonic::Request<super::FindContractsByEventRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FindContractsByEventResponse>,
+            tonic::Status,
+        >;
+        async fn open_channel(
+            &self,
+            request: tonic::Request<super::OpenChannelRequest>,
+        ) -> std::result::Result<tonic::Response<super::ChannelResponse>, tonic::Status>;
+        async fn accept_channel(
+            &self,
+            request: tonic::Request<super::ChannelRequest>,
+        ) -> std::result::Result<tonic::Response<super::ChannelResponse>, tonic::Status>;
+        async fn settle_channel(
+            &self,
+            request: tonic::Request<super::SettleChannelRequest>,
+        ) -> std::result::Result<tonic::Response<super::ChannelResponse>, tonic::Status>;
+        async fn accept_settle_channel(
+            &self,
+            request: tonic::Request<super::ChannelRequest>,
+        ) -> std::result::Result<tonic::Response<super::ChannelResponse>, tonic::Status>;
+        async fn renew_channel(
+            &self,
+            request: tonic::Request<super::RenewChannelRequest>,
+        ) -> std::result::Result<tonic::Response<super::ChannelResponse>, tonic::Status>;
+        async fn accept_renew_channel(
+            &self,
+            request: tonic::Request<super::ChannelRequest>,
+        ) -> std::result::Result<tonic::Response<super::ChannelResponse>, tonic::Status>;
+        async fn close_channel(
+            &self,
+            request: tonic::Request<super::CloseChannelRequest>,
+        ) -> std::result::Result<tonic::Response<super::ChannelResponse>, tonic::Status>;
+        async fn accept_close_channel(
+            &self,
+            request: tonic::Request<super::ChannelRequest>,
+        ) -> std::result::Result<tonic::Response<super::ChannelResponse>, tonic::Status>;
+        async fn list_channels(
+            &self,
+            request: tonic::Request<super::ListChannelsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListChannelsResponse>,
+            tonic::Status,
+        >;
+        async fn estimate_fee(
+            &self,
+            request: tonic::Request<super::EstimateFeeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EstimateFeeResponse>,
+            tonic::Status,
+        >;
+        async fn set_label(
+            &self,
+            request: tonic::Request<super::SetLabelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SetLabelResponse>,
+            tonic::Status,
+        >;
+        async fn get_label(
+            &self,
+            request: tonic::Request<super::GetLabelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetLabelResponse>,
+            tonic::Status,
+        >;
+        async fn get_contract_history(
+            &self,
+            request: tonic::Request<super::GetContractHistoryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContractHistoryResponse>,
+            tonic::Status,
+        >;
+        async fn show_keys(
+            &self,
+            request: tonic::Request<super::ShowKeysRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ShowKeysResponse>,
+            tonic::Status,
+        >;
+        async fn export_nostr_key(
+            &self,
+            request: tonic::Request<super::ExportNostrKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExportNostrKeyResponse>,
+            tonic::Status,
+        >;
+        async fn send(
+            &self,
+            request: tonic::Request<super::SendRequest>,
+        ) -> std::result::Result<tonic::Response<super::SendResponse>, tonic::Status>;
+        async fn force_attestation(
+            &self,
+            request: tonic::Request<super::ForceAttestationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ForceAttestationResponse>,
+            tonic::Status,
+        >;
+        async fn verify_funding(
+            &self,
+            request: tonic::Request<super::VerifyFundingRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::VerifyFundingResponse>,
+            tonic::Status,
+        >;
+        async fn resend_last_message(
+            &self,
+            request: tonic::Request<super::ResendLastMessageRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ResendLastMessageResponse>,
+            tonic::Status,
+        >;
+        async fn retry_dead_letter(
+            &self,
+            request: tonic::Request<super::RetryDeadLetterRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RetryDeadLetterResponse>,
+            tonic::Status,
+        >;
+        async fn get_cet_list(
+            &self,
+            request: tonic::Request<super::GetCetListRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetCetListResponse>,
+            tonic::Status,
+        >;
+        async fn get_contract(
+            &self,
+            request: tonic::Request<super::GetContractRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContractResponse>,
+            tonic::Status,
+        >;
+        async fn get_offer_message(
+            &self,
+            request: tonic::Request<super::GetOfferMessageRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetOfferMessageResponse>,
+            tonic::Status,
+        >;
+        async fn import_offer(
+            &self,
+            request: tonic::Request<super::ImportOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportOfferResponse>,
+            tonic::Status,
+        >;
+        async fn import_contract(
+            &self,
+            request: tonic::Request<super::ImportContractRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportContractResponse>,
+            tonic::Status,
+        >;
+        async fn health(
+            &self,
+            request: tonic::Request<super::HealthRequest>,
+        ) -> std::result::Result<tonic::Response<super::HealthResponse>, tonic::Status>;
+        async fn message_stats(
+            &self,
+            request: tonic::Request<super::MessageStatsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::MessageStatsResponse>,
+            tonic::Status,
+        >;
+        async fn add_relay(
+            &self,
+            request: tonic::Request<super::AddRelayRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AddRelayResponse>,
+            tonic::Status,
+        >;
+        async fn remove_relay(
+            &self,
+            request: tonic::Request<super::RemoveRelayRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RemoveRelayResponse>,
+            tonic::Status,
+        >;
+        async fn list_relays(
+            &self,
+            request: tonic::Request<super::ListRelaysRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListRelaysResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct DdkRpcServer<T: DdkRpc> {
@@ -671,21 +2780,1610 @@ pub mod ddk_rpc_server {
             match req.uri().path() {
                 "/ddkrpc.DdkRpc/Info" => {
                     #[allow(non_camel_case_types)]
-                    struct InfoSvc<T: DdkRpc>(pub Arc<T>);
-                    impl<T: DdkRpc> tonic::server::UnaryService<super::InfoRequest>
-                    for InfoSvc<T> {
-                        type Response = super::InfoResponse;
+                    struct InfoSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::InfoRequest>
+                    for InfoSvc<T> {
+                        type Response = super::InfoResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::InfoRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::info(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = InfoSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/SendOffer" => {
+                    #[allow(non_camel_case_types)]
+                    struct SendOfferSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::SendOfferRequest>
+                    for SendOfferSvc<T> {
+                        type Response = super::SendOfferResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SendOfferRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::send_offer(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SendOfferSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/AcceptOffer" => {
+                    #[allow(non_camel_case_types)]
+                    struct AcceptOfferSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::AcceptOfferRequest>
+                    for AcceptOfferSvc<T> {
+                        type Response = super::AcceptOfferResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AcceptOfferRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::accept_offer(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AcceptOfferSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ListOffers" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListOffersSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ListOffersRequest>
+                    for ListOffersSvc<T> {
+                        type Response = super::ListOffersResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListOffersRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::list_offers(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListOffersSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/NewAddress" => {
+                    #[allow(non_camel_case_types)]
+                    struct NewAddressSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::NewAddressRequest>
+                    for NewAddressSvc<T> {
+                        type Response = super::NewAddressResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::NewAddressRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::new_address(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = NewAddressSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/WalletBalance" => {
+                    #[allow(non_camel_case_types)]
+                    struct WalletBalanceSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::WalletBalanceRequest>
+                    for WalletBalanceSvc<T> {
+                        type Response = super::WalletBalanceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WalletBalanceRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::wallet_balance(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = WalletBalanceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/GetWalletTransactions" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetWalletTransactionsSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::ServerStreamingService<super::GetWalletTransactionsRequest>
+                    for GetWalletTransactionsSvc<T> {
+                        type Response = super::WalletTransaction;
+                        type ResponseStream = T::GetWalletTransactionsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetWalletTransactionsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::get_wallet_transactions(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetWalletTransactionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ListUtxos" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListUtxosSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ListUtxosRequest>
+                    for ListUtxosSvc<T> {
+                        type Response = super::ListUtxosResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListUtxosRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::list_utxos(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListUtxosSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ListPeers" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListPeersSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ListPeersRequest>
+                    for ListPeersSvc<T> {
+                        type Response = super::ListPeersResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListPeersRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::list_peers(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListPeersSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ConnectPeer" => {
+                    #[allow(non_camel_case_types)]
+                    struct ConnectPeerSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ConnectRequest>
+                    for ConnectPeerSvc<T> {
+                        type Response = super::ConnectResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ConnectRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::connect_peer(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ConnectPeerSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ListOracles" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListOraclesSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::ListOraclesRequest>
+                    for ListOraclesSvc<T> {
+                        type Response = super::ListOraclesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListOraclesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::list_oracles(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListOraclesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ListOracleEvents" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListOracleEventsSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::ListOracleEventsRequest>
+                    for ListOracleEventsSvc<T> {
+                        type Response = super::ListOracleEventsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListOracleEventsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::list_oracle_events(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListOracleEventsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ListContracts" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListContractsSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::ListContractsRequest>
+                    for ListContractsSvc<T> {
+                        type Response = super::ListContractsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListContractsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::list_contracts(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListContractsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/StreamContracts" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamContractsSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::ServerStreamingService<super::StreamContractsRequest>
+                    for StreamContractsSvc<T> {
+                        type Response = super::ContractSummary;
+                        type ResponseStream = T::StreamContractsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamContractsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::stream_contracts(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamContractsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/PortfolioSummary" => {
+                    #[allow(non_camel_case_types)]
+                    struct PortfolioSummarySvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::PortfolioSummaryRequest>
+                    for PortfolioSummarySvc<T> {
+                        type Response = super::PortfolioSummaryResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PortfolioSummaryRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::portfolio_summary(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PortfolioSummarySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/RebroadcastFunding" => {
+                    #[allow(non_camel_case_types)]
+                    struct RebroadcastFundingSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::RebroadcastFundingRequest>
+                    for RebroadcastFundingSvc<T> {
+                        type Response = super::RebroadcastFundingResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RebroadcastFundingRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::rebroadcast_funding(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RebroadcastFundingSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/GetContractTransactions" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetContractTransactionsSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::GetContractTransactionsRequest>
+                    for GetContractTransactionsSvc<T> {
+                        type Response = super::GetContractTransactionsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetContractTransactionsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::get_contract_transactions(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetContractTransactionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/Rescan" => {
+                    #[allow(non_camel_case_types)]
+                    struct RescanSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::RescanRequest>
+                    for RescanSvc<T> {
+                        type Response = super::RescanResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RescanRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::rescan(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RescanSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/CancelOffer" => {
+                    #[allow(non_camel_case_types)]
+                    struct CancelOfferSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::CancelOfferRequest>
+                    for CancelOfferSvc<T> {
+                        type Response = super::CancelOfferResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelOfferRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::cancel_offer(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CancelOfferSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/FillOffer" => {
+                    #[allow(non_camel_case_types)]
+                    struct FillOfferSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::FillOfferRequest>
+                    for FillOfferSvc<T> {
+                        type Response = super::FillOfferResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FillOfferRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::fill_offer(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = FillOfferSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/FindContractsByEvent" => {
+                    #[allow(non_camel_case_types)]
+                    struct FindContractsByEventSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::FindContractsByEventRequest>
+                    for FindContractsByEventSvc<T> {
+                        type Response = super::FindContractsByEventResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FindContractsByEventRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::find_contracts_by_event(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = FindContractsByEventSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/OpenChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct OpenChannelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::OpenChannelRequest>
+                    for OpenChannelSvc<T> {
+                        type Response = super::ChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::OpenChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::open_channel(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = OpenChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/AcceptChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct AcceptChannelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ChannelRequest>
+                    for AcceptChannelSvc<T> {
+                        type Response = super::ChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::accept_channel(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AcceptChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/SettleChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct SettleChannelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::SettleChannelRequest>
+                    for SettleChannelSvc<T> {
+                        type Response = super::ChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SettleChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::settle_channel(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SettleChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/AcceptSettleChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct AcceptSettleChannelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ChannelRequest>
+                    for AcceptSettleChannelSvc<T> {
+                        type Response = super::ChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::accept_settle_channel(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AcceptSettleChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/RenewChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct RenewChannelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::RenewChannelRequest>
+                    for RenewChannelSvc<T> {
+                        type Response = super::ChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RenewChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::renew_channel(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RenewChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/AcceptRenewChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct AcceptRenewChannelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ChannelRequest>
+                    for AcceptRenewChannelSvc<T> {
+                        type Response = super::ChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::accept_renew_channel(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AcceptRenewChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/CloseChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct CloseChannelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::CloseChannelRequest>
+                    for CloseChannelSvc<T> {
+                        type Response = super::ChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CloseChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::close_channel(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CloseChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/AcceptCloseChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct AcceptCloseChannelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ChannelRequest>
+                    for AcceptCloseChannelSvc<T> {
+                        type Response = super::ChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::accept_close_channel(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AcceptCloseChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ListChannels" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListChannelsSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::ListChannelsRequest>
+                    for ListChannelsSvc<T> {
+                        type Response = super::ListChannelsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListChannelsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::list_channels(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListChannelsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/EstimateFee" => {
+                    #[allow(non_camel_case_types)]
+                    struct EstimateFeeSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::EstimateFeeRequest>
+                    for EstimateFeeSvc<T> {
+                        type Response = super::EstimateFeeResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::EstimateFeeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::estimate_fee(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = EstimateFeeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/SetLabel" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetLabelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::SetLabelRequest>
+                    for SetLabelSvc<T> {
+                        type Response = super::SetLabelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetLabelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::set_label(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetLabelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/GetLabel" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetLabelSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::GetLabelRequest>
+                    for GetLabelSvc<T> {
+                        type Response = super::GetLabelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetLabelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::get_label(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetLabelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/GetContractHistory" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetContractHistorySvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::GetContractHistoryRequest>
+                    for GetContractHistorySvc<T> {
+                        type Response = super::GetContractHistoryResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetContractHistoryRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::get_contract_history(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetContractHistorySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ShowKeys" => {
+                    #[allow(non_camel_case_types)]
+                    struct ShowKeysSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ShowKeysRequest>
+                    for ShowKeysSvc<T> {
+                        type Response = super::ShowKeysResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ShowKeysRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::show_keys(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ShowKeysSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ExportNostrKey" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExportNostrKeySvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::ExportNostrKeyRequest>
+                    for ExportNostrKeySvc<T> {
+                        type Response = super::ExportNostrKeyResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::InfoRequest>,
+                            request: tonic::Request<super::ExportNostrKeyRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::info(&inner, request).await
+                                <T as DdkRpc>::export_nostr_key(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -697,7 +4395,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = InfoSvc(inner);
+                        let method = ExportNostrKeySvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -713,23 +4411,23 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/SendOffer" => {
+                "/ddkrpc.DdkRpc/Send" => {
                     #[allow(non_camel_case_types)]
-                    struct SendOfferSvc<T: DdkRpc>(pub Arc<T>);
-                    impl<T: DdkRpc> tonic::server::UnaryService<super::SendOfferRequest>
-                    for SendOfferSvc<T> {
-                        type Response = super::SendOfferResponse;
+                    struct SendSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::SendRequest>
+                    for SendSvc<T> {
+                        type Response = super::SendResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::SendOfferRequest>,
+                            request: tonic::Request<super::SendRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::send_offer(&inner, request).await
+                                <T as DdkRpc>::send(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -741,7 +4439,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = SendOfferSvc(inner);
+                        let method = SendSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -757,25 +4455,25 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/AcceptOffer" => {
+                "/ddkrpc.DdkRpc/ForceAttestation" => {
                     #[allow(non_camel_case_types)]
-                    struct AcceptOfferSvc<T: DdkRpc>(pub Arc<T>);
+                    struct ForceAttestationSvc<T: DdkRpc>(pub Arc<T>);
                     impl<
                         T: DdkRpc,
-                    > tonic::server::UnaryService<super::AcceptOfferRequest>
-                    for AcceptOfferSvc<T> {
-                        type Response = super::AcceptOfferResponse;
+                    > tonic::server::UnaryService<super::ForceAttestationRequest>
+                    for ForceAttestationSvc<T> {
+                        type Response = super::ForceAttestationResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::AcceptOfferRequest>,
+                            request: tonic::Request<super::ForceAttestationRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::accept_offer(&inner, request).await
+                                <T as DdkRpc>::force_attestation(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -787,7 +4485,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = AcceptOfferSvc(inner);
+                        let method = ForceAttestationSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -803,23 +4501,25 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/ListOffers" => {
+                "/ddkrpc.DdkRpc/VerifyFunding" => {
                     #[allow(non_camel_case_types)]
-                    struct ListOffersSvc<T: DdkRpc>(pub Arc<T>);
-                    impl<T: DdkRpc> tonic::server::UnaryService<super::ListOffersRequest>
-                    for ListOffersSvc<T> {
-                        type Response = super::ListOffersResponse;
+                    struct VerifyFundingSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::VerifyFundingRequest>
+                    for VerifyFundingSvc<T> {
+                        type Response = super::VerifyFundingResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListOffersRequest>,
+                            request: tonic::Request<super::VerifyFundingRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::list_offers(&inner, request).await
+                                <T as DdkRpc>::verify_funding(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -831,7 +4531,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ListOffersSvc(inner);
+                        let method = VerifyFundingSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -847,23 +4547,25 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/NewAddress" => {
+                "/ddkrpc.DdkRpc/ResendLastMessage" => {
                     #[allow(non_camel_case_types)]
-                    struct NewAddressSvc<T: DdkRpc>(pub Arc<T>);
-                    impl<T: DdkRpc> tonic::server::UnaryService<super::NewAddressRequest>
-                    for NewAddressSvc<T> {
-                        type Response = super::NewAddressResponse;
+                    struct ResendLastMessageSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::ResendLastMessageRequest>
+                    for ResendLastMessageSvc<T> {
+                        type Response = super::ResendLastMessageResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::NewAddressRequest>,
+                            request: tonic::Request<super::ResendLastMessageRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::new_address(&inner, request).await
+                                <T as DdkRpc>::resend_last_message(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -875,7 +4577,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = NewAddressSvc(inner);
+                        let method = ResendLastMessageSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -891,25 +4593,25 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/WalletBalance" => {
+                "/ddkrpc.DdkRpc/RetryDeadLetter" => {
                     #[allow(non_camel_case_types)]
-                    struct WalletBalanceSvc<T: DdkRpc>(pub Arc<T>);
+                    struct RetryDeadLetterSvc<T: DdkRpc>(pub Arc<T>);
                     impl<
                         T: DdkRpc,
-                    > tonic::server::UnaryService<super::WalletBalanceRequest>
-                    for WalletBalanceSvc<T> {
-                        type Response = super::WalletBalanceResponse;
+                    > tonic::server::UnaryService<super::RetryDeadLetterRequest>
+                    for RetryDeadLetterSvc<T> {
+                        type Response = super::RetryDeadLetterResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::WalletBalanceRequest>,
+                            request: tonic::Request<super::RetryDeadLetterRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::wallet_balance(&inner, request).await
+                                <T as DdkRpc>::retry_dead_letter(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -921,7 +4623,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = WalletBalanceSvc(inner);
+                        let method = RetryDeadLetterSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -937,26 +4639,69 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/GetWalletTransactions" => {
+                "/ddkrpc.DdkRpc/GetCetList" => {
                     #[allow(non_camel_case_types)]
-                    struct GetWalletTransactionsSvc<T: DdkRpc>(pub Arc<T>);
+                    struct GetCetListSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::GetCetListRequest>
+                    for GetCetListSvc<T> {
+                        type Response = super::GetCetListResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetCetListRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::get_cet_list(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetCetListSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/GetContract" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetContractSvc<T: DdkRpc>(pub Arc<T>);
                     impl<
                         T: DdkRpc,
-                    > tonic::server::UnaryService<super::GetWalletTransactionsRequest>
-                    for GetWalletTransactionsSvc<T> {
-                        type Response = super::GetWalletTransactionsResponse;
+                    > tonic::server::UnaryService<super::GetContractRequest>
+                    for GetContractSvc<T> {
+                        type Response = super::GetContractResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetWalletTransactionsRequest>,
+                            request: tonic::Request<super::GetContractRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::get_wallet_transactions(&inner, request)
-                                    .await
+                                <T as DdkRpc>::get_contract(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -968,7 +4713,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = GetWalletTransactionsSvc(inner);
+                        let method = GetContractSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -984,23 +4729,25 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/ListUtxos" => {
+                "/ddkrpc.DdkRpc/GetOfferMessage" => {
                     #[allow(non_camel_case_types)]
-                    struct ListUtxosSvc<T: DdkRpc>(pub Arc<T>);
-                    impl<T: DdkRpc> tonic::server::UnaryService<super::ListUtxosRequest>
-                    for ListUtxosSvc<T> {
-                        type Response = super::ListUtxosResponse;
+                    struct GetOfferMessageSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::GetOfferMessageRequest>
+                    for GetOfferMessageSvc<T> {
+                        type Response = super::GetOfferMessageResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListUtxosRequest>,
+                            request: tonic::Request<super::GetOfferMessageRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::list_utxos(&inner, request).await
+                                <T as DdkRpc>::get_offer_message(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1012,7 +4759,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ListUtxosSvc(inner);
+                        let method = GetOfferMessageSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1028,23 +4775,25 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/ListPeers" => {
+                "/ddkrpc.DdkRpc/ImportOffer" => {
                     #[allow(non_camel_case_types)]
-                    struct ListPeersSvc<T: DdkRpc>(pub Arc<T>);
-                    impl<T: DdkRpc> tonic::server::UnaryService<super::ListPeersRequest>
-                    for ListPeersSvc<T> {
-                        type Response = super::ListPeersResponse;
+                    struct ImportOfferSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::ImportOfferRequest>
+                    for ImportOfferSvc<T> {
+                        type Response = super::ImportOfferResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListPeersRequest>,
+                            request: tonic::Request<super::ImportOfferRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::list_peers(&inner, request).await
+                                <T as DdkRpc>::import_offer(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1056,7 +4805,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ListPeersSvc(inner);
+                        let method = ImportOfferSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1072,23 +4821,25 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/ConnectPeer" => {
+                "/ddkrpc.DdkRpc/ImportContract" => {
                     #[allow(non_camel_case_types)]
-                    struct ConnectPeerSvc<T: DdkRpc>(pub Arc<T>);
-                    impl<T: DdkRpc> tonic::server::UnaryService<super::ConnectRequest>
-                    for ConnectPeerSvc<T> {
-                        type Response = super::ConnectResponse;
+                    struct ImportContractSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<
+                        T: DdkRpc,
+                    > tonic::server::UnaryService<super::ImportContractRequest>
+                    for ImportContractSvc<T> {
+                        type Response = super::ImportContractResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ConnectRequest>,
+                            request: tonic::Request<super::ImportContractRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::connect_peer(&inner, request).await
+                                <T as DdkRpc>::import_contract(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1100,7 +4851,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ConnectPeerSvc(inner);
+                        let method = ImportContractSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1116,25 +4867,69 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/ListOracles" => {
+                "/ddkrpc.DdkRpc/Health" => {
                     #[allow(non_camel_case_types)]
-                    struct ListOraclesSvc<T: DdkRpc>(pub Arc<T>);
+                    struct HealthSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::HealthRequest>
+                    for HealthSvc<T> {
+                        type Response = super::HealthResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HealthRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::health(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = HealthSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/MessageStats" => {
+                    #[allow(non_camel_case_types)]
+                    struct MessageStatsSvc<T: DdkRpc>(pub Arc<T>);
                     impl<
                         T: DdkRpc,
-                    > tonic::server::UnaryService<super::ListOraclesRequest>
-                    for ListOraclesSvc<T> {
-                        type Response = super::ListOraclesResponse;
+                    > tonic::server::UnaryService<super::MessageStatsRequest>
+                    for MessageStatsSvc<T> {
+                        type Response = super::MessageStatsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListOraclesRequest>,
+                            request: tonic::Request<super::MessageStatsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::list_oracles(&inner, request).await
+                                <T as DdkRpc>::message_stats(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1146,7 +4941,7 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ListOraclesSvc(inner);
+                        let method = MessageStatsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1162,25 +4957,69 @@ pub mod ddk_rpc_server {
                     };
                     Box::pin(fut)
                 }
-                "/ddkrpc.DdkRpc/ListContracts" => {
+                "/ddkrpc.DdkRpc/AddRelay" => {
                     #[allow(non_camel_case_types)]
-                    struct ListContractsSvc<T: DdkRpc>(pub Arc<T>);
+                    struct AddRelaySvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::AddRelayRequest>
+                    for AddRelaySvc<T> {
+                        type Response = super::AddRelayResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AddRelayRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::add_relay(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AddRelaySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/RemoveRelay" => {
+                    #[allow(non_camel_case_types)]
+                    struct RemoveRelaySvc<T: DdkRpc>(pub Arc<T>);
                     impl<
                         T: DdkRpc,
-                    > tonic::server::UnaryService<super::ListContractsRequest>
-                    for ListContractsSvc<T> {
-                        type Response = super::ListContractsResponse;
+                    > tonic::server::UnaryService<super::RemoveRelayRequest>
+                    for RemoveRelaySvc<T> {
+                        type Response = super::RemoveRelayResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListContractsRequest>,
+                            request: tonic::Request<super::RemoveRelayRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as DdkRpc>::list_contracts(&inner, request).await
+                                <T as DdkRpc>::remove_relay(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1192,7 +5031,51 @@ pub mod ddk_rpc_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ListContractsSvc(inner);
+                        let method = RemoveRelaySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ddkrpc.DdkRpc/ListRelays" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListRelaysSvc<T: DdkRpc>(pub Arc<T>);
+                    impl<T: DdkRpc> tonic::server::UnaryService<super::ListRelaysRequest>
+                    for ListRelaysSvc<T> {
+                        type Response = super::ListRelaysResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListRelaysRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DdkRpc>::list_relays(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListRelaysSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -0,0 +1,247 @@
+//! JSON-RPC 2.0 over HTTP for clients that cannot speak gRPC.
+//!
+//! Each method is the snake case name of a gRPC method and calls the gRPC service of
+//! [DdkNode] directly. `params` is the gRPC request and `result` the gRPC response, both as
+//! JSON. Fields missing from `params` take their protobuf default.
+use crate::ddkrpc::ddk_rpc_server::DdkRpc;
+use crate::DdkNode;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Server, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+/// Error codes from the JSON-RPC 2.0 specification.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// An error returned by the gRPC service. The gRPC status code is in `data`.
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    id: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl JsonRpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<Status> for JsonRpcError {
+    fn from(status: Status) -> Self {
+        Self {
+            code: SERVER_ERROR,
+            message: status.message().to_string(),
+            data: Some(json!({ "grpc_code": format!("{:?}", status.code()) })),
+        }
+    }
+}
+
+/// Serve JSON-RPC on `addr` until `shutdown` completes. Requests are HTTP POSTs with a
+/// JSON-RPC request object as the body.
+pub async fn serve(
+    node: Arc<DdkNode>,
+    addr: SocketAddr,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let node = node.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| handle_http(node.clone(), request)))
+        }
+    });
+    Server::try_bind(&addr)?
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown)
+        .await?;
+    Ok(())
+}
+
+async fn handle_http(
+    node: Arc<DdkNode>,
+    request: hyper::Request<Body>,
+) -> Result<hyper::Response<Body>, Infallible> {
+    if request.method() != Method::POST {
+        let response = hyper::Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .expect("valid response");
+        return Ok(response);
+    }
+
+    let response = match hyper::body::to_bytes(request.into_body()).await {
+        Ok(body) => handle(&node, &body).await,
+        Err(e) => error_response(Value::Null, JsonRpcError::new(PARSE_ERROR, e.to_string())),
+    };
+    let response = hyper::Response::builder()
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(response.to_string()))
+        .expect("valid response");
+    Ok(response)
+}
+
+/// Handle the body of a JSON-RPC request and return the JSON-RPC response.
+pub async fn handle(node: &DdkNode, body: &[u8]) -> Value {
+    let request = match serde_json::from_slice::<Value>(body) {
+        Ok(request) => request,
+        Err(e) => return error_response(Value::Null, JsonRpcError::new(PARSE_ERROR, e.to_string())),
+    };
+    let request = match serde_json::from_value::<JsonRpcRequest>(request) {
+        Ok(request) if request.jsonrpc == "2.0" => request,
+        Ok(request) => {
+            let error = JsonRpcError::new(INVALID_REQUEST, "Only JSON-RPC 2.0 is supported.");
+            return error_response(request.id, error);
+        }
+        Err(e) => {
+            return error_response(Value::Null, JsonRpcError::new(INVALID_REQUEST, e.to_string()))
+        }
+    };
+
+    let params = match request.params {
+        Value::Null => json!({}),
+        params => params,
+    };
+    match dispatch(node, &request.method, params).await {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": request.id }),
+        Err(error) => error_response(request.id, error),
+    }
+}
+
+fn error_response(id: Value, error: JsonRpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "error": error, "id": id })
+}
+
+/// Calls the gRPC method with the same name as `$method`.
+macro_rules! dispatch_methods {
+    ($node:expr, $method:expr, $params:expr, [$($rpc:ident),* $(,)?]) => {
+        match $method {
+            $(stringify!($rpc) => call($params, |request| $node.$rpc(request)).await,)*
+            method => Err(JsonRpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method `{}` not found.", method),
+            )),
+        }
+    };
+}
+
+async fn dispatch(node: &DdkNode, method: &str, params: Value) -> Result<Value, JsonRpcError> {
+    // Streamed responses are returned as an array of the streamed messages.
+    if method == "get_wallet_transactions" {
+        return call_streaming(params, |request| node.get_wallet_transactions(request)).await;
+    }
+    if method == "stream_contracts" {
+        return call_streaming(params, |request| node.stream_contracts(request)).await;
+    }
+    dispatch_methods!(node, method, params, [
+        info,
+        health,
+        message_stats,
+        add_relay,
+        remove_relay,
+        list_relays,
+        send_offer,
+        accept_offer,
+        list_offers,
+        new_address,
+        wallet_balance,
+        list_utxos,
+        list_peers,
+        connect_peer,
+        list_oracles,
+        list_oracle_events,
+        list_contracts,
+        rebroadcast_funding,
+        get_contract_transactions,
+        rescan,
+        cancel_offer,
+        fill_offer,
+        find_contracts_by_event,
+        open_channel,
+        accept_channel,
+        settle_channel,
+        accept_settle_channel,
+        renew_channel,
+        accept_renew_channel,
+        close_channel,
+        accept_close_channel,
+        list_channels,
+        estimate_fee,
+        set_label,
+        get_label,
+        get_contract_history,
+        show_keys,
+        export_nostr_key,
+        send,
+        force_attestation,
+        verify_funding,
+        resend_last_message,
+        retry_dead_letter,
+        get_cet_list,
+        get_contract,
+        get_offer_message,
+        import_offer,
+        import_contract,
+        portfolio_summary,
+    ])
+}
+
+async fn call<Req, Res, F, Fut>(params: Value, rpc: F) -> Result<Value, JsonRpcError>
+where
+    Req: DeserializeOwned,
+    Res: Serialize,
+    F: FnOnce(Request<Req>) -> Fut,
+    Fut: Future<Output = Result<Response<Res>, Status>>,
+{
+    let request = serde_json::from_value::<Req>(params)
+        .map_err(|e| JsonRpcError::new(INVALID_PARAMS, e.to_string()))?;
+    let response = rpc(Request::new(request)).await?;
+    serde_json::to_value(response.into_inner())
+        .map_err(|e| JsonRpcError::new(INTERNAL_ERROR, e.to_string()))
+}
+
+async fn call_streaming<Req, Res, St, F, Fut>(params: Value, rpc: F) -> Result<Value, JsonRpcError>
+where
+    Req: DeserializeOwned,
+    Res: Serialize,
+    St: Stream<Item = Result<Res, Status>> + Unpin,
+    F: FnOnce(Request<Req>) -> Fut,
+    Fut: Future<Output = Result<Response<St>, Status>>,
+{
+    let request = serde_json::from_value::<Req>(params)
+        .map_err(|e| JsonRpcError::new(INVALID_PARAMS, e.to_string()))?;
+    let mut stream = rpc(Request::new(request)).await?.into_inner();
+    let mut messages = Vec::new();
+    while let Some(message) = stream.next().await {
+        let message = serde_json::to_value(message?)
+            .map_err(|e| JsonRpcError::new(INTERNAL_ERROR, e.to_string()))?;
+        messages.push(message);
+    }
+    Ok(Value::Array(messages))
+}
@@ -1,58 +1,322 @@
 pub mod ddkrpc;
+pub mod jsonrpc;
 
+use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use ddk::bitcoin::secp256k1::PublicKey;
 use ddk::dlc_manager::contract::contract_input::ContractInput;
-use ddk::dlc_manager::Storage;
+use ddk::dlc_manager::{Blockchain, ContractId, Storage};
 use ddk::oracle::KormirOracleClient;
-use ddk::storage::SledStorageProvider;
+use ddk::storage::{CachingStorage, SledStorageProvider};
 use ddk::transport::lightning::LightningTransport;
-use ddk::util::serialize_contract;
+#[cfg(feature = "nostr")]
+use ddk::transport::nostr::NostrDlcRelayHandler;
+use ddk::transport::AnyTransport;
+use ddk::util::{cet_list, contract_fees, contract_state, contract_summaries, encode_offer, npub, contract_transactions, funding_outpoint, preview_offer_id, serialize_contract};
+use ddk::wallet::{fee_rate_for_target, sats_per_vbyte};
 use ddk::DlcDevKit;
-use ddk::{DdkOracle, DdkTransport};
+use ddk::dlc_manager::contract::Contract;
+use ddk::{DdkOracle, DdkStorage, DdkTransport};
 use ddkrpc::ddk_rpc_server::DdkRpc;
 use ddkrpc::{
-    AcceptOfferRequest, AcceptOfferResponse, ConnectRequest, ConnectResponse, GetWalletTransactionsRequest, GetWalletTransactionsResponse, ListContractsRequest, ListContractsResponse, ListOffersRequest, ListOffersResponse, ListOraclesRequest, ListOraclesResponse, ListPeersRequest, ListPeersResponse, ListUtxosRequest, ListUtxosResponse, NewAddressRequest, NewAddressResponse, Peer, SendOfferRequest, SendOfferResponse, WalletBalanceRequest, WalletBalanceResponse
+    AcceptOfferRequest, AcceptOfferResponse, CancelOfferRequest, CancelOfferResponse, ConnectRequest, ConnectResponse, ContractTransaction, FillOfferRequest, FillOfferResponse, FindContractsByEventRequest, FindContractsByEventResponse, GetContractTransactionsRequest, GetContractTransactionsResponse, GetWalletTransactionsRequest, ListContractsRequest, ListContractsResponse, ListOffersRequest, ListOffersResponse, ListOraclesRequest, ListOraclesResponse, ListPeersRequest, ListPeersResponse, ListUtxosRequest, ListUtxosResponse, NewAddressRequest, NewAddressResponse, OfferExpiry, Peer, RebroadcastFundingRequest, RebroadcastFundingResponse, RescanRequest, RescanResponse, SendOfferRequest, SendOfferResponse, WalletBalanceRequest, WalletBalanceResponse
 };
-use ddkrpc::{InfoRequest, InfoResponse};
+use ddkrpc::{ContractStateCount, HealthRequest, HealthResponse, InfoRequest, InfoResponse};
+use ddkrpc::{MessageStatsRequest, MessageStatsResponse};
+use ddkrpc::{AddRelayRequest, AddRelayResponse, ListRelaysRequest, ListRelaysResponse, Relay, RemoveRelayRequest, RemoveRelayResponse};
+use ddkrpc::{
+    ChannelInfo, ChannelRequest, ChannelResponse, CloseChannelRequest, ListChannelsRequest, ListChannelsResponse, OpenChannelRequest, RenewChannelRequest, SettleChannelRequest
+};
+use ddk::channel::channel_state;
+use ddk::lightning::chain::chaininterface::{ConfirmationTarget, FeeEstimator};
+use ddkrpc::{EstimateFeeRequest, EstimateFeeResponse};
+use ddkrpc::{GetLabelRequest, GetLabelResponse, Label, SetLabelRequest, SetLabelResponse};
+use ddkrpc::ContractFees;
+use ddkrpc::{ContractTransition, GetContractHistoryRequest, GetContractHistoryResponse};
+use ddkrpc::{ExportNostrKeyRequest, ExportNostrKeyResponse, ShowKeysRequest, ShowKeysResponse};
+use ddk::error::{ContractError, OracleError};
+use ddk::transport::MessageReceipt;
+use ddk::storage::{contract_label, LabelKey};
+use ddk::bitcoin::{Address, Amount, FeeRate, OutPoint, Txid};
+use ddkrpc::WalletTransaction;
+use tokio_stream::wrappers::ReceiverStream;
+use ddkrpc::{ForceAttestationRequest, ForceAttestationResponse, SendRequest, SendResponse};
+use ddkrpc::{ResendLastMessageRequest, ResendLastMessageResponse, RetryDeadLetterRequest, RetryDeadLetterResponse, VerifyFundingRequest, VerifyFundingResponse};
+use ddkrpc::{Cet, GetCetListRequest, GetCetListResponse, GetContractRequest, GetContractResponse};
+use ddkrpc::{GetOfferMessageRequest, GetOfferMessageResponse, ImportOfferRequest, ImportOfferResponse};
+use ddkrpc::{ImportContractRequest, ImportContractResponse};
+use ddkrpc::{ContractSummary, StreamContractsRequest};
+use ddkrpc::{ListOracleEventsRequest, ListOracleEventsResponse, OracleEvent};
+use ddkrpc::{ContractRisk, PortfolioSummaryRequest, PortfolioSummaryResponse};
+use ddk::oracle::EventFilter;
 use tonic::{async_trait, Code};
 use tonic::Request;
 use tonic::Response;
 use tonic::Status;
 
-type DdkServer = DlcDevKit<LightningTransport, SledStorageProvider, KormirOracleClient>;
+/// Parse a hex encoded contract id.
+fn contract_id_from_hex(contract_id: &str) -> Result<ContractId, Status> {
+    let bytes = hex::decode(contract_id).map_err(|_| Status::new(Code::InvalidArgument, "Contract id is not valid hex."))?;
+    bytes.try_into().map_err(|_| Status::new(Code::InvalidArgument, "Contract id must be 32 bytes."))
+}
+
+/// Parse utxos given as `txid:vout`.
+fn outpoints(utxos: &[String]) -> Result<Vec<OutPoint>, Status> {
+    utxos
+        .iter()
+        .map(|utxo| {
+            OutPoint::from_str(utxo)
+                .map_err(|_| Status::new(Code::InvalidArgument, format!("Invalid utxo `{}`.", utxo)))
+        })
+        .collect()
+}
+
+/// Status of a failed relay change. Bad and unknown relay urls are the client's fault.
+#[cfg(feature = "nostr")]
+fn relay_status(e: ddk::error::NostrError) -> Status {
+    match e {
+        ddk::error::NostrError::InvalidRelayUrl(_) => Status::new(Code::InvalidArgument, e.to_string()),
+        ddk::error::NostrError::UnknownRelay(_) => Status::new(Code::NotFound, e.to_string()),
+        _ => Status::new(Code::Internal, e.to_string()),
+    }
+}
+
+/// Wallet transactions buffered for a slow client before the stream waits.
+const TRANSACTION_STREAM_BUFFER: usize = 64;
+/// Contract summaries buffered for a slow client before the stream waits.
+const CONTRACT_STREAM_BUFFER: usize = 256;
+
+type DdkServer = DlcDevKit<AnyTransport, CachingStorage<SledStorageProvider>, KormirOracleClient>;
 
 pub struct DdkNode {
     pub inner: Arc<DdkServer>,
+    /// Whether methods for testing, like forcing an attestation, are served.
+    pub dev_mode: bool,
+    /// The nostr transport, if the node listens on a relay next to lightning.
+    #[cfg(feature = "nostr")]
+    pub nostr: Option<Arc<NostrDlcRelayHandler>>,
 }
 
 impl DdkNode {
     pub fn new(ddk: DdkServer) -> Self {
         Self {
             inner: Arc::new(ddk),
-
+            dev_mode: false,
+            #[cfg(feature = "nostr")]
+            nostr: None,
         }
     }
+
+    /// The nostr transport of the node. Fails if the node does not listen on nostr.
+    #[cfg(feature = "nostr")]
+    fn nostr(&self) -> Result<&NostrDlcRelayHandler, Status> {
+        self.nostr.as_deref().ok_or_else(|| {
+            Status::new(Code::FailedPrecondition, "The node does not listen on nostr. Start it with --nostr-relay.")
+        })
+    }
+
+    /// Serve the nostr methods, like message stats, from the transport the node registered.
+    #[cfg(feature = "nostr")]
+    pub fn with_nostr(mut self, nostr: Arc<NostrDlcRelayHandler>) -> Self {
+        self.nostr = Some(nostr);
+        self
+    }
+
+    /// The lightning transport. The node is always built with it as its primary transport.
+    fn lightning(&self) -> &LightningTransport {
+        self.inner
+            .transport
+            .as_lightning()
+            .expect("ddk-node's primary transport is lightning")
+    }
+
+    /// Serve the methods for regtest and dispute testing. They also need the `dev` feature.
+    pub fn with_dev_mode(mut self, dev_mode: bool) -> Self {
+        self.dev_mode = dev_mode;
+        self
+    }
+
+    /// The stored contract id that `id` is a unique prefix of, or `id` itself if it is a full id.
+    fn resolve_contract_id(&self, id: &str) -> Result<ContractId, Status> {
+        self.inner.resolve_contract_id(id).map_err(|e| match e.downcast_ref::<ContractError>() {
+            Some(ContractError::UnknownContract(_)) => Status::new(Code::NotFound, e.to_string()),
+            Some(_) => Status::new(Code::InvalidArgument, e.to_string()),
+            None => Status::new(Code::Internal, e.to_string()),
+        })
+    }
 }
 
 #[async_trait]
 impl DdkRpc for DdkNode {
-    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
-    async fn info(&self, _request: Request<InfoRequest>) -> Result<Response<InfoResponse>, Status> {
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn info(&self, request: Request<InfoRequest>) -> Result<Response<InfoResponse>, Status> {
         tracing::info!("Request for node info.");
-        let pubkey = self.inner.transport.node_id.to_string();
+        let verbose = request.into_inner().verbose;
+        let node_id = self.lightning().node_id;
+        let pubkey = node_id.to_string();
+        let pubkey_xonly = node_id.x_only_public_key().0.to_string();
+        let pubkey_npub = npub(&node_id);
         let transport = self.inner.transport.name();
         let oracle = self.inner.oracle.name();
+        let oracle_endpoint = self.inner.oracle.host().to_string();
+        let storage = self.inner.storage.name();
+        let network = self.inner.network().to_string();
+        let connected_peers = self.lightning().ln_peer_manager().list_peers().len() as u64;
+
+        let mut counts: BTreeMap<&'static str, u64> = BTreeMap::new();
+        let contracts = self.inner.storage.get_contracts()
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        for contract in &contracts {
+            *counts.entry(contract_state(contract)).or_default() += 1;
+        }
+        let contracts = counts.into_iter()
+            .map(|(state, count)| ContractStateCount { state: state.to_string(), count })
+            .collect();
+
+        let (derivation_paths, sync_height) = if verbose {
+            let paths = self.inner.wallet.derivation_paths()
+                .into_iter()
+                .map(|(purpose, path)| format!("{purpose}: {path}"))
+                .collect();
+            let height = self.inner.wallet.tip_height()
+                .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+            (paths, Some(height))
+        } else {
+            (Vec::new(), None)
+        };
+
         let response = InfoResponse {
             pubkey,
             transport,
             oracle,
+            oracle_endpoint,
+            storage,
+            network,
+            connected_peers,
+            contracts,
+            derivation_paths,
+            sync_height,
+            pubkey_xonly,
+            pubkey_npub,
+            max_active_contracts: self.inner.max_active_contracts().map(|limit| limit as u64),
+            max_contract_collateral: self.inner.max_contract_collateral(),
+            node_version: env!("CARGO_PKG_VERSION").to_string(),
+            ddk_version: ddk::VERSION.to_string(),
+            rust_dlc_version: ddk::RUST_DLC_VERSION.to_string(),
+            protocol_version: ddk::DLC_PROTOCOL_VERSION,
         };
         Ok(Response::new(response))
     }
 
+    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
+    async fn health(&self, _request: Request<HealthRequest>) -> Result<Response<HealthResponse>, Status> {
+        tracing::info!("Request for node health.");
+        let network = self.inner.network();
+        let esplora = &self.inner.blockchain.async_client;
+        let (esplora_height, esplora_network_matches, esplora_error) = match esplora.get_height().await {
+            Ok(height) => match esplora.get_block_hash(0).await {
+                Ok(genesis) => {
+                    let matches = genesis == ddk::bitcoin::constants::genesis_block(network).block_hash();
+                    (Some(height), matches, None)
+                }
+                Err(e) => (Some(height), false, Some(e.to_string())),
+            },
+            Err(e) => (None, false, Some(e.to_string())),
+        };
+        let (oracle_key_matches, oracle_error) = match self.inner.oracle.get_public_key_async().await {
+            Ok(key) => (key == ddk::dlc_manager::Oracle::get_public_key(self.inner.oracle.as_ref()), None),
+            Err(e) => (false, Some(e.to_string())),
+        };
+        let wallet_height = self.inner.wallet.tip_height()
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+
+        Ok(Response::new(HealthResponse {
+            network: network.to_string(),
+            esplora_host: self.inner.blockchain.host().to_string(),
+            esplora_error,
+            esplora_height,
+            esplora_network_matches,
+            oracle_endpoint: self.inner.oracle.host().to_string(),
+            oracle_error,
+            oracle_key_matches,
+            connected_peers: self.lightning().ln_peer_manager().list_peers().len() as u64,
+            wallet_height,
+        }))
+    }
+
+    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
+    async fn message_stats(&self, _request: Request<MessageStatsRequest>) -> Result<Response<MessageStatsResponse>, Status> {
+        tracing::info!("Request for nostr message stats.");
+        #[cfg(feature = "nostr")]
+        {
+            let nostr = self.nostr()?;
+            let totals = |sizes: BTreeMap<String, ddk::transport::nostr::MessageSizes>| {
+                sizes
+                    .into_iter()
+                    .map(|(message_type, sizes)| ddkrpc::MessageTypeStats {
+                        message_type,
+                        messages: sizes.messages,
+                        plaintext_bytes: sizes.plaintext_bytes,
+                        encrypted_bytes: sizes.encrypted_bytes,
+                    })
+                    .collect()
+            };
+            Ok(Response::new(MessageStatsResponse {
+                sent: totals(nostr.message_stats.sent()),
+                received: totals(nostr.message_stats.received()),
+            }))
+        }
+        #[cfg(not(feature = "nostr"))]
+        Err(Status::new(Code::Unimplemented, "ddk-node was built without the nostr feature."))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn add_relay(&self, request: Request<AddRelayRequest>) -> Result<Response<AddRelayResponse>, Status> {
+        let AddRelayRequest { relay } = request.into_inner();
+        tracing::info!(relay, "Request to add a nostr relay.");
+        #[cfg(feature = "nostr")]
+        {
+            let relay = self.nostr()?.add_relay(&relay).await.map_err(relay_status)?;
+            Ok(Response::new(AddRelayResponse { relay: relay.to_string() }))
+        }
+        #[cfg(not(feature = "nostr"))]
+        Err(Status::new(Code::Unimplemented, "ddk-node was built without the nostr feature."))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn remove_relay(&self, request: Request<RemoveRelayRequest>) -> Result<Response<RemoveRelayResponse>, Status> {
+        let RemoveRelayRequest { relay } = request.into_inner();
+        tracing::info!(relay, "Request to remove a nostr relay.");
+        #[cfg(feature = "nostr")]
+        {
+            let relay = self.nostr()?.remove_relay(&relay).await.map_err(relay_status)?;
+            Ok(Response::new(RemoveRelayResponse { relay: relay.to_string() }))
+        }
+        #[cfg(not(feature = "nostr"))]
+        Err(Status::new(Code::Unimplemented, "ddk-node was built without the nostr feature."))
+    }
+
+    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
+    async fn list_relays(&self, _request: Request<ListRelaysRequest>) -> Result<Response<ListRelaysResponse>, Status> {
+        tracing::info!("Request for nostr relays.");
+        #[cfg(feature = "nostr")]
+        {
+            let relays = self
+                .nostr()?
+                .relays()
+                .await
+                .into_iter()
+                .map(|relay| Relay { url: relay.url.to_string(), status: relay.status.to_string() })
+                .collect();
+            Ok(Response::new(ListRelaysResponse { relays }))
+        }
+        #[cfg(not(feature = "nostr"))]
+        Err(Status::new(Code::Unimplemented, "ddk-node was built without the nostr feature."))
+    }
+
     #[tracing::instrument(skip(self, request), name = "grpc_server")]
     async fn send_offer(
         &self,
@@ -62,23 +326,51 @@ impl DdkRpc for DdkNode {
         let SendOfferRequest {
             contract_input,
             counter_party,
+            expiry_secs,
+            allow_partial,
+            label,
+            utxos,
+            funding_target_blocks,
         } = request.into_inner();
+        let utxos = outpoints(&utxos)?;
+        if allow_partial && !utxos.is_empty() {
+            return Err(Status::new(Code::InvalidArgument, "A partial offer cannot be funded from chosen utxos."));
+        }
+        let funding_target = funding_target_blocks.map(target_blocks).transpose()?;
+        if allow_partial && funding_target.is_some() {
+            return Err(Status::new(Code::InvalidArgument, "A partial offer cannot have a funding confirmation target."));
+        }
         let contract_input: ContractInput =
             serde_json::from_slice(&contract_input).expect("couldn't get bytes correct");
         let mut oracle_announcements = Vec::new();
         for info in &contract_input.contract_infos {
-            let announcement = self.inner.oracle.get_announcement_async(&info.oracles.event_id).await.unwrap();
+            let announcement = self.inner.get_verified_announcement(&info.oracles.event_id).await.map_err(|e| Status::new(Code::Cancelled, e.to_string()))?;
             oracle_announcements.push(announcement)
         }
 
         let counter_party = PublicKey::from_str(&counter_party).expect("no public key");
-        let offer_msg = self
-            .inner
-            .send_dlc_offer(&contract_input, counter_party, oracle_announcements).map_err(|e| Status::new(Code::Cancelled, format!("Contract offer could not be sent to counterparty. error={:?}", e)))?;
+        let expiry = expiry_secs.map(Duration::from_secs);
+        let offer_msg = if allow_partial {
+            self.inner.send_partial_offer(&contract_input, counter_party, oracle_announcements, expiry).await
+        } else if let Some(target) = funding_target {
+            self.inner.send_dlc_offer_for_target(&contract_input, counter_party, oracle_announcements, expiry, utxos, target).await
+        } else {
+            self.inner.send_dlc_offer_from_utxos(&contract_input, counter_party, oracle_announcements, expiry, utxos).await
+        }
+        .map_err(|e| match e.downcast_ref::<ContractError>() {
+            Some(limit) => Status::new(Code::ResourceExhausted, limit.to_string()),
+            None => Status::new(Code::Cancelled, format!("Contract offer could not be sent to counterparty. error={:?}", e)),
+        })?;
+
+        if let Some(label) = label {
+            self.inner.storage.save_label(&LabelKey::Contract(offer_msg.temporary_contract_id), &label)
+                .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        }
 
+        let contract_id = hex::encode(preview_offer_id(&offer_msg));
         let offer_dlc =
             serde_json::to_vec(&offer_msg).expect("OfferDlc could not be converted to vec.");
-        Ok(Response::new(SendOfferResponse { offer_dlc }))
+        Ok(Response::new(SendOfferResponse { offer_dlc, contract_id }))
     }
 
     #[tracing::instrument(skip(self, request), name = "grpc_server")]
@@ -87,13 +379,24 @@ impl DdkRpc for DdkNode {
         request: Request<AcceptOfferRequest>,
     ) -> Result<Response<AcceptOfferResponse>, Status> {
         tracing::info!("Request to accept offer.");
-        let mut contract_id = [0u8; 32];
-        let contract_id_bytes = hex::decode(&request.into_inner().contract_id).unwrap();
-        contract_id.copy_from_slice(&contract_id_bytes);
-        println!("{:?}", contract_id);
+        let AcceptOfferRequest { contract_id, label } = request.into_inner();
+        let contract_id = self.resolve_contract_id(&contract_id)?;
+        // The label is listed with the contract under its final id too.
+        if let Some(label) = label {
+            self.inner.storage.save_label(&LabelKey::Contract(contract_id), &label)
+                .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        }
         let (contract_id, counter_party, accept_dlc) = self
             .inner
-            .accept_dlc_offer(contract_id).map_err(|_| Status::new(Code::Cancelled, "Contract could not be accepted."))?;
+            .accept_dlc_offer(contract_id).await.map_err(|e| {
+                if let Some(unavailable) = e.downcast_ref::<OracleError>() {
+                    Status::new(Code::Unavailable, unavailable.to_string())
+                } else if let Some(limit) = e.downcast_ref::<ContractError>() {
+                    Status::new(Code::ResourceExhausted, limit.to_string())
+                } else {
+                    Status::new(Code::Cancelled, "Contract could not be accepted.")
+                }
+            })?;
 
         let accept_dlc = serde_json::to_vec(&accept_dlc).map_err(|_| Status::new(Code::Cancelled, "Accept DLC is malformed to create bytes."))?;
 
@@ -142,28 +445,57 @@ impl DdkRpc for DdkNode {
     ) -> Result<Response<WalletBalanceResponse>, Status> {
         tracing::info!("Request for wallet balance.");
         let wallet_balance = self.inner.wallet.get_balance().unwrap();
+        let reserved = self
+            .inner
+            .wallet
+            .reserved_balance()
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
 
         let response = WalletBalanceResponse {
             confirmed: wallet_balance.confirmed.to_sat(),
             unconfirmed: (wallet_balance.trusted_pending + wallet_balance.untrusted_pending).to_sat(),
+            reserved: reserved.to_sat(),
         };
         Ok(Response::new(response))
     }
 
-    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
+    type GetWalletTransactionsStream = ReceiverStream<Result<WalletTransaction, Status>>;
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
     async fn get_wallet_transactions(
         &self,
-        _request: Request<GetWalletTransactionsRequest>,
-    ) -> Result<Response<GetWalletTransactionsResponse>, Status> {
-        tracing::info!("Request for all wallet transactions.");
-        let wallet_transactions = self.inner.wallet.get_transactions().unwrap();
-        let transactions: Vec<Vec<u8>> = wallet_transactions
-            .iter()
-            .map(|t| serde_json::to_vec(&t).unwrap())
-            .collect();
-        Ok(Response::new(GetWalletTransactionsResponse {
-            transactions,
-        }))
+        request: Request<GetWalletTransactionsRequest>,
+    ) -> Result<Response<Self::GetWalletTransactionsStream>, Status> {
+        tracing::info!("Request for wallet transactions.");
+        let GetWalletTransactionsRequest { limit, before_height } = request.into_inner();
+        let history = self
+            .inner
+            .wallet
+            .transaction_history(limit.map(|limit| limit as usize), before_height)
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(TRANSACTION_STREAM_BUFFER);
+        let ddk = self.inner.clone();
+        tokio::spawn(async move {
+            for tx in history {
+                let txid = tx.transaction.compute_txid();
+                let record = ddk
+                    .storage
+                    .get_label(&LabelKey::Transaction(txid))
+                    .map_err(|e| Status::new(Code::Internal, e.to_string()))
+                    .map(|label| WalletTransaction {
+                        transaction: serde_json::to_vec(&tx.transaction).unwrap(),
+                        height: tx.height,
+                        time: tx.time,
+                        label,
+                    });
+                // The client hung up.
+                if sender.send(record).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
     }
 
     #[tracing::instrument(skip(self, _request), name = "grpc_server")]
@@ -184,7 +516,7 @@ impl DdkRpc for DdkNode {
     #[tracing::instrument(skip(self, _request), name = "grpc_server")]
     async fn list_peers(&self, _request: Request<ListPeersRequest>) -> Result<Response<ListPeersResponse>, Status> {
         tracing::info!("List peers request");
-        let peers = self.inner.transport.ln_peer_manager().list_peers();
+        let peers = self.lightning().ln_peer_manager().list_peers();
         let peers = peers.iter()
             .map(|peer| {
                 let host = match &peer.socket_address {
@@ -216,11 +548,613 @@ impl DdkRpc for DdkNode {
         Ok(Response::new(ListOraclesResponse { name, pubkey }))
     }
 
+    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
+    async fn portfolio_summary(&self, _request: Request<PortfolioSummaryRequest>) -> Result<Response<PortfolioSummaryResponse>, Status> {
+        tracing::info!("Request for the portfolio summary.");
+        let portfolio = self.inner.portfolio().await.map_err(|e| Status::new(Code::Cancelled, e.to_string()))?;
+        let contracts = portfolio.contracts.into_iter()
+            .map(|risk| ContractRisk {
+                contract_id: risk.contract_id,
+                collateral: risk.collateral,
+                max_loss: risk.max_loss,
+                mark_to_market: risk.mark_to_market,
+            })
+            .collect();
+        Ok(Response::new(PortfolioSummaryResponse {
+            total_collateral: portfolio.total_collateral,
+            total_max_loss: portfolio.total_max_loss,
+            mark_to_market: portfolio.mark_to_market,
+            contracts,
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn list_oracle_events(&self, request: Request<ListOracleEventsRequest>) -> Result<Response<ListOracleEventsResponse>, Status> {
+        tracing::info!("Request to list oracle events.");
+        let ListOracleEventsRequest { asset, from, to } = request.into_inner();
+        let filter = EventFilter { asset, from, to };
+        let announcements = self.inner.list_oracle_events(&filter).await
+            .map_err(|e| Status::new(Code::Unavailable, e.to_string()))?;
+        let events = announcements.iter()
+            .map(|announcement| OracleEvent {
+                event_id: announcement.oracle_event.event_id.clone(),
+                maturity: announcement.oracle_event.event_maturity_epoch,
+                announcement: serde_json::to_vec(announcement).unwrap(),
+            })
+            .collect();
+        Ok(Response::new(ListOracleEventsResponse { events }))
+    }
+
     async fn list_contracts(&self, _request: Request<ListContractsRequest>) -> Result<Response<ListContractsResponse>, Status> {
         let contracts = self.inner.storage.get_contracts().map_err(|e| Status::new(Code::Cancelled, e.to_string()))?;
         let contract_bytes: Vec<Vec<u8>> = contracts.iter()
             .map(|contract| serialize_contract(contract).unwrap())
             .collect();
-        Ok(Response::new(ListContractsResponse {contracts: contract_bytes}))
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let mut offer_expiries = Vec::new();
+        for contract in &contracts {
+            if let Contract::Offered(offer) = contract {
+                let expiry = self.inner.storage.get_offer_expiry(&offer.id).map_err(|e| Status::new(Code::Cancelled, e.to_string()))?;
+                if let Some(expiry) = expiry {
+                    offer_expiries.push(OfferExpiry {
+                        contract_id: hex::encode(offer.id),
+                        expires_in: expiry.saturating_sub(now),
+                    })
+                }
+            }
+        }
+
+        let mut labels = Vec::new();
+        for contract in &contracts {
+            let label = contract_label(self.inner.storage.as_ref(), contract)
+                .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+            if let Some(label) = label {
+                labels.push(Label { id: hex::encode(contract.get_id()), label });
+            }
+        }
+
+        let mut funding_conflicts = Vec::new();
+        for contract in &contracts {
+            if !matches!(contract, Contract::FailedSign(_)) {
+                continue;
+            }
+            let conflict = self.inner.storage.get_funding_conflict(&contract.get_id())
+                .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+            if let Some(conflict) = conflict {
+                funding_conflicts.push(ddkrpc::FundingConflict {
+                    contract_id: hex::encode(conflict.contract_id),
+                    outpoint: conflict.outpoint.to_string(),
+                    conflicting_txid: conflict.conflicting_txid.to_string(),
+                });
+            }
+        }
+
+        let mut dead_letters = Vec::new();
+        for contract in &contracts {
+            if !matches!(contract, Contract::Accepted(_) | Contract::Signed(_)) {
+                continue;
+            }
+            let dead_letter = self.inner.storage.get_dead_letter(&contract.get_id())
+                .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+            if let Some(dead_letter) = dead_letter {
+                dead_letters.push(ddkrpc::DeadLetter {
+                    contract_id: hex::encode(contract.get_id()),
+                    counter_party: dead_letter.counter_party.to_string(),
+                    attempts: dead_letter.attempts,
+                    failed_at: dead_letter.failed_at,
+                });
+            }
+        }
+
+        Ok(Response::new(ListContractsResponse {contracts: contract_bytes, offer_expiries, labels, funding_conflicts, dead_letters}))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn find_contracts_by_event(&self, request: Request<FindContractsByEventRequest>) -> Result<Response<FindContractsByEventResponse>, Status> {
+        let event_id = request.into_inner().event_id;
+        let contracts = self.inner.storage.get_contracts_by_oracle_event(&event_id)
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        let contracts = contracts.iter()
+            .map(|contract| serialize_contract(contract).unwrap())
+            .collect();
+        Ok(Response::new(FindContractsByEventResponse { contracts }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn rebroadcast_funding(&self, request: Request<RebroadcastFundingRequest>) -> Result<Response<RebroadcastFundingResponse>, Status> {
+        tracing::info!("Request to rebroadcast funding transaction.");
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        let response = match self.inner.rebroadcast_funding(&contract_id) {
+            Ok(txid) => RebroadcastFundingResponse { accepted: true, txid: txid.to_string(), error: String::new() },
+            Err(e) => RebroadcastFundingResponse { accepted: false, txid: String::new(), error: e.to_string() },
+        };
+        Ok(Response::new(response))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn resend_last_message(&self, request: Request<ResendLastMessageRequest>) -> Result<Response<ResendLastMessageResponse>, Status> {
+        tracing::info!("Request to resend the last message of a contract.");
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        let receipt = self.inner.resend_last_message(&contract_id).await.map_err(|e| {
+            match e.downcast_ref::<ContractError>() {
+                Some(ContractError::NoOutboundMessage(_)) => Status::new(Code::NotFound, e.to_string()),
+                _ => Status::new(Code::Unavailable, e.to_string()),
+            }
+        })?;
+        let (receipt, acknowledgement) = match receipt {
+            MessageReceipt::Queued => ("queued", String::new()),
+            MessageReceipt::Sent => ("sent", String::new()),
+            MessageReceipt::Acknowledged(proof) => ("acknowledged", proof),
+        };
+        Ok(Response::new(ResendLastMessageResponse {
+            receipt: receipt.to_string(),
+            acknowledgement,
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn retry_dead_letter(&self, request: Request<RetryDeadLetterRequest>) -> Result<Response<RetryDeadLetterResponse>, Status> {
+        tracing::info!("Request to retry a dead-lettered message.");
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        let receipt = self.inner.retry_dead_letter(&contract_id).await.map_err(|e| {
+            match e.downcast_ref::<ContractError>() {
+                Some(ContractError::NoDeadLetter(_) | ContractError::NoOutboundMessage(_)) => Status::new(Code::NotFound, e.to_string()),
+                _ => Status::new(Code::Unavailable, e.to_string()),
+            }
+        })?;
+        let (receipt, acknowledgement) = match receipt {
+            MessageReceipt::Queued => ("queued", String::new()),
+            MessageReceipt::Sent => ("sent", String::new()),
+            MessageReceipt::Acknowledged(proof) => ("acknowledged", proof),
+        };
+        Ok(Response::new(RetryDeadLetterResponse {
+            receipt: receipt.to_string(),
+            acknowledgement,
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn get_contract(&self, request: Request<GetContractRequest>) -> Result<Response<GetContractResponse>, Status> {
+        tracing::info!("Request for a contract.");
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        let contract = self.inner.storage.get_contract(&contract_id)
+            .map_err(|e| Status::new(Code::Cancelled, e.to_string()))?
+            .ok_or_else(|| Status::new(Code::NotFound, "Contract not found."))?;
+
+        let counter_party = contract.get_counter_party_id();
+        Ok(Response::new(GetContractResponse {
+            contract: serialize_contract(&contract).map_err(|e| Status::new(Code::Internal, e.to_string()))?,
+            state: contract_state(&contract).to_string(),
+            counter_party: counter_party.to_string(),
+            counterparty_online: self.inner.counterparty_online(&counter_party),
+        }))
+    }
+
+    type StreamContractsStream = ReceiverStream<Result<ContractSummary, Status>>;
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn stream_contracts(
+        &self,
+        request: Request<StreamContractsRequest>,
+    ) -> Result<Response<Self::StreamContractsStream>, Status> {
+        tracing::info!("Request to stream contracts.");
+        let StreamContractsRequest { state, direction } = request.into_inner();
+        let contracts = self.inner.storage.get_contracts().map_err(|e| Status::new(Code::Cancelled, e.to_string()))?;
+        let summaries = contract_summaries(&contracts, state.as_deref(), direction.as_deref());
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(CONTRACT_STREAM_BUFFER);
+        tokio::spawn(async move {
+            for summary in summaries {
+                let summary = ContractSummary {
+                    contract_id: summary.contract_id,
+                    state: summary.state,
+                    counter_party: summary.counter_party,
+                    direction: summary.direction,
+                    total_collateral: summary.total_collateral,
+                };
+                // The client hung up.
+                if sender.send(Ok(summary)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn get_offer_message(&self, request: Request<GetOfferMessageRequest>) -> Result<Response<GetOfferMessageResponse>, Status> {
+        tracing::info!("Request for the offer message of a contract.");
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        let contract = self.inner.storage.get_contract(&contract_id)
+            .map_err(|e| Status::new(Code::Cancelled, e.to_string()))?
+            .ok_or_else(|| Status::new(Code::NotFound, "Contract not found."))?;
+        let offer = encode_offer(&contract).map_err(|e| Status::new(Code::FailedPrecondition, e.to_string()))?;
+        Ok(Response::new(GetOfferMessageResponse { offer: hex::encode(offer) }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn import_offer(&self, request: Request<ImportOfferRequest>) -> Result<Response<ImportOfferResponse>, Status> {
+        tracing::info!("Request to import an offer.");
+        let ImportOfferRequest { offer, counter_party } = request.into_inner();
+        let offer = hex::decode(offer.trim())
+            .map_err(|_| Status::new(Code::InvalidArgument, "Offer is not valid hex."))?;
+        let counter_party = PublicKey::from_str(&counter_party)
+            .map_err(|_| Status::new(Code::InvalidArgument, "Invalid counterparty public key."))?;
+        let contract_id = self
+            .inner
+            .import_offer(&offer, counter_party)
+            .map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?;
+        Ok(Response::new(ImportOfferResponse { contract_id: hex::encode(contract_id) }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn import_contract(&self, request: Request<ImportContractRequest>) -> Result<Response<ImportContractResponse>, Status> {
+        tracing::info!("Request to import a contract.");
+        let ImportContractRequest { contract, overwrite } = request.into_inner();
+        let contract = hex::decode(contract.trim())
+            .map_err(|_| Status::new(Code::InvalidArgument, "Contract is not valid hex."))?;
+        let contract = self.inner.import_contract(&contract, overwrite).map_err(|e| {
+            match e.downcast_ref::<ContractError>() {
+                Some(ContractError::ContractExists { .. }) => Status::new(Code::AlreadyExists, e.to_string()),
+                _ => Status::new(Code::InvalidArgument, e.to_string()),
+            }
+        })?;
+        Ok(Response::new(ImportContractResponse {
+            contract_id: hex::encode(contract.get_id()),
+            state: contract_state(&contract).to_string(),
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn get_cet_list(&self, request: Request<GetCetListRequest>) -> Result<Response<GetCetListResponse>, Status> {
+        tracing::info!("Request for the CETs of a contract.");
+        let GetCetListRequest { contract_id, offset, limit } = request.into_inner();
+        let contract_id = self.resolve_contract_id(&contract_id)?;
+        let contract = self.inner.storage.get_contract(&contract_id)
+            .map_err(|e| Status::new(Code::Cancelled, e.to_string()))?
+            .ok_or_else(|| Status::new(Code::NotFound, "Contract not found."))?;
+
+        let cets = cet_list(&contract).map_err(|e| Status::new(Code::FailedPrecondition, e.to_string()))?;
+        let total = cets.len() as u32;
+        let cets = cets.into_iter()
+            .enumerate()
+            .skip(offset as usize)
+            .take(limit.map_or(usize::MAX, |limit| limit as usize))
+            .map(|(index, cet)| Cet {
+                index: index as u32,
+                txid: cet.txid.to_string(),
+                first_outcome: cet.first_outcome,
+                last_outcome: cet.last_outcome,
+                offer_payout: cet.offer_payout,
+                accept_payout: cet.accept_payout,
+            })
+            .collect();
+        Ok(Response::new(GetCetListResponse { cets, total }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn verify_funding(&self, request: Request<VerifyFundingRequest>) -> Result<Response<VerifyFundingResponse>, Status> {
+        tracing::info!("Request to verify funding output.");
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        let info = self.inner.verify_funding_output(&contract_id).map_err(|e| match e.downcast_ref::<ContractError>() {
+            Some(ContractError::FundingMismatch(_)) => Status::new(Code::DataLoss, e.to_string()),
+            _ => Status::new(Code::FailedPrecondition, e.to_string()),
+        })?;
+        Ok(Response::new(VerifyFundingResponse {
+            outpoint: info.outpoint.to_string(),
+            address: info.address.to_string(),
+            witness_script: hex::encode(info.witness_script.as_bytes()),
+            value: info.value,
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn get_contract_transactions(&self, request: Request<GetContractTransactionsRequest>) -> Result<Response<GetContractTransactionsResponse>, Status> {
+        tracing::info!("Request for contract transactions.");
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        let contract = self.inner.storage.get_contract(&contract_id)
+            .map_err(|e| Status::new(Code::Cancelled, e.to_string()))?
+            .ok_or_else(|| Status::new(Code::NotFound, "Contract not found."))?;
+
+        let txids = contract_transactions(&contract);
+        if txids.is_empty() {
+            return Err(Status::new(Code::FailedPrecondition, "Contract has no on-chain transactions yet."));
+        }
+
+        let transactions = txids.into_iter()
+            .map(|(kind, txid)| ContractTransaction {
+                kind: kind.to_string(),
+                txid: txid.to_string(),
+                // Unconfirmed transactions are reported as an error by the chain backend.
+                confirmations: self.inner.blockchain.get_transaction_confirmations(&txid).unwrap_or(0),
+            })
+            .collect();
+
+        let stored_fees = self.inner.storage.get_contract_fees(&contract_id)
+            .map_err(|e| Status::new(Code::Cancelled, e.to_string()))?;
+        let funding_target = self.inner.storage.get_funding_target(&contract_id)
+            .map_err(|e| Status::new(Code::Cancelled, e.to_string()))?;
+        let fees = contract_fees(&contract, stored_fees).map(|fees| ContractFees {
+            funding_fee: fees.funding_fee,
+            own_fee: fees.own_fee,
+            fund_output_value: fees.fund_output_value,
+            closing_fee: fees.closing_fee,
+            fee_rate_per_vb: fees.fee_rate_per_vb,
+            funding_target_blocks: funding_target.map(u32::from),
+        });
+
+        let funding_outpoint = funding_outpoint(&contract).map(|o| o.to_string()).unwrap_or_default();
+        Ok(Response::new(GetContractTransactionsResponse { funding_outpoint, transactions, fees }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn rescan(&self, request: Request<RescanRequest>) -> Result<Response<RescanResponse>, Status> {
+        tracing::info!("Request to rescan wallet.");
+        let rescan = self.inner.wallet.rescan(request.into_inner().from_height)
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        Ok(Response::new(RescanResponse {
+            transactions: rescan.transactions as u64,
+            confirmed: rescan.balance.confirmed.to_sat(),
+            unconfirmed: (rescan.balance.trusted_pending + rescan.balance.untrusted_pending).to_sat(),
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn cancel_offer(&self, request: Request<CancelOfferRequest>) -> Result<Response<CancelOfferResponse>, Status> {
+        tracing::info!("Request to cancel offer.");
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        self.inner.cancel_offer(&contract_id).await
+            .map_err(|e| Status::new(Code::FailedPrecondition, e.to_string()))?;
+        Ok(Response::new(CancelOfferResponse {}))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn fill_offer(&self, request: Request<FillOfferRequest>) -> Result<Response<FillOfferResponse>, Status> {
+        tracing::info!("Request to fill offer.");
+        let FillOfferRequest {
+            contract_id,
+            counter_party,
+            accept_collateral,
+            expiry_secs,
+        } = request.into_inner();
+        let contract_id = self.resolve_contract_id(&contract_id)?;
+        let counter_party = PublicKey::from_str(&counter_party)
+            .map_err(|_| Status::new(Code::InvalidArgument, "Invalid counterparty public key."))?;
+        let offer_msg = self
+            .inner
+            .fill_offer(&contract_id, counter_party, accept_collateral, expiry_secs.map(Duration::from_secs))
+            .await
+            .map_err(|e| Status::new(Code::FailedPrecondition, e.to_string()))?;
+
+        let offer_dlc =
+            serde_json::to_vec(&offer_msg).expect("OfferDlc could not be converted to vec.");
+        Ok(Response::new(FillOfferResponse { offer_dlc }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn open_channel(&self, request: Request<OpenChannelRequest>) -> Result<Response<ChannelResponse>, Status> {
+        tracing::info!("Request to open channel.");
+        let OpenChannelRequest { contract_input, counter_party } = request.into_inner();
+        let contract_input: ContractInput = serde_json::from_slice(&contract_input)
+            .map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?;
+        let counter_party = PublicKey::from_str(&counter_party)
+            .map_err(|_| Status::new(Code::InvalidArgument, "Invalid counterparty public key."))?;
+        let channel_id = self.inner.open_channel(&contract_input, counter_party).await.map_err(channel_error)?;
+        Ok(Response::new(ChannelResponse { channel_id: hex::encode(channel_id) }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn accept_channel(&self, request: Request<ChannelRequest>) -> Result<Response<ChannelResponse>, Status> {
+        tracing::info!("Request to accept channel.");
+        let channel_id = contract_id_from_hex(&request.into_inner().channel_id)?;
+        let channel_id = self.inner.accept_channel(&channel_id).await.map_err(channel_error)?;
+        Ok(Response::new(ChannelResponse { channel_id: hex::encode(channel_id) }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn settle_channel(&self, request: Request<SettleChannelRequest>) -> Result<Response<ChannelResponse>, Status> {
+        tracing::info!("Request to settle channel.");
+        let SettleChannelRequest { channel_id, counter_payout } = request.into_inner();
+        let id = contract_id_from_hex(&channel_id)?;
+        self.inner.settle_channel(&id, counter_payout).await.map_err(channel_error)?;
+        Ok(Response::new(ChannelResponse { channel_id }))
     }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn accept_settle_channel(&self, request: Request<ChannelRequest>) -> Result<Response<ChannelResponse>, Status> {
+        tracing::info!("Request to accept channel settlement.");
+        let channel_id = request.into_inner().channel_id;
+        self.inner.accept_settle_channel(&contract_id_from_hex(&channel_id)?).await.map_err(channel_error)?;
+        Ok(Response::new(ChannelResponse { channel_id }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn renew_channel(&self, request: Request<RenewChannelRequest>) -> Result<Response<ChannelResponse>, Status> {
+        tracing::info!("Request to renew channel.");
+        let RenewChannelRequest { channel_id, counter_payout, contract_input } = request.into_inner();
+        let contract_input: ContractInput = serde_json::from_slice(&contract_input)
+            .map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?;
+        let id = contract_id_from_hex(&channel_id)?;
+        self.inner.renew_channel(&id, counter_payout, &contract_input).await.map_err(channel_error)?;
+        Ok(Response::new(ChannelResponse { channel_id }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn accept_renew_channel(&self, request: Request<ChannelRequest>) -> Result<Response<ChannelResponse>, Status> {
+        tracing::info!("Request to accept channel renewal.");
+        let channel_id = request.into_inner().channel_id;
+        self.inner.accept_renew_channel(&contract_id_from_hex(&channel_id)?).await.map_err(channel_error)?;
+        Ok(Response::new(ChannelResponse { channel_id }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn close_channel(&self, request: Request<CloseChannelRequest>) -> Result<Response<ChannelResponse>, Status> {
+        tracing::info!("Request to close channel.");
+        let CloseChannelRequest { channel_id, counter_payout, force } = request.into_inner();
+        let id = contract_id_from_hex(&channel_id)?;
+        if force {
+            self.inner.force_close_channel(&id).await
+        } else {
+            self.inner.close_channel(&id, counter_payout).await
+        }
+        .map_err(channel_error)?;
+        Ok(Response::new(ChannelResponse { channel_id }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn accept_close_channel(&self, request: Request<ChannelRequest>) -> Result<Response<ChannelResponse>, Status> {
+        tracing::info!("Request to accept channel close.");
+        let channel_id = request.into_inner().channel_id;
+        self.inner.accept_close_channel(&contract_id_from_hex(&channel_id)?).await.map_err(channel_error)?;
+        Ok(Response::new(ChannelResponse { channel_id }))
+    }
+
+    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
+    async fn list_channels(&self, _request: Request<ListChannelsRequest>) -> Result<Response<ListChannelsResponse>, Status> {
+        let channels = self.inner.storage.list_channels()
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?
+            .iter()
+            .map(|channel| ChannelInfo {
+                channel_id: hex::encode(channel.get_id()),
+                counter_party: channel.get_counter_party_id().to_string(),
+                state: channel_state(channel),
+            })
+            .collect();
+        Ok(Response::new(ListChannelsResponse { channels }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn estimate_fee(&self, request: Request<EstimateFeeRequest>) -> Result<Response<EstimateFeeResponse>, Status> {
+        let EstimateFeeRequest { target, blocks } = request.into_inner();
+        let sats_per_vbyte = match blocks {
+            Some(blocks) => fee_rate_for_target(self.inner.wallet.as_ref(), target_blocks(blocks)?),
+            None => sats_per_vbyte(self.inner.wallet.get_est_sat_per_1000_weight(confirmation_target(&target)?)),
+        };
+        Ok(Response::new(EstimateFeeResponse { sats_per_vbyte }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn set_label(&self, request: Request<SetLabelRequest>) -> Result<Response<SetLabelResponse>, Status> {
+        let SetLabelRequest { kind, id, label } = request.into_inner();
+        self.inner.storage.save_label(&label_key(&kind, &id)?, &label)
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        Ok(Response::new(SetLabelResponse {}))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn get_label(&self, request: Request<GetLabelRequest>) -> Result<Response<GetLabelResponse>, Status> {
+        let GetLabelRequest { kind, id } = request.into_inner();
+        let label = self.inner.storage.get_label(&label_key(&kind, &id)?)
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        Ok(Response::new(GetLabelResponse { label }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn get_contract_history(&self, request: Request<GetContractHistoryRequest>) -> Result<Response<GetContractHistoryResponse>, Status> {
+        let contract_id = self.resolve_contract_id(&request.into_inner().contract_id)?;
+        let history = self.inner.storage.get_contract_history(&contract_id)
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        if history.is_empty() {
+            return Err(Status::new(Code::NotFound, "Contract has no recorded history."));
+        }
+
+        let transitions = history.into_iter()
+            .map(|transition| ContractTransition {
+                from_state: transition.from_state,
+                to_state: transition.to_state,
+                timestamp: transition.timestamp,
+                txid: transition.txid.map(|txid| txid.to_string()),
+            })
+            .collect();
+        Ok(Response::new(GetContractHistoryResponse { transitions }))
+    }
+
+    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
+    async fn show_keys(&self, _request: Request<ShowKeysRequest>) -> Result<Response<ShowKeysResponse>, Status> {
+        let npub = self.inner.wallet.nostr_npub()
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        let node_pubkey = self.lightning().node_id.to_string();
+        Ok(Response::new(ShowKeysResponse { node_pubkey, npub }))
+    }
+
+    #[tracing::instrument(skip(self, _request), name = "grpc_server")]
+    async fn export_nostr_key(&self, _request: Request<ExportNostrKeyRequest>) -> Result<Response<ExportNostrKeyResponse>, Status> {
+        tracing::warn!("Exporting the Nostr secret key.");
+        let nsec = self.inner.wallet.nostr_nsec()
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        Ok(Response::new(ExportNostrKeyResponse { nsec }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn send(&self, request: Request<SendRequest>) -> Result<Response<SendResponse>, Status> {
+        let SendRequest { address, amount, fee_rate, utxos } = request.into_inner();
+        let address = Address::from_str(&address)
+            .map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?
+            .require_network(self.inner.network())
+            .map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?;
+        let fee_rate = FeeRate::from_sat_per_vb(fee_rate)
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "Fee rate is too high."))?;
+        let txid = self
+            .inner
+            .wallet
+            .send_from_utxos(address, Amount::from_sat(amount), fee_rate, outpoints(&utxos)?)
+            .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+        Ok(Response::new(SendResponse { txid: txid.to_string() }))
+    }
+
+    #[tracing::instrument(skip(self, request), name = "grpc_server")]
+    async fn force_attestation(&self, request: Request<ForceAttestationRequest>) -> Result<Response<ForceAttestationResponse>, Status> {
+        if !self.dev_mode {
+            return Err(Status::new(Code::PermissionDenied, "Forcing attestations needs the node to run with --dev."));
+        }
+        #[cfg(feature = "dev")]
+        {
+            let ForceAttestationRequest { contract_id, attestation } = request.into_inner();
+            let contract_id = self.resolve_contract_id(&contract_id)?;
+            let attestation = serde_json::from_slice(&attestation)
+                .map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?;
+            let contract = self.inner.force_attestation(&contract_id, attestation)
+                .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+            Ok(Response::new(ForceAttestationResponse { state: contract_state(&contract).to_string() }))
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            let _ = request;
+            Err(Status::new(Code::Unimplemented, "ddk-node was built without the dev feature."))
+        }
+    }
+}
+
+/// Label key of a `tx` or `contract` id.
+fn label_key(kind: &str, id: &str) -> Result<LabelKey, Status> {
+    match kind {
+        "tx" => Txid::from_str(id)
+            .map(LabelKey::Transaction)
+            .map_err(|_| Status::new(Code::InvalidArgument, "Invalid txid.")),
+        "contract" => contract_id_from_hex(id).map(LabelKey::Contract),
+        _ => Err(Status::new(Code::InvalidArgument, format!("Unknown label kind `{}`.", kind))),
+    }
+}
+
+/// Confirmation target of a fee estimate: `urgent`, `normal`, or `background`.
+fn confirmation_target(target: &str) -> Result<ConfirmationTarget, Status> {
+    match target {
+        "urgent" => Ok(ConfirmationTarget::UrgentOnChainSweep),
+        "normal" => Ok(ConfirmationTarget::NonAnchorChannelFee),
+        "background" => Ok(ConfirmationTarget::ChannelCloseMinimum),
+        _ => Err(Status::new(Code::InvalidArgument, format!("Unknown fee target `{}`.", target))),
+    }
+}
+
+/// A funding confirmation target in blocks.
+fn target_blocks(blocks: u32) -> Result<u16, Status> {
+    match u16::try_from(blocks) {
+        Ok(blocks) if blocks > 0 => Ok(blocks),
+        _ => Err(Status::new(Code::InvalidArgument, format!("Confirmation target of {} blocks is out of range.", blocks))),
+    }
+}
+
+fn channel_error(e: anyhow::Error) -> Status {
+    Status::new(Code::FailedPrecondition, e.to_string())
 }
@@ -0,0 +1,79 @@
+//! Derives `RUST_DLC_VERSION` from the dlc-manager version Cargo resolved and
+//! `DLC_PROTOCOL_VERSION` from `[package.metadata.dlc]`, so neither is edited by hand when
+//! rust-dlc is bumped.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const RUST_DLC_PACKAGE: &str = "dlc-manager";
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR"));
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("cargo sets OUT_DIR"));
+    let manifest = fs::read_to_string(manifest_dir.join("Cargo.toml")).expect("ddk has a manifest");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+
+    // The lock file is next to the workspace, or next to the target directory of a project
+    // that depends on ddk.
+    let lock_file = manifest_dir
+        .ancestors()
+        .chain(out_dir.ancestors())
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.exists());
+    if let Some(lock_file) = &lock_file {
+        println!("cargo:rerun-if-changed={}", lock_file.display());
+    }
+    let rust_dlc_version = lock_file
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|lock| locked_version(&lock, RUST_DLC_PACKAGE))
+        .or_else(|| required_version(&manifest, RUST_DLC_PACKAGE))
+        .expect("ddk depends on dlc-manager");
+    let protocol_version = metadata_value(&manifest, "dlc", "protocol-version")
+        .and_then(|value| value.parse::<u32>().ok())
+        .expect("[package.metadata.dlc] sets protocol-version");
+
+    write_versions(&out_dir.join("dlc_versions.rs"), &rust_dlc_version, protocol_version);
+}
+
+/// The version of `package` in a Cargo.lock.
+fn locked_version(lock: &str, package: &str) -> Option<String> {
+    let name = format!("name = \"{}\"", package);
+    let mut lines = lock.lines().map(str::trim);
+    lines.find(|line| *line == name)?;
+    lines.next()?.strip_prefix("version = ").map(unquote)
+}
+
+/// The version requirement of dependency `package` in a manifest.
+fn required_version(manifest: &str, package: &str) -> Option<String> {
+    let line = manifest
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with(&format!("{} ", package)) || line.starts_with(&format!("{}=", package)))?;
+    let version = &line[line.find("version")? + "version".len()..];
+    let version = version.trim_start().strip_prefix('=')?.trim_start();
+    Some(unquote(version.split([',', '}']).next()?))
+}
+
+/// The value of `key` in the `[package.metadata.<table>]` table of a manifest.
+fn metadata_value(manifest: &str, table: &str, key: &str) -> Option<String> {
+    let header = format!("[package.metadata.{}]", table);
+    let mut lines = manifest.lines().map(str::trim);
+    lines.find(|line| *line == header)?;
+    lines
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| unquote(value))
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+fn write_versions(path: &Path, rust_dlc_version: &str, protocol_version: u32) {
+    let versions = format!(
+        "pub const RUST_DLC_VERSION: &str = \"{}\";\npub const DLC_PROTOCOL_VERSION: u32 = {};\n",
+        rust_dlc_version, protocol_version
+    );
+    fs::write(path, versions).expect("OUT_DIR is writable");
+}
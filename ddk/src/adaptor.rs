@@ -0,0 +1,398 @@
+//! Verification of CET adaptor signatures on a pool of threads.
+//!
+//! Numeric contracts carry an adaptor signature for every CET, thousands for a wide payout
+//! curve. secp256k1-zkp has no batch equation for ECDSA adaptor signatures, so
+//! [AdaptorVerifier] splits a batch across its threads and reports the same first invalid
+//! signature as checking them one by one with [verify_serial].
+//!
+//! The adaptor signatures of received accept and sign messages for enum contracts are checked
+//! on the node's verifier before [dlc_manager] processes them, see [accept_checks] and
+//! [sign_checks]. The manager still verifies those of numeric contracts one by one.
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::{Amount, Script, Transaction};
+use dlc::secp256k1_zkp::{All, EcdsaAdaptorSignature, Message, PublicKey, Secp256k1};
+use dlc::{OracleInfo, PartyParams, Payout, TxInputInfo};
+use dlc_manager::contract::accepted_contract::AcceptedContract;
+use dlc_manager::contract::contract_info::ContractInfo;
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::ContractDescriptor;
+use dlc_messages::{AcceptDlc, SignDlc};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use std::time::{Duration, Instant};
+
+/// An adaptor signature of a CET and what it is verified against.
+#[derive(Debug, Clone)]
+pub struct AdaptorCheck {
+    pub signature: EcdsaAdaptorSignature,
+    /// Sighash of the CET.
+    pub message: Message,
+    /// Funding key of the signer.
+    pub pubkey: PublicKey,
+    /// Adaptor point of the outcomes the CET pays out for.
+    pub adaptor_point: PublicKey,
+}
+
+impl AdaptorCheck {
+    fn is_valid(&self, secp: &Secp256k1<All>) -> bool {
+        self.signature
+            .verify(secp, &self.message, &self.pubkey, &self.adaptor_point)
+            .is_ok()
+    }
+}
+
+/// Index of the first invalid signature in `checks`, or `None` if all are valid.
+pub fn verify_serial(secp: &Secp256k1<All>, checks: &[AdaptorCheck]) -> Option<usize> {
+    checks.iter().position(|check| !check.is_valid(secp))
+}
+
+/// Time taken to verify a batch one by one and on an [AdaptorVerifier].
+#[derive(Debug, Clone, Copy)]
+pub struct VerificationTiming {
+    pub serial: Duration,
+    pub parallel: Duration,
+}
+
+impl VerificationTiming {
+    /// How many times faster the parallel verification was.
+    pub fn speedup(&self) -> f64 {
+        self.serial.as_secs_f64() / self.parallel.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Verifies batches of adaptor signatures on its own thread pool.
+pub struct AdaptorVerifier {
+    secp: Secp256k1<All>,
+    pool: ThreadPool,
+}
+
+impl AdaptorVerifier {
+    /// A verifier with `threads` threads. Zero uses a thread per core.
+    pub fn new(threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("ddk-adaptor-{i}"))
+            .build()?;
+        Ok(Self {
+            secp: Secp256k1::new(),
+            pool,
+        })
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Index of the first invalid signature in `checks`, or `None` if all are valid. Always
+    /// the same answer as [verify_serial].
+    pub fn verify(&self, checks: &[AdaptorCheck]) -> Option<usize> {
+        let secp = &self.secp;
+        self.pool
+            .install(|| checks.par_iter().position_first(|check| !check.is_valid(secp)))
+    }
+
+    /// Verify `checks` one by one and then on the pool, and time both.
+    pub fn measure(&self, checks: &[AdaptorCheck]) -> VerificationTiming {
+        let start = Instant::now();
+        let serial_result = verify_serial(&self.secp, checks);
+        let serial = start.elapsed();
+
+        let start = Instant::now();
+        let parallel_result = self.verify(checks);
+        let parallel = start.elapsed();
+
+        debug_assert_eq!(serial_result, parallel_result);
+        VerificationTiming { serial, parallel }
+    }
+}
+
+/// The checks of the adaptor signatures of the offer party in `sign`, against the CETs of the
+/// contract we accepted. `None` if the manager verifies them itself.
+pub(crate) fn sign_checks(
+    secp: &Secp256k1<All>,
+    accepted: &AcceptedContract,
+    sign: &SignDlc,
+) -> Result<Option<Vec<AdaptorCheck>>, dlc::Error> {
+    let transactions = &accepted.dlc_transactions;
+    let signatures = sign
+        .cet_adaptor_signatures
+        .ecdsa_adaptor_signatures
+        .iter()
+        .map(|s| s.signature)
+        .collect::<Vec<_>>();
+    enum_checks(
+        secp,
+        &accepted.offered_contract.contract_info,
+        &transactions.cets,
+        &transactions.funding_script_pubkey,
+        transactions.get_fund_output().value,
+        &accepted.offered_contract.offer_params.fund_pubkey,
+        &signatures,
+    )
+}
+
+/// The checks of the adaptor signatures of the accept party in `accept`, against the CETs of
+/// `offer` funded with the inputs of the accept. `None` if the manager verifies them itself.
+pub(crate) fn accept_checks(
+    secp: &Secp256k1<All>,
+    offer: &OfferedContract,
+    accept: &AcceptDlc,
+) -> Result<Option<Vec<AdaptorCheck>>, dlc::Error> {
+    let [info] = offer.contract_info.as_slice() else {
+        return Ok(None);
+    };
+    let ContractDescriptor::Enum(descriptor) = &info.contract_descriptor else {
+        return Ok(None);
+    };
+    let mut input_amount = 0;
+    for input in &accept.funding_inputs {
+        let Ok(prev_tx) = bitcoin::consensus::deserialize::<Transaction>(&input.prev_tx) else {
+            return Ok(None);
+        };
+        let Some(output) = prev_tx.output.get(input.prev_tx_vout as usize) else {
+            return Ok(None);
+        };
+        input_amount += output.value.to_sat();
+    }
+    let accept_params = PartyParams {
+        fund_pubkey: accept.funding_pubkey,
+        change_script_pubkey: accept.change_spk.clone(),
+        change_serial_id: accept.change_serial_id,
+        payout_script_pubkey: accept.payout_spk.clone(),
+        payout_serial_id: accept.payout_serial_id,
+        inputs: accept.funding_inputs.iter().map(TxInputInfo::from).collect(),
+        input_amount,
+        collateral: accept.accept_collateral,
+    };
+    let payouts = descriptor
+        .outcome_payouts
+        .iter()
+        .map(|outcome| outcome.payout.clone())
+        .collect::<Vec<Payout>>();
+    let transactions = dlc::create_dlc_transactions(
+        &offer.offer_params,
+        &accept_params,
+        &payouts,
+        offer.refund_locktime,
+        offer.fee_rate_per_vb,
+        offer.fund_locktime,
+        offer.cet_locktime,
+        offer.fund_output_serial_id,
+    )?;
+    let signatures = accept
+        .cet_adaptor_signatures
+        .ecdsa_adaptor_signatures
+        .iter()
+        .map(|s| s.signature)
+        .collect::<Vec<_>>();
+    enum_checks(
+        secp,
+        &offer.contract_info,
+        &transactions.cets,
+        &transactions.funding_script_pubkey,
+        transactions.get_fund_output().value,
+        &accept.funding_pubkey,
+        &signatures,
+    )
+}
+
+/// Pairs the adaptor signatures of an enum contract with the sighash of their CET and the
+/// adaptor point of their outcome, in the order rust-dlc signs them: for each set of
+/// `threshold` oracles, one signature per outcome. `None` for numeric contracts, contracts
+/// with several contract infos, or a signature count the manager will refuse anyway.
+fn enum_checks(
+    secp: &Secp256k1<All>,
+    contract_info: &[ContractInfo],
+    cets: &[Transaction],
+    funding_script: &Script,
+    fund_output_value: Amount,
+    fund_pubkey: &PublicKey,
+    signatures: &[EcdsaAdaptorSignature],
+) -> Result<Option<Vec<AdaptorCheck>>, dlc::Error> {
+    let [info] = contract_info else {
+        return Ok(None);
+    };
+    let ContractDescriptor::Enum(descriptor) = &info.contract_descriptor else {
+        return Ok(None);
+    };
+    let oracle_infos = info
+        .oracle_announcements
+        .iter()
+        .map(|announcement| OracleInfo {
+            public_key: announcement.oracle_public_key,
+            nonces: announcement.oracle_event.oracle_nonces.clone(),
+        })
+        .collect::<Vec<_>>();
+    let oracle_sets = combinations(oracle_infos.len(), info.threshold);
+    let outcomes = &descriptor.outcome_payouts;
+    if cets.len() != outcomes.len() || signatures.len() != oracle_sets.len() * outcomes.len() {
+        return Ok(None);
+    }
+
+    let sighashes = cets
+        .iter()
+        .map(|cet| dlc::util::get_sig_hash_msg(cet, 0, funding_script, fund_output_value))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut signatures = signatures.iter();
+    let mut checks = Vec::with_capacity(signatures.len());
+    for oracle_set in oracle_sets {
+        let oracles = oracle_set.iter().map(|i| oracle_infos[*i].clone()).collect::<Vec<_>>();
+        for (cet, outcome) in outcomes.iter().enumerate() {
+            let message = Message::from_digest(sha256::Hash::hash(outcome.outcome.as_bytes()).to_byte_array());
+            let adaptor_point =
+                dlc::get_adaptor_point_from_oracle_info(secp, &oracles, &vec![vec![message]; oracles.len()])?;
+            checks.push(AdaptorCheck {
+                signature: *signatures.next().expect("one signature per outcome and oracle set"),
+                message: sighashes[cet],
+                pubkey: *fund_pubkey,
+                adaptor_point,
+            });
+        }
+    }
+    Ok(Some(checks))
+}
+
+/// The `size` element subsets of `0..n` in lexicographic order, the order rust-dlc goes
+/// through the oracle sets of a threshold contract in.
+fn combinations(n: usize, size: usize) -> Vec<Vec<usize>> {
+    if size == 0 || size > n {
+        return Vec::new();
+    }
+    let mut current = (0..size).collect::<Vec<_>>();
+    let mut all = vec![current.clone()];
+    while let Some(i) = (0..size).rev().find(|&i| current[i] < n - size + i) {
+        current[i] += 1;
+        for j in i + 1..size {
+            current[j] = current[j - 1] + 1;
+        }
+        all.push(current.clone());
+    }
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+    use dlc::secp256k1_zkp::SecretKey;
+
+    fn random_bytes() -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        rng::fill_bytes(&mut bytes);
+        bytes
+    }
+
+    fn random_key() -> SecretKey {
+        SecretKey::from_slice(&random_bytes()).unwrap()
+    }
+
+    /// The adaptor signatures of a contract with `cets` CETs, each invalid with a one in
+    /// `invalid_one_in` chance.
+    fn random_contract(secp: &Secp256k1<All>, cets: usize, invalid_one_in: u8) -> Vec<AdaptorCheck> {
+        let funding_key = random_key();
+        let pubkey = funding_key.public_key(secp);
+        (0..cets)
+            .map(|_| {
+                let message = Message::from_digest(random_bytes());
+                let adaptor_point = random_key().public_key(secp);
+                let signature = EcdsaAdaptorSignature::encrypt(secp, &message, &funding_key, &adaptor_point);
+                let invalid = invalid_one_in > 0 && random_bytes()[0] % invalid_one_in == 0;
+                AdaptorCheck {
+                    signature,
+                    // A signature for another CET does not verify.
+                    message: if invalid { Message::from_digest(random_bytes()) } else { message },
+                    pubkey,
+                    adaptor_point,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_verification_matches_serial() {
+        let _seeded = rng::SEED_LOCK.lock().unwrap();
+        rng::set_seed(153);
+        let secp = Secp256k1::new();
+        let verifier = AdaptorVerifier::new(4).unwrap();
+        assert_eq!(verifier.threads(), 4);
+
+        for invalid_one_in in [0, 2, 50, 255] {
+            for _ in 0..5 {
+                let checks = random_contract(&secp, 200, invalid_one_in);
+                assert_eq!(verifier.verify(&checks), verify_serial(&secp, &checks));
+            }
+        }
+        assert_eq!(verifier.verify(&[]), None);
+
+        let checks = random_contract(&secp, 2_000, 0);
+        let timing = verifier.measure(&checks);
+        // Four threads only beat one on a machine with the cores to run them.
+        if std::thread::available_parallelism().is_ok_and(|cores| cores.get() >= 4) {
+            assert!(timing.speedup() > 1.0, "no speedup: {:?}", timing);
+        }
+    }
+
+    #[test]
+    fn oracle_sets_are_in_lexicographic_order() {
+        assert_eq!(combinations(3, 2), vec![vec![0, 1], vec![0, 2], vec![1, 2]]);
+        assert_eq!(combinations(1, 1), vec![vec![0]]);
+        assert_eq!(combinations(4, 4).len(), 1);
+        assert!(combinations(2, 3).is_empty());
+    }
+
+    #[test]
+    fn sign_signatures_are_checked_against_the_accepted_cets() {
+        let secp = Secp256k1::new();
+        let mut signed = crate::test_util::signed_contract();
+        let accepted = &mut signed.accepted_contract;
+        let cets = accepted.dlc_transactions.cets.clone();
+
+        // An enum contract with an outcome per CET, attested by the first oracle.
+        let info = &mut accepted.offered_contract.contract_info[0];
+        info.oracle_announcements.truncate(1);
+        info.threshold = 1;
+        info.contract_descriptor = ContractDescriptor::Enum(dlc_manager::contract::enum_descriptor::EnumDescriptor {
+            outcome_payouts: (0..cets.len())
+                .map(|i| dlc::EnumerationPayout {
+                    outcome: format!("outcome-{i}"),
+                    payout: Payout { offer: 0, accept: 0 },
+                })
+                .collect(),
+        });
+        let announcement = &info.oracle_announcements[0];
+        let oracle = OracleInfo {
+            public_key: announcement.oracle_public_key,
+            nonces: announcement.oracle_event.oracle_nonces.clone(),
+        };
+        let funding_key = random_key();
+        accepted.offered_contract.offer_params.fund_pubkey = funding_key.public_key(&secp);
+
+        let transactions = &accepted.dlc_transactions;
+        let fund_value = transactions.get_fund_output().value;
+        let mut signatures = cets
+            .iter()
+            .enumerate()
+            .map(|(i, cet)| {
+                let sighash =
+                    dlc::util::get_sig_hash_msg(cet, 0, &transactions.funding_script_pubkey, fund_value).unwrap();
+                let outcome = Message::from_digest(sha256::Hash::hash(format!("outcome-{i}").as_bytes()).to_byte_array());
+                let point = dlc::get_adaptor_point_from_oracle_info(&secp, &[oracle.clone()], &[vec![outcome]]).unwrap();
+                EcdsaAdaptorSignature::encrypt(&secp, &sighash, &funding_key, &point)
+            })
+            .collect::<Vec<_>>();
+
+        let verifier = AdaptorVerifier::new(2).unwrap();
+        let sign = signed.get_sign_dlc(signatures.clone());
+        let checks = sign_checks(&secp, &signed.accepted_contract, &sign).unwrap().unwrap();
+        assert_eq!(checks.len(), cets.len());
+        assert_eq!(verifier.verify(&checks), None);
+
+        // A signature for another outcome's CET is refused.
+        if signatures.len() > 1 {
+            signatures.swap(0, 1);
+            let sign = signed.get_sign_dlc(signatures);
+            let checks = sign_checks(&secp, &signed.accepted_contract, &sign).unwrap().unwrap();
+            assert_eq!(verifier.verify(&checks), Some(0));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::adaptor::AdaptorVerifier;
 use crate::io;
 use core::fmt;
 use crossbeam::channel::unbounded;
@@ -5,24 +6,58 @@ use dlc_manager::manager::Manager;
 use dlc_manager::SystemTimeProvider;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::{Address, Network};
+use reqwest::Url;
 
-use crate::chain::EsploraClient;
-use crate::config::DdkConfig;
-use crate::ddk::{DlcDevKit, DlcManagerMessage};
-use crate::wallet::DlcDevKitWallet;
-use crate::{DdkOracle, DdkStorage, DdkTransport};
+use crate::chain::{ChainMonitor, EsploraClient, EsploraMonitor};
+use crate::config::{default_esplora_host, DdkConfig, SeedConfig};
+use crate::delivery::{DeliveryRetries, RetryPolicy};
+use crate::policy::{settling_contract, SettlementDelay, SettlementTimer, SpendingPolicy};
+use crate::ddk::{CheckSchedule, DlcDevKit, DlcManagerMessage};
+use crate::wallet::{fee_rate_for_target, AddressReusePolicy, DlcDevKitWallet, WalletBirthday, WalletDescriptors, WalletKeys};
+use crate::{DdkEventHandler, DdkOracle, DdkStorage, DdkTransport, DEFAULT_MIN_CONFIRMATIONS};
 
 /// Builder pattern for creating a [crate::ddk::DlcDevKit] process.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct DdkBuilder<T, S, O> {
     name: Option<String>,
     config: Option<DdkConfig>,
     transport: Option<Arc<T>>,
+    transports: Vec<Arc<T>>,
     storage: Option<Arc<S>>,
     oracle: Option<Arc<O>>,
     wallet_storage: Option<S>,
+    event_handler: Option<Arc<dyn DdkEventHandler>>,
+    webhook_url: Option<Url>,
+    min_confirmations: Option<u32>,
+    wallet_birthday: Option<WalletBirthday>,
+    wallet_passphrase: Option<String>,
+    wallet_descriptors: Option<WalletDescriptors>,
+    watch_only: Option<PublicKey>,
+    payout_whitelist: Vec<Address>,
+    address_reuse: AddressReusePolicy,
+    esplora_url: Option<String>,
+    broadcast_fallbacks: Vec<Url>,
+    allow_mainnet: bool,
+    check_schedule: CheckSchedule,
+    max_active_contracts: Option<usize>,
+    max_contract_collateral: Option<u64>,
+    deterministic_serial_ids: bool,
+    require_signed_offers: bool,
+    adaptor_threads: usize,
+    message_retry: RetryPolicy,
+    chain_monitor: Option<Arc<dyn ChainMonitor>>,
+    spending_policy: Option<Arc<dyn SpendingPolicy>>,
+    settlement_delay: Option<SettlementDelay>,
+    #[cfg(any(test, feature = "deterministic-rng"))]
+    rng_seed: Option<u64>,
 }
 
+/// Environment variable that allows mainnet when set to `1`. See [DdkBuilder::allow_mainnet].
+pub const ALLOW_MAINNET_ENV: &str = "DDK_ALLOW_MAINNET";
+
 /// An error that could be thrown while building [crate::ddk::DlcDevKit]
 #[derive(Debug, Clone, Copy)]
 pub enum BuilderError {
@@ -38,6 +73,8 @@ pub enum BuilderError {
     NoConfig,
     /// No wallet storage provided.
     NoWalletStorage,
+    /// Mainnet was selected without allowing it.
+    MainnetNotAllowed,
 }
 
 impl fmt::Display for BuilderError {
@@ -49,6 +86,11 @@ impl fmt::Display for BuilderError {
             BuilderError::NoSeed => write!(f, "No seed configuration was provided."),
             BuilderError::NoConfig => write!(f, "No config was provided"),
             BuilderError::NoWalletStorage => write!(f, "No wallet storage was provided."),
+            BuilderError::MainnetNotAllowed => write!(
+                f,
+                "Mainnet uses real funds. Call allow_mainnet(true) or set {}=1 to run on mainnet.",
+                ALLOW_MAINNET_ENV
+            ),
         }
     }
 }
@@ -65,9 +107,34 @@ impl<T: DdkTransport, S: DdkStorage, O: DdkOracle> Default for DdkBuilder<T, S,
             name: None,
             config,
             transport: None,
+            transports: Vec::new(),
             storage: None,
             oracle: None,
             wallet_storage: None,
+            event_handler: None,
+            webhook_url: None,
+            min_confirmations: None,
+            wallet_birthday: None,
+            wallet_passphrase: None,
+            wallet_descriptors: None,
+            watch_only: None,
+            payout_whitelist: Vec::new(),
+            address_reuse: AddressReusePolicy::NeverReuse,
+            esplora_url: None,
+            broadcast_fallbacks: Vec::new(),
+            allow_mainnet: false,
+            check_schedule: CheckSchedule::default(),
+            max_active_contracts: None,
+            max_contract_collateral: None,
+            deterministic_serial_ids: false,
+            require_signed_offers: false,
+            adaptor_threads: 0,
+            message_retry: RetryPolicy::default(),
+            chain_monitor: None,
+            spending_policy: None,
+            settlement_delay: None,
+            #[cfg(any(test, feature = "deterministic-rng"))]
+            rng_seed: None,
         }
     }
 }
@@ -95,6 +162,15 @@ impl<T: DdkTransport, S: DdkStorage, O: DdkOracle> DdkBuilder<T, S, O> {
         self
     }
 
+    /// Register an additional transport. DDK listens on every registered transport and
+    /// responds to a message over the transport it arrived on. If no transport is set with
+    /// [DdkBuilder::set_transport], the first added transport is the primary transport. Use
+    /// [crate::transport::AnyTransport] to register transports of different kinds.
+    pub fn add_transport(&mut self, transport: Arc<T>) -> &mut Self {
+        self.transports.push(transport);
+        self
+    }
+
     /// DLC contract storage. Storage is used by the [dlc_manager::manager::Manager] to create, update, retrieve, and
     /// delete contracts. MUST implement [crate::DdkStorage]
     pub fn set_storage(&mut self, storage: Arc<S>) -> &mut Self {
@@ -118,18 +194,223 @@ impl<T: DdkTransport, S: DdkStorage, O: DdkOracle> DdkBuilder<T, S, O> {
         self
     }
 
+    /// Callbacks for contract state transitions. See [crate::DdkEventHandler].
+    pub fn set_event_handler(&mut self, event_handler: Arc<dyn DdkEventHandler>) -> &mut Self {
+        self.event_handler = Some(event_handler);
+        self
+    }
+
+    /// POST contract events as JSON to `url`, with retries and a timeout. Runs alongside the
+    /// event handler. See [crate::webhook].
+    pub fn set_webhook_url(&mut self, url: Url) -> &mut Self {
+        self.webhook_url = Some(url);
+        self
+    }
+
+    /// Source of confirmations for the funding transactions of contracts. Defaults to an
+    /// [EsploraMonitor] on the esplora host.
+    pub fn set_chain_monitor(&mut self, chain_monitor: Arc<dyn ChainMonitor>) -> &mut Self {
+        self.chain_monitor = Some(chain_monitor);
+        self
+    }
+
+    /// Confirmations a utxo needs before it is used to fund a DLC or for a send. Coinbase
+    /// outputs always need 100 confirmations. Defaults to [crate::DEFAULT_MIN_CONFIRMATIONS].
+    pub fn set_min_confirmations(&mut self, min_confirmations: u32) -> &mut Self {
+        self.min_confirmations = Some(min_confirmations);
+        self
+    }
+
+    /// Where the wallet's history starts. Sync and rescans start at the birthday, so a
+    /// restored wallet does not scan from genesis. Stored with the wallet. A wallet from a
+    /// newly generated seed is born now; a restored wallet without a birthday scans from
+    /// genesis.
+    pub fn set_wallet_birthday(&mut self, birthday: WalletBirthday) -> &mut Self {
+        self.wallet_birthday = Some(birthday);
+        self
+    }
+
+    /// Encrypt the wallet database at rest with a key derived from `passphrase`. An existing
+    /// wallet must be opened with the passphrase it was created with. Existing plaintext
+    /// wallets stay plaintext and are opened without one.
+    pub fn set_wallet_passphrase(&mut self, passphrase: &str) -> &mut Self {
+        self.wallet_passphrase = Some(passphrase.to_string());
+        self
+    }
+
+    /// Output descriptors for the on-chain wallet instead of the BIP84 descriptors derived
+    /// from the seed, for example with a specific key origin. Both must be for the network of
+    /// the config, and an existing wallet must be opened with the descriptors it was created
+    /// with. Contract keys are still derived from the seed.
+    pub fn set_descriptors(&mut self, external: String, internal: String) -> &mut Self {
+        self.wallet_descriptors = Some(WalletDescriptors { external, internal });
+        self
+    }
+
+    /// Build a node without signing keys. The wallet imports `public_descriptors`, which must
+    /// not hold private keys, and tracks the contracts of `node_pubkey`. The node syncs, shows
+    /// balances and tracks contracts, and anything that signs fails with
+    /// [crate::error::WalletError::WatchOnly]. The seed of the config is not loaded and
+    /// nothing is broadcast, funding rebroadcasts included. Pair it with
+    /// [crate::transport::lightning::LightningTransport::watch_only].
+    pub fn watch_only(
+        &mut self,
+        public_descriptors: WalletDescriptors,
+        node_pubkey: PublicKey,
+    ) -> &mut Self {
+        self.wallet_descriptors = Some(public_descriptors);
+        self.watch_only = Some(node_pubkey);
+        self
+    }
+
+    /// Addresses contract payouts are sent to in turn, such as cold storage, instead of new
+    /// wallet addresses. Addresses for another network fail the build. An empty whitelist
+    /// keeps paying out to the wallet.
+    pub fn set_payout_whitelist(&mut self, whitelist: Vec<Address>) -> &mut Self {
+        self.payout_whitelist = whitelist;
+        self
+    }
+
+    /// Whether contracts that were rejected or failed before funding give their payout and
+    /// change addresses to the next contract. Defaults to [AddressReusePolicy::NeverReuse].
+    pub fn set_address_reuse_policy(&mut self, policy: AddressReusePolicy) -> &mut Self {
+        self.address_reuse = policy;
+        self
+    }
+
+    /// Esplora API to use. Overrides the host in the `DdkConfig`. If neither is set, a
+    /// public host for the network is used. See [crate::config::default_esplora_host].
+    pub fn set_esplora_url(&mut self, esplora_url: &str) -> &mut Self {
+        self.esplora_url = Some(esplora_url.into());
+        self
+    }
+
+    /// Esplora APIs to broadcast funding transactions and CETs to, in order, when the esplora
+    /// host does not accept them. Broadcasting succeeds as soon as one host accepts.
+    pub fn set_broadcast_fallbacks(&mut self, fallbacks: Vec<Url>) -> &mut Self {
+        self.broadcast_fallbacks = fallbacks;
+        self
+    }
+
+    /// Policy asked to approve every transaction before it is broadcast: wallet sends,
+    /// funding transactions, CETs and refunds. Denied transactions fail with
+    /// [crate::error::WalletError::PolicyRejected]. Everything is approved by default.
+    pub fn set_spending_policy(&mut self, spending_policy: Arc<dyn SpendingPolicy>) -> &mut Self {
+        self.spending_policy = Some(spending_policy);
+        self
+    }
+
+    /// Hold CETs back for up to the delay while the next block fee rate is above its rate. A
+    /// held CET is tried again on each periodic check and is broadcast regardless of fees once
+    /// the delay ends or the refund locktime is near. Contracts can override it with
+    /// [crate::DlcDevKit::set_settlement_delay]. CETs are broadcast at once by default.
+    pub fn set_settlement_delay(&mut self, delay: SettlementDelay) -> &mut Self {
+        self.settlement_delay = Some(delay);
+        self
+    }
+
+    /// Time between periodic checks of contracts for confirmations, attestations and expired
+    /// offers. Defaults to [crate::DEFAULT_CHECK_INTERVAL_SECS]. Checks are skipped while no
+    /// contract is pending.
+    pub fn set_check_interval(&mut self, interval: Duration) -> &mut Self {
+        self.check_schedule.interval = interval;
+        self
+    }
+
+    /// Percent the check interval is randomly moved either way, so many nodes do not query
+    /// shared esplora and oracle hosts at once. Capped at 100. Defaults to
+    /// [crate::DEFAULT_CHECK_JITTER_PERCENT].
+    pub fn set_check_jitter(&mut self, jitter_percent: u8) -> &mut Self {
+        self.check_schedule.jitter_percent = jitter_percent.min(100);
+        self
+    }
+
+    /// Most contracts that can be active at once: our own offers and contracts accepted to
+    /// confirmed. Offers we received are not counted. Offers and accepts beyond it fail with
+    /// [crate::error::ContractError::ContractLimitReached]. Unlimited by default.
+    pub fn set_max_active_contracts(&mut self, max_active_contracts: usize) -> &mut Self {
+        self.max_active_contracts = Some(max_active_contracts);
+        self
+    }
+
+    /// Most collateral, in sats, this node commits to a single contract, whatever the wallet
+    /// balance. Offers and accepts over it fail with
+    /// [crate::error::ContractError::CollateralCapExceeded] before any coins are selected.
+    /// Uncapped by default.
+    pub fn set_max_contract_collateral(&mut self, max_contract_collateral: u64) -> &mut Self {
+        self.max_contract_collateral = Some(max_contract_collateral);
+        self
+    }
+
+    /// Derive the funding transaction serial ids of our offers from the seed and the temporary
+    /// contract id instead of drawing them at random. The same offer then always builds the
+    /// same funding transaction, and outside observers still cannot predict the ids. Serial
+    /// ids of accepts are still random. Off by default.
+    pub fn set_deterministic_serial_ids(&mut self, deterministic: bool) -> &mut Self {
+        self.deterministic_serial_ids = deterministic;
+        self
+    }
+
+    /// Refuse received offers that do not carry a valid signature by the node key of the
+    /// counterparty, such as offers from transports that do not send one. See
+    /// [crate::util::sign_offer]. Imported offers are taken in either way. Off by default.
+    pub fn set_require_signed_offers(&mut self, require: bool) -> &mut Self {
+        self.require_signed_offers = require;
+        self
+    }
+
+    /// Threads that verify the CET adaptor signatures of received accept and sign messages.
+    /// See [crate::adaptor]. Defaults to a thread per core.
+    pub fn set_adaptor_threads(&mut self, threads: usize) -> &mut Self {
+        self.adaptor_threads = threads;
+        self
+    }
+
+    /// How often and how long a message the counterparty does not answer is sent again
+    /// before it is dead-lettered. See [crate::delivery]. Defaults to [RetryPolicy::default].
+    pub fn set_message_retry(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.message_retry = policy;
+        self
+    }
+
     /// Configuration for `DlcDevKit`. Storage dir, seed config, network, and esplora host.
     pub fn set_config(&mut self, config: DdkConfig) -> &mut Self {
         self.config = Some(config);
         self
     }
 
+    /// Allow building on [bitcoin::Network::Bitcoin]. Mainnet is refused unless this is set or
+    /// the [ALLOW_MAINNET_ENV] environment variable is `1`, so real funds are never used by
+    /// accident.
+    pub fn allow_mainnet(&mut self, allow: bool) -> &mut Self {
+        self.allow_mainnet = allow;
+        self
+    }
+
+    /// Seed the rng for values DDK generates itself: the default node name and the delays
+    /// between periodic checks. Temporary contract ids and serial ids come from the dlc
+    /// manager's rng and stay random, see [crate::rng]. Keys and seeds are always generated
+    /// from the system rng. Only available with the `deterministic-rng` feature.
+    #[cfg(any(test, feature = "deterministic-rng"))]
+    pub fn set_rng_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
     /// Builds the `DlcDevKit` instance. Fails if any components are missing.
     pub fn finish(&self) -> anyhow::Result<DlcDevKit<T, S, O>> {
+        #[cfg(any(test, feature = "deterministic-rng"))]
+        if let Some(seed) = self.rng_seed {
+            crate::rng::set_seed(seed);
+        }
+
         let config = self
             .config
             .as_ref()
             .map_or_else(|| Err(BuilderError::NoConfig), |c| Ok(c))?;
+        if config.network == Network::Bitcoin && !self.allow_mainnet && !mainnet_allowed_by_env() {
+            return Err(BuilderError::MainnetNotAllowed.into());
+        }
         tracing::info!("Using network {}", config.network);
 
         // Creates the DDK directory.
@@ -139,17 +420,53 @@ impl<T: DdkTransport, S: DdkStorage, O: DdkOracle> DdkBuilder<T, S, O> {
         std::fs::create_dir_all(&config.storage_path)?;
         tracing::info!(path=?config.storage_path, "Created directory for ddk node.");
 
-        let xprv = io::xprv_from_config(&config.seed_config, config.network)?;
-        tracing::info!(
-            strategy = config.seed_config.to_string(),
-            "Loaded private key"
-        );
+        let esplora_host = self
+            .esplora_url
+            .clone()
+            .or_else(|| config.esplora_host.clone())
+            .unwrap_or_else(|| default_esplora_host(config.network).to_string());
+
+        // A seed generated by this build has no history, so its wallet is born now. Any other
+        // seed may be a restore and keeps the configured birthday, or genesis.
+        let new_seed = self.watch_only.is_none()
+            && matches!(&config.seed_config, SeedConfig::File(path) if !std::path::Path::new(&format!("{path}/seed.ddk")).exists());
+        let wallet_birthday = self.wallet_birthday.or_else(|| {
+            new_seed.then(|| {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                WalletBirthday::Date(now)
+            })
+        });
+
+        let keys = match self.watch_only {
+            Some(node_pubkey) => {
+                tracing::info!(pubkey = node_pubkey.to_string(), "Building a watch-only node.");
+                WalletKeys::WatchOnly { node_pubkey }
+            }
+            None => {
+                let xprv = io::xprv_from_config(&config.seed_config, config.network)?;
+                tracing::info!(
+                    strategy = config.seed_config.to_string(),
+                    "Loaded private key"
+                );
+                WalletKeys::Seed(xprv)
+            }
+        };
 
         let transport = self
             .transport
             .as_ref()
+            .or_else(|| self.transports.first())
             .map_or_else(|| Err(BuilderError::NoTransport), |t| Ok(t.clone()))?;
 
+        let mut transports = vec![transport.clone()];
+        for t in &self.transports {
+            if !transports.iter().any(|existing| Arc::ptr_eq(existing, t)) {
+                transports.push(t.clone());
+            }
+        }
+
         let storage = self
             .storage
             .as_ref()
@@ -163,24 +480,78 @@ impl<T: DdkTransport, S: DdkStorage, O: DdkOracle> DdkBuilder<T, S, O> {
         let name = self
             .name
             .clone()
-            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            .unwrap_or_else(|| crate::rng::uuid().to_string());
 
-        let wallet = Arc::new(DlcDevKitWallet::new(
+        let mut wallet = DlcDevKitWallet::new(
             &name,
-            xprv,
-            &config.esplora_host,
+            keys,
+            &esplora_host,
             config.network,
             &config.storage_path,
             storage.clone(),
-        )?);
+            self.min_confirmations.unwrap_or(DEFAULT_MIN_CONFIRMATIONS),
+            wallet_birthday,
+            self.wallet_passphrase.as_deref(),
+            self.wallet_descriptors.clone(),
+        )?;
+        wallet.set_payout_whitelist(self.payout_whitelist.clone())?;
+        wallet.set_address_reuse_policy(self.address_reuse);
+        let wallet = Arc::new(wallet);
         tracing::info!("Opened BDK wallet. name={}", name);
 
         let mut oracles = HashMap::new();
         oracles.insert(oracle.get_public_key(), oracle.clone());
         tracing::info!(name = oracle.name(), "Connected to oracle.");
 
-        let esplora_client = Arc::new(EsploraClient::new(&config.esplora_host, config.network)?);
-        tracing::info!(host = config.esplora_host, "Connected to esplora client.");
+        let mut esplora_client = EsploraClient::new(&esplora_host, config.network)?
+            .with_broadcast_fallbacks(&self.broadcast_fallbacks);
+        if self.watch_only.is_some() {
+            esplora_client = esplora_client.with_broadcasts_disabled();
+        }
+        let esplora_client = Arc::new(esplora_client);
+        tracing::info!(
+            host = esplora_host,
+            fallbacks = self.broadcast_fallbacks.len(),
+            "Connected to esplora client."
+        );
+
+        if let Some(policy) = &self.spending_policy {
+            esplora_client.set_spending_policy(policy.clone());
+            wallet.blockchain.set_spending_policy(policy.clone());
+            let contracts = storage.clone();
+            esplora_client.set_classifier(Arc::new(move |tx| {
+                let contracts = dlc_manager::Storage::get_contracts(&*contracts).unwrap_or_default();
+                crate::policy::tx_purpose(tx, &contracts)
+            }));
+            tracing::info!("Using spending policy.");
+        }
+
+        let default_delay = self.settlement_delay;
+        let contracts = storage.clone();
+        let fees = wallet.clone();
+        let timer = SettlementTimer::default();
+        esplora_client.set_settlement_check(Arc::new(move |tx| {
+            let stored = dlc_manager::Storage::get_contracts(&*contracts).unwrap_or_default();
+            let Some(contract) = settling_contract(tx, &stored) else {
+                return false;
+            };
+            let contract_id = contract.accepted_contract.get_contract_id();
+            let delay = contracts.get_settlement_delay(&contract_id).ok().flatten().or(default_delay);
+            let Some(delay) = delay else {
+                return false;
+            };
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            let refund_locktime = contract.accepted_contract.offered_contract.refund_locktime;
+            timer.hold(tx.compute_txid(), &delay, refund_locktime, fee_rate_for_target(&*fees, 1), now)
+        }));
+
+        let chain_monitor = self
+            .chain_monitor
+            .clone()
+            .unwrap_or_else(|| Arc::new(EsploraMonitor::new(esplora_client.clone())));
+        tracing::info!(name = chain_monitor.name(), "Using chain monitor.");
 
         let (sender, receiver) = unbounded::<DlcManagerMessage>();
 
@@ -199,12 +570,114 @@ impl<T: DdkTransport, S: DdkStorage, O: DdkOracle> DdkBuilder<T, S, O> {
             runtime: Arc::new(RwLock::new(None)),
             wallet,
             manager,
+            blockchain: esplora_client,
             sender: Arc::new(sender),
             receiver: Arc::new(receiver),
             transport,
+            transports,
+            peer_transports: Arc::new(RwLock::new(HashMap::new())),
             storage,
             oracle,
             network: config.network,
+            event_handler: self.event_handler.clone(),
+            webhook_url: self.webhook_url.clone(),
+            check_schedule: self.check_schedule,
+            max_active_contracts: self.max_active_contracts,
+            max_contract_collateral: self.max_contract_collateral,
+            deterministic_serial_ids: self.deterministic_serial_ids,
+            require_signed_offers: self.require_signed_offers,
+            adaptor_verifier: Arc::new(AdaptorVerifier::new(self.adaptor_threads)?),
+            delivery_retries: Arc::new(DeliveryRetries::new(self.message_retry)),
+            chain_monitor,
         })
     }
 }
+
+/// Whether [ALLOW_MAINNET_ENV] is set to `1`.
+pub fn mainnet_allowed_by_env() -> bool {
+    std::env::var(ALLOW_MAINNET_ENV).map_or(false, |v| v == "1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::P2PDOracleClient;
+    use crate::storage::SledStorageProvider;
+    use crate::test_util::MockTransport;
+    use crate::config::SeedConfig;
+    use crate::error::WalletError;
+    use crate::transport::lightning::LightningTransport;
+
+    #[test]
+    fn mainnet_requires_opt_in() {
+        if mainnet_allowed_by_env() {
+            return;
+        }
+        let mut config = DdkConfig::default();
+        config.network = Network::Bitcoin;
+        config.storage_path = "tests/data/mainnet_requires_opt_in".into();
+
+        let mut builder: DdkBuilder<MockTransport, SledStorageProvider, P2PDOracleClient> =
+            DdkBuilder::new();
+        builder.set_config(config);
+        let err = builder.finish().err().expect("mainnet without opt-in");
+        assert!(matches!(
+            err.downcast_ref::<BuilderError>(),
+            Some(BuilderError::MainnetNotAllowed)
+        ));
+        // Refused before anything is written.
+        assert!(!std::path::Path::new("tests/data/mainnet_requires_opt_in").exists());
+
+        // Opting in gets past the guard to the missing components.
+        builder.allow_mainnet(true);
+        let err = builder.finish().err().expect("no transport");
+        assert!(!matches!(
+            err.downcast_ref::<BuilderError>(),
+            Some(BuilderError::MainnetNotAllowed)
+        ));
+        let _ = std::fs::remove_dir_all("tests/data/mainnet_requires_opt_in");
+    }
+
+    #[test]
+    fn watch_only_node_creates_no_seed_file() {
+        let path = "tests/data/watch_only_node_creates_no_seed_file";
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let xprv = bitcoin::bip32::Xpriv::new_master(Network::Regtest, &[12u8; 32]).unwrap();
+        let xpub = bitcoin::bip32::Xpub::from_priv(&secp, &xprv);
+        let descriptors = WalletDescriptors {
+            external: format!("wpkh({xpub}/0/*)"),
+            internal: format!("wpkh({xpub}/1/*)"),
+        };
+
+        let transport = Arc::new(LightningTransport::watch_only(xpub.public_key, 0).unwrap());
+        assert_eq!(transport.node_id, xpub.public_key);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let send = runtime.block_on(transport.send_message(xpub.public_key, dlc_messages::Message::Reject(
+            dlc_messages::channel::Reject { channel_id: [0u8; 32], timestamp: 0 },
+        )));
+        assert!(matches!(send.unwrap_err().downcast_ref(), Some(WalletError::WatchOnly)));
+
+        let mut config = DdkConfig::default();
+        config.network = Network::Regtest;
+        config.storage_path = path.into();
+        config.seed_config = SeedConfig::File(path.to_string());
+        let storage = Arc::new(SledStorageProvider::new(&format!("{path}/sled_db")).unwrap());
+        let mut builder: DdkBuilder<LightningTransport, SledStorageProvider, P2PDOracleClient> =
+            DdkBuilder::new();
+        builder.set_config(config);
+        builder.set_transport(transport);
+        builder.set_storage(storage);
+        builder.watch_only(descriptors, xpub.public_key);
+        // Fails on the missing oracle, after the keys would have been loaded.
+        let err = builder.finish().err().expect("no oracle");
+        assert!(matches!(err.downcast_ref::<BuilderError>(), Some(BuilderError::NoOracle)));
+        let seed_file = std::path::Path::new(path).join("seed.ddk");
+        assert!(!seed_file.exists());
+
+        // A node with keys creates its seed at the same point.
+        builder.watch_only = None;
+        assert!(builder.finish().is_err());
+        assert!(seed_file.exists());
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -1,27 +1,155 @@
-use crate::error::esplora_err_to_manager_err;
+use crate::error::{esplora_err_to_manager_err, WalletError};
+use crate::policy::{ApproveAll, Classifier, Decision, SettlementCheck, SpendingPolicy, TxPurpose};
 use bdk_esplora::esplora_client::Error as EsploraError;
 use bdk_esplora::esplora_client::{AsyncClient, BlockingClient, Builder};
 use bitcoin::Network;
 use bitcoin::{Transaction, Txid};
 use dlc_manager::error::Error as ManagerError;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
 
 pub struct EsploraClient {
     pub blocking_client: BlockingClient,
     pub async_client: AsyncClient,
+    host: String,
     network: Network,
+    broadcast_fallbacks: Vec<BlockingClient>,
+    spending_policy: RwLock<Arc<dyn SpendingPolicy>>,
+    classifier: RwLock<Option<Classifier>>,
+    settlement_check: RwLock<Option<SettlementCheck>>,
+    /// Refuse every broadcast. See [EsploraClient::with_broadcasts_disabled].
+    broadcasts_disabled: bool,
+    /// Confirmations, counting the block the transaction is in, of the transactions a chain
+    /// monitor watches. See [EsploraClient::watch_confirmations].
+    monitored: Mutex<HashMap<Txid, u32>>,
 }
 
 impl EsploraClient {
-    pub fn new(esplora_host: &str, network: Network) -> anyhow::Result<EsploraClient> {
+    pub fn new(esplora_host: &str, network: Network) -> Result<EsploraClient, EsploraError> {
         let builder = Builder::new(esplora_host);
         let blocking_client = builder.clone().build_blocking();
         let async_client = builder.build_async()?;
         Ok(EsploraClient {
             blocking_client,
             async_client,
+            host: esplora_host.to_string(),
             network,
+            broadcast_fallbacks: Vec::new(),
+            spending_policy: RwLock::new(Arc::new(ApproveAll)),
+            classifier: RwLock::new(None),
+            settlement_check: RwLock::new(None),
+            broadcasts_disabled: false,
+            monitored: Mutex::new(HashMap::new()),
         })
     }
+
+    /// The primary esplora host.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Esplora APIs to broadcast to, in order, when the primary host does not accept a
+    /// transaction.
+    pub fn with_broadcast_fallbacks(mut self, fallbacks: &[Url]) -> Self {
+        self.broadcast_fallbacks = fallbacks
+            .iter()
+            .map(|url| Builder::new(url.as_str().trim_end_matches('/')).build_blocking())
+            .collect();
+        self
+    }
+
+    /// Refuse every broadcast with [WalletError::WatchOnly], for a watch-only node. Its
+    /// funding transactions and CETs are broadcast by the node that holds the keys.
+    pub fn with_broadcasts_disabled(mut self) -> Self {
+        self.broadcasts_disabled = true;
+        self
+    }
+
+    /// Whether every broadcast is refused.
+    pub fn broadcasts_disabled(&self) -> bool {
+        self.broadcasts_disabled
+    }
+
+    /// Answer confirmation queries for `txid` from the confirmations a chain monitor reports
+    /// with [EsploraClient::set_confirmations] instead of polling esplora.
+    pub(crate) fn watch_confirmations(&self, txid: Txid) {
+        self.monitored.lock().unwrap().entry(txid).or_insert(0);
+    }
+
+    /// Poll esplora for the confirmations of `txid` again.
+    pub(crate) fn unwatch_confirmations(&self, txid: &Txid) {
+        self.monitored.lock().unwrap().remove(txid);
+    }
+
+    /// Record the confirmations a chain monitor reported for a watched `txid`.
+    pub(crate) fn set_confirmations(&self, txid: &Txid, confirmations: u32) {
+        if let Some(reported) = self.monitored.lock().unwrap().get_mut(txid) {
+            *reported = confirmations;
+        }
+    }
+
+    /// Broadcast to the primary host, then to each fallback until one accepts the transaction.
+    /// Returns the error of the last host if none do.
+    pub fn broadcast(&self, transaction: &Transaction) -> Result<(), EsploraError> {
+        let clients = std::iter::once(&self.blocking_client).chain(&self.broadcast_fallbacks);
+        first_accepted(clients, |client| client.broadcast(transaction))
+    }
+
+    /// Policy asked to approve each transaction before it is broadcast.
+    pub fn set_spending_policy(&self, policy: Arc<dyn SpendingPolicy>) {
+        *self.spending_policy.write().unwrap() = policy;
+    }
+
+    /// Tells the spending policy what the transactions the dlc manager broadcasts are for.
+    /// Without one they are [TxPurpose::Other].
+    pub(crate) fn set_classifier(&self, classifier: Classifier) {
+        *self.classifier.write().unwrap() = Some(classifier);
+    }
+
+    /// Holds CETs back while their settlement delay waits for lower fees.
+    pub(crate) fn set_settlement_check(&self, check: SettlementCheck) {
+        *self.settlement_check.write().unwrap() = Some(check);
+    }
+
+    /// Ask the spending policy whether `transaction` may be broadcast.
+    pub fn approve(&self, transaction: &Transaction, purpose: TxPurpose) -> Result<(), WalletError> {
+        if self.broadcasts_disabled {
+            return Err(WalletError::WatchOnly);
+        }
+        let policy = self.spending_policy.read().unwrap().clone();
+        match policy.approve(transaction, purpose) {
+            Decision::Approve => Ok(()),
+            Decision::Deny(reason) => {
+                tracing::warn!(
+                    txid = transaction.compute_txid().to_string(),
+                    %purpose,
+                    reason = reason.as_str(),
+                    "Spending policy denied broadcast."
+                );
+                Err(WalletError::PolicyRejected { purpose, reason })
+            }
+        }
+    }
+}
+
+/// Try `broadcast` with each client in order. Succeeds as soon as one client does.
+fn first_accepted<'a, E: std::fmt::Debug>(
+    clients: impl Iterator<Item = &'a BlockingClient>,
+    mut broadcast: impl FnMut(&BlockingClient) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut last_error = None;
+    // Host 0 is the primary host.
+    for (host, client) in clients.enumerate() {
+        match broadcast(client) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                tracing::warn!(host, error=?e, "Esplora host did not accept transaction.");
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.expect("the primary host is always tried"))
 }
 
 impl dlc_manager::Blockchain for EsploraClient {
@@ -44,10 +172,16 @@ impl dlc_manager::Blockchain for EsploraClient {
     }
 
     fn send_transaction(&self, transaction: &bitcoin::Transaction) -> Result<(), ManagerError> {
-        Ok(self
-            .blocking_client
-            .broadcast(transaction)
-            .map_err(esplora_err_to_manager_err)?)
+        let classifier = self.classifier.read().unwrap().clone();
+        let purpose = classifier.map_or(TxPurpose::Other, |classify| classify(transaction));
+        self.approve(transaction, purpose)?;
+        let settlement_check = self.settlement_check.read().unwrap().clone();
+        if settlement_check.is_some_and(|hold| hold(transaction)) {
+            let txid = transaction.compute_txid();
+            tracing::info!(txid = txid.to_string(), "Holding back CET until fees drop.");
+            return Err(WalletError::SettlementHeld(txid).into());
+        }
+        self.broadcast(transaction).map_err(esplora_err_to_manager_err)
     }
 
     fn get_block_at_height(&self, height: u64) -> Result<bitcoin::Block, ManagerError> {
@@ -75,6 +209,10 @@ impl dlc_manager::Blockchain for EsploraClient {
     }
 
     fn get_transaction_confirmations(&self, tx_id: &bitcoin::Txid) -> Result<u32, ManagerError> {
+        // Counted like below, as blocks on top of the one the transaction is in.
+        if let Some(confirmations) = self.monitored.lock().unwrap().get(tx_id) {
+            return Ok(confirmations.saturating_sub(1));
+        }
         let txn = self
             .blocking_client
             .get_tx_status(tx_id)
@@ -96,3 +234,115 @@ impl dlc_manager::Blockchain for EsploraClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// An esplora host that accepts one broadcast.
+    fn accepting_host() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            // Read the headers and the hex transaction body before answering.
+            loop {
+                let read = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..read]);
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                let complete = text.find("\r\n\r\n").is_some_and(|end| {
+                    let length = text[..end]
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |length| length.trim().parse::<usize>().unwrap());
+                    request.len() >= end + 4 + length
+                });
+                if read == 0 || complete {
+                    break;
+                }
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn broadcast_falls_back_until_accepted() {
+        let transaction = Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        };
+        // Nothing listens on the discard port, so the primary host errors.
+        let client = EsploraClient::new("http://127.0.0.1:9", Network::Regtest).unwrap();
+        assert!(client.broadcast(&transaction).is_err());
+
+        let client = client.with_broadcast_fallbacks(&[accepting_host()]);
+        assert!(client.broadcast(&transaction).is_ok());
+    }
+
+    struct DenyCets;
+
+    impl SpendingPolicy for DenyCets {
+        fn approve(&self, _tx: &Transaction, purpose: TxPurpose) -> Decision {
+            match purpose {
+                TxPurpose::Cet => Decision::Deny("CETs need a manual review".to_string()),
+                _ => Decision::Approve,
+            }
+        }
+    }
+
+    #[test]
+    fn denied_cet_is_not_broadcast() {
+        let contract = dlc_manager::contract::Contract::Confirmed(crate::test_util::signed_contract());
+        let dlc_transactions = match &contract {
+            dlc_manager::contract::Contract::Confirmed(s) => s.accepted_contract.dlc_transactions.clone(),
+            _ => unreachable!(),
+        };
+        let cet = dlc_transactions.cets[0].clone();
+        let contracts = vec![contract];
+        assert_eq!(crate::policy::tx_purpose(&cet, &contracts), TxPurpose::Cet);
+        assert_eq!(
+            crate::policy::tx_purpose(&dlc_transactions.fund, &contracts),
+            TxPurpose::Funding
+        );
+
+        // A host that records whether anything was broadcast to it.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let client = EsploraClient::new(&host, Network::Regtest).unwrap();
+        client.set_spending_policy(Arc::new(DenyCets));
+        client.set_classifier(Arc::new(move |tx| crate::policy::tx_purpose(tx, &contracts)));
+
+        let result = dlc_manager::Blockchain::send_transaction(&client, &cet);
+        assert!(result.unwrap_err().to_string().contains("manual review"));
+        assert!(matches!(
+            listener.accept(),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock
+        ));
+    }
+
+    #[test]
+    fn monitored_transactions_are_not_polled() {
+        use bitcoin::hashes::Hash;
+        use dlc_manager::Blockchain;
+        // Nothing listens on the discard port, so every poll errors.
+        let client = EsploraClient::new("http://127.0.0.1:9", Network::Regtest).unwrap();
+        let txid = Txid::all_zeros();
+        client.watch_confirmations(txid);
+        assert_eq!(client.get_transaction_confirmations(&txid).unwrap(), 0);
+        client.set_confirmations(&txid, 3);
+        assert_eq!(client.get_transaction_confirmations(&txid).unwrap(), 2);
+
+        client.unwatch_confirmations(&txid);
+        assert!(client.get_transaction_confirmations(&txid).is_err());
+    }
+}
@@ -1,3 +1,5 @@
 mod esplora;
+mod monitor;
 
 pub use esplora::EsploraClient;
+pub use monitor::{ChainMonitor, Confirmation, EsploraMonitor, DEFAULT_POLL_INTERVAL_SECS};
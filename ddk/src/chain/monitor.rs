@@ -0,0 +1,124 @@
+//! Confirmation notifications for the funding transactions of contracts.
+use crate::chain::EsploraClient;
+use async_trait::async_trait;
+use bitcoin::OutPoint;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Default seconds between the polls of [EsploraMonitor].
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 10;
+
+/// The transaction of a watched outpoint got a new confirmation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Confirmation {
+    pub outpoint: OutPoint,
+    /// Confirmations, counting the block the transaction is in.
+    pub confirmations: u32,
+}
+
+/// Pushes confirmations of the funding transactions DDK waits on, so contracts advance as soon
+/// as a block confirms them instead of on the next periodic check.
+///
+/// [EsploraMonitor] polls esplora. Implementations backed by bitcoind ZMQ or a mempool.space
+/// websocket can push blocks as they arrive.
+#[async_trait]
+pub trait ChainMonitor: std::marker::Send + std::marker::Sync + 'static {
+    /// Name for the monitor.
+    fn name(&self) -> String;
+    /// Watch the transaction of `outpoint`. Registering a watched outpoint again does nothing.
+    fn register_outpoint(&self, outpoint: OutPoint);
+    /// Stop watching `outpoint`.
+    fn unregister_outpoint(&self, outpoint: &OutPoint);
+    /// Send a [Confirmation] to `events` each time a watched transaction is confirmed by
+    /// another block. Returns once `events` is closed.
+    async fn run(&self, events: UnboundedSender<Confirmation>);
+}
+
+/// [ChainMonitor] that polls esplora for the status of each watched transaction.
+pub struct EsploraMonitor {
+    client: Arc<EsploraClient>,
+    poll_interval: Duration,
+    /// Confirmations last reported for each watched outpoint.
+    watched: Mutex<HashMap<OutPoint, u32>>,
+}
+
+impl EsploraMonitor {
+    pub fn new(client: Arc<EsploraClient>) -> Self {
+        Self {
+            client,
+            poll_interval: Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS),
+            watched: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Time between polls. Defaults to [DEFAULT_POLL_INTERVAL_SECS].
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Confirmations of the transaction of `outpoint`. Zero while it is unconfirmed.
+    async fn confirmations(&self, outpoint: &OutPoint, tip_height: u32) -> anyhow::Result<u32> {
+        let status = self.client.async_client.get_tx_status(&outpoint.txid).await?;
+        Ok(match (status.confirmed, status.block_height) {
+            (true, Some(height)) => tip_height.saturating_sub(height) + 1,
+            _ => 0,
+        })
+    }
+}
+
+#[async_trait]
+impl ChainMonitor for EsploraMonitor {
+    fn name(&self) -> String {
+        "esplora".to_string()
+    }
+
+    fn register_outpoint(&self, outpoint: OutPoint) {
+        self.watched.lock().unwrap().entry(outpoint).or_insert(0);
+    }
+
+    fn unregister_outpoint(&self, outpoint: &OutPoint) {
+        self.watched.lock().unwrap().remove(outpoint);
+    }
+
+    async fn run(&self, events: UnboundedSender<Confirmation>) {
+        let mut timer = tokio::time::interval(self.poll_interval);
+        while !events.is_closed() {
+            timer.tick().await;
+            let outpoints = self.watched.lock().unwrap().keys().copied().collect::<Vec<_>>();
+            if outpoints.is_empty() {
+                continue;
+            }
+            let tip_height = match self.client.async_client.get_height().await {
+                Ok(height) => height,
+                Err(e) => {
+                    tracing::warn!(error=?e, "Could not retrieve the chain tip for the chain monitor.");
+                    continue;
+                }
+            };
+
+            for outpoint in outpoints {
+                let confirmations = match self.confirmations(&outpoint, tip_height).await {
+                    Ok(confirmations) => confirmations,
+                    Err(e) => {
+                        tracing::warn!(outpoint = outpoint.to_string(), error=?e, "Could not retrieve transaction status.");
+                        continue;
+                    }
+                };
+                // The outpoint may have been unregistered while polling.
+                let changed = match self.watched.lock().unwrap().get_mut(&outpoint) {
+                    Some(reported) if *reported != confirmations => {
+                        *reported = confirmations;
+                        confirmations > 0
+                    }
+                    _ => false,
+                };
+                if changed {
+                    let _ = events.send(Confirmation { outpoint, confirmations });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::ddk::{DlcDevKitDlcManager, DlcManagerMessage};
+use crate::{DdkOracle, DdkStorage, DdkTransport, DlcDevKit};
+use anyhow::anyhow;
+use bitcoin::secp256k1::PublicKey;
+use crossbeam::channel::unbounded;
+use dlc_manager::channel::Channel;
+use dlc_manager::contract::contract_input::ContractInput;
+use dlc_manager::{ChannelId, Storage};
+use dlc_messages::Message;
+
+/// A step of the DLC channel protocol for the manager thread to run.
+#[derive(Debug)]
+pub enum ChannelAction {
+    Offer {
+        contract_input: ContractInput,
+        counter_party: PublicKey,
+    },
+    Accept(ChannelId),
+    OfferSettle {
+        channel_id: ChannelId,
+        counter_payout: u64,
+    },
+    AcceptSettle(ChannelId),
+    OfferRenew {
+        channel_id: ChannelId,
+        counter_payout: u64,
+        contract_input: ContractInput,
+    },
+    AcceptRenew(ChannelId),
+    OfferClose {
+        channel_id: ChannelId,
+        counter_payout: u64,
+    },
+    AcceptClose(ChannelId),
+    ForceClose(ChannelId),
+}
+
+/// Runs a channel action. Returns the id of the channel and the message to send to the
+/// counterparty, if the action has one.
+pub(crate) fn run_channel_action<S: DdkStorage, O: DdkOracle>(
+    manager: &DlcDevKitDlcManager<S, O>,
+    action: ChannelAction,
+) -> anyhow::Result<(ChannelId, Option<(PublicKey, Message)>)> {
+    let counter_party = |channel_id: &ChannelId| -> anyhow::Result<PublicKey> {
+        manager
+            .get_store()
+            .get_channel(channel_id)?
+            .map(|channel| channel.get_counter_party_id())
+            .ok_or_else(|| anyhow!("Channel not found."))
+    };
+
+    let result = match action {
+        ChannelAction::Offer {
+            contract_input,
+            counter_party,
+        } => {
+            let offer = manager.offer_channel(&contract_input, counter_party)?;
+            (offer.temporary_channel_id, Some((counter_party, Message::OfferChannel(offer))))
+        }
+        ChannelAction::Accept(channel_id) => {
+            let (accept, channel_id, _, counter_party) = manager.accept_channel(&channel_id)?;
+            (channel_id, Some((counter_party, Message::AcceptChannel(accept))))
+        }
+        ChannelAction::OfferSettle {
+            channel_id,
+            counter_payout,
+        } => {
+            let settle = manager.settle_offer(&channel_id, counter_payout)?;
+            (channel_id, Some((counter_party(&channel_id)?, Message::SettleOffer(settle))))
+        }
+        ChannelAction::AcceptSettle(channel_id) => {
+            let (accept, counter_party) = manager.accept_settle_offer(&channel_id)?;
+            (channel_id, Some((counter_party, Message::SettleAccept(accept))))
+        }
+        ChannelAction::OfferRenew {
+            channel_id,
+            counter_payout,
+            contract_input,
+        } => {
+            let (renew, counter_party) =
+                manager.renew_offer(&channel_id, counter_payout, &contract_input)?;
+            (channel_id, Some((counter_party, Message::RenewOffer(renew))))
+        }
+        ChannelAction::AcceptRenew(channel_id) => {
+            let (accept, counter_party) = manager.accept_renew_offer(&channel_id)?;
+            (channel_id, Some((counter_party, Message::RenewAccept(accept))))
+        }
+        ChannelAction::OfferClose {
+            channel_id,
+            counter_payout,
+        } => {
+            let close = manager.offer_collaborative_close(&channel_id, counter_payout)?;
+            (
+                channel_id,
+                Some((counter_party(&channel_id)?, Message::CollaborativeCloseOffer(close))),
+            )
+        }
+        ChannelAction::AcceptClose(channel_id) => {
+            manager.accept_collaborative_close(&channel_id)?;
+            (channel_id, None)
+        }
+        ChannelAction::ForceClose(channel_id) => {
+            manager.force_close_channel(&channel_id)?;
+            (channel_id, None)
+        }
+    };
+
+    Ok(result)
+}
+
+/// Name of the state a channel is in.
+pub fn channel_state(channel: &Channel) -> String {
+    match channel {
+        Channel::Offered(_) => "offered".to_string(),
+        Channel::Accepted(_) => "accepted".to_string(),
+        Channel::Signed(s) => format!("{:?}", s.state.get_type()).to_lowercase(),
+        Channel::FailedAccept(_) => "failed accept".to_string(),
+        Channel::FailedSign(_) => "failed sign".to_string(),
+        Channel::Closing(_) => "closing".to_string(),
+        Channel::Closed(_) => "closed".to_string(),
+        Channel::CounterClosed(_) => "counter closed".to_string(),
+        Channel::ClosedPunished(_) => "closed punished".to_string(),
+        Channel::CollaborativelyClosed(_) => "collaboratively closed".to_string(),
+        Channel::Cancelled(_) => "cancelled".to_string(),
+    }
+}
+
+impl<T, S, O> DlcDevKit<T, S, O>
+where
+    T: DdkTransport,
+    S: DdkStorage,
+    O: DdkOracle,
+{
+    /// Offer a DLC channel funded with the collateral of `contract_input`. The first contract
+    /// of the channel is the contract input. Returns the temporary channel id.
+    pub async fn open_channel(
+        &self,
+        contract_input: &ContractInput,
+        counter_party: PublicKey,
+    ) -> anyhow::Result<ChannelId> {
+        self.channel_action(ChannelAction::Offer {
+            contract_input: contract_input.to_owned(),
+            counter_party,
+        })
+        .await
+    }
+
+    /// Accept a channel offer. Returns the id of the channel.
+    pub async fn accept_channel(&self, channel_id: &ChannelId) -> anyhow::Result<ChannelId> {
+        self.channel_action(ChannelAction::Accept(*channel_id)).await
+    }
+
+    /// Offer to settle the contract of a channel off-chain, paying the counterparty
+    /// `counter_payout` and keeping the channel open.
+    pub async fn settle_channel(&self, channel_id: &ChannelId, counter_payout: u64) -> anyhow::Result<()> {
+        self.channel_action(ChannelAction::OfferSettle {
+            channel_id: *channel_id,
+            counter_payout,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Accept the counterparty's offer to settle the contract of a channel.
+    pub async fn accept_settle_channel(&self, channel_id: &ChannelId) -> anyhow::Result<()> {
+        self.channel_action(ChannelAction::AcceptSettle(*channel_id)).await.map(|_| ())
+    }
+
+    /// Offer a new contract in a channel. The counterparty is paid `counter_payout` of the
+    /// current contract and the channel funds the new contract.
+    pub async fn renew_channel(
+        &self,
+        channel_id: &ChannelId,
+        counter_payout: u64,
+        contract_input: &ContractInput,
+    ) -> anyhow::Result<()> {
+        self.channel_action(ChannelAction::OfferRenew {
+            channel_id: *channel_id,
+            counter_payout,
+            contract_input: contract_input.to_owned(),
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Accept the counterparty's offer of a new contract in a channel.
+    pub async fn accept_renew_channel(&self, channel_id: &ChannelId) -> anyhow::Result<()> {
+        self.channel_action(ChannelAction::AcceptRenew(*channel_id)).await.map(|_| ())
+    }
+
+    /// Offer to close a channel cooperatively, paying the counterparty `counter_payout`.
+    pub async fn close_channel(&self, channel_id: &ChannelId, counter_payout: u64) -> anyhow::Result<()> {
+        self.channel_action(ChannelAction::OfferClose {
+            channel_id: *channel_id,
+            counter_payout,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Accept the counterparty's offer to close a channel and broadcast the close transaction.
+    pub async fn accept_close_channel(&self, channel_id: &ChannelId) -> anyhow::Result<()> {
+        self.channel_action(ChannelAction::AcceptClose(*channel_id)).await.map(|_| ())
+    }
+
+    /// Close a channel unilaterally with the latest signed state.
+    pub async fn force_close_channel(&self, channel_id: &ChannelId) -> anyhow::Result<()> {
+        self.channel_action(ChannelAction::ForceClose(*channel_id)).await.map(|_| ())
+    }
+
+    async fn channel_action(&self, action: ChannelAction) -> anyhow::Result<ChannelId> {
+        let (responder, receiver) = unbounded();
+        self.sender
+            .send(DlcManagerMessage::Channel { action, responder })
+            .expect("couldnt send channel action");
+        let (channel_id, message) = receiver.recv().expect("no channel action result")?;
+
+        // The channel state must be on disk before the counterparty acts on the message.
+        self.storage.flush()?;
+        if let Some((counter_party, message)) = message {
+            self.deliver(counter_party, message).await?;
+        }
+        tracing::info!(channel_id = hex::encode(channel_id), "Updated DLC channel.");
+
+        Ok(channel_id)
+    }
+}
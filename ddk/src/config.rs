@@ -2,8 +2,20 @@ use std::{fmt, path::PathBuf};
 
 use bitcoin::Network;
 
+use crate::ESPLORA_HOST;
+
 pub const DEFAULT_STORAGE_DIR: &str = "/tmp/ddk";
 
+/// Public esplora API for a network. Regtest uses the local [crate::ESPLORA_HOST].
+pub fn default_esplora_host(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => "https://mempool.space/api",
+        Network::Testnet => "https://mempool.space/testnet/api",
+        Network::Signet => "https://mempool.space/signet/api",
+        _ => ESPLORA_HOST,
+    }
+}
+
 /// Configuration values for creating a DDK process.
 ///
 /// I think this should be a requirement for all implementations in some way.
@@ -11,10 +23,10 @@ pub const DEFAULT_STORAGE_DIR: &str = "/tmp/ddk";
 /// As well some might rely on seed. Ex. transport with nostr & ln.
 #[derive(Debug, Clone)]
 pub struct DdkConfig {
-    /// The bitcoin network to run on. Defaults to mutiny net.
+    /// The bitcoin network to run on. Defaults to signet.
     pub network: Network,
-    /// The esplora API to call to. Defaults to mutiny net
-    pub esplora_host: String,
+    /// The esplora API to call to. Defaults to [default_esplora_host] for the network.
+    pub esplora_host: Option<String>,
     /// The directory the DDK instance will be stored at. Defaults to /tmp/ddk/.
     /// Probably an enum? Or is this even used? Maybe wallet_storage_path?
     /// TODO: no-std config
@@ -27,7 +39,7 @@ impl Default for DdkConfig {
     fn default() -> Self {
         Self {
             network: Network::Signet,
-            esplora_host: "https://mutinynet.com/api".to_string(),
+            esplora_host: None,
             storage_path: DEFAULT_STORAGE_DIR.into(),
             seed_config: SeedConfig::default(),
         }
@@ -57,3 +69,16 @@ impl Default for SeedConfig {
         Self::Bytes([0u8; 64])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn esplora_host_per_network() {
+        assert_eq!(default_esplora_host(Network::Bitcoin), "https://mempool.space/api");
+        assert_eq!(default_esplora_host(Network::Testnet), "https://mempool.space/testnet/api");
+        assert_eq!(default_esplora_host(Network::Signet), "https://mempool.space/signet/api");
+        assert_eq!(default_esplora_host(Network::Regtest), ESPLORA_HOST);
+    }
+}
@@ -1,18 +1,44 @@
-use crate::chain::EsploraClient;
-use crate::wallet::DlcDevKitWallet;
-use crate::{DdkOracle, DdkStorage, DdkTransport};
+use crate::adaptor::{accept_checks, sign_checks, AdaptorVerifier};
+use crate::chain::{ChainMonitor, Confirmation, EsploraClient};
+use crate::delivery::{retry_deliveries, DeliveryRetries, DELIVERY_CHECK_INTERVAL_SECS};
+use crate::error::{ContractError, NegotiationError};
+use crate::oracle::{fetch_announcement, EventFilter, OracleRetry};
+use crate::events::{contract_states, spawn_dispatcher, state_changes, DdkEvent, EventHandlers};
+use crate::order::{release_failed_fills, release_fill, reserve_fill, scale_contract_input, PartialOffer};
+use crate::storage::FundingConflict;
+use crate::policy::SettlementDelay;
+use crate::transport::MessageReceipt;
+use crate::util::{
+    attested_outcome, deserialize_contract_bytes, funding_outpoint, message_contract_id, oracle_event_ids, portfolio, verify_funding_output, verify_offer_signature, verify_refund_transaction,
+    FundingOutputInfo, Portfolio,
+};
+use crate::wallet::{fee_rate_for_target, DlcDevKitWallet};
+use crate::webhook::WebhookHandler;
+use crate::{DdkEventHandler, DdkOracle, DdkStorage, DdkTransport};
+use crate::{DEFAULT_CHECK_INTERVAL_SECS, DEFAULT_CHECK_JITTER_PERCENT, DEFAULT_OFFER_EXPIRY_SECS};
 use anyhow::anyhow;
-use bitcoin::secp256k1::PublicKey;
-use bitcoin::Network;
+use ddk_payouts::TotalCost;
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::{PublicKey, Secp256k1};
+use bitcoin::{Address, Network};
+use bitcoin::{OutPoint, Transaction, Txid};
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::signed_contract::SignedContract;
+use dlc_manager::contract::{Contract, FailedSignContract, PreClosedContract};
+use dlc_manager::manager::NB_CONFIRMATIONS;
 use dlc_manager::{
     contract::contract_input::ContractInput, CachedContractSignerProvider, ContractId,
-    SimpleSigner, SystemTimeProvider,
+    Blockchain, SimpleSigner, SystemTimeProvider,
 };
-use dlc_messages::oracle_msgs::OracleAnnouncement;
-use dlc_messages::{AcceptDlc, Message, OfferDlc};
+use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation};
+use dlc_messages::channel::Reject;
+use dlc_messages::{AcceptDlc, Message, OfferDlc, SignDlc};
+use reqwest::Url;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use tokio::runtime::Runtime;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use crossbeam::channel::{unbounded, Sender, Receiver};
 
 /// DlcDevKit type alias for the [dlc_manager::manager::Manager]
@@ -31,27 +57,119 @@ pub type DlcDevKitDlcManager<S, O> = dlc_manager::manager::Manager<
 pub enum DlcManagerMessage {
     AcceptDlc {
         contract: ContractId,
-        responder: Sender<(ContractId, PublicKey, AcceptDlc)>
+        responder: Sender<anyhow::Result<(ContractId, PublicKey, AcceptDlc)>>,
     },
     OfferDlc {
         contract_input: ContractInput,
         counter_party: PublicKey,
         oracle_announcements: Vec<OracleAnnouncement>,
-        responder: Sender<OfferDlc>,
+        /// Utxos to fund the offer with. Empty lets coin selection choose.
+        utxos: Vec<OutPoint>,
+        /// Secret to derive the serial ids of the offer from. See [set_serial_ids].
+        serial_id_secret: Option<[u8; 32]>,
+        responder: Sender<anyhow::Result<OfferDlc>>,
+    },
+    CancelOffer {
+        contract: ContractId,
+        responder: Sender<anyhow::Result<PublicKey>>,
+    },
+    ImportOffer {
+        offer: OfferDlc,
+        counter_party: PublicKey,
+        responder: Sender<anyhow::Result<ContractId>>,
+    },
+    ImportContract {
+        contract: Box<Contract>,
+        overwrite: bool,
+        responder: Sender<anyhow::Result<()>>,
+    },
+    #[cfg(feature = "channels")]
+    Channel {
+        action: crate::channel::ChannelAction,
+        responder: Sender<anyhow::Result<(dlc_manager::ChannelId, Option<(PublicKey, Message)>)>>,
+    },
+    #[cfg(feature = "dev")]
+    ForceAttestation {
+        contract: ContractId,
+        attestation: OracleAttestation,
+        responder: Sender<anyhow::Result<Contract>>,
     },
     ProcessMessages,
+    PeriodicCheck,
+    /// The chain monitor saw another block confirm a funding transaction.
+    FundingConfirmed(Confirmation),
 }
 
 pub struct DlcDevKit<T: DdkTransport, S: DdkStorage, O: DdkOracle> {
     pub runtime: Arc<RwLock<Option<Runtime>>>,
     pub wallet: Arc<DlcDevKitWallet<S>>,
     pub manager: Arc<DlcDevKitDlcManager<S, O>>,
+    pub blockchain: Arc<EsploraClient>,
     pub sender: Arc<Sender<DlcManagerMessage>>,
     pub receiver: Arc<Receiver<DlcManagerMessage>>,
+    /// The primary transport. Offers are sent over it.
     pub transport: Arc<T>,
+    /// Every transport DDK listens on, including the primary transport.
+    pub transports: Vec<Arc<T>>,
+    /// The transport each counterparty was last heard from on.
+    pub peer_transports: Arc<RwLock<HashMap<PublicKey, Arc<T>>>>,
     pub storage: Arc<S>,
     pub oracle: Arc<O>,
     pub network: Network,
+    pub event_handler: Option<Arc<dyn DdkEventHandler>>,
+    /// Endpoint contract events are POSTed to. See [crate::webhook].
+    pub webhook_url: Option<Url>,
+    pub(crate) check_schedule: CheckSchedule,
+    pub(crate) max_active_contracts: Option<usize>,
+    pub(crate) max_contract_collateral: Option<u64>,
+    pub(crate) deterministic_serial_ids: bool,
+    /// Refuse offers without a valid signature. See
+    /// [crate::builder::DdkBuilder::set_require_signed_offers].
+    pub(crate) require_signed_offers: bool,
+    /// Checks the adaptor signatures of received accept and sign messages. See [crate::adaptor].
+    pub(crate) adaptor_verifier: Arc<AdaptorVerifier>,
+    /// How unanswered messages are sent again. See [crate::delivery].
+    pub(crate) delivery_retries: Arc<DeliveryRetries>,
+    /// Pushes confirmations of funding transactions. See [crate::chain::ChainMonitor].
+    pub chain_monitor: Arc<dyn ChainMonitor>,
+}
+
+/// When the periodic check runs. Each delay is the interval moved by a random amount of up
+/// to `jitter_percent` either way, so nodes started together do not poll esplora and
+/// oracles at the same moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CheckSchedule {
+    pub interval: Duration,
+    pub jitter_percent: u8,
+}
+
+impl Default for CheckSchedule {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(DEFAULT_CHECK_INTERVAL_SECS),
+            jitter_percent: DEFAULT_CHECK_JITTER_PERCENT,
+        }
+    }
+}
+
+impl CheckSchedule {
+    /// Shortest delay between checks, whatever the interval and jitter.
+    const MIN_DELAY: Duration = Duration::from_secs(1);
+
+    /// Delay until the next check.
+    pub fn next_delay(&self) -> Duration {
+        let mut sample = [0u8; 8];
+        crate::rng::fill_bytes(&mut sample);
+        self.delay(u64::from_le_bytes(sample))
+    }
+
+    /// Delay for a random `sample`, within the jitter band around the interval.
+    fn delay(&self, sample: u64) -> Duration {
+        let interval = self.interval.as_millis() as u64;
+        let jitter = interval * u64::from(self.jitter_percent.min(100)) / 100;
+        let offset = sample % (2 * jitter + 1);
+        Duration::from_millis(interval - jitter + offset).max(Self::MIN_DELAY)
+    }
 }
 
 impl<T, S, O> DlcDevKit<T, S, O>
@@ -70,15 +188,40 @@ where
             .build()?;
 
         
-        let manager_transport = self.transport.clone();
+        let manager_transports = self.transports.clone();
+        let peer_transports = self.peer_transports.clone();
         let manager_clone = self.manager.clone();
+        let blockchain_clone = self.blockchain.clone();
         let receiver_clone = self.receiver.clone();
-        std::thread::spawn(move || Self::run_manager(manager_clone, manager_transport, receiver_clone));
+        let manager_wallet = self.wallet.clone();
+        let mut handlers = Vec::new();
+        if let Some(url) = &self.webhook_url {
+            tracing::info!(url = url.to_string(), "Sending contract events to webhook.");
+            handlers.push(Arc::new(WebhookHandler::spawn(runtime.handle(), url.clone())?) as Arc<dyn DdkEventHandler>);
+        }
+        handlers.extend(self.event_handler.clone());
+        let events = match handlers.len() {
+            0 => None,
+            1 => Some(spawn_dispatcher(&runtime, handlers.remove(0))),
+            _ => Some(spawn_dispatcher(&runtime, Arc::new(EventHandlers(handlers)))),
+        };
+        let manager_runtime = runtime.handle().clone();
+        let manager_monitor = self.chain_monitor.clone();
+        let require_signed_offers = self.require_signed_offers;
+        let adaptor_verifier = self.adaptor_verifier.clone();
+        let delivery_events = events.clone();
+        let manager_retries = self.delivery_retries.clone();
+        let max_active_contracts = self.max_active_contracts;
+        let max_contract_collateral = self.max_contract_collateral;
+        std::thread::spawn(move || Self::run_manager(manager_clone, manager_wallet, blockchain_clone, manager_monitor, manager_transports, peer_transports, require_signed_offers, adaptor_verifier, max_active_contracts, max_contract_collateral, manager_retries, events, receiver_clone, manager_runtime));
 
-        let transport_clone = self.transport.clone();
-        runtime.spawn(async move {
-            transport_clone.listen().await;
-        });
+        for transport in &self.transports {
+            let transport_clone = transport.clone();
+            tracing::info!(transport = transport_clone.name(), "Listening on transport.");
+            runtime.spawn(async move {
+                transport_clone.listen().await;
+            });
+        }
 
         let wallet_clone = self.wallet.clone();
         runtime.spawn(async move {
@@ -98,6 +241,46 @@ where
             }
         });
 
+        let checker = self.sender.clone();
+        let schedule = self.check_schedule;
+        runtime.spawn(async move {
+            loop {
+                tokio::time::sleep(schedule.next_delay()).await;
+                checker.send(DlcManagerMessage::PeriodicCheck).expect("couldn't send periodic check");
+            }
+        });
+
+        let (confirmations, confirmation_events) = unbounded_channel();
+        let monitor = self.chain_monitor.clone();
+        tracing::info!(monitor = monitor.name(), "Watching funding transactions.");
+        runtime.spawn(async move {
+            monitor.run(confirmations).await;
+        });
+        runtime.spawn(forward_confirmations(confirmation_events, self.sender.clone()));
+
+        let retry_storage = self.storage.clone();
+        let retries = self.delivery_retries.clone();
+        let retry_blockchain = self.blockchain.clone();
+        let retry_transport = self.transport.clone();
+        let retry_peers = self.peer_transports.clone();
+        runtime.spawn(async move {
+            let mut timer = tokio::time::interval(Duration::from_secs(DELIVERY_CHECK_INTERVAL_SECS));
+            loop {
+                timer.tick().await;
+                retry_deliveries(
+                    retry_storage.clone(),
+                    retries.clone(),
+                    retry_blockchain.clone(),
+                    &retry_transport,
+                    &retry_peers,
+                    delivery_events.as_ref(),
+                )
+                .await;
+            }
+        });
+
+        self.resume_negotiations(&runtime);
+
         // TODO: connect stored peers.
 
         *runtime_lock = Some(runtime);
@@ -105,41 +288,358 @@ where
         Ok(())
     }
 
-    fn run_manager(manager: Arc<DlcDevKitDlcManager<S, O>>, transport: Arc<T>, receiver: Arc<Receiver<DlcManagerMessage>>) {
+    /// Drive forward the negotiations a crash may have cut short. See
+    /// [interrupted_negotiations]. Signed contracts are also rebroadcast by a periodic check
+    /// sent right away, in case their funding transaction never reached esplora. Resumed
+    /// messages are tracked for resending, except the dead-lettered ones, which wait for
+    /// [Self::retry_dead_letter].
+    fn resume_negotiations(&self, runtime: &Runtime) {
+        let messages = match interrupted_negotiations(self.storage.as_ref()) {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::error!(error=?e, "Could not find interrupted negotiations.");
+                return;
+            }
+        };
+        for (contract_id, counter_party, message) in messages {
+            match self.storage.get_dead_letter(&contract_id) {
+                Ok(None) => (),
+                Ok(Some(_)) => continue,
+                Err(e) => tracing::error!(error=?e, "Could not look up dead letter."),
+            }
+            if let Err(e) = record_outbound_message(self.storage.as_ref(), counter_party, &message) {
+                tracing::error!(error=?e, "Could not record resumed message.");
+            }
+            self.delivery_retries.track(contract_id, counter_party, unix_time());
+            let contract_id = hex::encode(contract_id);
+            tracing::info!(contract_id, counterparty = counter_party.to_string(), "Resuming interrupted negotiation.");
+            let transport = self.transport_for(&counter_party);
+            runtime.spawn(async move {
+                match transport.send_message(counter_party, message).await {
+                    Ok(receipt) => tracing::debug!(contract_id, ?receipt, "Resent DLC message."),
+                    Err(e) => tracing::warn!(contract_id, error=?e, "Could not resend DLC message."),
+                }
+            });
+        }
+        let _ = self.sender.send(DlcManagerMessage::PeriodicCheck);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn run_manager(
+        manager: Arc<DlcDevKitDlcManager<S, O>>,
+        wallet: Arc<DlcDevKitWallet<S>>,
+        blockchain: Arc<EsploraClient>,
+        chain_monitor: Arc<dyn ChainMonitor>,
+        transports: Vec<Arc<T>>,
+        peer_transports: Arc<RwLock<HashMap<PublicKey, Arc<T>>>>,
+        require_signed_offers: bool,
+        adaptor_verifier: Arc<AdaptorVerifier>,
+        max_active_contracts: Option<usize>,
+        max_contract_collateral: Option<u64>,
+        delivery_retries: Arc<DeliveryRetries>,
+        events: Option<UnboundedSender<DdkEvent>>,
+        receiver: Arc<Receiver<DlcManagerMessage>>,
+        runtime: Handle,
+    ) {
+        let mut watched = HashSet::new();
         while let Ok(msg) = receiver.recv() {
+            // Only snapshot contract states when there is someone to notify.
+            let before = match (&events, &msg) {
+                (Some(_), DlcManagerMessage::ProcessMessages | DlcManagerMessage::PeriodicCheck | DlcManagerMessage::FundingConfirmed(_) | DlcManagerMessage::CancelOffer { .. } | DlcManagerMessage::ImportOffer { .. } | DlcManagerMessage::ImportContract { .. }) => {
+                    manager.get_store().get_contracts().ok().map(|c| contract_states(&c))
+                }
+                _ => None,
+            };
+
             match msg {
-                DlcManagerMessage::OfferDlc { contract_input, counter_party, oracle_announcements, responder } => {
-                    let offer = manager.send_offer_with_announcements(&contract_input, counter_party, vec![oracle_announcements]).expect("can't create offerdlc");
+                // The limit is checked on this thread, which stores every new contract, so
+                // offers and accepts sent at the same time cannot all pass it.
+                DlcManagerMessage::OfferDlc { contract_input, counter_party, oracle_announcements, utxos, serial_id_secret, responder } => {
+                    let offer = check_contract_limit(manager.get_store().as_ref(), max_active_contracts).and_then(|_| {
+                        let offer = wallet
+                            .with_funding_utxos(utxos, || {
+                                manager.send_offer_with_announcements(&contract_input, counter_party, vec![oracle_announcements])
+                            })
+                            .map_err(anyhow::Error::from)?;
+                        // Nothing else writes the offer before this thread reads the next message.
+                        match serial_id_secret {
+                            Some(secret) => derive_offer_serial_ids(manager.get_store().as_ref(), &offer.temporary_contract_id, &secret),
+                            None => Ok(offer),
+                        }
+                    });
                     responder.send(offer).expect("send offer error")
                 },
                 DlcManagerMessage::AcceptDlc { contract, responder } => {
-                    let accept = manager.accept_contract_offer(&contract).expect("can't accept offer");
+                    let accept = check_contract_limit(manager.get_store().as_ref(), max_active_contracts).and_then(|_| {
+                        manager.accept_contract_offer(&contract).map_err(anyhow::Error::from)
+                    });
                     responder.send(accept).expect("can't send")
                 }
+                DlcManagerMessage::CancelOffer { contract, responder } => {
+                    // An accept that already arrived wins the race, so handle it before cancelling.
+                    Self::process_messages(&manager, &blockchain, &transports, &peer_transports, require_signed_offers, &adaptor_verifier, &delivery_retries, &runtime);
+                    responder.send(cancel_offer(manager.get_store().as_ref(), &contract)).expect("can't send")
+                }
+                DlcManagerMessage::ImportOffer { offer, counter_party, responder } => {
+                    let contract_id = offer.temporary_contract_id;
+                    let imported = check_imported_offer(manager.get_store().as_ref(), &offer, &counter_party, require_signed_offers, max_active_contracts, max_contract_collateral).and_then(|_| {
+                        manager.on_dlc_message(&Message::Offer(offer), counter_party)?;
+                        Ok(contract_id)
+                    });
+                    responder.send(imported).expect("can't send")
+                }
+                // Written on this thread so the manager cannot store a contract with the same
+                // id in between, then the funding of a signed contract is watched right away.
+                DlcManagerMessage::ImportContract { contract, overwrite, responder } => {
+                    let imported = manager.get_store().import_contract(&contract, overwrite);
+                    if imported.is_ok() {
+                        if let Err(e) = watch_funding_outpoints(manager.get_store().as_ref(), chain_monitor.as_ref(), &blockchain, &mut watched) {
+                            tracing::error!(error=?e, "Could not update the funding outpoints of the chain monitor.");
+                        }
+                    }
+                    responder.send(imported).expect("can't send")
+                }
+                #[cfg(feature = "channels")]
+                DlcManagerMessage::Channel { action, responder } => {
+                    responder.send(crate::channel::run_channel_action(&manager, action)).expect("can't send")
+                }
+                #[cfg(feature = "dev")]
+                DlcManagerMessage::ForceAttestation { contract, attestation, responder } => {
+                    responder.send(force_attestation(&manager, &contract, attestation)).expect("can't send")
+                }
                 DlcManagerMessage::ProcessMessages => {
-                    let messages = transport.get_and_clear_received_messages();
-
-                    for (counter_party, message) in messages {
-                        tracing::info!(
-                            counter_party = counter_party.to_string(),
-                            "Processing DLC message"
-                        );
-
-                        let message_response = manager.on_dlc_message(&message, counter_party).expect("no on dlc message");
-                        if let Some(msg) = message_response {
-                            tracing::info!("Responding to message received.");
-                            tracing::debug!(message=?msg);
-                            transport.send_message(counter_party, msg);
+                    Self::process_messages(&manager, &blockchain, &transports, &peer_transports, require_signed_offers, &adaptor_verifier, &delivery_retries, &runtime);
+                    // A reject or a failed accept of a fill arrives as a message.
+                    release_fills(manager.get_store().as_ref());
+                    if let Err(e) = watch_funding_outpoints(manager.get_store().as_ref(), chain_monitor.as_ref(), &blockchain, &mut watched) {
+                        tracing::error!(error=?e, "Could not update the funding outpoints of the chain monitor.");
+                    }
+                }
+                DlcManagerMessage::FundingConfirmed(confirmation) => {
+                    blockchain.set_confirmations(&confirmation.outpoint.txid, confirmation.confirmations);
+                    match confirm_funding(manager.get_store().as_ref(), &confirmation) {
+                        Ok(Some(contract_id)) => {
+                            tracing::info!(contract_id = hex::encode(contract_id), "Funding transaction is confirmed.")
+                        }
+                        Ok(None) => {}
+                        Err(e) => tracing::error!(error=?e, "Could not confirm the funded contract."),
+                    }
+                    if let Err(e) = watch_funding_outpoints(manager.get_store().as_ref(), chain_monitor.as_ref(), &blockchain, &mut watched) {
+                        tracing::error!(error=?e, "Could not update the funding outpoints of the chain monitor.");
+                    }
+                }
+                DlcManagerMessage::PeriodicCheck => {
+                    match needs_periodic_check(manager.get_store().as_ref()) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            tracing::debug!("No pending contracts, skipping periodic check.");
+                            continue;
+                        }
+                        Err(e) => tracing::error!(error=?e, "Could not retrieve contracts for periodic check."),
+                    }
+
+                    let confirmed = manager
+                        .get_store()
+                        .get_confirmed_contracts()
+                        .map(|c| c.iter().map(|s| s.accepted_contract.get_contract_id()).collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    if let Err(e) = manager.periodic_check(cfg!(feature = "channels")) {
+                        tracing::error!(error=?e, "Error running periodic check.");
+                    }
+                    match settled_contracts(manager.get_store().as_ref(), &confirmed) {
+                        Ok(settled) => Self::notify_settlements(settled, &transports, &peer_transports, &runtime),
+                        Err(e) => tracing::error!(error=?e, "Could not retrieve settled contracts."),
+                    }
+
+                    match abandon_expired_offers(manager.get_store().as_ref(), unix_time()) {
+                        Ok(abandoned) => {
+                            for contract_id in abandoned {
+                                tracing::info!(contract_id = hex::encode(contract_id), "Abandoned expired offer.");
+                            }
+                        }
+                        Err(e) => tracing::error!(error=?e, "Could not abandon expired offers."),
+                    }
+                    release_fills(manager.get_store().as_ref());
+
+                    // A conflicted funding transaction can never confirm, so it is not rebroadcast.
+                    let spender = |outpoint: &OutPoint| -> anyhow::Result<Option<Txid>> {
+                        let status = blockchain
+                            .blocking_client
+                            .get_output_status(&outpoint.txid, u64::from(outpoint.vout))?;
+                        Ok(status.filter(|status| status.spent).and_then(|status| status.txid))
+                    };
+                    match fail_conflicted_contracts(manager.get_store().as_ref(), spender) {
+                        Ok(conflicts) => {
+                            for conflict in conflicts {
+                                tracing::warn!(
+                                    contract_id = hex::encode(conflict.contract_id),
+                                    outpoint = conflict.outpoint.to_string(),
+                                    conflicting_txid = conflict.conflicting_txid.to_string(),
+                                    "Funding input was spent by another transaction. The contract failed."
+                                );
+                                if let Some(events) = &events {
+                                    let _ = events.send(DdkEvent::FundingConflict(conflict));
+                                }
+                            }
+                        }
+                        Err(e) => tracing::error!(error=?e, "Could not check funding inputs for conflicts."),
+                    }
+
+                    match unconfirmed_funding_transactions(manager.get_store().as_ref()) {
+                        Ok(_) if blockchain.broadcasts_disabled() => (),
+                        Ok(funding) => {
+                            for (contract_id, transaction) in funding {
+                                if let Err(e) = blockchain.send_transaction(&transaction) {
+                                    tracing::warn!(contract_id = hex::encode(contract_id), error=?e, "Rebroadcast of funding transaction failed.");
+                                }
+                            }
+                        }
+                        Err(e) => tracing::error!(error=?e, "Could not retrieve unconfirmed funding transactions."),
+                    }
+
+                    if let Err(e) = watch_funding_outpoints(manager.get_store().as_ref(), chain_monitor.as_ref(), &blockchain, &mut watched) {
+                        tracing::error!(error=?e, "Could not update the funding outpoints of the chain monitor.");
+                    }
+                }
+            }
+
+            // Storage is updated at this point, so handlers see the new state.
+            if let (Some(events), Some(before)) = (&events, before) {
+                match manager.get_store().get_contracts() {
+                    Ok(after) => {
+                        for event in state_changes(&before, after) {
+                            let _ = events.send(event);
+                        }
+                    }
+                    Err(e) => tracing::error!(error=?e, "Could not retrieve contracts for events."),
+                }
+            }
+        }
+
+    }
+
+    fn process_messages(
+        manager: &DlcDevKitDlcManager<S, O>,
+        blockchain: &EsploraClient,
+        transports: &[Arc<T>],
+        peer_transports: &RwLock<HashMap<PublicKey, Arc<T>>>,
+        require_signed_offers: bool,
+        adaptor_verifier: &AdaptorVerifier,
+        delivery_retries: &DeliveryRetries,
+        runtime: &Handle,
+    ) {
+        for transport in transports {
+            let counter_parties = process_transport_messages(transport.as_ref(), runtime, |message, counter_party| {
+                if let Message::Reject(reject) = message {
+                    let reason = match RejectReason::of(manager.get_store().as_ref(), reject, counter_party) {
+                        Ok(reason) => reason,
+                        Err(e) => {
+                            tracing::error!(error=?e, "Could not read the contract of a reject.");
+                            return None;
+                        }
+                    };
+                    match reason {
+                        RejectReason::OfferCancelled => {
+                            match offer_cancelled(manager.get_store().as_ref(), &reject.channel_id, counter_party) {
+                                Ok(true) => tracing::info!(contract_id = hex::encode(reject.channel_id), "Counterparty cancelled their offer."),
+                                Ok(false) => tracing::debug!(contract_id = hex::encode(reject.channel_id), "Cancel is not for an open offer from the counterparty."),
+                                Err(e) => tracing::error!(error=?e, "Could not cancel received offer."),
+                            }
+                            return None;
+                        }
+                        RejectReason::Settled => {
+                            let spender = |outpoint: &OutPoint| funding_spender(blockchain, outpoint);
+                            match settle_on_notice(manager.get_store().as_ref(), &reject.channel_id, counter_party, spender) {
+                                Ok(Some(settlement)) => {
+                                    tracing::info!(
+                                        contract_id = hex::encode(settlement.contract_id),
+                                        txid = settlement.txid.to_string(),
+                                        outcome = settlement.outcome.as_deref().unwrap_or("refund"),
+                                        "Counterparty settled the contract."
+                                    );
+                                }
+                                Ok(None) => tracing::debug!(contract_id = hex::encode(reject.channel_id), "Settlement notice is not for a confirmed contract with the counterparty."),
+                                Err(e) => tracing::warn!(error=?e, "Could not verify settlement notice."),
+                            }
+                            return None;
                         }
+                        RejectReason::Rejected => (),
                     }
+                }
+
+                if let Message::Offer(offer) = message {
+                    if let Err(e) = check_received_offer(transport.as_ref(), offer, &counter_party, require_signed_offers) {
+                        tracing::warn!(counter_party = counter_party.to_string(), error=?e, "Refused DLC offer.");
+                        return None;
+                    }
+                }
 
-                    if transport.has_pending_messages() {
-                        transport.process_messages()
+                if let Err(e) = check_terms(manager.get_store().as_ref(), message, counter_party) {
+                    tracing::warn!(error=?e, "Refused DLC message.");
+                    return None;
+                }
+                if let Err(e) = check_adaptor_signatures(manager.get_store().as_ref(), adaptor_verifier, message) {
+                    tracing::warn!(error=?e, "Refused DLC message.");
+                    return None;
+                }
+
+                let response = match manager.on_dlc_message(message, counter_party) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        tracing::error!(error=?e, "Could not process DLC message.");
+                        return None;
+                    }
+                };
+                // Our signatures let the counterparty fund the contract, so they are only
+                // sent for a refund transaction that matches the offer.
+                if let Some(Message::Sign(sign)) = &response {
+                    if let Err(e) = check_refund(manager.get_store().as_ref(), sign) {
+                        tracing::error!(error=?e, "Withheld sign message. The contract failed.");
+                        return None;
+                    }
+                }
+                // The manager has stored the new contract state, including any adaptor
+                // signatures. Make sure it is on disk before the counterparty acts on it.
+                if let Some(response) = &response {
+                    if let Err(e) = record_outbound_message(manager.get_store().as_ref(), counter_party, response) {
+                        tracing::error!(error=?e, "Could not record response for resending.");
+                    }
+                    if let Message::Sign(sign) = response {
+                        delivery_retries.track(sign.contract_id, counter_party, unix_time());
+                    }
+                    if let Err(e) = manager.get_store().flush() {
+                        tracing::error!(error=?e, "Could not flush storage before responding.");
                     }
                 }
+                response
+            });
+
+            let mut peers = peer_transports.write().unwrap();
+            for counter_party in counter_parties {
+                peers.insert(counter_party, transport.clone());
             }
         }
+    }
 
+    /// Tell counterparties that their contracts were settled, over the transport they were
+    /// last heard from on.
+    fn notify_settlements(
+        settled: Vec<(ContractId, PublicKey)>,
+        transports: &[Arc<T>],
+        peer_transports: &RwLock<HashMap<PublicKey, Arc<T>>>,
+        runtime: &Handle,
+    ) {
+        for (contract_id, counter_party) in settled {
+            let transport = peer_transports.read().unwrap().get(&counter_party).cloned();
+            let Some(transport) = transport.or_else(|| transports.first().cloned()) else {
+                continue;
+            };
+            let notice = Message::Reject(Reject { channel_id: contract_id, timestamp: unix_time() });
+            match runtime.block_on(transport.send_message(counter_party, notice)) {
+                Ok(receipt) => tracing::debug!(contract_id = hex::encode(contract_id), ?receipt, "Sent settlement notice."),
+                Err(e) => tracing::warn!(contract_id = hex::encode(contract_id), error=?e, "Could not send settlement notice."),
+            }
+        }
     }
 
     pub fn connect_if_necessary(&self) -> anyhow::Result<()> {
@@ -154,19 +654,370 @@ where
         self.network
     }
 
-    pub fn send_dlc_offer(
+    /// Retrieve an oracle announcement, reusing a stored copy signed by the oracle if there is
+    /// one. Announcements fetched from the oracle are verified before they are stored.
+    pub async fn get_verified_announcement(&self, event_id: &str) -> anyhow::Result<OracleAnnouncement> {
+        match self.storage.get_announcement(event_id) {
+            Ok(Some(announcement)) if announcement.oracle_public_key == dlc_manager::Oracle::get_public_key(self.oracle.as_ref()) => {
+                return Ok(announcement)
+            }
+            Ok(_) => (),
+            Err(e) => tracing::warn!(event_id, error=?e, "Stored announcement is not trusted. Fetching from oracle."),
+        }
+
+        let announcement = fetch_announcement(self.oracle.as_ref(), event_id, OracleRetry::default()).await?;
+        announcement
+            .validate(&Secp256k1::verification_only())
+            .map_err(|e| anyhow!("Oracle announcement is invalid. {:?}", e))?;
+        self.storage.save_announcement(&announcement)?;
+
+        Ok(announcement)
+    }
+
+    /// Announcements of the oracle that match `filter`. Empty if the oracle cannot list its
+    /// events.
+    pub async fn list_oracle_events(&self, filter: &EventFilter) -> anyhow::Result<Vec<OracleAnnouncement>> {
+        let events = self
+            .oracle
+            .list_events(filter)
+            .await
+            .map_err(|e| anyhow!("Could not list oracle events. {:?}", e))?;
+        Ok(events.unwrap_or_default())
+    }
+
+    /// Store a contract serialized with [crate::util::serialize_contract], to recover it from a
+    /// backup. Refuses to replace a stored contract with the same id unless `overwrite` is
+    /// set and the stored contract is terminal. See [DdkStorage::import_contract].
+    ///
+    /// The chain monitor watches the funding of an imported signed contract, and a periodic
+    /// check runs for an imported confirmed contract so it settles or refunds without waiting
+    /// for the next scheduled one.
+    pub fn import_contract(&self, contract: &[u8], overwrite: bool) -> anyhow::Result<Contract> {
+        let contract = deserialize_contract_bytes(&contract.to_vec())
+            .map_err(|e| anyhow!("Could not read contract. {:?}", e))?;
+        let (responder, receiver) = unbounded();
+        self.sender
+            .send(DlcManagerMessage::ImportContract { contract: Box::new(contract.clone()), overwrite, responder })
+            .expect("couldnt send import");
+        receiver.recv().expect("couldnt import contract")?;
+        if matches!(contract, Contract::Confirmed(_)) {
+            self.sender.send(DlcManagerMessage::PeriodicCheck).expect("couldn't send periodic check");
+        }
+        tracing::info!(
+            contract_id = hex::encode(contract.get_id()),
+            state = crate::util::contract_state(&contract),
+            overwrite,
+            "Imported contract."
+        );
+        Ok(contract)
+    }
+
+    /// Collateral at risk in the signed and confirmed contracts. Contracts whose oracle
+    /// already attested are marked to the attested outcome.
+    pub async fn portfolio(&self) -> anyhow::Result<Portfolio> {
+        let contracts = self.storage.get_contracts()?;
+        let event_ids = contracts
+            .iter()
+            .filter(|c| matches!(c, Contract::Signed(_) | Contract::Confirmed(_)))
+            .flat_map(oracle_event_ids)
+            .collect::<std::collections::BTreeSet<_>>();
+
+        let mut reading = HashMap::new();
+        for event_id in event_ids {
+            let oracle = self.oracle.clone();
+            let id = event_id.clone();
+            // Oracle clients answer attestations with blocking requests.
+            let attestation = tokio::task::spawn_blocking(move || oracle.get_attestation(&id)).await?;
+            let Ok(attestation) = attestation else {
+                continue;
+            };
+            let outcome = contracts
+                .iter()
+                .filter(|c| oracle_event_ids(c).contains(&event_id))
+                .find_map(|c| attested_outcome(c, &attestation));
+            if let Some(outcome) = outcome {
+                reading.insert(event_id, outcome);
+            }
+        }
+        portfolio(&contracts, &reading)
+    }
+
+    /// Hold the CET of a contract back for lower fees with `delay`, instead of the delay set
+    /// on the builder. See [crate::builder::DdkBuilder::set_settlement_delay].
+    pub fn set_settlement_delay(&self, contract_id: &ContractId, delay: SettlementDelay) -> anyhow::Result<()> {
+        if self.storage.get_contract(contract_id)?.is_none() {
+            return Err(anyhow!("Contract {} not found.", hex::encode(contract_id)));
+        }
+        self.storage.save_settlement_delay(contract_id, &delay)
+    }
+
+    /// Force buffered storage writes to disk. Call before shutting down.
+    pub fn flush(&self) -> anyhow::Result<()> {
+        self.storage.flush()?;
+        tracing::info!("Flushed storage.");
+        Ok(())
+    }
+
+    /// Withdraw an offer that the counterparty has not accepted yet. The offer is marked
+    /// rejected locally and the counterparty is sent a [Reject] for the offer id, which it reads
+    /// as [RejectReason::OfferCancelled]. Fails if an accept for the offer was already received. Cancelling a partial offer stops further fills;
+    /// offers already sent for fills are not affected.
+    pub async fn cancel_offer(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        let filling = self.storage.get_partial_offer(contract_id)?.is_some()
+            && matches!(self.storage.get_contract(contract_id)?, Some(Contract::Rejected(_)));
+        if !filling {
+            self.withdraw_offer(contract_id).await?;
+        }
+        self.storage.delete_partial_offer(contract_id)
+    }
+
+    async fn withdraw_offer(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        let (responder, receiver) = unbounded();
+        self.sender.send(DlcManagerMessage::CancelOffer { contract: *contract_id, responder }).expect("couldnt send cancel");
+        let counter_party = receiver.recv().expect("couldnt cancel offer")?;
+        self.storage.delete_offer_expiry(contract_id)?;
+
+        let reject = Reject { channel_id: *contract_id, timestamp: unix_time() };
+        self.deliver(counter_party, Message::Reject(reject)).await?;
+        tracing::info!(
+            counterparty = counter_party.to_string(),
+            contract_id = hex::encode(contract_id),
+            "Cancelled DLC offer."
+        );
+
+        Ok(())
+    }
+
+    /// Rebroadcast the funding transaction of a signed contract that has not confirmed yet.
+    /// Returns the funding txid if the chain backend accepted the transaction.
+    pub fn rebroadcast_funding(&self, contract_id: &ContractId) -> anyhow::Result<Txid> {
+        let contract = self
+            .storage
+            .get_contract(contract_id)?
+            .ok_or_else(|| anyhow!("Contract not found."))?;
+
+        let fund = match contract {
+            Contract::Signed(signed) => signed.accepted_contract.dlc_transactions.fund,
+            Contract::Confirmed(_) => return Err(anyhow!("Funding transaction is already confirmed.")),
+            _ => return Err(anyhow!("Contract does not have a funding transaction to broadcast.")),
+        };
+
+        self.blockchain.send_transaction(&fund)?;
+        let txid = fund.compute_txid();
+        tracing::info!(contract_id = hex::encode(contract_id), txid = txid.to_string(), "Rebroadcast funding transaction.");
+
+        Ok(txid)
+    }
+
+    /// Rebuild the funding output of a contract from the fund pubkeys of both parties and check
+    /// it against the funding transaction on chain. See [crate::util::verify_funding_output].
+    pub fn verify_funding_output(&self, contract_id: &ContractId) -> anyhow::Result<FundingOutputInfo> {
+        let contract = self
+            .storage
+            .get_contract(contract_id)?
+            .ok_or_else(|| anyhow!("Contract not found."))?;
+        let outpoint = funding_outpoint(&contract)
+            .ok_or_else(|| anyhow!("Contract does not have a funding transaction."))?;
+        let funding_transaction = self.blockchain.get_transaction(&outpoint.txid)?;
+        verify_funding_output(&contract, &funding_transaction, self.network)
+    }
+
+    /// Export the DLC messages of a contract in their standard wire encoding, for backup or to
+    /// move it to another rust-dlc based wallet. See [crate::util::export_contract_standard].
+    pub fn export_contract_standard(&self, contract_id: &ContractId) -> anyhow::Result<Vec<u8>> {
+        let contract = self
+            .storage
+            .get_contract(contract_id)?
+            .ok_or_else(|| anyhow!("Contract not found."))?;
+        crate::util::export_contract_standard(&contract)
+    }
+
+    /// Rebuild a contract from messages written by [Self::export_contract_standard]. Only an
+    /// offer can be rebuilt. It is imported as an offer received from `counter_party`, the same
+    /// as [Self::import_offer]. An accepted or signed contract also needs the adaptor
+    /// information and signatures derived from the keys of the wallet that negotiated it, so
+    /// restore those with [Self::import_contract] instead.
+    pub fn import_contract_standard(&self, bytes: &[u8], counter_party: PublicKey) -> anyhow::Result<ContractId> {
+        let standard = crate::util::import_contract_standard(bytes)?;
+        if standard.accept.is_some() {
+            return Err(anyhow!(
+                "An {} contract cannot be rebuilt from its messages. Import its serialized contract.",
+                standard.state()
+            ));
+        }
+        let (responder, receiver) = unbounded();
+        self.sender
+            .send(DlcManagerMessage::ImportOffer { offer: standard.offer, counter_party, responder })
+            .expect("couldnt send import");
+        let contract_id = receiver.recv().expect("couldnt import offer")?;
+        tracing::info!(contract_id = hex::encode(contract_id), "Imported DLC offer from standard messages.");
+        Ok(contract_id)
+    }
+
+    /// Settle a confirmed contract with an attestation supplied by hand instead of waiting for
+    /// the oracle, and broadcast the CET. The attestation is verified against the contract's
+    /// announcement, so it cannot settle on an outcome the oracle did not sign. For regtest
+    /// and dispute testing. Only available with the `dev` feature.
+    #[cfg(feature = "dev")]
+    pub fn force_attestation(
+        &self,
+        contract_id: &ContractId,
+        attestation: OracleAttestation,
+    ) -> anyhow::Result<Contract> {
+        let (responder, receiver) = unbounded();
+        self.sender
+            .send(DlcManagerMessage::ForceAttestation { contract: *contract_id, attestation, responder })
+            .expect("couldnt send forced attestation");
+        let contract = receiver.recv().expect("no forced attestation result")?;
+        self.storage.flush()?;
+        tracing::warn!(contract_id = hex::encode(contract_id), "Settled contract with a forced attestation.");
+        Ok(contract)
+    }
+
+    /// The contract id that `id` is a unique prefix of, like a short git hash. A full id is
+    /// returned as is, whether or not a contract is stored under it.
+    pub fn resolve_contract_id(&self, id: &str) -> anyhow::Result<ContractId> {
+        resolve_contract_id(self.storage.as_ref(), id)
+    }
+
+    /// Most contracts that can be active at once. See [crate::builder::DdkBuilder::set_max_active_contracts].
+    pub fn max_active_contracts(&self) -> Option<usize> {
+        self.max_active_contracts
+    }
+
+    /// Most collateral, in sats, this node commits to a single contract. See
+    /// [crate::builder::DdkBuilder::set_max_contract_collateral].
+    pub fn max_contract_collateral(&self) -> Option<u64> {
+        self.max_contract_collateral
+    }
+
+    /// Whether `counter_party` is reachable on any of the transports, to tell a stalled
+    /// negotiation from an offline peer. `None` if no transport can tell.
+    pub fn counterparty_online(&self, counter_party: &PublicKey) -> Option<bool> {
+        counterparty_online(&self.transports, counter_party)
+    }
+
+    /// The transport a counterparty was last heard from on, or the primary transport.
+    pub(crate) fn transport_for(&self, counter_party: &PublicKey) -> Arc<T> {
+        self.peer_transports
+            .read()
+            .unwrap()
+            .get(counter_party)
+            .cloned()
+            .unwrap_or_else(|| self.transport.clone())
+    }
+
+    /// Send a message over the transport the counterparty was last heard from on. The message
+    /// is recorded first so that it can be resent if it does not arrive.
+    pub(crate) async fn deliver(&self, counter_party: PublicKey, message: Message) -> anyhow::Result<MessageReceipt> {
+        record_outbound_message(self.storage.as_ref(), counter_party, &message)?;
+        let receipt = self
+            .transport_for(&counter_party)
+            .send_message(counter_party, message)
+            .await?;
+        tracing::debug!(counterparty = counter_party.to_string(), ?receipt, "Sent DLC message.");
+        Ok(receipt)
+    }
+
+    /// The offer message of a contract, in the wire encoding of [OfferDlc], to share the offer
+    /// without a transport. See [crate::util::encode_offer].
+    pub fn export_offer(&self, contract_id: &ContractId) -> anyhow::Result<Vec<u8>> {
+        let contract = self
+            .storage
+            .get_contract(contract_id)?
+            .ok_or_else(|| anyhow!("Contract not found."))?;
+        crate::util::encode_offer(&contract)
+    }
+
+    /// Take in an offer exported by `counter_party` as if it had arrived over a transport.
+    /// Returns the id the offer is stored under, to accept it with.
+    pub fn import_offer(&self, offer: &[u8], counter_party: PublicKey) -> anyhow::Result<ContractId> {
+        let offer = crate::util::decode_offer(offer)?;
+        let (responder, receiver) = unbounded();
+        self.sender
+            .send(DlcManagerMessage::ImportOffer { offer, counter_party, responder })
+            .expect("couldnt send import");
+        let contract_id = receiver.recv().expect("couldnt import offer")?;
+        tracing::info!(
+            counterparty = counter_party.to_string(),
+            contract_id = hex::encode(contract_id),
+            "Imported DLC offer."
+        );
+        Ok(contract_id)
+    }
+
+    /// Send the last message of a contract again, for a counterparty that did not receive it.
+    /// Returns the receipt of the new delivery.
+    pub async fn resend_last_message(&self, contract_id: &ContractId) -> anyhow::Result<MessageReceipt> {
+        let (counter_party, message) = last_outbound_message(self.storage.as_ref(), contract_id)?;
+        tracing::info!(
+            contract_id = hex::encode(contract_id),
+            counterparty = counter_party.to_string(),
+            "Resending last DLC message."
+        );
+        self.deliver(counter_party, message).await
+    }
+
+    /// Send a dead-lettered message again and clear the delivery failed flag of its contract.
+    /// The message gets its full attempts again. See [crate::delivery].
+    pub async fn retry_dead_letter(&self, contract_id: &ContractId) -> anyhow::Result<MessageReceipt> {
+        let dead_letter = self
+            .storage
+            .get_dead_letter(contract_id)?
+            .ok_or_else(|| ContractError::NoDeadLetter(hex::encode(contract_id)))?;
+        self.storage.delete_dead_letter(&dead_letter.contract_id)?;
+        tracing::info!(
+            contract_id = hex::encode(contract_id),
+            attempts = dead_letter.attempts,
+            "Retrying dead-lettered DLC message."
+        );
+        let receipt = self.resend_last_message(contract_id).await?;
+        self.delivery_retries.track(dead_letter.contract_id, dead_letter.counter_party, unix_time());
+        Ok(receipt)
+    }
+
+    /// Send an offer to a counterparty. The offer is abandoned if it is not accepted
+    /// before `offer_expiry` elapses, which defaults to [crate::DEFAULT_OFFER_EXPIRY_SECS].
+    pub async fn send_dlc_offer(
+        &self,
+        contract_input: &ContractInput,
+        counter_party: PublicKey,
+        oracle_announcements: Vec<OracleAnnouncement>,
+        offer_expiry: Option<Duration>,
+    ) -> anyhow::Result<OfferDlc> {
+        self.send_dlc_offer_from_utxos(contract_input, counter_party, oracle_announcements, offer_expiry, Vec::new())
+            .await
+    }
+
+    /// Send an offer funded only by `utxos`. Fails if they are not spendable utxos of the
+    /// wallet or do not cover the offer collateral and fees. An empty list lets coin selection
+    /// choose.
+    pub async fn send_dlc_offer_from_utxos(
         &self,
         contract_input: &ContractInput,
         counter_party: PublicKey,
         oracle_announcements: Vec<OracleAnnouncement>,
+        offer_expiry: Option<Duration>,
+        utxos: Vec<OutPoint>,
     ) -> anyhow::Result<OfferDlc> {
+        check_collateral_cap(contract_input.offer_collateral, self.max_contract_collateral)?;
+        let cost = ddk_payouts::estimate_total_cost(contract_input, contract_input.fee_rate);
+        check_funding_cost(&cost, self.spendable_for_funding(&utxos)?)?;
+        let serial_id_secret = if self.deterministic_serial_ids {
+            Some(self.wallet.serial_id_secret()?)
+        } else {
+            None
+        };
         let (responder, receiver) = unbounded();
-        self.sender.send(DlcManagerMessage::OfferDlc { contract_input: contract_input.to_owned(), counter_party, oracle_announcements, responder }).expect("sending offer message");
-        let offer = receiver.recv().expect("no offer dlc");
+        self.sender.send(DlcManagerMessage::OfferDlc { contract_input: contract_input.to_owned(), counter_party, oracle_announcements, utxos, serial_id_secret, responder }).expect("sending offer message");
+        let offer = receiver.recv().expect("no offer dlc")?;
+
+        let offer_expiry = offer_expiry.unwrap_or(Duration::from_secs(DEFAULT_OFFER_EXPIRY_SECS));
+        self.storage
+            .save_offer_expiry(&offer.temporary_contract_id, unix_time() + offer_expiry.as_secs())?;
 
         let contract_id = hex::encode(&offer.temporary_contract_id);
-        self.transport
-            .send_message(counter_party, Message::Offer(offer.clone()));
+        self.deliver(counter_party, Message::Offer(offer.clone())).await?;
         tracing::info!(
             counterparty = counter_party.to_string(),
             contract_id,
@@ -176,16 +1027,128 @@ where
         Ok(offer)
     }
 
-    pub fn accept_dlc_offer(
+    /// Sats that can fund a contract: the value of `utxos`, or of the confirmed balance not
+    /// reserved for pending contracts when coin selection chooses.
+    fn spendable_for_funding(&self, utxos: &[OutPoint]) -> anyhow::Result<u64> {
+        if utxos.is_empty() {
+            let confirmed = self.wallet.get_balance()?.confirmed;
+            return Ok(confirmed.to_sat().saturating_sub(self.wallet.reserved_balance()?.to_sat()));
+        }
+        Ok(self
+            .wallet
+            .list_utxos()?
+            .iter()
+            .filter(|utxo| utxos.contains(&utxo.outpoint))
+            .map(|utxo| utxo.txout.value.to_sat())
+            .sum())
+    }
+
+    /// Send an offer whose funding transaction pays the fee rate the wallet estimates to be
+    /// mined within `target_blocks`. The fee rate of `contract_input` is replaced, and the
+    /// target is stored with the offer.
+    pub async fn send_dlc_offer_for_target(
+        &self,
+        contract_input: &ContractInput,
+        counter_party: PublicKey,
+        oracle_announcements: Vec<OracleAnnouncement>,
+        offer_expiry: Option<Duration>,
+        utxos: Vec<OutPoint>,
+        target_blocks: u16,
+    ) -> anyhow::Result<OfferDlc> {
+        let mut contract_input = contract_input.to_owned();
+        contract_input.fee_rate = fee_rate_for_target(self.wallet.as_ref(), target_blocks);
+        tracing::info!(target_blocks, fee_rate = contract_input.fee_rate, "Estimated funding fee rate.");
+        let offer = self
+            .send_dlc_offer_from_utxos(&contract_input, counter_party, oracle_announcements, offer_expiry, utxos)
+            .await?;
+        self.storage.save_funding_target(&offer.temporary_contract_id, target_blocks)?;
+        Ok(offer)
+    }
+
+    /// Send an offer that can also be filled in parts with [DlcDevKit::fill_offer]. The offer
+    /// stays fillable until its full accept collateral is filled or it is cancelled.
+    pub async fn send_partial_offer(
+        &self,
+        contract_input: &ContractInput,
+        counter_party: PublicKey,
+        oracle_announcements: Vec<OracleAnnouncement>,
+        offer_expiry: Option<Duration>,
+    ) -> anyhow::Result<OfferDlc> {
+        scale_contract_input(contract_input, contract_input.accept_collateral)?;
+        let offer = self.send_dlc_offer(contract_input, counter_party, oracle_announcements.clone(), offer_expiry).await?;
+        self.storage.save_partial_offer(
+            &offer.temporary_contract_id,
+            &PartialOffer::new(contract_input.to_owned(), oracle_announcements),
+        )?;
+        Ok(offer)
+    }
+
+    /// Fill part of a partial offer by sending `counter_party` its own offer for
+    /// `accept_collateral` of it. The offer for the whole amount is withdrawn on the first fill.
+    pub async fn fill_offer(
+        &self,
+        contract_id: &ContractId,
+        counter_party: PublicKey,
+        accept_collateral: u64,
+        offer_expiry: Option<Duration>,
+    ) -> anyhow::Result<OfferDlc> {
+        if matches!(self.storage.get_contract(contract_id)?, Some(Contract::Offered(_)))
+            && self.storage.get_partial_offer(contract_id)?.is_some()
+        {
+            self.withdraw_offer(contract_id).await?;
+        }
+
+        let (contract_input, mut partial) = reserve_fill(self.storage.as_ref(), contract_id, accept_collateral)?;
+        let offer = match self.send_dlc_offer(&contract_input, counter_party, partial.oracle_announcements.clone(), offer_expiry).await {
+            Ok(offer) => offer,
+            Err(e) => {
+                release_fill(self.storage.as_ref(), contract_id, accept_collateral)?;
+                return Err(e);
+            }
+        };
+        // Read again, as a failed fill may have been released meanwhile.
+        let mut partial = self.storage.get_partial_offer(contract_id)?.unwrap_or(partial);
+        partial.fills.push(offer.temporary_contract_id);
+        self.storage.save_partial_offer(contract_id, &partial)?;
+        tracing::info!(
+            contract_id = hex::encode(contract_id),
+            fill = hex::encode(offer.temporary_contract_id),
+            accept_collateral,
+            remaining_collateral = partial.remaining_collateral,
+            "Filled part of DLC offer."
+        );
+
+        Ok(offer)
+    }
+
+    pub async fn accept_dlc_offer(
         &self,
         contract: [u8; 32],
     ) -> anyhow::Result<(String, String, AcceptDlc)> {
+        if let Some(Contract::Offered(offer)) = self.storage.get_contract(&contract)? {
+            let accept_collateral = offer.total_collateral - offer.offer_params.collateral;
+            check_collateral_cap(accept_collateral, self.max_contract_collateral)?;
+            let cost = ddk_payouts::estimate_party_cost(accept_collateral, offer.fee_rate_per_vb);
+            check_funding_cost(&cost, self.spendable_for_funding(&[])?)?;
+
+            let mut on_chain = HashSet::new();
+            for txid in offer_funding_txids(&offer) {
+                if self.blockchain.async_client.get_tx(&txid).await?.is_some() {
+                    on_chain.insert(txid);
+                }
+            }
+            check_offer_network(&offer, self.network, &on_chain)?;
+        }
+        // Nothing is stored for the accept until the oracle knows the event, so an accept that
+        // fails with [crate::error::OracleError::Unavailable] can be retried.
+        self.check_offer_announcements(&contract).await?;
+
         let (responder, receiver) = unbounded();
         self.sender.send(DlcManagerMessage::AcceptDlc { contract, responder }).expect("couldnt send accept");
-        let (contract_id, public_key, accept_dlc) = receiver.recv().expect("coudlnt accept dlc");
+        let (contract_id, public_key, accept_dlc) = receiver.recv().expect("coudlnt accept dlc")?;
 
-        self.transport
-            .send_message(public_key, Message::Accept(accept_dlc.clone()));
+        self.deliver(public_key, Message::Accept(accept_dlc.clone())).await?;
+        self.delivery_retries.track(contract_id, public_key, unix_time());
 
         let contract_id = hex::encode(&contract_id);
         let counter_party = public_key.to_string();
@@ -193,5 +1156,1647 @@ where
 
         Ok((contract_id, counter_party, accept_dlc))
     }
+
+    /// Fetch the announcements an offer was made with from our oracle, so the contract is only
+    /// accepted while the oracle is reachable, and refuse the offer if one is not what our
+    /// oracle published for `network`. Announcements of other oracles are taken as offered.
+    async fn check_offer_announcements(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        let Some(Contract::Offered(offer)) = self.storage.get_contract(contract_id)? else {
+            return Err(anyhow!("No offer {} to accept.", hex::encode(contract_id)));
+        };
+        let oracle_key = dlc_manager::Oracle::get_public_key(self.oracle.as_ref());
+        let announcements = offer
+            .contract_info
+            .iter()
+            .flat_map(|info| &info.oracle_announcements)
+            .filter(|announcement| announcement.oracle_public_key == oracle_key);
+        for announcement in announcements {
+            let published = self.get_verified_announcement(&announcement.oracle_event.event_id).await?;
+            if published != *announcement {
+                return Err(ContractError::NetworkMismatch {
+                    network: self.network,
+                    reason: format!(
+                        "announcement of event {} is not the one our oracle published",
+                        announcement.oracle_event.event_id
+                    ),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Passes the received messages of a transport to `handler` and sends any response back
+/// over the same transport on `runtime`. Returns the counterparties that messages were
+/// received from.
+pub(crate) fn process_transport_messages<T, F>(transport: &T, runtime: &Handle, mut handler: F) -> Vec<PublicKey>
+where
+    T: DdkTransport,
+    F: FnMut(&Message, PublicKey) -> Option<Message>,
+{
+    let messages = transport.get_and_clear_received_messages();
+    let mut counter_parties = Vec::with_capacity(messages.len());
+
+    for (counter_party, message) in messages {
+        tracing::info!(
+            counter_party = counter_party.to_string(),
+            transport = transport.name(),
+            "Processing DLC message"
+        );
+
+        if let Some(msg) = handler(&message, counter_party) {
+            tracing::info!("Responding to message received.");
+            tracing::debug!(message=?msg);
+            match runtime.block_on(transport.send_message(counter_party, msg)) {
+                Ok(receipt) => tracing::debug!(?receipt, "Sent response."),
+                Err(e) => tracing::error!(error=?e, "Could not send response."),
+            }
+        }
+        counter_parties.push(counter_party);
+    }
+
+    if transport.has_pending_messages() {
+        transport.process_messages()
+    }
+
+    counter_parties
 }
 
+/// Record a sent message as the last message of its contract. Messages without a contract
+/// are not recorded.
+pub(crate) fn record_outbound_message<S: DdkStorage>(
+    storage: &S,
+    counter_party: PublicKey,
+    message: &Message,
+) -> anyhow::Result<()> {
+    if let Some(contract_id) = message_contract_id(message) {
+        storage.save_outbound_message(&contract_id, &counter_party, message)?;
+    }
+    Ok(())
+}
+
+/// The last message sent for a contract and its counterparty.
+pub(crate) fn last_outbound_message<S: DdkStorage>(
+    storage: &S,
+    contract_id: &ContractId,
+) -> anyhow::Result<(PublicKey, Message)> {
+    storage
+        .get_outbound_message(contract_id)?
+        .ok_or_else(|| ContractError::NoOutboundMessage(hex::encode(contract_id)).into())
+}
+
+/// Messages to send again on start for negotiations a crash may have cut short: the accept
+/// of an offer we accepted that was never signed, and the sign message of an offer we signed
+/// that is not funded yet. The counterparty may not have received either. A sign message
+/// that was never recorded is rebuilt from the signed contract. Each message comes with the
+/// final id of its contract.
+pub(crate) fn interrupted_negotiations<S: DdkStorage>(
+    storage: &S,
+) -> anyhow::Result<Vec<(ContractId, PublicKey, Message)>> {
+    let mut messages = Vec::new();
+    for contract in storage.get_contracts()? {
+        match contract {
+            Contract::Accepted(accepted) => {
+                let contract_id = accepted.get_contract_id();
+                if let Some((counter_party, message)) = storage.get_outbound_message(&contract_id)? {
+                    messages.push((contract_id, counter_party, message));
+                }
+            }
+            Contract::Signed(signed) if signed.accepted_contract.offered_contract.is_offer_party => {
+                let contract_id = signed.accepted_contract.get_contract_id();
+                match storage.get_outbound_message(&contract_id)? {
+                    Some((counter_party, message @ Message::Sign(_))) => {
+                        messages.push((contract_id, counter_party, message))
+                    }
+                    _ => {
+                        if let Some(sign) = rebuild_sign_message(&signed) {
+                            let counter_party = signed.accepted_contract.offered_contract.counter_party;
+                            messages.push((contract_id, counter_party, Message::Sign(sign)));
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(messages)
+}
+
+/// The sign message of a contract we offered, from the signatures stored with it. `None`
+/// without our CET adaptor signatures.
+pub(crate) fn rebuild_sign_message(signed: &SignedContract) -> Option<SignDlc> {
+    let adaptor_signatures = signed.adaptor_signatures.as_ref()?;
+    Some(SignDlc {
+        protocol_version: crate::DLC_PROTOCOL_VERSION,
+        contract_id: signed.accepted_contract.get_contract_id(),
+        cet_adaptor_signatures: adaptor_signatures.as_slice().into(),
+        refund_signature: signed.offer_refund_signature,
+        funding_signatures: signed.funding_signatures.clone(),
+    })
+}
+
+/// Move signed contracts to failed sign when `spender`, which gives the transaction spending
+/// an outpoint in the mempool or chain, reports one of their funding inputs spent by a
+/// transaction other than the funding transaction. Failing the contract releases the rest of
+/// its funding inputs. Returns the conflicts found.
+pub(crate) fn fail_conflicted_contracts<S: DdkStorage>(
+    storage: &S,
+    spender: impl Fn(&OutPoint) -> anyhow::Result<Option<Txid>>,
+) -> anyhow::Result<Vec<FundingConflict>> {
+    let mut conflicts = Vec::new();
+    for signed in storage.get_signed_contracts()? {
+        let fund = &signed.accepted_contract.dlc_transactions.fund;
+        let funding_txid = fund.compute_txid();
+        let mut conflict = None;
+        for input in &fund.input {
+            match spender(&input.previous_output)? {
+                Some(txid) if txid != funding_txid => {
+                    conflict = Some((input.previous_output, txid));
+                    break;
+                }
+                _ => (),
+            }
+        }
+        let Some((outpoint, conflicting_txid)) = conflict else {
+            continue;
+        };
+
+        let contract_id = signed.accepted_contract.get_contract_id();
+        let conflict = FundingConflict { contract_id, outpoint, conflicting_txid };
+        storage.save_funding_conflict(&conflict)?;
+        let sign_message = signed.get_sign_dlc(signed.adaptor_signatures.clone().unwrap_or_default());
+        storage.update_contract(&Contract::FailedSign(FailedSignContract {
+            accepted_contract: signed.accepted_contract,
+            sign_message,
+            error_message: format!("Funding input {outpoint} was spent by {conflicting_txid}."),
+        }))?;
+        conflicts.push(conflict);
+    }
+    Ok(conflicts)
+}
+
+/// Funding transactions of signed contracts that have not reached the required confirmations.
+pub(crate) fn unconfirmed_funding_transactions<S: DdkStorage>(
+    storage: &S,
+) -> anyhow::Result<Vec<(ContractId, Transaction)>> {
+    Ok(storage
+        .get_signed_contracts()?
+        .into_iter()
+        .map(|signed| {
+            (
+                signed.accepted_contract.get_contract_id(),
+                signed.accepted_contract.dlc_transactions.fund,
+            )
+        })
+        .collect())
+}
+
+/// Close a confirmed contract with `attestation` once it is verified against the
+/// announcement of the same oracle in the contract.
+#[cfg(feature = "dev")]
+fn force_attestation<S: DdkStorage, O: DdkOracle>(
+    manager: &DlcDevKitDlcManager<S, O>,
+    contract_id: &ContractId,
+    attestation: OracleAttestation,
+) -> anyhow::Result<Contract> {
+    let Some(Contract::Confirmed(contract)) = manager.get_store().get_contract(contract_id)? else {
+        return Err(anyhow!("Only confirmed contracts can be settled with an attestation."));
+    };
+    let (index, announcement) = contract
+        .accepted_contract
+        .offered_contract
+        .contract_info
+        .iter()
+        .flat_map(|info| info.oracle_announcements.iter().enumerate())
+        .find(|(_, announcement)| announcement.oracle_public_key == attestation.oracle_public_key)
+        .ok_or_else(|| anyhow!("The attestation is not from an oracle of the contract."))?;
+    crate::oracle::verify_attestation(announcement, &attestation)?;
+
+    Ok(manager.close_confirmed_contract(contract_id, vec![(index, attestation)])?)
+}
+
+/// Whether any contract still waits on the chain, an oracle or an expiry. Channels are
+/// always checked.
+pub(crate) fn needs_periodic_check<S: DdkStorage>(storage: &S) -> anyhow::Result<bool> {
+    if cfg!(feature = "channels") {
+        return Ok(true);
+    }
+    Ok(storage.get_contracts()?.iter().any(|contract| {
+        matches!(
+            contract,
+            Contract::Offered(_)
+                | Contract::Accepted(_)
+                | Contract::Signed(_)
+                | Contract::Confirmed(_)
+                | Contract::PreClosed(_)
+        )
+    }))
+}
+
+/// Watches the funding outpoints of signed contracts that wait on confirmations and stops
+/// watching those of contracts that moved on. `watched` holds the outpoints registered so far.
+/// While the monitor watches an outpoint, the periodic check reads the confirmations of its
+/// transaction from what the monitor reported instead of polling esplora.
+pub(crate) fn watch_funding_outpoints<S: DdkStorage>(
+    storage: &S,
+    monitor: &dyn ChainMonitor,
+    blockchain: &EsploraClient,
+    watched: &mut HashSet<OutPoint>,
+) -> anyhow::Result<()> {
+    let pending = storage
+        .get_signed_contracts()?
+        .into_iter()
+        .filter_map(|contract| funding_outpoint(&Contract::Signed(contract)))
+        .collect::<HashSet<_>>();
+    for outpoint in pending.difference(watched) {
+        monitor.register_outpoint(*outpoint);
+        blockchain.watch_confirmations(outpoint.txid);
+    }
+    for outpoint in watched.difference(&pending) {
+        monitor.unregister_outpoint(outpoint);
+        blockchain.unwatch_confirmations(&outpoint.txid);
+    }
+    *watched = pending;
+    Ok(())
+}
+
+/// Moves the signed contract funded at the outpoint of `confirmation` to confirmed once
+/// [NB_CONFIRMATIONS] blocks are on top of its funding transaction, as the periodic check
+/// would. Returns the id of the contract that moved. No other contract is read or written.
+pub(crate) fn confirm_funding<S: DdkStorage>(
+    storage: &S,
+    confirmation: &Confirmation,
+) -> anyhow::Result<Option<ContractId>> {
+    // The monitor counts the block the transaction is in, the manager only those on top.
+    if confirmation.confirmations.saturating_sub(1) < NB_CONFIRMATIONS {
+        return Ok(None);
+    }
+    let signed = storage
+        .get_signed_contracts()?
+        .into_iter()
+        .find(|contract| contract.accepted_contract.dlc_transactions.get_fund_outpoint() == confirmation.outpoint);
+    let Some(signed) = signed else {
+        return Ok(None);
+    };
+    let contract_id = signed.accepted_contract.get_contract_id();
+    storage.update_contract(&Contract::Confirmed(signed))?;
+    Ok(Some(contract_id))
+}
+
+/// Hands each confirmation the chain monitor reports to the manager thread, so the contract
+/// it funds advances without waiting for the next periodic check.
+pub(crate) async fn forward_confirmations(
+    mut confirmations: UnboundedReceiver<Confirmation>,
+    checker: Arc<Sender<DlcManagerMessage>>,
+) {
+    while let Some(confirmation) = confirmations.recv().await {
+        tracing::debug!(
+            outpoint = confirmation.outpoint.to_string(),
+            confirmations = confirmation.confirmations,
+            "Funding transaction confirmed."
+        );
+        if checker.send(DlcManagerMessage::FundingConfirmed(confirmation)).is_err() {
+            return;
+        }
+    }
+}
+
+pub(crate) fn resolve_contract_id<S: DdkStorage>(storage: &S, id: &str) -> anyhow::Result<ContractId> {
+    let prefix = id.to_lowercase();
+    if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ContractError::InvalidId(id.to_string()).into());
+    }
+    if prefix.len() == 64 {
+        let mut contract_id = [0u8; 32];
+        hex::decode_to_slice(&prefix, &mut contract_id)?;
+        return Ok(contract_id);
+    }
+
+    let mut matches = storage
+        .get_contracts()?
+        .iter()
+        .map(|contract| contract.get_id())
+        .filter(|contract_id| hex::encode(contract_id).starts_with(&prefix))
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches.dedup();
+    match matches.as_slice() {
+        [] => Err(ContractError::UnknownContract(id.to_string()).into()),
+        [contract_id] => Ok(*contract_id),
+        _ => Err(ContractError::AmbiguousPrefix {
+            prefix: id.to_string(),
+            matches: matches.iter().map(hex::encode).collect(),
+        }
+        .into()),
+    }
+}
+
+/// Fails with [ContractError::ContractLimitReached] if a new contract would take the active
+/// contracts beyond `limit`. Active are our own offers and contracts accepted to confirmed.
+/// Offers we received are not counted: they take nothing of ours until we accept one.
+pub(crate) fn check_contract_limit<S: DdkStorage>(storage: &S, limit: Option<usize>) -> anyhow::Result<()> {
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+    let active = storage
+        .get_contracts()?
+        .iter()
+        .filter(|contract| match contract {
+            Contract::Offered(offered) => offered.is_offer_party,
+            Contract::Accepted(_) | Contract::Signed(_) | Contract::Confirmed(_) => true,
+            _ => false,
+        })
+        .count();
+    if active >= limit {
+        return Err(ContractError::ContractLimitReached { active, limit }.into());
+    }
+    Ok(())
+}
+
+/// Fails if `available` sats do not cover the collateral and fees of `cost`.
+pub(crate) fn check_funding_cost(cost: &TotalCost, available: u64) -> anyhow::Result<()> {
+    if cost.total() > available {
+        return Err(ContractError::InsufficientFunds { cost: cost.total(), available }.into());
+    }
+    Ok(())
+}
+
+/// Fails if `collateral` is over the per contract `cap`.
+pub(crate) fn check_collateral_cap(collateral: u64, cap: Option<u64>) -> anyhow::Result<()> {
+    match cap {
+        Some(cap) if collateral > cap => Err(ContractError::CollateralCapExceeded { collateral, cap }.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Seconds since the unix epoch.
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before unix epoch")
+        .as_secs()
+}
+
+/// Moves offers that are past their expiry to [Contract::Rejected] so they are no longer
+/// considered open. Returns the ids of the abandoned offers.
+pub(crate) fn abandon_expired_offers<S: DdkStorage>(
+    storage: &S,
+    now: u64,
+) -> anyhow::Result<Vec<ContractId>> {
+    let mut abandoned = Vec::new();
+    for offer in storage.get_contract_offers()? {
+        let Some(expiry) = storage.get_offer_expiry(&offer.id)? else {
+            continue;
+        };
+
+        if now < expiry {
+            continue;
+        }
+
+        // Rejecting the offer releases the utxos reserved for its funding.
+        storage.update_contract(&Contract::Rejected(offer.clone()))?;
+        storage.delete_offer_expiry(&offer.id)?;
+        abandoned.push(offer.id);
+    }
+
+    Ok(abandoned)
+}
+
+/// Returns the collateral of partial fills that will never be funded to their offers. See
+/// [release_failed_fills].
+fn release_fills<S: DdkStorage>(storage: &S) {
+    match release_failed_fills(storage) {
+        Ok(released) => {
+            for fill in released {
+                tracing::info!(fill = hex::encode(fill), "Released the collateral of a fill that will not be funded.");
+            }
+        }
+        Err(e) => tracing::error!(error=?e, "Could not release the collateral of failed fills."),
+    }
+}
+
+/// Marks an offer we sent as rejected, returning the counterparty to notify. This releases the
+/// utxos reserved for its funding.
+pub(crate) fn cancel_offer<S: DdkStorage>(
+    storage: &S,
+    contract_id: &ContractId,
+) -> anyhow::Result<PublicKey> {
+    let offer = match storage.get_contract(contract_id)? {
+        Some(Contract::Offered(offer)) if offer.is_offer_party => offer,
+        Some(Contract::Offered(_)) => return Err(anyhow!("Only offers you sent can be cancelled.")),
+        Some(Contract::Rejected(_)) => return Err(anyhow!("Offer is already cancelled.")),
+        Some(_) => return Err(anyhow!(ACCEPT_RECEIVED)),
+        // Once accepted, the contract is stored under its final id.
+        None if storage
+            .get_contracts()?
+            .iter()
+            .any(|c| c.get_temporary_id() == *contract_id) =>
+        {
+            return Err(anyhow!(ACCEPT_RECEIVED))
+        }
+        None => return Err(anyhow!("Offer not found.")),
+    };
+
+    storage.update_contract(&Contract::Rejected(offer.clone()))?;
+    Ok(offer.counter_party)
+}
+
+const ACCEPT_RECEIVED: &str =
+    "Counterparty already accepted the offer. Reject the accept instead of cancelling.";
+
+/// What a [Reject] from a counterparty stands for. DLC messages have no message to cancel an
+/// offer or announce a settlement, so DDK sends a plain [Reject] for the contract id and the
+/// receiver tells them apart by the contract it holds: only the offer party sends a reject for
+/// an offer we received, and nothing is negotiated on a confirmed contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RejectReason {
+    /// A reject the manager handles.
+    Rejected,
+    /// The offer party withdrew its offer. See [offer_cancelled].
+    OfferCancelled,
+    /// The counterparty broadcast the closing transaction. See [settle_on_notice].
+    Settled,
+}
+
+impl RejectReason {
+    pub(crate) fn of<S: DdkStorage>(storage: &S, reject: &Reject, counter_party: PublicKey) -> anyhow::Result<Self> {
+        Ok(match storage.get_contract(&reject.channel_id)? {
+            Some(Contract::Offered(offer)) if !offer.is_offer_party && offer.counter_party == counter_party => {
+                Self::OfferCancelled
+            }
+            Some(Contract::Confirmed(signed)) if signed.accepted_contract.offered_contract.counter_party == counter_party => {
+                Self::Settled
+            }
+            _ => Self::Rejected,
+        })
+    }
+}
+
+/// Marks an offer received from `counter_party` as rejected after they cancelled it. Returns
+/// false if there is no such open offer.
+pub(crate) fn offer_cancelled<S: DdkStorage>(
+    storage: &S,
+    contract_id: &ContractId,
+    counter_party: PublicKey,
+) -> anyhow::Result<bool> {
+    match storage.get_contract(contract_id)? {
+        Some(Contract::Offered(offer))
+            if !offer.is_offer_party && offer.counter_party == counter_party =>
+        {
+            storage.update_contract(&Contract::Rejected(offer))?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// A counterparty's notice that it broadcast the closing transaction of a contract, once
+/// verified against the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Settlement {
+    pub contract_id: ContractId,
+    pub txid: Txid,
+    /// The outcome the CET pays out for. `None` for the refund transaction.
+    pub outcome: Option<String>,
+}
+
+/// The transaction spending a funding output, from esplora.
+fn funding_spender(blockchain: &EsploraClient, outpoint: &OutPoint) -> anyhow::Result<Option<Transaction>> {
+    let status = blockchain
+        .blocking_client
+        .get_output_status(&outpoint.txid, u64::from(outpoint.vout))?;
+    match status.filter(|status| status.spent).and_then(|status| status.txid) {
+        Some(txid) => Ok(blockchain.blocking_client.get_tx(&txid)?),
+        None => Ok(None),
+    }
+}
+
+/// Contracts among the `confirmed` ones that the last periodic check closed with a CET or
+/// the refund transaction, and their counterparties.
+pub(crate) fn settled_contracts<S: DdkStorage>(
+    storage: &S,
+    confirmed: &[ContractId],
+) -> anyhow::Result<Vec<(ContractId, PublicKey)>> {
+    let mut settled = Vec::new();
+    for contract_id in confirmed {
+        match storage.get_contract(contract_id)? {
+            Some(Contract::PreClosed(p)) => {
+                settled.push((*contract_id, p.signed_contract.accepted_contract.offered_contract.counter_party))
+            }
+            Some(Contract::Closed(c)) if c.signed_cet.is_some() => settled.push((*contract_id, c.counter_party_id)),
+            Some(Contract::Refunded(s)) => {
+                settled.push((*contract_id, s.accepted_contract.offered_contract.counter_party))
+            }
+            _ => (),
+        }
+    }
+    Ok(settled)
+}
+
+/// Settle a confirmed contract on a settlement notice from its counterparty. DLC messages have
+/// no settlement message, so the notice is a [Reject] read as [RejectReason::Settled], and
+/// the closing transaction is the one `spender` finds spending the funding output. It must be
+/// a CET or the refund transaction of the contract. A contract settled by a CET is pre-closed
+/// until the CET confirms. Returns `None` if the notice is not for a confirmed contract with
+/// `counter_party`.
+pub(crate) fn settle_on_notice<S: DdkStorage>(
+    storage: &S,
+    contract_id: &ContractId,
+    counter_party: PublicKey,
+    spender: impl Fn(&OutPoint) -> anyhow::Result<Option<Transaction>>,
+) -> anyhow::Result<Option<Settlement>> {
+    let signed = match storage.get_contract(contract_id)? {
+        Some(Contract::Confirmed(signed)) if signed.accepted_contract.offered_contract.counter_party == counter_party => signed,
+        _ => return Ok(None),
+    };
+    let accepted = &signed.accepted_contract;
+    let outpoint = accepted.dlc_transactions.get_fund_outpoint();
+    let closing_tx = spender(&outpoint)?
+        .ok_or_else(|| anyhow!("Funding output {} of the settled contract is not spent.", outpoint))?;
+    let txid = closing_tx.compute_txid();
+
+    if txid == accepted.dlc_transactions.refund.compute_txid() {
+        storage.update_contract(&Contract::Refunded(signed))?;
+        return Ok(Some(Settlement { contract_id: *contract_id, txid, outcome: None }));
+    }
+
+    let cets = crate::util::cet_list(&Contract::Confirmed(signed.clone()))?;
+    let cet = cets
+        .iter()
+        .find(|cet| cet.txid == txid)
+        .ok_or_else(|| anyhow!("Funding output {} is spent by {}, which is not a CET of the contract.", outpoint, txid))?;
+    let outcome = if cet.first_outcome == cet.last_outcome {
+        cet.first_outcome.clone()
+    } else {
+        format!("{}..={}", cet.first_outcome, cet.last_outcome)
+    };
+
+    // The periodic check closes the contract once the CET has enough confirmations.
+    storage.update_contract(&Contract::PreClosed(PreClosedContract {
+        signed_contract: signed,
+        attestations: None,
+        signed_cet: closing_tx,
+    }))?;
+    Ok(Some(Settlement { contract_id: *contract_id, txid, outcome: Some(outcome) }))
+}
+
+/// Replaces the serial ids the manager drew for the new offer `temporary_id` with ones derived
+/// from `secret`, before the offer is sent. Runs on the manager thread, which stored the offer.
+pub(crate) fn derive_offer_serial_ids<S: DdkStorage>(
+    storage: &S,
+    temporary_id: &ContractId,
+    secret: &[u8; 32],
+) -> anyhow::Result<OfferDlc> {
+    let Some(Contract::Offered(mut offer)) = storage.get_contract(temporary_id)? else {
+        return Err(anyhow!("Offer {} was not stored.", hex::encode(temporary_id)));
+    };
+    set_serial_ids(&mut offer, secret);
+    storage.update_contract(&Contract::Offered(offer.clone()))?;
+    Ok(OfferDlc::from(&offer))
+}
+
+/// Sets the serial ids of the funding outputs and inputs of our `offer` from `secret` and its
+/// temporary id, so the same offer always orders its funding transaction the same way.
+pub(crate) fn set_serial_ids(offer: &mut OfferedContract, secret: &[u8; 32]) {
+    let ids = crate::rng::derive_serial_ids(secret, &offer.id, 3 + offer.funding_inputs.len());
+    offer.fund_output_serial_id = ids[0];
+    offer.offer_params.payout_serial_id = ids[1];
+    offer.offer_params.change_serial_id = ids[2];
+    // Both lists hold the same inputs in the same order.
+    for (input, id) in offer.funding_inputs.iter_mut().zip(&ids[3..]) {
+        input.input_serial_id = *id;
+    }
+    for (input, id) in offer.offer_params.inputs.iter_mut().zip(&ids[3..]) {
+        input.serial_id = *id;
+    }
+}
+
+/// Online if any transport reaches `counter_party`, offline if the transports that can tell
+/// all say it is not.
+pub(crate) fn counterparty_online<T: DdkTransport>(transports: &[Arc<T>], counter_party: &PublicKey) -> Option<bool> {
+    transports
+        .iter()
+        .filter_map(|transport| transport.counterparty_online(counter_party))
+        .reduce(|a, b| a || b)
+}
+
+/// Transactions of the inputs the offerer funds `offer` with.
+pub(crate) fn offer_funding_txids(offer: &OfferedContract) -> Vec<Txid> {
+    offer
+        .funding_inputs
+        .iter()
+        .filter_map(|input| bitcoin::consensus::deserialize::<Transaction>(&input.prev_tx).ok())
+        .map(|prev_tx| prev_tx.compute_txid())
+        .collect()
+}
+
+/// Checks that a received offer is for `network` before accepting it. The transactions of the
+/// offerer's funding inputs must be on our chain, as listed in `on_chain`, so an offer without
+/// funding inputs cannot be checked and is refused. An offer made on another network would
+/// fund a transaction that can never confirm. The offerer's payout and change scripts must
+/// also be addresses on `network`.
+pub(crate) fn check_offer_network(
+    offer: &OfferedContract,
+    network: Network,
+    on_chain: &HashSet<Txid>,
+) -> Result<(), ContractError> {
+    let mismatch = |reason: String| Err(ContractError::NetworkMismatch { network, reason });
+    let txids = offer_funding_txids(offer);
+    if txids.is_empty() {
+        return mismatch("offer has no funding inputs".to_string());
+    }
+    if let Some(txid) = txids.into_iter().find(|txid| !on_chain.contains(txid)) {
+        return mismatch(format!("funding input transaction {} is not on the chain", txid));
+    }
+    let scripts = [
+        ("payout", &offer.offer_params.payout_script_pubkey),
+        ("change", &offer.offer_params.change_script_pubkey),
+    ];
+    for (name, script) in scripts {
+        if let Err(e) = Address::from_script(script, network) {
+            return mismatch(format!("{} script is not an address: {}", name, e));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a message from `counter_party` against the contract it is for, so an offer cannot
+/// reuse the id of a known contract and an accept cannot change the terms of the offer.
+pub(crate) fn check_terms<S: DdkStorage>(
+    storage: &S,
+    message: &Message,
+    counter_party: PublicKey,
+) -> anyhow::Result<()> {
+    let mismatch = |reason: String| Err(NegotiationError::TermsMismatch(reason).into());
+    match message {
+        Message::Offer(offer) => {
+            if storage.get_contract(&offer.temporary_contract_id)?.is_some() {
+                return mismatch("offer reuses the id of a known contract".to_string());
+            }
+        }
+        Message::Accept(accept) => {
+            let offer = match storage.get_contract(&accept.temporary_contract_id)? {
+                Some(Contract::Offered(offer)) if offer.is_offer_party => offer,
+                _ => return mismatch("accept is not for an open offer we sent".to_string()),
+            };
+            if offer.counter_party != counter_party {
+                return mismatch("accept is not from the counterparty of the offer".to_string());
+            }
+            let accept_collateral = offer.total_collateral - offer.offer_params.collateral;
+            if accept.accept_collateral != accept_collateral {
+                return mismatch(format!(
+                    "accept collateral is {}, the offer asked for {}",
+                    accept.accept_collateral, accept_collateral
+                ));
+            }
+            if accept.funding_pubkey == offer.offer_params.fund_pubkey {
+                return mismatch("accept uses our funding key".to_string());
+            }
+            // Negotiation fields change the payouts the offer was made with.
+            if accept.negotiation_fields.is_some() {
+                return mismatch("accept renegotiates the payouts".to_string());
+            }
+        }
+        // The manager broadcasts the funding transaction as it processes the sign message, so
+        // the refund transaction is checked before.
+        Message::Sign(sign) => check_refund(storage, sign)?,
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Checks the CET adaptor signatures of an accept or sign message on `verifier` before the
+/// manager stores them. Contracts the verifier cannot check are left to the manager.
+pub(crate) fn check_adaptor_signatures<S: DdkStorage>(
+    storage: &S,
+    verifier: &AdaptorVerifier,
+    message: &Message,
+) -> anyhow::Result<()> {
+    let secp = Secp256k1::new();
+    let (contract_id, checks) = match message {
+        Message::Accept(accept) => match storage.get_contract(&accept.temporary_contract_id)? {
+            Some(Contract::Offered(offer)) => (accept.temporary_contract_id, accept_checks(&secp, &offer, accept)?),
+            _ => return Ok(()),
+        },
+        Message::Sign(sign) => match storage.get_contract(&sign.contract_id)? {
+            Some(Contract::Accepted(accepted)) => (sign.contract_id, sign_checks(&secp, &accepted, sign)?),
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+    let Some(checks) = checks else {
+        return Ok(());
+    };
+    match verifier.verify(&checks) {
+        Some(index) => Err(NegotiationError::InvalidAdaptorSignature {
+            contract_id: hex::encode(contract_id),
+            index,
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Checks the signature `counter_party` sent with `offer`. Offers without one are only
+/// refused when `require_signed` is set, offers with one that is not valid always are.
+pub(crate) fn check_offer_signature(
+    offer: &OfferDlc,
+    signature: Option<&Signature>,
+    counter_party: &PublicKey,
+    require_signed: bool,
+) -> anyhow::Result<()> {
+    match signature {
+        Some(signature) if verify_offer_signature(offer, signature, counter_party) => Ok(()),
+        Some(_) => Err(NegotiationError::InvalidOfferSignature.into()),
+        None if require_signed => Err(NegotiationError::UnsignedOffer.into()),
+        None => Ok(()),
+    }
+}
+
+/// Checks the signature the transport received `offer` with. See [check_offer_signature].
+pub(crate) fn check_received_offer<T: DdkTransport>(
+    transport: &T,
+    offer: &OfferDlc,
+    counter_party: &PublicKey,
+    require_signed: bool,
+) -> anyhow::Result<()> {
+    let signature = transport.offer_signature(counter_party, offer);
+    check_offer_signature(offer, signature.as_ref(), counter_party, require_signed)
+}
+
+/// Checks an offer imported without a transport the way offers that arrive over one are
+/// checked, then refuses it if accepting it would go over the contract limit or the collateral
+/// cap. An imported offer carries no signature, so it is refused when `require_signed` is set.
+pub(crate) fn check_imported_offer<S: DdkStorage>(
+    storage: &S,
+    offer: &OfferDlc,
+    counter_party: &PublicKey,
+    require_signed: bool,
+    max_active_contracts: Option<usize>,
+    max_contract_collateral: Option<u64>,
+) -> anyhow::Result<()> {
+    check_offer_signature(offer, None, counter_party, require_signed)?;
+    check_terms(storage, &Message::Offer(offer.clone()), *counter_party)?;
+    check_contract_limit(storage, max_active_contracts)?;
+    let accept_collateral = offer.contract_info.get_total_collateral() - offer.offer_collateral;
+    check_collateral_cap(accept_collateral, max_contract_collateral)
+}
+
+/// Checks the refund transaction of the contract `sign` is for. A contract whose refund does
+/// not match the offer is moved to [Contract::FailedSign], so it is neither signed nor funded.
+/// See [crate::util::verify_refund_transaction].
+pub(crate) fn check_refund<S: DdkStorage>(storage: &S, sign: &SignDlc) -> anyhow::Result<()> {
+    let accepted_contract = match storage.get_contract(&sign.contract_id)? {
+        Some(Contract::Accepted(accepted)) => accepted,
+        Some(Contract::Signed(signed)) => signed.accepted_contract,
+        // The manager refuses sign messages for other contracts.
+        _ => return Ok(()),
+    };
+    if let Err(e) = verify_refund_transaction(&accepted_contract) {
+        storage.update_contract(&Contract::FailedSign(FailedSignContract {
+            accepted_contract,
+            sign_message: sign.clone(),
+            error_message: e.to_string(),
+        }))?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::SledStorageProvider;
+    use bitcoin::hashes::Hash;
+    use dlc_manager::Storage;
+    use crate::test_util::{offered_contract, signed_contract, MockTransport};
+
+    #[test]
+    fn expired_offer_is_abandoned() {
+        let path = "tests/data/dlc_storage/sleddb/expired_offer_is_abandoned";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let offer = offered_contract();
+            storage.create_contract(&offer).unwrap();
+            storage.save_offer_expiry(&offer.id, 1_000).unwrap();
+
+            let abandoned = abandon_expired_offers(&storage, 999).unwrap();
+            assert!(abandoned.is_empty());
+            assert_eq!(storage.get_contract_offers().unwrap().len(), 1);
+
+            let abandoned = abandon_expired_offers(&storage, 1_000).unwrap();
+            assert_eq!(abandoned, vec![offer.id]);
+            assert!(storage.get_contract_offers().unwrap().is_empty());
+            assert!(matches!(
+                storage.get_contract(&offer.id).unwrap(),
+                Some(Contract::Rejected(_))
+            ));
+            assert!(storage.get_offer_expiry(&offer.id).unwrap().is_none());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn responds_on_transport_message_arrived_on() {
+        let counter_party = MockTransport::counter_party();
+        let offer: OfferDlc = (&offered_contract()).into();
+
+        let first = MockTransport::new("first");
+        let second = MockTransport::new("second");
+        second.receive(counter_party, Message::Offer(offer.clone()));
+
+        let runtime = Runtime::new().unwrap();
+        for transport in [&first, &second] {
+            process_transport_messages(transport, runtime.handle(), |_, _| Some(Message::Offer(offer.clone())));
+        }
+
+        assert!(first.sent().is_empty());
+        let sent = second.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, counter_party);
+    }
+
+    #[cfg(feature = "nostr")]
+    #[test]
+    fn offers_over_nostr_carry_their_signature() {
+        use crate::transport::nostr::nostr::Keys;
+        use crate::transport::nostr::NostrDlcRelayHandler;
+
+        let sender = NostrDlcRelayHandler::with_keys(Keys::generate(), crate::RELAY_HOST).unwrap();
+        let recipient = NostrDlcRelayHandler::with_keys(Keys::generate(), crate::RELAY_HOST).unwrap();
+        let offer: OfferDlc = (&offered_contract()).into();
+        let event = sender
+            .create_dlc_msg_event(&recipient.node_id().unwrap(), None, Message::Offer(offer.clone()))
+            .unwrap();
+        let (counter_party, message) = recipient.read_dlc_msg_event(&event).unwrap();
+        assert_eq!(counter_party, sender.node_id().unwrap());
+        recipient.queue_received(counter_party, message);
+
+        // The same offer over a transport that carries no signature.
+        let unsigned = MockTransport::new("unsigned");
+        unsigned.receive(counter_party, Message::Offer(offer));
+
+        let runtime = Runtime::new().unwrap();
+        let mut checked = Vec::new();
+        process_transport_messages(&recipient, runtime.handle(), |message, counter_party| {
+            if let Message::Offer(offer) = message {
+                checked.push(check_received_offer(&recipient, offer, &counter_party, true).is_ok());
+            }
+            None
+        });
+        process_transport_messages(&unsigned, runtime.handle(), |message, counter_party| {
+            if let Message::Offer(offer) = message {
+                checked.push(check_received_offer(&unsigned, offer, &counter_party, true).is_ok());
+            }
+            None
+        });
+        assert_eq!(checked, vec![true, false]);
+    }
+
+    #[cfg(feature = "nostr")]
+    #[test]
+    fn contracts_name_the_counterparty_by_node_key_on_every_transport() {
+        use crate::transport::lightning::LightningIdentity;
+        use crate::transport::nostr::nostr::{Keys, SecretKey};
+        use crate::transport::nostr::NostrDlcRelayHandler;
+        use crate::transport::TransportIdentity;
+        use dlc_manager::contract::offered_contract::OfferedContract;
+
+        let path = "tests/data/dlc_storage/sleddb/contracts_name_the_counterparty_by_node_key_on_every_transport";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let recipient = NostrDlcRelayHandler::with_keys(Keys::generate(), crate::RELAY_HOST).unwrap();
+            let offer: OfferDlc = (&offered_contract()).into();
+            for secret in 1u8..=4 {
+                let node_key = bitcoin::secp256k1::SecretKey::from_slice(&[secret; 32]).unwrap();
+                let node_id = node_key.public_key(&Secp256k1::new());
+                let keys = Keys::new(SecretKey::from_slice(&node_key.secret_bytes()).unwrap());
+                let sender = NostrDlcRelayHandler::with_keys(keys, crate::RELAY_HOST).unwrap();
+
+                let event = sender
+                    .create_dlc_msg_event(&recipient.node_id().unwrap(), None, Message::Offer(offer.clone()))
+                    .unwrap();
+                let (over_nostr, _) = recipient.read_dlc_msg_event(&event).unwrap();
+                let over_lightning = LightningIdentity::node_id(&LightningIdentity::peer_id(&node_id)).unwrap();
+
+                // Store the offer the way the manager does for each sender and read it back.
+                for (index, counter_party) in [over_nostr, over_lightning].into_iter().enumerate() {
+                    let mut received = offer.clone();
+                    received.temporary_contract_id = [secret * 2 + index as u8; 32];
+                    let contract = OfferedContract::try_from_offer_dlc(&received, counter_party, [0u8; 32]).unwrap();
+                    storage.create_contract(&contract).unwrap();
+                    let stored = storage.get_contract(&contract.id).unwrap().unwrap();
+                    assert_eq!(stored.get_counter_party_id(), node_id);
+                }
+            }
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn unconfirmed_funding_is_rebroadcast() {
+        let path = "tests/data/dlc_storage/sleddb/unconfirmed_funding_is_rebroadcast";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = signed_contract();
+            let fund_txid = signed.accepted_contract.dlc_transactions.fund.compute_txid();
+            storage.update_contract(&Contract::Signed(signed.clone())).unwrap();
+
+            let funding = unconfirmed_funding_transactions(&storage).unwrap();
+            assert_eq!(funding.len(), 1);
+            assert_eq!(funding[0].0, signed.accepted_contract.get_contract_id());
+            assert_eq!(funding[0].1.compute_txid(), fund_txid);
+
+            storage.update_contract(&Contract::Confirmed(signed)).unwrap();
+            assert!(unconfirmed_funding_transactions(&storage).unwrap().is_empty());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn mismatched_refund_fails_the_contract() {
+        let path = "tests/data/dlc_storage/sleddb/mismatched_refund_fails_the_contract";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = signed_contract();
+            let contract_id = signed.accepted_contract.get_contract_id();
+            let sign = signed.get_sign_dlc(signed.adaptor_signatures.clone().unwrap_or_default());
+            storage.update_contract(&Contract::Signed(signed.clone())).unwrap();
+            check_refund(&storage, &sign).unwrap();
+            assert!(matches!(storage.get_contract(&contract_id).unwrap(), Some(Contract::Signed(_))));
+
+            let mut early = signed;
+            early.accepted_contract.dlc_transactions.refund.lock_time =
+                bitcoin::absolute::LockTime::from_consensus(early.accepted_contract.offered_contract.refund_locktime - 1);
+            storage.update_contract(&Contract::Signed(early)).unwrap();
+            assert!(check_refund(&storage, &sign).is_err());
+            match storage.get_contract(&contract_id).unwrap() {
+                Some(Contract::FailedSign(failed)) => assert!(failed.error_message.contains("locks until")),
+                other => panic!("Contract did not fail: {:?}", other.map(|c| crate::util::contract_state(&c))),
+            }
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn reject_reasons_follow_the_contract_state() {
+        let path = "tests/data/dlc_storage/sleddb/reject_reasons_follow_the_contract_state";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let counter_party = MockTransport::counter_party();
+            let other_party = PublicKey::from_secret_key(&Secp256k1::new(), &bitcoin::secp256k1::SecretKey::from_slice(&[3u8; 32]).unwrap());
+            let reason = |contract_id: ContractId, from: PublicKey| {
+                RejectReason::of(&storage, &Reject { channel_id: contract_id, timestamp: 1_700_000_000 }, from).unwrap()
+            };
+
+            let mut received = offered_contract();
+            received.id = [1u8; 32];
+            received.is_offer_party = false;
+            received.counter_party = counter_party;
+            storage.create_contract(&received).unwrap();
+            assert_eq!(reason(received.id, counter_party), RejectReason::OfferCancelled);
+            assert_eq!(reason(received.id, other_party), RejectReason::Rejected);
+
+            // The counterparty rejecting our own offer is for the manager.
+            let mut sent = received.clone();
+            sent.id = [2u8; 32];
+            sent.is_offer_party = true;
+            storage.create_contract(&sent).unwrap();
+            assert_eq!(reason(sent.id, counter_party), RejectReason::Rejected);
+
+            let mut signed = signed_contract();
+            signed.accepted_contract.offered_contract.counter_party = counter_party;
+            let confirmed = Contract::Confirmed(signed);
+            storage.update_contract(&confirmed).unwrap();
+            assert_eq!(reason(confirmed.get_id(), counter_party), RejectReason::Settled);
+            assert_eq!(reason([9u8; 32], counter_party), RejectReason::Rejected);
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn settlement_notice_pre_closes_the_contract() {
+        let path = "tests/data/dlc_storage/sleddb/settlement_notice_pre_closes_the_contract";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let mut signed = signed_contract();
+            let counter_party = MockTransport::counter_party();
+            signed.accepted_contract.offered_contract.counter_party = counter_party;
+            let contract_id = signed.accepted_contract.get_contract_id();
+            let dlc_transactions = signed.accepted_contract.dlc_transactions.clone();
+            storage.update_contract(&Contract::Confirmed(signed)).unwrap();
+
+            // We notify counterparties of contracts the periodic check closed.
+            assert!(settled_contracts(&storage, &[contract_id]).unwrap().is_empty());
+
+            // Notices from someone else and unverifiable notices leave the contract as is.
+            let stranger = PublicKey::from_secret_key(&Secp256k1::new(), &bitcoin::secp256k1::SecretKey::from_slice(&[2u8; 32]).unwrap());
+            let cet = dlc_transactions.cets[0].clone();
+            assert_eq!(settle_on_notice(&storage, &contract_id, stranger, |_| Ok(Some(cet.clone()))).unwrap(), None);
+            let unrelated = dlc_transactions.fund.clone();
+            assert!(settle_on_notice(&storage, &contract_id, counter_party, |_| Ok(Some(unrelated.clone()))).is_err());
+            assert!(settle_on_notice(&storage, &contract_id, counter_party, |_| Ok(None)).is_err());
+            assert!(matches!(storage.get_contract(&contract_id).unwrap(), Some(Contract::Confirmed(_))));
+
+            let settlement = settle_on_notice(&storage, &contract_id, counter_party, |outpoint| {
+                assert_eq!(*outpoint, dlc_transactions.get_fund_outpoint());
+                Ok(Some(cet.clone()))
+            })
+            .unwrap()
+            .unwrap();
+            assert_eq!(settlement.txid, cet.compute_txid());
+            assert!(settlement.outcome.is_some());
+            match storage.get_contract(&contract_id).unwrap() {
+                Some(Contract::PreClosed(pre_closed)) => {
+                    assert_eq!(pre_closed.signed_cet.compute_txid(), cet.compute_txid());
+                    assert_eq!(pre_closed.signed_contract.accepted_contract.offered_contract.counter_party, counter_party);
+                }
+                other => panic!("Contract is not pre-closed: {:?}", other.map(|c| crate::util::contract_state(&c))),
+            }
+            assert_eq!(settled_contracts(&storage, &[contract_id]).unwrap(), vec![(contract_id, counter_party)]);
+
+            // A second notice for the pre-closed contract is ignored.
+            assert_eq!(settle_on_notice(&storage, &contract_id, counter_party, |_| Ok(Some(cet.clone()))).unwrap(), None);
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn double_spent_funding_input_fails_the_contract() {
+        let path = "tests/data/dlc_storage/sleddb/double_spent_funding_input_fails_the_contract";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = signed_contract();
+            let contract_id = signed.accepted_contract.get_contract_id();
+            let fund = signed.accepted_contract.dlc_transactions.fund.clone();
+            storage.update_contract(&Contract::Signed(signed)).unwrap();
+
+            // Inputs unspent or spent by the funding transaction are not conflicts.
+            let fund_txid = fund.compute_txid();
+            assert!(fail_conflicted_contracts(&storage, |_| Ok(None)).unwrap().is_empty());
+            assert!(fail_conflicted_contracts(&storage, |_| Ok(Some(fund_txid))).unwrap().is_empty());
+
+            let double_spent = fund.input[0].previous_output;
+            let conflicting_txid = Txid::from_byte_array([7; 32]);
+            let conflicts = fail_conflicted_contracts(&storage, |outpoint| {
+                Ok((*outpoint == double_spent).then_some(conflicting_txid))
+            })
+            .unwrap();
+            let expected = FundingConflict { contract_id, outpoint: double_spent, conflicting_txid };
+            assert_eq!(conflicts, vec![expected.clone()]);
+            assert!(matches!(storage.get_contract(&contract_id).unwrap(), Some(Contract::FailedSign(_))));
+            assert_eq!(storage.get_funding_conflict(&contract_id).unwrap(), Some(expected));
+            assert!(unconfirmed_funding_transactions(&storage).unwrap().is_empty());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn cancel_races_incoming_accept() {
+        let path = "tests/data/dlc_storage/sleddb/cancel_races_incoming_accept";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = signed_contract();
+            let mut offer = signed.accepted_contract.offered_contract.clone();
+            offer.is_offer_party = true;
+
+            // Cancel is handled first. The accept then finds no open offer.
+            storage.create_contract(&offer).unwrap();
+            assert_eq!(cancel_offer(&storage, &offer.id).unwrap(), offer.counter_party);
+            assert!(storage.get_contract_offers().unwrap().is_empty());
+            assert!(cancel_offer(&storage, &offer.id).is_err());
+
+            // The accept is handled first. The offer id now belongs to a signed contract.
+            storage.create_contract(&offer).unwrap();
+            storage.update_contract(&Contract::Signed(signed)).unwrap();
+            let error = cancel_offer(&storage, &offer.id).unwrap_err();
+            assert_eq!(error.to_string(), ACCEPT_RECEIVED);
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn received_offer_is_cancelled_by_sender_only() {
+        let path = "tests/data/dlc_storage/sleddb/received_offer_is_cancelled_by_sender_only";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let mut offer = offered_contract();
+            offer.is_offer_party = false;
+            storage.create_contract(&offer).unwrap();
+
+            let stranger = MockTransport::counter_party();
+            if stranger != offer.counter_party {
+                assert!(!offer_cancelled(&storage, &offer.id, stranger).unwrap());
+            }
+            assert!(offer_cancelled(&storage, &offer.id, offer.counter_party).unwrap());
+            assert!(matches!(
+                storage.get_contract(&offer.id).unwrap(),
+                Some(Contract::Rejected(_))
+            ));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn derived_serial_ids_are_stored_with_the_offer() {
+        let path = "tests/data/dlc_storage/sleddb/derived_serial_ids_are_stored_with_the_offer";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let offer = offered_contract();
+            storage.create_contract(&offer).unwrap();
+            let sent = derive_offer_serial_ids(&storage, &offer.id, &[5u8; 32]).unwrap();
+
+            let Some(Contract::Offered(stored)) = storage.get_contract(&offer.id).unwrap() else {
+                panic!("offer is stored");
+            };
+            let mut expected = offer.clone();
+            set_serial_ids(&mut expected, &[5u8; 32]);
+            assert_eq!(stored.fund_output_serial_id, expected.fund_output_serial_id);
+            assert_eq!(sent.fund_output_serial_id, expected.fund_output_serial_id);
+            assert_eq!(sent.payout_serial_id, expected.offer_params.payout_serial_id);
+            assert!(derive_offer_serial_ids(&storage, &[9u8; 32], &[5u8; 32]).is_err());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn derived_serial_ids_are_stable() {
+        let secret = [5u8; 32];
+        let (mut first, mut second) = (offered_contract(), offered_contract());
+        set_serial_ids(&mut first, &secret);
+        set_serial_ids(&mut second, &secret);
+        let serial_ids = |offer: &OfferedContract| {
+            let inputs = offer.funding_inputs.iter().map(|input| input.input_serial_id).collect::<Vec<_>>();
+            (offer.fund_output_serial_id, offer.offer_params.payout_serial_id, offer.offer_params.change_serial_id, inputs)
+        };
+        assert_eq!(serial_ids(&first), serial_ids(&second));
+
+        let mut ids = vec![
+            first.fund_output_serial_id,
+            first.offer_params.payout_serial_id,
+            first.offer_params.change_serial_id,
+        ];
+        ids.extend(first.funding_inputs.iter().map(|input| input.input_serial_id));
+        let inputs = first.offer_params.inputs.iter().map(|input| input.serial_id);
+        assert!(inputs.eq(first.funding_inputs.iter().map(|input| input.input_serial_id)));
+        let distinct = ids.iter().collect::<HashSet<_>>();
+        assert_eq!(distinct.len(), ids.len());
+
+        let mut other = offered_contract();
+        set_serial_ids(&mut other, &[6u8; 32]);
+        assert_ne!(other.fund_output_serial_id, first.fund_output_serial_id);
+    }
+
+    #[test]
+    fn offer_from_another_network_is_refused() {
+        let offer = offered_contract();
+        let txids = offer_funding_txids(&offer);
+        assert!(!txids.is_empty());
+
+        // The funding inputs of a testnet offer are unknown to a regtest chain.
+        let refused = check_offer_network(&offer, Network::Regtest, &HashSet::new()).unwrap_err();
+        assert!(matches!(refused, ContractError::NetworkMismatch { network: Network::Regtest, .. }));
+
+        let on_chain = txids.into_iter().collect::<HashSet<_>>();
+        assert!(check_offer_network(&offer, Network::Regtest, &on_chain).is_ok());
+
+        // A payout that is not an address cannot be checked.
+        let mut nonstandard = offer.clone();
+        nonstandard.offer_params.payout_script_pubkey = bitcoin::ScriptBuf::from_bytes(vec![0x51]);
+        assert!(check_offer_network(&nonstandard, Network::Regtest, &on_chain).is_err());
+
+        // Nor can an offer without funding inputs.
+        let mut unfunded = offer;
+        unfunded.funding_inputs.clear();
+        let refused = check_offer_network(&unfunded, Network::Regtest, &on_chain).unwrap_err();
+        assert!(matches!(refused, ContractError::NetworkMismatch { network: Network::Regtest, .. }));
+    }
+
+    #[test]
+    fn accept_with_altered_collateral_is_refused() {
+        let path = "tests/data/dlc_storage/sleddb/accept_with_altered_collateral_is_refused";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let accepted = signed_contract().accepted_contract;
+            let mut offer = accepted.offered_contract.clone();
+            offer.is_offer_party = true;
+            offer.counter_party = MockTransport::counter_party();
+            storage.create_contract(&offer).unwrap();
+
+            let mut accept = accepted.get_accept_contract_msg(&[]);
+            let counter_party = MockTransport::counter_party();
+            assert!(check_terms(&storage, &Message::Accept(accept.clone()), counter_party).is_ok());
+
+            accept.accept_collateral += 1;
+            let refused = check_terms(&storage, &Message::Accept(accept), counter_party).unwrap_err();
+            assert!(matches!(
+                refused.downcast_ref::<NegotiationError>(),
+                Some(NegotiationError::TermsMismatch(_))
+            ));
+
+            // A new offer cannot take over the id of the stored one.
+            let reused = OfferDlc::from(&offer);
+            assert!(check_terms(&storage, &Message::Offer(reused), counter_party).is_err());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn check_delays_fall_within_the_jitter_band() {
+        let schedule = CheckSchedule {
+            interval: Duration::from_secs(30),
+            jitter_percent: 10,
+        };
+        let band = Duration::from_secs(27)..=Duration::from_secs(33);
+        for sample in [0, 1, 3_000, 5_999, 6_000, u64::MAX] {
+            assert!(band.contains(&schedule.delay(sample)));
+        }
+        assert_eq!(schedule.delay(0), Duration::from_secs(27));
+        assert_eq!(schedule.delay(6_000), Duration::from_secs(33));
+        assert!((0..100).all(|_| band.contains(&schedule.next_delay())));
+
+        let fixed = CheckSchedule { jitter_percent: 0, ..schedule };
+        assert_eq!(fixed.delay(u64::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn periodic_check_is_skipped_without_pending_contracts() {
+        let path = "tests/data/dlc_storage/sleddb/periodic_check_is_skipped_without_pending_contracts";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            assert_eq!(needs_periodic_check(&storage).unwrap(), cfg!(feature = "channels"));
+
+            let offer = offered_contract();
+            storage.create_contract(&offer).unwrap();
+            assert!(needs_periodic_check(&storage).unwrap());
+
+            storage.update_contract(&Contract::Rejected(offer)).unwrap();
+            assert_eq!(needs_periodic_check(&storage).unwrap(), cfg!(feature = "channels"));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn contracts_beyond_the_limit_are_rejected() {
+        let path = "tests/data/dlc_storage/sleddb/contracts_beyond_the_limit_are_rejected";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let limit = Some(3);
+            let mut offers = Vec::new();
+            for id in 1..=3u8 {
+                check_contract_limit(&storage, limit).unwrap();
+                let mut offer = offered_contract();
+                offer.id = [id; 32];
+                offer.is_offer_party = true;
+                storage.create_contract(&offer).unwrap();
+                offers.push(offer);
+            }
+
+            let error = check_contract_limit(&storage, limit).unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<ContractError>(),
+                Some(&ContractError::ContractLimitReached { active: 3, limit: 3 })
+            );
+            check_contract_limit(&storage, None).unwrap();
+
+            // Offers received do not count, so spam cannot block our own offers and accepts.
+            storage.update_contract(&Contract::Rejected(offers.remove(0))).unwrap();
+            for id in 10..20u8 {
+                let mut received = offered_contract();
+                received.id = [id; 32];
+                received.is_offer_party = false;
+                storage.create_contract(&received).unwrap();
+            }
+            check_contract_limit(&storage, limit).unwrap();
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn collateral_over_the_cap_is_rejected() {
+        let offer = offered_contract();
+        let offer_collateral = offer.offer_params.collateral;
+        let accept_collateral = offer.total_collateral - offer_collateral;
+
+        let error = check_collateral_cap(offer_collateral, Some(offer_collateral - 1)).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ContractError>(),
+            Some(&ContractError::CollateralCapExceeded { collateral: offer_collateral, cap: offer_collateral - 1 })
+        );
+        check_collateral_cap(offer_collateral, Some(offer_collateral)).unwrap();
+        check_collateral_cap(accept_collateral, None).unwrap();
+    }
+
+    #[test]
+    fn funds_must_cover_the_collateral_and_fees() {
+        let offer = offered_contract();
+        let accept_collateral = offer.total_collateral - offer.offer_params.collateral;
+        let cost = ddk_payouts::estimate_party_cost(accept_collateral, 2);
+        assert!(cost.total() > accept_collateral);
+
+        let error = check_funding_cost(&cost, accept_collateral).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ContractError>(),
+            Some(&ContractError::InsufficientFunds { cost: cost.total(), available: accept_collateral })
+        );
+        check_funding_cost(&cost, cost.total()).unwrap();
+    }
+
+    #[test]
+    fn ambiguous_contract_id_prefix_is_rejected() {
+        let path = "tests/data/dlc_storage/sleddb/ambiguous_contract_id_prefix_is_rejected";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let mut first = offered_contract();
+            first.id = [0xab; 32];
+            let mut second = offered_contract();
+            second.id = [0xab; 32];
+            second.id[1] = 0xcd;
+            storage.create_contract(&first).unwrap();
+            storage.create_contract(&second).unwrap();
+
+            let error = resolve_contract_id(&storage, "ab").unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<ContractError>(),
+                Some(&ContractError::AmbiguousPrefix {
+                    prefix: "ab".to_string(),
+                    matches: vec![hex::encode(first.id), hex::encode(second.id)],
+                })
+            );
+
+            assert_eq!(resolve_contract_id(&storage, "abab").unwrap(), first.id);
+            assert_eq!(resolve_contract_id(&storage, "ABCD").unwrap(), second.id);
+            assert_eq!(resolve_contract_id(&storage, &hex::encode(second.id)).unwrap(), second.id);
+            assert!(matches!(
+                resolve_contract_id(&storage, "ff").unwrap_err().downcast_ref::<ContractError>(),
+                Some(ContractError::UnknownContract(_))
+            ));
+            assert!(matches!(
+                resolve_contract_id(&storage, "xyz").unwrap_err().downcast_ref::<ContractError>(),
+                Some(ContractError::InvalidId(_))
+            ));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn exported_offer_imports_as_the_same_offer() {
+        let path = "tests/data/dlc_storage/sleddb/exported_offer_imports_as_the_same_offer";
+        {
+            let offered = offered_contract();
+            let exported = crate::util::encode_offer(&Contract::Offered(offered.clone())).unwrap();
+            let imported = crate::util::decode_offer(&exported).unwrap();
+            assert_eq!(imported, OfferDlc::from(&offered));
+
+            // The importing node does not know the offer yet, so it is taken in.
+            let storage = SledStorageProvider::new(path).unwrap();
+            let counter_party = MockTransport::counter_party();
+            let message = Message::Offer(imported);
+            assert!(check_terms(&storage, &message, counter_party).is_ok());
+
+            // Importing it a second time is refused.
+            storage.create_contract(&offered).unwrap();
+            assert!(check_terms(&storage, &message, counter_party).is_err());
+
+            let mut trailing = exported.clone();
+            trailing.push(0);
+            assert!(crate::util::decode_offer(&trailing).is_err());
+            assert!(crate::util::decode_offer(&exported[..exported.len() - 1]).is_err());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn imported_offers_are_checked_like_received_ones() {
+        let path = "tests/data/dlc_storage/sleddb/imported_offers_are_checked_like_received_ones";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let offer = OfferDlc::from(&offered_contract());
+            let counter_party = MockTransport::counter_party();
+            let accept_collateral = offer.contract_info.get_total_collateral() - offer.offer_collateral;
+            check_imported_offer(&storage, &offer, &counter_party, false, Some(1), Some(accept_collateral)).unwrap();
+
+            let refused = check_imported_offer(&storage, &offer, &counter_party, true, None, None).unwrap_err();
+            assert_eq!(refused.downcast_ref::<NegotiationError>(), Some(&NegotiationError::UnsignedOffer));
+
+            let refused = check_imported_offer(&storage, &offer, &counter_party, false, None, Some(accept_collateral - 1)).unwrap_err();
+            assert_eq!(
+                refused.downcast_ref::<ContractError>(),
+                Some(&ContractError::CollateralCapExceeded { collateral: accept_collateral, cap: accept_collateral - 1 })
+            );
+
+            let mut own = offered_contract();
+            own.id = [9u8; 32];
+            own.is_offer_party = true;
+            storage.create_contract(&own).unwrap();
+            let refused = check_imported_offer(&storage, &offer, &counter_party, false, Some(1), None).unwrap_err();
+            assert_eq!(refused.downcast_ref::<ContractError>(), Some(&ContractError::ContractLimitReached { active: 1, limit: 1 }));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn offer_with_bad_signature_is_refused() {
+        let offer = OfferDlc::from(&offered_contract());
+        let node_key = bitcoin::secp256k1::SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let counter_party = MockTransport::counter_party();
+        let signature = crate::util::sign_offer(&offer, &node_key);
+        assert!(check_offer_signature(&offer, Some(&signature), &counter_party, true).is_ok());
+
+        // Signed by another key than the one the offer comes from.
+        let other_key = bitcoin::secp256k1::SecretKey::from_slice(&[2u8; 32]).unwrap();
+        let forged = crate::util::sign_offer(&offer, &other_key);
+        let refused = check_offer_signature(&offer, Some(&forged), &counter_party, false).unwrap_err();
+        assert_eq!(refused.downcast_ref::<NegotiationError>(), Some(&NegotiationError::InvalidOfferSignature));
+
+        // A signature of the offer does not carry over to altered terms.
+        let mut altered = offer.clone();
+        altered.fee_rate_per_vb += 1;
+        assert!(check_offer_signature(&altered, Some(&signature), &counter_party, false).is_err());
+
+        // Unsigned offers are only refused in strict mode.
+        assert!(check_offer_signature(&offer, None, &counter_party, false).is_ok());
+        let refused = check_offer_signature(&offer, None, &counter_party, true).unwrap_err();
+        assert_eq!(refused.downcast_ref::<NegotiationError>(), Some(&NegotiationError::UnsignedOffer));
+    }
+
+    #[test]
+    fn previewed_offer_id_is_the_stored_contract_id() {
+        let path = "tests/data/dlc_storage/sleddb/previewed_offer_id_is_the_stored_contract_id";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let offered = offered_contract();
+            let offer = OfferDlc::from(&offered);
+            storage.create_contract(&offered).unwrap();
+
+            let contract_id = crate::util::preview_offer_id(&offer);
+            let offers = storage.get_contract_offers().unwrap();
+            assert_eq!(offers.iter().map(|o| o.id).collect::<Vec<_>>(), vec![contract_id]);
+            assert!(matches!(storage.get_contract(&contract_id).unwrap(), Some(Contract::Offered(_))));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    /// Chain monitor that confirms every registered outpoint once it runs.
+    #[derive(Default)]
+    struct MockMonitor {
+        registered: std::sync::Mutex<HashSet<OutPoint>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ChainMonitor for MockMonitor {
+        fn name(&self) -> String {
+            "mock".to_string()
+        }
+
+        fn register_outpoint(&self, outpoint: OutPoint) {
+            self.registered.lock().unwrap().insert(outpoint);
+        }
+
+        fn unregister_outpoint(&self, outpoint: &OutPoint) {
+            self.registered.lock().unwrap().remove(outpoint);
+        }
+
+        async fn run(&self, events: UnboundedSender<Confirmation>) {
+            let outpoints = self.registered.lock().unwrap().clone();
+            for outpoint in outpoints {
+                let _ = events.send(Confirmation { outpoint, confirmations: 1 });
+            }
+        }
+    }
+
+    #[test]
+    fn confirmation_event_confirms_its_contract() {
+        let path = "tests/data/dlc_storage/sleddb/confirmation_event_confirms_its_contract";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = signed_contract();
+            let outpoint = funding_outpoint(&Contract::Signed(signed.clone())).unwrap();
+            storage.update_contract(&Contract::Signed(signed.clone())).unwrap();
+
+            let monitor = MockMonitor::default();
+            // Nothing listens on the discard port, so a poll of esplora would error.
+            let blockchain = EsploraClient::new("http://127.0.0.1:9", Network::Regtest).unwrap();
+            let mut watched = HashSet::new();
+            watch_funding_outpoints(&storage, &monitor, &blockchain, &mut watched).unwrap();
+            assert_eq!(*monitor.registered.lock().unwrap(), HashSet::from([outpoint]));
+            assert_eq!(blockchain.get_transaction_confirmations(&outpoint.txid).unwrap(), 0);
+
+            let runtime = Runtime::new().unwrap();
+            let (sender, receiver) = unbounded();
+            let (confirmations, confirmation_events) = unbounded_channel();
+            runtime.block_on(async {
+                monitor.run(confirmations).await;
+                forward_confirmations(confirmation_events, Arc::new(sender)).await;
+            });
+            let confirmation = match receiver.try_recv() {
+                Ok(DlcManagerMessage::FundingConfirmed(confirmation)) => confirmation,
+                other => panic!("expected a funding confirmation, got {:?}", other),
+            };
+            assert_eq!(confirmation.outpoint, outpoint);
+            assert!(receiver.try_recv().is_err());
+
+            // One block is not enough, and an outpoint no contract is funded at moves nothing.
+            assert_eq!(confirm_funding(&storage, &confirmation).unwrap(), None);
+            let other = Confirmation { outpoint: OutPoint::new(outpoint.txid, outpoint.vout + 1), confirmations: NB_CONFIRMATIONS + 1 };
+            assert_eq!(confirm_funding(&storage, &other).unwrap(), None);
+            let confirmed = Confirmation { outpoint, confirmations: NB_CONFIRMATIONS + 1 };
+            let contract_id = Contract::Signed(signed.clone()).get_id();
+            assert_eq!(confirm_funding(&storage, &confirmed).unwrap(), Some(contract_id));
+            assert!(matches!(storage.get_contract(&contract_id).unwrap(), Some(Contract::Confirmed(_))));
+
+            // Once confirmed, the funding outpoint is no longer watched.
+            watch_funding_outpoints(&storage, &monitor, &blockchain, &mut watched).unwrap();
+            assert!(monitor.registered.lock().unwrap().is_empty());
+            assert!(blockchain.get_transaction_confirmations(&outpoint.txid).is_err());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn counterparty_is_online_on_any_transport() {
+        let counter_party = MockTransport::counter_party();
+        let transports = vec![Arc::new(MockTransport::new("first")), Arc::new(MockTransport::new("second"))];
+        assert_eq!(counterparty_online(&transports, &counter_party), None);
+
+        transports[0].set_online(counter_party, false);
+        assert_eq!(counterparty_online(&transports, &counter_party), Some(false));
+
+        transports[1].set_online(counter_party, true);
+        assert_eq!(counterparty_online(&transports, &counter_party), Some(true));
+    }
+
+    #[test]
+    fn interrupted_sign_is_resumed_after_restart() {
+        let path = "tests/data/dlc_storage/sleddb/interrupted_sign_is_resumed_after_restart";
+        {
+            let counter_party = MockTransport::counter_party();
+            let mut signed = signed_contract();
+            signed.accepted_contract.offered_contract.is_offer_party = true;
+            signed.accepted_contract.offered_contract.counter_party = counter_party;
+            // Our adaptor signatures, as the offering party stores them.
+            signed.adaptor_signatures = Some(signed.accepted_contract.adaptor_signatures.clone());
+            let contract_id = signed.accepted_contract.get_contract_id();
+            {
+                // The accept was processed, then the node stopped before the sign message
+                // was recorded or sent.
+                let storage = SledStorageProvider::new(path).unwrap();
+                storage.update_contract(&Contract::Signed(signed.clone())).unwrap();
+                storage.flush().unwrap();
+            }
+
+            let storage = SledStorageProvider::new(path).unwrap();
+            let transport = MockTransport::new("resume");
+            let runtime = Runtime::new().unwrap();
+            let resumed = interrupted_negotiations(&storage).unwrap();
+            assert_eq!(resumed.len(), 1);
+            let (resumed_id, to, message) = resumed.into_iter().next().unwrap();
+            assert_eq!(resumed_id, contract_id);
+            let Message::Sign(sign) = &message else {
+                panic!("expected a sign message");
+            };
+            assert_eq!(sign.contract_id, contract_id);
+            assert_eq!(sign.refund_signature, signed.offer_refund_signature);
+            assert_eq!(sign.cet_adaptor_signatures.ecdsa_adaptor_signatures.len(), signed.accepted_contract.adaptor_signatures.len());
+            runtime.block_on(transport.send_message(to, message)).unwrap();
+            assert_eq!(transport.sent()[0].0, counter_party);
+            assert!(matches!(storage.get_contract(&contract_id).unwrap(), Some(Contract::Signed(_))));
+
+            // Nothing is left to resume once the contract is funded.
+            storage.update_contract(&Contract::Confirmed(signed)).unwrap();
+            assert!(interrupted_negotiations(&storage).unwrap().is_empty());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn resend_sends_the_last_message_again() {
+        let path = "tests/data/dlc_storage/sleddb/resend_sends_the_last_message_again";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let counter_party = MockTransport::counter_party();
+            let offer: OfferDlc = (&offered_contract()).into();
+            let contract_id = offer.temporary_contract_id;
+
+            let missing = last_outbound_message(&storage, &contract_id).unwrap_err();
+            assert!(matches!(
+                missing.downcast_ref::<ContractError>(),
+                Some(ContractError::NoOutboundMessage(_))
+            ));
+
+            let transport = MockTransport::new("resend");
+            let runtime = Runtime::new().unwrap();
+            record_outbound_message(&storage, counter_party, &Message::Offer(offer.clone())).unwrap();
+            runtime.block_on(transport.send_message(counter_party, Message::Offer(offer))).unwrap();
+
+            let (to, message) = last_outbound_message(&storage, &contract_id).unwrap();
+            let receipt = runtime.block_on(transport.send_message(to, message)).unwrap();
+            assert_eq!(receipt, MessageReceipt::Sent);
+            let sent = transport.sent();
+            assert_eq!(sent.len(), 2);
+            assert_eq!(sent[1], sent[0]);
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,297 @@
+//! Bounded resends of DLC messages the counterparty did not answer.
+//!
+//! DDK waits on an answer to the accept of a contract that is not signed yet and to the sign
+//! message of a contract whose funding transaction esplora has not seen. Each is tracked when
+//! it is sent, and the ones [crate::ddk::interrupted_negotiations] finds on start are tracked
+//! when they are resumed. While the contract stays put, its message is sent again on a
+//! backoff schedule. After [RetryPolicy::max_attempts] sends the message is moved to the dead letters
+//! in [DdkStorage], the contract is flagged delivery failed and a
+//! [crate::DdkEventHandler::on_delivery_failed] event is sent. Nothing more is sent for the
+//! contract until [crate::DlcDevKit::retry_dead_letter] puts the message back.
+use crate::chain::EsploraClient;
+use crate::ddk::rebuild_sign_message;
+use crate::events::DdkEvent;
+use crate::storage::DeadLetter;
+use crate::transport::MessageReceipt;
+use crate::{DdkStorage, DdkTransport};
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Txid;
+use dlc_manager::contract::Contract;
+use dlc_manager::ContractId;
+use dlc_messages::Message;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Default sends of a message before it is dead-lettered. With the default delays that is
+/// about six hours, longer than a funding transaction usually takes to confirm.
+pub const DEFAULT_MAX_DELIVERY_ATTEMPTS: u32 = 12;
+/// Default seconds before the first resend. Each later resend waits twice as long.
+pub const DEFAULT_RETRY_DELAY_SECS: u64 = 60;
+/// Default longest wait between two resends. One hour.
+pub const DEFAULT_MAX_RETRY_DELAY_SECS: u64 = 60 * 60;
+/// Seconds between looks for messages due to be sent again.
+pub(crate) const DELIVERY_CHECK_INTERVAL_SECS: u64 = 15;
+
+/// How often and how long a message is sent again before it is dead-lettered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Sends of a message, the first one included.
+    pub max_attempts: u32,
+    /// Wait after the first send.
+    pub initial_delay: Duration,
+    /// Longest wait between two sends.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_DELIVERY_ATTEMPTS,
+            initial_delay: Duration::from_secs(DEFAULT_RETRY_DELAY_SECS),
+            max_delay: Duration::from_secs(DEFAULT_MAX_RETRY_DELAY_SECS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait after a message was sent `attempts` times.
+    pub fn delay(&self, attempts: u32) -> Duration {
+        let doublings = attempts.saturating_sub(1).min(31);
+        self.initial_delay.saturating_mul(1 << doublings).min(self.max_delay)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sends {
+    counter_party: PublicKey,
+    attempts: u32,
+    /// Unix time in seconds the message is due again.
+    next_at: u64,
+}
+
+/// What to do with the pending messages in one round.
+#[derive(Debug, Default)]
+pub(crate) struct RetryRound {
+    pub resend: Vec<(ContractId, PublicKey, Message)>,
+    /// Messages that ran out of attempts this round. They are already stored.
+    pub dead_letters: Vec<DeadLetter>,
+}
+
+/// Counts the sends of each pending message, by the final id of its contract. Messages are
+/// tracked when they are sent, so a round only reads the contracts with a message due. The
+/// counts are not stored, so a restart gives every message its full attempts again.
+pub(crate) struct DeliveryRetries {
+    policy: RetryPolicy,
+    sends: Mutex<HashMap<ContractId, Sends>>,
+}
+
+impl DeliveryRetries {
+    pub(crate) fn new(policy: RetryPolicy) -> Self {
+        Self { policy, sends: Mutex::new(HashMap::new()) }
+    }
+
+    /// Wait on an answer to the accept or sign message of `contract_id`, sent once at `now`.
+    pub(crate) fn track(&self, contract_id: ContractId, counter_party: PublicKey, now: u64) {
+        let send = Sends {
+            counter_party,
+            attempts: 1,
+            next_at: now + self.policy.delay(1).as_secs(),
+        };
+        self.sends.lock().unwrap().insert(contract_id, send);
+    }
+
+    /// The pending messages due at `now`, and the ones to dead-letter. A message is answered
+    /// once its contract leaves the accepted state, or for a sign message once
+    /// `funding_seen` finds the funding transaction. A message whose funding lookup fails is
+    /// looked at again next round without counting a send.
+    pub(crate) fn due<S: DdkStorage>(
+        &self,
+        storage: &S,
+        now: u64,
+        funding_seen: impl Fn(&Txid) -> anyhow::Result<bool>,
+    ) -> anyhow::Result<RetryRound> {
+        let mut sends = self.sends.lock().unwrap();
+        let mut round = RetryRound::default();
+        let mut answered = Vec::new();
+        for (contract_id, send) in sends.iter_mut() {
+            if now < send.next_at {
+                continue;
+            }
+            let message = match storage.get_contract(contract_id)? {
+                Some(Contract::Accepted(accepted)) => {
+                    storage.get_outbound_message(&accepted.get_contract_id())?.map(|(_, message)| message)
+                }
+                Some(Contract::Signed(signed)) if signed.accepted_contract.offered_contract.is_offer_party => {
+                    let fund_txid = signed.accepted_contract.dlc_transactions.fund.compute_txid();
+                    match funding_seen(&fund_txid) {
+                        Ok(false) => match storage.get_outbound_message(contract_id)? {
+                            Some((_, message @ Message::Sign(_))) => Some(message),
+                            _ => rebuild_sign_message(&signed).map(Message::Sign),
+                        },
+                        Ok(true) => None,
+                        Err(e) => {
+                            tracing::warn!(txid = fund_txid.to_string(), error=?e, "Could not look up funding transaction.");
+                            continue;
+                        }
+                    }
+                }
+                _ => None,
+            };
+            let Some(message) = message else {
+                answered.push(*contract_id);
+                continue;
+            };
+            if send.attempts >= self.policy.max_attempts {
+                let dead_letter = DeadLetter {
+                    contract_id: *contract_id,
+                    counter_party: send.counter_party,
+                    attempts: send.attempts,
+                    failed_at: now,
+                };
+                storage.save_dead_letter(&dead_letter)?;
+                answered.push(*contract_id);
+                round.dead_letters.push(dead_letter);
+                continue;
+            }
+            send.attempts += 1;
+            send.next_at = now + self.policy.delay(send.attempts).as_secs();
+            round.resend.push((*contract_id, send.counter_party, message));
+        }
+        for contract_id in answered {
+            sends.remove(&contract_id);
+        }
+        Ok(round)
+    }
+
+    /// Stop tracking a message the transport acknowledged.
+    pub(crate) fn acknowledged(&self, contract_id: &ContractId) {
+        self.sends.lock().unwrap().remove(contract_id);
+    }
+}
+
+/// Whether esplora has the transaction, in the mempool or in a block.
+fn funding_seen(blockchain: &EsploraClient, txid: &Txid) -> anyhow::Result<bool> {
+    Ok(blockchain.blocking_client.get_tx(txid)?.is_some())
+}
+
+/// One round of resends. Each message goes out on the transport its counterparty was last
+/// heard from on, else the primary transport.
+pub(crate) async fn retry_deliveries<T: DdkTransport, S: DdkStorage>(
+    storage: Arc<S>,
+    retries: Arc<DeliveryRetries>,
+    blockchain: Arc<EsploraClient>,
+    transport: &Arc<T>,
+    peer_transports: &RwLock<HashMap<PublicKey, Arc<T>>>,
+    events: Option<&UnboundedSender<DdkEvent>>,
+) {
+    let due_retries = retries.clone();
+    let round = tokio::task::spawn_blocking(move || {
+        due_retries.due(storage.as_ref(), crate::ddk::unix_time(), |txid| funding_seen(&blockchain, txid))
+    })
+    .await;
+    let round = match round.map_err(anyhow::Error::from).and_then(|round| round) {
+        Ok(round) => round,
+        Err(e) => {
+            tracing::error!(error=?e, "Could not find messages to resend.");
+            return;
+        }
+    };
+    for dead_letter in round.dead_letters {
+        tracing::error!(
+            contract_id = hex::encode(dead_letter.contract_id),
+            counterparty = dead_letter.counter_party.to_string(),
+            attempts = dead_letter.attempts,
+            "Gave up resending DLC message. The contract is flagged delivery failed."
+        );
+        if let Some(events) = events {
+            let _ = events.send(DdkEvent::DeliveryFailed(dead_letter));
+        }
+    }
+    for (contract_id, counter_party, message) in round.resend {
+        let peer_transport = peer_transports.read().unwrap().get(&counter_party).cloned();
+        let transport = peer_transport.unwrap_or_else(|| transport.clone());
+        match transport.send_message(counter_party, message).await {
+            Ok(receipt) => {
+                tracing::debug!(contract_id = hex::encode(contract_id), ?receipt, "Resent unanswered DLC message.");
+                if let MessageReceipt::Acknowledged(_) = receipt {
+                    retries.acknowledged(&contract_id);
+                }
+            }
+            Err(e) => tracing::warn!(contract_id = hex::encode(contract_id), error=?e, "Could not resend DLC message."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::SledStorageProvider;
+    use crate::test_util::{signed_contract, MockTransport};
+    use dlc_manager::Storage;
+
+    #[test]
+    fn exhausted_retries_dead_letter_the_message() {
+        let path = "tests/data/dlc_storage/sleddb/exhausted_retries_dead_letter_the_message";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let counter_party = MockTransport::counter_party();
+            let mut signed = signed_contract();
+            signed.accepted_contract.offered_contract.is_offer_party = true;
+            signed.accepted_contract.offered_contract.counter_party = counter_party;
+            signed.adaptor_signatures = Some(signed.accepted_contract.adaptor_signatures.clone());
+            let contract_id = signed.accepted_contract.get_contract_id();
+            storage.update_contract(&Contract::Signed(signed)).unwrap();
+
+            let policy = RetryPolicy {
+                max_attempts: 3,
+                initial_delay: Duration::from_secs(10),
+                max_delay: Duration::from_secs(15),
+            };
+            assert_eq!(policy.delay(1), Duration::from_secs(10));
+            assert_eq!(policy.delay(2), Duration::from_secs(15));
+
+            let retries = DeliveryRetries::new(policy);
+            let unfunded = |_: &Txid| Ok(false);
+            // Nothing is resent before it was tracked.
+            assert!(retries.due(&storage, 10, unfunded).unwrap().resend.is_empty());
+            retries.track(contract_id, counter_party, 0);
+            assert!(retries.due(&storage, 9, unfunded).unwrap().resend.is_empty());
+            let round = retries.due(&storage, 10, unfunded).unwrap();
+            assert_eq!(round.resend.len(), 1);
+            assert!(matches!(round.resend[0], (id, to, Message::Sign(_)) if id == contract_id && to == counter_party));
+            // A failed funding lookup does not count as a send.
+            assert!(retries.due(&storage, 25, |_| Err(anyhow::anyhow!("esplora is down"))).unwrap().resend.is_empty());
+            assert_eq!(retries.due(&storage, 25, unfunded).unwrap().resend.len(), 1);
+
+            let round = retries.due(&storage, 40, unfunded).unwrap();
+            assert!(round.resend.is_empty());
+            let expected = DeadLetter { contract_id, counter_party, attempts: 3, failed_at: 40 };
+            assert_eq!(round.dead_letters, vec![expected.clone()]);
+            assert_eq!(storage.get_dead_letter(&contract_id).unwrap(), Some(expected));
+
+            // Nothing more is sent until the dead letter is retried.
+            let round = retries.due(&storage, 1_000, unfunded).unwrap();
+            assert!(round.resend.is_empty() && round.dead_letters.is_empty());
+            storage.delete_dead_letter(&contract_id).unwrap();
+            retries.track(contract_id, counter_party, 1_000);
+            assert_eq!(retries.due(&storage, 1_010, unfunded).unwrap().resend.len(), 1);
+
+            // Once the funding transaction is seen the sign message was received, however
+            // long the contract waits for confirmations.
+            let round = retries.due(&storage, 1_025, |_| Ok(true)).unwrap();
+            assert!(round.resend.is_empty() && round.dead_letters.is_empty());
+            let round = retries.due(&storage, 10_000, unfunded).unwrap();
+            assert!(round.resend.is_empty() && round.dead_letters.is_empty());
+            assert_eq!(storage.get_dead_letter(&contract_id).unwrap(), None);
+
+            // An acknowledged message is no longer tracked.
+            retries.track(contract_id, counter_party, 10_000);
+            retries.acknowledged(&contract_id);
+            assert!(retries.sends.lock().unwrap().is_empty());
+            assert!(retries.due(&storage, 10_010, unfunded).unwrap().resend.is_empty());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
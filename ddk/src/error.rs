@@ -1,4 +1,5 @@
 use bdk_esplora::esplora_client::Error as EsploraError;
+use bitcoin::{Amount, Network};
 use dlc_manager::error::Error as ManagerError;
 
 #[derive(Debug)]
@@ -64,4 +65,134 @@ pub enum WalletError {
     SendMessage(String),
     #[error("Bincode error")]
     Bincode(#[from] bincode::Error),
+    #[error("Insufficient funds. Needed {needed}, available {available}.")]
+    InsufficientFunds { needed: Amount, available: Amount },
+    #[error("Address {address} is not valid for {network}.")]
+    AddressNetworkMismatch { address: String, network: Network },
+    #[error("Could not build transaction: {0}")]
+    CreateTx(String),
+    #[error("Could not derive key: {0}")]
+    KeyDerivation(#[from] bitcoin::bip32::Error),
+    #[error("Error with the wallet seed: {0}")]
+    Seed(String),
+    #[error("Could not load or create the wallet: {0}")]
+    Load(String),
+    #[error("Wallet io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not read contracts: {0}")]
+    Contracts(String),
+    #[error("Utxo {0} is not a spendable utxo of the wallet.")]
+    UtxoNotSpendable(bitcoin::OutPoint),
+    #[error("Could not decrypt the wallet database. The passphrase is wrong or the database is corrupt.")]
+    DecryptionFailed,
+    #[error("The wallet database is not encrypted. Open it without a passphrase.")]
+    NotEncrypted,
+    #[error("The wallet database is encrypted. Open it with its passphrase.")]
+    PassphraseRequired,
+    #[error("Invalid wallet descriptor: {0}")]
+    InvalidDescriptor(String),
+    #[error("The wallet descriptors are not for {network}.")]
+    DescriptorNetworkMismatch { network: Network },
+    #[error("The wallet is watch-only and cannot sign.")]
+    WatchOnly,
+    #[error("Data directory {} does not exist and cannot be created.", .0.display())]
+    DataDirUnwritable(std::path::PathBuf),
+    #[error("Spending policy rejected the {purpose} transaction: {reason}")]
+    PolicyRejected { purpose: crate::policy::TxPurpose, reason: String },
+    #[error("CET {0} is held back until fees drop or its settlement delay ends.")]
+    SettlementHeld(bitcoin::Txid),
+}
+
+impl From<WalletError> for ManagerError {
+    fn from(e: WalletError) -> ManagerError {
+        ManagerError::WalletError(Box::new(e))
+    }
+}
+
+/// A counterparty message that does not match the negotiation it claims to be part of.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum NegotiationError {
+    #[error("Terms do not match the stored contract: {0}.")]
+    TermsMismatch(String),
+    #[error("Offer is not signed by the node key of the counterparty.")]
+    UnsignedOffer,
+    #[error("Offer signature is not valid for the node key of the counterparty.")]
+    InvalidOfferSignature,
+    #[error("CET adaptor signature {index} of contract {contract_id} is not valid.")]
+    InvalidAdaptorSignature { contract_id: String, index: usize },
+}
+
+/// A contract DDK refuses to take on.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ContractError {
+    #[error("Contract limit reached. {active} contracts are active and the limit is {limit}.")]
+    ContractLimitReached { active: usize, limit: usize },
+    #[error("Contract id `{0}` is not hex.")]
+    InvalidId(String),
+    #[error("No contract id starts with `{0}`.")]
+    UnknownContract(String),
+    #[error("Contract id prefix `{prefix}` is ambiguous. It matches {}.", .matches.join(", "))]
+    AmbiguousPrefix { prefix: String, matches: Vec<String> },
+    #[error("Funding output does not match the contract: {0}.")]
+    FundingMismatch(String),
+    #[error("Refund transaction does not match the contract: {0}.")]
+    RefundMismatch(String),
+    #[error("No message was sent for contract {0}.")]
+    NoOutboundMessage(String),
+    #[error("No message of contract {0} is dead-lettered.")]
+    NoDeadLetter(String),
+    #[error("Offer is not for {network}: {reason}.")]
+    NetworkMismatch { network: Network, reason: String },
+    #[error("Collateral of {collateral} sats is over the cap of {cap} sats per contract.")]
+    CollateralCapExceeded { collateral: u64, cap: u64 },
+    #[error("Funding the contract costs {cost} sats with fees but only {available} sats are spendable.")]
+    InsufficientFunds { cost: u64, available: u64 },
+    #[error("Contract {contract_id} already exists as a {state} contract. Only closed, refunded, rejected or failed contracts can be overwritten.")]
+    ContractExists { contract_id: String, state: String },
+}
+
+/// An oracle that could not provide what a contract needs from it.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum OracleError {
+    #[error("Oracle is unavailable for event {event_id} after {attempts} attempts: {reason}")]
+    Unavailable {
+        event_id: String,
+        attempts: u32,
+        reason: String,
+    },
+}
+
+#[cfg(feature = "nostr")]
+#[derive(thiserror::Error, Debug)]
+pub enum NostrError {
+    #[error("Invalid relay url `{0}`. Relays must use a ws:// or wss:// url.")]
+    InvalidRelayUrl(String),
+    #[error("Nostr key error: {0}")]
+    Key(#[from] nostr::key::Error),
+    #[error("Could not derive the nostr key: {0}")]
+    KeyDerivation(#[from] WalletError),
+    #[error("Secp256k1 error: {0}")]
+    Secp256k1(#[from] nostr::secp256k1::Error),
+    #[error("Invalid node public key: {0}")]
+    NodeKey(String),
+    #[error("Could not encrypt or decrypt message: {0}")]
+    Encryption(#[from] nostr::nips::nip04::Error),
+    #[error("Could not build event: {0}")]
+    EventBuilder(#[from] nostr::event::builder::Error),
+    #[error("Invalid base64 content: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("Could not decode DLC message: {0}")]
+    Decode(String),
+    #[error("Segmented DLC messages are not supported over nostr.")]
+    SegmentedMessage,
+    #[error("Relay client error: {0}")]
+    Client(#[from] nostr_sdk::client::Error),
+    #[error("Nostr event worker stopped.")]
+    WorkerStopped,
+    #[error("Relay `{0}` is not one of the relays of the handler.")]
+    UnknownRelay(String),
+    #[error("Could not read or save the relay list: {0}")]
+    RelayList(#[from] std::io::Error),
+    #[error("Only {acks} relays accepted the DLC message. {quorum} must accept it.")]
+    DeliveryQuorumNotMet { acks: usize, quorum: usize },
 }
@@ -0,0 +1,191 @@
+use crate::storage::{DeadLetter, FundingConflict};
+use crate::util::contract_state;
+use crate::DdkEventHandler;
+use async_trait::async_trait;
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::signed_contract::SignedContract;
+use dlc_manager::contract::Contract;
+use dlc_manager::ContractId;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// A contract state transition to pass to the [DdkEventHandler].
+#[derive(Debug, Clone)]
+pub(crate) enum DdkEvent {
+    OfferReceived(OfferedContract),
+    ContractConfirmed(SignedContract),
+    ContractClosed(Contract),
+    FundingConflict(FundingConflict),
+    DeliveryFailed(DeadLetter),
+}
+
+/// Spawns a task that calls the handler for each event, one at a time, in the order sent.
+/// Each call runs in its own task so a panicking handler is logged instead of stopping DDK.
+pub(crate) fn spawn_dispatcher(
+    runtime: &Runtime,
+    handler: Arc<dyn DdkEventHandler>,
+) -> UnboundedSender<DdkEvent> {
+    let (sender, mut receiver) = unbounded_channel::<DdkEvent>();
+    runtime.spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            let handler = handler.clone();
+            let result = tokio::spawn(async move { dispatch(handler.as_ref(), event).await }).await;
+            if let Err(e) = result {
+                tracing::error!(error=?e, "Event handler panicked.");
+            }
+        }
+    });
+    sender
+}
+
+/// Passes each event to several handlers in turn.
+pub(crate) struct EventHandlers(pub Vec<Arc<dyn DdkEventHandler>>);
+
+#[async_trait]
+impl DdkEventHandler for EventHandlers {
+    async fn on_offer_received(&self, offer: &OfferedContract) {
+        for handler in &self.0 {
+            handler.on_offer_received(offer).await;
+        }
+    }
+
+    async fn on_contract_confirmed(&self, contract: &SignedContract) {
+        for handler in &self.0 {
+            handler.on_contract_confirmed(contract).await;
+        }
+    }
+
+    async fn on_contract_closed(&self, contract: &Contract) {
+        for handler in &self.0 {
+            handler.on_contract_closed(contract).await;
+        }
+    }
+
+    async fn on_funding_conflict(&self, conflict: &FundingConflict) {
+        for handler in &self.0 {
+            handler.on_funding_conflict(conflict).await;
+        }
+    }
+
+    async fn on_delivery_failed(&self, dead_letter: &DeadLetter) {
+        for handler in &self.0 {
+            handler.on_delivery_failed(dead_letter).await;
+        }
+    }
+}
+
+async fn dispatch(handler: &dyn DdkEventHandler, event: DdkEvent) {
+    match event {
+        DdkEvent::OfferReceived(offer) => handler.on_offer_received(&offer).await,
+        DdkEvent::ContractConfirmed(contract) => handler.on_contract_confirmed(&contract).await,
+        DdkEvent::ContractClosed(contract) => handler.on_contract_closed(&contract).await,
+        DdkEvent::FundingConflict(conflict) => handler.on_funding_conflict(&conflict).await,
+        DdkEvent::DeliveryFailed(dead_letter) => handler.on_delivery_failed(&dead_letter).await,
+    }
+}
+
+/// Snapshot of the state of every contract.
+pub(crate) fn contract_states(contracts: &[Contract]) -> HashMap<ContractId, &'static str> {
+    contracts
+        .iter()
+        .map(|c| (c.get_id(), contract_state(c)))
+        .collect()
+}
+
+/// Events for contracts that changed state since the `before` snapshot was taken.
+pub(crate) fn state_changes(
+    before: &HashMap<ContractId, &'static str>,
+    after: Vec<Contract>,
+) -> Vec<DdkEvent> {
+    let mut events = Vec::new();
+    for contract in after {
+        if before.get(&contract.get_id()) == Some(&contract_state(&contract)) {
+            continue;
+        }
+
+        match contract {
+            Contract::Offered(offer) if !offer.is_offer_party => {
+                events.push(DdkEvent::OfferReceived(offer))
+            }
+            Contract::Confirmed(signed) => events.push(DdkEvent::ContractConfirmed(signed)),
+            Contract::Closed(_) | Contract::Refunded(_) => {
+                events.push(DdkEvent::ContractClosed(contract))
+            }
+            _ => (),
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{offered_contract, signed_contract};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn confirmation_is_a_state_change() {
+        let signed = signed_contract();
+        let before = contract_states(&[Contract::Signed(signed.clone())]);
+
+        let events = state_changes(&before, vec![Contract::Signed(signed.clone())]);
+        assert!(events.is_empty());
+
+        let events = state_changes(&before, vec![Contract::Confirmed(signed.clone())]);
+        assert!(matches!(events[..], [DdkEvent::ContractConfirmed(_)]));
+
+        let events = state_changes(&before, vec![Contract::Refunded(signed)]);
+        assert!(matches!(events[..], [DdkEvent::ContractClosed(_)]));
+    }
+
+    #[test]
+    fn own_offer_is_not_received() {
+        let mut offer = offered_contract();
+        offer.is_offer_party = true;
+        let events = state_changes(&HashMap::new(), vec![Contract::Offered(offer.clone())]);
+        assert!(events.is_empty());
+
+        offer.is_offer_party = false;
+        let events = state_changes(&HashMap::new(), vec![Contract::Offered(offer)]);
+        assert!(matches!(events[..], [DdkEvent::OfferReceived(_)]));
+    }
+
+    struct PanickingHandler {
+        calls: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl DdkEventHandler for PanickingHandler {
+        async fn on_offer_received(&self, _offer: &OfferedContract) {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            panic!("handler panic");
+        }
+    }
+
+    #[test]
+    fn panicking_handler_does_not_stop_dispatch() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let calls = Arc::new(AtomicU32::new(0));
+        let sender = spawn_dispatcher(
+            &runtime,
+            Arc::new(PanickingHandler {
+                calls: calls.clone(),
+            }),
+        );
+
+        sender.send(DdkEvent::OfferReceived(offered_contract())).unwrap();
+        sender.send(DdkEvent::OfferReceived(offered_contract())).unwrap();
+
+        runtime.block_on(async {
+            while calls.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        });
+        assert!(!sender.is_closed());
+    }
+}
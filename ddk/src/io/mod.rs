@@ -1,14 +1,30 @@
-use bitcoin::bip32::Xpriv;
+use bitcoin::bip32::{DerivationPath, Xpriv};
+use bitcoin::secp256k1::{Secp256k1, SecretKey};
 use bitcoin::Network;
+use std::str::FromStr;
 use bitcoin::key::rand;
 use rand::Fill;
 use std::{fs::File, io::Write, path::Path};
+use std::path::PathBuf;
 use crate::config::SeedConfig;
+use crate::error::WalletError;
 
+/// Create `dir` and its parents if they are missing. Fails with
+/// [WalletError::DataDirUnwritable] if they cannot be created, as with a read-only home in a
+/// container.
+pub fn ensure_data_dir(dir: &Path) -> Result<(), WalletError> {
+    std::fs::create_dir_all(dir).map_err(|e| {
+        tracing::error!(dir = dir.display().to_string(), error=?e, "Could not create the data directory.");
+        WalletError::DataDirUnwritable(dir.to_path_buf())
+    })
+}
+
+/// Load the seed of `seed_config`. A seed file is created with a new seed, along with its
+/// directory, if there is none yet.
 pub fn xprv_from_config(
     seed_config: &SeedConfig,
     network: Network,
-) -> anyhow::Result<Xpriv> {
+) -> Result<Xpriv, WalletError> {
     let seed = match seed_config {
         SeedConfig::Bytes(bytes) => Xpriv::new_master(network, bytes)?,
         SeedConfig::File(file) => {
@@ -19,9 +35,14 @@ pub fn xprv_from_config(
                 let xprv = Xpriv::new_master(network, &seed)?;
                 xprv
             } else {
-                let mut file = File::create(format!("{file}/seed.ddk"))?;
+                let dir = PathBuf::from(file);
+                ensure_data_dir(&dir)?;
+                let mut file = File::create(dir.join("seed.ddk"))
+                    .map_err(|_| WalletError::DataDirUnwritable(dir.clone()))?;
                 let mut entropy = [0u8; 64];
-                entropy.try_fill(&mut rand::thread_rng())?;
+                entropy
+                    .try_fill(&mut rand::thread_rng())
+                    .map_err(|e| WalletError::Seed(e.to_string()))?;
                 // let _mnemonic = Mnemonic::from_entropy(&entropy)?;
                 let xprv = Xpriv::new_master(network, &entropy)?;
                 file.write_all(&entropy)?;
@@ -32,3 +53,78 @@ pub fn xprv_from_config(
 
     Ok(seed)
 }
+
+/// NIP-06 derivation path for the Nostr identity key.
+pub const NOSTR_DERIVATION_PATH: &str = "m/44'/1237'/0'/0/0";
+
+/// Derive the Nostr identity key from the wallet seed so that the seed backs up both.
+pub fn nostr_secret_key_from_xprv(xprv: &Xpriv) -> Result<SecretKey, WalletError> {
+    let secp = Secp256k1::new();
+    let path = DerivationPath::from_str(NOSTR_DERIVATION_PATH)?;
+    Ok(xprv.derive_priv(&secp, &path)?.private_key)
+}
+
+/// NIP-19 bech32 encoding of a Nostr key, `npub` for public keys and `nsec` for secret keys.
+pub fn nip19_encode(hrp: &str, key: &[u8; 32]) -> String {
+    let hrp = bitcoin::bech32::Hrp::parse(hrp).expect("valid NIP-19 prefix");
+    bitcoin::bech32::encode::<bitcoin::bech32::Bech32>(hrp, key).expect("32 byte keys fit bech32")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://github.com/nostr-protocol/nips/blob/master/06.md
+    const MNEMONIC: &str =
+        "leader monkey parrot ring guide accident before fence cannon height naive bean";
+    const NOSTR_SECRET_KEY: &str =
+        "7f7ff03d123792d6ac594bfa67bf6d0c0ab55b6b1fdb6249303fe861f1ccba9a";
+    const NOSTR_PUBLIC_KEY: &str =
+        "17162c921dc4d2518f9a101db33695df1afb56ab82f5ff3e5da6eec3ca5cd917";
+
+    #[test]
+    fn seed_dir_is_created_or_reported_unwritable() {
+        let dir = "tests/data/seed_dir/nested";
+        let config = SeedConfig::File(dir.to_string());
+        let xprv = xprv_from_config(&config, Network::Regtest).unwrap();
+        assert_eq!(xprv, xprv_from_config(&config, Network::Regtest).unwrap());
+
+        // A directory cannot be made under a file, even by root.
+        let blocked = "tests/data/seed_dir/nested/seed.ddk/keys";
+        let config = SeedConfig::File(blocked.to_string());
+        let error = xprv_from_config(&config, Network::Regtest).unwrap_err();
+        assert!(matches!(error, WalletError::DataDirUnwritable(path) if path == Path::new(blocked)));
+
+        std::fs::remove_dir_all("tests/data/seed_dir").unwrap();
+    }
+
+    #[test]
+    fn restore_wallet_and_nostr_from_mnemonic() {
+        let seed = bip39::Mnemonic::parse(MNEMONIC).unwrap().to_seed("");
+        let config = SeedConfig::Bytes(seed);
+
+        let xprv = xprv_from_config(&config, Network::Regtest).unwrap();
+        let restored = xprv_from_config(&config, Network::Regtest).unwrap();
+        assert_eq!(xprv, restored);
+
+        let nostr_key = nostr_secret_key_from_xprv(&restored).unwrap();
+        assert_eq!(hex::encode(nostr_key.secret_bytes()), NOSTR_SECRET_KEY);
+        let (public_key, _) = nostr_key.x_only_public_key(&Secp256k1::new());
+        assert_eq!(public_key.to_string(), NOSTR_PUBLIC_KEY);
+    }
+
+    // https://github.com/nostr-protocol/nips/blob/master/19.md
+    #[test]
+    fn nip19_keys() {
+        let public_key = hex::decode("3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d").unwrap();
+        assert_eq!(
+            nip19_encode("npub", &public_key.try_into().unwrap()),
+            "npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6"
+        );
+        let secret_key = hex::decode("67dea2ed018072d675f5415ecfaed7d2597555e202d85b3d65ea4e58d2d92ffa").unwrap();
+        assert_eq!(
+            nip19_encode("nsec", &secret_key.try_into().unwrap()),
+            "nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5"
+        );
+    }
+}
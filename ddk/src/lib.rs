@@ -2,28 +2,48 @@
 
 #![allow(dead_code)]
 // #![allow(unused_imports)]
-mod chain;
 // pub mod ddk;
 mod ddk;
-mod error;
+mod events;
 mod io;
+mod rng;
 mod signer;
 mod test_util;
 
+/// Parallel verification of CET adaptor signatures.
+pub mod adaptor;
 /// Build a DDK application.
 pub mod builder;
+/// Esplora access and confirmation monitoring.
+pub mod chain;
+/// Bounded resends of DLC messages the counterparty did not answer.
+pub mod delivery;
+/// DLC channels that update contracts off-chain.
+#[cfg(feature = "channels")]
+pub mod channel;
+/// Error types.
+pub mod error;
 /// Configuration for a DDK application.
 pub mod config;
 /// DLC utilities.
 pub mod util;
 /// Oracle clients.
 pub mod oracle;
+/// Offers that can be filled by several takers.
+pub mod order;
+/// Approval of transactions before they are broadcast.
+pub mod policy;
+/// Block mining and wallet funding for regtest DLC tests.
+#[cfg(any(test, feature = "regtest"))]
+pub mod regtest;
 /// Storage implementations.
 pub mod storage;
 /// Transport services.
 pub mod transport;
 /// The internal [bdk::Wallet].
 pub mod wallet;
+/// HTTP callbacks for contract events.
+pub mod webhook;
 /// DDK object with all services
 pub use ddk::DlcDevKit;
 /// Type alias for [dlc_manager::manager::Manager]
@@ -34,21 +54,47 @@ pub use bitcoin;
 pub use dlc;
 pub use dlc_manager;
 pub use dlc_messages;
+pub use lightning;
 pub use bdk_wallet::LocalOutput;
 
+/// Version of the ddk crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+mod dlc_versions {
+    include!(concat!(env!("OUT_DIR"), "/dlc_versions.rs"));
+}
+
+/// Version of rust-dlc that DDK is built on, as resolved in Cargo.lock.
+pub const RUST_DLC_VERSION: &str = dlc_versions::RUST_DLC_VERSION;
+/// The `protocol_version` of the DLC messages rust-dlc sends and accepts, from
+/// `[package.metadata.dlc]` in the manifest.
+pub const DLC_PROTOCOL_VERSION: u32 = dlc_versions::DLC_PROTOCOL_VERSION;
+
 /// Nostr relay host. TODO: nostr feature
 pub const RELAY_HOST: &str = "ws://localhost:8081";
 /// Default, local oracle host.
 pub const ORACLE_HOST: &str = "http://localhost:8080";
 /// Default, local esplora host.
 pub const ESPLORA_HOST: &str = "http://localhost:30000";
+/// Default time an offer stays open before it is abandoned. Three hours.
+pub const DEFAULT_OFFER_EXPIRY_SECS: u64 = 3 * 60 * 60;
+/// Default confirmations before a utxo is spent. Unconfirmed utxos are spendable.
+pub const DEFAULT_MIN_CONFIRMATIONS: u32 = 0;
+/// Default seconds between periodic checks of contracts.
+pub const DEFAULT_CHECK_INTERVAL_SECS: u64 = 30;
+/// Default jitter of the periodic check interval, in percent either way.
+pub const DEFAULT_CHECK_JITTER_PERCENT: u8 = 10;
 
 use async_trait::async_trait;
+use bitcoin::secp256k1::ecdsa::Signature;
 use bitcoin::secp256k1::PublicKey;
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::signed_contract::SignedContract;
+use dlc_manager::contract::Contract;
+use dlc_manager::ContractId;
 use dlc_messages::oracle_msgs::OracleAnnouncement;
-use dlc_messages::Message;
+use dlc_messages::{Message, OfferDlc};
 use signer::DeriveSigner;
-use transport::PeerInformation;
+use transport::{MessageReceipt, PeerInformation};
 use bdk_wallet::WalletPersister;
 use bitcoin::key::XOnlyPublicKey;
 
@@ -72,20 +118,105 @@ pub trait DdkTransport: std::marker::Send + std::marker::Sync + 'static {
     fn peer_manager(&self) -> Self::PeerManager;
     /// Process messages
     fn process_messages(&self);
-    /// Send a message to a specific counterparty.
-    fn send_message(&self, counterparty: PublicKey, message: Message);
+    /// Send a message to a specific counterparty. The receipt tells how far the message got.
+    async fn send_message(&self, counterparty: PublicKey, message: Message) -> anyhow::Result<MessageReceipt>;
     /// Get messages that have not been processed yet.
     fn get_and_clear_received_messages(&self) -> Vec<(PublicKey, Message)>;
     /// If their are messages that still need to be processed.
     fn has_pending_messages(&self) -> bool;
     /// Connect to another peer
     async fn connect_outbound(&self, pubkey: PublicKey, host: &str);
+    /// Whether `counterparty` is reachable on the transport right now. `None` if the
+    /// transport cannot tell.
+    fn counterparty_online(&self, _counterparty: &PublicKey) -> Option<bool> {
+        None
+    }
+    /// The signature `counterparty` sent with `offer`, for transports that carry one. See
+    /// [crate::util::sign_offer].
+    fn offer_signature(&self, _counterparty: &PublicKey, _offer: &OfferDlc) -> Option<Signature> {
+        None
+    }
 }
 
 /// Storage for DLC contracts.
 pub trait DdkStorage: dlc_manager::Storage + DeriveSigner + std::marker::Send + std::marker::Sync + 'static + WalletPersister {
+    /// Name for the storage backend.
+    fn name(&self) -> String;
     fn list_peers(&self) -> anyhow::Result<Vec<PeerInformation>>;
     fn save_peer(&self, peer: PeerInformation) -> anyhow::Result<()>;
+    /// Store the unix timestamp (seconds) that an offer expires at.
+    fn save_offer_expiry(&self, contract_id: &ContractId, expiry: u64) -> anyhow::Result<()>;
+    /// Retrieve the expiry of an offer, if one was set.
+    fn get_offer_expiry(&self, contract_id: &ContractId) -> anyhow::Result<Option<u64>>;
+    /// Remove the expiry of an offer once it is accepted or abandoned.
+    fn delete_offer_expiry(&self, contract_id: &ContractId) -> anyhow::Result<()>;
+    /// Force any buffered writes to disk.
+    fn flush(&self) -> anyhow::Result<()>;
+    /// Store an oracle announcement, keyed by its event id.
+    fn save_announcement(&self, announcement: &OracleAnnouncement) -> anyhow::Result<()>;
+    /// Retrieve a stored announcement. Errors unless the oracle signature of the stored
+    /// announcement still verifies, so an announcement changed after it was stored is refused.
+    fn get_announcement(&self, event_id: &str) -> anyhow::Result<Option<OracleAnnouncement>>;
+    /// Store the remaining capacity of an offer that allows partial fills.
+    fn save_partial_offer(&self, contract_id: &ContractId, partial: &order::PartialOffer) -> anyhow::Result<()>;
+    /// Retrieve a partially fillable offer.
+    fn get_partial_offer(&self, contract_id: &ContractId) -> anyhow::Result<Option<order::PartialOffer>>;
+    /// Every partially fillable offer, with the id of the offer for the whole amount.
+    fn get_partial_offers(&self) -> anyhow::Result<Vec<(ContractId, order::PartialOffer)>>;
+    /// Remove a partially fillable offer once it is cancelled.
+    fn delete_partial_offer(&self, contract_id: &ContractId) -> anyhow::Result<()>;
+    /// Contracts that settle on the oracle event `event_id`.
+    fn get_contracts_by_oracle_event(&self, event_id: &str) -> anyhow::Result<Vec<Contract>>;
+    /// Every stored DLC channel, in any state.
+    fn list_channels(&self) -> anyhow::Result<Vec<dlc_manager::channel::Channel>>;
+    /// Attach a note to a transaction or contract. An empty label removes it.
+    fn save_label(&self, key: &storage::LabelKey, label: &str) -> anyhow::Result<()>;
+    /// Retrieve the note of a transaction or contract.
+    fn get_label(&self, key: &storage::LabelKey) -> anyhow::Result<Option<String>>;
+    /// Fees recorded for a contract as it was signed and closed.
+    fn get_contract_fees(&self, contract_id: &ContractId) -> anyhow::Result<Option<util::ContractFees>>;
+    /// Every state transition of a contract, oldest first.
+    fn get_contract_history(&self, contract_id: &ContractId) -> anyhow::Result<Vec<storage::ContractTransition>>;
+    /// Store the confirmation target, in blocks, that the fee rate of an offer was estimated for.
+    fn save_funding_target(&self, contract_id: &ContractId, target_blocks: u16) -> anyhow::Result<()>;
+    /// The confirmation target of a contract offered for one. Found by the final or temporary id.
+    fn get_funding_target(&self, contract_id: &ContractId) -> anyhow::Result<Option<u16>>;
+    /// Store the last DLC message sent for a contract and the counterparty it was sent to.
+    fn save_outbound_message(&self, contract_id: &ContractId, counter_party: &PublicKey, message: &Message) -> anyhow::Result<()>;
+    /// The last DLC message sent for a contract. Found by the final or temporary id.
+    fn get_outbound_message(&self, contract_id: &ContractId) -> anyhow::Result<Option<(PublicKey, Message)>>;
+    /// Record that a funding input of a contract was spent by another transaction.
+    fn save_funding_conflict(&self, conflict: &storage::FundingConflict) -> anyhow::Result<()>;
+    /// The funding conflict a contract failed with, if any.
+    fn get_funding_conflict(&self, contract_id: &ContractId) -> anyhow::Result<Option<storage::FundingConflict>>;
+    /// Save the settlement delay of one contract, overriding the one set on the builder.
+    fn save_settlement_delay(&self, contract_id: &ContractId, delay: &policy::SettlementDelay) -> anyhow::Result<()>;
+    /// The settlement delay set for a contract, if any.
+    fn get_settlement_delay(&self, contract_id: &ContractId) -> anyhow::Result<Option<policy::SettlementDelay>>;
+    /// Record that DDK gave up resending the last message of a contract.
+    fn save_dead_letter(&self, dead_letter: &storage::DeadLetter) -> anyhow::Result<()>;
+    /// The dead-lettered message of a contract, if any. Found by the final or temporary id.
+    fn get_dead_letter(&self, contract_id: &ContractId) -> anyhow::Result<Option<storage::DeadLetter>>;
+    /// Remove a dead letter by the id it was stored under.
+    fn delete_dead_letter(&self, contract_id: &ContractId) -> anyhow::Result<()>;
+    /// Store a contract recovered from an export. A stored contract with the same final or
+    /// temporary id fails the import with [error::ContractError::ContractExists], unless
+    /// `overwrite` is set and the stored contract is terminal. See [util::is_terminal].
+    ///
+    /// The default reads and then writes the contract, so implementations should override it
+    /// to check and write in one transaction.
+    fn import_contract(&self, contract: &Contract, overwrite: bool) -> anyhow::Result<()> {
+        for id in [contract.get_id(), contract.get_temporary_id()] {
+            let Some(existing) = self.get_contract(&id)? else {
+                continue;
+            };
+            if let Some(conflict) = util::import_conflict(&existing, overwrite) {
+                return Err(conflict.into());
+            }
+        }
+        self.update_contract(contract)?;
+        Ok(())
+    }
 }
 
 /// Oracle client
@@ -97,4 +228,33 @@ pub trait DdkOracle: dlc_manager::Oracle + std::marker::Send + std::marker::Sync
         event_id: &str,
     ) -> Result<OracleAnnouncement, dlc_manager::error::Error>;
     async fn get_public_key_async(&self) -> Result<XOnlyPublicKey, dlc_manager::error::Error>;
+    /// Announcements of the oracle that match `filter`, or `None` if the oracle cannot list
+    /// its events.
+    async fn list_events(
+        &self,
+        _filter: &oracle::EventFilter,
+    ) -> Result<Option<Vec<OracleAnnouncement>>, dlc_manager::error::Error> {
+        Ok(None)
+    }
+}
+
+/// Callbacks for embedders to react to contract state transitions.
+///
+/// Handlers are called after the new state has been written to storage. Events are delivered
+/// one at a time in the order the transitions were observed. A handler that panics is logged
+/// and does not stop DDK or later events.
+#[async_trait]
+pub trait DdkEventHandler: std::marker::Send + std::marker::Sync + 'static {
+    /// A counterparty sent an offer.
+    async fn on_offer_received(&self, _offer: &OfferedContract) {}
+    /// The funding transaction of a contract reached the required confirmations.
+    async fn on_contract_confirmed(&self, _contract: &SignedContract) {}
+    /// A contract was closed, either by a CET or the refund transaction.
+    async fn on_contract_closed(&self, _contract: &Contract) {}
+    /// A funding input of a signed contract was spent by another transaction. The contract
+    /// can never confirm and was moved to failed sign.
+    async fn on_funding_conflict(&self, _conflict: &storage::FundingConflict) {}
+    /// DDK gave up resending the last message of a contract. Nothing more is sent for it until
+    /// it is retried with [DlcDevKit::retry_dead_letter].
+    async fn on_delivery_failed(&self, _dead_letter: &storage::DeadLetter) {}
 }
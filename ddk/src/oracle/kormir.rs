@@ -41,6 +41,11 @@ impl KormirOracleClient {
         Ok(KormirOracleClient { pubkey, client, host: host.to_string() })
     }
 
+    /// The oracle host the client connects to.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
     pub async fn get_pubkey(&self) -> anyhow::Result<XOnlyPublicKey> {
         let request = reqwest::get(format!("{}/pubkey", self.host))
             .await?
@@ -126,4 +131,14 @@ impl crate::DdkOracle for KormirOracleClient {
         }
 
     }
+
+    async fn list_events(
+        &self,
+        filter: &crate::oracle::EventFilter,
+    ) -> Result<Option<Vec<OracleAnnouncement>>, dlc_manager::error::Error> {
+        let announcements = KormirOracleClient::list_events(self)
+            .await
+            .map_err(|e| Error::OracleError(format!("Could not list events. {}", e)))?;
+        Ok(Some(announcements.into_iter().filter(|announcement| filter.matches(announcement)).collect()))
+    }
 }
@@ -1,5 +1,389 @@
 mod kormir;
+#[cfg(feature = "nostr")]
+mod nostr;
 mod p2p_derivatives;
 
 pub use kormir::KormirOracleClient;
+#[cfg(feature = "nostr")]
+pub use nostr::NostrOracle;
 pub use p2p_derivatives::P2PDOracleClient;
+
+use crate::error::OracleError;
+use crate::DdkOracle;
+use anyhow::{anyhow, bail};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::{schnorr::Signature, Message, Secp256k1, XOnlyPublicKey};
+use dlc_messages::oracle_msgs::{EventDescriptor, OracleAnnouncement, OracleAttestation};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Selects oracle announcements by asset and maturity.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    /// Matches events whose id contains the asset, ignoring case. For example `btcusd`.
+    pub asset: Option<String>,
+    /// Earliest maturity, in unix seconds.
+    pub from: Option<u32>,
+    /// Latest maturity, in unix seconds.
+    pub to: Option<u32>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, announcement: &OracleAnnouncement) -> bool {
+        let event = &announcement.oracle_event;
+        let asset = self.asset.as_ref().map_or(true, |asset| {
+            event.event_id.to_lowercase().contains(&asset.to_lowercase())
+        });
+        asset
+            && self.from.map_or(true, |from| event.event_maturity_epoch >= from)
+            && self.to.map_or(true, |to| event.event_maturity_epoch <= to)
+    }
+}
+
+/// Wraps an oracle and keeps the announcements it returns. [DdkOracle::list_events] asks the
+/// oracle when it can list its events and searches the kept announcements when it cannot.
+pub struct CachingOracle<O> {
+    inner: O,
+    announcements: RwLock<BTreeMap<String, OracleAnnouncement>>,
+}
+
+impl<O: DdkOracle> CachingOracle<O> {
+    pub fn new(inner: O) -> Self {
+        Self {
+            inner,
+            announcements: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    fn cache(&self, announcement: &OracleAnnouncement) {
+        self.announcements.write().unwrap().insert(
+            announcement.oracle_event.event_id.clone(),
+            announcement.clone(),
+        );
+    }
+
+    /// Kept announcements that match `filter`, ordered by maturity.
+    pub fn cached_events(&self, filter: &EventFilter) -> Vec<OracleAnnouncement> {
+        let mut events = self
+            .announcements
+            .read()
+            .unwrap()
+            .values()
+            .filter(|announcement| filter.matches(announcement))
+            .cloned()
+            .collect::<Vec<_>>();
+        events.sort_by_key(|announcement| announcement.oracle_event.event_maturity_epoch);
+        events
+    }
+}
+
+impl<O: DdkOracle> dlc_manager::Oracle for CachingOracle<O> {
+    fn get_public_key(&self) -> XOnlyPublicKey {
+        self.inner.get_public_key()
+    }
+
+    fn get_announcement(&self, event_id: &str) -> Result<OracleAnnouncement, dlc_manager::error::Error> {
+        let announcement = self.inner.get_announcement(event_id)?;
+        self.cache(&announcement);
+        Ok(announcement)
+    }
+
+    fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, dlc_manager::error::Error> {
+        self.inner.get_attestation(event_id)
+    }
+}
+
+#[async_trait::async_trait]
+impl<O: DdkOracle> DdkOracle for CachingOracle<O> {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    async fn get_announcement_async(&self, event_id: &str) -> Result<OracleAnnouncement, dlc_manager::error::Error> {
+        let announcement = self.inner.get_announcement_async(event_id).await?;
+        self.cache(&announcement);
+        Ok(announcement)
+    }
+
+    async fn get_public_key_async(&self) -> Result<XOnlyPublicKey, dlc_manager::error::Error> {
+        self.inner.get_public_key_async().await
+    }
+
+    async fn list_events(
+        &self,
+        filter: &EventFilter,
+    ) -> Result<Option<Vec<OracleAnnouncement>>, dlc_manager::error::Error> {
+        match self.inner.list_events(filter).await? {
+            Some(events) => {
+                events.iter().for_each(|announcement| self.cache(announcement));
+                Ok(Some(events))
+            }
+            None => Ok(Some(self.cached_events(filter))),
+        }
+    }
+}
+
+/// How often an oracle is asked for an announcement before it is considered unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct OracleRetry {
+    pub attempts: u32,
+    /// Delay before the second attempt. Each later delay is twice the one before.
+    pub initial_delay: Duration,
+}
+
+impl Default for OracleRetry {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            initial_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Fetch an announcement, backing off and retrying while the oracle fails.
+pub(crate) async fn fetch_announcement<O: DdkOracle + ?Sized>(
+    oracle: &O,
+    event_id: &str,
+    retry: OracleRetry,
+) -> Result<OracleAnnouncement, OracleError> {
+    let attempts = retry.attempts.max(1);
+    let mut delay = retry.initial_delay;
+    let mut attempt = 1;
+    loop {
+        match oracle.get_announcement_async(event_id).await {
+            Ok(announcement) => return Ok(announcement),
+            Err(e) if attempt < attempts => {
+                tracing::warn!(event_id, attempt, error=?e, "Oracle request failed. Retrying.");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(OracleError::Unavailable {
+                    event_id: event_id.to_string(),
+                    attempts,
+                    reason: e.to_string(),
+                })
+            }
+        }
+    }
+}
+
+/// Checks that `attestation` was signed by the oracle of `announcement`, with the announced
+/// nonces, for outcomes the event can have. The announcement itself is checked with
+/// [OracleAnnouncement::validate].
+pub fn verify_attestation(
+    announcement: &OracleAnnouncement,
+    attestation: &OracleAttestation,
+) -> anyhow::Result<()> {
+    let event = &announcement.oracle_event;
+    if attestation.oracle_public_key != announcement.oracle_public_key {
+        bail!("Attestation is not from the oracle of event {}.", event.event_id);
+    }
+    let nonces = &event.oracle_nonces;
+    if attestation.signatures.len() != nonces.len() || attestation.outcomes.len() != nonces.len() {
+        bail!(
+            "Attestation has {} signatures for {} nonces.",
+            attestation.signatures.len(),
+            nonces.len()
+        );
+    }
+
+    let secp = Secp256k1::verification_only();
+    let signed = attestation.signatures.iter().zip(&attestation.outcomes).zip(nonces);
+    for (index, ((signature, outcome), nonce)) in signed.enumerate() {
+        if !is_event_outcome(&event.event_descriptor, index, outcome) {
+            bail!("`{}` is not an outcome of event {}.", outcome, event.event_id);
+        }
+        if signature_nonce(signature)? != *nonce {
+            bail!("Signature {} does not use the announced nonce.", index);
+        }
+        let message = Message::from_digest(sha256::Hash::hash(outcome.as_bytes()).to_byte_array());
+        secp.verify_schnorr(signature, &message, &attestation.oracle_public_key)
+            .map_err(|_| anyhow!("Signature {} of the attestation is invalid.", index))?;
+    }
+    Ok(())
+}
+
+/// Whether `outcome` can be attested with the nonce at `index` of an event.
+fn is_event_outcome(descriptor: &EventDescriptor, index: usize, outcome: &str) -> bool {
+    match descriptor {
+        EventDescriptor::EnumEvent(e) => e.outcomes.iter().any(|o| o == outcome),
+        // The first nonce of a signed event attests the sign.
+        EventDescriptor::DigitDecompositionEvent(d) if d.is_signed && index == 0 => {
+            outcome == "+" || outcome == "-"
+        }
+        EventDescriptor::DigitDecompositionEvent(d) => {
+            outcome.parse::<u16>().is_ok_and(|digit| digit < d.base)
+        }
+    }
+}
+
+/// The nonce a Schnorr signature was made with, the x coordinate of its R point.
+fn signature_nonce(signature: &Signature) -> anyhow::Result<XOnlyPublicKey> {
+    XOnlyPublicKey::from_slice(&signature.serialize()[..32])
+        .map_err(|_| anyhow!("Signature has an invalid nonce."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::Keypair;
+    use dlc_messages::oracle_msgs::{EnumEventDescriptor, OracleEvent};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// An oracle that fails the first `failures` announcement requests.
+    struct FlakyOracle {
+        announcement: OracleAnnouncement,
+        failures: u32,
+        requests: AtomicU32,
+    }
+
+    impl dlc_manager::Oracle for FlakyOracle {
+        fn get_public_key(&self) -> XOnlyPublicKey {
+            self.announcement.oracle_public_key
+        }
+
+        fn get_announcement(&self, _event_id: &str) -> Result<OracleAnnouncement, dlc_manager::error::Error> {
+            if self.requests.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(dlc_manager::error::Error::OracleError("connection refused".into()));
+            }
+            Ok(self.announcement.clone())
+        }
+
+        fn get_attestation(&self, _event_id: &str) -> Result<OracleAttestation, dlc_manager::error::Error> {
+            Err(dlc_manager::error::Error::OracleError("not attested".into()))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DdkOracle for FlakyOracle {
+        fn name(&self) -> String {
+            "flaky".into()
+        }
+
+        async fn get_announcement_async(&self, event_id: &str) -> Result<OracleAnnouncement, dlc_manager::error::Error> {
+            dlc_manager::Oracle::get_announcement(self, event_id)
+        }
+
+        async fn get_public_key_async(&self) -> Result<XOnlyPublicKey, dlc_manager::error::Error> {
+            Ok(self.announcement.oracle_public_key)
+        }
+    }
+
+    /// An announcement whose nonce is the one `sign` uses for `outcome`, and its attestation.
+    fn attested_event(outcome: &str) -> (OracleAnnouncement, OracleAttestation) {
+        let secp = Secp256k1::new();
+        let keypair = Keypair::from_seckey_slice(&secp, &[3u8; 32]).unwrap();
+        let (oracle_public_key, _) = keypair.x_only_public_key();
+        let message = Message::from_digest(sha256::Hash::hash(outcome.as_bytes()).to_byte_array());
+        let signature = secp.sign_schnorr_no_aux_rand(&message, &keypair);
+
+        let oracle_event = OracleEvent {
+            oracle_nonces: vec![signature_nonce(&signature).unwrap()],
+            event_maturity_epoch: 1_700_000_000,
+            event_descriptor: EventDescriptor::EnumEvent(EnumEventDescriptor {
+                outcomes: vec!["rain".to_string(), "sun".to_string()],
+            }),
+            event_id: "weather".to_string(),
+        };
+        let announcement = OracleAnnouncement {
+            announcement_signature: signature,
+            oracle_public_key,
+            oracle_event,
+        };
+        let attestation = OracleAttestation {
+            oracle_public_key,
+            signatures: vec![signature],
+            outcomes: vec![outcome.to_string()],
+        };
+        (announcement, attestation)
+    }
+
+    #[test]
+    fn only_valid_attestations_are_accepted() {
+        let (announcement, attestation) = attested_event("rain");
+        assert!(verify_attestation(&announcement, &attestation).is_ok());
+
+        // A signature for one outcome cannot attest another.
+        let mut forged = attestation.clone();
+        forged.outcomes = vec!["sun".to_string()];
+        assert!(verify_attestation(&announcement, &forged).is_err());
+
+        // Nor can a valid signature over an outcome the event does not have.
+        let (_, unknown) = attested_event("snow");
+        assert!(verify_attestation(&announcement, &unknown).is_err());
+
+        let (other_event, _) = attested_event("sun");
+        assert!(verify_attestation(&other_event, &attestation).is_err());
+    }
+
+    #[tokio::test]
+    async fn announcement_fetch_retries_a_flaky_oracle() {
+        let (announcement, _) = attested_event("rain");
+        let retry = OracleRetry {
+            attempts: 3,
+            initial_delay: Duration::from_millis(1),
+        };
+        let oracle = FlakyOracle {
+            announcement: announcement.clone(),
+            failures: 2,
+            requests: AtomicU32::new(0),
+        };
+        let fetched = fetch_announcement(&oracle, "weather", retry).await.unwrap();
+        assert_eq!(fetched, announcement);
+        assert_eq!(oracle.requests.load(Ordering::SeqCst), 3);
+
+        // An oracle that stays down is reported as unavailable.
+        let oracle = FlakyOracle {
+            announcement,
+            failures: u32::MAX,
+            requests: AtomicU32::new(0),
+        };
+        let err = fetch_announcement(&oracle, "weather", retry).await.unwrap_err();
+        assert!(matches!(err, OracleError::Unavailable { attempts: 3, .. }));
+        assert_eq!(oracle.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn cached_announcements_are_searched_by_asset_and_maturity() {
+        let (weather, _) = attested_event("rain");
+        let oracle = CachingOracle::new(FlakyOracle {
+            announcement: weather.clone(),
+            failures: 0,
+            requests: AtomicU32::new(0),
+        });
+        // Nothing was fetched yet, so nothing is found.
+        let all = EventFilter::default();
+        assert_eq!(oracle.list_events(&all).await.unwrap(), Some(vec![]));
+
+        oracle.get_announcement_async("weather").await.unwrap();
+        let mut btc = weather.clone();
+        btc.oracle_event.event_id = "BTCUSD-1700086400".to_string();
+        btc.oracle_event.event_maturity_epoch = 1_700_086_400;
+        oracle.cache(&btc);
+
+        let asset = EventFilter {
+            asset: Some("btcusd".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(oracle.list_events(&asset).await.unwrap(), Some(vec![btc.clone()]));
+        let from = EventFilter {
+            from: Some(1_700_000_001),
+            ..Default::default()
+        };
+        assert_eq!(oracle.list_events(&from).await.unwrap(), Some(vec![btc.clone()]));
+        let window = EventFilter {
+            asset: Some("BTCUSD".to_string()),
+            from: None,
+            to: Some(1_700_000_000),
+        };
+        assert_eq!(oracle.list_events(&window).await.unwrap(), Some(vec![]));
+        assert_eq!(oracle.list_events(&all).await.unwrap(), Some(vec![weather, btc]));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::error::NostrError;
+use crate::transport::nostr::dlc_handler::{
+    oracle_announcement_from_str, oracle_attestation_from_str,
+};
+use crate::transport::nostr::relay_handler::{
+    parse_relay_url, ORACLE_ANNOUNCMENT_KIND, ORACLE_ATTESTATION_KIND,
+};
+use bitcoin::key::XOnlyPublicKey;
+use dlc_manager::error::Error;
+use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation};
+use nostr::{Event, EventId, Filter, Keys, PublicKey, Tag};
+use nostr_relay_pool::RelayPoolNotification;
+use nostr_sdk::Client;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Oracle that publishes its announcements and attestations as nostr events. Events from the
+/// oracle are collected from a relay in the background and served from memory.
+///
+/// Attestation events reference the announcement event they attest to with an `e` tag.
+pub struct NostrOracle {
+    events: Arc<OracleEvents>,
+    /// Kept so the relay connection lives as long as the oracle.
+    _client: Client,
+}
+
+impl NostrOracle {
+    /// Subscribe to the announcements and attestations published by `oracle` on `relay_host`.
+    /// The nostr key of the oracle is its oracle key. Must be called within a tokio runtime.
+    pub async fn new(relay_host: &str, oracle: PublicKey) -> Result<NostrOracle, NostrError> {
+        let relay_url = parse_relay_url(relay_host)?;
+        let oracle_pubkey = XOnlyPublicKey::from_slice(&oracle.to_bytes())
+            .map_err(|e| NostrError::Decode(e.to_string()))?;
+        let events = Arc::new(OracleEvents::new(oracle, oracle_pubkey));
+
+        let client = Client::new(&Keys::generate());
+        client.add_relay(relay_url.as_str()).await?;
+        client.connect().await;
+
+        let mut notifications = client.notifications();
+        let listener = events.clone();
+        tokio::spawn(async move {
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayPoolNotification::Event { event, .. } = notification {
+                    listener.receive(&event);
+                }
+            }
+        });
+
+        // No `since`, so the relay also sends the events the oracle already published.
+        let filter = Filter::new()
+            .kinds([ORACLE_ANNOUNCMENT_KIND, ORACLE_ATTESTATION_KIND])
+            .author(oracle);
+        client.subscribe(vec![filter], None).await;
+        tracing::info!(oracle = oracle.to_string(), relay = relay_host, "Subscribed to nostr oracle.");
+
+        Ok(NostrOracle { events, _client: client })
+    }
+}
+
+/// Announcements and attestations received from the oracle.
+struct OracleEvents {
+    author: PublicKey,
+    oracle_pubkey: XOnlyPublicKey,
+    /// Announcements by event id, with the id of the nostr event they were published in.
+    announcements: RwLock<HashMap<String, (EventId, OracleAnnouncement)>>,
+    /// Attestations by the id of the nostr event of their announcement.
+    attestations: RwLock<HashMap<EventId, OracleAttestation>>,
+}
+
+impl OracleEvents {
+    fn new(author: PublicKey, oracle_pubkey: XOnlyPublicKey) -> Self {
+        Self {
+            author,
+            oracle_pubkey,
+            announcements: RwLock::new(HashMap::new()),
+            attestations: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Store an oracle event. Events from other authors or for other oracle keys are ignored.
+    /// Announcements are verified when DDK uses them, not here.
+    fn receive(&self, event: &Event) {
+        if event.pubkey != self.author {
+            return;
+        }
+
+        if event.kind == ORACLE_ANNOUNCMENT_KIND {
+            match oracle_announcement_from_str(&event.content) {
+                Ok(announcement) if announcement.oracle_public_key == self.oracle_pubkey => {
+                    let event_id = announcement.oracle_event.event_id.clone();
+                    self.announcements
+                        .write()
+                        .unwrap()
+                        .insert(event_id, (event.id, announcement));
+                }
+                Ok(_) => tracing::warn!("Announcement is for a different oracle key."),
+                Err(e) => tracing::warn!(error=?e, "Could not parse oracle announcement event."),
+            }
+        } else if event.kind == ORACLE_ATTESTATION_KIND {
+            let announcement_event = event.tags.iter().find_map(|tag| match tag {
+                Tag::Event { event_id, .. } => Some(*event_id),
+                _ => None,
+            });
+            let Some(announcement_event) = announcement_event else {
+                tracing::warn!("Attestation event does not reference an announcement.");
+                return;
+            };
+            match oracle_attestation_from_str(&event.content) {
+                Ok(attestation) if attestation.oracle_public_key == self.oracle_pubkey => {
+                    self.attestations
+                        .write()
+                        .unwrap()
+                        .insert(announcement_event, attestation);
+                }
+                Ok(_) => tracing::warn!("Attestation is for a different oracle key."),
+                Err(e) => tracing::warn!(error=?e, "Could not parse oracle attestation event."),
+            }
+        }
+    }
+
+    fn announcement(&self, event_id: &str) -> Result<OracleAnnouncement, Error> {
+        self.announcements
+            .read()
+            .unwrap()
+            .get(event_id)
+            .map(|(_, announcement)| announcement.clone())
+            .ok_or_else(|| Error::OracleError("Announcement not received from nostr oracle.".into()))
+    }
+
+    fn attestation(&self, event_id: &str) -> Result<OracleAttestation, Error> {
+        let announcement_event = self
+            .announcements
+            .read()
+            .unwrap()
+            .get(event_id)
+            .map(|(id, _)| *id)
+            .ok_or_else(|| Error::OracleError("Announcement not received from nostr oracle.".into()))?;
+        self.attestations
+            .read()
+            .unwrap()
+            .get(&announcement_event)
+            .cloned()
+            .ok_or_else(|| Error::OracleError("Attestation not received from nostr oracle.".into()))
+    }
+}
+
+impl dlc_manager::Oracle for NostrOracle {
+    fn get_public_key(&self) -> XOnlyPublicKey {
+        self.events.oracle_pubkey
+    }
+
+    fn get_announcement(&self, event_id: &str) -> Result<OracleAnnouncement, Error> {
+        self.events.announcement(event_id)
+    }
+
+    fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, Error> {
+        self.events.attestation(event_id)
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::DdkOracle for NostrOracle {
+    fn name(&self) -> String {
+        "nostr".into()
+    }
+
+    async fn get_public_key_async(&self) -> Result<XOnlyPublicKey, Error> {
+        Ok(self.events.oracle_pubkey)
+    }
+
+    async fn get_announcement_async(&self, event_id: &str) -> Result<OracleAnnouncement, Error> {
+        self.events.announcement(event_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::offered_contract;
+    use lightning::util::ser::Writeable;
+    use nostr::EventBuilder;
+
+    fn announcement_event(keys: &Keys, announcement: &OracleAnnouncement) -> Event {
+        let content = base64::encode(announcement.encode());
+        EventBuilder::new(ORACLE_ANNOUNCMENT_KIND, content, [])
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn announcements_are_served_from_oracle_events() {
+        let oracle = Keys::generate();
+        let announcement = offered_contract().contract_info[0].oracle_announcements[0].clone();
+        let event_id = announcement.oracle_event.event_id.clone();
+        let events = OracleEvents::new(oracle.public_key(), announcement.oracle_public_key);
+
+        // Published by someone else.
+        events.receive(&announcement_event(&Keys::generate(), &announcement));
+        assert!(events.announcement(&event_id).is_err());
+
+        events.receive(&announcement_event(&oracle, &announcement));
+        assert_eq!(events.announcement(&event_id).unwrap(), announcement);
+        assert!(events.attestation(&event_id).is_err());
+    }
+}
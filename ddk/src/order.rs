@@ -0,0 +1,284 @@
+use crate::DdkStorage;
+use anyhow::anyhow;
+use dlc::{EnumerationPayout, Payout};
+use dlc_manager::contract::contract_input::ContractInput;
+use dlc_manager::contract::enum_descriptor::EnumDescriptor;
+use dlc_manager::contract::{Contract, ContractDescriptor};
+use dlc_manager::ContractId;
+use dlc_messages::oracle_msgs::OracleAnnouncement;
+use serde::{Deserialize, Serialize};
+
+/// An offer that several takers can each fill part of. Each fill is its own contract for a
+/// share of the collateral, with payouts scaled to that share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialOffer {
+    /// Contract input for filling the whole offer.
+    pub contract_input: ContractInput,
+    /// Announcements the contract input is for.
+    pub oracle_announcements: Vec<OracleAnnouncement>,
+    /// Accept collateral that has not been filled yet.
+    pub remaining_collateral: u64,
+    /// Temporary ids of the offers sent for each fill.
+    pub fills: Vec<ContractId>,
+}
+
+impl PartialOffer {
+    pub fn new(
+        contract_input: ContractInput,
+        oracle_announcements: Vec<OracleAnnouncement>,
+    ) -> Self {
+        Self {
+            remaining_collateral: contract_input.accept_collateral,
+            contract_input,
+            oracle_announcements,
+            fills: Vec::new(),
+        }
+    }
+}
+
+/// Scales a contract input so the accepting party puts up `accept_collateral`. Collateral and
+/// payouts keep the ratios of the original input. Only enum contracts can be scaled.
+pub fn scale_contract_input(
+    input: &ContractInput,
+    accept_collateral: u64,
+) -> anyhow::Result<ContractInput> {
+    if input.accept_collateral == 0 {
+        return Err(anyhow!("Offer has no accept collateral to fill."));
+    }
+    let scale =
+        |amount: u64, of: u64| (amount as u128 * accept_collateral as u128 / of as u128) as u64;
+
+    let offer_collateral = scale(input.offer_collateral, input.accept_collateral);
+    let old_total = input.offer_collateral + input.accept_collateral;
+    let new_total = offer_collateral + accept_collateral;
+
+    let mut contract_infos = input.contract_infos.clone();
+    for info in contract_infos.iter_mut() {
+        let ContractDescriptor::Enum(descriptor) = &info.contract_descriptor else {
+            return Err(anyhow!(
+                "Partial fills are only supported for enum contracts."
+            ));
+        };
+        let outcome_payouts = descriptor
+            .outcome_payouts
+            .iter()
+            .map(|p| {
+                let offer = (p.payout.offer as u128 * new_total as u128 / old_total as u128) as u64;
+                EnumerationPayout {
+                    outcome: p.outcome.clone(),
+                    payout: Payout {
+                        offer,
+                        accept: new_total - offer,
+                    },
+                }
+            })
+            .collect();
+        info.contract_descriptor = ContractDescriptor::Enum(EnumDescriptor { outcome_payouts });
+    }
+
+    Ok(ContractInput {
+        offer_collateral,
+        accept_collateral,
+        fee_rate: input.fee_rate,
+        contract_infos,
+    })
+}
+
+/// Takes `accept_collateral` from the remaining capacity of a partial offer and returns the
+/// contract input for the fill. The offer for the whole amount must already be withdrawn.
+pub(crate) fn reserve_fill<S: DdkStorage>(
+    storage: &S,
+    offer_id: &ContractId,
+    accept_collateral: u64,
+) -> anyhow::Result<(ContractInput, PartialOffer)> {
+    let mut partial = storage
+        .get_partial_offer(offer_id)?
+        .ok_or_else(|| anyhow!("Offer does not allow partial fills."))?;
+
+    match storage.get_contract(offer_id)? {
+        Some(Contract::Rejected(_)) => (),
+        Some(Contract::Offered(_)) => {
+            return Err(anyhow!("Offer for the whole amount is still open."))
+        }
+        _ => return Err(anyhow!("Offer was accepted in full.")),
+    }
+
+    if accept_collateral == 0 || accept_collateral > partial.remaining_collateral {
+        return Err(anyhow!(
+            "Fill of {} is not within the remaining collateral of {}.",
+            accept_collateral,
+            partial.remaining_collateral
+        ));
+    }
+
+    let contract_input = scale_contract_input(&partial.contract_input, accept_collateral)?;
+    partial.remaining_collateral -= accept_collateral;
+    storage.save_partial_offer(offer_id, &partial)?;
+    Ok((contract_input, partial))
+}
+
+/// Returns `accept_collateral` taken with [reserve_fill] to the partial offer, for a fill
+/// whose offer was never sent.
+pub(crate) fn release_fill<S: DdkStorage>(
+    storage: &S,
+    offer_id: &ContractId,
+    accept_collateral: u64,
+) -> anyhow::Result<()> {
+    if let Some(mut partial) = storage.get_partial_offer(offer_id)? {
+        partial.remaining_collateral += accept_collateral;
+        storage.save_partial_offer(offer_id, &partial)?;
+    }
+    Ok(())
+}
+
+/// Returns the collateral of fills that will never be funded to their partial offers: fills
+/// the counterparty rejected, that expired or were cancelled, and fills whose accept failed.
+/// Returns the temporary ids of the released fills.
+pub(crate) fn release_failed_fills<S: DdkStorage>(storage: &S) -> anyhow::Result<Vec<ContractId>> {
+    let mut released = Vec::new();
+    for (offer_id, mut partial) in storage.get_partial_offers()? {
+        let released_before = released.len();
+        let mut open = Vec::with_capacity(partial.fills.len());
+        for fill in std::mem::take(&mut partial.fills) {
+            let offered = match storage.get_contract(&fill)? {
+                Some(Contract::Rejected(offered)) => offered,
+                Some(Contract::FailedAccept(failed)) => failed.offered_contract,
+                _ => {
+                    open.push(fill);
+                    continue;
+                }
+            };
+            partial.remaining_collateral += offered.total_collateral - offered.offer_params.collateral;
+            released.push(fill);
+        }
+        partial.fills = open;
+        if released.len() > released_before {
+            storage.save_partial_offer(&offer_id, &partial)?;
+        }
+    }
+    Ok(released)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::SledStorageProvider;
+    use crate::test_util::offered_contract;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use dlc_manager::contract::contract_input::{ContractInputInfo, OracleInput};
+    use dlc_manager::Storage;
+
+    fn enum_input() -> ContractInput {
+        let secret = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        let (public_key, _) = secret.x_only_public_key(&Secp256k1::new());
+        let payout = |outcome: &str, offer, accept| EnumerationPayout {
+            outcome: outcome.to_string(),
+            payout: Payout { offer, accept },
+        };
+        ContractInput {
+            offer_collateral: 100_000,
+            accept_collateral: 300_000,
+            fee_rate: 2,
+            contract_infos: vec![ContractInputInfo {
+                contract_descriptor: ContractDescriptor::Enum(EnumDescriptor {
+                    outcome_payouts: vec![
+                        payout("a", 400_000, 0),
+                        payout("b", 0, 400_000),
+                        payout("c", 100_000, 300_000),
+                    ],
+                }),
+                oracles: OracleInput {
+                    public_keys: vec![public_key],
+                    event_id: "event".to_string(),
+                    threshold: 1,
+                },
+            }],
+        }
+    }
+
+    #[test]
+    fn two_partial_fills_fill_the_offer() {
+        let path = "tests/data/dlc_storage/sleddb/two_partial_fills_fill_the_offer";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let input = enum_input();
+            let offer = offered_contract();
+            storage.create_contract(&offer).unwrap();
+            storage
+                .save_partial_offer(&offer.id, &PartialOffer::new(input.clone(), vec![]))
+                .unwrap();
+
+            // The whole offer must be withdrawn before it is split.
+            assert!(reserve_fill(&storage, &offer.id, 100_000).is_err());
+            storage
+                .update_contract(&Contract::Rejected(offer.clone()))
+                .unwrap();
+
+            let (first, _) = reserve_fill(&storage, &offer.id, 200_000).unwrap();
+            let (second, partial) = reserve_fill(&storage, &offer.id, 100_000).unwrap();
+            assert_eq!(partial.remaining_collateral, 0);
+            assert!(reserve_fill(&storage, &offer.id, 1).is_err());
+
+            assert_eq!(
+                first.accept_collateral + second.accept_collateral,
+                input.accept_collateral
+            );
+            assert_eq!(
+                first.offer_collateral + second.offer_collateral,
+                input.offer_collateral
+            );
+            for fill in [first, second] {
+                let total = fill.offer_collateral + fill.accept_collateral;
+                let ContractDescriptor::Enum(descriptor) =
+                    &fill.contract_infos[0].contract_descriptor
+                else {
+                    panic!("not an enum contract");
+                };
+                for p in &descriptor.outcome_payouts {
+                    assert_eq!(p.payout.offer + p.payout.accept, total);
+                }
+            }
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn fills_that_will_not_be_funded_release_their_collateral() {
+        let path = "tests/data/dlc_storage/sleddb/fills_that_will_not_be_funded_release_their_collateral";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let offer = offered_contract();
+            storage.create_contract(&offer).unwrap();
+            storage
+                .save_partial_offer(&offer.id, &PartialOffer::new(enum_input(), vec![]))
+                .unwrap();
+            storage.update_contract(&Contract::Rejected(offer.clone())).unwrap();
+
+            // A fill offer that was sent.
+            let (_, mut partial) = reserve_fill(&storage, &offer.id, 200_000).unwrap();
+            let mut fill = offered_contract();
+            fill.id = [9u8; 32];
+            fill.total_collateral = fill.offer_params.collateral + 200_000;
+            storage.create_contract(&fill).unwrap();
+            partial.fills.push(fill.id);
+            storage.save_partial_offer(&offer.id, &partial).unwrap();
+
+            // A fill whose offer could not be sent.
+            reserve_fill(&storage, &offer.id, 50_000).unwrap();
+            release_fill(&storage, &offer.id, 50_000).unwrap();
+            let remaining = |storage: &SledStorageProvider| {
+                storage.get_partial_offer(&offer.id).unwrap().unwrap().remaining_collateral
+            };
+            assert_eq!(remaining(&storage), 100_000);
+
+            // An open fill keeps its collateral until it is rejected or expires.
+            assert!(release_failed_fills(&storage).unwrap().is_empty());
+            storage.update_contract(&Contract::Rejected(fill.clone())).unwrap();
+            assert_eq!(release_failed_fills(&storage).unwrap(), vec![fill.id]);
+            assert_eq!(remaining(&storage), 300_000);
+            assert!(storage.get_partial_offer(&offer.id).unwrap().unwrap().fills.is_empty());
+            assert!(release_failed_fills(&storage).unwrap().is_empty());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
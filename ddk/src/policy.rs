@@ -0,0 +1,202 @@
+//! Checks on every transaction DDK is about to broadcast.
+//!
+//! A [SpendingPolicy] set with [crate::builder::DdkBuilder::set_spending_policy] is asked to
+//! approve wallet sends, funding transactions, CETs and refunds before they reach esplora. A
+//! denied transaction is not broadcast and the broadcast fails with
+//! [crate::error::WalletError::PolicyRejected].
+//!
+//! A [SettlementDelay] holds CETs back while fees are high, and is set for all contracts with
+//! [crate::builder::DdkBuilder::set_settlement_delay] or for one with
+//! [crate::DlcDevKit::set_settlement_delay].
+use bitcoin::{Transaction, Txid};
+use dlc_manager::contract::signed_contract::SignedContract;
+use dlc_manager::contract::Contract;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// What a transaction is broadcast for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TxPurpose {
+    /// A payment from the wallet.
+    Send,
+    /// The funding transaction of a contract.
+    Funding,
+    /// A CET that settles a contract on an oracle attestation.
+    Cet,
+    /// The refund transaction of a contract.
+    Refund,
+    /// A transaction that does not belong to a stored contract.
+    Other,
+}
+
+impl std::fmt::Display for TxPurpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let purpose = match self {
+            TxPurpose::Send => "send",
+            TxPurpose::Funding => "funding",
+            TxPurpose::Cet => "cet",
+            TxPurpose::Refund => "refund",
+            TxPurpose::Other => "other",
+        };
+        write!(f, "{}", purpose)
+    }
+}
+
+/// Answer of a [SpendingPolicy].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Approve,
+    /// Do not broadcast the transaction, for the given reason.
+    Deny(String),
+}
+
+/// Approves or denies transactions before they are broadcast.
+pub trait SpendingPolicy: Send + Sync + 'static {
+    fn approve(&self, tx: &Transaction, purpose: TxPurpose) -> Decision;
+}
+
+/// The default policy. Every transaction is broadcast.
+pub struct ApproveAll;
+
+impl SpendingPolicy for ApproveAll {
+    fn approve(&self, _tx: &Transaction, _purpose: TxPurpose) -> Decision {
+        Decision::Approve
+    }
+}
+
+/// Finds the [TxPurpose] of a transaction the dlc manager broadcasts.
+pub(crate) type Classifier = Arc<dyn Fn(&Transaction) -> TxPurpose + Send + Sync>;
+
+/// The purpose of `tx` among the transactions of `contracts`. Transactions are matched by
+/// txid, which the signatures added before broadcast do not change.
+pub fn tx_purpose(tx: &Transaction, contracts: &[Contract]) -> TxPurpose {
+    let txid = tx.compute_txid();
+    contracts
+        .iter()
+        .find_map(|contract| contract_tx_purpose(contract, &txid))
+        .unwrap_or(TxPurpose::Other)
+}
+
+fn contract_tx_purpose(contract: &Contract, txid: &Txid) -> Option<TxPurpose> {
+    let dlc_transactions = match contract {
+        Contract::Accepted(a) => &a.dlc_transactions,
+        Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => {
+            &s.accepted_contract.dlc_transactions
+        }
+        Contract::PreClosed(p) => &p.signed_contract.accepted_contract.dlc_transactions,
+        _ => return None,
+    };
+    if dlc_transactions.fund.compute_txid() == *txid {
+        Some(TxPurpose::Funding)
+    } else if dlc_transactions.refund.compute_txid() == *txid {
+        Some(TxPurpose::Refund)
+    } else if dlc_transactions.cets.iter().any(|cet| cet.compute_txid() == *txid) {
+        Some(TxPurpose::Cet)
+    } else {
+        None
+    }
+}
+
+/// Seconds before the refund locktime of a contract at which a held CET is broadcast whatever
+/// the fees are.
+pub const SETTLEMENT_REFUND_MARGIN_SECS: u64 = 24 * 60 * 60;
+
+/// How long a CET may wait for lower fees before it is broadcast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementDelay {
+    /// Longest time to hold a CET back, in seconds. The CET is broadcast earlier if the refund
+    /// locktime is less than [SETTLEMENT_REFUND_MARGIN_SECS] away.
+    pub max_delay_secs: u64,
+    /// Next block fee rate in sats/vbyte at or below which the CET is broadcast.
+    pub fee_rate: u64,
+}
+
+impl SettlementDelay {
+    /// Unix time by which a CET first ready at `ready_at` is broadcast. Refund locktimes given
+    /// as a block height leave no time to wait.
+    pub fn deadline(&self, ready_at: u64, refund_locktime: u32) -> u64 {
+        let refund_deadline = (refund_locktime as u64).saturating_sub(SETTLEMENT_REFUND_MARGIN_SECS);
+        ready_at.saturating_add(self.max_delay_secs).min(refund_deadline)
+    }
+}
+
+/// Decides whether to hold back a CET the dlc manager broadcasts.
+pub(crate) type SettlementCheck = Arc<dyn Fn(&Transaction) -> bool + Send + Sync>;
+
+/// Remembers when each held CET was first ready to broadcast. Held CETs are tried again on
+/// each periodic check. The times are not stored, so a restart starts the delay again, still
+/// bounded by the refund locktime.
+#[derive(Default)]
+pub(crate) struct SettlementTimer {
+    ready_at: Mutex<HashMap<Txid, u64>>,
+}
+
+impl SettlementTimer {
+    /// Whether to hold back `cet` at `now`, with the next block estimate at `fee_rate`.
+    pub(crate) fn hold(
+        &self,
+        cet: Txid,
+        delay: &SettlementDelay,
+        refund_locktime: u32,
+        fee_rate: u64,
+        now: u64,
+    ) -> bool {
+        let mut ready_at = self.ready_at.lock().unwrap();
+        let first_ready = *ready_at.entry(cet).or_insert(now);
+        let hold = fee_rate > delay.fee_rate && now < delay.deadline(first_ready, refund_locktime);
+        if !hold {
+            ready_at.remove(&cet);
+        }
+        hold
+    }
+}
+
+/// The confirmed contract that `cet` settles.
+pub(crate) fn settling_contract<'a>(cet: &Transaction, contracts: &'a [Contract]) -> Option<&'a SignedContract> {
+    let txid = cet.compute_txid();
+    contracts.iter().find_map(|contract| match contract {
+        Contract::Confirmed(signed)
+            if signed
+                .accepted_contract
+                .dlc_transactions
+                .cets
+                .iter()
+                .any(|c| c.compute_txid() == txid) =>
+        {
+            Some(signed)
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+
+    #[test]
+    fn held_cet_is_broadcast_by_the_deadline() {
+        let delay = SettlementDelay {
+            max_delay_secs: 3_600,
+            fee_rate: 5,
+        };
+        let timer = SettlementTimer::default();
+        let cet = Txid::from_byte_array([1u8; 32]);
+        let start = 1_700_000_000;
+        let refund_locktime = (start + 30 * 24 * 60 * 60) as u32;
+
+        // Fees never drop, yet the CET goes out once the delay has passed.
+        assert!(timer.hold(cet, &delay, refund_locktime, 50, start));
+        assert!(timer.hold(cet, &delay, refund_locktime, 50, start + 3_599));
+        assert!(!timer.hold(cet, &delay, refund_locktime, 50, start + 3_600));
+
+        // Near the refund locktime there is no waiting.
+        let near_refund = (start + SETTLEMENT_REFUND_MARGIN_SECS + 60) as u32;
+        assert!(timer.hold(cet, &delay, near_refund, 50, start));
+        assert!(!timer.hold(cet, &delay, near_refund, 50, start + 60));
+
+        // Nor is a CET held once fees are low enough.
+        assert!(!timer.hold(cet, &delay, refund_locktime, 5, start));
+    }
+}
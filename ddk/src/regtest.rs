@@ -0,0 +1,140 @@
+//! Helpers for running DLC tests against the regtest `bitcoind` and `electrs` services in
+//! `docker-compose.yaml`.
+//!
+//! The helpers block the calling thread. From an async test, call them with
+//! [tokio::task::spawn_blocking].
+use crate::wallet::DlcDevKitWallet;
+use crate::DdkStorage;
+use anyhow::anyhow;
+use bitcoin::{Amount, BlockHash, Txid};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+/// Default, local bitcoind rpc host.
+pub const BITCOIND_HOST: &str = "http://localhost:18443";
+/// Rpc user and password from `testconfig/bitcoin.conf`.
+pub const BITCOIND_AUTH: (&str, &str) = ("ddk", "ddk");
+/// The bitcoind wallet coins are mined to and sent from.
+pub const BITCOIND_WALLET: &str = "ddk";
+
+/// Blocks before a coinbase output can be spent.
+const COINBASE_MATURITY: u64 = 100;
+/// How long to wait for esplora to index a block or transaction.
+const INDEX_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Mine `count` blocks to a throwaway address and wait for esplora to index them.
+pub fn mine_blocks(count: u64) -> anyhow::Result<Vec<BlockHash>> {
+    ensure_bitcoind_wallet()?;
+    let address: String = rpc(Some(BITCOIND_WALLET), "getnewaddress", json!([]))?;
+    generate_to_address(count, &address)
+}
+
+/// Send `amount` from the bitcoind wallet to a new address of `wallet`, confirm it in a block,
+/// and sync the wallet once esplora has indexed the transaction.
+pub fn fund_wallet<S: DdkStorage>(
+    wallet: &DlcDevKitWallet<S>,
+    amount: Amount,
+) -> anyhow::Result<Txid> {
+    ensure_bitcoind_wallet()?;
+    let balance: f64 = rpc(Some(BITCOIND_WALLET), "getbalance", json!([]))?;
+    if Amount::from_btc(balance)? < amount {
+        let address: String = rpc(Some(BITCOIND_WALLET), "getnewaddress", json!([]))?;
+        generate_to_address(COINBASE_MATURITY + 1, &address)?;
+    }
+
+    let address = wallet.new_external_address()?.address;
+    let txid: Txid = rpc(
+        Some(BITCOIND_WALLET),
+        "sendtoaddress",
+        json!([address.to_string(), amount.to_btc()]),
+    )?;
+    mine_blocks(1)?;
+    wait_for_tx(&txid)?;
+    wallet.sync()?;
+    Ok(txid)
+}
+
+fn generate_to_address(count: u64, address: &str) -> anyhow::Result<Vec<BlockHash>> {
+    let start: u64 = rpc(None, "getblockcount", json!([]))?;
+    let hashes = rpc(None, "generatetoaddress", json!([count, address]))?;
+    wait_for_height(start + count)?;
+    Ok(hashes)
+}
+
+/// Load or create the bitcoind wallet. A fresh regtest node has no wallet loaded.
+fn ensure_bitcoind_wallet() -> anyhow::Result<()> {
+    let loaded: Vec<String> = rpc(None, "listwallets", json!([]))?;
+    if loaded.iter().any(|w| w == BITCOIND_WALLET) {
+        return Ok(());
+    }
+    if rpc::<Value>(None, "loadwallet", json!([BITCOIND_WALLET])).is_err() {
+        rpc::<Value>(None, "createwallet", json!([BITCOIND_WALLET]))?;
+    }
+    Ok(())
+}
+
+fn wait_for_height(height: u64) -> anyhow::Result<()> {
+    let url = format!("{}/blocks/tip/height", crate::ESPLORA_HOST);
+    poll(|| {
+        let tip = reqwest::blocking::get(&url).ok()?.text().ok()?;
+        (tip.trim().parse::<u64>().ok()? >= height).then_some(())
+    })
+    .ok_or_else(|| anyhow!("Esplora did not index block {height}."))
+}
+
+fn wait_for_tx(txid: &Txid) -> anyhow::Result<()> {
+    let url = format!("{}/tx/{}/status", crate::ESPLORA_HOST, txid);
+    poll(|| {
+        let status = reqwest::blocking::get(&url).ok()?.text().ok()?;
+        let status: Value = serde_json::from_str(&status).ok()?;
+        status["confirmed"].as_bool()?.then_some(())
+    })
+    .ok_or_else(|| anyhow!("Esplora did not index transaction {txid}."))
+}
+
+fn poll<T>(mut check: impl FnMut() -> Option<T>) -> Option<T> {
+    let deadline = Instant::now() + INDEX_TIMEOUT;
+    while Instant::now() < deadline {
+        if let Some(result) = check() {
+            return Some(result);
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+    None
+}
+
+fn rpc<T: DeserializeOwned>(wallet: Option<&str>, method: &str, params: Value) -> anyhow::Result<T> {
+    let url = match wallet {
+        Some(wallet) => format!("{BITCOIND_HOST}/wallet/{wallet}"),
+        None => BITCOIND_HOST.to_string(),
+    };
+    let response = reqwest::blocking::Client::new()
+        .post(url)
+        .basic_auth(BITCOIND_AUTH.0, Some(BITCOIND_AUTH.1))
+        .body(rpc_request(method, params).to_string())
+        .send()?
+        .text()?;
+    let response: Value = serde_json::from_str(&response)?;
+    if !response["error"].is_null() {
+        return Err(anyhow!("bitcoind {method} failed: {}", response["error"]));
+    }
+    Ok(serde_json::from_value(response["result"].clone())?)
+}
+
+fn rpc_request(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "1.0", "id": "ddk", "method": method, "params": params })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rpc_request_format() {
+        let request = rpc_request("generatetoaddress", json!([1, "bcrt1q"]));
+        assert_eq!(request["method"], "generatetoaddress");
+        assert_eq!(request["params"], json!([1, "bcrt1q"]));
+        assert_eq!(request["jsonrpc"], "1.0");
+    }
+}
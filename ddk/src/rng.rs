@@ -0,0 +1,105 @@
+//! Randomness for non-secret values that DDK generates itself. Builds with the
+//! `deterministic-rng` feature can seed it, see [crate::builder::DdkBuilder::set_rng_seed].
+//!
+//! A seed makes exactly these values reproducible:
+//! - the default node name,
+//! - the delays between periodic checks.
+//!
+//! It does not cover temporary contract ids, funding serial ids or any other value
+//! [dlc_manager] draws with its own rng, so two seeded runs still build different offers.
+//! The serial ids of our offers can be made reproducible on their own with
+//! [derive_serial_ids], see [crate::builder::DdkBuilder::set_deterministic_serial_ids].
+//! Secret keys, seeds, storage keys and encryption nonces never use this rng.
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::key::rand::{thread_rng, RngCore};
+use dlc_manager::ContractId;
+#[cfg(any(test, feature = "deterministic-rng"))]
+use bitcoin::key::rand::{rngs::StdRng, SeedableRng};
+#[cfg(any(test, feature = "deterministic-rng"))]
+use std::sync::Mutex;
+
+#[cfg(any(test, feature = "deterministic-rng"))]
+static SEEDED: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// Held by tests that seed the rng, so parallel tests do not draw from each other's seed.
+#[cfg(test)]
+pub(crate) static SEED_LOCK: Mutex<()> = Mutex::new(());
+
+/// Seed the rng. Every value drawn afterwards is reproducible for the same seed.
+#[cfg(any(test, feature = "deterministic-rng"))]
+pub(crate) fn set_seed(seed: u64) {
+    *SEEDED.lock().unwrap() = Some(StdRng::seed_from_u64(seed));
+}
+
+/// Fill `dest` from the seeded rng if there is one, otherwise from the thread rng.
+pub(crate) fn fill_bytes(dest: &mut [u8]) {
+    #[cfg(any(test, feature = "deterministic-rng"))]
+    if let Some(rng) = SEEDED.lock().unwrap().as_mut() {
+        rng.fill_bytes(dest);
+        return;
+    }
+    thread_rng().fill_bytes(dest);
+}
+
+/// A random v4 uuid.
+pub(crate) fn uuid() -> uuid::Uuid {
+    let mut bytes = [0u8; 16];
+    fill_bytes(&mut bytes);
+    uuid::Builder::from_random_bytes(bytes).into_uuid()
+}
+
+/// `count` serial ids for the funding transaction of the offer with `temporary_id`. The same
+/// secret and offer always give the same ids, which look random to anyone without `secret`.
+pub(crate) fn derive_serial_ids(secret: &[u8; 32], temporary_id: &ContractId, count: usize) -> Vec<u64> {
+    (0..count as u64)
+        .map(|index| {
+            let mut engine = sha256::Hash::engine();
+            engine.input(b"ddk/serial-id");
+            engine.input(secret);
+            engine.input(temporary_id);
+            engine.input(&index.to_be_bytes());
+            let hash = sha256::Hash::from_engine(engine);
+            u64::from_be_bytes(hash.as_byte_array()[..8].try_into().unwrap())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_is_reproducible() {
+        let _seeded = SEED_LOCK.lock().unwrap();
+        set_seed(7);
+        let (mut first, mut second) = ([0u8; 32], [0u8; 32]);
+        fill_bytes(&mut first);
+        let id = uuid();
+
+        set_seed(7);
+        fill_bytes(&mut second);
+        assert_eq!(first, second);
+        assert_eq!(id, uuid());
+        assert_eq!(id.get_version_num(), 4);
+    }
+
+    #[test]
+    fn seed_covers_names_and_check_delays_only() {
+        let _seeded = SEED_LOCK.lock().unwrap();
+        let schedule = crate::ddk::CheckSchedule::default();
+        let draw = || (uuid(), [schedule.next_delay(), schedule.next_delay(), schedule.next_delay()]);
+
+        set_seed(11);
+        let first = draw();
+        set_seed(11);
+        assert_eq!(draw(), first);
+        set_seed(12);
+        assert_ne!(draw(), first);
+
+        // Serial ids follow the wallet secret and the temporary id, whatever the seed.
+        let (secret, temporary_id) = ([1u8; 32], [2u8; 32]);
+        let ids = derive_serial_ids(&secret, &temporary_id, 4);
+        set_seed(11);
+        assert_eq!(derive_serial_ids(&secret, &temporary_id, 4), ids);
+    }
+}
@@ -0,0 +1,569 @@
+//! Write-through cache of active contracts in front of a [DdkStorage] backend.
+use crate::order::PartialOffer;
+use crate::policy::SettlementDelay;
+use crate::signer::{DeriveSigner, SignerInformation};
+use crate::storage::{ContractTransition, DeadLetter, FundingConflict, LabelKey};
+use crate::transport::PeerInformation;
+use crate::util::ContractFees;
+use crate::DdkStorage;
+use bdk_wallet::{ChangeSet, WalletPersister};
+use bitcoin::secp256k1::{PublicKey, SecretKey};
+use dlc_manager::chain_monitor::ChainMonitor;
+use dlc_manager::channel::offered_channel::OfferedChannel;
+use dlc_manager::channel::signed_channel::{SignedChannel, SignedChannelStateType};
+use dlc_manager::channel::Channel;
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::signed_contract::SignedContract;
+use dlc_manager::contract::{Contract, PreClosedContract};
+use dlc_manager::error::Error;
+use dlc_manager::{ChannelId, ContractId, Storage};
+use dlc_messages::oracle_msgs::OracleAnnouncement;
+use dlc_messages::Message;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Default number of contracts kept in memory.
+pub const DEFAULT_CONTRACT_CACHE_SIZE: usize = 256;
+
+/// Wraps a storage backend and keeps the most recently used active contracts in memory.
+///
+/// Writes go to the inner storage first and update the cache once they succeed, so the inner
+/// storage stays the source of truth. Closed, refunded and failed contracts are not cached.
+/// Lists of contracts are read from the inner storage, so memory stays bounded by `capacity`.
+pub struct CachingStorage<S: DdkStorage> {
+    inner: S,
+    contracts: Mutex<ContractCache>,
+}
+
+impl<S: DdkStorage> CachingStorage<S> {
+    /// Cache up to `capacity` contracts of `inner`.
+    pub fn new(inner: S, capacity: usize) -> Self {
+        Self {
+            inner,
+            contracts: Mutex::new(ContractCache::new(capacity)),
+        }
+    }
+
+    /// The wrapped storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn cache_contract(&self, contract: &Contract) {
+        let mut cache = self.contracts.lock().unwrap();
+        cache.generation += 1;
+        // An accepted contract is stored under its final id and the offer is removed.
+        cache.remove(&contract.get_temporary_id());
+        if is_active(contract) {
+            cache.insert(contract.clone());
+        } else {
+            cache.remove(&contract.get_id());
+        }
+    }
+}
+
+/// Least recently used contracts, by id. The front of `order` is evicted first.
+struct ContractCache {
+    capacity: usize,
+    entries: HashMap<ContractId, Contract>,
+    order: VecDeque<ContractId>,
+    /// Counts the writes, so a read from the inner storage can tell whether a write landed
+    /// while it was not holding the lock.
+    generation: u64,
+}
+
+impl ContractCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            generation: 0,
+        }
+    }
+
+    /// Cache `contract`, read from the inner storage at `generation`, unless a write landed
+    /// since. The write already cached the newer state, or removed the contract.
+    fn insert_read(&mut self, contract: Contract, generation: u64) {
+        if self.generation == generation {
+            self.insert(contract);
+        }
+    }
+
+    fn get(&mut self, contract_id: &ContractId) -> Option<Contract> {
+        let contract = self.entries.get(contract_id)?.clone();
+        self.touch(contract_id);
+        Some(contract)
+    }
+
+    fn insert(&mut self, contract: Contract) {
+        if self.capacity == 0 {
+            return;
+        }
+        let contract_id = contract.get_id();
+        if self.entries.insert(contract_id, contract).is_some() {
+            self.touch(&contract_id);
+            return;
+        }
+        self.order.push_back(contract_id);
+        while self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+
+    fn remove(&mut self, contract_id: &ContractId) {
+        if self.entries.remove(contract_id).is_some() {
+            self.order.retain(|id| id != contract_id);
+        }
+    }
+
+    fn touch(&mut self, contract_id: &ContractId) {
+        self.order.retain(|id| id != contract_id);
+        self.order.push_back(*contract_id);
+    }
+}
+
+/// Contracts the periodic check and message handling still read.
+fn is_active(contract: &Contract) -> bool {
+    matches!(
+        contract,
+        Contract::Offered(_)
+            | Contract::Accepted(_)
+            | Contract::Signed(_)
+            | Contract::Confirmed(_)
+            | Contract::PreClosed(_)
+    )
+}
+
+impl<S: DdkStorage> Storage for CachingStorage<S> {
+    fn get_contract(&self, contract_id: &ContractId) -> Result<Option<Contract>, Error> {
+        let generation = {
+            let mut cache = self.contracts.lock().unwrap();
+            if let Some(contract) = cache.get(contract_id) {
+                return Ok(Some(contract));
+            }
+            cache.generation
+        };
+        let contract = self.inner.get_contract(contract_id)?;
+        if let Some(contract) = contract.as_ref().filter(|c| is_active(c)) {
+            self.contracts
+                .lock()
+                .unwrap()
+                .insert_read(contract.clone(), generation);
+        }
+        Ok(contract)
+    }
+
+    fn get_contracts(&self) -> Result<Vec<Contract>, Error> {
+        self.inner.get_contracts()
+    }
+
+    fn create_contract(&self, contract: &OfferedContract) -> Result<(), Error> {
+        self.inner.create_contract(contract)?;
+        self.cache_contract(&Contract::Offered(contract.clone()));
+        Ok(())
+    }
+
+    fn delete_contract(&self, contract_id: &ContractId) -> Result<(), Error> {
+        self.inner.delete_contract(contract_id)?;
+        let mut cache = self.contracts.lock().unwrap();
+        cache.generation += 1;
+        cache.remove(contract_id);
+        Ok(())
+    }
+
+    fn update_contract(&self, contract: &Contract) -> Result<(), Error> {
+        self.inner.update_contract(contract)?;
+        self.cache_contract(contract);
+        Ok(())
+    }
+
+    fn get_contract_offers(&self) -> Result<Vec<OfferedContract>, Error> {
+        self.inner.get_contract_offers()
+    }
+
+    fn get_signed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
+        self.inner.get_signed_contracts()
+    }
+
+    fn get_confirmed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
+        self.inner.get_confirmed_contracts()
+    }
+
+    fn get_preclosed_contracts(&self) -> Result<Vec<PreClosedContract>, Error> {
+        self.inner.get_preclosed_contracts()
+    }
+
+    fn upsert_channel(&self, channel: Channel, contract: Option<Contract>) -> Result<(), Error> {
+        self.inner.upsert_channel(channel, contract.clone())?;
+        if let Some(contract) = contract {
+            self.cache_contract(&contract);
+        }
+        Ok(())
+    }
+
+    fn delete_channel(&self, channel_id: &ChannelId) -> Result<(), Error> {
+        self.inner.delete_channel(channel_id)
+    }
+
+    fn get_channel(&self, channel_id: &ChannelId) -> Result<Option<Channel>, Error> {
+        self.inner.get_channel(channel_id)
+    }
+
+    fn get_signed_channels(
+        &self,
+        channel_state: Option<SignedChannelStateType>,
+    ) -> Result<Vec<SignedChannel>, Error> {
+        self.inner.get_signed_channels(channel_state)
+    }
+
+    fn get_offered_channels(&self) -> Result<Vec<OfferedChannel>, Error> {
+        self.inner.get_offered_channels()
+    }
+
+    fn persist_chain_monitor(&self, monitor: &ChainMonitor) -> Result<(), Error> {
+        self.inner.persist_chain_monitor(monitor)
+    }
+
+    fn get_chain_monitor(&self) -> Result<Option<ChainMonitor>, Error> {
+        self.inner.get_chain_monitor()
+    }
+}
+
+impl<S: DdkStorage> DeriveSigner for CachingStorage<S> {
+    type Error = <S as DeriveSigner>::Error;
+
+    fn get_key_information(&self, key_id: [u8; 32]) -> Result<SignerInformation, Self::Error> {
+        self.inner.get_key_information(key_id)
+    }
+
+    fn store_derived_key_id(
+        &self,
+        key_id: [u8; 32],
+        signer_info: SignerInformation,
+    ) -> Result<(), Self::Error> {
+        self.inner.store_derived_key_id(key_id, signer_info)
+    }
+
+    fn get_secret_key(&self, public_key: &PublicKey) -> Result<SecretKey, Self::Error> {
+        self.inner.get_secret_key(public_key)
+    }
+
+    fn import_address_to_storage(&self, address: &bitcoin::Address) -> Result<(), Self::Error> {
+        self.inner.import_address_to_storage(address)
+    }
+}
+
+impl<S: DdkStorage> WalletPersister for CachingStorage<S> {
+    type Error = <S as WalletPersister>::Error;
+
+    fn persist(persister: &mut Self, changeset: &ChangeSet) -> Result<(), Self::Error> {
+        S::persist(&mut persister.inner, changeset)
+    }
+
+    fn initialize(persister: &mut Self) -> Result<ChangeSet, Self::Error> {
+        S::initialize(&mut persister.inner)
+    }
+}
+
+impl<S: DdkStorage> DdkStorage for CachingStorage<S> {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn list_peers(&self) -> anyhow::Result<Vec<PeerInformation>> {
+        self.inner.list_peers()
+    }
+
+    fn save_peer(&self, peer: PeerInformation) -> anyhow::Result<()> {
+        self.inner.save_peer(peer)
+    }
+
+    fn save_offer_expiry(&self, contract_id: &ContractId, expiry: u64) -> anyhow::Result<()> {
+        self.inner.save_offer_expiry(contract_id, expiry)
+    }
+
+    fn get_offer_expiry(&self, contract_id: &ContractId) -> anyhow::Result<Option<u64>> {
+        self.inner.get_offer_expiry(contract_id)
+    }
+
+    fn delete_offer_expiry(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        self.inner.delete_offer_expiry(contract_id)
+    }
+
+    fn flush(&self) -> anyhow::Result<()> {
+        self.inner.flush()
+    }
+
+    fn save_announcement(&self, announcement: &OracleAnnouncement) -> anyhow::Result<()> {
+        self.inner.save_announcement(announcement)
+    }
+
+    fn get_announcement(&self, event_id: &str) -> anyhow::Result<Option<OracleAnnouncement>> {
+        self.inner.get_announcement(event_id)
+    }
+
+    fn save_partial_offer(
+        &self,
+        contract_id: &ContractId,
+        partial: &PartialOffer,
+    ) -> anyhow::Result<()> {
+        self.inner.save_partial_offer(contract_id, partial)
+    }
+
+    fn get_partial_offer(&self, contract_id: &ContractId) -> anyhow::Result<Option<PartialOffer>> {
+        self.inner.get_partial_offer(contract_id)
+    }
+
+    fn get_partial_offers(&self) -> anyhow::Result<Vec<(ContractId, PartialOffer)>> {
+        self.inner.get_partial_offers()
+    }
+
+    fn delete_partial_offer(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        self.inner.delete_partial_offer(contract_id)
+    }
+
+    fn get_contracts_by_oracle_event(&self, event_id: &str) -> anyhow::Result<Vec<Contract>> {
+        self.inner.get_contracts_by_oracle_event(event_id)
+    }
+
+    fn list_channels(&self) -> anyhow::Result<Vec<Channel>> {
+        self.inner.list_channels()
+    }
+
+    fn save_label(&self, key: &LabelKey, label: &str) -> anyhow::Result<()> {
+        self.inner.save_label(key, label)
+    }
+
+    fn get_label(&self, key: &LabelKey) -> anyhow::Result<Option<String>> {
+        self.inner.get_label(key)
+    }
+
+    fn get_contract_fees(&self, contract_id: &ContractId) -> anyhow::Result<Option<ContractFees>> {
+        self.inner.get_contract_fees(contract_id)
+    }
+
+    fn get_contract_history(
+        &self,
+        contract_id: &ContractId,
+    ) -> anyhow::Result<Vec<ContractTransition>> {
+        self.inner.get_contract_history(contract_id)
+    }
+
+    fn save_funding_target(
+        &self,
+        contract_id: &ContractId,
+        target_blocks: u16,
+    ) -> anyhow::Result<()> {
+        self.inner.save_funding_target(contract_id, target_blocks)
+    }
+
+    fn get_funding_target(&self, contract_id: &ContractId) -> anyhow::Result<Option<u16>> {
+        self.inner.get_funding_target(contract_id)
+    }
+
+    fn save_outbound_message(
+        &self,
+        contract_id: &ContractId,
+        counter_party: &PublicKey,
+        message: &Message,
+    ) -> anyhow::Result<()> {
+        self.inner
+            .save_outbound_message(contract_id, counter_party, message)
+    }
+
+    fn get_outbound_message(
+        &self,
+        contract_id: &ContractId,
+    ) -> anyhow::Result<Option<(PublicKey, Message)>> {
+        self.inner.get_outbound_message(contract_id)
+    }
+
+    fn save_funding_conflict(&self, conflict: &FundingConflict) -> anyhow::Result<()> {
+        self.inner.save_funding_conflict(conflict)
+    }
+
+    fn get_funding_conflict(
+        &self,
+        contract_id: &ContractId,
+    ) -> anyhow::Result<Option<FundingConflict>> {
+        self.inner.get_funding_conflict(contract_id)
+    }
+
+    fn save_settlement_delay(
+        &self,
+        contract_id: &ContractId,
+        delay: &SettlementDelay,
+    ) -> anyhow::Result<()> {
+        self.inner.save_settlement_delay(contract_id, delay)
+    }
+
+    fn get_settlement_delay(
+        &self,
+        contract_id: &ContractId,
+    ) -> anyhow::Result<Option<SettlementDelay>> {
+        self.inner.get_settlement_delay(contract_id)
+    }
+
+    fn save_dead_letter(&self, dead_letter: &DeadLetter) -> anyhow::Result<()> {
+        self.inner.save_dead_letter(dead_letter)
+    }
+
+    fn get_dead_letter(&self, contract_id: &ContractId) -> anyhow::Result<Option<DeadLetter>> {
+        self.inner.get_dead_letter(contract_id)
+    }
+
+    fn delete_dead_letter(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        self.inner.delete_dead_letter(contract_id)
+    }
+
+    fn import_contract(&self, contract: &Contract, overwrite: bool) -> anyhow::Result<()> {
+        self.inner.import_contract(contract, overwrite)?;
+        self.cache_contract(contract);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::SledStorageProvider;
+    use crate::test_util::{offered_contract, signed_contract};
+    use crate::util::contract_state;
+
+    #[test]
+    fn cache_follows_updates_and_eviction() {
+        let path = "tests/data/dlc_storage/sleddb/cache_follows_updates_and_eviction";
+        {
+            let storage = CachingStorage::new(SledStorageProvider::new(path).unwrap(), 1);
+            let signed = signed_contract();
+            let signed_id = Contract::Signed(signed.clone()).get_id();
+            storage
+                .create_contract(&signed.accepted_contract.offered_contract)
+                .unwrap();
+            storage
+                .update_contract(&Contract::Signed(signed.clone()))
+                .unwrap();
+            storage
+                .update_contract(&Contract::Confirmed(signed.clone()))
+                .unwrap();
+
+            let cached = storage.get_contract(&signed_id).unwrap().unwrap();
+            assert_eq!(contract_state(&cached), "confirmed");
+
+            // A second contract evicts the first. Reads fall back to the inner storage.
+            let mut other = offered_contract();
+            other.id = [7u8; 32];
+            storage.create_contract(&other).unwrap();
+            assert!(!storage
+                .contracts
+                .lock()
+                .unwrap()
+                .entries
+                .contains_key(&signed_id));
+            let stored = storage.get_contract(&signed_id).unwrap().unwrap();
+            assert_eq!(contract_state(&stored), "confirmed");
+
+            storage
+                .update_contract(&Contract::Refunded(signed))
+                .unwrap();
+            assert!(storage.contracts.lock().unwrap().entries.is_empty());
+            let refunded = storage.get_contract(&signed_id).unwrap().unwrap();
+            assert_eq!(contract_state(&refunded), "refunded");
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn reads_do_not_cache_over_a_newer_write() {
+        let mut cache = ContractCache::new(4);
+        let signed = signed_contract();
+        let contract_id = Contract::Signed(signed.clone()).get_id();
+        // A read misses, then a write caches the newer state before the read inserts.
+        let read_at = cache.generation;
+        cache.generation += 1;
+        cache.insert(Contract::Confirmed(signed.clone()));
+        cache.insert_read(Contract::Signed(signed.clone()), read_at);
+        assert_eq!(
+            contract_state(&cache.get(&contract_id).unwrap()),
+            "confirmed"
+        );
+
+        // A write that removed the contract is not undone by the read either.
+        cache.remove(&contract_id);
+        cache.insert_read(Contract::Signed(signed), read_at);
+        assert!(cache.get(&contract_id).is_none());
+    }
+
+    #[test]
+    fn imports_replace_the_cached_contract() {
+        let path = "tests/data/dlc_storage/sleddb/imports_replace_the_cached_contract";
+        {
+            let storage = CachingStorage::new(SledStorageProvider::new(path).unwrap(), 4);
+            let signed = signed_contract();
+            let signed_id = Contract::Signed(signed.clone()).get_id();
+            storage
+                .create_contract(&signed.accepted_contract.offered_contract)
+                .unwrap();
+            storage
+                .update_contract(&Contract::Refunded(signed.clone()))
+                .unwrap();
+            assert_eq!(contract_state(&storage.get_contract(&signed_id).unwrap().unwrap()), "refunded");
+
+            // A restored backup overwrites the refunded contract and is cached as active.
+            storage
+                .import_contract(&Contract::Confirmed(signed.clone()), true)
+                .unwrap();
+            assert!(storage
+                .contracts
+                .lock()
+                .unwrap()
+                .entries
+                .contains_key(&signed_id));
+            assert_eq!(contract_state(&storage.get_contract(&signed_id).unwrap().unwrap()), "confirmed");
+
+            // A refused import leaves the cached contract as it was.
+            assert!(storage
+                .import_contract(&Contract::Signed(signed), true)
+                .is_err());
+            assert_eq!(contract_state(&storage.get_contract(&signed_id).unwrap().unwrap()), "confirmed");
+            assert_eq!(
+                contract_state(&storage.inner().get_contract(&signed_id).unwrap().unwrap()),
+                "confirmed"
+            );
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn lists_are_read_from_the_inner_storage() {
+        let path = "tests/data/dlc_storage/sleddb/lists_are_read_from_the_inner_storage";
+        {
+            let storage = CachingStorage::new(SledStorageProvider::new(path).unwrap(), 1);
+            let signed = signed_contract();
+            storage
+                .create_contract(&signed.accepted_contract.offered_contract)
+                .unwrap();
+            let mut other = offered_contract();
+            other.id = [7u8; 32];
+            storage.create_contract(&other).unwrap();
+            storage
+                .update_contract(&Contract::Signed(signed.clone()))
+                .unwrap();
+
+            // Only one contract is held in memory, the lists still have both.
+            assert_eq!(storage.contracts.lock().unwrap().entries.len(), 1);
+            assert_eq!(storage.get_contract_offers().unwrap().len(), 1);
+            assert_eq!(storage.get_signed_contracts().unwrap().len(), 1);
+            let ids =
+                |contracts: Vec<Contract>| contracts.iter().map(|c| c.get_id()).collect::<Vec<_>>();
+            assert_eq!(
+                ids(storage.get_contracts().unwrap()),
+                ids(storage.inner().get_contracts().unwrap())
+            );
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
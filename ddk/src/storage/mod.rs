@@ -1,3 +1,178 @@
+mod cache;
 mod sled;
 
+pub use cache::{CachingStorage, DEFAULT_CONTRACT_CACHE_SIZE};
 pub use sled::SledStorageProvider;
+
+use crate::DdkStorage;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::{OutPoint, Txid};
+use dlc_manager::contract::Contract;
+use dlc_manager::ContractId;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// First byte of a bincode record written before records carried a version. Read as version 0.
+const LEGACY_BINCODE_RECORD_TAG: u8 = 0x00;
+/// First byte of a bincode record. The second is the [Record::VERSION] it was written at. JSON
+/// never starts with either tag, so records written as JSON stay readable whatever the format
+/// is set to.
+const BINCODE_RECORD_TAG: u8 = 0x01;
+
+/// How DDK encodes the records it keeps besides contracts and channels: fees, history,
+/// peers, partial offers, funding conflicts, settlement delays and dead letters. Contracts and
+/// channels are always stored in the rust-dlc encoding.
+///
+/// Records are read in either format, so the format of a database can be changed at any time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageFormat {
+    /// The format of databases written before bincode records. Still read, and rewritten as
+    /// bincode by [SledStorageProvider::migrate_records].
+    Json,
+    /// Compact and fast. Bincode records are positional, so each carries the [Record::VERSION]
+    /// of its type and older versions are read with [Record::upgrade].
+    #[default]
+    Bincode,
+}
+
+/// A record DDK stores in a [StorageFormat]. A change to the fields of a record type bumps
+/// `VERSION` and reads the records of earlier versions in `upgrade`.
+pub trait Record: Serialize + DeserializeOwned {
+    /// Version of the record type, written with each bincode record.
+    const VERSION: u8 = 0;
+
+    /// Read the bincode `bytes` of a record written at an earlier `version`.
+    fn upgrade(version: u8, _bytes: &[u8]) -> anyhow::Result<Self> {
+        Err(anyhow::anyhow!("No upgrade from version {} of the record to version {}.", version, Self::VERSION))
+    }
+}
+
+impl Record for crate::util::ContractFees {}
+impl Record for crate::order::PartialOffer {}
+impl Record for crate::policy::SettlementDelay {}
+impl Record for crate::transport::PeerInformation {}
+impl Record for ContractTransition {}
+impl Record for FundingConflict {}
+impl Record for DeadLetter {}
+
+impl<T: Record> Record for Vec<T> {
+    const VERSION: u8 = T::VERSION;
+}
+
+impl StorageFormat {
+    /// Format a stored record was written in.
+    pub fn of(bytes: &[u8]) -> StorageFormat {
+        match bytes.first() {
+            Some(&BINCODE_RECORD_TAG) | Some(&LEGACY_BINCODE_RECORD_TAG) => StorageFormat::Bincode,
+            _ => StorageFormat::Json,
+        }
+    }
+
+    pub fn encode<T: Record>(&self, record: &T) -> anyhow::Result<Vec<u8>> {
+        match self {
+            StorageFormat::Json => Ok(serde_json::to_vec(record)?),
+            StorageFormat::Bincode => {
+                let mut bytes = vec![BINCODE_RECORD_TAG, T::VERSION];
+                bincode::serialize_into(&mut bytes, record)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Read a record in the format and at the version it was written in.
+    pub fn decode<T: Record>(bytes: &[u8]) -> anyhow::Result<T> {
+        let (version, payload) = match bytes {
+            [BINCODE_RECORD_TAG, version, payload @ ..] => (*version, payload),
+            [LEGACY_BINCODE_RECORD_TAG, payload @ ..] => (0, payload),
+            [BINCODE_RECORD_TAG] => return Err(anyhow::anyhow!("Bincode record has no version.")),
+            _ => return Ok(serde_json::from_slice(bytes)?),
+        };
+        match version.cmp(&T::VERSION) {
+            std::cmp::Ordering::Equal => Ok(bincode::deserialize(payload)?),
+            std::cmp::Ordering::Less => T::upgrade(version, payload),
+            std::cmp::Ordering::Greater => Err(anyhow::anyhow!(
+                "Record is at version {}, newer than version {} this DDK reads.",
+                version,
+                T::VERSION
+            )),
+        }
+    }
+
+    /// Whether `bytes` are a record of `T` written in this format at the current version, so
+    /// [SledStorageProvider::migrate_records] leaves it as is.
+    pub fn is_current<T: Record>(&self, bytes: &[u8]) -> bool {
+        match self {
+            StorageFormat::Json => StorageFormat::of(bytes) == StorageFormat::Json,
+            StorageFormat::Bincode => matches!(bytes, [BINCODE_RECORD_TAG, version, ..] if *version == T::VERSION),
+        }
+    }
+}
+
+/// What a label is attached to. Labels are local notes and are never sent to a counterparty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelKey {
+    Transaction(Txid),
+    Contract(ContractId),
+}
+
+impl LabelKey {
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let (prefix, id) = match self {
+            LabelKey::Transaction(txid) => (b't', txid.to_byte_array()),
+            LabelKey::Contract(contract_id) => (b'c', *contract_id),
+        };
+        let mut bytes = vec![prefix];
+        bytes.extend_from_slice(&id);
+        bytes
+    }
+}
+
+/// A change of state of a contract, recorded in its history with the state update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractTransition {
+    pub contract_id: ContractId,
+    /// State before the transition. `None` when the contract was first stored.
+    pub from_state: Option<String>,
+    pub to_state: String,
+    /// Unix time in seconds.
+    pub timestamp: u64,
+    /// The transaction that moved the contract to `to_state`, if any.
+    pub txid: Option<Txid>,
+}
+
+/// A funding input of a signed contract that another transaction spent, so the funding
+/// transaction can never confirm. The contract is moved to failed sign when it is found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingConflict {
+    pub contract_id: ContractId,
+    /// The funding input that was spent.
+    pub outpoint: OutPoint,
+    /// The transaction that spent it, in the mempool or chain.
+    pub conflicting_txid: Txid,
+}
+
+/// The last message of a contract that DDK gave up resending. The contract is delivery failed
+/// until the message is retried with [crate::DlcDevKit::retry_dead_letter].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// The final id of the contract the message was sent for.
+    pub contract_id: ContractId,
+    pub counter_party: PublicKey,
+    /// Times the message was sent.
+    pub attempts: u32,
+    /// Unix time in seconds.
+    pub failed_at: u64,
+}
+
+/// The label of a contract. A label set on an offer stays with the contract once it is
+/// accepted and stored under its final id.
+pub fn contract_label<S: DdkStorage>(
+    storage: &S,
+    contract: &Contract,
+) -> anyhow::Result<Option<String>> {
+    match storage.get_label(&LabelKey::Contract(contract.get_id()))? {
+        Some(label) => Ok(Some(label)),
+        None => storage.get_label(&LabelKey::Contract(contract.get_temporary_id())),
+    }
+}
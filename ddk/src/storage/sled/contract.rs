@@ -12,10 +12,13 @@ use dlc_manager::contract::ser::Serializable;
 use dlc_manager::contract::signed_contract::SignedContract;
 use dlc_manager::contract::{Contract, PreClosedContract};
 use dlc_manager::{error::Error, ContractId, Storage};
-use sled::transaction::{ConflictableTransactionResult, UnabortableTransactionError};
+use sled::transaction::{
+    ConflictableTransactionError, ConflictableTransactionResult, TransactionError, UnabortableTransactionError,
+};
 use sled::Transactional;
 use std::convert::TryInto;
-use crate::util::{serialize_contract, deserialize_contract};
+use crate::storage::{ContractTransition, StorageFormat};
+use crate::util::{contract_state, contract_transactions, deserialize_contract, import_conflict, serialize_contract};
 
 macro_rules! convertible_enum {
     (enum $name:ident {
@@ -137,11 +140,21 @@ impl Storage for SledStorageProvider {
     }
 
     fn create_contract(&self, contract: &OfferedContract) -> Result<(), Error> {
-        let serialized = serialize_contract(&Contract::Offered(contract.clone()))?;
-        self.contract_tree()?
-            .insert(contract.id, serialized)
+        let contract = Contract::Offered(contract.clone());
+        let serialized = serialize_contract(&contract)?;
+        let entry_id = self.db.generate_id().map_err(to_storage_error)?;
+        let contract_tree = self.contract_tree()?;
+        let history_tree = self.contract_history_tree()?;
+        (&contract_tree, &history_tree)
+            .transaction::<_, ()>(
+                |(contract_db, history_db)| -> ConflictableTransactionResult<(), UnabortableTransactionError> {
+                    record_transition(contract_db, history_db, &contract, entry_id, self.format)?;
+                    contract_db.insert(&contract.get_id(), serialized.clone())?;
+                    Ok(())
+                },
+            )
             .map_err(to_storage_error)?;
-        Ok(())
+        self.index_oracle_events(&contract)
     }
 
     fn delete_contract(&self, contract_id: &ContractId) -> Result<(), Error> {
@@ -153,20 +166,20 @@ impl Storage for SledStorageProvider {
 
     fn update_contract(&self, contract: &Contract) -> Result<(), Error> {
         let serialized = serialize_contract(contract)?;
-        self.contract_tree()?
-            .transaction::<_, _, UnabortableTransactionError>(|db| {
-                match contract {
-                    a @ Contract::Accepted(_) | a @ Contract::Signed(_) => {
-                        db.remove(&a.get_temporary_id())?;
-                    }
-                    _ => {}
-                };
-
-                db.insert(&contract.get_id(), serialized.clone())?;
-                Ok(())
-            })
+        let entry_id = self.db.generate_id().map_err(to_storage_error)?;
+        let contract_tree = self.contract_tree()?;
+        let history_tree = self.contract_history_tree()?;
+        (&contract_tree, &history_tree)
+            .transaction::<_, ()>(
+                |(contract_db, history_db)| -> ConflictableTransactionResult<(), UnabortableTransactionError> {
+                    record_transition(contract_db, history_db, contract, entry_id, self.format)?;
+                    insert_contract(contract_db, serialized.clone(), contract)?;
+                    Ok(())
+                },
+            )
             .map_err(to_storage_error)?;
-        Ok(())
+        self.record_contract_fees(contract)?;
+        self.index_oracle_events(contract)
     }
 
     fn get_contract_offers(&self) -> Result<Vec<OfferedContract>, Error> {
@@ -207,11 +220,13 @@ impl Storage for SledStorageProvider {
             Some(c) => Some(serialize_contract(c)?),
             None => None,
         };
+        let entry_id = self.db.generate_id().map_err(to_storage_error)?;
         let channel_tree = self.channel_tree()?;
         let contract_tree = self.contract_tree()?;
-        (&channel_tree, &contract_tree)
+        let history_tree = self.contract_history_tree()?;
+        (&channel_tree, &contract_tree, &history_tree)
             .transaction::<_, ()>(
-                |(channel_db, contract_db)| -> ConflictableTransactionResult<(), UnabortableTransactionError> {
+                |(channel_db, contract_db, history_db)| -> ConflictableTransactionResult<(), UnabortableTransactionError> {
                     match &channel {
                         a @ Channel::Accepted(_) | a @ Channel::Signed(_) => {
                             channel_db.remove(&a.get_temporary_id())?;
@@ -222,6 +237,7 @@ impl Storage for SledStorageProvider {
                     channel_db.insert(&channel.get_id(), serialized.clone())?;
 
                     if let Some(c) = contract.as_ref() {
+                        record_transition(contract_db, history_db, c, entry_id, self.format)?;
                         insert_contract(
                             contract_db,
                             serialized_contract
@@ -234,7 +250,10 @@ impl Storage for SledStorageProvider {
                 },
             )
         .map_err(to_storage_error)?;
-        Ok(())
+        match contract.as_ref() {
+            Some(c) => self.index_oracle_events(c),
+            None => Ok(()),
+        }
     }
 
     fn delete_channel(&self, channel_id: &dlc_manager::ChannelId) -> Result<(), Error> {
@@ -304,6 +323,90 @@ impl Storage for SledStorageProvider {
     }
 }
 
+impl SledStorageProvider {
+    pub(super) fn get_channels(&self) -> Result<Vec<Channel>, Error> {
+        self.channel_tree()?
+            .iter()
+            .values()
+            .map(|x| deserialize_channel(&x.map_err(to_storage_error)?))
+            .collect()
+    }
+
+    /// Check for a stored contract with the same final or temporary id and write `contract`
+    /// in one transaction, so no other write lands between the two.
+    pub(super) fn import_contract_record(&self, contract: &Contract, overwrite: bool) -> anyhow::Result<()> {
+        let serialized = serialize_contract(contract)?;
+        let entry_id = self.db.generate_id().map_err(to_storage_error)?;
+        let contract_tree = self.contract_tree()?;
+        let history_tree = self.contract_history_tree()?;
+        (&contract_tree, &history_tree)
+            .transaction(
+                |(contract_db, history_db)| -> ConflictableTransactionResult<(), anyhow::Error> {
+                    for id in [contract.get_id(), contract.get_temporary_id()] {
+                        let Some(stored) = contract_db.get(id)? else {
+                            continue;
+                        };
+                        let existing = deserialize_contract(&stored)
+                            .map_err(|e| ConflictableTransactionError::Abort(e.into()))?;
+                        if let Some(conflict) = import_conflict(&existing, overwrite) {
+                            return Err(ConflictableTransactionError::Abort(conflict.into()));
+                        }
+                    }
+                    record_transition(contract_db, history_db, contract, entry_id, self.format)?;
+                    insert_contract(contract_db, serialized.clone(), contract)?;
+                    Ok(())
+                },
+            )
+            .map_err(|e| match e {
+                TransactionError::Abort(e) => e,
+                TransactionError::Storage(e) => to_storage_error(e).into(),
+            })?;
+        self.record_contract_fees(contract)?;
+        self.index_oracle_events(contract)?;
+        Ok(())
+    }
+}
+
+/// Append a transition to the history of `contract` if it changes the stored state. Must run
+/// before the contract is written, in the same transaction.
+fn record_transition(
+    contract_db: &sled::transaction::TransactionalTree,
+    history_db: &sled::transaction::TransactionalTree,
+    contract: &Contract,
+    entry_id: u64,
+    format: StorageFormat,
+) -> Result<(), UnabortableTransactionError> {
+    let stored = match contract_db.get(contract.get_id())? {
+        Some(stored) => Some(stored),
+        None => contract_db.get(contract.get_temporary_id())?,
+    };
+    let from_state = stored
+        .and_then(|stored| deserialize_contract(&stored).ok())
+        .map(|stored| contract_state(&stored).to_string());
+    let to_state = contract_state(contract).to_string();
+    if from_state.as_deref() == Some(to_state.as_str()) {
+        return Ok(());
+    }
+
+    let transition = ContractTransition {
+        contract_id: contract.get_id(),
+        from_state,
+        to_state,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        txid: contract_transactions(contract).last().map(|(_, txid)| *txid),
+    };
+    let mut key = contract.get_id().to_vec();
+    key.extend_from_slice(&entry_id.to_be_bytes());
+    history_db.insert(
+        key,
+        format.encode(&transition).expect("contract transitions serialize"),
+    )?;
+    Ok(())
+}
+
 fn insert_contract(
     db: &sled::transaction::TransactionalTree,
     serialized: Vec<u8>,
@@ -411,6 +514,34 @@ mod tests {
         };
     }
 
+    #[test]
+    fn signed_contract_survives_restart() {
+        let path = "tests/data/dlc_storage/sleddb/signed_contract_survives_restart";
+        let serialized = include_bytes!("../../../tests/data/dlc_storage/sled/Signed");
+        let signed: SignedContract = deserialize_object(serialized);
+        {
+            // Stop right after the manager stored the sign data.
+            let storage = SledStorageProvider::new(path).expect("Error opening sled DB");
+            storage
+                .update_contract(&Contract::Signed(signed.clone()))
+                .expect("Error updating contract.");
+            crate::DdkStorage::flush(&storage).expect("Error flushing storage.");
+        }
+        {
+            let storage = SledStorageProvider::new(path).expect("Error opening sled DB");
+            let retrieved = storage
+                .get_contract(&signed.accepted_contract.get_contract_id())
+                .expect("Error retrieving contract.");
+
+            if let Some(Contract::Signed(retrieved)) = retrieved {
+                assert_eq!(serialized[..], retrieved.serialize().unwrap()[..]);
+            } else {
+                unreachable!();
+            }
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
     fn deserialize_object<T>(serialized: &[u8]) -> T
     where
         T: Serializable,
@@ -613,6 +744,20 @@ mod tests {
         }
     );
 
+    sled_test!(
+        get_channels_all_returned,
+        |mut storage: SledStorageProvider| {
+            insert_offered_and_signed_channels(&mut storage);
+
+            let channels = storage.get_channels().expect("Error retrieving channels");
+            let offered = storage.get_offered_channels().unwrap().len();
+            let signed = storage.get_signed_channels(None).unwrap().len();
+
+            assert_eq!(offered + signed, channels.len());
+            assert!(channels.iter().any(|c| matches!(c, Channel::Offered(_))));
+        }
+    );
+
     sled_test!(
         persist_chain_monitor_test,
         |storage: SledStorageProvider| {
@@ -7,9 +7,21 @@ mod wallet;
 
 use dlc_manager::contract::ser::Serializable;
 use dlc_manager::error::Error;
+use dlc_manager::channel::Channel;
+use dlc_manager::contract::Contract;
+use dlc_manager::{ContractId, Storage};
+use dlc_messages::oracle_msgs::OracleAnnouncement;
+use dlc_messages::{Message, WireMessage};
+use bitcoin::secp256k1::{PublicKey, Secp256k1};
 use sled::{Db, Tree};
 use lightning::io::{Cursor, Read};
+use lightning::util::ser::{Readable, Writeable};
+use bitcoin::hashes::{sha256::Hash as Sha256Hash, Hash};
 
+use crate::order::PartialOffer;
+use crate::policy::SettlementDelay;
+use crate::storage::{ContractTransition, DeadLetter, FundingConflict, LabelKey, Record, StorageFormat};
+use crate::util::{contract_fees, decode_dlc_message, dlc_message_bytes, oracle_event_ids, ContractFees};
 use crate::transport::PeerInformation;
 use crate::DdkStorage;
 
@@ -20,11 +32,32 @@ pub const CHAIN_MONITOR_KEY: u8 = 4;
 const PEER_KEY: u8 = 5;
 const SIGNER_TREE: u8 = 6;
 const WALLET_TREE: u8 = 7;
+const OFFER_EXPIRY_TREE: u8 = 8;
+const ANNOUNCEMENT_TREE: u8 = 9;
+const PARTIAL_OFFER_TREE: u8 = 10;
+const ORACLE_EVENT_INDEX_TREE: u8 = 11;
+const LABEL_TREE: u8 = 12;
+const CONTRACT_FEES_TREE: u8 = 13;
+const CONTRACT_HISTORY_TREE: u8 = 14;
+const FUNDING_TARGET_TREE: u8 = 15;
+const OUTBOUND_MESSAGE_TREE: u8 = 16;
+const FUNDING_CONFLICT_TREE: u8 = 17;
+const SETTLEMENT_DELAY_TREE: u8 = 18;
+const DEAD_LETTER_TREE: u8 = 19;
+
+/// Key of the version of the oracle event index. Stores written before the index existed,
+/// or before this version, have their contracts indexed again on the first lookup.
+const ORACLE_EVENT_INDEX_VERSION_KEY: &str = "oracle_event_index_version";
+const ORACLE_EVENT_INDEX_VERSION: u8 = 1;
 
 /// Implementation of Storage interface using the sled DB backend.
 #[derive(Debug, Clone)]
 pub struct SledStorageProvider {
     db: Db,
+    /// Encrypts the wallet changesets when the wallet database was opened with a passphrase.
+    wallet_key: Option<wallet::WalletKey>,
+    /// Format new records are written in.
+    format: StorageFormat,
 }
 
 impl SledStorageProvider {
@@ -32,9 +65,56 @@ impl SledStorageProvider {
     pub fn new(path: &str) -> Result<Self, sled::Error> {
         Ok(SledStorageProvider {
             db: sled::open(path)?,
+            wallet_key: None,
+            format: StorageFormat::default(),
         })
     }
 
+    /// Write new records in `format`. Records already stored are read in the format they were
+    /// written in, and [SledStorageProvider::migrate_records] rewrites them.
+    pub fn with_format(mut self, format: StorageFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn format(&self) -> StorageFormat {
+        self.format
+    }
+
+    /// Rewrite the records stored in another format, or at an earlier version of their type, in
+    /// the current format and version. Returns how many were rewritten.
+    pub fn migrate_records(&self) -> anyhow::Result<usize> {
+        let mut migrated = 0;
+        migrated += self.migrate_tree::<ContractFees>(&self.contract_fees_tree()?)?;
+        migrated += self.migrate_tree::<PartialOffer>(&self.partial_offer_tree()?)?;
+        migrated += self.migrate_tree::<ContractTransition>(&self.contract_history_tree()?)?;
+        migrated += self.migrate_tree::<FundingConflict>(&self.funding_conflict_tree()?)?;
+        migrated += self.migrate_tree::<SettlementDelay>(&self.settlement_delay_tree()?)?;
+        migrated += self.migrate_tree::<DeadLetter>(&self.dead_letter_tree()?)?;
+        if let Some(bytes) = self.db.get("peers")? {
+            if !self.format.is_current::<Vec<PeerInformation>>(&bytes) {
+                let peers: Vec<PeerInformation> = StorageFormat::decode(&bytes)?;
+                self.db.insert("peers", self.format.encode(&peers)?)?;
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
+    fn migrate_tree<T: Record>(&self, tree: &Tree) -> anyhow::Result<usize> {
+        let mut migrated = 0;
+        for entry in tree.iter() {
+            let (key, bytes) = entry?;
+            if self.format.is_current::<T>(&bytes) {
+                continue;
+            }
+            let record: T = StorageFormat::decode(&bytes)?;
+            tree.insert(key, self.format.encode(&record)?)?;
+            migrated += 1;
+        }
+        Ok(migrated)
+    }
+
     fn get_data_with_prefix<T: Serializable>(
         &self,
         tree: &Tree,
@@ -81,12 +161,117 @@ impl SledStorageProvider {
     pub fn wallet_tree(&self) -> Result<Tree, sled::Error> {
         self.db.open_tree(&[WALLET_TREE])
     }
+
+    fn offer_expiry_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[OFFER_EXPIRY_TREE])
+    }
+
+    fn announcement_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[ANNOUNCEMENT_TREE])
+    }
+
+    fn partial_offer_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[PARTIAL_OFFER_TREE])
+    }
+
+    fn label_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[LABEL_TREE])
+    }
+
+    fn contract_history_tree(&self) -> Result<Tree, Error> {
+        self.open_tree(&[CONTRACT_HISTORY_TREE])
+    }
+
+    fn funding_target_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[FUNDING_TARGET_TREE])
+    }
+
+    fn outbound_message_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[OUTBOUND_MESSAGE_TREE])
+    }
+
+    fn funding_conflict_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[FUNDING_CONFLICT_TREE])
+    }
+
+    fn settlement_delay_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[SETTLEMENT_DELAY_TREE])
+    }
+
+    fn dead_letter_tree(&self) -> Result<Tree, sled::Error> {
+        self.db.open_tree(&[DEAD_LETTER_TREE])
+    }
+
+    fn contract_fees_tree(&self) -> Result<Tree, Error> {
+        self.open_tree(&[CONTRACT_FEES_TREE])
+    }
+
+    fn oracle_event_index_tree(&self) -> Result<Tree, Error> {
+        self.open_tree(&[ORACLE_EVENT_INDEX_TREE])
+    }
+
+    /// Index a contract by the oracle events it settles on. Keys are the hash of the event id
+    /// followed by the contract id, so the contracts of an event share a prefix.
+    fn index_oracle_events(&self, contract: &Contract) -> Result<(), Error> {
+        let index = self.oracle_event_index_tree()?;
+        for event_id in oracle_event_ids(contract) {
+            let mut key = oracle_event_key(&event_id).to_vec();
+            key.extend_from_slice(&contract.get_id());
+            index.insert(key, &[]).map_err(|e| Error::StorageError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Record the fees of a contract while its transactions are known. Closed contracts
+    /// drop the funding transaction, so their fees build on the ones recorded before.
+    fn record_contract_fees(&self, contract: &Contract) -> Result<(), Error> {
+        let stored = self.read_contract_fees(&contract.get_id())?;
+        let Some(fees) = contract_fees(contract, stored) else {
+            return Ok(());
+        };
+        let bytes = self
+            .format
+            .encode(&fees)
+            .map_err(|e| Error::StorageError(e.to_string()))?;
+        self.contract_fees_tree()?
+            .insert(contract.get_id(), bytes)
+            .map_err(|e| Error::StorageError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn read_contract_fees(&self, contract_id: &ContractId) -> Result<Option<ContractFees>, Error> {
+        match self
+            .contract_fees_tree()?
+            .get(contract_id)
+            .map_err(|e| Error::StorageError(e.to_string()))?
+        {
+            Some(bytes) => StorageFormat::decode(&bytes)
+                .map(Some)
+                .map_err(|e| Error::StorageError(e.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+fn oracle_event_key(event_id: &str) -> [u8; 32] {
+    Sha256Hash::hash(event_id.as_bytes()).to_byte_array()
+}
+
+/// A fixed width record, or an error for a record of another length.
+fn fixed_width<const N: usize>(bytes: &[u8], what: &str) -> anyhow::Result<[u8; N]> {
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Stored {} is {} bytes instead of {}.", what, bytes.len(), N))
 }
 
 impl DdkStorage for SledStorageProvider {
+    fn name(&self) -> String {
+        "sled".into()
+    }
+
     fn list_peers(&self) -> anyhow::Result<Vec<PeerInformation>> {
         if let Some(bytes) = self.db.get("peers")? {
-            let peers: Vec<PeerInformation> = serde_json::from_slice(&bytes)?;
+            let peers: Vec<PeerInformation> = StorageFormat::decode(&bytes)?;
             Ok(peers)
         } else {
             Ok(vec![])
@@ -101,10 +286,655 @@ impl DdkStorage for SledStorageProvider {
         }
 
         known_peers.push(peer);
-        let peer_vec = serde_json::to_vec(&known_peers)?;
+        let peer_vec = self.format.encode(&known_peers)?;
 
         self.db.insert("peers", peer_vec)?;
 
         Ok(())
     }
+
+    fn save_offer_expiry(&self, contract_id: &ContractId, expiry: u64) -> anyhow::Result<()> {
+        self.offer_expiry_tree()?
+            .insert(contract_id, expiry.to_be_bytes().to_vec())?;
+        Ok(())
+    }
+
+    fn get_offer_expiry(&self, contract_id: &ContractId) -> anyhow::Result<Option<u64>> {
+        match self.offer_expiry_tree()?.get(contract_id)? {
+            Some(bytes) => Ok(Some(u64::from_be_bytes(fixed_width(&bytes, "offer expiry")?))),
+            None => Ok(None),
+        }
+    }
+
+    fn delete_offer_expiry(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        self.offer_expiry_tree()?.remove(contract_id)?;
+        Ok(())
+    }
+
+    fn flush(&self) -> anyhow::Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    fn save_announcement(&self, announcement: &OracleAnnouncement) -> anyhow::Result<()> {
+        self.announcement_tree()?
+            .insert(announcement.oracle_event.event_id.as_bytes(), announcement.encode())?;
+        Ok(())
+    }
+
+    // Anyone who can write the database could also rewrite a hash stored next to the
+    // announcement, so the oracle signature is what is checked.
+    fn get_announcement(&self, event_id: &str) -> anyhow::Result<Option<OracleAnnouncement>> {
+        let Some(value) = self.announcement_tree()?.get(event_id.as_bytes())? else {
+            return Ok(None);
+        };
+
+        let announcement = OracleAnnouncement::read(&mut Cursor::new(&value))
+            .map_err(|e| anyhow::anyhow!("Could not read stored announcement. {:?}", e))?;
+        if announcement.oracle_event.event_id != event_id {
+            return Err(anyhow::anyhow!("Stored announcement is not for event {}.", event_id));
+        }
+        announcement
+            .validate(&Secp256k1::verification_only())
+            .map_err(|e| anyhow::anyhow!("Stored announcement for {} has an invalid signature. {:?}", event_id, e))?;
+
+        Ok(Some(announcement))
+    }
+
+    fn save_partial_offer(&self, contract_id: &ContractId, partial: &PartialOffer) -> anyhow::Result<()> {
+        self.partial_offer_tree()?
+            .insert(contract_id, self.format.encode(partial)?)?;
+        Ok(())
+    }
+
+    fn get_partial_offer(&self, contract_id: &ContractId) -> anyhow::Result<Option<PartialOffer>> {
+        match self.partial_offer_tree()?.get(contract_id)? {
+            Some(bytes) => Ok(Some(StorageFormat::decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_partial_offers(&self) -> anyhow::Result<Vec<(ContractId, PartialOffer)>> {
+        self.partial_offer_tree()?
+            .iter()
+            .map(|entry| {
+                let (key, bytes) = entry?;
+                let contract_id: ContractId = key.as_ref().try_into()?;
+                Ok((contract_id, StorageFormat::decode(&bytes)?))
+            })
+            .collect()
+    }
+
+    fn delete_partial_offer(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        self.partial_offer_tree()?.remove(contract_id)?;
+        Ok(())
+    }
+
+    fn get_contracts_by_oracle_event(&self, event_id: &str) -> anyhow::Result<Vec<Contract>> {
+        let index = self.oracle_event_index_tree()?;
+        let version = self.db.get(ORACLE_EVENT_INDEX_VERSION_KEY)?;
+        if version.as_deref() != Some(&[ORACLE_EVENT_INDEX_VERSION][..]) {
+            for contract in self.get_contracts()? {
+                self.index_oracle_events(&contract)?;
+            }
+            self.db.insert(ORACLE_EVENT_INDEX_VERSION_KEY, &[ORACLE_EVENT_INDEX_VERSION])?;
+        }
+
+        let mut contracts: Vec<Contract> = Vec::new();
+        for entry in index.scan_prefix(oracle_event_key(event_id)) {
+            let (key, _) = entry?;
+            let contract_id: ContractId = fixed_width(key.get(32..).unwrap_or_default(), "oracle event index key")?;
+            // Entries for temporary ids are left behind once a contract is accepted.
+            let Some(contract) = self.get_contract(&contract_id)? else {
+                continue;
+            };
+            if !contracts.iter().any(|c| c.get_id() == contract.get_id()) {
+                contracts.push(contract);
+            }
+        }
+        Ok(contracts)
+    }
+
+    fn list_channels(&self) -> anyhow::Result<Vec<Channel>> {
+        Ok(self.get_channels()?)
+    }
+
+    fn save_label(&self, key: &LabelKey, label: &str) -> anyhow::Result<()> {
+        let tree = self.label_tree()?;
+        if label.is_empty() {
+            tree.remove(key.to_bytes())?;
+        } else {
+            tree.insert(key.to_bytes(), label.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn get_label(&self, key: &LabelKey) -> anyhow::Result<Option<String>> {
+        match self.label_tree()?.get(key.to_bytes())? {
+            Some(bytes) => Ok(Some(String::from_utf8(bytes.to_vec())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_contract_fees(&self, contract_id: &ContractId) -> anyhow::Result<Option<ContractFees>> {
+        Ok(self.read_contract_fees(contract_id)?)
+    }
+
+    fn get_contract_history(&self, contract_id: &ContractId) -> anyhow::Result<Vec<ContractTransition>> {
+        // Transitions before the contract was accepted are stored under its temporary id.
+        let mut ids = vec![*contract_id];
+        if let Some(contract) = self.get_contract(contract_id)? {
+            if contract.get_temporary_id() != *contract_id {
+                ids.push(contract.get_temporary_id());
+            }
+        }
+
+        let tree = self.contract_history_tree()?;
+        let mut history = Vec::new();
+        for id in ids {
+            for entry in tree.scan_prefix(id) {
+                let (key, value) = entry?;
+                let entry_id = fixed_width(key.get(32..).unwrap_or_default(), "contract history key")?;
+                let transition: ContractTransition = StorageFormat::decode(&value)?;
+                history.push((u64::from_be_bytes(entry_id), transition));
+            }
+        }
+        history.sort_by_key(|(entry_id, _)| *entry_id);
+        Ok(history.into_iter().map(|(_, transition)| transition).collect())
+    }
+
+    fn save_funding_target(&self, contract_id: &ContractId, target_blocks: u16) -> anyhow::Result<()> {
+        self.funding_target_tree()?.insert(contract_id, target_blocks.to_be_bytes().to_vec())?;
+        Ok(())
+    }
+
+    fn get_funding_target(&self, contract_id: &ContractId) -> anyhow::Result<Option<u16>> {
+        // Offers are stored under their temporary id, which accepted contracts keep.
+        let tree = self.funding_target_tree()?;
+        let bytes = match tree.get(contract_id)? {
+            Some(bytes) => Some(bytes),
+            None => match self.get_contract(contract_id)? {
+                Some(contract) => tree.get(contract.get_temporary_id())?,
+                None => None,
+            },
+        };
+        match bytes {
+            Some(bytes) => Ok(Some(u16::from_be_bytes(fixed_width(&bytes, "funding target")?))),
+            None => Ok(None),
+        }
+    }
+
+    fn save_outbound_message(&self, contract_id: &ContractId, counter_party: &PublicKey, message: &Message) -> anyhow::Result<()> {
+        let mut bytes = counter_party.serialize().to_vec();
+        bytes.extend(dlc_message_bytes(message));
+        self.outbound_message_tree()?.insert(contract_id, bytes)?;
+        Ok(())
+    }
+
+    fn get_outbound_message(&self, contract_id: &ContractId) -> anyhow::Result<Option<(PublicKey, Message)>> {
+        let tree = self.outbound_message_tree()?;
+        let bytes = match tree.get(contract_id)? {
+            Some(bytes) => Some(bytes),
+            None => match self.get_contract(contract_id)? {
+                Some(contract) => tree.get(contract.get_temporary_id())?,
+                None => None,
+            },
+        };
+        let Some(bytes) = bytes else {
+            return Ok(None);
+        };
+        // The counterparty key is compressed.
+        let counter_party = PublicKey::from_slice(&bytes[..33])?;
+        match decode_dlc_message(&bytes[33..])? {
+            WireMessage::Message(message) => Ok(Some((counter_party, message))),
+            _ => Err(anyhow::anyhow!("Stored outbound message is a segment.")),
+        }
+    }
+
+    fn save_funding_conflict(&self, conflict: &FundingConflict) -> anyhow::Result<()> {
+        self.funding_conflict_tree()?
+            .insert(conflict.contract_id, self.format.encode(conflict)?)?;
+        Ok(())
+    }
+
+    fn get_funding_conflict(&self, contract_id: &ContractId) -> anyhow::Result<Option<FundingConflict>> {
+        match self.funding_conflict_tree()?.get(contract_id)? {
+            Some(bytes) => Ok(Some(StorageFormat::decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn save_settlement_delay(&self, contract_id: &ContractId, delay: &SettlementDelay) -> anyhow::Result<()> {
+        self.settlement_delay_tree()?
+            .insert(contract_id, self.format.encode(delay)?)?;
+        Ok(())
+    }
+
+    fn get_settlement_delay(&self, contract_id: &ContractId) -> anyhow::Result<Option<SettlementDelay>> {
+        match self.settlement_delay_tree()?.get(contract_id)? {
+            Some(bytes) => Ok(Some(StorageFormat::decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn save_dead_letter(&self, dead_letter: &DeadLetter) -> anyhow::Result<()> {
+        self.dead_letter_tree()?
+            .insert(dead_letter.contract_id, self.format.encode(dead_letter)?)?;
+        Ok(())
+    }
+
+    fn get_dead_letter(&self, contract_id: &ContractId) -> anyhow::Result<Option<DeadLetter>> {
+        let tree = self.dead_letter_tree()?;
+        let bytes = match tree.get(contract_id)? {
+            Some(bytes) => Some(bytes),
+            None => match self.get_contract(contract_id)? {
+                Some(contract) => tree.get(contract.get_temporary_id())?,
+                None => None,
+            },
+        };
+        match bytes {
+            Some(bytes) => Ok(Some(StorageFormat::decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn delete_dead_letter(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        self.dead_letter_tree()?.remove(contract_id)?;
+        Ok(())
+    }
+
+    fn import_contract(&self, contract: &Contract, overwrite: bool) -> anyhow::Result<()> {
+        self.import_contract_record(contract, overwrite)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::contract_label;
+    use crate::test_util::{offered_contract, signed_contract};
+
+    #[test]
+    fn tampered_announcement_is_rejected() {
+        let path = "tests/data/dlc_storage/sleddb/tampered_announcement_is_rejected";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let announcement = offered_contract().contract_info[0].oracle_announcements[0].clone();
+            let event_id = announcement.oracle_event.event_id.clone();
+
+            storage.save_announcement(&announcement).unwrap();
+            assert_eq!(storage.get_announcement(&event_id).unwrap().unwrap(), announcement);
+
+            // An announcement rewritten in the database, with any hash rewritten alongside,
+            // no longer carries a valid oracle signature.
+            let mut tampered = announcement.clone();
+            tampered.oracle_event.event_maturity_epoch += 1;
+            storage
+                .announcement_tree()
+                .unwrap()
+                .insert(event_id.as_bytes(), tampered.encode())
+                .unwrap();
+            let error = storage.get_announcement(&event_id).unwrap_err();
+            assert!(error.to_string().contains("invalid signature"));
+
+            assert!(storage.get_announcement("unknown").unwrap().is_none());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn import_does_not_overwrite_active_contracts() {
+        use crate::error::ContractError;
+        let path = "tests/data/dlc_storage/sleddb/import_does_not_overwrite_active_contracts";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = signed_contract();
+            let confirmed = Contract::Confirmed(signed.clone());
+            let refunded = Contract::Refunded(signed);
+            storage.import_contract(&confirmed, false).unwrap();
+
+            // An active contract is kept, even when asked to overwrite it.
+            for overwrite in [false, true] {
+                let error = storage.import_contract(&refunded, overwrite).unwrap_err();
+                assert!(matches!(
+                    error.downcast_ref::<ContractError>(),
+                    Some(ContractError::ContractExists { state, .. }) if state == "confirmed"
+                ));
+            }
+            let stored = storage.get_contract(&confirmed.get_id()).unwrap();
+            assert!(matches!(stored, Some(Contract::Confirmed(_))));
+
+            // A terminal contract is only replaced when asked to.
+            storage.update_contract(&refunded).unwrap();
+            assert!(storage.import_contract(&confirmed, false).is_err());
+            storage.import_contract(&confirmed, true).unwrap();
+            let stored = storage.get_contract(&confirmed.get_id()).unwrap();
+            assert!(matches!(stored, Some(Contract::Confirmed(_))));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn contracts_are_found_by_oracle_event() {
+        let path = "tests/data/dlc_storage/sleddb/contracts_are_found_by_oracle_event";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let offer = offered_contract();
+            let event_id = offer.contract_info[0].oracle_announcements[0].oracle_event.event_id.clone();
+            storage.create_contract(&offer).unwrap();
+
+            let found = storage.get_contracts_by_oracle_event(&event_id).unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].get_id(), offer.id);
+            assert!(storage.get_contracts_by_oracle_event("unknown").unwrap().is_empty());
+
+            // The contract is stored under its final id once signed.
+            let signed = signed_contract();
+            let event_id = oracle_event_ids(&Contract::Signed(signed.clone())).remove(0);
+            storage.create_contract(&signed.accepted_contract.offered_contract).unwrap();
+            storage.update_contract(&Contract::Signed(signed.clone())).unwrap();
+            let signed = Contract::Signed(signed);
+            let found = storage.get_contracts_by_oracle_event(&event_id).unwrap();
+            assert_eq!(found.iter().filter(|c| c.get_id() == signed.get_id()).count(), 1);
+            assert!(!found.iter().any(|c| c.get_id() == signed.get_temporary_id()));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn older_stores_are_indexed_by_oracle_event_once() {
+        let path = "tests/data/dlc_storage/sleddb/older_stores_are_indexed_by_oracle_event_once";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = Contract::Signed(signed_contract());
+            let event_id = oracle_event_ids(&signed).remove(0);
+            storage.update_contract(&signed).unwrap();
+            // A store written before the index existed.
+            storage.oracle_event_index_tree().unwrap().clear().unwrap();
+            storage.db.remove(ORACLE_EVENT_INDEX_VERSION_KEY).unwrap();
+
+            // Contracts stored since then are indexed, so the index is not empty.
+            storage.create_contract(&offered_contract()).unwrap();
+            assert!(!storage.oracle_event_index_tree().unwrap().is_empty());
+
+            let found = storage.get_contracts_by_oracle_event(&event_id).unwrap();
+            assert!(found.iter().any(|c| c.get_id() == signed.get_id()));
+            assert_eq!(
+                storage.db.get(ORACLE_EVENT_INDEX_VERSION_KEY).unwrap().as_deref(),
+                Some(&[ORACLE_EVENT_INDEX_VERSION][..])
+            );
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn labels_round_trip() {
+        let path = "tests/data/dlc_storage/sleddb/labels_round_trip";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = signed_contract();
+            let txid = signed.accepted_contract.dlc_transactions.fund.compute_txid();
+            let tx = LabelKey::Transaction(txid);
+            // Same bytes as the txid, but a different kind of key.
+            let contract = LabelKey::Contract(txid.to_byte_array());
+
+            storage.save_label(&tx, "hedge for BTC treasury").unwrap();
+            assert_eq!(storage.get_label(&tx).unwrap().as_deref(), Some("hedge for BTC treasury"));
+            assert!(storage.get_label(&contract).unwrap().is_none());
+
+            // A label set on the offer is listed with the signed contract.
+            let signed = Contract::Signed(signed);
+            storage
+                .save_label(&LabelKey::Contract(signed.get_temporary_id()), "offer note")
+                .unwrap();
+            assert_eq!(contract_label(&storage, &signed).unwrap().as_deref(), Some("offer note"));
+            storage.save_label(&LabelKey::Contract(signed.get_id()), "contract note").unwrap();
+            assert_eq!(contract_label(&storage, &signed).unwrap().as_deref(), Some("contract note"));
+
+            storage.save_label(&tx, "").unwrap();
+            assert!(storage.get_label(&tx).unwrap().is_none());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn fee_rate_of_the_offer_is_stored() {
+        let path = "tests/data/dlc_storage/sleddb/fee_rate_of_the_offer_is_stored";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let mut signed = signed_contract();
+            signed.accepted_contract.offered_contract.fee_rate_per_vb = 7;
+            let offered = Contract::Offered(signed.accepted_contract.offered_contract.clone());
+            assert_eq!(crate::util::contract_fee_rate(&offered), Some(7));
+
+            let contract_id = signed.accepted_contract.get_contract_id();
+            storage.update_contract(&Contract::Signed(signed.clone())).unwrap();
+            let fees = storage.get_contract_fees(&contract_id).unwrap().unwrap();
+            assert_eq!(fees.fee_rate_per_vb, 7);
+
+            // The closed contract drops the offer, the stored fees keep its rate.
+            let closed = Contract::Closed(dlc_manager::contract::ClosedContract {
+                attestations: None,
+                signed_cet: Some(signed.accepted_contract.dlc_transactions.cets[0].clone()),
+                contract_id,
+                temporary_contract_id: signed.accepted_contract.offered_contract.id,
+                counter_party_id: signed.accepted_contract.offered_contract.counter_party,
+                pnl: 0,
+            });
+            storage.update_contract(&closed).unwrap();
+            assert_eq!(crate::util::contract_fee_rate(&closed), None);
+            let fees = storage.get_contract_fees(&contract_id).unwrap().unwrap();
+            assert_eq!(fees.fee_rate_per_vb, 7);
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn history_records_each_transition() {
+        let path = "tests/data/dlc_storage/sleddb/history_records_each_transition";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let signed = signed_contract();
+            let fund_txid = signed.accepted_contract.dlc_transactions.fund.compute_txid();
+            storage.create_contract(&signed.accepted_contract.offered_contract).unwrap();
+            storage.update_contract(&Contract::Signed(signed.clone())).unwrap();
+            // Storing the same state again is not a transition.
+            storage.update_contract(&Contract::Signed(signed.clone())).unwrap();
+            storage.update_contract(&Contract::Confirmed(signed.clone())).unwrap();
+
+            let history = storage
+                .get_contract_history(&Contract::Signed(signed).get_id())
+                .unwrap();
+            let states = history
+                .iter()
+                .map(|t| (t.from_state.as_deref(), t.to_state.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                states,
+                vec![(None, "offered"), (Some("offered"), "signed"), (Some("signed"), "confirmed")]
+            );
+            assert_eq!(history[0].txid, None);
+            assert_eq!(history[2].txid, Some(fund_txid));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    fn store_records(storage: &SledStorageProvider) -> ContractId {
+        let signed = signed_contract();
+        let contract_id = signed.accepted_contract.get_contract_id();
+        storage.create_contract(&signed.accepted_contract.offered_contract).unwrap();
+        storage.update_contract(&Contract::Confirmed(signed)).unwrap();
+        storage
+            .save_peer(PeerInformation {
+                pubkey: "peer".to_string(),
+                host: "127.0.0.1:1776".to_string(),
+            })
+            .unwrap();
+        storage
+            .save_funding_conflict(&FundingConflict {
+                contract_id,
+                outpoint: bitcoin::OutPoint::null(),
+                conflicting_txid: bitcoin::Txid::all_zeros(),
+            })
+            .unwrap();
+        storage
+            .save_settlement_delay(&contract_id, &SettlementDelay { max_delay_secs: 60, fee_rate: 5 })
+            .unwrap();
+        contract_id
+    }
+
+    #[test]
+    fn records_round_trip_in_both_formats() {
+        let mut stored = Vec::new();
+        for format in [StorageFormat::Json, StorageFormat::Bincode] {
+            let path = format!("tests/data/dlc_storage/sleddb/records_round_trip_{:?}", format);
+            {
+                let storage = SledStorageProvider::new(&path).unwrap().with_format(format);
+                let contract_id = store_records(&storage);
+                let fees = storage.contract_fees_tree().unwrap().get(contract_id).unwrap().unwrap();
+                assert_eq!(StorageFormat::of(&fees), format);
+                stored.push((
+                    storage.get_contract_fees(&contract_id).unwrap(),
+                    storage
+                        .get_contract_history(&contract_id)
+                        .unwrap()
+                        .into_iter()
+                        .map(|t| (t.from_state, t.to_state, t.txid))
+                        .collect::<Vec<_>>(),
+                    storage.list_peers().unwrap(),
+                    storage.get_funding_conflict(&contract_id).unwrap(),
+                    storage.get_settlement_delay(&contract_id).unwrap(),
+                ));
+            }
+            std::fs::remove_dir_all(path).unwrap();
+        }
+        assert!(stored[0].0.is_some() && stored[0].3.is_some() && stored[0].4.is_some());
+        assert_eq!(stored[0], stored[1]);
+    }
+
+    #[test]
+    fn bincode_is_smaller_and_faster_for_a_large_contract() {
+        // A partial offer holds the whole contract input. A curve of a thousand steps is one of
+        // the largest records DDK keeps.
+        let announcements = offered_contract().contract_info[0].oracle_announcements.clone();
+        let contract_input = ddk_payouts::create_contract_input(
+            0,
+            100_000,
+            1_000,
+            50_000,
+            50_000,
+            2,
+            announcements[0].oracle_public_key.to_string(),
+            announcements[0].oracle_event.event_id.clone(),
+            None,
+        )
+        .unwrap();
+        let partial = PartialOffer::new(contract_input, announcements);
+
+        let measure = |format: StorageFormat| {
+            let start = std::time::Instant::now();
+            let mut bytes = Vec::new();
+            for _ in 0..20 {
+                bytes = format.encode(&partial).unwrap();
+                let decoded: PartialOffer = StorageFormat::decode(&bytes).unwrap();
+                assert_eq!(format.encode(&decoded).unwrap(), bytes);
+            }
+            (bytes.len(), start.elapsed())
+        };
+        let (json_size, json_time) = measure(StorageFormat::Json);
+        let (bincode_size, bincode_time) = measure(StorageFormat::Bincode);
+        assert!(bincode_size * 2 < json_size, "bincode {bincode_size} bytes, json {json_size} bytes");
+        assert!(bincode_time < json_time, "bincode {bincode_time:?}, json {json_time:?}");
+    }
+
+    #[test]
+    fn json_records_are_migrated_to_bincode() {
+        let path = "tests/data/dlc_storage/sleddb/json_records_are_migrated_to_bincode";
+        {
+            let storage = SledStorageProvider::new(path).unwrap().with_format(StorageFormat::Json);
+            let contract_id = store_records(&storage);
+            let history = storage.get_contract_history(&contract_id).unwrap();
+            let peers = storage.list_peers().unwrap();
+
+            // Records written as JSON are still read once new ones are bincode.
+            let storage = storage.with_format(StorageFormat::Bincode);
+            assert_eq!(storage.get_contract_history(&contract_id).unwrap(), history);
+            assert_eq!(storage.list_peers().unwrap(), peers);
+
+            // Fees, two transitions, peers, the funding conflict and the settlement delay.
+            assert_eq!(storage.migrate_records().unwrap(), 6);
+            assert_eq!(storage.migrate_records().unwrap(), 0);
+            let fees = storage.contract_fees_tree().unwrap().get(contract_id).unwrap().unwrap();
+            assert_eq!(StorageFormat::of(&fees), StorageFormat::Bincode);
+            assert_eq!(storage.get_contract_history(&contract_id).unwrap(), history);
+            assert_eq!(storage.list_peers().unwrap(), peers);
+            assert!(storage.get_settlement_delay(&contract_id).unwrap().is_some());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn records_of_the_wrong_width_are_an_error() {
+        let path = "tests/data/dlc_storage/sleddb/records_of_the_wrong_width_are_an_error";
+        {
+            let storage = SledStorageProvider::new(path).unwrap();
+            let contract_id = [4u8; 32];
+            storage.save_offer_expiry(&contract_id, 1_700_000_000).unwrap();
+            storage.save_funding_target(&contract_id, 6).unwrap();
+            assert_eq!(storage.get_offer_expiry(&contract_id).unwrap(), Some(1_700_000_000));
+            assert_eq!(storage.get_funding_target(&contract_id).unwrap(), Some(6));
+
+            storage.offer_expiry_tree().unwrap().insert(contract_id, vec![1u8; 4]).unwrap();
+            storage.funding_target_tree().unwrap().insert(contract_id, vec![1u8; 3]).unwrap();
+            assert!(storage.get_offer_expiry(&contract_id).is_err());
+            assert!(storage.get_funding_target(&contract_id).is_err());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn records_of_an_earlier_version_are_upgraded() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Fees {
+            funding_fee: u64,
+        }
+        impl Record for Fees {}
+
+        // The same record once `fee_rate` was added.
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct FeesWithRate {
+            funding_fee: u64,
+            fee_rate: u64,
+        }
+        impl Record for FeesWithRate {
+            const VERSION: u8 = 1;
+
+            fn upgrade(version: u8, bytes: &[u8]) -> anyhow::Result<Self> {
+                match version {
+                    0 => {
+                        let fees: Fees = bincode::deserialize(bytes)?;
+                        Ok(FeesWithRate { funding_fee: fees.funding_fee, fee_rate: 0 })
+                    }
+                    _ => Err(anyhow::anyhow!("Unknown version {}.", version)),
+                }
+            }
+        }
+
+        assert_eq!(StorageFormat::default(), StorageFormat::Bincode);
+        let old = StorageFormat::Bincode.encode(&Fees { funding_fee: 300 }).unwrap();
+        assert!(!StorageFormat::Bincode.is_current::<FeesWithRate>(&old));
+        let upgraded: FeesWithRate = StorageFormat::decode(&old).unwrap();
+        assert_eq!(upgraded, FeesWithRate { funding_fee: 300, fee_rate: 0 });
+
+        // A record from a newer DDK is refused instead of misread.
+        let new = StorageFormat::Bincode.encode(&FeesWithRate { funding_fee: 300, fee_rate: 2 }).unwrap();
+        assert!(StorageFormat::decode::<Fees>(&new).is_err());
+
+        // Bincode records from before versions read as version 0, and JSON as before.
+        let mut legacy = vec![0x00];
+        bincode::serialize_into(&mut legacy, &Fees { funding_fee: 300 }).unwrap();
+        assert_eq!(StorageFormat::decode::<FeesWithRate>(&legacy).unwrap().funding_fee, 300);
+        let json = StorageFormat::Json.encode(&Fees { funding_fee: 300 }).unwrap();
+        assert_eq!(StorageFormat::decode::<Fees>(&json).unwrap(), Fees { funding_fee: 300 });
+    }
 }
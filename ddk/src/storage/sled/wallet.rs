@@ -1,31 +1,167 @@
 use super::SledStorageProvider;
 use crate::error::WalletError;
 use crate::signer::{DeriveSigner, SignerInformation};
+use bdk_chain::Merge;
 use bdk_wallet::ChangeSet;
 use bdk_wallet::WalletPersister;
 use bitcoin::{
-    key::rand::{thread_rng, Rng},
+    key::rand::{rngs::OsRng, RngCore},
     secp256k1::{PublicKey, SecretKey},
 };
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+const BIRTH_HEIGHT_KEY: &str = "birth_height";
+const WALLET_SALT_KEY: &str = "wallet_salt";
+const WALLET_CHECK_KEY: &str = "wallet_check";
+/// Stored encrypted with the wallet key, so a wrong passphrase fails when the database is
+/// opened rather than on the first read.
+const WALLET_CHECK: &[u8] = b"ddk wallet";
+/// PBKDF2-HMAC-SHA256 rounds deriving the wallet key from the passphrase.
+const KDF_ROUNDS: u32 = 100_000;
+const NONCE_LEN: usize = 12;
+
+/// Key of an encrypted wallet database.
+#[derive(Clone)]
+pub(crate) struct WalletKey([u8; 32]);
+
+impl std::fmt::Debug for WalletKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WalletKey(..)")
+    }
+}
+
+impl WalletKey {
+    fn derive(passphrase: &str, salt: &[u8]) -> Self {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(passphrase.as_bytes(), salt, KDF_ROUNDS, &mut key);
+        Self(key)
+    }
+
+    /// A random nonce followed by the ChaCha20-Poly1305 ciphertext of `plaintext`. Nonces
+    /// come from the OS rng, never the seedable one, so they are not repeated across runs.
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.0));
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .expect("changesets are within the ChaCha20-Poly1305 length limit");
+        [nonce.as_slice(), &ciphertext].concat()
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, WalletError> {
+        if data.len() < NONCE_LEN {
+            return Err(WalletError::DecryptionFailed);
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        ChaCha20Poly1305::new(Key::from_slice(&self.0))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| WalletError::DecryptionFailed)
+    }
+}
+
+impl SledStorageProvider {
+    /// Open the wallet database at `path` with its changesets encrypted by a key derived from
+    /// `passphrase`. A new database is encrypted from the start. Fails with
+    /// [WalletError::DecryptionFailed] if the database was encrypted with another passphrase
+    /// and with [WalletError::NotEncrypted] if it holds a plaintext wallet.
+    pub fn new_encrypted(path: &str, passphrase: &str) -> Result<Self, WalletError> {
+        let mut storage = Self::new(path)?;
+        let key = match storage.db.get(WALLET_SALT_KEY)? {
+            Some(salt) => {
+                let key = WalletKey::derive(passphrase, &salt);
+                let check = storage
+                    .db
+                    .get(WALLET_CHECK_KEY)?
+                    .ok_or(WalletError::DecryptionFailed)?;
+                if key.decrypt(&check)? != WALLET_CHECK {
+                    return Err(WalletError::DecryptionFailed);
+                }
+                key
+            }
+            None => {
+                if !storage.wallet_tree()?.is_empty() {
+                    return Err(WalletError::NotEncrypted);
+                }
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                let key = WalletKey::derive(passphrase, &salt);
+                storage.db.insert(WALLET_CHECK_KEY, key.encrypt(WALLET_CHECK))?;
+                storage.db.insert(WALLET_SALT_KEY, salt.to_vec())?;
+                key
+            }
+        };
+        storage.wallet_key = Some(key);
+        Ok(storage)
+    }
+
+    /// Whether the wallet database was created with a passphrase.
+    pub fn is_wallet_encrypted(&self) -> Result<bool, WalletError> {
+        Ok(self.db.contains_key(WALLET_SALT_KEY)?)
+    }
+
+    /// Every wallet changeset persisted so far, oldest first, decrypted if the database is
+    /// encrypted. Changesets written before they were keyed by sequence have random keys and
+    /// come first in no particular order.
+    pub(crate) fn wallet_changesets(&self) -> Result<Vec<ChangeSet>, WalletError> {
+        self.wallet_tree()?
+            .iter()
+            .values()
+            .map(|value| {
+                let value = value?;
+                let bytes = match &self.wallet_key {
+                    Some(key) => key.decrypt(&value)?,
+                    None => value.to_vec(),
+                };
+                Ok(bincode::deserialize(&bytes)?)
+            })
+            .collect()
+    }
+
+    /// Store the chain height the wallet was created at.
+    pub fn save_birth_height(&self, height: u32) -> Result<(), WalletError> {
+        self.db.insert(BIRTH_HEIGHT_KEY, height.to_be_bytes().to_vec())?;
+        Ok(())
+    }
+
+    /// The chain height the wallet was created at, if known.
+    pub fn get_birth_height(&self) -> Result<Option<u32>, WalletError> {
+        Ok(self.db.get(BIRTH_HEIGHT_KEY)?.map(|bytes| {
+            let mut height = [0u8; 4];
+            height.copy_from_slice(&bytes);
+            u32::from_be_bytes(height)
+        }))
+    }
+}
 
 impl WalletPersister for SledStorageProvider {
     type Error = WalletError;
 
     fn persist(persister: &mut Self, changeset: &ChangeSet) -> Result<(), Self::Error> {
         let wallet_tree = persister.wallet_tree()?;
-        let rand_key: [u8; 32] = thread_rng().gen();
+        // Keyed by sequence so changesets are read back in the order they were written.
+        let sequence = persister.db.generate_id()?.to_be_bytes();
         let new_changeset = bincode::serialize(changeset).map_err(|_| {
             WalletError::StorageError(sled::Error::Io(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 "Serialization error",
             )))
         })?;
-        wallet_tree.insert(rand_key, new_changeset)?;
+        let new_changeset = match &persister.wallet_key {
+            Some(key) => key.encrypt(&new_changeset),
+            None => new_changeset,
+        };
+        wallet_tree.insert(sequence, new_changeset)?;
         Ok(())
     }
 
-    fn initialize(_persister: &mut Self) -> Result<ChangeSet, Self::Error> {
-        Ok(ChangeSet::default())
+    fn initialize(persister: &mut Self) -> Result<ChangeSet, Self::Error> {
+        let mut aggregate = ChangeSet::default();
+        for changeset in persister.wallet_changesets()? {
+            aggregate.merge(changeset);
+        }
+        Ok(aggregate)
     }
 }
 
@@ -84,3 +220,75 @@ impl DeriveSigner for SledStorageProvider {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::template::Bip84;
+    use bdk_wallet::{KeychainKind, Wallet};
+    use bitcoin::bip32::Xpriv;
+    use bitcoin::Network;
+
+    #[test]
+    fn encrypted_wallet_round_trips() {
+        let path = "tests/data/dlc_storage/sleddb/encrypted_wallet_round_trips";
+        {
+            let changeset = ChangeSet {
+                network: Some(Network::Regtest),
+                ..Default::default()
+            };
+            {
+                let mut storage = SledStorageProvider::new_encrypted(path, "correct horse").unwrap();
+                WalletPersister::persist(&mut storage, &changeset).unwrap();
+
+                let plaintext = bincode::serialize(&changeset).unwrap();
+                let stored = storage.wallet_tree().unwrap().iter().values().next().unwrap().unwrap();
+                assert_ne!(stored.as_ref(), plaintext.as_slice());
+            }
+
+            let storage = SledStorageProvider::new_encrypted(path, "correct horse").unwrap();
+            assert!(storage.is_wallet_encrypted().unwrap());
+            assert_eq!(storage.wallet_changesets().unwrap(), vec![changeset]);
+            drop(storage);
+
+            assert!(matches!(
+                SledStorageProvider::new_encrypted(path, "wrong passphrase"),
+                Err(WalletError::DecryptionFailed)
+            ));
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn encrypted_wallet_loads_after_restart() {
+        let path = "tests/data/dlc_storage/sleddb/encrypted_wallet_loads_after_restart";
+        {
+            let xprv = Xpriv::new_master(Network::Regtest, &[3u8; 32]).unwrap();
+            let external = Bip84(xprv, KeychainKind::External);
+            let internal = Bip84(xprv, KeychainKind::Internal);
+            let address = {
+                let mut storage = SledStorageProvider::new_encrypted(path, "correct horse").unwrap();
+                let mut wallet = Wallet::create(external.clone(), internal.clone())
+                    .network(Network::Regtest)
+                    .create_wallet(&mut storage)
+                    .unwrap();
+                let address = wallet.reveal_next_address(KeychainKind::External).address;
+                wallet.persist(&mut storage).unwrap();
+                address
+            };
+
+            let mut storage = SledStorageProvider::new_encrypted(path, "correct horse").unwrap();
+            let wallet = Wallet::load()
+                .descriptor(KeychainKind::External, Some(external))
+                .descriptor(KeychainKind::Internal, Some(internal))
+                .extract_keys()
+                .check_network(Network::Regtest)
+                .load_wallet(&mut storage)
+                .unwrap()
+                .expect("the wallet was stored");
+            assert_eq!(wallet.derivation_index(KeychainKind::External), Some(0));
+            assert_eq!(wallet.peek_address(KeychainKind::External, 0).address, address);
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -1,10 +1,19 @@
+use async_trait::async_trait;
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
 use bitcoin::{bip32::Xpriv, key::rand::Fill, Network};
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::ser::Serializable;
+use dlc_manager::contract::signed_contract::SignedContract;
 use dlc_manager::{manager::Manager, SystemTimeProvider};
-use std::sync::Arc;
+use dlc_messages::Message;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use crate::{
     chain::EsploraClient, oracle::P2PDOracleClient, storage::SledStorageProvider,
-    wallet::DlcDevKitWallet,
+    transport::MessageReceipt,
+    wallet::{DlcDevKitWallet, WalletKeys},
+    DdkTransport,
 };
 
 type TestManager = Arc<
@@ -41,11 +50,15 @@ impl TestWallet {
         let xpriv = Xpriv::new_master(Network::Regtest, &entropy).unwrap();
         let wallet = DlcDevKitWallet::new(
             "test".into(),
-            xpriv,
+            WalletKeys::Seed(xpriv),
             "http://localhost:30000",
             Network::Regtest,
             &path,
             storage.clone(),
+            0,
+            None,
+            None,
+            None,
         )
         .unwrap();
         TestWallet { wallet, path }
@@ -57,3 +70,99 @@ impl Drop for TestWallet {
         std::fs::remove_dir_all(&self.path).expect("Couldn't remove wallet dir");
     }
 }
+
+/// The offered contract used by the sled storage test vectors.
+pub fn offered_contract() -> OfferedContract {
+    let serialized = include_bytes!("../tests/data/dlc_storage/sled/Offered");
+    let mut cursor = lightning::io::Cursor::new(&serialized);
+    OfferedContract::deserialize(&mut cursor).unwrap()
+}
+
+/// The signed contract used by the sled storage test vectors.
+pub fn signed_contract() -> SignedContract {
+    let serialized = include_bytes!("../tests/data/dlc_storage/sled/Signed");
+    let mut cursor = lightning::io::Cursor::new(&serialized);
+    SignedContract::deserialize(&mut cursor).unwrap()
+}
+
+/// In-memory transport that records the messages sent over it.
+pub struct MockTransport {
+    name: String,
+    received: Mutex<Vec<(PublicKey, Message)>>,
+    sent: Mutex<Vec<(PublicKey, Message)>>,
+    online: Mutex<HashMap<PublicKey, bool>>,
+}
+
+impl MockTransport {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            received: Mutex::new(Vec::new()),
+            sent: Mutex::new(Vec::new()),
+            online: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A fixed counterparty public key.
+    pub fn counter_party() -> PublicKey {
+        let secret = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        PublicKey::from_secret_key(&Secp256k1::new(), &secret)
+    }
+
+    /// Report `counter_party` as online or offline. Counterparties never set are unknown.
+    pub fn set_online(&self, counter_party: PublicKey, online: bool) {
+        self.online.lock().unwrap().insert(counter_party, online);
+    }
+
+    /// Queue a message as if it was received from `counter_party`.
+    pub fn receive(&self, counter_party: PublicKey, message: Message) {
+        self.received.lock().unwrap().push((counter_party, message));
+    }
+
+    /// Counterparties that messages were sent to.
+    pub fn sent(&self) -> Vec<(PublicKey, String)> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(pk, msg)| (*pk, format!("{:?}", msg)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DdkTransport for MockTransport {
+    type PeerManager = ();
+    type MessageHandler = ();
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn listen(&self) {}
+
+    fn message_handler(&self) -> Self::MessageHandler {}
+
+    fn peer_manager(&self) -> Self::PeerManager {}
+
+    fn process_messages(&self) {}
+
+    async fn send_message(&self, counterparty: PublicKey, message: Message) -> anyhow::Result<MessageReceipt> {
+        self.sent.lock().unwrap().push((counterparty, message));
+        Ok(MessageReceipt::Sent)
+    }
+
+    fn get_and_clear_received_messages(&self) -> Vec<(PublicKey, Message)> {
+        std::mem::take(&mut *self.received.lock().unwrap())
+    }
+
+    fn has_pending_messages(&self) -> bool {
+        !self.received.lock().unwrap().is_empty()
+    }
+
+    async fn connect_outbound(&self, _pubkey: PublicKey, _host: &str) {}
+
+    fn counterparty_online(&self, counterparty: &PublicKey) -> Option<bool> {
+        self.online.lock().unwrap().get(counterparty).copied()
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use crate::transport::lightning::peer_manager::LnPeerManager;
+use crate::transport::lightning::LightningTransport;
+#[cfg(feature = "nostr")]
+use crate::transport::nostr::NostrDlcRelayHandler;
+use crate::transport::MessageReceipt;
+use crate::DdkTransport;
+use async_trait::async_trait;
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::PublicKey;
+use dlc_messages::message_handler::MessageHandler as DlcMessageHandler;
+use dlc_messages::{Message, OfferDlc};
+
+/// One of the transports DDK ships with. Registering several with
+/// [crate::builder::DdkBuilder::add_transport] lets a node listen on lightning and nostr at
+/// once, since the builder takes one transport type.
+#[derive(Clone)]
+pub enum AnyTransport {
+    Lightning(Arc<LightningTransport>),
+    #[cfg(feature = "nostr")]
+    Nostr(Arc<NostrDlcRelayHandler>),
+}
+
+impl From<Arc<LightningTransport>> for AnyTransport {
+    fn from(transport: Arc<LightningTransport>) -> Self {
+        AnyTransport::Lightning(transport)
+    }
+}
+
+#[cfg(feature = "nostr")]
+impl From<Arc<NostrDlcRelayHandler>> for AnyTransport {
+    fn from(transport: Arc<NostrDlcRelayHandler>) -> Self {
+        AnyTransport::Nostr(transport)
+    }
+}
+
+impl AnyTransport {
+    /// The lightning transport, to manage its peers.
+    pub fn as_lightning(&self) -> Option<&Arc<LightningTransport>> {
+        match self {
+            AnyTransport::Lightning(transport) => Some(transport),
+            #[cfg(feature = "nostr")]
+            _ => None,
+        }
+    }
+}
+
+/// Run `$call` on the transport inside `$transport`.
+macro_rules! dispatch {
+    ($transport:expr, $inner:ident => $call:expr) => {
+        match $transport {
+            AnyTransport::Lightning($inner) => $call,
+            #[cfg(feature = "nostr")]
+            AnyTransport::Nostr($inner) => $call,
+        }
+    };
+}
+
+#[async_trait]
+impl DdkTransport for AnyTransport {
+    /// The lightning peer manager. `None` for transports without one.
+    type PeerManager = Option<Arc<LnPeerManager>>;
+    /// The lightning message handler. `None` for transports without one.
+    type MessageHandler = Option<Arc<DlcMessageHandler>>;
+
+    fn name(&self) -> String {
+        dispatch!(self, transport => DdkTransport::name(transport.as_ref()))
+    }
+
+    async fn listen(&self) {
+        dispatch!(self, transport => DdkTransport::listen(transport.as_ref()).await)
+    }
+
+    fn message_handler(&self) -> Self::MessageHandler {
+        self.as_lightning().map(|transport| transport.message_handler())
+    }
+
+    fn peer_manager(&self) -> Self::PeerManager {
+        self.as_lightning().map(|transport| transport.ln_peer_manager())
+    }
+
+    fn process_messages(&self) {
+        dispatch!(self, transport => DdkTransport::process_messages(transport.as_ref()))
+    }
+
+    async fn send_message(&self, counterparty: PublicKey, message: Message) -> anyhow::Result<MessageReceipt> {
+        dispatch!(self, transport => DdkTransport::send_message(transport.as_ref(), counterparty, message).await)
+    }
+
+    fn get_and_clear_received_messages(&self) -> Vec<(PublicKey, Message)> {
+        dispatch!(self, transport => DdkTransport::get_and_clear_received_messages(transport.as_ref()))
+    }
+
+    fn has_pending_messages(&self) -> bool {
+        dispatch!(self, transport => DdkTransport::has_pending_messages(transport.as_ref()))
+    }
+
+    async fn connect_outbound(&self, pubkey: PublicKey, host: &str) {
+        dispatch!(self, transport => DdkTransport::connect_outbound(transport.as_ref(), pubkey, host).await)
+    }
+
+    fn counterparty_online(&self, counterparty: &PublicKey) -> Option<bool> {
+        dispatch!(self, transport => DdkTransport::counterparty_online(transport.as_ref(), counterparty))
+    }
+
+    fn offer_signature(&self, counterparty: &PublicKey, offer: &OfferDlc) -> Option<Signature> {
+        dispatch!(self, transport => DdkTransport::offer_signature(transport.as_ref(), counterparty, offer))
+    }
+}
+
+#[cfg(all(test, feature = "nostr"))]
+mod tests {
+    use super::*;
+    use crate::test_util::{offered_contract, MockTransport};
+    use crate::RELAY_HOST;
+
+    #[test]
+    fn lightning_and_nostr_share_one_transport_type() {
+        let node_pubkey = MockTransport::counter_party();
+        let lightning = Arc::new(LightningTransport::watch_only(node_pubkey, 0).unwrap());
+        let keys = nostr::Keys::generate();
+        let nostr = Arc::new(NostrDlcRelayHandler::with_keys(keys, RELAY_HOST).unwrap());
+        let transports: Vec<Arc<AnyTransport>> =
+            vec![Arc::new(lightning.clone().into()), Arc::new(nostr.clone().into())];
+
+        let names = transports.iter().map(|t| t.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["lightning", "nostr"]);
+        assert!(transports[0].peer_manager().is_some());
+        assert!(transports[1].peer_manager().is_none());
+
+        // Messages received on the nostr transport surface through the enum.
+        let offer = Message::Offer((&offered_contract()).into());
+        nostr.queue_received(node_pubkey, offer);
+        assert!(!transports[0].has_pending_messages());
+        assert!(transports[1].has_pending_messages());
+        assert_eq!(transports[1].get_and_clear_received_messages().len(), 1);
+    }
+}
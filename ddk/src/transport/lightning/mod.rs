@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use crate::transport::{MessageReceipt, TransportIdentity};
 use crate::DdkTransport;
 use async_trait::async_trait;
 use bitcoin::secp256k1::PublicKey;
@@ -10,6 +11,22 @@ pub(crate) mod peer_manager;
 pub use peer_manager::LightningTransport;
 use tokio::net::TcpListener;
 
+/// Lightning peers are named by their compressed node key.
+pub struct LightningIdentity;
+
+impl TransportIdentity for LightningIdentity {
+    type PeerId = PublicKey;
+    type Error = std::convert::Infallible;
+
+    fn node_id(peer: &PublicKey) -> Result<PublicKey, Self::Error> {
+        Ok(*peer)
+    }
+
+    fn peer_id(node_id: &PublicKey) -> PublicKey {
+        *node_id
+    }
+}
+
 #[async_trait]
 impl DdkTransport for LightningTransport {
     type PeerManager = Arc<super::lightning::peer_manager::LnPeerManager>;
@@ -20,6 +37,10 @@ impl DdkTransport for LightningTransport {
     }
 
     async fn listen(&self) {
+        if self.disabled {
+            tracing::info!("Lightning transport is disabled on a watch-only node. Not listening.");
+            return;
+        }
         let peer_manager_connection_handler = self.peer_manager();
 
         let listener = TcpListener::bind(format!("0.0.0.0:{}", self.listening_port))
@@ -49,12 +70,26 @@ impl DdkTransport for LightningTransport {
         self.ln_peer_manager().process_events()
     }
 
-    fn send_message(&self, counterparty: PublicKey, message: dlc_messages::Message) {
-        self.message_handler().send_message(counterparty, message)
+    /// Messages are queued on the message handler and written to the peer when the peer
+    /// manager processes its events.
+    async fn send_message(&self, counterparty: PublicKey, message: dlc_messages::Message) -> anyhow::Result<MessageReceipt> {
+        if self.disabled {
+            return Err(crate::error::WalletError::WatchOnly.into());
+        }
+        self.message_handler().send_message(LightningIdentity::peer_id(&counterparty), message);
+        Ok(MessageReceipt::Queued)
     }
 
+    /// Senders are named by their node key, the same as over every other transport.
     fn get_and_clear_received_messages(&self) -> Vec<(PublicKey, Message)> {
-        self.message_handler().get_and_clear_received_messages()
+        self.message_handler()
+            .get_and_clear_received_messages()
+            .into_iter()
+            .map(|(peer, message)| {
+                let node_id = LightningIdentity::node_id(&peer).unwrap_or_else(|never| match never {});
+                (node_id, message)
+            })
+            .collect()
     }
 
     fn has_pending_messages(&self) -> bool {
@@ -62,6 +97,15 @@ impl DdkTransport for LightningTransport {
     }
 
     async fn connect_outbound(&self, pubkey: PublicKey, host: &str) {
-        connect_outbound(self.peer_manager(), pubkey, host.parse().unwrap()).await;
+        if self.disabled {
+            tracing::warn!(pubkey = pubkey.to_string(), "Watch-only nodes do not connect to peers.");
+            return;
+        }
+        connect_outbound(self.peer_manager(), LightningIdentity::peer_id(&pubkey), host.parse().unwrap()).await;
+    }
+
+    /// Online while the counterparty has a connection with the peer manager.
+    fn counterparty_online(&self, counterparty: &PublicKey) -> Option<bool> {
+        Some(self.ln_peer_manager().peer_by_node_id(&LightningIdentity::peer_id(counterparty)).is_some())
     }
 }
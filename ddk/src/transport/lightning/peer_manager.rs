@@ -10,6 +10,7 @@ use lightning::{
     util::logger::{Logger, Record},
 };
 use lightning_net_tokio::SocketDescriptor;
+use bitcoin::key::rand::{rngs::OsRng, RngCore};
 use std::{sync::Arc, time::SystemTime};
 
 use crate::config::SeedConfig;
@@ -37,6 +38,8 @@ pub struct LightningTransport {
     message_handler: Arc<DlcMessageHandler>,
     pub node_id: PublicKey,
     pub listening_port: u16,
+    /// Neither listens nor sends. See [LightningTransport::watch_only].
+    pub(crate) disabled: bool,
 }
 
 impl LightningTransport {
@@ -48,8 +51,24 @@ impl LightningTransport {
         let seed = crate::io::xprv_from_config(seed_config, network)?
             .private_key
             .secret_bytes();
+        Self::from_seed(&seed, listening_port)
+    }
+
+    /// A transport for a watch-only node, which cannot sign DLC messages. It goes by
+    /// `node_pubkey`, never loads a seed, and neither listens nor sends. Its noise key is
+    /// random and never leaves the process.
+    pub fn watch_only(node_pubkey: PublicKey, listening_port: u16) -> anyhow::Result<LightningTransport> {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        let mut transport = Self::from_seed(&seed, listening_port)?;
+        transport.node_id = node_pubkey;
+        transport.disabled = true;
+        Ok(transport)
+    }
+
+    fn from_seed(seed: &[u8; 32], listening_port: u16) -> anyhow::Result<LightningTransport> {
         let time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
-        let key_signer = KeysManager::new(&seed, time.as_secs(), time.as_nanos() as u32);
+        let key_signer = KeysManager::new(seed, time.as_secs(), time.as_nanos() as u32);
         let node_id = key_signer
             .get_node_id(lightning::sign::Recipient::Node)
             .map_err(|_| anyhow!("Could not get node id."))?;
@@ -66,13 +85,14 @@ impl LightningTransport {
             peer_manager: Arc::new(LnPeerManager::new(
                 message_handler,
                 time.as_secs() as u32,
-                &seed,
+                seed,
                 Arc::new(DlcDevKitLogger {}),
                 Arc::new(key_signer),
             )),
             message_handler: dlc_message_handler,
             node_id,
             listening_port,
+            disabled: false,
         })
     }
 
@@ -1,7 +1,36 @@
+mod any;
 pub mod lightning;
 #[cfg(feature = "nostr")]
 pub mod nostr;
 
+pub use any::AnyTransport;
+
+/// How far an outbound DLC message got when [crate::DdkTransport::send_message] returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageReceipt {
+    /// Queued for the transport to send, for example until the peer is connected.
+    Queued,
+    /// Written to the connection of the counterparty.
+    Sent,
+    /// Accepted by an intermediary that delivers it, with its proof of acceptance such as the
+    /// id of the published Nostr event.
+    Acknowledged(String),
+}
+
+/// Maps how a transport names its peers to the node key the DLC manager keys contracts by.
+/// Each transport maps the same node key the same way, so a peer reached over several
+/// transports is one counterparty.
+pub trait TransportIdentity {
+    /// How the transport names a peer.
+    type PeerId;
+    type Error;
+
+    /// The node key of `peer`.
+    fn node_id(peer: &Self::PeerId) -> Result<bitcoin::secp256k1::PublicKey, Self::Error>;
+    /// How the transport names the peer with `node_id`.
+    fn peer_id(node_id: &bitcoin::secp256k1::PublicKey) -> Self::PeerId;
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct PeerInformation {
     pub pubkey: String,
@@ -0,0 +1,177 @@
+//! NIP-42 authentication for relays that only serve authenticated clients.
+use crate::error::NostrError;
+use nostr::{ClientMessage, EventBuilder, EventId, Keys, RelayMessage, Url};
+
+/// How a relay handler answers NIP-42 AUTH challenges from its relay.
+#[derive(Debug, Clone, Default)]
+pub enum RelayAuth {
+    /// Authenticate with the keys of the handler.
+    #[default]
+    HandlerKeys,
+    /// Authenticate with a separate identity, such as the one registered with a paid relay.
+    Keys(Keys),
+    /// Ignore AUTH challenges. Relays that require AUTH will refuse the subscriptions.
+    Disabled,
+}
+
+/// What to do after a relay message was passed to a [RelayAuthenticator].
+#[derive(Debug, PartialEq)]
+pub(crate) enum AuthStep {
+    /// Send the AUTH event to the relay.
+    Send(ClientMessage),
+    /// The relay accepted the AUTH event. Subscriptions it refused must be sent again.
+    Authenticated,
+    /// The message is not part of authentication.
+    Ignore,
+}
+
+/// Answers the AUTH challenges of one relay and tracks the pending AUTH event.
+pub(crate) struct RelayAuthenticator {
+    keys: Keys,
+    relay_url: Url,
+    pending: Option<EventId>,
+}
+
+impl RelayAuthenticator {
+    /// Authenticator for `relay_url`. `None` if authentication is disabled.
+    pub fn new(auth: &RelayAuth, handler_keys: &Keys, relay_url: Url) -> Option<Self> {
+        let keys = match auth {
+            RelayAuth::HandlerKeys => handler_keys.clone(),
+            RelayAuth::Keys(keys) => keys.clone(),
+            RelayAuth::Disabled => return None,
+        };
+        Some(Self {
+            keys,
+            relay_url,
+            pending: None,
+        })
+    }
+
+    pub fn on_message(&mut self, message: &RelayMessage) -> Result<AuthStep, NostrError> {
+        match message {
+            RelayMessage::Auth { challenge } => {
+                let event =
+                    EventBuilder::auth(challenge, self.relay_url.clone()).to_event(&self.keys)?;
+                self.pending = Some(event.id);
+                Ok(AuthStep::Send(ClientMessage::auth(event)))
+            }
+            RelayMessage::Ok {
+                event_id, status, ..
+            } if self.pending == Some(*event_id) => {
+                self.pending = None;
+                if *status {
+                    Ok(AuthStep::Authenticated)
+                } else {
+                    tracing::warn!(relay = self.relay_url.to_string(), "Relay rejected AUTH.");
+                    Ok(AuthStep::Ignore)
+                }
+            }
+            _ => Ok(AuthStep::Ignore),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostr::{Event, Filter, Kind, SubscriptionId, Tag};
+
+    const RELAY: &str = "wss://relay.example.com";
+    const CHALLENGE: &str = "challenge-7f3a";
+
+    /// A relay that refuses subscriptions until a client authenticates with NIP-42.
+    struct MockRelay {
+        authenticated: Option<nostr::PublicKey>,
+    }
+
+    impl MockRelay {
+        fn challenge(&self) -> RelayMessage {
+            RelayMessage::Auth {
+                challenge: CHALLENGE.to_string(),
+            }
+        }
+
+        fn handle(&mut self, message: ClientMessage) -> RelayMessage {
+            match message {
+                ClientMessage::Req {
+                    subscription_id, ..
+                } if self.authenticated.is_none() => RelayMessage::Closed {
+                    subscription_id,
+                    message: "auth-required: subscribe after AUTH".to_string(),
+                },
+                ClientMessage::Req {
+                    subscription_id, ..
+                } => RelayMessage::EndOfStoredEvents(subscription_id),
+                ClientMessage::Auth(event) => {
+                    let accepted = is_valid_auth(&event);
+                    if accepted {
+                        self.authenticated = Some(event.pubkey);
+                    }
+                    RelayMessage::Ok {
+                        event_id: event.id,
+                        status: accepted,
+                        message: String::new(),
+                    }
+                }
+                _ => RelayMessage::Notice {
+                    message: "unsupported".to_string(),
+                },
+            }
+        }
+    }
+
+    fn is_valid_auth(event: &Event) -> bool {
+        let has_tag = |wanted: &Tag| event.tags.iter().any(|tag| tag == wanted);
+        event.verify().is_ok()
+            && event.kind == Kind::Authentication
+            && has_tag(&Tag::Challenge(CHALLENGE.to_string()))
+            && has_tag(&Tag::Relay(nostr::UncheckedUrl::from(RELAY)))
+    }
+
+    fn subscribe() -> ClientMessage {
+        ClientMessage::req(SubscriptionId::new("dlc"), vec![Filter::new()])
+    }
+
+    #[test]
+    fn subscribes_after_authenticating() {
+        let keys = Keys::generate();
+        let mut relay = MockRelay {
+            authenticated: None,
+        };
+        let mut auth =
+            RelayAuthenticator::new(&RelayAuth::HandlerKeys, &keys, Url::parse(RELAY).unwrap())
+                .unwrap();
+
+        assert!(matches!(relay.handle(subscribe()), RelayMessage::Closed { .. }));
+
+        let AuthStep::Send(auth_message) = auth.on_message(&relay.challenge()).unwrap() else {
+            panic!("challenge was not answered");
+        };
+        let ok = relay.handle(auth_message);
+        assert_eq!(auth.on_message(&ok).unwrap(), AuthStep::Authenticated);
+        assert_eq!(relay.authenticated, Some(keys.public_key()));
+
+        assert!(matches!(relay.handle(subscribe()), RelayMessage::EndOfStoredEvents(_)));
+    }
+
+    #[test]
+    fn auth_uses_the_configured_identity() {
+        let handler_keys = Keys::generate();
+        let relay_keys = Keys::generate();
+        let url = Url::parse(RELAY).unwrap();
+        let mut relay = MockRelay {
+            authenticated: None,
+        };
+
+        let mut auth =
+            RelayAuthenticator::new(&RelayAuth::Keys(relay_keys.clone()), &handler_keys, url.clone())
+                .unwrap();
+        let AuthStep::Send(auth_message) = auth.on_message(&relay.challenge()).unwrap() else {
+            panic!("challenge was not answered");
+        };
+        relay.handle(auth_message);
+        assert_eq!(relay.authenticated, Some(relay_keys.public_key()));
+
+        assert!(RelayAuthenticator::new(&RelayAuth::Disabled, &handler_keys, url).is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use lightning::io::Cursor;
 
+use crate::error::NostrError;
 use crate::storage::SledStorageProvider;
 use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation};
 use lightning::util::ser::Readable;
@@ -30,16 +31,16 @@ impl NostrDlcHandler {
     }
 }
 
-fn oracle_announcement_from_str(content: &str) -> anyhow::Result<OracleAnnouncement> {
+pub(crate) fn oracle_announcement_from_str(content: &str) -> Result<OracleAnnouncement, NostrError> {
     let bytes = base64::decode(content)?;
     let mut cursor = Cursor::new(bytes);
-    Ok(OracleAnnouncement::read(&mut cursor)
-        .map_err(|_| anyhow::anyhow!("could not get oracle announcement"))?)
+    OracleAnnouncement::read(&mut cursor)
+        .map_err(|_| NostrError::Decode("could not get oracle announcement".into()))
 }
 
-fn oracle_attestation_from_str(content: &str) -> anyhow::Result<OracleAttestation> {
+pub(crate) fn oracle_attestation_from_str(content: &str) -> Result<OracleAttestation, NostrError> {
     let bytes = base64::decode(content)?;
     let mut cursor = Cursor::new(bytes);
-    Ok(OracleAttestation::read(&mut cursor)
-        .map_err(|_| anyhow::anyhow!("could not read oracle attestation"))?)
+    OracleAttestation::read(&mut cursor)
+        .map_err(|_| NostrError::Decode("could not read oracle attestation".into()))
 }
@@ -0,0 +1,167 @@
+//! Protection against senders flooding the relay handler with DLC message events.
+use nostr::PublicKey;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Senders tracked before the state of idle senders is dropped.
+const MAX_TRACKED_SENDERS: usize = 10_000;
+
+/// Limits on the DLC message events the relay handler processes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Events accepted from one sender per `window`. Later events in the window are dropped.
+    pub max_events: u32,
+    pub window: Duration,
+    /// Events in a row from one sender that fail to decrypt or decode before it is blocked.
+    pub max_failures: u32,
+    /// How long a sender stays blocked.
+    pub block_for: Duration,
+    /// Messages being handled at once across all senders. Events beyond it are dropped.
+    pub max_in_flight: usize,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max_events: 60,
+            window: Duration::from_secs(60),
+            max_failures: 5,
+            block_for: Duration::from_secs(10 * 60),
+            max_in_flight: 256,
+        }
+    }
+}
+
+/// Whether an event from a sender is processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Verdict {
+    Allow,
+    /// The sender sent more than [RateLimit::max_events] in the window.
+    RateLimited,
+    /// The sender sent too many events that could not be read.
+    Blocked,
+}
+
+#[derive(Debug)]
+struct SenderState {
+    window_start: Instant,
+    events: u32,
+    failures: u32,
+    blocked_until: Option<Instant>,
+}
+
+/// Tracks the events of each sender against a [RateLimit].
+pub(crate) struct SenderLimiter {
+    limit: RateLimit,
+    senders: HashMap<PublicKey, SenderState>,
+}
+
+impl SenderLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            senders: HashMap::new(),
+        }
+    }
+
+    /// Count an event from `sender` received at `now`.
+    pub fn check(&mut self, sender: &PublicKey, now: Instant) -> Verdict {
+        if self.senders.len() >= MAX_TRACKED_SENDERS && !self.senders.contains_key(sender) {
+            self.prune(now);
+        }
+        let window = self.limit.window;
+        let state = self.senders.entry(*sender).or_insert(SenderState {
+            window_start: now,
+            events: 0,
+            failures: 0,
+            blocked_until: None,
+        });
+
+        match state.blocked_until {
+            Some(until) if now < until => return Verdict::Blocked,
+            Some(_) => {
+                state.blocked_until = None;
+                state.failures = 0;
+            }
+            None => (),
+        }
+        if now.duration_since(state.window_start) >= window {
+            state.window_start = now;
+            state.events = 0;
+        }
+        state.events += 1;
+        if state.events > self.limit.max_events {
+            return Verdict::RateLimited;
+        }
+        Verdict::Allow
+    }
+
+    /// Record that an event from `sender` could not be decrypted or decoded.
+    pub fn record_failure(&mut self, sender: &PublicKey, now: Instant) {
+        let Some(state) = self.senders.get_mut(sender) else {
+            return;
+        };
+        state.failures += 1;
+        if state.failures >= self.limit.max_failures {
+            tracing::warn!(sender = sender.to_string(), "Blocking Nostr sender of unreadable events.");
+            state.blocked_until = Some(now + self.limit.block_for);
+        }
+    }
+
+    /// Record that an event from `sender` was read.
+    pub fn record_success(&mut self, sender: &PublicKey) {
+        if let Some(state) = self.senders.get_mut(sender) {
+            state.failures = 0;
+        }
+    }
+
+    /// Drop senders that are neither blocked nor in their current window.
+    fn prune(&mut self, now: Instant) {
+        let window = self.limit.window;
+        self.senders.retain(|_, state| {
+            state.blocked_until.is_some_and(|until| now < until)
+                || now.duration_since(state.window_start) < window
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostr::Keys;
+
+    #[test]
+    fn limiter_engages_on_flood() {
+        let limit = RateLimit {
+            max_events: 10,
+            window: Duration::from_secs(60),
+            max_failures: 3,
+            block_for: Duration::from_secs(600),
+            max_in_flight: 4,
+        };
+        let mut limiter = SenderLimiter::new(limit);
+        let flooder = Keys::generate().public_key();
+        let honest = Keys::generate().public_key();
+        let start = Instant::now();
+
+        let verdicts = (0..100)
+            .map(|_| limiter.check(&flooder, start))
+            .collect::<Vec<_>>();
+        assert!(verdicts[..10].iter().all(|v| *v == Verdict::Allow));
+        assert!(verdicts[10..].iter().all(|v| *v == Verdict::RateLimited));
+        assert_eq!(limiter.check(&honest, start), Verdict::Allow);
+
+        // The next window accepts the sender again.
+        let next_window = start + Duration::from_secs(60);
+        assert_eq!(limiter.check(&flooder, next_window), Verdict::Allow);
+
+        // Unreadable events block the sender until the block expires.
+        for _ in 0..3 {
+            limiter.record_failure(&flooder, next_window);
+        }
+        assert_eq!(limiter.check(&flooder, next_window), Verdict::Blocked);
+        assert_eq!(limiter.check(&honest, next_window), Verdict::Allow);
+        let unblocked = next_window + Duration::from_secs(600);
+        assert_eq!(limiter.check(&flooder, unblocked), Verdict::Allow);
+    }
+}
@@ -1,7 +1,75 @@
+pub mod auth;
 pub mod dlc_handler;
+pub mod limiter;
 pub mod relay_handler;
+pub mod stats;
+#[cfg(test)]
+mod test_vectors;
+pub mod worker;
 
+pub use auth::RelayAuth;
 pub use dlc_handler::NostrDlcHandler;
+pub use limiter::RateLimit;
 pub use nostr;
-pub use nostr_relay_pool::RelayPoolNotification;
+pub use nostr_relay_pool::{RelayPoolNotification, RelayStatus};
+pub use relay_handler::{NostrDlcRelayHandler, NostrIdentity, NostrPeer, RelayConnection};
+pub use stats::{MessageSizes, MessageStats};
 pub use nostr_sdk;
+
+use crate::transport::MessageReceipt;
+use crate::DdkTransport;
+use async_trait::async_trait;
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::PublicKey;
+use dlc_messages::{Message, OfferDlc};
+
+#[async_trait]
+impl DdkTransport for NostrDlcRelayHandler {
+    type PeerManager = ();
+    type MessageHandler = ();
+
+    fn name(&self) -> String {
+        "nostr".into()
+    }
+
+    /// Received DLC messages are queued until the manager processes them.
+    async fn listen(&self) {
+        if let Err(e) = self.listen_with_workers(self.workers, self.queue_handler()).await {
+            tracing::error!(error=?e, "Stopped listening on nostr relays.");
+        }
+    }
+
+    fn message_handler(&self) -> Self::MessageHandler {}
+
+    fn peer_manager(&self) -> Self::PeerManager {}
+
+    /// Messages are published as they are sent, so there is nothing to flush.
+    fn process_messages(&self) {}
+
+    async fn send_message(&self, counterparty: PublicKey, message: Message) -> anyhow::Result<MessageReceipt> {
+        Ok(self.send_dlc_message(&counterparty, message).await?)
+    }
+
+    fn get_and_clear_received_messages(&self) -> Vec<(PublicKey, Message)> {
+        self.take_received()
+    }
+
+    fn has_pending_messages(&self) -> bool {
+        self.has_received()
+    }
+
+    /// Counterparties are reached through the relays, so there is no connection to open.
+    async fn connect_outbound(&self, pubkey: PublicKey, _host: &str) {
+        tracing::debug!(counterparty = pubkey.to_string(), "Nostr counterparties need no connection.");
+    }
+
+    fn counterparty_online(&self, counterparty: &PublicKey) -> Option<bool> {
+        Some(NostrDlcRelayHandler::counterparty_online(self, counterparty))
+    }
+
+    /// The signature from the [relay_handler::OFFER_SIGNATURE_TAG] of the event the offer
+    /// arrived in.
+    fn offer_signature(&self, counterparty: &PublicKey, offer: &OfferDlc) -> Option<Signature> {
+        self.take_offer_signature(counterparty, &offer.temporary_contract_id)
+    }
+}
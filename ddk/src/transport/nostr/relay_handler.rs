@@ -1,54 +1,332 @@
 use crate::config::SeedConfig;
-use crate::{io, RELAY_HOST};
+use crate::error::NostrError;
+use crate::io;
+use crate::transport::{MessageReceipt, TransportIdentity};
+use bitcoin::secp256k1::Parity;
 use bitcoin::Network;
-use dlc_messages::{message_handler::read_dlc_message, Message, WireMessage};
-use lightning::{
-    ln::wire::Type,
-    util::ser::{Readable, Writeable},
-};
+use crate::util::{decode_dlc_message, dlc_message_bytes, message_contract_id};
+use dlc_messages::{Message, WireMessage};
 use nostr::{
     nips::nip04::{decrypt, encrypt},
     secp256k1::Secp256k1,
-    Event, EventBuilder, EventId, Filter, Keys, Kind, PublicKey, SecretKey, Tag, Timestamp, Url,
+    ClientMessage, Event, EventBuilder, EventId, Filter, Keys, Kind, PublicKey, SecretKey,
+    SubscriptionId, Tag, TagKind, Timestamp, Url,
 };
+use nostr_relay_pool::{Relay, RelayPoolNotification, RelaySendOptions, RelayStatus};
 use nostr_sdk::Client;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, OwnedSemaphorePermit, Semaphore};
+
+use super::auth::{AuthStep, RelayAuth, RelayAuthenticator};
+use super::limiter::{RateLimit, SenderLimiter, Verdict};
+use super::stats::{message_type_name, MessageStats};
+use super::worker::{KeyedWorkerPool, DEFAULT_NOSTR_WORKERS, DEFAULT_NOSTR_WORKER_CAPACITY};
 
 pub const DLC_MESSAGE_KIND: Kind = Kind::Custom(8_888);
 pub const ORACLE_ANNOUNCMENT_KIND: Kind = Kind::Custom(88);
 pub const ORACLE_ATTESTATION_KIND: Kind = Kind::Custom(89);
+/// Tag with the parity of the sender's node key. Nostr keys are x-only, so the recipient needs
+/// it to recover the full public key of its counterparty.
+pub const PARITY_TAG: &str = "parity";
+/// Tag with the hex signature of an offer by the sender's node key. See
+/// [crate::util::sign_offer].
+pub const OFFER_SIGNATURE_TAG: &str = "offer_sig";
+
+/// Base64 encoding of a DLC message prefixed with its type id. This is the plaintext of
+/// the encrypted content of a DLC message event.
+pub fn encode_dlc_message(msg: &Message) -> String {
+    base64::encode(dlc_message_bytes(msg))
+}
+
+/// A Nostr peer: the x-only key it publishes under and the parity of its node key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NostrPeer {
+    pub public_key: PublicKey,
+    pub parity: Parity,
+}
+
+impl NostrPeer {
+    /// The sender of a DLC message event, with the parity from its [PARITY_TAG] tag. Events
+    /// without the tag are from a key with even parity, as in BIP-340.
+    pub fn from_event(event: &Event) -> Result<Self, NostrError> {
+        let parity = event.tags.iter().find_map(|tag| {
+            let values = tag.as_vec();
+            match values.as_slice() {
+                [kind, parity, ..] if kind == PARITY_TAG => Some(parity.clone()),
+                _ => None,
+            }
+        });
+        let parity = match parity.as_deref() {
+            None | Some("even") => Parity::Even,
+            Some("odd") => Parity::Odd,
+            Some(other) => return Err(NostrError::NodeKey(format!("unknown parity `{other}`"))),
+        };
+        Ok(NostrPeer {
+            public_key: event.pubkey,
+            parity,
+        })
+    }
+
+    /// Value of the [PARITY_TAG] tag of events from this peer.
+    fn parity_tag_value(&self) -> &'static str {
+        match self.parity {
+            Parity::Odd => "odd",
+            Parity::Even => "even",
+        }
+    }
+}
+
+/// Nostr peers are named by an x-only key, which drops the parity of the node key. The
+/// parity travels in the [PARITY_TAG] tag of each event.
+pub struct NostrIdentity;
+
+impl TransportIdentity for NostrIdentity {
+    type PeerId = NostrPeer;
+    type Error = NostrError;
+
+    fn node_id(peer: &NostrPeer) -> Result<bitcoin::secp256k1::PublicKey, NostrError> {
+        let prefix = match peer.parity {
+            Parity::Even => 0x02,
+            Parity::Odd => 0x03,
+        };
+        let mut bytes = vec![prefix];
+        bytes.extend_from_slice(&peer.public_key.to_bytes());
+        bitcoin::secp256k1::PublicKey::from_slice(&bytes).map_err(|e| NostrError::NodeKey(e.to_string()))
+    }
+
+    fn peer_id(node_id: &bitcoin::secp256k1::PublicKey) -> NostrPeer {
+        let (xonly, parity) = node_id.x_only_public_key();
+        NostrPeer {
+            public_key: PublicKey::from_slice(&xonly.serialize()).expect("x-only key of a node key"),
+            parity,
+        }
+    }
+}
+
+/// The node public key of the sender of a DLC message event.
+pub fn sender_node_id(event: &Event) -> Result<bitcoin::secp256k1::PublicKey, NostrError> {
+    NostrIdentity::node_id(&NostrPeer::from_event(event)?)
+}
+
+/// The offer signature an event carries in its [OFFER_SIGNATURE_TAG] tag, if any. Tags that
+/// are not a signature read as none.
+pub fn offer_signature(event: &Event) -> Option<bitcoin::secp256k1::ecdsa::Signature> {
+    event.tags.iter().find_map(|tag| {
+        let values = tag.as_vec();
+        match values.as_slice() {
+            [kind, signature, ..] if kind == OFFER_SIGNATURE_TAG => {
+                let bytes = hex::decode(signature).ok()?;
+                bitcoin::secp256k1::ecdsa::Signature::from_compact(&bytes).ok()
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Parses a relay url, rejecting anything that is not a websocket url.
+pub(crate) fn parse_relay_url(relay_host: &str) -> Result<Url, NostrError> {
+    let relay_url = relay_host
+        .parse::<Url>()
+        .map_err(|_| NostrError::InvalidRelayUrl(relay_host.to_string()))?;
+    match relay_url.scheme() {
+        "ws" | "wss" => Ok(relay_url),
+        _ => Err(NostrError::InvalidRelayUrl(relay_host.to_string())),
+    }
+}
+
+/// Relays saved in a relay list file, one url per line.
+pub(crate) fn read_relay_list(path: &Path) -> Result<Vec<Url>, NostrError> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(parse_relay_url)
+        .collect()
+}
+
+/// Replace the relay list file at `path` with `relays`.
+pub(crate) fn write_relay_list(path: &Path, relays: &[Url]) -> Result<(), NostrError> {
+    let contents = relays.iter().map(|url| format!("{url}\n")).collect::<String>();
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// A relay of the handler and the state of its connection.
+#[derive(Debug, Clone)]
+pub struct RelayConnection {
+    pub url: Url,
+    pub status: RelayStatus,
+}
 
 pub struct NostrDlcRelayHandler {
     pub keys: Keys,
+    /// The relay to listen on when there is no relay list file yet.
     pub relay_url: Url,
     pub client: Client,
+    /// File the relays are saved to when they are added or removed. When it exists, the
+    /// handler listens on the relays in it instead of `relay_url`.
+    pub relays_file: Option<PathBuf>,
+    /// How to answer NIP-42 AUTH challenges from the relay.
+    pub relay_auth: RelayAuth,
+    /// Limits on the DLC message events handled from each sender and overall.
+    pub rate_limit: RateLimit,
+    /// Sizes of the DLC messages sent and received per message type.
+    pub message_stats: Arc<MessageStats>,
+    /// Log the plaintext and encrypted size of each DLC message at debug level.
+    pub log_message_sizes: bool,
+    /// When each sender last published a DLC message event on the relays.
+    last_seen: Arc<Mutex<HashMap<PublicKey, Instant>>>,
+    /// How long a sender counts as online after their last event.
+    pub online_window: Duration,
+    /// Relays that must accept a DLC message before it is delivered.
+    pub delivery_quorum: DeliveryQuorum,
+    /// Workers handling received DLC messages when listening as a transport.
+    pub workers: usize,
+    /// DLC messages received and not yet processed by the manager.
+    received: Arc<Mutex<Vec<(bitcoin::secp256k1::PublicKey, Message)>>>,
+    /// Signatures received offers carried, by sender and temporary contract id.
+    offer_signatures: OfferSignatures,
+}
+
+type OfferSignatures = Arc<Mutex<HashMap<(bitcoin::secp256k1::PublicKey, [u8; 32]), bitcoin::secp256k1::ecdsa::Signature>>>;
+
+/// Default seconds a counterparty counts as online after their last DLC message event.
+pub const DEFAULT_ONLINE_WINDOW_SECS: u64 = 600;
+/// Default seconds to wait for relays to accept a DLC message.
+pub const DEFAULT_DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// How many relays must accept a DLC message for it to count as delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeliveryQuorum {
+    /// Relays that must answer the event with an OK. At least one.
+    pub min_acks: usize,
+    /// How long to wait for the relays to answer.
+    pub timeout: Duration,
+}
+
+impl Default for DeliveryQuorum {
+    fn default() -> Self {
+        Self {
+            min_acks: 1,
+            timeout: Duration::from_secs(DEFAULT_DELIVERY_TIMEOUT_SECS),
+        }
+    }
 }
 
 impl NostrDlcRelayHandler {
+    /// Creates a relay handler with the Nostr identity derived from the wallet seed at the
+    /// NIP-06 path, so the wallet seed restores both the wallet and the Nostr identity.
     pub fn new(
         seed_config: &SeedConfig,
         relay_host: &str,
         network: Network,
-    ) -> anyhow::Result<NostrDlcRelayHandler> {
+    ) -> Result<NostrDlcRelayHandler, NostrError> {
         let secp = Secp256k1::new();
         let seed = io::xprv_from_config(seed_config, network)?;
-        // TODO: Seed to bytes is 78 not 64?
-        let secret_key = SecretKey::from_slice(&seed.encode())?;
+        let derived = io::nostr_secret_key_from_xprv(&seed)?;
+        // The nostr crate depends on a different secp256k1 version.
+        let secret_key = SecretKey::from_slice(&derived.secret_bytes())?;
         let keys = Keys::new_with_ctx(&secp, secret_key.into());
 
-        let relay_url = relay_host.parse()?;
+        Self::with_keys(keys, relay_host)
+    }
+
+    /// Creates a relay handler with an imported, standalone Nostr identity.
+    pub fn with_keys(keys: Keys, relay_host: &str) -> Result<NostrDlcRelayHandler, NostrError> {
+        let relay_url = parse_relay_url(relay_host)?;
         let client = Client::new(&keys);
 
         Ok(NostrDlcRelayHandler {
             keys,
             relay_url,
             client,
+            relays_file: None,
+            relay_auth: RelayAuth::default(),
+            rate_limit: RateLimit::default(),
+            message_stats: Arc::new(MessageStats::default()),
+            log_message_sizes: false,
+            last_seen: Arc::new(Mutex::new(HashMap::new())),
+            online_window: Duration::from_secs(DEFAULT_ONLINE_WINDOW_SECS),
+            delivery_quorum: DeliveryQuorum::default(),
+            workers: DEFAULT_NOSTR_WORKERS,
+            received: Arc::new(Mutex::new(Vec::new())),
+            offer_signatures: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
+    /// Save the relays of the handler to `path` so relays added or removed at runtime are kept
+    /// across restarts.
+    pub fn with_relays_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.relays_file = Some(path.into());
+        self
+    }
+
+    /// Set how the handler authenticates to its relay. Defaults to its own keys.
+    pub fn with_relay_auth(mut self, relay_auth: RelayAuth) -> Self {
+        self.relay_auth = relay_auth;
+        self
+    }
+
+    /// Set the limits on DLC message events. See [RateLimit] for the defaults.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Log the type and sizes of each DLC message sent and received at debug level.
+    pub fn with_message_size_logging(mut self, log_message_sizes: bool) -> Self {
+        self.log_message_sizes = log_message_sizes;
+        self
+    }
+
+    /// Set how many relays must accept a DLC message. Defaults to one within
+    /// [DEFAULT_DELIVERY_TIMEOUT_SECS].
+    pub fn with_delivery_quorum(mut self, delivery_quorum: DeliveryQuorum) -> Self {
+        self.delivery_quorum = delivery_quorum;
+        self
+    }
+
+    /// Set how many workers handle received DLC messages at once. Defaults to
+    /// [DEFAULT_NOSTR_WORKERS].
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    /// Whether `counterparty` published a DLC message event within [Self::online_window].
+    /// Relays do not report who is connected, so a quiet counterparty counts as offline.
+    pub fn counterparty_online(&self, counterparty: &bitcoin::secp256k1::PublicKey) -> bool {
+        let sender = NostrIdentity::peer_id(counterparty).public_key;
+        self.last_seen
+            .lock()
+            .unwrap()
+            .get(&sender)
+            .is_some_and(|seen| seen.elapsed() <= self.online_window)
+    }
+
+    fn record_seen(&self, sender: PublicKey, at: Instant) {
+        self.last_seen.lock().unwrap().insert(sender, at);
+    }
+
     pub fn public_key(&self) -> PublicKey {
         self.keys.public_key()
     }
 
+    /// The full public key of the handler, including the parity its nostr key drops.
+    pub fn node_id(&self) -> Result<bitcoin::secp256k1::PublicKey, NostrError> {
+        let secret_key =
+            bitcoin::secp256k1::SecretKey::from_slice(&self.keys.secret_key()?.secret_bytes())
+                .map_err(|e| NostrError::NodeKey(e.to_string()))?;
+        Ok(secret_key.public_key(&bitcoin::secp256k1::Secp256k1::new()))
+    }
+
     pub fn create_dlc_message_filter(&self, since: Timestamp) -> Filter {
         Filter::new()
             .kind(DLC_MESSAGE_KIND)
@@ -62,35 +340,53 @@ impl NostrDlcRelayHandler {
             .since(since)
     }
 
+    /// Encrypt a DLC message to the node key of `to`. The event is addressed to the x-only
+    /// key of `to` and carries the parity of the sender's key in a [PARITY_TAG] tag. Offers
+    /// are signed by the node key in an [OFFER_SIGNATURE_TAG] tag.
     pub fn create_dlc_msg_event(
         &self,
-        to: PublicKey,
+        to: &bitcoin::secp256k1::PublicKey,
         event_id: Option<EventId>,
         msg: Message,
-    ) -> anyhow::Result<Event> {
-        let mut bytes = msg.type_id().encode();
-        bytes.extend(msg.encode());
-
-        let content = encrypt(
-            &self.keys.secret_key()?.clone(),
-            &to,
-            base64::encode(&bytes),
-        )?;
+    ) -> Result<Event, NostrError> {
+        let to = NostrIdentity::peer_id(to).public_key;
+        let offer_signature = match &msg {
+            Message::Offer(offer) => {
+                let node_key = bitcoin::secp256k1::SecretKey::from_slice(&self.keys.secret_key()?.secret_bytes())
+                    .map_err(|e| NostrError::NodeKey(e.to_string()))?;
+                let signature = crate::util::sign_offer(offer, &node_key);
+                Some(Tag::Generic(
+                    TagKind::Custom(OFFER_SIGNATURE_TAG.to_string()),
+                    vec![hex::encode(signature.serialize_compact())],
+                ))
+            }
+            _ => None,
+        };
+        let plaintext = dlc_message_bytes(&msg);
+        let plaintext_bytes = plaintext.len();
+        let content = encrypt(&self.keys.secret_key()?.clone(), &to, base64::encode(plaintext))?;
+        self.record_message_size(true, message_type_name(&msg), plaintext_bytes, content.len());
 
         let p_tags = Tag::PublicKey {
-            public_key: self.public_key(),
+            public_key: to,
             relay_url: None,
             alias: None,
             uppercase: false,
         };
 
+        let parity = NostrIdentity::peer_id(&self.node_id()?).parity_tag_value();
+        let parity_tag = Tag::Generic(
+            TagKind::Custom(PARITY_TAG.to_string()),
+            vec![parity.to_string()],
+        );
+
         let e_tags = event_id.map(|e| Tag::Event {
             event_id: e,
             relay_url: None,
             marker: None,
         });
 
-        let tags = [Some(p_tags), e_tags]
+        let tags = [Some(p_tags), Some(parity_tag), e_tags, offer_signature]
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
@@ -100,28 +396,113 @@ impl NostrDlcRelayHandler {
         Ok(event)
     }
 
-    pub fn parse_dlc_msg_event(&self, event: &Event) -> anyhow::Result<Message> {
+    /// Publish a DLC message to `to` on every relay. The message is delivered once the
+    /// [DeliveryQuorum] of relays accepted it, and fails with
+    /// [NostrError::DeliveryQuorumNotMet] otherwise. The receipt carries the id of the event.
+    pub async fn send_dlc_message(
+        &self,
+        to: &bitcoin::secp256k1::PublicKey,
+        msg: Message,
+    ) -> Result<MessageReceipt, NostrError> {
+        let event = self.create_dlc_msg_event(to, None, msg)?;
+        let event_id = event.id;
+        let relays = self.client.relays().await.into_iter().collect::<Vec<_>>();
+        let acks = publish_with_quorum(relays, self.delivery_quorum, |relay: Relay| {
+            let event = event.clone();
+            async move {
+                relay
+                    .send_event(event, RelaySendOptions::default())
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+        })
+        .await?;
+        tracing::debug!(event_id = event_id.to_hex(), acks, "Relays accepted DLC message.");
+        Ok(MessageReceipt::Acknowledged(event_id.to_hex()))
+    }
+
+    pub fn parse_dlc_msg_event(&self, event: &Event) -> Result<Message, NostrError> {
         let decrypt = decrypt(
-            &self.keys.secret_key().unwrap(),
+            self.keys.secret_key()?,
             &event.pubkey,
             &event.content,
         )?;
 
         let bytes = base64::decode(decrypt)?;
 
-        let mut cursor = lightning::io::Cursor::new(bytes);
+        match decode_dlc_message(&bytes).map_err(|e| NostrError::Decode(e.to_string()))? {
+            WireMessage::Message(msg) => {
+                let message_type = message_type_name(&msg);
+                self.record_message_size(false, message_type, bytes.len(), event.content.len());
+                Ok(msg)
+            }
+            WireMessage::SegmentStart(_) | WireMessage::SegmentChunk(_) => {
+                Err(NostrError::SegmentedMessage)
+            }
+        }
+    }
 
-        let msg_type: u16 = Readable::read(&mut cursor).unwrap();
+    /// The sender and DLC message of an event. The [OFFER_SIGNATURE_TAG] of an offer is kept
+    /// for [crate::DdkTransport::offer_signature].
+    pub fn read_dlc_msg_event(&self, event: &Event) -> Result<(bitcoin::secp256k1::PublicKey, Message), NostrError> {
+        let message = self.parse_dlc_msg_event(event)?;
+        let sender = sender_node_id(event)?;
+        if let (Message::Offer(offer), Some(signature)) = (&message, offer_signature(event)) {
+            self.offer_signatures
+                .lock()
+                .unwrap()
+                .insert((sender, offer.temporary_contract_id), signature);
+        }
+        Ok((sender, message))
+    }
 
-        let Some(wire) = read_dlc_message(msg_type, &mut cursor).unwrap() else {
-            return Err(anyhow::anyhow!("Couldn't read DLC message."));
-        };
+    /// The signature `counterparty` sent with the offer with `temporary_id`. Each signature is
+    /// handed out once.
+    pub(crate) fn take_offer_signature(
+        &self,
+        counterparty: &bitcoin::secp256k1::PublicKey,
+        temporary_id: &[u8; 32],
+    ) -> Option<bitcoin::secp256k1::ecdsa::Signature> {
+        self.offer_signatures.lock().unwrap().remove(&(*counterparty, *temporary_id))
+    }
 
-        match wire {
-            WireMessage::Message(msg) => Ok(msg),
-            WireMessage::SegmentStart(_) | WireMessage::SegmentChunk(_) => {
-                Err(anyhow::anyhow!("Blah blah, something with a wire"))
-            }
+    /// Queue a received DLC message for the manager.
+    #[cfg(test)]
+    pub(crate) fn queue_received(&self, counterparty: bitcoin::secp256k1::PublicKey, message: Message) {
+        self.received.lock().unwrap().push((counterparty, message));
+    }
+
+    /// Take the DLC messages received since the last call.
+    pub(crate) fn take_received(&self) -> Vec<(bitcoin::secp256k1::PublicKey, Message)> {
+        std::mem::take(&mut *self.received.lock().unwrap())
+    }
+
+    pub(crate) fn has_received(&self) -> bool {
+        !self.received.lock().unwrap().is_empty()
+    }
+
+    /// A handler for [Self::listen_with_workers] that queues each message for the manager.
+    pub(crate) fn queue_handler(
+        &self,
+    ) -> impl Fn(bitcoin::secp256k1::PublicKey, Message) -> std::future::Ready<()> + Clone + Send + Sync + 'static {
+        let received = self.received.clone();
+        move |counterparty, message| {
+            received.lock().unwrap().push((counterparty, message));
+            std::future::ready(())
+        }
+    }
+
+    /// Count a DLC message in [Self::message_stats]. Only its type and sizes are logged.
+    fn record_message_size(&self, sent: bool, message_type: String, plaintext_bytes: usize, encrypted_bytes: usize) {
+        if self.log_message_sizes {
+            let direction = if sent { "sent" } else { "received" };
+            tracing::debug!(direction, message_type, plaintext_bytes, encrypted_bytes, "DLC message size.");
+        }
+        if sent {
+            self.message_stats.record_sent(message_type, plaintext_bytes, encrypted_bytes);
+        } else {
+            self.message_stats.record_received(message_type, plaintext_bytes, encrypted_bytes);
         }
     }
 
@@ -134,22 +515,513 @@ impl NostrDlcRelayHandler {
         }
     }
 
-    pub async fn listen(&self) -> anyhow::Result<Client> {
-        let client = Client::new(&self.keys);
+    /// Listen for DLC messages and pass them to `handler` with the node key of the sender on a
+    /// [KeyedWorkerPool] of `concurrency` workers. Messages for the same contract are handled
+    /// in order. Events beyond the [RateLimit] of the handler are dropped.
+    pub async fn listen_with_workers<F, Fut>(
+        &self,
+        concurrency: usize,
+        handler: F,
+    ) -> Result<(), NostrError>
+    where
+        F: Fn(bitcoin::secp256k1::PublicKey, Message) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let client = self.listen().await?;
+        let pool = KeyedWorkerPool::new(
+            concurrency,
+            DEFAULT_NOSTR_WORKER_CAPACITY,
+            move |(counterparty, message, permit): (
+                bitcoin::secp256k1::PublicKey,
+                Message,
+                OwnedSemaphorePermit,
+            )| {
+                let handled = handler(counterparty, message);
+                async move {
+                    handled.await;
+                    drop(permit);
+                }
+            },
+        );
+        let mut limiter = SenderLimiter::new(self.rate_limit.clone());
+        let in_flight = Arc::new(Semaphore::new(self.rate_limit.max_in_flight.max(1)));
+
+        let mut notifications = client.notifications();
+        while let Ok(notification) = notifications.recv().await {
+            let RelayPoolNotification::Event { event, .. } = notification else {
+                continue;
+            };
+            let event = Event::clone(&event);
+            if event.kind != DLC_MESSAGE_KIND {
+                self.handle_dlc_msg_event(event);
+                continue;
+            }
+            // Relays verify event signatures, so the author was active when this arrived.
+            self.record_seen(event.pubkey, Instant::now());
+
+            match limiter.check(&event.pubkey, Instant::now()) {
+                Verdict::Allow => (),
+                verdict => {
+                    tracing::debug!(sender = event.pubkey.to_string(), ?verdict, "Dropped DLC message event.");
+                    continue;
+                }
+            }
+
+            let (counterparty, message) = match self.read_dlc_msg_event(&event) {
+                Ok(parsed) => {
+                    limiter.record_success(&event.pubkey);
+                    parsed
+                }
+                Err(e) => {
+                    limiter.record_failure(&event.pubkey, Instant::now());
+                    tracing::warn!(error=?e, "Could not parse DLC message event.");
+                    continue;
+                }
+            };
+
+            let Ok(permit) = in_flight.clone().try_acquire_owned() else {
+                tracing::warn!(sender = event.pubkey.to_string(), "Too many DLC messages in flight, dropped event.");
+                continue;
+            };
+            // Messages without a contract are ordered with the other messages of the counterparty.
+            let key = message_contract_id(&message).unwrap_or(event.pubkey.to_bytes());
+            pool.dispatch(&key, (counterparty, message, permit)).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn listen(&self) -> Result<Client, NostrError> {
+        let client = self.client.clone();
 
         let since = Timestamp::now();
 
-        client.add_relay(RELAY_HOST).await?;
+        for relay_url in self.initial_relays()? {
+            client.add_relay(relay_url.as_str()).await?;
+        }
 
         let msg_subscription = self.create_dlc_message_filter(since);
         let oracle_subscription = self.create_oracle_message_filter(since);
+        let filters = vec![msg_subscription, oracle_subscription];
 
-        client
-            .subscribe(vec![msg_subscription, oracle_subscription], None)
-            .await;
+        // One id for the subscriptions, so sending them again after AUTH replaces them.
+        let subscription = SubscriptionId::generate();
+        client.subscribe_with_id(subscription.clone(), filters.clone(), None).await;
+
+        if !matches!(self.relay_auth, RelayAuth::Disabled) {
+            // Taken before connecting, so a challenge sent on connect is not missed.
+            let notifications = client.notifications();
+            let auth_client = client.clone();
+            let send_auth = move |relay_url: Url, message: ClientMessage| {
+                let client = auth_client.clone();
+                async move { Ok(client.send_msg_to([relay_url], message).await?) }
+            };
+            let subscribe_client = client.clone();
+            let resubscribe = move |relay_url: Url| {
+                let client = subscribe_client.clone();
+                let (subscription, filters) = (subscription.clone(), filters.clone());
+                async move {
+                    Ok(client
+                        .subscribe_with_id_to([relay_url], subscription, filters, None)
+                        .await?)
+                }
+            };
+            tokio::spawn(authenticate(
+                notifications,
+                self.relay_auth.clone(),
+                self.keys.clone(),
+                send_auth,
+                resubscribe,
+            ));
+        }
 
         client.connect().await;
 
         Ok(client)
     }
+
+    /// Add a relay while listening. The relay pool sends its DLC subscriptions to the relay
+    /// once it is connected.
+    pub async fn add_relay(&self, relay_host: &str) -> Result<Url, NostrError> {
+        let relay_url = parse_relay_url(relay_host)?;
+        self.client.add_relay(relay_url.as_str()).await?;
+        self.client.connect_relay(relay_url.as_str()).await?;
+        self.save_relays().await?;
+        tracing::info!(relay = relay_url.to_string(), "Added relay.");
+        Ok(relay_url)
+    }
+
+    /// Close the subscriptions on a relay and disconnect from it.
+    pub async fn remove_relay(&self, relay_host: &str) -> Result<Url, NostrError> {
+        let relay_url = parse_relay_url(relay_host)?;
+        let Ok(relay) = self.client.relay(relay_url.as_str()).await else {
+            return Err(NostrError::UnknownRelay(relay_url.to_string()));
+        };
+        if let Err(e) = relay.unsubscribe_all(RelaySendOptions::default()).await {
+            tracing::warn!(relay = relay_url.to_string(), error=?e, "Could not close subscriptions on relay.");
+        }
+        self.client.remove_relay(relay_url.as_str()).await?;
+        self.save_relays().await?;
+        tracing::info!(relay = relay_url.to_string(), "Removed relay.");
+        Ok(relay_url)
+    }
+
+    /// The relays of the handler and the state of their connection, sorted by url.
+    pub async fn relays(&self) -> Vec<RelayConnection> {
+        let mut relays = Vec::new();
+        for (url, relay) in self.client.relays().await {
+            let status = relay.status().await;
+            relays.push(RelayConnection { url, status });
+        }
+        relays.sort_by(|a, b| a.url.cmp(&b.url));
+        relays
+    }
+
+    fn initial_relays(&self) -> Result<Vec<Url>, NostrError> {
+        match &self.relays_file {
+            Some(path) if path.exists() => read_relay_list(path),
+            _ => Ok(vec![self.relay_url.clone()]),
+        }
+    }
+
+    async fn save_relays(&self) -> Result<(), NostrError> {
+        let Some(path) = &self.relays_file else {
+            return Ok(());
+        };
+        let relays = self
+            .relays()
+            .await
+            .into_iter()
+            .map(|relay| relay.url)
+            .collect::<Vec<_>>();
+        write_relay_list(path, &relays)
+    }
+}
+
+/// Publish to each relay with `publish` until [DeliveryQuorum::min_acks] relays accepted or
+/// the timeout passes. Returns the relays that accepted. Relays that have not answered when
+/// the quorum is met are left to finish publishing.
+pub(crate) async fn publish_with_quorum<R, F, Fut>(
+    relays: Vec<(Url, R)>,
+    quorum: DeliveryQuorum,
+    publish: F,
+) -> Result<usize, NostrError>
+where
+    F: Fn(R) -> Fut,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    let needed = quorum.min_acks.max(1);
+    let mut publishing = tokio::task::JoinSet::new();
+    for (url, relay) in relays {
+        let published = publish(relay);
+        publishing.spawn(async move { (url, published.await) });
+    }
+
+    let deadline = tokio::time::Instant::now() + quorum.timeout;
+    let mut acks = 0;
+    while acks < needed {
+        match tokio::time::timeout_at(deadline, publishing.join_next()).await {
+            Ok(Some(Ok((_, Ok(()))))) => acks += 1,
+            Ok(Some(Ok((url, Err(e))))) => {
+                tracing::warn!(relay = url.as_str(), error = e.as_str(), "Relay did not accept DLC message.")
+            }
+            Ok(Some(Err(e))) => tracing::warn!(error=?e, "Could not publish to relay."),
+            // Every relay answered or the timeout passed.
+            Ok(None) | Err(_) => break,
+        }
+    }
+    publishing.detach_all();
+
+    if acks < needed {
+        return Err(NostrError::DeliveryQuorumNotMet { acks, quorum: needed });
+    }
+    Ok(acks)
+}
+
+/// Answer AUTH challenges from the relays and send the subscriptions again to each relay that
+/// accepts the AUTH event, since relays that require AUTH refuse them before. Relays added
+/// while listening are authenticated too. `notifications` must be taken before the relays
+/// connect.
+async fn authenticate<S, SFut, R, RFut>(
+    mut notifications: broadcast::Receiver<RelayPoolNotification>,
+    relay_auth: RelayAuth,
+    keys: Keys,
+    send_auth: S,
+    resubscribe: R,
+) where
+    S: Fn(Url, ClientMessage) -> SFut,
+    SFut: Future<Output = Result<(), NostrError>>,
+    R: Fn(Url) -> RFut,
+    RFut: Future<Output = Result<(), NostrError>>,
+{
+    let mut authenticators = HashMap::new();
+    loop {
+        let notification = match notifications.recv().await {
+            Ok(notification) => notification,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, "Missed relay notifications while authenticating.");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        let RelayPoolNotification::Message { relay_url, message } = notification else {
+            continue;
+        };
+        let auth = match authenticators.entry(relay_url.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                match RelayAuthenticator::new(&relay_auth, &keys, relay_url.clone()) {
+                    Some(auth) => entry.insert(auth),
+                    None => return,
+                }
+            }
+        };
+        match auth.on_message(&message) {
+            Ok(AuthStep::Send(auth_message)) => {
+                if let Err(e) = send_auth(relay_url, auth_message).await {
+                    tracing::warn!(error=?e, "Could not send AUTH to relay.");
+                }
+            }
+            Ok(AuthStep::Authenticated) => {
+                tracing::info!(relay = relay_url.to_string(), "Authenticated to relay.");
+                if let Err(e) = resubscribe(relay_url).await {
+                    tracing::warn!(error=?e, "Could not subscribe to relay after AUTH.");
+                }
+            }
+            Ok(AuthStep::Ignore) => (),
+            Err(e) => tracing::warn!(error=?e, "Could not sign AUTH event."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::offered_contract;
+    use nostr::RelayMessage;
+    use crate::RELAY_HOST;
+    use dlc_messages::OfferDlc;
+
+    #[test]
+    fn recent_senders_are_online() {
+        let mut handler = NostrDlcRelayHandler::with_keys(Keys::generate(), RELAY_HOST).unwrap();
+        let counterparty = NostrDlcRelayHandler::with_keys(Keys::generate(), RELAY_HOST).unwrap();
+        let node_id = counterparty.node_id().unwrap();
+        assert!(!handler.counterparty_online(&node_id));
+
+        handler.record_seen(counterparty.public_key(), Instant::now());
+        assert!(handler.counterparty_online(&node_id));
+
+        handler.online_window = Duration::ZERO;
+        handler.record_seen(counterparty.public_key(), Instant::now() - Duration::from_secs(1));
+        assert!(!handler.counterparty_online(&node_id));
+    }
+
+    #[test]
+    fn sent_messages_are_counted_by_type() {
+        let sender = NostrDlcRelayHandler::with_keys(Keys::generate(), RELAY_HOST).unwrap();
+        let recipient = NostrDlcRelayHandler::with_keys(Keys::generate(), RELAY_HOST).unwrap();
+        let offer: OfferDlc = (&offered_contract()).into();
+        let plaintext_bytes = dlc_message_bytes(&Message::Offer(offer.clone())).len() as u64;
+
+        for _ in 0..2 {
+            let event = sender
+                .create_dlc_msg_event(&recipient.node_id().unwrap(), None, Message::Offer(offer.clone()))
+                .unwrap();
+            recipient.parse_dlc_msg_event(&event).unwrap();
+            let signature = offer_signature(&event).unwrap();
+            assert!(crate::util::verify_offer_signature(&offer, &signature, &sender.node_id().unwrap()));
+        }
+
+        let sent = sender.message_stats.sent()["offer"];
+        assert_eq!(sent.messages, 2);
+        assert_eq!(sent.plaintext_bytes, 2 * plaintext_bytes);
+        assert!(sent.encrypted_bytes > sent.plaintext_bytes);
+        assert_eq!(recipient.message_stats.received()["offer"], sent);
+        assert!(sender.message_stats.received().is_empty());
+    }
+
+    #[test]
+    fn counterparty_is_the_same_over_nostr_and_lightning() {
+        use crate::transport::lightning::LightningIdentity;
+
+        let recipient = NostrDlcRelayHandler::with_keys(Keys::generate(), RELAY_HOST).unwrap();
+        let offer: OfferDlc = (&offered_contract()).into();
+        let mut parities = Vec::new();
+        for secret in 1u8..=8 {
+            let node_key = bitcoin::secp256k1::SecretKey::from_slice(&[secret; 32]).unwrap();
+            let node_id = node_key.public_key(&bitcoin::secp256k1::Secp256k1::new());
+            let keys = Keys::new(SecretKey::from_slice(&node_key.secret_bytes()).unwrap());
+            let sender = NostrDlcRelayHandler::with_keys(keys, RELAY_HOST).unwrap();
+
+            // The offer arrives over Nostr from the x-only key and over Lightning from the
+            // compressed key. Both name the counterparty the manager keys contracts by.
+            let event = sender
+                .create_dlc_msg_event(&recipient.node_id().unwrap(), None, Message::Offer(offer.clone()))
+                .unwrap();
+            let over_nostr = sender_node_id(&event).unwrap();
+            let over_lightning = LightningIdentity::node_id(&LightningIdentity::peer_id(&node_id)).unwrap();
+            assert_eq!(over_nostr, node_id);
+            assert_eq!(over_nostr, over_lightning);
+
+            let peer = NostrIdentity::peer_id(&node_id);
+            assert_eq!(peer.public_key, event.pubkey);
+            assert_eq!(NostrIdentity::node_id(&peer).unwrap(), node_id);
+            parities.push(peer.parity);
+        }
+        assert!(parities.contains(&Parity::Even) && parities.contains(&Parity::Odd));
+    }
+
+    #[test]
+    fn only_websocket_relays_are_accepted() {
+        assert!(parse_relay_url("ws://localhost:8081").is_ok());
+        assert!(parse_relay_url("wss://relay.damus.io").is_ok());
+
+        for bad in ["http://localhost:8081", "localhost:8081", "ws//typo"] {
+            let err = parse_relay_url(bad).unwrap_err();
+            assert!(matches!(&err, NostrError::InvalidRelayUrl(url) if url == bad));
+            assert!(err.to_string().contains(bad));
+        }
+    }
+
+    #[test]
+    fn relay_list_round_trips() {
+        let dir = std::env::temp_dir().join("ddk-relay-list");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("relays");
+        let relays = vec![
+            Url::parse("wss://relay.damus.io").unwrap(),
+            Url::parse("ws://localhost:8081").unwrap(),
+        ];
+
+        write_relay_list(&path, &relays).unwrap();
+        assert_eq!(read_relay_list(&path).unwrap(), relays);
+
+        std::fs::write(&path, "wss://relay.damus.io\n\nhttp://localhost:8081\n").unwrap();
+        assert!(matches!(read_relay_list(&path), Err(NostrError::InvalidRelayUrl(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn quorum_is_met_when_one_relay_fails() {
+        let relays = ["wss://a.example", "wss://b.example", "wss://c.example"]
+            .map(|relay| (Url::parse(relay).unwrap(), relay))
+            .to_vec();
+        let publish = |relay: &'static str| async move {
+            match relay {
+                "wss://b.example" => Err("blocked: rate-limited".to_string()),
+                _ => Ok(()),
+            }
+        };
+
+        let quorum = DeliveryQuorum {
+            min_acks: 2,
+            timeout: Duration::from_secs(5),
+        };
+        assert_eq!(publish_with_quorum(relays.clone(), quorum, publish).await.unwrap(), 2);
+
+        let all = DeliveryQuorum { min_acks: 3, ..quorum };
+        let error = publish_with_quorum(relays.clone(), all, publish).await.unwrap_err();
+        assert!(matches!(error, NostrError::DeliveryQuorumNotMet { acks: 2, quorum: 3 }));
+
+        // A relay that never answers does not count once the timeout passes.
+        let slow = |relay: &'static str| async move {
+            if relay == "wss://c.example" {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            Ok(())
+        };
+        let quick = DeliveryQuorum {
+            min_acks: 3,
+            timeout: Duration::from_millis(50),
+        };
+        let error = publish_with_quorum(relays, quick, slow).await.unwrap_err();
+        assert!(matches!(error, NostrError::DeliveryQuorumNotMet { acks: 2, quorum: 3 }));
+    }
+
+    #[tokio::test]
+    async fn relays_that_require_auth_are_subscribed_once_after_auth() {
+        let relay_url = Url::parse("wss://auth.example").unwrap();
+        let keys = Keys::generate();
+        let (relay, notifications) = broadcast::channel(16);
+        let from_relay = |message: RelayMessage| RelayPoolNotification::Message {
+            relay_url: relay_url.clone(),
+            message,
+        };
+
+        // The relay refuses the subscriptions and challenges on connect, before the
+        // authenticator runs.
+        let subscription_id = SubscriptionId::new("dlc");
+        relay
+            .send(from_relay(RelayMessage::Closed {
+                subscription_id: subscription_id.clone(),
+                message: "auth-required: subscribe after AUTH".to_string(),
+            }))
+            .unwrap();
+        relay
+            .send(from_relay(RelayMessage::Auth {
+                challenge: "challenge-7f3a".to_string(),
+            }))
+            .unwrap();
+
+        // The mock relay accepts a valid AUTH event of the handler keys.
+        let authenticated = Arc::new(Mutex::new(None));
+        let relay_sender = Arc::new(Mutex::new(Some(relay.clone())));
+        let send_auth = {
+            let (authenticated, relay_sender, relay_url) =
+                (authenticated.clone(), relay_sender.clone(), relay_url.clone());
+            move |url: Url, message: ClientMessage| {
+                assert_eq!(url, relay_url);
+                let ClientMessage::Auth(event) = message else {
+                    panic!("expected an AUTH message");
+                };
+                let accepted = event.verify().is_ok() && event.kind == Kind::Authentication;
+                if accepted {
+                    *authenticated.lock().unwrap() = Some(event.pubkey);
+                }
+                if let Some(relay) = relay_sender.lock().unwrap().as_ref() {
+                    let ok = RelayMessage::Ok {
+                        event_id: event.id,
+                        status: accepted,
+                        message: String::new(),
+                    };
+                    relay
+                        .send(RelayPoolNotification::Message { relay_url: url, message: ok })
+                        .unwrap();
+                }
+                async { Ok(()) }
+            }
+        };
+        let (subscribed, mut subscriptions) = tokio::sync::mpsc::unbounded_channel();
+        let resubscribe = {
+            let authenticated = authenticated.clone();
+            move |url: Url| {
+                assert!(authenticated.lock().unwrap().is_some(), "subscribed before AUTH");
+                subscribed.send(url).unwrap();
+                async { Ok(()) }
+            }
+        };
+
+        let task = tokio::spawn(authenticate(
+            notifications,
+            RelayAuth::HandlerKeys,
+            keys.clone(),
+            send_auth,
+            resubscribe,
+        ));
+        let resubscribed = tokio::time::timeout(Duration::from_secs(5), subscriptions.recv())
+            .await
+            .unwrap();
+        assert_eq!(resubscribed, Some(relay_url.clone()));
+        assert_eq!(*authenticated.lock().unwrap(), Some(keys.public_key()));
+
+        // Stored events after the subscription do not send it again.
+        relay
+            .send(from_relay(RelayMessage::EndOfStoredEvents(subscription_id)))
+            .unwrap();
+        relay_sender.lock().unwrap().take();
+        drop(relay);
+        task.await.unwrap();
+        assert!(subscriptions.try_recv().is_err());
+    }
 }
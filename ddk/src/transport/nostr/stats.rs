@@ -0,0 +1,73 @@
+//! Sizes of the DLC messages sent and received over Nostr, to size relay usage and the
+//! segmentation threshold. Only message types and sizes are kept, never their content.
+use dlc_messages::Message;
+use lightning::ln::wire::Type;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Totals for the messages of one type in one direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessageSizes {
+    pub messages: u64,
+    /// Bytes of the type id and message before encryption.
+    pub plaintext_bytes: u64,
+    /// Bytes of the encrypted content of the events.
+    pub encrypted_bytes: u64,
+}
+
+impl MessageSizes {
+    fn add(&mut self, plaintext_bytes: usize, encrypted_bytes: usize) {
+        self.messages += 1;
+        self.plaintext_bytes += plaintext_bytes as u64;
+        self.encrypted_bytes += encrypted_bytes as u64;
+    }
+}
+
+/// Counters of the DLC message events of a relay handler per message type.
+#[derive(Debug, Default)]
+pub struct MessageStats {
+    sent: Mutex<BTreeMap<String, MessageSizes>>,
+    received: Mutex<BTreeMap<String, MessageSizes>>,
+}
+
+impl MessageStats {
+    pub(crate) fn record_sent(&self, message_type: String, plaintext_bytes: usize, encrypted_bytes: usize) {
+        let mut sent = self.sent.lock().unwrap();
+        sent.entry(message_type).or_default().add(plaintext_bytes, encrypted_bytes);
+    }
+
+    pub(crate) fn record_received(&self, message_type: String, plaintext_bytes: usize, encrypted_bytes: usize) {
+        let mut received = self.received.lock().unwrap();
+        received.entry(message_type).or_default().add(plaintext_bytes, encrypted_bytes);
+    }
+
+    /// Totals of the messages sent, by message type.
+    pub fn sent(&self) -> BTreeMap<String, MessageSizes> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Totals of the messages received, by message type.
+    pub fn received(&self) -> BTreeMap<String, MessageSizes> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+/// Name of the type of a DLC message for [MessageStats]. Types without a name use their
+/// wire type id.
+pub fn message_type_name(message: &Message) -> String {
+    let name = match message {
+        Message::Offer(_) => "offer",
+        Message::Accept(_) => "accept",
+        Message::Sign(_) => "sign",
+        Message::Reject(_) => "reject",
+        Message::OfferChannel(_) => "offer_channel",
+        Message::AcceptChannel(_) => "accept_channel",
+        Message::SettleOffer(_) => "settle_offer",
+        Message::SettleAccept(_) => "settle_accept",
+        Message::RenewOffer(_) => "renew_offer",
+        Message::RenewAccept(_) => "renew_accept",
+        Message::CollaborativeCloseOffer(_) => "collaborative_close_offer",
+        _ => return format!("type_{}", message.type_id()),
+    };
+    name.to_string()
+}
@@ -0,0 +1,268 @@
+//! Fixed vectors for the encoding of DLC messages in nostr events. The encrypted content of
+//! an event is NIP-04 over the base64 encoding of the message type id and message bytes.
+
+use super::relay_handler::{
+    encode_dlc_message, sender_node_id, NostrDlcRelayHandler, DLC_MESSAGE_KIND,
+};
+use crate::test_util::offered_contract;
+use crate::util::decode_dlc_message;
+use crate::RELAY_HOST;
+use dlc_messages::{Message, OfferDlc, WireMessage};
+use crate::util::dlc_message_bytes;
+use nostr::secp256k1::rand::{self, RngCore};
+use nostr::{EventBuilder, Keys, SecretKey, Tag};
+
+/// Secret key of the party sending the vectors.
+const SENDER_SECRET_KEY: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+/// Secret key of the party receiving the vectors.
+const RECIPIENT_SECRET_KEY: &str =
+    "2222222222222222222222222222222222222222222222222222222222222222";
+const SENDER_PUBLIC_KEY: &str = "4f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa";
+const RECIPIENT_PUBLIC_KEY: &str =
+    "466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f27";
+
+/// Type id used as the prefix of offer messages.
+const OFFER_TYPE: u16 = 42778;
+
+/// Segment start with two segments and the data `ddk segment`.
+const SEGMENT_START_PLAINTEXT: &str = "p5QAAgtkZGsgc2VnbWVudA==";
+/// [SEGMENT_START_PLAINTEXT] encrypted from the sender to the recipient with the iv `0..16`.
+const SEGMENT_START_CIPHERTEXT: &str =
+    "ozGSSmeDnsF1T5lg4W4vqW+AF2Op26+riQrKEy7E0tM=?iv=AAECAwQFBgcICQoLDA0ODw==";
+
+/// An enumerated offer of 1 BTC against 1 BTC on a `win`/`lose` event, with no funding
+/// inputs. Oracle signature and keys are fixed test values.
+const OFFER_MESSAGE: &str = "\
+    a71a000000010006226e46111a0b59caaf126043eb5bbf28c34f3a5e332a1fc7b2b73cf188910f1212121212\
+    12121212121212121212121212121212121212121212121212121200000000000bebc20000020377696e0000\
+    00000bebc200046c6f7365000000000000000000fdd824a40505050505050505050505050505050505050505\
+    0505050505050505050505050505050505050505050505050505050505050505050505050505050505050505\
+    3c72addb4fdf09af94f0c94d7fe92a386a7e70cf8a1d85916386bb2535c7b1b1fdd8224000012c0b7cf95324\
+    a07d05398b240174dc0c2be444d96b159aa6c7f7b1e6686809916553f100fdd8060b00020377696e046c6f73\
+    650a64646b2d766563746f72034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871\
+    aa00160014666666666666666666666666666666666666666600000000000000030000000005f5e100000016\
+    0014777777777777777777777777777777777777777700000000000000040000000000000005000000000000\
+    000200000000655d2b80";
+
+/// [OFFER_MESSAGE] in base64, encrypted from the sender to the recipient with the iv
+/// `0x10..0x20`.
+const OFFER_CIPHERTEXT: &str = "\
+    zfkw9OPYjOO7Z+F8S9VxOsZ0vXXXayxxom7nRKGQAaLXv5/VAjazNLyWisLpGBCI0uAQxl6UmhVnFQi9msWiyekf\
+    7o0cEYgZ1uPceLlZzbRY02PRmjfjRX5qIeMkgakQR2trkZMpJfNeU9vXWB82McOndpTJULr/4ETz/DBety8MV/rC\
+    7mdjKvk9rs5sQrHh/tEFtkTdLICzXhB3fZqdl+DOgBC9N2oqGqFRbkGlJz8MzcZCIzp1Y4r4EWKbXmvbYXdQHoIQ\
+    UUyATT2yX1OFa3ScMQSTppWwN/QeJEsKMMtW2aYgG/678c17GcnJWL83nP46C8DOnIK2Hd8MZDfZHplz3qbH11By\
+    0N7fF25thXsTicnqWNOpbKptiQLbm7Obj5sayliayqT53sX88/YB5FrNncsm2BIX7xDv8dQJHby5yJdPLe2lnlcU\
+    zIW89v0djWzfo/ILdOqoqpaIgTYoWlevRr+M2wW0WVd81In1rKH+lm11kV8hrq/gfBBJsns8tylI61GseXIAPG9L\
+    Wn9SKWAh1Qp8hLTTPDX6o4s2016eazeTHOv67FMhxVWIkig+dgr3oLjyOq24AcDBPBEdZSxI3V5y5QMbznXXQE9D\
+    T0lu5uWwm/D+jyTRsBa4NtRXHeOG/XD4nJllLPsiYRgOd5Dm87BD463Uu7xVEowieElFuwF3hHyZHkT+1IRXDdBM\
+    5u+CzZpFPyMNuFdnM/RhBcM95gR64OkNCxsAFmSumiI=?iv=EBESExQVFhcYGRobHB0eHw==";
+
+/// An accept of [OFFER_MESSAGE] for 1 BTC without funding inputs or CET signatures.
+const ACCEPT_MESSAGE: &str = "\
+    a71c0000000112121212121212121212121212121212121212121212121212121212121212120000000005f5\
+    e10002466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f27001600144444444444\
+    4444444444444444444444444444440000000000000001000016001455555555555555555555555555555555\
+    5555555500000000000000020002020202020202020202020202020202020202020202020202020202020202\
+    02020202020202020202020202020202020202020202020202020202020202020200";
+
+/// [ACCEPT_MESSAGE] in base64, encrypted from the recipient to the sender with the iv
+/// `0x20..0x30`.
+const ACCEPT_CIPHERTEXT: &str = "\
+    RJ75hUUGu7LPZEXo2QVdXEFgAIkHASjxFy9XPdLR67sw7GGE7XDHxE8D0jsN/+ZtYXoMLgOIKKrpA3IJPcel9CAF\
+    T2XxGani8eKtPku760A+9eB9S8Z5EcjI3ZfduF1ijjEktJHqCPBJB21ChVUWKAVwOEKHSZIRxxI1PFjcc3VYT+aU\
+    GgqFLBSflBLZv9sXVG2NxouDscwWRT1VrMw/clC1vqeKwFprIy+yLf9DVf7WWcY9CpOgqTdhPOG/nDOMEFlDRTpt\
+    2z8FWSIcjHhAWyjkcXlE0HLEp2wFCD+E3OF7zcRNFu536IrlXHMBJrSgW9nflOmfpmOYkwSGmnYoHz/K0qmtWZC/\
+    cPR+CIjGMTqwZgCDxTXng+H5ja5YECgk?iv=ICEiIyQlJicoKSorLC0uLw==";
+
+/// A sign message without CET or funding signatures.
+const SIGN_MESSAGE: &str = "\
+    a71e000000013434343434343434343434343434343434343434343434343434343434343434000303030303\
+    0303030303030303030303030303030303030303030303030303030303030303030303030303030303030303\
+    03030303030303030303030303030300";
+
+/// [SIGN_MESSAGE] in base64, encrypted from the sender to the recipient with the iv
+/// `0x30..0x40`.
+const SIGN_CIPHERTEXT: &str = "\
+    O9knoBnMabV8twFtrxPWnQ4QLtiXCHwySM7JeSDyi0ywNUsWfgLbg09irA1NkbNP9JK7R0uZt8936ZPotOulDOF8\
+    m0Bh0rj0CXx601DYfid6dkyYUHRBIY0XlWm8yDax2jWdpUgCanhThuShGCkYRq47zpKYsMS7WqN1eZd2XKGBAJus\
+    XLVe3lBNFnq3JVRK?iv=MDEyMzQ1Njc4OTo7PD0+Pw==";
+
+/// The DLC message vectors: sender secret key, recipient secret key, message, iv and
+/// ciphertext.
+const MESSAGE_VECTORS: [(&str, &str, &str, u8, &str); 3] = [
+    (SENDER_SECRET_KEY, RECIPIENT_SECRET_KEY, OFFER_MESSAGE, 0x10, OFFER_CIPHERTEXT),
+    (RECIPIENT_SECRET_KEY, SENDER_SECRET_KEY, ACCEPT_MESSAGE, 0x20, ACCEPT_CIPHERTEXT),
+    (SENDER_SECRET_KEY, RECIPIENT_SECRET_KEY, SIGN_MESSAGE, 0x30, SIGN_CIPHERTEXT),
+];
+
+/// Hands out consecutive bytes from its start value, for the iv of a NIP-04 encryption.
+struct FixedIv(u8);
+
+impl RngCore for FixedIv {
+    fn next_u32(&mut self) -> u32 {
+        rand::rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand::rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            *byte = self.0;
+            self.0 = self.0.wrapping_add(1);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn keys(secret_key: &str) -> Keys {
+    Keys::new(SecretKey::from_slice(&hex::decode(secret_key).unwrap()).unwrap())
+}
+
+fn handler(secret_key: &str) -> NostrDlcRelayHandler {
+    NostrDlcRelayHandler::with_keys(keys(secret_key), RELAY_HOST).unwrap()
+}
+
+#[test]
+fn vector_keys() {
+    assert_eq!(keys(SENDER_SECRET_KEY).public_key().to_string(), SENDER_PUBLIC_KEY);
+    assert_eq!(keys(RECIPIENT_SECRET_KEY).public_key().to_string(), RECIPIENT_PUBLIC_KEY);
+}
+
+#[test]
+fn offer_is_prefixed_with_type_id() {
+    let offer: OfferDlc = (&offered_contract()).into();
+    let encoded = base64::decode(encode_dlc_message(&Message::Offer(offer.clone()))).unwrap();
+    assert_eq!(encoded[..2], OFFER_TYPE.to_be_bytes());
+
+    match decode_dlc_message(&encoded).unwrap() {
+        WireMessage::Message(Message::Offer(decoded)) => assert_eq!(decoded, offer),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn offer_event_round_trip() {
+    let sender = handler(SENDER_SECRET_KEY);
+    let recipient = handler(RECIPIENT_SECRET_KEY);
+    let offer: OfferDlc = (&offered_contract()).into();
+
+    let event = sender
+        .create_dlc_msg_event(&recipient.node_id().unwrap(), None, Message::Offer(offer.clone()))
+        .unwrap();
+
+    match recipient.parse_dlc_msg_event(&event).unwrap() {
+        Message::Offer(decoded) => assert_eq!(decoded, offer),
+        _ => unreachable!(),
+    }
+}
+
+/// Handlers whose node keys have odd parity, so the x-only nostr key alone does not give the
+/// node key.
+fn odd_parity_handlers() -> impl Iterator<Item = NostrDlcRelayHandler> {
+    (1u8..u8::MAX)
+        .map(|byte| handler(&hex::encode([byte; 32])))
+        .filter(|handler| handler.node_id().unwrap().serialize()[0] == 0x03)
+}
+
+#[test]
+fn odd_parity_counterparty_round_trip() {
+    let mut odd = odd_parity_handlers();
+    let sender = odd.next().unwrap();
+    let recipient = odd.next().unwrap();
+    let offer: OfferDlc = (&offered_contract()).into();
+
+    let event = sender
+        .create_dlc_msg_event(&recipient.node_id().unwrap(), None, Message::Offer(offer.clone()))
+        .unwrap();
+    assert!(event.tags.iter().any(|tag| {
+        tag.as_vec() == vec!["p".to_string(), recipient.public_key().to_string()]
+    }));
+
+    match recipient.parse_dlc_msg_event(&event).unwrap() {
+        Message::Offer(decoded) => assert_eq!(decoded, offer),
+        _ => unreachable!(),
+    }
+    assert_eq!(sender_node_id(&event).unwrap(), sender.node_id().unwrap());
+}
+
+#[test]
+fn decode_segment_start_vector() {
+    let bytes = base64::decode(SEGMENT_START_PLAINTEXT).unwrap();
+    match decode_dlc_message(&bytes).unwrap() {
+        WireMessage::SegmentStart(start) => {
+            assert_eq!(start.nb_segments, 2);
+            assert_eq!(start.data, b"ddk segment".to_vec());
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn decrypt_third_party_segment_vector() {
+    let sender = keys(SENDER_SECRET_KEY);
+    let recipient = handler(RECIPIENT_SECRET_KEY);
+    let event = EventBuilder::new(DLC_MESSAGE_KIND, SEGMENT_START_CIPHERTEXT, Vec::<Tag>::new())
+        .to_event(&sender)
+        .unwrap();
+
+    let decrypted = nostr::nips::nip04::decrypt(
+        &recipient.keys.secret_key().unwrap(),
+        &event.pubkey,
+        &event.content,
+    )
+    .unwrap();
+    assert_eq!(decrypted, SEGMENT_START_PLAINTEXT);
+
+    // Segments are decoded but not reassembled over nostr.
+    assert!(recipient.parse_dlc_msg_event(&event).is_err());
+}
+
+#[test]
+fn message_vectors_encrypt_to_their_ciphertext() {
+    for (from, to, message, iv, ciphertext) in MESSAGE_VECTORS {
+        let plaintext = base64::encode(hex::decode(message).unwrap());
+        let encrypted = nostr::nips::nip04::encrypt_with_rng(
+            &mut FixedIv(iv),
+            &keys(from).secret_key().unwrap(),
+            &keys(to).public_key(),
+            &plaintext,
+        )
+        .unwrap();
+        assert_eq!(encrypted, ciphertext);
+    }
+}
+
+#[test]
+fn message_vectors_decrypt_to_their_message() {
+    for (from, to, message, _, ciphertext) in MESSAGE_VECTORS {
+        let event = EventBuilder::new(DLC_MESSAGE_KIND, ciphertext, Vec::<Tag>::new())
+            .to_event(&keys(from))
+            .unwrap();
+        let decoded = handler(to).parse_dlc_msg_event(&event).unwrap();
+        assert_eq!(hex::encode(dlc_message_bytes(&decoded)), message);
+
+        match decoded {
+            Message::Offer(offer) => {
+                assert_eq!(offer.temporary_contract_id, [0x12; 32]);
+                assert_eq!(offer.offer_collateral, 100_000_000);
+                assert_eq!(offer.refund_locktime, 1_700_604_800);
+            }
+            Message::Accept(accept) => {
+                assert_eq!(accept.temporary_contract_id, [0x12; 32]);
+                assert_eq!(accept.accept_collateral, 100_000_000);
+                assert_eq!(accept.funding_pubkey, handler(RECIPIENT_SECRET_KEY).node_id().unwrap());
+            }
+            Message::Sign(sign) => {
+                assert_eq!(sign.contract_id, [0x34; 32]);
+                assert!(sign.cet_adaptor_signatures.ecdsa_adaptor_signatures.is_empty());
+            }
+            _ => unreachable!(),
+        }
+    }
+}
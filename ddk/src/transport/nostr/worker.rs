@@ -0,0 +1,114 @@
+use crate::error::NostrError;
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use tokio::sync::mpsc;
+
+/// Default number of workers processing nostr events.
+pub const DEFAULT_NOSTR_WORKERS: usize = 4;
+/// Default number of events each worker queues before dispatching waits.
+pub const DEFAULT_NOSTR_WORKER_CAPACITY: usize = 64;
+
+/// Bounded pool of workers. Events with the same key always go to the same worker and are
+/// processed in the order they were dispatched, while events with different keys can be
+/// processed in parallel. Dispatching waits when the worker queue is full.
+pub struct KeyedWorkerPool<E> {
+    workers: Vec<mpsc::Sender<E>>,
+}
+
+impl<E: Send + 'static> KeyedWorkerPool<E> {
+    /// Spawns `concurrency` workers on the current tokio runtime.
+    pub fn new<F, Fut>(concurrency: usize, capacity: usize, handler: F) -> Self
+    where
+        F: Fn(E) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let workers = (0..concurrency.max(1))
+            .map(|_| {
+                let (sender, mut receiver) = mpsc::channel::<E>(capacity.max(1));
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    while let Some(event) = receiver.recv().await {
+                        handler(event).await;
+                    }
+                });
+                sender
+            })
+            .collect();
+
+        Self { workers }
+    }
+
+    /// Index of the worker that handles the events of `key`.
+    fn worker_for<K: Hash>(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.workers.len() as u64) as usize
+    }
+
+    /// Queue an event on the worker for `key`.
+    pub async fn dispatch<K: Hash>(&self, key: &K, event: E) -> Result<(), NostrError> {
+        self.workers[self.worker_for(key)]
+            .send(event)
+            .await
+            .map_err(|_| NostrError::WorkerStopped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashSet};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Barrier;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn events_are_ordered_per_key_and_processed_in_parallel() {
+        const WORKERS: usize = 4;
+        const EVENTS_PER_KEY: u32 = 3;
+
+        // The first event of each key waits until every worker holds one, which only
+        // happens if the workers run at the same time.
+        let barrier = Arc::new(Barrier::new(WORKERS));
+        let (completed, mut completions) = mpsc::unbounded_channel();
+        let pool = KeyedWorkerPool::new(WORKERS, EVENTS_PER_KEY as usize, move |(key, seq): (u32, u32)| {
+            let barrier = barrier.clone();
+            let completed = completed.clone();
+            async move {
+                if seq == 0 {
+                    barrier.wait().await;
+                }
+                let _ = completed.send((key, seq));
+            }
+        });
+
+        // One key for each worker.
+        let mut workers = HashSet::new();
+        let keys = (0u32..)
+            .filter(|key| workers.insert(pool.worker_for(key)))
+            .take(WORKERS)
+            .collect::<Vec<_>>();
+
+        for seq in 0..EVENTS_PER_KEY {
+            for key in &keys {
+                pool.dispatch(key, (*key, seq)).await.unwrap();
+            }
+        }
+
+        let mut seqs = BTreeMap::<u32, Vec<u32>>::new();
+        let all = WORKERS * EVENTS_PER_KEY as usize;
+        tokio::time::timeout(Duration::from_secs(10), async {
+            for _ in 0..all {
+                let (key, seq) = completions.recv().await.unwrap();
+                seqs.entry(key).or_default().push(seq);
+            }
+        })
+        .await
+        .expect("workers did not process events in parallel");
+
+        for key in keys {
+            assert_eq!(seqs[&key], (0..EVENTS_PER_KEY).collect::<Vec<_>>());
+        }
+    }
+}
@@ -3,10 +3,25 @@ use dlc_manager::contract::offered_contract::OfferedContract;
 use dlc_manager::contract::ser::Serializable;
 use dlc_manager::contract::signed_contract::SignedContract;
 use dlc_manager::contract::{
-    ClosedContract, Contract, FailedAcceptContract, FailedSignContract, PreClosedContract,
+    ClosedContract, Contract, ContractDescriptor, FailedAcceptContract, FailedSignContract,
+    PreClosedContract,
 };
 use dlc_manager::error::Error;
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use bitcoin::{Address, Network, OutPoint, ScriptBuf, Sequence, Transaction, Txid};
+use dlc::PartyParams;
+use dlc_messages::message_handler::read_dlc_message;
+use dlc_manager::ContractId;
+use dlc_messages::oracle_msgs::OracleAttestation;
+use dlc_messages::{AcceptDlc, Message, OfferDlc, SignDlc, WireMessage};
 use lightning::io::Read;
+use lightning::ln::wire::Type;
+use lightning::util::ser::{Readable, Writeable};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 macro_rules! convertible_enum {
     (enum $name:ident {
@@ -72,6 +87,835 @@ where
     Error::StorageError(e.to_string())
 }
 
+/// Human readable name of the state a contract is in.
+pub fn contract_state(contract: &Contract) -> &'static str {
+    match contract {
+        Contract::Offered(_) => "offered",
+        Contract::Accepted(_) => "accepted",
+        Contract::Signed(_) => "signed",
+        Contract::Confirmed(_) => "confirmed",
+        Contract::PreClosed(_) => "pre-closed",
+        Contract::Closed(_) => "closed",
+        Contract::Refunded(_) => "refunded",
+        Contract::FailedAccept(_) => "failed accept",
+        Contract::FailedSign(_) => "failed sign",
+        Contract::Rejected(_) => "rejected",
+    }
+}
+
+/// Whether a contract reached a state it never leaves: closed, refunded, rejected or failed.
+pub fn is_terminal(contract: &Contract) -> bool {
+    matches!(
+        contract,
+        Contract::Closed(_)
+            | Contract::Refunded(_)
+            | Contract::Rejected(_)
+            | Contract::FailedAccept(_)
+            | Contract::FailedSign(_)
+    )
+}
+
+/// The error of importing over `existing`, unless `overwrite` is set and `existing` is
+/// terminal. See [crate::DdkStorage::import_contract].
+pub(crate) fn import_conflict(existing: &Contract, overwrite: bool) -> Option<crate::error::ContractError> {
+    if overwrite && is_terminal(existing) {
+        return None;
+    }
+    Some(crate::error::ContractError::ContractExists {
+        contract_id: hex::encode(existing.get_id()),
+        state: contract_state(existing).to_string(),
+    })
+}
+
+/// Encode a DLC message prefixed with its type id, as it is sent over the transports.
+pub fn dlc_message_bytes(message: &Message) -> Vec<u8> {
+    let mut bytes = message.type_id().encode();
+    bytes.extend(message.encode());
+    bytes
+}
+
+/// The contract a DLC message is about. Offers and accepts carry the temporary id.
+pub fn message_contract_id(message: &Message) -> Option<ContractId> {
+    match message {
+        Message::Offer(offer) => Some(offer.temporary_contract_id),
+        Message::Accept(accept) => Some(accept.temporary_contract_id),
+        Message::Sign(sign) => Some(sign.contract_id),
+        Message::Reject(reject) => Some(reject.channel_id),
+        _ => None,
+    }
+}
+
+/// Decode a DLC message prefixed with its type id, as it is sent over the transports.
+pub fn decode_dlc_message(bytes: &[u8]) -> anyhow::Result<WireMessage> {
+    let mut cursor = lightning::io::Cursor::new(bytes);
+    let msg_type: u16 = Readable::read(&mut cursor)
+        .map_err(|e| anyhow::anyhow!("Could not read message type. {:?}", e))?;
+
+    read_dlc_message(msg_type, &mut cursor)
+        .map_err(|e| anyhow::anyhow!("Could not read DLC message. {:?}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Unknown DLC message type {}.", msg_type))
+}
+
+/// The DLC messages a contract was negotiated with, in the wire encoding of the DLC
+/// specification. Other rust-dlc based wallets read the same messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandardContract {
+    pub offer: OfferDlc,
+    pub accept: Option<AcceptDlc>,
+    pub sign: Option<SignDlc>,
+}
+
+impl StandardContract {
+    /// Id of the contract. The temporary id until the contract is signed.
+    pub fn contract_id(&self) -> ContractId {
+        self.sign
+            .as_ref()
+            .map_or(self.offer.temporary_contract_id, |sign| sign.contract_id)
+    }
+
+    /// State the messages bring the contract to.
+    pub fn state(&self) -> &'static str {
+        match (&self.accept, &self.sign) {
+            (_, Some(_)) => "signed",
+            (Some(_), None) => "accepted",
+            (None, None) => "offered",
+        }
+    }
+
+    fn messages(&self) -> Vec<Message> {
+        let mut messages = vec![Message::Offer(self.offer.clone())];
+        messages.extend(self.accept.clone().map(Message::Accept));
+        messages.extend(self.sign.clone().map(Message::Sign));
+        messages
+    }
+}
+
+/// Export the offer, accept and sign messages of a contract, one hex encoded message per
+/// line. Each message is prefixed with its type id, as it is sent over the transports. Fails
+/// for closed, rejected and failed contracts, which no longer hold their messages.
+pub fn export_contract_standard(contract: &Contract) -> anyhow::Result<Vec<u8>> {
+    let signed = match contract {
+        Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => Some(s),
+        Contract::PreClosed(p) => Some(&p.signed_contract),
+        _ => None,
+    };
+    let accepted = match contract {
+        Contract::Accepted(a) => Some(a),
+        _ => signed.map(|s| &s.accepted_contract),
+    };
+    let offered = match contract {
+        Contract::Offered(o) => Some(o),
+        _ => accepted.map(|a| &a.offered_contract),
+    }
+    .ok_or_else(|| anyhow::anyhow!("A {} contract cannot be exported.", contract_state(contract)))?;
+
+    let standard = StandardContract {
+        offer: offered.into(),
+        accept: accepted.map(|a| {
+            a.get_accept_contract_msg(a.adaptor_signatures.as_deref().unwrap_or_default())
+        }),
+        sign: signed.map(|s| s.get_sign_dlc(s.adaptor_signatures.clone().unwrap_or_default())),
+    };
+    let lines = standard
+        .messages()
+        .iter()
+        .map(|msg| hex::encode(dlc_message_bytes(msg)))
+        .collect::<Vec<_>>();
+    Ok(lines.join("\n").into_bytes())
+}
+
+/// Read the messages written by [export_contract_standard]. The messages must be an offer,
+/// optionally followed by its accept and then the sign message. Only the offer can be turned
+/// back into a contract, see [crate::DlcDevKit::import_contract_standard].
+pub fn import_contract_standard(bytes: &[u8]) -> anyhow::Result<StandardContract> {
+    let text = std::str::from_utf8(bytes)?;
+    let mut messages = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match decode_dlc_message(&hex::decode(line)?)? {
+            WireMessage::Message(msg) => messages.push(msg),
+            _ => return Err(anyhow::anyhow!("Segmented messages cannot be imported.")),
+        }
+    }
+
+    let mut messages = messages.into_iter();
+    let Some(Message::Offer(offer)) = messages.next() else {
+        return Err(anyhow::anyhow!("The first message must be an offer."));
+    };
+    let mut standard = StandardContract { offer, accept: None, sign: None };
+    for msg in messages {
+        match msg {
+            Message::Accept(accept) if standard.accept.is_none() => {
+                if accept.temporary_contract_id != standard.offer.temporary_contract_id {
+                    return Err(anyhow::anyhow!("The accept is for another offer."));
+                }
+                standard.accept = Some(accept);
+            }
+            Message::Sign(sign) if standard.accept.is_some() && standard.sign.is_none() => {
+                standard.sign = Some(sign);
+            }
+            _ => return Err(anyhow::anyhow!("Messages must be an offer, accept and sign in order.")),
+        }
+    }
+    Ok(standard)
+}
+
+/// The offer message of a contract in the wire encoding of [OfferDlc], without the message
+/// type. Read it back with [decode_offer].
+pub fn encode_offer(contract: &Contract) -> anyhow::Result<Vec<u8>> {
+    let offered = contract_offer(contract).ok_or_else(|| {
+        anyhow::anyhow!("A {} contract has no offer to export.", contract_state(contract))
+    })?;
+    Ok(OfferDlc::from(offered).encode())
+}
+
+/// Read an offer message written by [encode_offer].
+pub fn decode_offer(bytes: &[u8]) -> anyhow::Result<OfferDlc> {
+    let mut cursor = lightning::io::Cursor::new(bytes);
+    let offer = OfferDlc::read(&mut cursor).map_err(|e| anyhow::anyhow!("Could not read offer. {:?}", e))?;
+    if cursor.position() as usize != bytes.len() {
+        return Err(anyhow::anyhow!("Unexpected bytes after the offer."));
+    }
+    Ok(offer)
+}
+
+/// Hashed in front of the offer in [sign_offer], so an offer signature is never valid for
+/// anything else the node key signs.
+const OFFER_SIGNATURE_TAG: &[u8] = b"DDK/offer-signature";
+
+fn offer_digest(offer: &OfferDlc) -> bitcoin::secp256k1::Message {
+    let mut engine = sha256::Hash::engine();
+    engine.input(OFFER_SIGNATURE_TAG);
+    engine.input(&offer.encode());
+    bitcoin::secp256k1::Message::from_digest(sha256::Hash::from_engine(engine).to_byte_array())
+}
+
+/// Sign `offer` with the node key, for a counterparty to check that the offer comes from the
+/// node it claims to. Transports that support it send the signature along with the offer.
+pub fn sign_offer(offer: &OfferDlc, node_key: &SecretKey) -> Signature {
+    Secp256k1::signing_only().sign_ecdsa(&offer_digest(offer), node_key)
+}
+
+/// Whether `signature` is the signature of `offer` by `node_id`. See [sign_offer].
+pub fn verify_offer_signature(offer: &OfferDlc, signature: &Signature, node_id: &PublicKey) -> bool {
+    Secp256k1::verification_only()
+        .verify_ecdsa(&offer_digest(offer), signature, node_id)
+        .is_ok()
+}
+
+/// Id that the contract of `offer` is stored and listed under until it is accepted. The
+/// offering party knows it as soon as the offer message is built.
+pub fn preview_offer_id(offer: &OfferDlc) -> ContractId {
+    offer.temporary_contract_id
+}
+
+/// Outpoint of the funding output, if the contract has a funding transaction.
+pub fn funding_outpoint(contract: &Contract) -> Option<OutPoint> {
+    match contract {
+        Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => {
+            Some(s.accepted_contract.dlc_transactions.get_fund_outpoint())
+        }
+        Contract::PreClosed(p) => Some(
+            p.signed_contract
+                .accepted_contract
+                .dlc_transactions
+                .get_fund_outpoint(),
+        ),
+        _ => None,
+    }
+}
+
+/// The funding output of a contract, derived from the fund pubkeys of both parties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FundingOutputInfo {
+    pub outpoint: OutPoint,
+    /// The 2-of-2 multisig script of the fund pubkeys.
+    pub witness_script: ScriptBuf,
+    /// P2WSH address of the witness script.
+    pub address: Address,
+    pub value: u64,
+}
+
+/// Rebuilds the funding output of `contract` from the fund pubkeys of both parties and checks
+/// it against the output of `funding_transaction` it is funded by, as found on chain. Fails
+/// with [crate::error::ContractError::FundingMismatch] if they differ.
+pub fn verify_funding_output(
+    contract: &Contract,
+    funding_transaction: &Transaction,
+    network: Network,
+) -> anyhow::Result<FundingOutputInfo> {
+    let signed = match contract {
+        Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => s,
+        Contract::PreClosed(p) => &p.signed_contract,
+        _ => return Err(anyhow::anyhow!("Contract does not have a funding transaction.")),
+    };
+    let mismatch = |reason: String| -> anyhow::Error { crate::error::ContractError::FundingMismatch(reason).into() };
+    let accepted = &signed.accepted_contract;
+    let offer_pubkey = &accepted.offered_contract.offer_params.fund_pubkey;
+    let accept_pubkey = &accepted.accept_params.fund_pubkey;
+    let witness_script = dlc::make_funding_redeemscript(offer_pubkey, accept_pubkey);
+    if witness_script != accepted.dlc_transactions.funding_script_pubkey {
+        return Err(mismatch("the stored funding script is not the multisig of the fund pubkeys".to_string()));
+    }
+
+    let outpoint = accepted.dlc_transactions.get_fund_outpoint();
+    if funding_transaction.compute_txid() != outpoint.txid {
+        return Err(mismatch(format!("transaction {} is not the funding transaction {}", funding_transaction.compute_txid(), outpoint.txid)));
+    }
+    let output = funding_transaction
+        .output
+        .get(outpoint.vout as usize)
+        .ok_or_else(|| mismatch(format!("the funding transaction has no output {}", outpoint.vout)))?;
+    let expected_script_pubkey = ScriptBuf::new_p2wsh(&witness_script.wscript_hash());
+    if output.script_pubkey != expected_script_pubkey {
+        return Err(mismatch(format!("output {} does not pay to the multisig of the fund pubkeys", outpoint)));
+    }
+    let expected_value = accepted.dlc_transactions.get_fund_output().value;
+    if output.value != expected_value {
+        return Err(mismatch(format!("output {} holds {} instead of {}", outpoint, output.value, expected_value)));
+    }
+
+    Ok(FundingOutputInfo {
+        outpoint,
+        address: Address::p2wsh(&witness_script, network),
+        witness_script,
+        value: output.value.to_sat(),
+    })
+}
+
+/// Checks the refund transaction of `accepted` against the offer: it spends only the funding
+/// output, locks until the offer's `refund_locktime`, which falls after the maturity of every
+/// oracle event, and pays each party back its collateral and no one else. The refund fee comes out of the closing fee reserved
+/// in the funding output. Fails with [crate::error::ContractError::RefundMismatch] otherwise.
+pub fn verify_refund_transaction(accepted: &AcceptedContract) -> anyhow::Result<()> {
+    let mismatch = |reason: String| -> anyhow::Error { crate::error::ContractError::RefundMismatch(reason).into() };
+    let offered = &accepted.offered_contract;
+    let refund = &accepted.dlc_transactions.refund;
+
+    let refund_locktime = LockTime::from_consensus(offered.refund_locktime);
+    if refund.lock_time != refund_locktime {
+        return Err(mismatch(format!("it locks until {} instead of {}", refund.lock_time, refund_locktime)));
+    }
+    // The locktime is ignored when every input has a final sequence.
+    if refund.input.iter().all(|input| input.sequence == Sequence::MAX) {
+        return Err(mismatch("its inputs do not enable the locktime".to_string()));
+    }
+    let fund_outpoint = accepted.dlc_transactions.get_fund_outpoint();
+    if refund.input.len() != 1 || refund.input[0].previous_output != fund_outpoint {
+        return Err(mismatch(format!("it does not spend only the funding output {}", fund_outpoint)));
+    }
+    let payout_scripts = [&offered.offer_params.payout_script_pubkey, &accepted.accept_params.payout_script_pubkey];
+    if let Some(output) = refund.output.iter().find(|output| !payout_scripts.contains(&&output.script_pubkey)) {
+        return Err(mismatch(format!("it pays {} to a script of neither party", output.value)));
+    }
+    let latest_maturity = offered
+        .contract_info
+        .iter()
+        .flat_map(|info| info.oracle_announcements.iter())
+        .map(|announcement| announcement.oracle_event.event_maturity_epoch)
+        .max();
+    if let (LockTime::Seconds(_), Some(maturity)) = (refund_locktime, latest_maturity) {
+        if offered.refund_locktime <= maturity {
+            return Err(mismatch(format!(
+                "the refund locktime {} is not after the oracle maturity {}",
+                offered.refund_locktime, maturity
+            )));
+        }
+    }
+
+    for (party, params) in [("offer", &offered.offer_params), ("accept", &accepted.accept_params)] {
+        // Outputs below dust are left out of the refund transaction.
+        if params.collateral < crate::wallet::FUNDING_DUST_LIMIT {
+            continue;
+        }
+        let paid: u64 = refund
+            .output
+            .iter()
+            .filter(|output| output.script_pubkey == params.payout_script_pubkey)
+            .map(|output| output.value.to_sat())
+            .sum();
+        if paid != params.collateral {
+            return Err(mismatch(format!(
+                "it pays the {} party {} sats instead of its collateral of {} sats",
+                party, paid, params.collateral
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Ids of the oracle events a contract settles on. Empty for closed contracts, which do not
+/// keep their announcements.
+pub fn oracle_event_ids(contract: &Contract) -> Vec<String> {
+    let Some(offered) = contract_offer(contract) else {
+        return Vec::new();
+    };
+    let mut event_ids = offered
+        .contract_info
+        .iter()
+        .flat_map(|info| info.oracle_announcements.iter())
+        .map(|announcement| announcement.oracle_event.event_id.clone())
+        .collect::<Vec<_>>();
+    event_ids.sort();
+    event_ids.dedup();
+    event_ids
+}
+
+/// The offer a contract was negotiated from. Closed contracts do not keep it.
+fn contract_offer(contract: &Contract) -> Option<&OfferedContract> {
+    match contract {
+        Contract::Offered(o) | Contract::Rejected(o) => Some(o),
+        Contract::Accepted(a) => Some(&a.offered_contract),
+        Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => {
+            Some(&s.accepted_contract.offered_contract)
+        }
+        Contract::PreClosed(p) => Some(&p.signed_contract.accepted_contract.offered_contract),
+        Contract::FailedAccept(f) => Some(&f.offered_contract),
+        Contract::FailedSign(f) => Some(&f.accepted_contract.offered_contract),
+        Contract::Closed(_) => None,
+    }
+}
+
+/// Numerical oracle outcomes from `start` to `end`, inclusive, every `step`. Parsed from
+/// `start:end:step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutcomeRange {
+    pub start: u64,
+    pub end: u64,
+    pub step: u64,
+}
+
+impl std::str::FromStr for OutcomeRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split(':')
+            .map(|part| part.trim().parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow::anyhow!("Outcome range `{s}` is not min:max:step."))?;
+        let [start, end, step] = parts[..] else {
+            return Err(anyhow::anyhow!("Outcome range `{s}` is not min:max:step."));
+        };
+        if step == 0 || start > end {
+            return Err(anyhow::anyhow!("Outcome range `{s}` is empty."));
+        }
+        Ok(OutcomeRange { start, end, step })
+    }
+}
+
+/// What the local party is paid at oracle outcomes of a contract, as `(outcome, payout)`.
+///
+/// Enumerated contracts list each outcome and `range` is ignored. Numerical contracts are
+/// evaluated with the rounding of the contract, at the outcomes of `range`, or by default at
+/// the first outcome of each run of outcomes with the same rounded payout.
+pub fn simulate_payouts(
+    contract: &Contract,
+    range: Option<OutcomeRange>,
+) -> anyhow::Result<Vec<(String, u64)>> {
+    let offered = contract_offer(contract)
+        .ok_or_else(|| anyhow::anyhow!("Closed contracts do not keep their payouts."))?;
+    let info = offered
+        .contract_info
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("Contract has no contract info."))?;
+    descriptor_payouts(
+        &info.contract_descriptor,
+        offered.total_collateral,
+        offered.is_offer_party,
+        range,
+    )
+}
+
+fn descriptor_payouts(
+    descriptor: &ContractDescriptor,
+    total_collateral: u64,
+    is_offer_party: bool,
+    range: Option<OutcomeRange>,
+) -> anyhow::Result<Vec<(String, u64)>> {
+    let own = |payout: &dlc::Payout| if is_offer_party { payout.offer } else { payout.accept };
+    let numerical = match descriptor {
+        ContractDescriptor::Enum(descriptor) => {
+            return Ok(descriptor
+                .outcome_payouts
+                .iter()
+                .map(|outcome| (outcome.outcome.clone(), own(&outcome.payout)))
+                .collect())
+        }
+        ContractDescriptor::Numerical(numerical) => numerical,
+    };
+
+    let info = &numerical.oracle_numeric_infos;
+    let nb_digits = info.nb_digits.iter().min().copied().unwrap_or_default();
+    let max_outcome = (info.base as u64)
+        .checked_pow(nb_digits as u32)
+        .map(|outcomes| outcomes - 1)
+        .unwrap_or(u64::MAX);
+    let ranges = numerical
+        .payout_function
+        .to_range_payouts(total_collateral, &numerical.rounding_intervals)
+        .map_err(|e| anyhow::anyhow!("Could not evaluate the payout function. {:?}", e))?;
+
+    let Some(range) = range else {
+        return Ok(ranges
+            .iter()
+            .map(|r| (r.start.to_string(), own(&r.payout)))
+            .collect());
+    };
+    if range.end > max_outcome {
+        return Err(anyhow::anyhow!(
+            "Outcome {} is above the largest oracle outcome {}.",
+            range.end,
+            max_outcome
+        ));
+    }
+    (range.start..=range.end)
+        .step_by(range.step as usize)
+        .map(|outcome| {
+            let index = ranges.partition_point(|r| ((r.start + r.count) as u64) <= outcome);
+            let payout = ranges
+                .get(index)
+                .ok_or_else(|| anyhow::anyhow!("No payout for outcome {outcome}."))?;
+            Ok((outcome.to_string(), own(&payout.payout)))
+        })
+        .collect()
+}
+
+/// A CET of a contract and the oracle outcomes it settles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CetInfo {
+    pub txid: Txid,
+    /// First outcome settled by the CET. The outcome itself for enum contracts.
+    pub first_outcome: String,
+    /// Last outcome settled by the CET, inclusive.
+    pub last_outcome: String,
+    pub offer_payout: u64,
+    pub accept_payout: u64,
+}
+
+/// The CETs of a signed contract in the order of its contract infos and outcomes, which is
+/// the order rust-dlc builds them in. Fails for contracts that were not signed, as they have
+/// no CETs to settle with.
+pub fn cet_list(contract: &Contract) -> anyhow::Result<Vec<CetInfo>> {
+    let accepted = match contract {
+        Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => &s.accepted_contract,
+        Contract::PreClosed(p) => &p.signed_contract.accepted_contract,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "A {} contract has no signed CETs.",
+                contract_state(contract)
+            ))
+        }
+    };
+    let offered = &accepted.offered_contract;
+
+    let mut buckets = Vec::new();
+    for info in &offered.contract_info {
+        match &info.contract_descriptor {
+            ContractDescriptor::Enum(descriptor) => {
+                buckets.extend(descriptor.outcome_payouts.iter().map(|outcome| {
+                    (outcome.outcome.clone(), outcome.outcome.clone(), outcome.payout.clone())
+                }))
+            }
+            ContractDescriptor::Numerical(numerical) => {
+                let ranges = numerical
+                    .payout_function
+                    .to_range_payouts(offered.total_collateral, &numerical.rounding_intervals)
+                    .map_err(|e| anyhow::anyhow!("Could not evaluate the payout function. {:?}", e))?;
+                buckets.extend(ranges.into_iter().map(|range| {
+                    let last = range.start + range.count - 1;
+                    (range.start.to_string(), last.to_string(), range.payout)
+                }))
+            }
+        }
+    }
+
+    let cets = &accepted.dlc_transactions.cets;
+    if buckets.len() != cets.len() {
+        return Err(anyhow::anyhow!(
+            "Contract has {} CETs for {} payouts.",
+            cets.len(),
+            buckets.len()
+        ));
+    }
+    Ok(cets
+        .iter()
+        .zip(buckets)
+        .map(|(cet, (first_outcome, last_outcome, payout))| CetInfo {
+            txid: cet.compute_txid(),
+            first_outcome,
+            last_outcome,
+            offer_payout: payout.offer,
+            accept_payout: payout.accept,
+        })
+        .collect())
+}
+
+/// The on-chain transactions of a contract and what they are: `funding`, `cet`, or `refund`.
+/// Empty if the contract has no on-chain footprint yet.
+pub fn contract_transactions(contract: &Contract) -> Vec<(&'static str, Txid)> {
+    let mut txids = Vec::new();
+    if let Some(outpoint) = funding_outpoint(contract) {
+        txids.push(("funding", outpoint.txid));
+    }
+
+    match contract {
+        Contract::PreClosed(p) => txids.push(("cet", p.signed_cet.compute_txid())),
+        Contract::Closed(c) => {
+            if let Some(cet) = &c.signed_cet {
+                txids.push(("cet", cet.compute_txid()))
+            }
+        }
+        Contract::Refunded(s) => txids.push((
+            "refund",
+            s.accepted_contract.dlc_transactions.refund.compute_txid(),
+        )),
+        _ => (),
+    }
+
+    txids
+}
+
+/// Summary of a contract for listings of many contracts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractSummary {
+    pub contract_id: String,
+    /// See [contract_state].
+    pub state: String,
+    pub counter_party: String,
+    /// `outgoing` for contracts we offered and `incoming` for contracts we were offered.
+    /// Closed contracts no longer record it.
+    pub direction: Option<String>,
+    /// Collateral of both parties. Closed contracts no longer record it.
+    pub total_collateral: Option<u64>,
+}
+
+/// `outgoing` for a contract we offered, `incoming` for one offered to us. `None` for closed
+/// contracts, which do not record who offered.
+pub fn contract_direction(contract: &Contract) -> Option<&'static str> {
+    contract_offer(contract).map(|o| if o.is_offer_party { "outgoing" } else { "incoming" })
+}
+
+impl From<&Contract> for ContractSummary {
+    fn from(contract: &Contract) -> Self {
+        Self {
+            contract_id: hex::encode(contract.get_id()),
+            state: contract_state(contract).to_string(),
+            counter_party: contract.get_counter_party_id().to_string(),
+            direction: contract_direction(contract).map(str::to_string),
+            total_collateral: contract_offer(contract).map(|o| o.total_collateral),
+        }
+    }
+}
+
+/// Summaries of the contracts in `state` and `direction`, ordered by contract id. A filter
+/// that is `None` matches every contract.
+pub fn contract_summaries(
+    contracts: &[Contract],
+    state: Option<&str>,
+    direction: Option<&str>,
+) -> Vec<ContractSummary> {
+    let mut summaries = contracts
+        .iter()
+        .filter(|c| state.map_or(true, |state| contract_state(c) == state))
+        .filter(|c| direction.map_or(true, |direction| contract_direction(c) == Some(direction)))
+        .map(ContractSummary::from)
+        .collect::<Vec<_>>();
+    summaries.sort_by(|a, b| a.contract_id.cmp(&b.contract_id));
+    summaries
+}
+
+/// What this party has at stake in one contract, in sats.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractRisk {
+    pub contract_id: String,
+    /// Collateral this party put into the contract.
+    pub collateral: u64,
+    /// Collateral lost at the outcome that pays this party least.
+    pub max_loss: u64,
+    /// Payout at the outcome the oracle reported, if there is a reading for the contract.
+    pub mark_to_market: Option<u64>,
+}
+
+/// Collateral at risk across contracts, in sats.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub total_collateral: u64,
+    pub total_max_loss: u64,
+    /// Sum of the payouts of the contracts that have an oracle reading.
+    pub mark_to_market: u64,
+    pub contracts: Vec<ContractRisk>,
+}
+
+/// The outcome of an attestation as [simulate_payouts] names outcomes of the contract: the
+/// enum outcome, or the attested digits as a number. `None` for digits the contract cannot
+/// read.
+pub fn attested_outcome(contract: &Contract, attestation: &OracleAttestation) -> Option<String> {
+    let info = contract_offer(contract)?.contract_info.first()?;
+    match &info.contract_descriptor {
+        ContractDescriptor::Enum(_) => attestation.outcomes.first().cloned(),
+        ContractDescriptor::Numerical(numerical) => {
+            let base = numerical.oracle_numeric_infos.base as u64;
+            attestation
+                .outcomes
+                .iter()
+                .try_fold(0u64, |value, digit| {
+                    value.checked_mul(base)?.checked_add(digit.parse::<u64>().ok()?)
+                })
+                .map(|value| value.to_string())
+        }
+    }
+}
+
+/// This party's payout at `outcome`, named as by [simulate_payouts].
+fn payout_at(contract: &Contract, outcome: &str) -> Option<u64> {
+    let info = contract_offer(contract)?.contract_info.first()?;
+    let range = match info.contract_descriptor {
+        ContractDescriptor::Enum(_) => None,
+        ContractDescriptor::Numerical(_) => {
+            let outcome = outcome.parse::<u64>().ok()?;
+            Some(OutcomeRange { start: outcome, end: outcome, step: 1 })
+        }
+    };
+    let payouts = simulate_payouts(contract, range).ok()?;
+    match range {
+        Some(_) => payouts.first().map(|(_, payout)| *payout),
+        None => payouts.into_iter().find(|(o, _)| o == outcome).map(|(_, payout)| payout),
+    }
+}
+
+/// Collateral, worst case loss and, with `reading`, the payout of a signed or confirmed
+/// contract. `reading` maps oracle event ids to their outcome, named as by [simulate_payouts].
+/// `None` for contracts in other states, which have no collateral locked.
+pub fn contract_risk(contract: &Contract, reading: &HashMap<String, String>) -> anyhow::Result<Option<ContractRisk>> {
+    let (Contract::Signed(signed) | Contract::Confirmed(signed)) = contract else {
+        return Ok(None);
+    };
+    let offered = &signed.accepted_contract.offered_contract;
+    let collateral = if offered.is_offer_party {
+        offered.offer_params.collateral
+    } else {
+        offered.total_collateral - offered.offer_params.collateral
+    };
+    let worst_payout = simulate_payouts(contract, None)?
+        .into_iter()
+        .map(|(_, payout)| payout)
+        .min()
+        .unwrap_or_default();
+    let mark_to_market = oracle_event_ids(contract)
+        .iter()
+        .find_map(|event_id| reading.get(event_id))
+        .and_then(|outcome| payout_at(contract, outcome));
+    Ok(Some(ContractRisk {
+        contract_id: hex::encode(contract.get_id()),
+        collateral,
+        max_loss: collateral.saturating_sub(worst_payout),
+        mark_to_market,
+    }))
+}
+
+/// [contract_risk] of each signed and confirmed contract and their sums.
+pub fn portfolio(contracts: &[Contract], reading: &HashMap<String, String>) -> anyhow::Result<Portfolio> {
+    let mut portfolio = Portfolio::default();
+    for contract in contracts {
+        let Some(risk) = contract_risk(contract, reading)? else {
+            continue;
+        };
+        portfolio.total_collateral += risk.collateral;
+        portfolio.total_max_loss += risk.max_loss;
+        portfolio.mark_to_market += risk.mark_to_market.unwrap_or_default();
+        portfolio.contracts.push(risk);
+    }
+    Ok(portfolio)
+}
+
+/// A public key as a NIP-19 `npub`. Nostr identifies counterparties by the x-only part of
+/// their key, so this is how the key appears as the author of Nostr DLC messages.
+pub fn npub(public_key: &bitcoin::secp256k1::PublicKey) -> String {
+    let (xonly, _) = public_key.x_only_public_key();
+    crate::io::nip19_encode("npub", &xonly.serialize())
+}
+
+/// Fees a contract paid on-chain, in sats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractFees {
+    /// Fee of the funding transaction, shared by both parties.
+    pub funding_fee: u64,
+    /// What this party put towards fees: its share of the funding fee and of the closing fee
+    /// reserved in the funding output.
+    pub own_fee: u64,
+    /// Value of the funding output, both collaterals and the reserved closing fee.
+    pub fund_output_value: u64,
+    /// Fee of the CET or refund transaction, once one is broadcast.
+    pub closing_fee: Option<u64>,
+    /// Fee rate in sats/vbyte agreed in the offer. The CETs and refund transaction are signed
+    /// at this rate. Zero for fees recorded before the rate was.
+    #[serde(default)]
+    pub fee_rate_per_vb: u64,
+}
+
+/// Fee rate in sats/vbyte agreed in the offer of a contract. `None` for closed contracts, which
+/// no longer carry the offer; [ContractFees] keeps their rate.
+pub fn contract_fee_rate(contract: &Contract) -> Option<u64> {
+    contract_offer(contract).map(|o| o.fee_rate_per_vb)
+}
+
+/// Fees of a contract's on-chain transactions. `None` before the contract is signed.
+///
+/// Closed contracts no longer carry their funding transaction, so their fees are completed
+/// from the `stored` fees of an earlier state.
+pub fn contract_fees(contract: &Contract, stored: Option<ContractFees>) -> Option<ContractFees> {
+    let (accepted, closing_tx) = match contract {
+        Contract::Signed(s) | Contract::Confirmed(s) => (&s.accepted_contract, None),
+        Contract::Refunded(s) => (
+            &s.accepted_contract,
+            Some(&s.accepted_contract.dlc_transactions.refund),
+        ),
+        Contract::PreClosed(p) => (&p.signed_contract.accepted_contract, Some(&p.signed_cet)),
+        Contract::Closed(c) => {
+            return stored.map(|fees| ContractFees {
+                closing_fee: c
+                    .signed_cet
+                    .as_ref()
+                    .and_then(|cet| fees.fund_output_value.checked_sub(output_value(cet)))
+                    .or(fees.closing_fee),
+                ..fees
+            })
+        }
+        _ => return None,
+    };
+
+    let offered = &accepted.offered_contract;
+    let fund = &accepted.dlc_transactions.fund;
+    let (own, counter) = if offered.is_offer_party {
+        (&offered.offer_params, &accepted.accept_params)
+    } else {
+        (&accepted.accept_params, &offered.offer_params)
+    };
+    let change = |params: &PartyParams| -> u64 {
+        fund.output
+            .iter()
+            .filter(|output| output.script_pubkey == params.change_script_pubkey)
+            .map(|output| output.value.to_sat())
+            .sum()
+    };
+
+    let fund_output_value = accepted.dlc_transactions.get_fund_output().value.to_sat();
+    Some(ContractFees {
+        funding_fee: (own.input_amount + counter.input_amount).saturating_sub(output_value(fund)),
+        own_fee: own
+            .input_amount
+            .saturating_sub(own.collateral)
+            .saturating_sub(change(own)),
+        fund_output_value,
+        closing_fee: closing_tx.and_then(|tx| fund_output_value.checked_sub(output_value(tx))),
+        fee_rate_per_vb: offered.fee_rate_per_vb,
+    })
+}
+
+fn output_value(tx: &Transaction) -> u64 {
+    tx.output.iter().map(|output| output.value.to_sat()).sum()
+}
+
 pub fn serialize_contract(contract: &Contract) -> Result<Vec<u8>, ::lightning::io::Error> {
     let serialized = match contract {
         Contract::Offered(o) | Contract::Rejected(o) => o.serialize(),
@@ -169,3 +1013,355 @@ pub fn deserialize_contract_bytes(buff: &Vec<u8>) -> Result<Contract, Error> {
     Ok(contract)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{offered_contract, signed_contract};
+
+    #[test]
+    fn refund_transaction_matches_the_contract() {
+        let signed = signed_contract();
+        let accepted = signed.accepted_contract.clone();
+        verify_refund_transaction(&accepted).unwrap();
+
+        // Both parties get their collateral back, with the fee taken from the reserve in the
+        // funding output.
+        let refund = &accepted.dlc_transactions.refund;
+        let offered = &accepted.offered_contract;
+        assert_eq!(output_value(refund), offered.total_collateral);
+        let fund_output_value = accepted.dlc_transactions.get_fund_output().value.to_sat();
+        assert!(fund_output_value > output_value(refund));
+
+        // A refund that unlocks earlier than agreed is refused.
+        let mut early = accepted.clone();
+        early.dlc_transactions.refund.lock_time = LockTime::from_consensus(offered.refund_locktime - 1);
+        let error = verify_refund_transaction(&early).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<crate::error::ContractError>(),
+            Some(crate::error::ContractError::RefundMismatch(_))
+        ));
+
+        // So is a locktime agreed before the oracle attests.
+        let mut before_maturity = accepted.clone();
+        let maturity = before_maturity.offered_contract.contract_info[0].oracle_announcements[0]
+            .oracle_event
+            .event_maturity_epoch;
+        before_maturity.offered_contract.refund_locktime = maturity;
+        before_maturity.dlc_transactions.refund.lock_time = LockTime::from_consensus(maturity);
+        assert!(verify_refund_transaction(&before_maturity).is_err());
+
+        // And one that shorts a party.
+        let mut short = accepted.clone();
+        short.dlc_transactions.refund.output[0].value -= bitcoin::Amount::from_sat(1);
+        assert!(verify_refund_transaction(&short).is_err());
+
+        // Or spends something other than the funding output.
+        let mut elsewhere = accepted.clone();
+        elsewhere.dlc_transactions.refund.input[0].previous_output.vout += 1;
+        assert!(verify_refund_transaction(&elsewhere).is_err());
+
+        // Or pays someone else.
+        let mut stranger = accepted;
+        stranger.dlc_transactions.refund.output[0].script_pubkey = ScriptBuf::new();
+        assert!(verify_refund_transaction(&stranger).is_err());
+    }
+
+    #[test]
+    fn funding_output_matches_the_fund_pubkeys() {
+        let signed = signed_contract();
+        let contract = Contract::Signed(signed.clone());
+        let mut funding = signed.accepted_contract.dlc_transactions.fund.clone();
+
+        let info = verify_funding_output(&contract, &funding, Network::Regtest).unwrap();
+        assert_eq!(info.outpoint, signed.accepted_contract.dlc_transactions.get_fund_outpoint());
+        assert_eq!(info.witness_script, signed.accepted_contract.dlc_transactions.funding_script_pubkey);
+        assert_eq!(
+            funding.output[info.outpoint.vout as usize].script_pubkey,
+            info.address.script_pubkey()
+        );
+
+        // Fund pubkeys that do not make the stored funding script are a mismatch.
+        let mut tampered = contract.clone();
+        if let Contract::Signed(s) = &mut tampered {
+            s.accepted_contract.accept_params.fund_pubkey = s.accepted_contract.offered_contract.offer_params.fund_pubkey;
+        }
+        let error = verify_funding_output(&tampered, &funding, Network::Regtest).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<crate::error::ContractError>(),
+            Some(crate::error::ContractError::FundingMismatch(_))
+        ));
+
+        // So is a transaction other than the funding transaction.
+        funding.output.clear();
+        assert!(verify_funding_output(&contract, &funding, Network::Regtest).is_err());
+    }
+
+    #[test]
+    fn decode_offer_message() {
+        let offer: OfferDlc = (&offered_contract()).into();
+        let bytes = dlc_message_bytes(&Message::Offer(offer.clone()));
+
+        match decode_dlc_message(&bytes).unwrap() {
+            WireMessage::Message(Message::Offer(decoded)) => assert_eq!(decoded, offer),
+            _ => unreachable!(),
+        }
+
+        assert!(decode_dlc_message(&bytes[..1]).is_err());
+    }
+
+    #[test]
+    fn cets_pay_out_the_collateral() {
+        let signed = signed_contract();
+        let cets = cet_list(&Contract::Signed(signed.clone())).unwrap();
+        let dlc_transactions = &signed.accepted_contract.dlc_transactions;
+        assert_eq!(cets.len(), dlc_transactions.cets.len());
+        assert_eq!(cets[0].txid, dlc_transactions.cets[0].compute_txid());
+        let total_collateral = signed.accepted_contract.offered_contract.total_collateral;
+        for cet in &cets {
+            assert_eq!(cet.offer_payout + cet.accept_payout, total_collateral);
+        }
+
+        assert!(cet_list(&Contract::Offered(offered_contract())).is_err());
+    }
+
+    #[test]
+    fn contract_transactions_by_state() {
+        let offered = Contract::Offered(offered_contract());
+        assert!(funding_outpoint(&offered).is_none());
+        assert!(contract_transactions(&offered).is_empty());
+
+        let signed = signed_contract();
+        let fund_txid = signed.accepted_contract.dlc_transactions.fund.compute_txid();
+        let refund_txid = signed.accepted_contract.dlc_transactions.refund.compute_txid();
+
+        let txs = contract_transactions(&Contract::Signed(signed.clone()));
+        assert_eq!(txs, vec![("funding", fund_txid)]);
+
+        let txs = contract_transactions(&Contract::Refunded(signed));
+        assert_eq!(txs, vec![("funding", fund_txid), ("refund", refund_txid)]);
+    }
+
+    #[test]
+    fn fees_match_the_contract_transactions() {
+        let signed = signed_contract();
+        let accepted = &signed.accepted_contract;
+        let fund = &accepted.dlc_transactions.fund;
+        let input_value: u64 = accepted
+            .offered_contract
+            .funding_inputs
+            .iter()
+            .chain(accepted.funding_inputs.iter())
+            .map(|input| {
+                let prev_tx: Transaction = bitcoin::consensus::deserialize(&input.prev_tx).unwrap();
+                prev_tx.output[input.prev_tx_vout as usize].value.to_sat()
+            })
+            .sum();
+
+        assert!(contract_fees(&Contract::Offered(offered_contract()), None).is_none());
+        let fees = contract_fees(&Contract::Signed(signed.clone()), None).unwrap();
+        assert_eq!(fees.funding_fee, input_value - output_value(fund));
+        assert!(fees.closing_fee.is_none());
+
+        let cet = accepted.dlc_transactions.cets[0].clone();
+        let preclosed = Contract::PreClosed(PreClosedContract {
+            signed_contract: signed.clone(),
+            attestations: None,
+            signed_cet: cet.clone(),
+        });
+        let fees = contract_fees(&preclosed, None).unwrap();
+        let spent = &fund.output[cet.input[0].previous_output.vout as usize];
+        assert_eq!(fees.closing_fee, Some(spent.value.to_sat() - output_value(&cet)));
+        assert!(fees.own_fee > 0 && fees.own_fee <= fees.funding_fee + fees.closing_fee.unwrap());
+
+        let closed = Contract::Closed(ClosedContract {
+            attestations: None,
+            signed_cet: Some(cet),
+            contract_id: signed.accepted_contract.get_contract_id(),
+            temporary_contract_id: signed.accepted_contract.offered_contract.id,
+            counter_party_id: signed.accepted_contract.offered_contract.counter_party,
+            pnl: 0,
+        });
+        assert!(contract_fees(&closed, None).is_none());
+        assert_eq!(contract_fees(&closed, Some(fees)), Some(fees));
+    }
+
+    #[test]
+    fn npub_ignores_key_parity() {
+        // https://github.com/nostr-protocol/nips/blob/master/19.md
+        let xonly = "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d";
+        let npub_vector = "npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6";
+        for parity in ["02", "03"] {
+            let key: bitcoin::secp256k1::PublicKey = format!("{parity}{xonly}").parse().unwrap();
+            assert_eq!(npub(&key), npub_vector);
+            assert_eq!(key.x_only_public_key().0.to_string(), xonly);
+        }
+    }
+
+    #[test]
+    fn payouts_at_outcomes() {
+        use dlc::{EnumerationPayout, Payout};
+        use dlc_manager::contract::enum_descriptor::EnumDescriptor;
+        use dlc_manager::contract::numerical_descriptor::NumericalDescriptor;
+        use dlc_manager::payout_curve::{
+            PayoutFunction, PayoutFunctionPiece, PayoutPoint, PolynomialPayoutCurvePiece,
+            RoundingInterval, RoundingIntervals,
+        };
+
+        let point = |event_outcome, outcome_payout| PayoutPoint {
+            event_outcome,
+            outcome_payout,
+            extra_precision: 0,
+        };
+        // Four binary digits, paying the offer party ten sats per outcome.
+        let piece = PolynomialPayoutCurvePiece::new(vec![point(0, 0), point(15, 150)]).unwrap();
+        let numerical = ContractDescriptor::Numerical(NumericalDescriptor {
+            payout_function: PayoutFunction::new(vec![
+                PayoutFunctionPiece::PolynomialPayoutCurvePiece(piece),
+            ])
+            .unwrap(),
+            rounding_intervals: RoundingIntervals {
+                intervals: vec![RoundingInterval {
+                    begin_interval: 0,
+                    rounding_mod: 1,
+                }],
+            },
+            difference_params: None,
+            oracle_numeric_infos: dlc_trie::OracleNumericInfo {
+                base: 2,
+                nb_digits: vec![4],
+            },
+        });
+
+        let range = "0:15:5".parse::<OutcomeRange>().unwrap();
+        let offer = descriptor_payouts(&numerical, 150, true, Some(range)).unwrap();
+        let outcomes = offer.iter().map(|(o, p)| (o.as_str(), *p)).collect::<Vec<_>>();
+        assert_eq!(outcomes, vec![("0", 0), ("5", 50), ("10", 100), ("15", 150)]);
+        let accept = descriptor_payouts(&numerical, 150, false, Some(range)).unwrap();
+        assert_eq!(accept.iter().map(|(_, p)| *p).collect::<Vec<_>>(), vec![150, 100, 50, 0]);
+        assert_eq!(descriptor_payouts(&numerical, 150, true, None).unwrap().len(), 16);
+
+        let too_far = "0:16:1".parse::<OutcomeRange>().unwrap();
+        assert!(descriptor_payouts(&numerical, 150, true, Some(too_far)).is_err());
+        assert!("5:0:1".parse::<OutcomeRange>().is_err());
+        assert!("0:10".parse::<OutcomeRange>().is_err());
+
+        let enumerated = ContractDescriptor::Enum(EnumDescriptor {
+            outcome_payouts: vec![
+                EnumerationPayout {
+                    outcome: "yes".to_string(),
+                    payout: Payout { offer: 150, accept: 0 },
+                },
+                EnumerationPayout {
+                    outcome: "no".to_string(),
+                    payout: Payout { offer: 0, accept: 150 },
+                },
+            ],
+        });
+        let payouts = descriptor_payouts(&enumerated, 150, false, None).unwrap();
+        assert_eq!(payouts, vec![("yes".to_string(), 0), ("no".to_string(), 150)]);
+    }
+
+    #[test]
+    fn standard_export_round_trips() {
+        let signed = Contract::Signed(signed_contract());
+        let imported = import_contract_standard(&export_contract_standard(&signed).unwrap()).unwrap();
+        assert_eq!(imported.contract_id(), signed.get_id());
+        assert_eq!(imported.state(), "signed");
+        let Contract::Signed(s) = &signed else { unreachable!() };
+        assert_eq!(imported.offer, OfferDlc::from(&s.accepted_contract.offered_contract));
+
+        let offered = Contract::Offered(offered_contract());
+        let imported = import_contract_standard(&export_contract_standard(&offered).unwrap()).unwrap();
+        assert_eq!(imported.contract_id(), offered.get_id());
+        assert_eq!(imported.state(), "offered");
+
+        let rejected = Contract::Rejected(offered_contract());
+        assert!(export_contract_standard(&rejected).is_err());
+        // The sign message cannot come before the accept.
+        let export = String::from_utf8(export_contract_standard(&signed).unwrap()).unwrap();
+        let lines = export.lines().collect::<Vec<_>>();
+        let reordered = [lines[0], lines[2], lines[1]].join("\n");
+        assert!(import_contract_standard(reordered.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn summaries_are_filtered_and_ordered_by_id() {
+        let offer = |id: u8, is_offer_party: bool| {
+            let mut offer = offered_contract();
+            offer.id = [id; 32];
+            offer.is_offer_party = is_offer_party;
+            Contract::Offered(offer)
+        };
+        let signed = signed_contract();
+        let contracts = vec![
+            offer(9, true),
+            offer(1, false),
+            Contract::Confirmed(signed.clone()),
+            offer(4, true),
+            Contract::Rejected(match offer(2, true) {
+                Contract::Offered(o) => o,
+                _ => unreachable!(),
+            }),
+        ];
+
+        let ids = |summaries: Vec<ContractSummary>| {
+            summaries.into_iter().map(|s| s.contract_id).collect::<Vec<_>>()
+        };
+        let outgoing_offers = contract_summaries(&contracts, Some("offered"), Some("outgoing"));
+        assert_eq!(ids(outgoing_offers), vec![hex::encode([4u8; 32]), hex::encode([9u8; 32])]);
+
+        let incoming = contract_summaries(&contracts, Some("offered"), Some("incoming"));
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].direction.as_deref(), Some("incoming"));
+        assert_eq!(incoming[0].total_collateral, Some(offered_contract().total_collateral));
+
+        let confirmed = contract_summaries(&contracts, Some("confirmed"), None);
+        assert_eq!(ids(confirmed), vec![hex::encode(signed.accepted_contract.get_contract_id())]);
+
+        let mut all = contracts.iter().map(|c| hex::encode(c.get_id())).collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(ids(contract_summaries(&contracts, None, None)), all);
+        assert!(contract_summaries(&contracts, Some("closed"), None).is_empty());
+    }
+
+    #[test]
+    fn portfolio_sums_the_contract_figures() {
+        let signed = signed_contract();
+        let mut accepted = signed.clone();
+        accepted.accepted_contract.offered_contract.is_offer_party =
+            !signed.accepted_contract.offered_contract.is_offer_party;
+        let contracts = vec![
+            Contract::Confirmed(signed.clone()),
+            Contract::Signed(accepted),
+            Contract::Offered(offered_contract()),
+        ];
+
+        // The first outcome of the contract is reported, which has a known payout.
+        let first = Contract::Confirmed(signed);
+        let (outcome, payout) = simulate_payouts(&first, None).unwrap().remove(0);
+        let reading = oracle_event_ids(&first)
+            .into_iter()
+            .map(|event_id| (event_id, outcome.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let portfolio = portfolio(&contracts, &reading).unwrap();
+        assert_eq!(portfolio.contracts.len(), 2);
+        let risks = contracts
+            .iter()
+            .filter_map(|c| contract_risk(c, &reading).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(portfolio.contracts, risks);
+        assert_eq!(portfolio.total_collateral, risks.iter().map(|r| r.collateral).sum::<u64>());
+        assert_eq!(portfolio.total_max_loss, risks.iter().map(|r| r.max_loss).sum::<u64>());
+        assert_eq!(risks[0].mark_to_market, Some(payout));
+        assert_eq!(
+            portfolio.mark_to_market,
+            risks.iter().filter_map(|r| r.mark_to_market).sum::<u64>()
+        );
+
+        // Both sides together put up the whole collateral.
+        let total_collateral = contract_offer(&first).unwrap().total_collateral;
+        assert_eq!(portfolio.total_collateral, total_collateral);
+        assert!(risks.iter().all(|r| r.max_loss <= r.collateral));
+    }
+}
@@ -1,23 +1,30 @@
 use crate::{
     chain::EsploraClient, signer::SignerInformation, storage::SledStorageProvider, DdkStorage,
 };
-use bdk_chain::Balance;
+use bdk_chain::local_chain::CheckPoint;
+use bdk_chain::{Balance, BlockId, ChainPosition, ConfirmationBlockTime, ConfirmationTime, TxUpdate};
+use bdk_esplora::EsploraExt;
 use bdk_wallet::{
     bitcoin::{
         bip32::{DerivationPath, Xpriv},
         secp256k1::{All, PublicKey, Secp256k1},
         Address, Network, Txid,
-    }, template::Bip84, AddressInfo, KeychainKind, LocalOutput, PersistedWallet, SignOptions, Wallet
+    }, error::CreateTxError, template::Bip84, AddressInfo, KeychainKind, LocalOutput, PersistedWallet, SignOptions, Update, Wallet
 };
-use bitcoin::{hashes::{sha256::HashEngine, Hash}, psbt::Psbt, secp256k1::SecretKey, Amount, FeeRate, ScriptBuf, Transaction};
+use bdk_wallet::descriptor::{DescriptorError, ExtendedDescriptor, IntoWalletDescriptor};
+use bdk_wallet::keys::{KeyError, KeyMap};
+use bdk_wallet::miniscript::descriptor::DescriptorType;
+use bitcoin::{hashes::{sha256::HashEngine, Hash}, psbt::Psbt, secp256k1::SecretKey, Amount, FeeRate, OutPoint, ScriptBuf, Transaction};
 use bitcoin::hashes::sha256::Hash as Sha256Hash;
 use crossbeam::channel::{unbounded, Receiver, Sender};
-use dlc_manager::{error::Error as ManagerError, SimpleSigner};
+use dlc_manager::contract::Contract;
+use dlc_manager::{error::Error as ManagerError, SimpleSigner, Storage};
 use lightning::chain::chaininterface::{ConfirmationTarget, FeeEstimator};
-use std::{io::Write, sync::{atomic::Ordering, Arc}};
-use std::{collections::HashMap, path::Path};
-use std::{str::FromStr, sync::atomic::AtomicU32};
+use std::{io::Write, sync::{atomic::Ordering, Arc, Mutex}};
+use std::{collections::{HashMap, HashSet}, path::Path};
+use std::{str::FromStr, sync::atomic::{AtomicU32, AtomicUsize}};
 use crate::error::WalletError;
+use crate::policy::TxPurpose;
 
 /// Internal [bdk::Wallet] for ddk.
 /// Uses eplora blocking for the [ddk::DlcDevKit] being sync only
@@ -26,11 +33,76 @@ pub struct DlcDevKitWallet<S> {
     pub blockchain: Arc<EsploraClient>,
     pub sender: Sender<WalletOperation>,
     pub network: Network,
-    pub xprv: Xpriv,
+    /// Seed the wallet and contract keys are derived from. `None` for a watch-only wallet.
+    pub xprv: Option<Xpriv>,
     pub name: String,
+    /// Confirmations a utxo needs before it is used for funding or sends.
+    pub min_confirmations: u32,
     pub fees: Arc<HashMap<ConfirmationTarget, AtomicU32>>,
     derive_signer: Arc<S>,
     secp: Secp256k1<All>,
+    /// Utxos chosen to fund the contract being offered. `None` lets coin selection choose.
+    funding_utxos: Mutex<Option<Vec<OutPoint>>>,
+    external_descriptor: ExtendedDescriptor,
+    internal_descriptor: ExtendedDescriptor,
+    /// The descriptors were supplied with [WalletDescriptors] instead of derived from the seed.
+    custom_descriptors: bool,
+    /// Addresses contract payouts go to in turn instead of new wallet addresses.
+    payout_whitelist: Vec<Address>,
+    next_payout: AtomicUsize,
+    address_reuse: AddressReusePolicy,
+    /// Last payout and change addresses handed to the dlc manager, under
+    /// [AddressReusePolicy::ReuseUntilFunded].
+    held_payout: Mutex<Option<Address>>,
+    held_change: Mutex<Option<Address>>,
+    node_pubkey: PublicKey,
+}
+
+/// Whether the payout and change addresses of contracts that never funded are used again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AddressReusePolicy {
+    /// Every contract gets new addresses.
+    #[default]
+    NeverReuse,
+    /// The addresses of a contract that was rejected or failed before funding go to the next
+    /// contract, so abandoned offers do not use up address indices.
+    ReuseUntilFunded,
+}
+
+/// Keys of a [DlcDevKitWallet].
+#[derive(Debug, Clone, Copy)]
+pub enum WalletKeys {
+    /// Wallet and contract keys derived from the seed.
+    Seed(Xpriv),
+    /// No private keys. The wallet tracks public descriptors and the contracts of
+    /// `node_pubkey`, and anything that signs fails with [WalletError::WatchOnly].
+    WatchOnly { node_pubkey: PublicKey },
+}
+
+/// Output descriptors for the external and change addresses of the wallet, used instead of
+/// the BIP84 descriptors derived from the seed. The seed still derives the contract keys.
+///
+/// The descriptors must hold the private keys to sign with for the wallet to fund contracts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletDescriptors {
+    pub external: String,
+    pub internal: String,
+}
+
+/// Parses a descriptor and checks that its keys are for `network`.
+fn parse_descriptor(
+    descriptor: &str,
+    secp: &Secp256k1<All>,
+    network: Network,
+) -> Result<(ExtendedDescriptor, KeyMap), WalletError> {
+    descriptor
+        .into_wallet_descriptor(secp, network)
+        .map_err(|e| match e {
+            DescriptorError::Key(KeyError::InvalidNetwork) => {
+                WalletError::DescriptorNetworkMismatch { network }
+            }
+            e => WalletError::InvalidDescriptor(e.to_string()),
+        })
 }
 
 /// Messages that can be sent to the internal wallet.
@@ -43,58 +115,577 @@ pub enum WalletOperation {
     NewExternalAddress(Sender<AddressInfo>),
     // Get a new, unused change address.
     NewChangeAddress(Sender<AddressInfo>),
-    // Send an amount to an address.
-    SendToAddress(Address, Amount, FeeRate, Sender<Result<Txid, WalletError>>),
+    // Send an amount to an address, spending only the given utxos if there are any.
+    SendToAddress(Address, Amount, FeeRate, Vec<OutPoint>, Sender<Result<Txid, WalletError>>),
     // Get all Transactions in the wallet.
     // TODO: Deref from Arc
     GetTransactions(Sender<Vec<Arc<Transaction>>>),
     // Get all UTXO's owned by the wallet.
     ListUtxos(Sender<Vec<LocalOutput>>),
+    // Get the UTXO's that meet the minimum confirmations.
+    ListSpendableUtxos(Sender<Vec<LocalOutput>>),
     // Sign an input.
     SignPsbtInput(Psbt, usize, Sender<Result<(), WalletError>>),
     // Get the next unused derivation path.
     NextDerivationIndex(Sender<u32>),
+    // Full scan of the wallet script pubkeys.
+    Rescan(Option<u32>, Sender<Result<RescanResult, WalletError>>),
+    // Height of the latest block the wallet has synced.
+    TipHeight(Sender<u32>),
+    // Wallet transactions with their place in the chain.
+    TransactionHistory(Sender<Vec<WalletTransaction>>),
+}
+
+/// A wallet transaction and where it is in the chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletTransaction {
+    pub transaction: Arc<Transaction>,
+    /// Height of the confirming block. `None` while unconfirmed.
+    pub height: Option<u32>,
+    /// Time of the confirming block, or when an unconfirmed transaction was last seen.
+    pub time: u64,
+}
+
+/// Orders transactions newest first, unconfirmed before confirmed. With `before_height`, only
+/// transactions confirmed below it are kept. At most `limit` are returned.
+pub fn page_transactions(
+    mut transactions: Vec<WalletTransaction>,
+    limit: Option<usize>,
+    before_height: Option<u32>,
+) -> Vec<WalletTransaction> {
+    if let Some(before) = before_height {
+        transactions.retain(|tx| tx.height.is_some_and(|height| height < before));
+    }
+    transactions.sort_by_key(|tx| std::cmp::Reverse((tx.height.unwrap_or(u32::MAX), tx.time)));
+    transactions.truncate(limit.unwrap_or(usize::MAX));
+    transactions
+}
+
+/// Where a wallet's history starts. Sync and rescans start at the birthday instead of
+/// genesis, so a restored wallet does not scan blocks from before it was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletBirthday {
+    /// The first block height the wallet may have transactions in.
+    Height(u32),
+    /// The time the wallet was created, in seconds since the unix epoch. The first block
+    /// mined at or after it is the birthday. It is resolved to a height once and stored, so a
+    /// later date does not move the birthday of an existing wallet.
+    Date(u64),
+}
+
+/// Outcome of a wallet rescan.
+#[derive(Debug, Clone)]
+pub struct RescanResult {
+    /// Transactions found at or after the rescan height. Unconfirmed transactions are included.
+    pub transactions: usize,
+    /// The wallet balance after the rescan.
+    pub balance: Balance,
 }
 
 const MIN_FEERATE: u32 = 253;
 
+/// Unused script pubkeys to scan past before a keychain is considered done.
+const STOP_GAP: usize = 20;
+const PARALLEL_REQUESTS: usize = 5;
+
+/// Parent path of the keys that sign contract funding inputs.
+const SIGNER_KEY_PATH: &str = "m/84'/0'/0'/0'";
+/// Parent path of the secret keys for contract payouts.
+const SECRET_KEY_PATH: &str = "m/86'/0'/0'/0'";
+/// Path of the key funding transaction serial ids are derived from.
+const SERIAL_ID_KEY_PATH: &str = "m/86'/0'/1'";
+
+/// Confirmations before a coinbase output can be spent.
+const COINBASE_MATURITY: u32 = 100;
+
+/// Number of confirmations at the chain tip. Unconfirmed transactions have zero.
+fn confirmations(confirmation_time: &ConfirmationTime, tip: u32) -> u32 {
+    match confirmation_time {
+        ConfirmationTime::Confirmed { height, .. } => tip.saturating_sub(*height) + 1,
+        ConfirmationTime::Unconfirmed { .. } => 0,
+    }
+}
+
+/// If a utxo has enough confirmations to be spent. Coinbase outputs must also be mature.
+fn is_spendable(
+    confirmation_time: &ConfirmationTime,
+    tip: u32,
+    min_confirmations: u32,
+    is_coinbase: bool,
+) -> bool {
+    let confs = confirmations(confirmation_time, tip);
+    let required = if is_coinbase {
+        min_confirmations.max(COINBASE_MATURITY)
+    } else {
+        min_confirmations
+    };
+    confs >= required
+}
+
+/// Witness length dlc_manager assumes for every funding input when it estimates the funding fee.
+const MANAGER_WITNESS_LEN: usize = 107;
+
+/// Largest witness needed to spend an output with `script_pubkey`, including the item count and
+/// length prefixes. `None` if the output cannot fund a DLC or its witness depends on a script.
+pub fn max_witness_len(script_pubkey: &bitcoin::Script) -> Option<usize> {
+    if script_pubkey.is_p2wpkh() {
+        // A low-R signature with its sighash byte, then a compressed key.
+        Some(1 + (1 + 71) + (1 + 33))
+    } else if script_pubkey.is_p2tr() {
+        // A key path spend signed with the default sighash.
+        Some(1 + (1 + 64))
+    } else if script_pubkey.is_p2sh() {
+        // Only P2SH wrapped P2WPKH is supported, which has the same witness as P2WPKH.
+        Some(MANAGER_WITNESS_LEN)
+    } else {
+        None
+    }
+}
+
+/// A utxo of the wallet as a funding input for dlc_manager, sized by the script type of the
+/// `descriptor` it was derived from. `None` if its witness is larger than dlc_manager assumes,
+/// since the funding fee would be underpaid.
+fn funding_utxo(
+    descriptor: &ExtendedDescriptor,
+    utxo: &LocalOutput,
+    network: Network,
+) -> Option<dlc_manager::Utxo> {
+    let witness_len = match descriptor.desc_type() {
+        DescriptorType::Wpkh | DescriptorType::ShWpkh | DescriptorType::Tr => {
+            max_witness_len(&utxo.txout.script_pubkey)?
+        }
+        _ => return None,
+    };
+    if witness_len > MANAGER_WITNESS_LEN {
+        return None;
+    }
+    // The funding transaction needs the redeem script to spend a P2SH wrapped output.
+    let redeem_script = match descriptor.desc_type() {
+        DescriptorType::ShWpkh => descriptor
+            .at_derivation_index(utxo.derivation_index)
+            .ok()?
+            .explicit_script()
+            .ok()?,
+        _ => ScriptBuf::new(),
+    };
+    Some(dlc_manager::Utxo {
+        tx_out: utxo.txout.clone(),
+        outpoint: utxo.outpoint,
+        address: Address::from_script(&utxo.txout.script_pubkey, network).ok()?,
+        redeem_script,
+        reserved: false,
+    })
+}
+
+/// Weight dlc_manager adds to the funding fee for each funding input.
+const FUNDING_INPUT_WEIGHT: u64 = 164 + MANAGER_WITNESS_LEN as u64;
+/// Weight of the P2WPKH change output of a funding transaction.
+const CHANGE_OUTPUT_WEIGHT: u64 = 36 + 4 * 22;
+
+/// Outpoints funding contracts that are offered, accepted or signed but not confirmed yet.
+/// These coins are committed and must not fund another contract. They are released once the
+/// funding transaction spends them or the contract is rejected or fails.
+pub fn reserved_outpoints(contracts: &[Contract]) -> HashSet<OutPoint> {
+    contracts
+        .iter()
+        .flat_map(|contract| {
+            let (offered, accepted) = match contract {
+                Contract::Offered(o) => (Some(o), None),
+                Contract::Accepted(a) => (Some(&a.offered_contract), Some(a)),
+                Contract::Signed(s) => {
+                    (Some(&s.accepted_contract.offered_contract), Some(&s.accepted_contract))
+                }
+                _ => (None, None),
+            };
+            offered
+                .into_iter()
+                .flat_map(|o| o.funding_inputs.iter())
+                .chain(accepted.into_iter().flat_map(|a| a.funding_inputs.iter()))
+        })
+        .filter_map(|input| {
+            let prev_tx: Transaction = bitcoin::consensus::deserialize(&input.prev_tx).ok()?;
+            Some(OutPoint::new(prev_tx.compute_txid(), input.prev_tx_vout))
+        })
+        .collect()
+}
+
+/// Whether an address of ours can go to another contract under
+/// [AddressReusePolicy::ReuseUntilFunded]: a contract that was rejected or failed before
+/// funding used it and no other contract does. An address no stored contract uses may have
+/// been paid out to by a closed contract, so it is not reused.
+pub fn reusable_address(contracts: &[Contract], script_pubkey: &bitcoin::Script) -> bool {
+    let mut unfunded = false;
+    for contract in contracts {
+        let (offered, accept_params) = match contract {
+            Contract::Offered(o) | Contract::Rejected(o) => (o, None),
+            Contract::FailedAccept(f) => (&f.offered_contract, None),
+            Contract::Accepted(a) => (&a.offered_contract, Some(&a.accept_params)),
+            Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => {
+                (&s.accepted_contract.offered_contract, Some(&s.accepted_contract.accept_params))
+            }
+            Contract::PreClosed(p) => (
+                &p.signed_contract.accepted_contract.offered_contract,
+                Some(&p.signed_contract.accepted_contract.accept_params),
+            ),
+            Contract::FailedSign(f) => (&f.accepted_contract.offered_contract, Some(&f.accepted_contract.accept_params)),
+            Contract::Closed(_) => continue,
+        };
+        let own = if offered.is_offer_party { Some(&offered.offer_params) } else { accept_params };
+        let Some(own) = own else { continue };
+        if own.payout_script_pubkey.as_script() != script_pubkey && own.change_script_pubkey.as_script() != script_pubkey {
+            continue;
+        }
+        match contract {
+            Contract::Rejected(_) | Contract::FailedAccept(_) | Contract::FailedSign(_) => unfunded = true,
+            _ => return false,
+        }
+    }
+    unfunded
+}
+
+/// Largest first selection of the unreserved utxos that cover `amount` and the fee of the
+/// selected inputs and a change output at `fee_rate` sats/vbyte.
+fn select_utxos(
+    mut utxos: Vec<dlc_manager::Utxo>,
+    reserved: &HashSet<OutPoint>,
+    amount: u64,
+    fee_rate: u64,
+) -> Result<Vec<dlc_manager::Utxo>, WalletError> {
+    utxos.retain(|utxo| !reserved.contains(&utxo.outpoint));
+    utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.tx_out.value));
+
+    let (mut selected, mut unselected) = (Vec::new(), Vec::new());
+    for utxo in utxos {
+        if covers(&selected, amount, fee_rate).is_ok() {
+            unselected.push(utxo);
+        } else {
+            selected.push(utxo);
+        }
+    }
+    covers(&selected, amount, fee_rate)?;
+    avoid_dust_change(&mut selected, unselected, amount, fee_rate);
+    Ok(selected)
+}
+
+/// When the change of `selected` is dust and would be paid to the fee, add the smallest of
+/// `unselected` that turns it into a change output for less fee than the dust.
+fn avoid_dust_change(
+    selected: &mut Vec<dlc_manager::Utxo>,
+    mut unselected: Vec<dlc_manager::Utxo>,
+    amount: u64,
+    fee_rate: u64,
+) {
+    let funding = funding_change(selected, amount, fee_rate);
+    if funding.change.is_some() || funding.absorbed == 0 {
+        return;
+    }
+    unselected.sort_by_key(|utxo| utxo.tx_out.value);
+    for utxo in unselected {
+        selected.push(utxo);
+        let topped_up = funding_change(selected, amount, fee_rate);
+        if topped_up.change.is_some() && topped_up.fee < funding.fee {
+            return;
+        }
+        selected.pop();
+    }
+    tracing::info!(
+        absorbed = funding.absorbed,
+        fee = funding.fee,
+        "Funding change is below the dust limit. Adding it to the fee."
+    );
+}
+
+/// The utxos `chosen` to fund a contract. Fails if one of them is not a spendable, unreserved
+/// utxo of the wallet or if together they do not cover `amount` and the fees.
+fn chosen_utxos(
+    utxos: Vec<dlc_manager::Utxo>,
+    chosen: &[OutPoint],
+    reserved: &HashSet<OutPoint>,
+    amount: u64,
+    fee_rate: u64,
+) -> Result<Vec<dlc_manager::Utxo>, WalletError> {
+    let mut selected = utxos
+        .into_iter()
+        .filter(|utxo| chosen.contains(&utxo.outpoint))
+        .collect::<Vec<_>>();
+    if let Some(outpoint) = chosen.iter().find(|outpoint| {
+        reserved.contains(outpoint) || !selected.iter().any(|utxo| utxo.outpoint == **outpoint)
+    }) {
+        return Err(WalletError::UtxoNotSpendable(*outpoint));
+    }
+    covers(&selected, amount, fee_rate)?;
+    // Chosen utxos are spent as given, with dust change going to the fee.
+    avoid_dust_change(&mut selected, Vec::new(), amount, fee_rate);
+    Ok(selected)
+}
+
+/// Start the chain of a new wallet at its birthday block, so sync requests blocks from there
+/// instead of from genesis.
+fn start_at_birthday(wallet: &mut Wallet, birthday: BlockId) -> Result<(), WalletError> {
+    if birthday.height == 0 {
+        return Ok(());
+    }
+    let genesis = BlockId {
+        height: 0,
+        hash: wallet.local_chain().genesis_hash(),
+    };
+    let chain = CheckPoint::new(genesis)
+        .push(birthday)
+        .map_err(|_| WalletError::SyncError)?;
+    wallet.apply_update(Update {
+        chain: Some(chain),
+        ..Default::default()
+    })?;
+    Ok(())
+}
+
+/// Drop the transactions of a scan that only confirmed below `from_height`. Unconfirmed
+/// transactions and those with an anchor at or above the height are kept.
+fn retain_from_height(update: &mut TxUpdate<ConfirmationBlockTime>, from_height: u32) {
+    let kept: HashSet<Txid> = update
+        .anchors
+        .iter()
+        .filter(|(anchor, _)| anchor.block_id.height >= from_height)
+        .map(|(_, txid)| *txid)
+        .collect();
+    let anchored: HashSet<Txid> = update.anchors.iter().map(|(_, txid)| *txid).collect();
+    update
+        .txs
+        .retain(|tx| !anchored.contains(&tx.compute_txid()) || kept.contains(&tx.compute_txid()));
+    update.anchors.retain(|(_, txid)| kept.contains(txid));
+}
+
+/// Height of the block a [WalletBirthday] refers to. Dates are found by a binary search over
+/// the block times reported by esplora. A date after the tip, as for a wallet born now, is the
+/// tip without a search.
+fn birthday_height(blockchain: &EsploraClient, birthday: WalletBirthday) -> Result<u32, WalletError> {
+    let timestamp = match birthday {
+        WalletBirthday::Height(height) => return Ok(height),
+        WalletBirthday::Date(timestamp) => timestamp,
+    };
+    let client = &blockchain.blocking_client;
+    let block_time = |height: u32| -> Result<u64, WalletError> {
+        let hash = client
+            .get_block_hash(height)
+            .map_err(|e| WalletError::Esplora(Box::new(e)))?;
+        let header = client
+            .get_header_by_hash(&hash)
+            .map_err(|e| WalletError::Esplora(Box::new(e)))?;
+        Ok(u64::from(header.time))
+    };
+
+    let tip = client.get_height().map_err(|e| WalletError::Esplora(Box::new(e)))?;
+    if block_time(tip)? <= timestamp {
+        return Ok(tip);
+    }
+    let (mut low, mut high) = (0, tip);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if block_time(mid)? < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+/// Change outputs below this many sats are left out of the funding transaction and paid to
+/// the fee, as rust-dlc builds it and as BDK drains change.
+pub const FUNDING_DUST_LIMIT: u64 = 1_000;
+
+/// Change and fee of funding `amount` with `utxos`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FundingChange {
+    /// Value of the change output. `None` if the change is dust and goes to the fee.
+    change: Option<u64>,
+    fee: u64,
+    /// Change added to the fee instead of paid to a change output.
+    absorbed: u64,
+}
+
+/// What funding `amount` with `utxos`, which [covers] it, leaves as change and fee.
+fn funding_change(utxos: &[dlc_manager::Utxo], amount: u64, fee_rate: u64) -> FundingChange {
+    let fee = |weight: u64| weight.div_ceil(4) * fee_rate;
+    let inputs_fee = utxos.len() as u64 * fee(FUNDING_INPUT_WEIGHT);
+    let available = utxos.iter().map(|utxo| utxo.tx_out.value.to_sat()).sum::<u64>();
+    let remainder = available.saturating_sub(amount + inputs_fee + fee(CHANGE_OUTPUT_WEIGHT));
+    if remainder < FUNDING_DUST_LIMIT {
+        return FundingChange {
+            change: None,
+            fee: available.saturating_sub(amount),
+            absorbed: remainder,
+        };
+    }
+    FundingChange {
+        change: Some(remainder),
+        fee: inputs_fee + fee(CHANGE_OUTPUT_WEIGHT),
+        absorbed: 0,
+    }
+}
+
+/// Checks that `utxos` cover `amount` and the fee of spending them with a change output.
+fn covers(utxos: &[dlc_manager::Utxo], amount: u64, fee_rate: u64) -> Result<(), WalletError> {
+    let fee = |weight: u64| weight.div_ceil(4) * fee_rate;
+    let needed = amount
+        + fee(CHANGE_OUTPUT_WEIGHT)
+        + utxos.len() as u64 * fee(FUNDING_INPUT_WEIGHT);
+    let available = utxos.iter().map(|utxo| utxo.tx_out.value.to_sat()).sum::<u64>();
+    if available < needed {
+        return Err(WalletError::InsufficientFunds {
+            needed: Amount::from_sat(needed),
+            available: Amount::from_sat(available),
+        });
+    }
+    Ok(())
+}
+
+/// Utxos of the wallet that do not meet the minimum confirmations.
+fn unspendable_utxos(
+    wallet: &PersistedWallet<SledStorageProvider>,
+    min_confirmations: u32,
+) -> Vec<LocalOutput> {
+    let tip = wallet.latest_checkpoint().height();
+    wallet
+        .list_unspent()
+        .filter(|utxo| {
+            let is_coinbase = utxo_is_coinbase(wallet, utxo);
+            !is_spendable(&utxo.confirmation_time, tip, min_confirmations, is_coinbase)
+        })
+        .collect()
+}
+
+fn utxo_is_coinbase(wallet: &PersistedWallet<SledStorageProvider>, utxo: &LocalOutput) -> bool {
+    wallet
+        .get_tx(utxo.outpoint.txid)
+        .map(|tx| tx.tx_node.tx.is_coinbase())
+        .unwrap_or(false)
+}
+
+/// `balance` with the confirmed `unspendable` utxos moved from confirmed to pending. Each
+/// utxo comes with whether it is a coinbase output. Immature coinbase outputs are left out,
+/// the wallet already counts them as immature rather than confirmed.
+fn balance_with_threshold(mut balance: Balance, unspendable: &[(LocalOutput, bool)], tip: u32) -> Balance {
+    let below_threshold = unspendable
+        .iter()
+        .filter(|(utxo, is_coinbase)| !is_coinbase && confirmations(&utxo.confirmation_time, tip) > 0)
+        .map(|(utxo, _)| utxo.txout.value)
+        .sum::<Amount>()
+        .min(balance.confirmed);
+    balance.confirmed -= below_threshold;
+    balance.trusted_pending += below_threshold;
+    balance
+}
+
 impl<S: DdkStorage> DlcDevKitWallet<S> {
     pub fn new<P>(
         name: &str,
-        xprv: Xpriv,
+        keys: WalletKeys,
         esplora_url: &str,
         network: Network,
         wallet_storage_path: P,
         derive_signer: Arc<S>,
-    ) -> anyhow::Result<DlcDevKitWallet<S>>
+        min_confirmations: u32,
+        birthday: Option<WalletBirthday>,
+        passphrase: Option<&str>,
+        descriptors: Option<WalletDescriptors>,
+    ) -> Result<DlcDevKitWallet<S>, WalletError>
     where
         P: AsRef<Path>,
     {
         let secp = Secp256k1::new();
         let wallet_storage_path = wallet_storage_path.as_ref().join("wallet-db");
 
-        let external_descriptor = Bip84(xprv, KeychainKind::External);
-        let internal_descriptor = Bip84(xprv, KeychainKind::Internal);
+        let (xprv, node_pubkey) = match keys {
+            WalletKeys::Seed(xprv) => (Some(xprv), PublicKey::from_secret_key(&secp, &xprv.private_key)),
+            WalletKeys::WatchOnly { node_pubkey } => (None, node_pubkey),
+        };
+        let custom_descriptors = descriptors.is_some();
+        let (external_descriptor, internal_descriptor) = match (descriptors, xprv) {
+            (Some(descriptors), _) => (
+                parse_descriptor(&descriptors.external, &secp, network)?,
+                parse_descriptor(&descriptors.internal, &secp, network)?,
+            ),
+            (None, None) => {
+                return Err(WalletError::InvalidDescriptor(
+                    "a watch-only wallet needs public descriptors".to_string(),
+                ))
+            }
+            (None, Some(xprv)) => (
+                Bip84(xprv, KeychainKind::External)
+                    .into_wallet_descriptor(&secp, network)
+                    .map_err(|e| WalletError::InvalidDescriptor(e.to_string()))?,
+                Bip84(xprv, KeychainKind::Internal)
+                    .into_wallet_descriptor(&secp, network)
+                    .map_err(|e| WalletError::InvalidDescriptor(e.to_string()))?,
+            ),
+        };
+        if xprv.is_none() && !(external_descriptor.1.is_empty() && internal_descriptor.1.is_empty()) {
+            return Err(WalletError::InvalidDescriptor(
+                "watch-only descriptors must not hold private keys".to_string(),
+            ));
+        }
+        let public_descriptors = (external_descriptor.0.clone(), internal_descriptor.0.clone());
         // let file_store = bdk_file_store::Store::<ChangeSet>::open_or_create_new(b"ddk-wallet", wallet_storage_path)?;
-        let mut storage = SledStorageProvider::new(wallet_storage_path.to_str().unwrap())?;
+        let wallet_storage_path = wallet_storage_path.to_str().unwrap();
+        let mut storage = match passphrase {
+            Some(passphrase) => SledStorageProvider::new_encrypted(wallet_storage_path, passphrase)?,
+            None => SledStorageProvider::new(wallet_storage_path)?,
+        };
+        if passphrase.is_none() && storage.is_wallet_encrypted()? {
+            return Err(WalletError::PassphraseRequired);
+        }
 
         let load_wallet = Wallet::load()
             .descriptor(KeychainKind::External, Some(external_descriptor.clone()))
             .descriptor(KeychainKind::Internal, Some(internal_descriptor.clone()))
             .extract_keys()
             .check_network(network)
-            .load_wallet(&mut storage)?;
+            .load_wallet(&mut storage)
+            .map_err(|e| WalletError::Load(e.to_string()))?;
+
+        let blockchain = Arc::new(
+            EsploraClient::new(esplora_url, network).map_err(|e| WalletError::Esplora(Box::new(e)))?,
+        );
 
         let mut wallet = match load_wallet {
-            Some(w) => w,
-            None => Wallet::create(external_descriptor, internal_descriptor)
-                .network(network)
-                .create_wallet(&mut storage)?
+            Some(w) => {
+                // A date is resolved to a height once, when the wallet is first given it.
+                match birthday {
+                    Some(WalletBirthday::Height(height)) => storage.save_birth_height(height)?,
+                    Some(date) if storage.get_birth_height()?.is_none() => {
+                        storage.save_birth_height(birthday_height(&blockchain, date)?)?
+                    }
+                    _ => {}
+                }
+                w
+            }
+            None => {
+                let mut wallet = Wallet::create(external_descriptor, internal_descriptor)
+                    .network(network)
+                    .create_wallet(&mut storage)
+                    .map_err(|e| WalletError::Load(e.to_string()))?;
+                // Without a birthday the wallet may be a restore with history anywhere in the
+                // chain, so it starts at genesis.
+                let height = match birthday {
+                    Some(birthday) => birthday_height(&blockchain, birthday)?,
+                    None => 0,
+                };
+                storage.save_birth_height(height)?;
+                if height > 0 {
+                    let hash = blockchain
+                        .blocking_client
+                        .get_block_hash(height)
+                        .map_err(|e| WalletError::Esplora(Box::new(e)))?;
+                    start_at_birthday(&mut wallet, BlockId { height, hash })?;
+                    wallet.persist(&mut storage)?;
+                }
+                wallet
+            }
         };
 
-        let blockchain = Arc::new(EsploraClient::new(esplora_url, network)?);
-
-        // TODO: Actually get fees. I don't think it's used for regular DLCs though
+        // Starting estimates. Each sync replaces those of the funding and settlement targets with
+        // the ones from esplora, see [Self::refresh_fee_estimates].
         let mut fees: HashMap<ConfirmationTarget, AtomicU32> = HashMap::new();
         fees.insert(ConfirmationTarget::UrgentOnChainSweep, AtomicU32::new(5000));
         fees.insert(
@@ -126,7 +717,7 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
         let (sender, receiver) = unbounded::<WalletOperation>();
 
         let esplora = blockchain.clone();
-        std::thread::spawn(move || Self::run(&mut wallet, receiver, esplora));
+        std::thread::spawn(move || Self::run(&mut wallet, &mut storage, receiver, esplora, min_confirmations));
 
         Ok(DlcDevKitWallet {
             blockchain,
@@ -137,13 +728,26 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
             derive_signer,
             secp,
             name: name.to_string(),
+            min_confirmations,
+            funding_utxos: Mutex::new(None),
+            external_descriptor: public_descriptors.0,
+            internal_descriptor: public_descriptors.1,
+            custom_descriptors,
+            payout_whitelist: Vec::new(),
+            next_payout: AtomicUsize::new(0),
+            address_reuse: AddressReusePolicy::default(),
+            held_payout: Mutex::new(None),
+            held_change: Mutex::new(None),
+            node_pubkey,
         })
     }
 
     pub fn run(
         wallet: &mut PersistedWallet<SledStorageProvider>,
+        storage: &mut SledStorageProvider,
         receiver: Receiver<WalletOperation>,
         blockchain: Arc<EsploraClient>,
+        min_confirmations: u32,
     ) {
         while let Ok(op) = receiver.recv() {
             match op {
@@ -174,7 +778,16 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
                     }
                 }
                 WalletOperation::Balance(responder) => {
-                    let balance = wallet.balance();
+                    // Confirmed funds below the threshold are reported as pending.
+                    let tip = wallet.latest_checkpoint().height();
+                    let unspendable = unspendable_utxos(wallet, min_confirmations)
+                        .into_iter()
+                        .map(|utxo| {
+                            let is_coinbase = utxo_is_coinbase(wallet, &utxo);
+                            (utxo, is_coinbase)
+                        })
+                        .collect::<Vec<_>>();
+                    let balance = balance_with_threshold(wallet.balance(), &unspendable, tip);
                     if let Err(e) = responder.send(balance) {
                         tracing::error!(message=?e, "Could not send message in balance message")
                     }
@@ -191,20 +804,42 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
                         tracing::error!(message=?e, "Could not send message in balance message")
                     }
                 }
-                WalletOperation::SendToAddress(address, amount, fee_rate, responder) => {
+                WalletOperation::SendToAddress(address, amount, fee_rate, utxos, responder) => {
                     let send = |wallet: &mut PersistedWallet<SledStorageProvider>| -> Result<Txid, WalletError> {
+                        let unspendable: Vec<OutPoint> = unspendable_utxos(wallet, min_confirmations)
+                            .iter()
+                            .map(|utxo| utxo.outpoint)
+                            .collect();
+                        if let Some(outpoint) = utxos.iter().find(|o| unspendable.contains(o)) {
+                            return Err(WalletError::UtxoNotSpendable(*outpoint));
+                        }
+
                         let mut txn_builder = wallet.build_tx();
 
                         txn_builder
                             .add_recipient(address.script_pubkey(), amount)
+                            .unspendable(unspendable)
                             .fee_rate(fee_rate);
+                        if !utxos.is_empty() {
+                            txn_builder
+                                .add_utxos(&utxos)
+                                .map_err(|_| WalletError::UtxoNotSpendable(utxos[0]))?
+                                .manually_selected_only();
+                        }
 
-                        let mut psbt = txn_builder.finish().unwrap();
+                        let mut psbt = txn_builder.finish().map_err(|e| match e {
+                            CreateTxError::CoinSelection(e) => WalletError::InsufficientFunds {
+                                needed: e.needed,
+                                available: e.available,
+                            },
+                            e => WalletError::CreateTx(e.to_string()),
+                        })?;
 
                         wallet.sign(&mut psbt, SignOptions::default())?;
 
                         let tx = psbt.extract_tx()?;
 
+                        blockchain.approve(&tx, TxPurpose::Send)?;
                         blockchain.blocking_client.broadcast(&tx)?;
 
                         Ok(tx.compute_txid())
@@ -224,6 +859,23 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
                         tracing::error!(message=?e, "Could not send message to get transactions.")
                     }
                 }
+                WalletOperation::TransactionHistory(responder) => {
+                    let transactions = wallet
+                        .transactions()
+                        .map(|t| {
+                            let (height, time) = match t.chain_position {
+                                ChainPosition::Confirmed(anchor) => {
+                                    (Some(anchor.block_id.height), anchor.confirmation_time)
+                                }
+                                ChainPosition::Unconfirmed(last_seen) => (None, last_seen),
+                            };
+                            WalletTransaction { transaction: t.tx_node.tx, height, time }
+                        })
+                        .collect();
+                    if let Err(e) = responder.send(transactions) {
+                        tracing::error!(message=?e, "Could not send message to get transaction history.")
+                    }
+                }
                 WalletOperation::ListUtxos(responder) => {
                     let utxos: Vec<LocalOutput> = wallet
                         .list_unspent()
@@ -234,6 +886,55 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
                         tracing::error!(message=?e, "Could not send message to get utxos.")
                     }
                 }
+                WalletOperation::ListSpendableUtxos(responder) => {
+                    let unspendable = unspendable_utxos(wallet, min_confirmations);
+                    let utxos: Vec<LocalOutput> = wallet
+                        .list_unspent()
+                        .filter(|utxo| !unspendable.iter().any(|u| u.outpoint == utxo.outpoint))
+                        .collect();
+                    if let Err(e) = responder.send(utxos) {
+                        tracing::error!(message=?e, "Could not send message to get spendable utxos.")
+                    }
+                }
+                WalletOperation::Rescan(from_height, responder) => {
+                    let rescan = |wallet: &mut PersistedWallet<SledStorageProvider>, storage: &mut SledStorageProvider| -> Result<RescanResult, WalletError> {
+                        let from_height = match from_height {
+                            Some(height) => height,
+                            None => storage.get_birth_height()?.unwrap_or(0),
+                        };
+                        tracing::info!(from_height, "Rescanning wallet.");
+
+                        let request = wallet.start_full_scan();
+                        let mut update = blockchain
+                            .blocking_client
+                            .full_scan(request, STOP_GAP, PARALLEL_REQUESTS)?;
+                        // Esplora returns the full history of each script pubkey, so history
+                        // from before the rescan height is dropped before it reaches the wallet.
+                        retain_from_height(&mut update.tx_update, from_height);
+                        wallet.apply_update(update)?;
+                        wallet.persist(storage)?;
+
+                        let transactions = wallet
+                            .transactions()
+                            .filter(|tx| match tx.chain_position {
+                                ChainPosition::Confirmed(anchor) => anchor.block_id.height >= from_height,
+                                ChainPosition::Unconfirmed(_) => true,
+                            })
+                            .count();
+
+                        Ok(RescanResult { transactions, balance: wallet.balance() })
+                    };
+                    let result = rescan(wallet, storage);
+                    if let Err(e) = responder.send(result) {
+                        tracing::error!(message=?e, "Could not send message to rescan wallet.")
+                    }
+                }
+                WalletOperation::TipHeight(responder) => {
+                    let height = wallet.latest_checkpoint().height();
+                    if let Err(e) = responder.send(height) {
+                        tracing::error!(message=?e, "Could not send message to get tip height.")
+                    }
+                }
                 WalletOperation::NextDerivationIndex(responder) => {
                     let next_index = wallet.next_derivation_index(KeychainKind::External);
                     if let Err(e) = responder.send(next_index) {
@@ -255,17 +956,108 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
         }
     }
 
+    /// Send contract payouts only to `whitelist`, in turn, instead of to new wallet addresses.
+    /// Fails if an address is not for the network of the wallet. An empty whitelist pays out
+    /// to new wallet addresses.
+    pub fn set_payout_whitelist(&mut self, whitelist: Vec<Address>) -> Result<(), WalletError> {
+        if let Some(address) = whitelist
+            .iter()
+            .find(|address| !address.as_unchecked().is_valid_for_network(self.network))
+        {
+            return Err(WalletError::AddressNetworkMismatch {
+                address: address.to_string(),
+                network: self.network,
+            });
+        }
+        self.payout_whitelist = whitelist;
+        Ok(())
+    }
+
+    /// Whether contracts get new payout and change addresses. See [AddressReusePolicy].
+    pub fn set_address_reuse_policy(&mut self, policy: AddressReusePolicy) {
+        self.address_reuse = policy;
+    }
+
+    /// New payout or change address for a contract. Under
+    /// [AddressReusePolicy::ReuseUntilFunded] the last one handed out is returned again while
+    /// only contracts that never funded use it.
+    fn contract_address(&self, keychain: KeychainKind) -> Result<Address, ManagerError> {
+        let held = match keychain {
+            KeychainKind::External => &self.held_payout,
+            KeychainKind::Internal => &self.held_change,
+        };
+        let mut held = held.lock().unwrap();
+        let reuse = self.address_reuse == AddressReusePolicy::ReuseUntilFunded;
+        if let (true, Some(address)) = (reuse, held.as_ref()) {
+            let contracts = self.derive_signer.get_contracts()?;
+            if reusable_address(&contracts, &address.script_pubkey()) {
+                tracing::info!(address = address.to_string(), "Reusing address of an unfunded contract.");
+                return Ok(address.clone());
+            }
+        }
+        let address = match keychain {
+            KeychainKind::External => self.new_external_address()?,
+            KeychainKind::Internal => self.new_change_address()?,
+        }
+        .address;
+        if reuse {
+            *held = Some(address.clone());
+        }
+        Ok(address)
+    }
+
+    /// The next whitelisted payout address, if there is a whitelist.
+    fn next_payout_address(&self) -> Option<Address> {
+        if self.payout_whitelist.is_empty() {
+            return None;
+        }
+        let next = self.next_payout.fetch_add(1, Ordering::Relaxed);
+        Some(self.payout_whitelist[next % self.payout_whitelist.len()].clone())
+    }
+
+    /// Sync the wallet and refresh its fee estimates. A failed refresh keeps the last estimates.
     pub fn sync(&self) -> Result<(), WalletError> {
         let (sender, receiver) = unbounded();
         self.sender
             .send(WalletOperation::Sync(sender))
             .map_err(|e| WalletError::SendMessage(e.to_string()))?;
-        receiver.recv()?
+        let synced = receiver.recv()?;
+        if let Err(e) = self.refresh_fee_estimates() {
+            tracing::warn!(error=?e, "Could not refresh fee estimates. Keeping the last ones.");
+        }
+        synced
+    }
+
+    /// Replace the estimates of the confirmation targets [confirmation_target] hands out with
+    /// the ones esplora reports, in sats per 1000 weight. Other targets keep their fixed value.
+    pub fn refresh_fee_estimates(&self) -> Result<(), WalletError> {
+        let estimates = self
+            .blockchain
+            .blocking_client
+            .get_fee_estimates()
+            .map_err(|e| WalletError::Esplora(Box::new(e)))?;
+        for (target, blocks) in ESTIMATED_TARGETS {
+            let (Some(sat_per_vb), Some(fee)) = (fee_rate_from_estimates(blocks, &estimates), self.fees.get(&target)) else {
+                continue;
+            };
+            let sats_per_kw = ((sat_per_vb * 250.0).ceil() as u32).max(MIN_FEERATE);
+            fee.store(sats_per_kw, Ordering::Release);
+        }
+        Ok(())
     }
 
     pub fn get_pubkey(&self) -> PublicKey {
         tracing::info!("Getting wallet public key.");
-        PublicKey::from_secret_key(&self.secp, &self.xprv.private_key)
+        self.node_pubkey
+    }
+
+    /// The wallet holds no private keys and cannot sign. See [WalletKeys::WatchOnly].
+    pub fn is_watch_only(&self) -> bool {
+        self.xprv.is_none()
+    }
+
+    fn seed(&self) -> Result<&Xpriv, WalletError> {
+        self.xprv.as_ref().ok_or(WalletError::WatchOnly)
     }
 
     pub fn get_balance(&self) -> Result<Balance, WalletError> {
@@ -298,6 +1090,25 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
         amount: Amount,
         fee_rate: FeeRate,
     ) -> Result<Txid, WalletError> {
+        self.send_from_utxos(address, amount, fee_rate, Vec::new())
+    }
+
+    /// Send `amount` to `address`, funded only by `utxos`. An empty list lets coin selection
+    /// choose from every spendable utxo.
+    pub fn send_from_utxos(
+        &self,
+        address: Address,
+        amount: Amount,
+        fee_rate: FeeRate,
+        utxos: Vec<OutPoint>,
+    ) -> Result<Txid, WalletError> {
+        self.seed()?;
+        if !address.as_unchecked().is_valid_for_network(self.network) {
+            return Err(WalletError::AddressNetworkMismatch {
+                address: address.to_string(),
+                network: self.network,
+            });
+        }
         tracing::info!(
             address = address.to_string(),
             amount =? amount,
@@ -306,7 +1117,7 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
         let (sender, receiver) = unbounded();
         self.sender
             .send(WalletOperation::SendToAddress(
-                address, amount, fee_rate, sender,
+                address, amount, fee_rate, utxos, sender,
             ))
             .map_err(|e| WalletError::SendMessage(e.to_string()))?;
         receiver.recv()?
@@ -320,6 +1131,74 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
         Ok(receiver.recv()?)
     }
 
+    /// Wallet transactions newest first. See [page_transactions] for the filters.
+    pub fn transaction_history(
+        &self,
+        limit: Option<usize>,
+        before_height: Option<u32>,
+    ) -> Result<Vec<WalletTransaction>, WalletError> {
+        let (sender, receiver) = unbounded();
+        self.sender
+            .send(WalletOperation::TransactionHistory(sender))
+            .map_err(|e| WalletError::SendMessage(e.to_string()))?;
+        Ok(page_transactions(receiver.recv()?, limit, before_height))
+    }
+
+    /// Scan the chain for wallet transactions. Defaults to the birth height of the wallet.
+    pub fn rescan(&self, from_height: Option<u32>) -> Result<RescanResult, WalletError> {
+        let (sender, receiver) = unbounded();
+        self.sender
+            .send(WalletOperation::Rescan(from_height, sender))
+            .map_err(|e| WalletError::SendMessage(e.to_string()))?;
+        receiver.recv()?
+    }
+
+    /// Height of the latest block the wallet has synced.
+    pub fn tip_height(&self) -> Result<u32, WalletError> {
+        let (sender, receiver) = unbounded();
+        self.sender
+            .send(WalletOperation::TipHeight(sender))
+            .map_err(|e| WalletError::SendMessage(e.to_string()))?;
+        Ok(receiver.recv()?)
+    }
+
+    /// Derivation paths the wallet uses, by purpose. Custom descriptors are shown in place of
+    /// the external and internal paths, without their private keys.
+    pub fn derivation_paths(&self) -> Vec<(&'static str, String)> {
+        let coin_type = if self.network == Network::Bitcoin { 0 } else { 1 };
+        let (external, internal) = match self.custom_descriptors {
+            true => (self.external_descriptor.to_string(), self.internal_descriptor.to_string()),
+            false => (format!("m/84'/{coin_type}'/0'/0"), format!("m/84'/{coin_type}'/0'/1")),
+        };
+        vec![
+            ("external", external),
+            ("internal", internal),
+            ("contract signer", SIGNER_KEY_PATH.to_string()),
+            ("contract secret", SECRET_KEY_PATH.to_string()),
+        ]
+    }
+
+    /// The Nostr public key derived from the wallet seed at the NIP-06 path, as an `npub`.
+    pub fn nostr_npub(&self) -> Result<String, WalletError> {
+        let secret_key = crate::io::nostr_secret_key_from_xprv(self.seed()?)?;
+        let (public_key, _) = secret_key.x_only_public_key(&self.secp);
+        Ok(crate::io::nip19_encode("npub", &public_key.serialize()))
+    }
+
+    /// The Nostr secret key derived from the wallet seed, as an `nsec`. Anyone with it can
+    /// read and send DLC messages as this node.
+    pub fn nostr_nsec(&self) -> Result<String, WalletError> {
+        let secret_key = crate::io::nostr_secret_key_from_xprv(self.seed()?)?;
+        Ok(crate::io::nip19_encode("nsec", &secret_key.secret_bytes()))
+    }
+
+    /// Secret the serial ids of offers are derived from. See [crate::rng::derive_serial_ids].
+    pub(crate) fn serial_id_secret(&self) -> Result<[u8; 32], WalletError> {
+        let path = DerivationPath::from_str(SERIAL_ID_KEY_PATH).expect("valid derivation path");
+        let key = self.seed()?.derive_priv(&self.secp, &path)?;
+        Ok(key.private_key.secret_bytes())
+    }
+
     pub fn list_utxos(&self) -> Result<Vec<LocalOutput>, WalletError> {
         let (sender, receiver) = unbounded();
         self.sender
@@ -327,6 +1206,77 @@ impl<S: DdkStorage> DlcDevKitWallet<S> {
             .map_err(|e| WalletError::SendMessage(e.to_string()))?;
         Ok(receiver.recv()?)
     }
+
+    /// Fund the offers made while `f` runs with only `utxos`. An empty list lets coin
+    /// selection choose.
+    pub(crate) fn with_funding_utxos<T>(&self, utxos: Vec<OutPoint>, f: impl FnOnce() -> T) -> T {
+        let chosen = (!utxos.is_empty()).then_some(utxos);
+        *self.funding_utxos.lock().unwrap() = chosen;
+        let result = f();
+        *self.funding_utxos.lock().unwrap() = None;
+        result
+    }
+
+    /// Utxos committed to contracts whose funding transaction has not confirmed. See
+    /// [reserved_outpoints].
+    pub fn reserved_outpoints(&self) -> Result<HashSet<OutPoint>, WalletError> {
+        let contracts = self
+            .derive_signer
+            .get_contracts()
+            .map_err(|e| WalletError::Contracts(e.to_string()))?;
+        Ok(reserved_outpoints(&contracts))
+    }
+
+    /// Value of the wallet utxos reserved for pending contracts. It is part of the balance
+    /// but cannot fund another contract.
+    pub fn reserved_balance(&self) -> Result<Amount, WalletError> {
+        let reserved = self.reserved_outpoints()?;
+        Ok(self
+            .list_utxos()?
+            .iter()
+            .filter(|utxo| reserved.contains(&utxo.outpoint))
+            .map(|utxo| utxo.txout.value)
+            .sum())
+    }
+}
+
+/// Confirmation target of the estimate for getting mined within `target_blocks`. Up to 2
+/// blocks is urgent, up to 6 blocks normal, and longer targets background.
+pub fn confirmation_target(target_blocks: u16) -> ConfirmationTarget {
+    match target_blocks {
+        0..=2 => ConfirmationTarget::UrgentOnChainSweep,
+        3..=6 => ConfirmationTarget::NonAnchorChannelFee,
+        _ => ConfirmationTarget::ChannelCloseMinimum,
+    }
+}
+
+/// The targets of [confirmation_target] and the fewest blocks each stands for, so a refreshed
+/// estimate never pays less than the blocks asked for need.
+const ESTIMATED_TARGETS: [(ConfirmationTarget, u16); 3] = [
+    (ConfirmationTarget::UrgentOnChainSweep, 1),
+    (ConfirmationTarget::NonAnchorChannelFee, 3),
+    (ConfirmationTarget::ChannelCloseMinimum, 7),
+];
+
+/// The sats/vbyte estimate for `blocks` from esplora's estimates by confirmation target: the
+/// one for the longest target that is not longer than `blocks`.
+pub(crate) fn fee_rate_from_estimates(blocks: u16, estimates: &HashMap<u16, f64>) -> Option<f64> {
+    estimates
+        .iter()
+        .filter(|(target, _)| **target <= blocks)
+        .max_by_key(|(target, _)| **target)
+        .map(|(_, sat_per_vb)| *sat_per_vb)
+}
+
+/// Fee rate in sats/vbyte that `estimator` expects to be mined within `target_blocks`.
+pub fn fee_rate_for_target<F: FeeEstimator + ?Sized>(estimator: &F, target_blocks: u16) -> u64 {
+    sats_per_vbyte(estimator.get_est_sat_per_1000_weight(confirmation_target(target_blocks)))
+}
+
+/// A fee rate in sats per 1000 weight units as sats/vbyte, rounded up. 1000 weight units are
+/// 250 vbytes.
+pub fn sats_per_vbyte(sats_per_kw: u32) -> u64 {
+    (sats_per_kw as u64).div_ceil(250)
 }
 
 impl<S: DdkStorage> FeeEstimator for DlcDevKitWallet<S> {
@@ -343,16 +1293,19 @@ impl<S: DdkStorage> dlc_manager::ContractSignerProvider for DlcDevKitWallet<S> {
 
     // Using the data deterministically generate a key id. From a child key.
     fn derive_signer_key_id(&self, _is_offer_party: bool, temp_id: [u8; 32]) -> [u8; 32] {
+        // Without a seed no signer is stored, so deriving the contract signer fails.
+        let Some(xprv) = &self.xprv else {
+            return temp_id;
+        };
         let (sender, receiver) = unbounded();
         self.sender
             .send(WalletOperation::NextDerivationIndex(sender))
             .expect("sender.");
         let newest_index = receiver.recv().expect("recv error");
-        let derivation_path = format!("m/84'/0'/0'/0'/{}", newest_index);
+        let derivation_path = format!("{}/{}", SIGNER_KEY_PATH, newest_index);
         let child_path = DerivationPath::from_str(&derivation_path)
             .expect("Not a valid derivation path to derive signer key.");
-        let child_key = self
-            .xprv
+        let child_key = xprv
             .derive_priv(&self.secp, &child_path)
             .expect("Could not get child key for derivation path.");
 
@@ -378,12 +1331,14 @@ impl<S: DdkStorage> dlc_manager::ContractSignerProvider for DlcDevKitWallet<S> {
     }
 
     fn derive_contract_signer(&self, key_id: [u8; 32]) -> Result<Self::Signer, ManagerError> {
+        self.seed()?;
         let info = self.derive_signer.get_key_information(key_id).unwrap();
         tracing::info!("Derived new contract signer.");
         Ok(SimpleSigner::new(info.secret_key))
     }
 
     fn get_secret_key_for_pubkey(&self, pubkey: &PublicKey) -> Result<SecretKey, ManagerError> {
+        self.seed()?;
         tracing::info!(
             pubkey = pubkey.to_string(),
             "Getting secret key from pubkey"
@@ -395,43 +1350,33 @@ impl<S: DdkStorage> dlc_manager::ContractSignerProvider for DlcDevKitWallet<S> {
         let (sender, receiver) = unbounded();
         self.sender
             .send(WalletOperation::NextDerivationIndex(sender))
-            .expect("sender.");
-        let newest_index = receiver.recv().expect("recv error");
-        let derivation_path = format!("m/86'/0'/0'/0'/{}", newest_index);
-        let child_path = DerivationPath::from_str(&derivation_path)
-            .expect("Not a valid derivation path to derive signer key.");
+            .map_err(|e| WalletError::SendMessage(e.to_string()))?;
+        let newest_index = receiver.recv().map_err(WalletError::from)?;
+        let derivation_path = format!("{}/{}", SECRET_KEY_PATH, newest_index);
+        let child_path = DerivationPath::from_str(&derivation_path).map_err(WalletError::from)?;
         let child_key = self
-            .xprv
+            .seed()?
             .derive_priv(&self.secp, &child_path)
-            .expect("Could not get child key for derivation path.");
+            .map_err(WalletError::from)?;
         tracing::info!("Retrieved new secret key.");
         Ok(child_key.private_key)
     }
 }
 
 impl<S: DdkStorage> dlc_manager::Wallet for DlcDevKitWallet<S> {
+    // Used for the payout script of contracts, so a payout whitelist takes its place.
     fn get_new_address(&self) -> Result<bitcoin::Address, ManagerError> {
+        if let Some(address) = self.next_payout_address() {
+            tracing::info!(address = address.to_string(), "Using whitelisted payout address for dlc manager");
+            return Ok(address);
+        }
         tracing::info!("Retrieving new address for dlc manager");
-        let (sender, receiver) = unbounded();
-        self.sender
-            .send(WalletOperation::NewExternalAddress(sender))
-            .expect("couldn't send new address");
-        Ok(receiver
-            .recv()
-            .expect("no receive")
-            .address)
+        self.contract_address(KeychainKind::External)
     }
 
     fn get_new_change_address(&self) -> Result<bitcoin::Address, ManagerError> {
         tracing::info!("Retrieving new change address for dlc manager");
-        let (sender, receiver) = unbounded();
-        self.sender
-            .send(WalletOperation::NewChangeAddress(sender))
-            .expect("couldn't send new address");
-        Ok(receiver
-            .recv()
-            .expect("no receive")
-            .address)
+        self.contract_address(KeychainKind::Internal)
     }
 
     fn sign_psbt_input(
@@ -439,6 +1384,7 @@ impl<S: DdkStorage> dlc_manager::Wallet for DlcDevKitWallet<S> {
         psbt: &mut bitcoin::psbt::Psbt,
         input_index: usize,
     ) -> Result<(), ManagerError> {
+        self.seed()?;
         tracing::info!("Signing psbt input for dlc manager.");
         let (sender, receiver) = unbounded();
         self.sender
@@ -447,11 +1393,12 @@ impl<S: DdkStorage> dlc_manager::Wallet for DlcDevKitWallet<S> {
                 input_index,
                 sender,
             ))
-            .expect("no send psbt input");
-        Ok(receiver.recv().expect("no sign").unwrap())
+            .map_err(|e| WalletError::SendMessage(e.to_string()))?;
+        Ok(receiver.recv().map_err(WalletError::from)??)
     }
 
-    // TODO: Does BDK have reserved UTXOs?
+    // Utxos are reserved by the contracts they fund, see [reserved_outpoints]. A contract that
+    // is rejected or fails releases its utxos.
     fn unreserve_utxos(&self, _outpoints: &[bitcoin::OutPoint]) -> Result<(), ManagerError> {
         Ok(())
     }
@@ -464,17 +1411,16 @@ impl<S: DdkStorage> dlc_manager::Wallet for DlcDevKitWallet<S> {
             .unwrap())
     }
 
-    // return all utxos
-    // fixme use coin selector
+    // Selects from the spendable utxos that no pending contract is funded with.
     fn get_utxos_for_amount(
         &self,
-        _amount: u64,
-        _fee_rate: u64,
+        amount: u64,
+        fee_rate: u64,
         _lock_utxos: bool,
     ) -> Result<Vec<dlc_manager::Utxo>, ManagerError> {
         let (sender, receiver) = unbounded();
         self.sender
-            .send(WalletOperation::ListUtxos(sender))
+            .send(WalletOperation::ListSpendableUtxos(sender))
             .expect("list utxos");
         let local_utxos = receiver
             .recv()
@@ -482,29 +1428,131 @@ impl<S: DdkStorage> dlc_manager::Wallet for DlcDevKitWallet<S> {
 
         let dlc_utxos = local_utxos
             .iter()
-            .map(|utxo| {
-                let address =
-                    Address::from_script(&utxo.txout.script_pubkey, self.network).unwrap();
-                dlc_manager::Utxo {
-                    tx_out: utxo.txout.clone(),
-                    outpoint: utxo.outpoint,
-                    address,
-                    redeem_script: ScriptBuf::new(),
-                    reserved: false,
+            .filter_map(|utxo| {
+                let descriptor = match utxo.keychain {
+                    KeychainKind::External => &self.external_descriptor,
+                    KeychainKind::Internal => &self.internal_descriptor,
+                };
+                let funding = funding_utxo(descriptor, utxo, self.network);
+                if funding.is_none() {
+                    tracing::debug!(outpoint = utxo.outpoint.to_string(), "Utxo cannot fund a DLC.");
                 }
+                funding
             })
             .collect();
 
-        Ok(dlc_utxos)
+        let reserved = self.reserved_outpoints()?;
+        let selected = match self.funding_utxos.lock().unwrap().as_deref() {
+            Some(chosen) => chosen_utxos(dlc_utxos, chosen, &reserved, amount, fee_rate)?,
+            None => select_utxos(dlc_utxos, &reserved, amount, fee_rate)?,
+        };
+        Ok(selected)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use bdk_chain::ConfirmationTime;
     use bitcoin::{key::rand::Fill, AddressType};
+    use bitcoin::{Address, Amount, FeeRate};
     use dlc_manager::ContractSignerProvider;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    use super::{
+        balance_with_threshold, chosen_utxos, fee_rate_for_target, fee_rate_from_estimates, funding_change, FUNDING_DUST_LIMIT, is_spendable, max_witness_len, reserved_outpoints, select_utxos,
+        page_transactions, retain_from_height, start_at_birthday, WalletTransaction, MANAGER_WITNESS_LEN,
+    };
+    use super::{funding_utxo, parse_descriptor, AddressReusePolicy, DlcDevKitWallet, WalletDescriptors, WalletKeys};
+    use crate::storage::SledStorageProvider;
+    use bdk_wallet::LocalOutput;
+    use bitcoin::secp256k1::Secp256k1;
+    use std::sync::Arc;
+    use lightning::chain::chaininterface::{ConfirmationTarget, FeeEstimator};
+    use bdk_chain::{Balance, BlockId, ConfirmationBlockTime, TxUpdate};
+    use bdk_wallet::{bitcoin::Network, template::Bip84, KeychainKind, Wallet};
+    use crate::error::WalletError;
+    use crate::test_util::{offered_contract, signed_contract, TestWallet};
+    use bitcoin::hashes::Hash;
+    use dlc_manager::contract::Contract;
+    use std::collections::HashSet;
+
+    /// Estimates a different fee rate for each confirmation target.
+    struct TargetEstimator;
 
-    use crate::test_util::TestWallet;
+    impl FeeEstimator for TargetEstimator {
+        fn get_est_sat_per_1000_weight(&self, target: ConfirmationTarget) -> u32 {
+            match target {
+                ConfirmationTarget::UrgentOnChainSweep => 5_000,
+                ConfirmationTarget::NonAnchorChannelFee => 2_000,
+                ConfirmationTarget::ChannelCloseMinimum => 253,
+                _ => 1_000,
+            }
+        }
+    }
+
+    #[test]
+    fn estimates_are_read_for_the_fewest_blocks_of_a_target() {
+        let estimates = HashMap::from([(1, 30.5), (3, 12.0), (6, 8.0), (144, 1.5)]);
+        assert_eq!(fee_rate_from_estimates(1, &estimates), Some(30.5));
+        assert_eq!(fee_rate_from_estimates(3, &estimates), Some(12.0));
+        assert_eq!(fee_rate_from_estimates(7, &estimates), Some(8.0));
+        assert_eq!(fee_rate_from_estimates(1_008, &estimates), Some(1.5));
+        assert_eq!(fee_rate_from_estimates(1, &HashMap::new()), None);
+    }
+
+    #[test]
+    fn funding_target_uses_the_estimate_for_its_blocks() {
+        let estimator = TargetEstimator;
+        assert_eq!(fee_rate_for_target(&estimator, 1), 20);
+        assert_eq!(fee_rate_for_target(&estimator, 3), 8);
+        assert_eq!(fee_rate_for_target(&estimator, 6), 8);
+        // 253 sats per 1000 weight rounds up to 2 sats/vbyte.
+        assert_eq!(fee_rate_for_target(&estimator, 144), 2);
+        assert_eq!(
+            fee_rate_for_target(&estimator, 3),
+            estimator.get_est_sat_per_1000_weight(ConfirmationTarget::NonAnchorChannelFee).div_ceil(250) as u64
+        );
+    }
+
+    #[test]
+    fn utxo_below_min_confirmations_is_excluded() {
+        let one_conf = ConfirmationTime::Confirmed { height: 100, time: 0 };
+        assert!(!is_spendable(&one_conf, 100, 2, false));
+        assert!(is_spendable(&one_conf, 101, 2, false));
+
+        let unconfirmed = ConfirmationTime::Unconfirmed { last_seen: 0 };
+        assert!(is_spendable(&unconfirmed, 100, 0, false));
+        assert!(!is_spendable(&unconfirmed, 100, 1, false));
+
+        // Coinbase outputs need 100 confirmations regardless of the threshold.
+        assert!(!is_spendable(&one_conf, 150, 2, true));
+        assert!(is_spendable(&one_conf, 199, 2, true));
+    }
+
+    #[test]
+    fn immature_coinbase_is_not_moved_to_pending() {
+        let utxo = |vout: u32, sats: u64, height: u32| LocalOutput {
+            outpoint: bitcoin::OutPoint::new(bitcoin::Txid::all_zeros(), vout),
+            txout: bitcoin::TxOut { value: Amount::from_sat(sats), script_pubkey: bitcoin::ScriptBuf::new() },
+            keychain: KeychainKind::External,
+            is_spent: false,
+            derivation_index: vout,
+            confirmation_time: ConfirmationTime::Confirmed { height, time: 0 },
+        };
+        // The wallet counts the immature coinbase as immature and the young utxo as confirmed.
+        let balance = Balance {
+            immature: Amount::from_sat(5_000_000_000),
+            trusted_pending: Amount::ZERO,
+            untrusted_pending: Amount::ZERO,
+            confirmed: Amount::from_sat(30_000_000),
+        };
+        let unspendable = [(utxo(0, 5_000_000_000, 195), true), (utxo(1, 10_000_000, 200), false)];
+        let balance = balance_with_threshold(balance, &unspendable, 200);
+        assert_eq!(balance.immature, Amount::from_sat(5_000_000_000));
+        assert_eq!(balance.confirmed, Amount::from_sat(20_000_000));
+        assert_eq!(balance.trusted_pending, Amount::from_sat(10_000_000));
+    }
 
     #[test]
     fn address_is_p2wpkh() {
@@ -523,4 +1571,431 @@ mod tests {
         let key_info = test.wallet.derive_contract_signer(gen_key_id);
         assert!(key_info.is_ok())
     }
+
+    #[test]
+    fn send_reports_structured_errors() {
+        let test = TestWallet::create_wallet("send_reports_structured_errors");
+        let fee_rate = FeeRate::from_sat_per_vb(1).unwrap();
+
+        let address = test.wallet.new_external_address().unwrap().address;
+        let send = test.wallet.send_to_address(address, Amount::from_sat(10_000), fee_rate);
+        assert!(matches!(send, Err(WalletError::InsufficientFunds { .. })));
+
+        let mainnet = Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq")
+            .unwrap()
+            .assume_checked();
+        let send = test.wallet.send_to_address(mainnet, Amount::from_sat(10_000), fee_rate);
+        assert!(matches!(send, Err(WalletError::AddressNetworkMismatch { .. })));
+    }
+
+    #[test]
+    fn custom_descriptor_funds_contracts() {
+        let secp = Secp256k1::new();
+        let xprv = bitcoin::bip32::Xpriv::new_master(Network::Regtest, &[9u8; 32]).unwrap();
+        let descriptors = WalletDescriptors {
+            external: format!("wpkh({xprv}/84'/1'/5'/0/*)"),
+            internal: format!("wpkh({xprv}/84'/1'/5'/1/*)"),
+        };
+        let path = "tests/data/custom_descriptor";
+        let storage = Arc::new(SledStorageProvider::new(path).unwrap());
+        let seed = bitcoin::bip32::Xpriv::new_master(Network::Regtest, &[3u8; 32]).unwrap();
+        let wallet = DlcDevKitWallet::new(
+            "custom",
+            WalletKeys::Seed(seed),
+            "http://localhost:30000",
+            Network::Regtest,
+            path,
+            storage,
+            0,
+            None,
+            None,
+            Some(descriptors.clone()),
+        )
+        .unwrap();
+
+        let address = wallet.new_external_address().unwrap();
+        let (external, _) = parse_descriptor(&descriptors.external, &secp, Network::Regtest).unwrap();
+        let expected = external
+            .at_derivation_index(address.index)
+            .unwrap()
+            .address(Network::Regtest)
+            .unwrap();
+        assert_eq!(address.address, expected);
+
+        // A utxo of the descriptor funds a contract like a utxo of the seed's descriptor.
+        let utxo = LocalOutput {
+            outpoint: bitcoin::OutPoint::new(bitcoin::Txid::all_zeros(), 0),
+            txout: bitcoin::TxOut {
+                value: Amount::from_sat(100_000),
+                script_pubkey: address.address.script_pubkey(),
+            },
+            keychain: KeychainKind::External,
+            is_spent: false,
+            derivation_index: address.index,
+            confirmation_time: ConfirmationTime::Unconfirmed { last_seen: 0 },
+        };
+        let funding = funding_utxo(&external, &utxo, Network::Regtest).unwrap();
+        assert_eq!(funding.address, address.address);
+        assert!(funding.redeem_script.is_empty());
+        let selected = select_utxos(vec![funding], &HashSet::new(), 50_000, 1).unwrap();
+        assert_eq!(selected[0].outpoint, utxo.outpoint);
+
+        let mainnet = bitcoin::bip32::Xpriv::new_master(Network::Bitcoin, &[9u8; 32]).unwrap();
+        let wrong_network = parse_descriptor(&format!("wpkh({mainnet}/0/*)"), &secp, Network::Regtest);
+        assert!(matches!(wrong_network, Err(WalletError::DescriptorNetworkMismatch { .. })));
+
+        drop(wallet);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn watch_only_wallet_cannot_send() {
+        let secp = Secp256k1::new();
+        let xprv = bitcoin::bip32::Xpriv::new_master(Network::Regtest, &[11u8; 32]).unwrap();
+        let xpub = bitcoin::bip32::Xpub::from_priv(&secp, &xprv);
+        let descriptors = WalletDescriptors {
+            external: format!("wpkh({xpub}/0/*)"),
+            internal: format!("wpkh({xpub}/1/*)"),
+        };
+        let path = "tests/data/watch_only";
+        let storage = Arc::new(SledStorageProvider::new(path).unwrap());
+        let wallet = DlcDevKitWallet::new(
+            "watch-only",
+            WalletKeys::WatchOnly { node_pubkey: xpub.public_key },
+            "http://localhost:30000",
+            Network::Regtest,
+            path,
+            storage,
+            0,
+            None,
+            None,
+            Some(descriptors),
+        )
+        .unwrap();
+        assert!(wallet.is_watch_only());
+        assert_eq!(wallet.get_pubkey(), xpub.public_key);
+
+        // Fails before coin selection, so no transaction is built or broadcast.
+        let address = wallet.new_external_address().unwrap().address;
+        let send = wallet.send_to_address(address, Amount::from_sat(10_000), FeeRate::from_sat_per_vb(1).unwrap());
+        assert!(matches!(send, Err(WalletError::WatchOnly)));
+        assert!(wallet.list_utxos().unwrap().is_empty());
+
+        drop(wallet);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn payouts_go_to_the_whitelist() {
+        let mut test = TestWallet::create_wallet("payouts_go_to_the_whitelist");
+        let cold = TestWallet::create_wallet("payouts_go_to_the_whitelist_cold");
+        let whitelist = vec![
+            cold.wallet.new_external_address().unwrap().address,
+            cold.wallet.new_external_address().unwrap().address,
+        ];
+
+        let fresh = dlc_manager::Wallet::get_new_address(&test.wallet).unwrap();
+        assert!(!whitelist.contains(&fresh));
+
+        test.wallet.set_payout_whitelist(whitelist.clone()).unwrap();
+        let payouts = (0..3)
+            .map(|_| dlc_manager::Wallet::get_new_address(&test.wallet).unwrap().script_pubkey())
+            .collect::<Vec<_>>();
+        assert_eq!(payouts[0], whitelist[0].script_pubkey());
+        assert_eq!(payouts[1], whitelist[1].script_pubkey());
+        assert_eq!(payouts[2], whitelist[0].script_pubkey());
+
+        let mainnet = Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq")
+            .unwrap()
+            .assume_checked();
+        let mismatch = test.wallet.set_payout_whitelist(vec![mainnet]);
+        assert!(matches!(mismatch, Err(WalletError::AddressNetworkMismatch { .. })));
+    }
+
+    #[test]
+    fn rejected_offers_give_back_their_addresses() {
+        use dlc_manager::Storage;
+        let mut test = TestWallet::create_wallet("rejected_offers_give_back_their_addresses");
+        let storage = test.wallet.derive_signer.clone();
+        let new_addresses = |wallet: &DlcDevKitWallet<SledStorageProvider>| {
+            (
+                dlc_manager::Wallet::get_new_address(wallet).unwrap(),
+                dlc_manager::Wallet::get_new_change_address(wallet).unwrap(),
+            )
+        };
+        let mut offer = offered_contract();
+        offer.is_offer_party = true;
+
+        // Without reuse every contract gets new addresses.
+        let (payout, change) = new_addresses(&test.wallet);
+        offer.offer_params.payout_script_pubkey = payout.script_pubkey();
+        offer.offer_params.change_script_pubkey = change.script_pubkey();
+        storage.create_contract(&offer).unwrap();
+        storage.update_contract(&Contract::Rejected(offer.clone())).unwrap();
+        assert_ne!(new_addresses(&test.wallet).0, payout);
+
+        test.wallet.set_address_reuse_policy(AddressReusePolicy::ReuseUntilFunded);
+        let (payout, change) = new_addresses(&test.wallet);
+        offer.id = [7u8; 32];
+        offer.offer_params.payout_script_pubkey = payout.script_pubkey();
+        offer.offer_params.change_script_pubkey = change.script_pubkey();
+        storage.create_contract(&offer).unwrap();
+
+        // The offer is pending, so the next contract gets new addresses.
+        let (pending_payout, _) = new_addresses(&test.wallet);
+        assert_ne!(pending_payout, payout);
+
+        // Once the offer is rejected its addresses are used again.
+        let (payout, change) = new_addresses(&test.wallet);
+        offer.id = [8u8; 32];
+        offer.offer_params.payout_script_pubkey = payout.script_pubkey();
+        offer.offer_params.change_script_pubkey = change.script_pubkey();
+        storage.create_contract(&offer).unwrap();
+        storage.update_contract(&Contract::Rejected(offer.clone())).unwrap();
+        assert_eq!(new_addresses(&test.wallet), (payout.clone(), change.clone()));
+        assert_eq!(new_addresses(&test.wallet), (payout, change));
+    }
+
+    #[test]
+    fn witness_len_by_address_type() {
+        let witness_len = |address: &str| {
+            let address = Address::from_str(address).unwrap().assume_checked();
+            max_witness_len(&address.script_pubkey())
+        };
+
+        let p2wpkh = witness_len("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq");
+        assert_eq!(p2wpkh, Some(MANAGER_WITNESS_LEN));
+        let p2tr = witness_len("bc1p5d7rjq7g6rdk2yhzks9smlaqtedr4dekq08ge8ztwac72sfr9rusxg3297");
+        assert_eq!(p2tr, Some(66));
+        let p2sh = witness_len("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy");
+        assert_eq!(p2sh, Some(MANAGER_WITNESS_LEN));
+
+        // Legacy outputs have no witness and script hashes need the script to size it.
+        assert_eq!(witness_len("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"), None);
+        let p2wsh = "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3";
+        assert_eq!(witness_len(p2wsh), None);
+    }
+
+    /// P2WPKH utxos of the given values, with the index of each value as the vout.
+    fn utxos(values: &[u64]) -> Vec<dlc_manager::Utxo> {
+        let address = Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq")
+            .unwrap()
+            .assume_checked();
+        values
+            .iter()
+            .enumerate()
+            .map(|(vout, value)| dlc_manager::Utxo {
+                tx_out: bitcoin::TxOut {
+                    value: Amount::from_sat(*value),
+                    script_pubkey: address.script_pubkey(),
+                },
+                outpoint: bitcoin::OutPoint::new(bitcoin::Txid::all_zeros(), vout as u32),
+                address: address.clone(),
+                redeem_script: bitcoin::ScriptBuf::new(),
+                reserved: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reserved_utxos_do_not_fund_another_contract() {
+        let utxos = utxos(&[50_000, 50_000, 50_000]);
+
+        // Two offers of 80,000 sats against a balance of 150,000.
+        let first = select_utxos(utxos.clone(), &HashSet::new(), 80_000, 1).unwrap();
+        assert_eq!(first.len(), 2);
+        let reserved = first.iter().map(|utxo| utxo.outpoint).collect::<HashSet<_>>();
+        let second = select_utxos(utxos.clone(), &reserved, 80_000, 1);
+        assert!(matches!(second, Err(WalletError::InsufficientFunds { .. })));
+        assert_eq!(select_utxos(utxos, &reserved, 40_000, 1).unwrap().len(), 1);
+
+        // An offer reserves its funding inputs until it is rejected.
+        let offer = offered_contract();
+        let reserved = reserved_outpoints(&[Contract::Offered(offer.clone())]);
+        assert_eq!(reserved.len(), offer.funding_inputs.len());
+        assert!(reserved_outpoints(&[Contract::Rejected(offer)]).is_empty());
+    }
+
+    #[test]
+    fn dust_change_goes_to_the_fee() {
+        // One input and a change output at 1 sat/vbyte.
+        let planned_fee = super::FUNDING_INPUT_WEIGHT.div_ceil(4) + super::CHANGE_OUTPUT_WEIGHT.div_ceil(4);
+        let amount = 50_000;
+
+        let dust = utxos(&[amount + planned_fee + 200]);
+        let funding = funding_change(&dust, amount, 1);
+        assert_eq!(funding.change, None);
+        assert_eq!(funding.absorbed, 200);
+        assert_eq!(funding.fee, planned_fee + 200);
+
+        let change = utxos(&[amount + planned_fee + FUNDING_DUST_LIMIT]);
+        let funding = funding_change(&change, amount, 1);
+        assert_eq!(funding.change, Some(FUNDING_DUST_LIMIT));
+        assert_eq!(funding.fee, planned_fee);
+        assert_eq!(funding.absorbed, 0);
+    }
+
+    #[test]
+    fn dust_change_is_avoided_with_another_utxo() {
+        let planned_fee = super::FUNDING_INPUT_WEIGHT.div_ceil(4) + super::CHANGE_OUTPUT_WEIGHT.div_ceil(4);
+        let amount = 50_000;
+        let reserved = HashSet::new();
+
+        // The large utxo alone would burn 500 sats of change. Spending the 5,000 sat utxo too
+        // costs less and keeps the change.
+        let candidates = utxos(&[amount + planned_fee + 500, 5_000, 20_000]);
+        let selected = select_utxos(candidates.clone(), &reserved, amount, 1).unwrap();
+        let values: Vec<u64> = selected.iter().map(|utxo| utxo.tx_out.value.to_sat()).collect();
+        assert_eq!(values, vec![amount + planned_fee + 500, 5_000]);
+        assert!(funding_change(&selected, amount, 1).change.unwrap() >= FUNDING_DUST_LIMIT);
+
+        // At a high fee rate another input costs more than the dust, so it goes to the fee.
+        let fee_rate = 50;
+        let planned_fee = fee_rate * planned_fee;
+        let candidates = utxos(&[amount + planned_fee + 500, 5_000]);
+        let selected = select_utxos(candidates, &reserved, amount, fee_rate).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(funding_change(&selected, amount, fee_rate).change, None);
+    }
+
+    #[test]
+    fn funding_transaction_has_no_dust_change() {
+        let signed = signed_contract();
+        let mut offer_params = signed.accepted_contract.offered_contract.offer_params.clone();
+        let accept_params = signed.accepted_contract.accept_params.clone();
+        let planned_fee = super::FUNDING_INPUT_WEIGHT.div_ceil(4) + super::CHANGE_OUTPUT_WEIGHT.div_ceil(4);
+        let amount = offer_params.collateral;
+
+        let candidates = utxos(&[amount + planned_fee + 500, 5_000]);
+        let selected = select_utxos(candidates, &HashSet::new(), amount, 1).unwrap();
+        offer_params.inputs = selected
+            .iter()
+            .enumerate()
+            .map(|(serial_id, utxo)| dlc::TxInputInfo {
+                outpoint: utxo.outpoint,
+                max_witness_len: MANAGER_WITNESS_LEN,
+                redeem_script: utxo.redeem_script.clone(),
+                serial_id: serial_id as u64,
+            })
+            .collect();
+        offer_params.input_amount = selected.iter().map(|utxo| utxo.tx_out.value.to_sat()).sum();
+
+        let total_collateral = offer_params.collateral + accept_params.collateral;
+        let payouts = [dlc::Payout { offer: total_collateral, accept: 0 }];
+        let transactions =
+            dlc::create_dlc_transactions(&offer_params, &accept_params, &payouts, 100, 1, 0, 0, 0).unwrap();
+        let change = transactions
+            .fund
+            .output
+            .iter()
+            .find(|output| output.script_pubkey == offer_params.change_script_pubkey)
+            .expect("the offer change is kept");
+        assert!(change.value.to_sat() >= FUNDING_DUST_LIMIT);
+        assert!(transactions
+            .fund
+            .output
+            .iter()
+            .all(|output| output.value.to_sat() >= FUNDING_DUST_LIMIT));
+    }
+
+    #[test]
+    fn only_chosen_utxos_fund_the_contract() {
+        let utxos = utxos(&[100_000, 30_000, 20_000]);
+        let outpoint = |vout| bitcoin::OutPoint::new(bitcoin::Txid::all_zeros(), vout);
+        let chosen = [outpoint(1), outpoint(2)];
+
+        let selected = chosen_utxos(utxos.clone(), &chosen, &HashSet::new(), 40_000, 1).unwrap();
+        let selected = selected.iter().map(|utxo| utxo.outpoint).collect::<Vec<_>>();
+        assert_eq!(selected, chosen);
+
+        // The larger utxo is not used to make up the difference.
+        let short = chosen_utxos(utxos.clone(), &chosen, &HashSet::new(), 60_000, 1);
+        assert!(matches!(short, Err(WalletError::InsufficientFunds { .. })));
+
+        let unknown = chosen_utxos(utxos.clone(), &[outpoint(7)], &HashSet::new(), 1_000, 1);
+        assert!(matches!(unknown, Err(WalletError::UtxoNotSpendable(o)) if o == outpoint(7)));
+        let reserved = HashSet::from([outpoint(1)]);
+        let reserved = chosen_utxos(utxos, &chosen, &reserved, 1_000, 1);
+        assert!(matches!(reserved, Err(WalletError::UtxoNotSpendable(o)) if o == outpoint(1)));
+    }
+
+    #[test]
+    fn new_wallet_chain_starts_at_birthday() {
+        let xprv = bitcoin::bip32::Xpriv::new_master(Network::Regtest, &[7u8; 32]).unwrap();
+        let mut wallet = Wallet::create(
+            Bip84(xprv, KeychainKind::External),
+            Bip84(xprv, KeychainKind::Internal),
+        )
+        .network(Network::Regtest)
+        .create_wallet_no_persist()
+        .unwrap();
+        assert_eq!(wallet.latest_checkpoint().height(), 0);
+
+        let birthday = BlockId {
+            height: 800_000,
+            hash: bitcoin::BlockHash::all_zeros(),
+        };
+        start_at_birthday(&mut wallet, birthday).unwrap();
+        // Sync requests blocks from the latest checkpoint.
+        assert_eq!(wallet.latest_checkpoint().block_id(), birthday);
+        assert!(wallet.local_chain().get(1).is_none());
+    }
+
+    #[test]
+    fn rescans_drop_history_from_before_the_height() {
+        let tx = |lock_time: u32| {
+            Arc::new(bitcoin::Transaction {
+                version: bitcoin::transaction::Version::TWO,
+                lock_time: bitcoin::absolute::LockTime::from_consensus(lock_time),
+                input: vec![],
+                output: vec![],
+            })
+        };
+        let anchor = |height: u32| ConfirmationBlockTime {
+            block_id: BlockId { height, hash: bitcoin::BlockHash::all_zeros() },
+            confirmation_time: u64::from(height),
+        };
+        let (old, new, unconfirmed) = (tx(1), tx(2), tx(3));
+        let mut update = TxUpdate::<ConfirmationBlockTime>::default();
+        update.txs.extend([old.clone(), new.clone(), unconfirmed.clone()]);
+        update.anchors.insert((anchor(99), old.compute_txid()));
+        update.anchors.insert((anchor(100), new.compute_txid()));
+
+        retain_from_height(&mut update, 100);
+        let txids: Vec<_> = update.txs.iter().map(|tx| tx.compute_txid()).collect();
+        assert_eq!(txids, vec![new.compute_txid(), unconfirmed.compute_txid()]);
+        assert_eq!(update.anchors.len(), 1);
+        assert!(update.anchors.iter().all(|(_, txid)| *txid == new.compute_txid()));
+    }
+
+    #[test]
+    fn transaction_pages_are_newest_first_and_limited() {
+        let tx = |lock_time: u32, height: Option<u32>, time: u64| WalletTransaction {
+            transaction: std::sync::Arc::new(bitcoin::Transaction {
+                version: bitcoin::transaction::Version::TWO,
+                lock_time: bitcoin::absolute::LockTime::from_consensus(lock_time),
+                input: vec![],
+                output: vec![],
+            }),
+            height,
+            time,
+        };
+        let history = vec![
+            tx(1, Some(100), 1_000),
+            tx(2, None, 5_000),
+            tx(3, Some(300), 3_000),
+            tx(4, Some(200), 2_000),
+            tx(5, Some(300), 3_500),
+        ];
+        let order = |page: Vec<WalletTransaction>| {
+            page.iter()
+                .map(|t| t.transaction.lock_time.to_consensus_u32())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(order(page_transactions(history.clone(), None, None)), [2, 5, 3, 4, 1]);
+        assert_eq!(order(page_transactions(history.clone(), Some(2), None)), [2, 5]);
+        assert_eq!(order(page_transactions(history.clone(), Some(2), Some(300))), [4, 1]);
+        assert!(page_transactions(history, Some(0), None).is_empty());
+    }
 }
@@ -0,0 +1,224 @@
+//! HTTP callbacks for contract events, for systems that cannot hold a gRPC stream open.
+//!
+//! Each event is POSTed as a JSON [WebhookEvent] to the URL set with
+//! [crate::builder::DdkBuilder::set_webhook_url]. Events are delivered one at a time in the
+//! order they happened, from a queue of their own, so a slow or failing endpoint never holds
+//! up contracts.
+use crate::storage::{DeadLetter, FundingConflict};
+use crate::util::contract_state;
+use crate::DdkEventHandler;
+use async_trait::async_trait;
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::signed_contract::SignedContract;
+use dlc_manager::contract::Contract;
+use dlc_manager::ContractId;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// Seconds to wait for the endpoint to respond to a delivery.
+pub const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+/// Deliveries of an event before it is dropped.
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+/// Seconds before the first retry. Each later retry waits twice as long.
+pub const WEBHOOK_RETRY_DELAY_SECS: u64 = 1;
+
+/// Body of a webhook request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// `offer_received`, `contract_confirmed`, `contract_closed`, `funding_conflict` or
+    /// `delivery_failed`.
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Hex contract id. Offers use their temporary id.
+    pub contract_id: String,
+    /// State the contract moved to. See [crate::util::contract_state].
+    pub state: String,
+    /// Unix time in seconds the event was observed.
+    pub timestamp: u64,
+}
+
+impl WebhookEvent {
+    fn new(event_type: &str, contract_id: ContractId, state: &str) -> Self {
+        Self {
+            event_type: event_type.to_string(),
+            contract_id: hex::encode(contract_id),
+            state: state.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+}
+
+/// [DdkEventHandler] that queues each event for delivery to a webhook.
+pub struct WebhookHandler {
+    queue: UnboundedSender<WebhookEvent>,
+}
+
+impl WebhookHandler {
+    /// Start delivering events to `url` on `runtime`.
+    pub fn spawn(runtime: &Handle, url: Url) -> anyhow::Result<Self> {
+        Self::spawn_with_retry_delay(runtime, url, Duration::from_secs(WEBHOOK_RETRY_DELAY_SECS))
+    }
+
+    pub(crate) fn spawn_with_retry_delay(
+        runtime: &Handle,
+        url: Url,
+        retry_delay: Duration,
+    ) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .build()?;
+        let (queue, mut events) = unbounded_channel::<WebhookEvent>();
+        runtime.spawn(async move {
+            while let Some(event) = events.recv().await {
+                deliver(&client, &url, &event, retry_delay).await;
+            }
+        });
+        Ok(Self { queue })
+    }
+
+    fn send(&self, event: WebhookEvent) {
+        let _ = self.queue.send(event);
+    }
+}
+
+/// POST `event` to `url` until the endpoint accepts it or [WEBHOOK_MAX_ATTEMPTS] is reached.
+async fn deliver(client: &reqwest::Client, url: &Url, event: &WebhookEvent, retry_delay: Duration) {
+    let body = match serde_json::to_vec(event) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!(error=?e, "Could not serialize webhook event.");
+            return;
+        }
+    };
+
+    let mut delay = retry_delay;
+    for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+        let response = client
+            .post(url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match response {
+            Ok(_) => return,
+            Err(e) => tracing::warn!(
+                event = event.event_type.as_str(),
+                contract_id = event.contract_id.as_str(),
+                attempt,
+                error=?e,
+                "Could not deliver webhook event."
+            ),
+        }
+        if attempt < WEBHOOK_MAX_ATTEMPTS {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+    tracing::error!(
+        event = event.event_type.as_str(),
+        contract_id = event.contract_id.as_str(),
+        "Dropped webhook event after {} attempts.",
+        WEBHOOK_MAX_ATTEMPTS
+    );
+}
+
+#[async_trait]
+impl DdkEventHandler for WebhookHandler {
+    async fn on_offer_received(&self, offer: &OfferedContract) {
+        self.send(WebhookEvent::new("offer_received", offer.id, "offered"));
+    }
+
+    async fn on_contract_confirmed(&self, contract: &SignedContract) {
+        let id = contract.accepted_contract.get_contract_id();
+        self.send(WebhookEvent::new("contract_confirmed", id, "confirmed"));
+    }
+
+    async fn on_contract_closed(&self, contract: &Contract) {
+        let state = contract_state(contract);
+        self.send(WebhookEvent::new("contract_closed", contract.get_id(), state));
+    }
+
+    async fn on_funding_conflict(&self, conflict: &FundingConflict) {
+        // The contract was moved to failed sign.
+        self.send(WebhookEvent::new("funding_conflict", conflict.contract_id, "failed sign"));
+    }
+
+    async fn on_delivery_failed(&self, dead_letter: &DeadLetter) {
+        // The contract keeps its state and is flagged until the message is retried.
+        self.send(WebhookEvent::new("delivery_failed", dead_letter.contract_id, "delivery failed"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::signed_contract;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Answers each request with the next status and returns the request bodies.
+    fn mock_server(statuses: Vec<u16>) -> (Url, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/events", listener.local_addr().unwrap())).unwrap();
+        let server = std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                let body = loop {
+                    let read = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.to_lowercase().strip_prefix("content-length: ").map(|l| l.parse::<usize>().unwrap()))
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break body.to_string();
+                        }
+                    }
+                };
+                bodies.push(body);
+                let response = format!("HTTP/1.1 {} OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            bodies
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn webhook_fires_on_confirmation() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        // The first delivery fails and is retried.
+        let (url, server) = mock_server(vec![500, 200]);
+        let webhook =
+            WebhookHandler::spawn_with_retry_delay(runtime.handle(), url, Duration::from_millis(10)).unwrap();
+
+        let contract = signed_contract();
+        runtime.block_on(webhook.on_contract_confirmed(&contract));
+
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0], bodies[1]);
+        let event: WebhookEvent = serde_json::from_str(&bodies[1]).unwrap();
+        assert_eq!(event.event_type, "contract_confirmed");
+        assert_eq!(event.state, "confirmed");
+        assert_eq!(
+            event.contract_id,
+            hex::encode(contract.accepted_contract.get_contract_id())
+        );
+        assert!(event.timestamp > 0);
+    }
+}
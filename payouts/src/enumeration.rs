@@ -15,7 +15,8 @@ pub fn create_contract_input(
     fee_rate: u64,
     oracle_pubkey: String,
     event_id: String,
-) -> ContractInput {
+) -> anyhow::Result<ContractInput> {
+    validate_payouts(&outcome_payouts, offer_collateral + accept_collateral)?;
     let contract_descriptor = ContractDescriptor::Enum(EnumDescriptor { outcome_payouts });
 
     let oracles = OracleInput {
@@ -29,10 +30,55 @@ pub fn create_contract_input(
         oracles,
     }];
 
-    ContractInput {
+    Ok(ContractInput {
         offer_collateral,
         accept_collateral,
         fee_rate,
         contract_infos,
+    })
+}
+
+/// Checks that the payouts of every outcome add up to `total_collateral`. The error names the
+/// first outcome that does not.
+pub fn validate_payouts(outcome_payouts: &[EnumerationPayout], total_collateral: u64) -> anyhow::Result<()> {
+    for outcome_payout in outcome_payouts {
+        let payout = &outcome_payout.payout;
+        let paid = payout.offer.saturating_add(payout.accept);
+        if paid != total_collateral {
+            return Err(anyhow::anyhow!(
+                "Outcome `{}` pays {} to the offerer and {} to the accepter, {} in total, but the total collateral is {}.",
+                outcome_payout.outcome,
+                payout.offer,
+                payout.accept,
+                paid,
+                total_collateral
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dlc::Payout;
+
+    #[test]
+    fn unbalanced_enum_payouts_are_rejected() {
+        let oracle_pk = "0d829c1cc556aa59060df5a9543c5357199ace5db9bcd5a8ddd6ee2fc7b6d174".to_string();
+        let payout = |outcome: &str, offer, accept| EnumerationPayout {
+            outcome: outcome.to_string(),
+            payout: Payout { offer, accept },
+        };
+        let balanced = vec![payout("rain", 100_000, 0), payout("sun", 30_000, 70_000)];
+        let contract = create_contract_input(balanced, 60_000, 40_000, 2, oracle_pk.clone(), "event".to_string());
+        assert!(contract.is_ok());
+
+        let unbalanced = vec![payout("rain", 100_000, 0), payout("sun", 30_000, 60_000), payout("snow", 0, 1)];
+        let error = create_contract_input(unbalanced, 60_000, 40_000, 2, oracle_pk, "event".to_string()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Outcome `sun` pays 30000 to the offerer and 60000 to the accepter, 90000 in total, but the total collateral is 100000."
+        );
     }
 }
@@ -0,0 +1,199 @@
+//! Fee rates with explicit units.
+//!
+//! Contract inputs take fee rates in sats/vbyte, BDK works in sats per 1000 weight units and
+//! other wallets quote sats per 1000 vbytes. A [FeeRate] is parsed from a number with its unit,
+//! `10sat/vb`, `2500sat/kwu` or `10000sat/kvb`, and kept in sats/kvbyte, which each of them
+//! converts to exactly.
+use std::fmt;
+use std::str::FromStr;
+
+use dlc_manager::contract::contract_input::ContractInput;
+use serde_json::Value;
+
+/// A unit a fee rate can be given in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeeRateUnit {
+    /// Sats per virtual byte, `sat/vb`.
+    SatPerVb,
+    /// Sats per 1000 weight units, `sat/kwu`.
+    SatPerKwu,
+    /// Sats per 1000 virtual bytes, `sat/kvb`.
+    SatPerKvb,
+}
+
+impl FeeRateUnit {
+    /// Sats/kvbyte of `value` in this unit.
+    fn to_sat_per_kvb(self, value: u64) -> Option<u64> {
+        match self {
+            FeeRateUnit::SatPerVb => value.checked_mul(1_000),
+            // A virtual byte is four weight units.
+            FeeRateUnit::SatPerKwu => value.checked_mul(4),
+            FeeRateUnit::SatPerKvb => Some(value),
+        }
+    }
+}
+
+impl FromStr for FeeRateUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sat/vb" | "sats/vb" | "sat/vbyte" | "sats/vbyte" => Ok(FeeRateUnit::SatPerVb),
+            "sat/kwu" | "sats/kwu" => Ok(FeeRateUnit::SatPerKwu),
+            "sat/kvb" | "sats/kvb" => Ok(FeeRateUnit::SatPerKvb),
+            unit => Err(anyhow::anyhow!(
+                "Unknown fee rate unit `{}`. Use sat/vb, sat/kwu or sat/kvb.",
+                unit
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FeeRateUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self {
+            FeeRateUnit::SatPerVb => "sat/vb",
+            FeeRateUnit::SatPerKwu => "sat/kwu",
+            FeeRateUnit::SatPerKvb => "sat/kvb",
+        };
+        write!(f, "{}", unit)
+    }
+}
+
+/// A fee rate, kept in sats/kvbyte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FeeRate(u64);
+
+impl FeeRate {
+    pub fn from_sat_per_vb(sat_per_vb: u64) -> Self {
+        FeeRate(sat_per_vb.saturating_mul(1_000))
+    }
+
+    pub fn from_sat_per_kvb(sat_per_kvb: u64) -> Self {
+        FeeRate(sat_per_kvb)
+    }
+
+    pub fn sat_per_kvb(&self) -> u64 {
+        self.0
+    }
+
+    /// Sats per 1000 weight units, rounded up.
+    pub fn sat_per_kwu(&self) -> u64 {
+        self.0.div_ceil(4)
+    }
+
+    /// Sats/vbyte, rounded up. Contract inputs only take whole sats/vbyte.
+    pub fn sat_per_vb(&self) -> u64 {
+        self.0.div_ceil(1_000)
+    }
+
+    /// Parse a whole number of sats followed by its unit, such as `10sat/vb`. A number without
+    /// a unit is read in `default_unit`, and refused without one.
+    pub fn parse(input: &str, default_unit: Option<FeeRateUnit>) -> anyhow::Result<Self> {
+        let input = input.trim();
+        let split = input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len());
+        let (value, unit) = input.split_at(split);
+        if value.is_empty() {
+            return Err(anyhow::anyhow!("Fee rate `{}` does not start with a number.", input));
+        }
+        let unit = match unit.trim() {
+            "" => default_unit.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Fee rate `{}` has no unit. Write it as {}sat/vb, {}sat/kwu or {}sat/kvb.",
+                    input,
+                    value,
+                    value,
+                    value
+                )
+            })?,
+            unit if unit.starts_with('.') => {
+                return Err(anyhow::anyhow!(
+                    "Fee rate `{}` is not a whole number. Use sat/kvb for fractional rates.",
+                    input
+                ))
+            }
+            unit => unit.parse()?,
+        };
+        let value = value.parse::<u64>()?;
+        let sat_per_kvb = unit
+            .to_sat_per_kvb(value)
+            .ok_or_else(|| anyhow::anyhow!("Fee rate `{}` is too large.", input))?;
+        Ok(FeeRate(sat_per_kvb))
+    }
+}
+
+impl FromStr for FeeRate {
+    type Err = anyhow::Error;
+
+    /// Parse a fee rate that states its unit.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FeeRate::parse(s, None)
+    }
+}
+
+impl fmt::Display for FeeRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 % 1_000 == 0 {
+            write!(f, "{}sat/vb", self.0 / 1_000)
+        } else {
+            write!(f, "{}sat/kvb", self.0)
+        }
+    }
+}
+
+/// Read a contract input from json. `feeRate` is either a number of sats/vbyte, as contract
+/// inputs define it, or a string with its unit. Strings without a unit are read in
+/// `default_unit`.
+pub fn parse_contract_input(json: &str, default_unit: Option<FeeRateUnit>) -> anyhow::Result<ContractInput> {
+    let mut contract_input = serde_json::from_str::<Value>(json)?;
+    if let Some(Value::String(rate)) = contract_input.get("feeRate") {
+        let rate = FeeRate::parse(rate, default_unit)?;
+        contract_input["feeRate"] = Value::from(rate.sat_per_vb());
+    }
+    Ok(serde_json::from_value(contract_input)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units_parse_to_the_same_rate() {
+        let rate = FeeRate::from_sat_per_vb(10);
+        assert_eq!(FeeRate::from_str("10sat/vb").unwrap(), rate);
+        assert_eq!(FeeRate::from_str("2500sat/kwu").unwrap(), rate);
+        assert_eq!(FeeRate::from_str("10000sat/kvb").unwrap(), rate);
+        assert_eq!(FeeRate::from_str(" 10 sats/vbyte ").unwrap(), rate);
+        assert_eq!(FeeRate::from_str("10SAT/VB").unwrap(), rate);
+        assert_eq!(rate.sat_per_kwu(), 2_500);
+        assert_eq!(rate.sat_per_kvb(), 10_000);
+        assert_eq!(rate.to_string(), "10sat/vb");
+
+        // Bare numbers need a default unit.
+        assert!(FeeRate::from_str("10").is_err());
+        assert_eq!(FeeRate::parse("10", Some(FeeRateUnit::SatPerVb)).unwrap(), rate);
+        assert_eq!(FeeRate::parse("2500", Some(FeeRateUnit::SatPerKwu)).unwrap(), rate);
+        assert!(FeeRate::from_str("10sat/byte").is_err());
+        assert!(FeeRate::from_str("1.5sat/vb").is_err());
+
+        // Rates between whole sats/vbyte round up.
+        let fractional = FeeRate::from_str("1500sat/kvb").unwrap();
+        assert_eq!(fractional.sat_per_vb(), 2);
+        assert_eq!(fractional.to_string(), "1500sat/kvb");
+    }
+
+    #[test]
+    fn contract_input_fee_rate_takes_a_unit() {
+        let json = |fee_rate: &str| {
+            format!(
+                r#"{{"offerCollateral":50000,"acceptCollateral":50000,"feeRate":{},"contractInfos":[]}}"#,
+                fee_rate
+            )
+        };
+        assert_eq!(parse_contract_input(&json("2"), None).unwrap().fee_rate, 2);
+        assert_eq!(parse_contract_input(&json(r#""2500sat/kwu""#), None).unwrap().fee_rate, 10);
+        assert!(parse_contract_input(&json(r#""10""#), None).is_err());
+        let input = parse_contract_input(&json(r#""10""#), Some(FeeRateUnit::SatPerVb)).unwrap();
+        assert_eq!(input.fee_rate, 10);
+    }
+}
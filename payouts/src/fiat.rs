@@ -0,0 +1,297 @@
+//! Contracts sized in dollars.
+//!
+//! A [FiatHedge] keeps the payout of the offer party worth a fixed notional in dollars at the
+//! price the oracle attests. At a price `p` the offer party is paid `notional / p` BTC. The
+//! offer collateral buys the notional at the reference price, and the counterparty puts up
+//! what it costs on top down to the floor price.
+//!
+//! `notional / p` is a curve, not a line. Payouts are linear in price between sample prices
+//! spaced geometrically from the floor to the cap, each paying the notional exactly. A line
+//! between two points of the curve lies above it, so between samples the offer party is paid
+//! a little more than the notional: at most `r² / 4` more for samples a ratio of `1 + r`
+//! apart. Below the floor the offer party is paid all the collateral, and above the cap what
+//! the notional is worth at the cap, so the notional only holds between the two.
+//!
+//! Prices are whole dollars. The oracle attests prices in steps of `10^precision` dollars, the
+//! precision of its event, and the notional holds at the attested price.
+use dlc_manager::contract::contract_input::ContractInput;
+use dlc_manager::payout_curve::PayoutPoint;
+use dlc_messages::oracle_msgs::{EventDescriptor, OracleAnnouncement};
+
+use crate::{
+    canonical_payout_points, linear_payout_function, max_outcome, numerical_contract_input,
+    oracle_numeric_infos, validate_offer_terms, validate_payout_points, DEFAULT_DUST_LIMIT,
+};
+
+const SATS_PER_BTC: u128 = 100_000_000;
+
+/// A contract that pays the offer party a fixed number of dollars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FiatHedge {
+    /// Dollars the payout of the offer party is worth.
+    pub notional: u64,
+    /// BTC/USD price now. The offer collateral is the notional at this price.
+    pub reference_price: u64,
+    /// Lowest price the notional holds at.
+    pub floor_price: u64,
+    /// Highest price the notional holds at. Without one it holds up to the highest price the
+    /// oracle can attest.
+    pub cap_price: Option<u64>,
+}
+
+impl FiatHedge {
+    /// Sats the notional is worth at `outcome`, rounded up.
+    fn sats_at(&self, scale: &PriceScale, outcome: u128) -> u128 {
+        (self.notional as u128 * SATS_PER_BTC * scale.outcomes).div_ceil(outcome * scale.dollars)
+    }
+}
+
+/// Prices of the outcomes of a numeric oracle event. Outcome `o` is the price
+/// `o * dollars / outcomes`.
+struct PriceScale {
+    dollars: u128,
+    outcomes: u128,
+}
+
+impl PriceScale {
+    fn new(precision: i32) -> anyhow::Result<Self> {
+        let step = match precision.unsigned_abs() {
+            exponent @ 0..=18 => 10u128.pow(exponent),
+            _ => return Err(anyhow::anyhow!("Oracle precision {} is out of range.", precision)),
+        };
+        Ok(if precision >= 0 {
+            PriceScale { dollars: step, outcomes: 1 }
+        } else {
+            PriceScale { dollars: 1, outcomes: step }
+        })
+    }
+
+    /// First outcome at or above `price`.
+    fn outcome_above(&self, price: u64) -> u128 {
+        (price as u128 * self.outcomes).div_ceil(self.dollars)
+    }
+
+    /// Last outcome at or below `price`.
+    fn outcome_below(&self, price: u64) -> u128 {
+        price as u128 * self.outcomes / self.dollars
+    }
+}
+
+/// Contract input for `hedge` on the numeric event of `announcement`, with `num_steps` sample
+/// prices from the floor to the cap. More samples pay closer to the notional between them.
+pub fn create_fiat_contract_input(
+    hedge: &FiatHedge,
+    announcement: &OracleAnnouncement,
+    num_steps: u64,
+    fee_rate: u64,
+) -> anyhow::Result<ContractInput> {
+    let event_id = &announcement.oracle_event.event_id;
+    let EventDescriptor::DigitDecompositionEvent(descriptor) =
+        &announcement.oracle_event.event_descriptor
+    else {
+        return Err(anyhow::anyhow!("Event {} is not a numeric event.", event_id));
+    };
+    let numeric_infos = oracle_numeric_infos();
+    if descriptor.is_signed
+        || descriptor.base as usize != numeric_infos.base
+        || descriptor.nb_digits as usize != numeric_infos.nb_digits[0]
+    {
+        return Err(anyhow::anyhow!(
+            "Event {} must have {} unsigned base {} digits.",
+            event_id,
+            numeric_infos.nb_digits[0],
+            numeric_infos.base
+        ));
+    }
+    if num_steps < 2 {
+        return Err(anyhow::anyhow!("A fiat hedge needs at least two sample prices."));
+    }
+    if hedge.floor_price >= hedge.reference_price
+        || hedge.cap_price.is_some_and(|cap| cap <= hedge.reference_price)
+    {
+        return Err(anyhow::anyhow!(
+            "The reference price must be above the floor price and below the cap price."
+        ));
+    }
+
+    let scale = PriceScale::new(descriptor.precision)?;
+    let max_outcome = max_outcome() as u128;
+    let floor = scale.outcome_above(hedge.floor_price);
+    if floor == 0 {
+        return Err(anyhow::anyhow!("The floor price must be above zero."));
+    }
+    let cap = hedge
+        .cap_price
+        .map_or(max_outcome, |cap| scale.outcome_below(cap))
+        .min(max_outcome);
+    if cap <= floor {
+        return Err(anyhow::anyhow!(
+            "The oracle attests no price between the floor and the cap."
+        ));
+    }
+
+    let total = hedge.sats_at(&scale, floor);
+    let offer = (hedge.notional as u128 * SATS_PER_BTC).div_ceil(hedge.reference_price as u128);
+    let total_collateral = u64::try_from(total)?;
+    let offer_collateral = u64::try_from(offer)?;
+    let accept_collateral = total_collateral.checked_sub(offer_collateral).ok_or_else(|| {
+        anyhow::anyhow!("The floor price is within one oracle price step of the reference price.")
+    })?;
+    validate_offer_terms(offer_collateral, accept_collateral, fee_rate)?;
+
+    // Prices just above the floor would pay the counterparty dust, so they pay the offer party
+    // all the collateral.
+    let dust = DEFAULT_DUST_LIMIT as u128;
+    let knee = (hedge.notional as u128 * SATS_PER_BTC * scale.outcomes)
+        .div_ceil(scale.dollars * (total - dust))
+        .max(floor);
+    if knee >= cap {
+        return Err(anyhow::anyhow!(
+            "No price between the floor and the cap pays the counterparty above the dust limit."
+        ));
+    }
+    let cap_payout = hedge.sats_at(&scale, cap);
+    if cap_payout < dust {
+        return Err(anyhow::anyhow!(
+            "The notional is worth less than the dust limit at the cap price."
+        ));
+    }
+
+    let point = |outcome: u128, payout: u128| PayoutPoint {
+        event_outcome: outcome as u64,
+        extra_precision: 0,
+        outcome_payout: payout as u64,
+    };
+    let mut points = vec![point(0, total), point(knee - 1, total)];
+    let ratio = (cap as f64 / knee as f64).powf(1.0 / (num_steps - 1) as f64);
+    for step in 0..num_steps {
+        let outcome = if step == num_steps - 1 {
+            cap
+        } else {
+            ((knee as f64 * ratio.powi(step as i32)).round() as u128).clamp(knee, cap)
+        };
+        points.push(point(outcome, hedge.sats_at(&scale, outcome).min(total)));
+    }
+    points.push(point(max_outcome, cap_payout));
+
+    let points = canonical_payout_points(points)?;
+    validate_payout_points(&points, total_collateral)?;
+    let payout_function = linear_payout_function(&points)?;
+    numerical_contract_input(
+        payout_function,
+        offer_collateral,
+        accept_collateral,
+        fee_rate,
+        announcement.oracle_public_key.to_string(),
+        event_id.clone(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::key::XOnlyPublicKey;
+    use bitcoin::secp256k1::schnorr::Signature;
+    use dlc_manager::contract::ContractDescriptor;
+    use dlc_messages::oracle_msgs::{DigitDecompositionEventDescriptor, OracleEvent};
+    use std::str::FromStr;
+
+    fn announcement(precision: i32, nb_digits: u16) -> OracleAnnouncement {
+        let key = XOnlyPublicKey::from_str(
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        )
+        .unwrap();
+        OracleAnnouncement {
+            announcement_signature: Signature::from_slice(&[1u8; 64]).unwrap(),
+            oracle_public_key: key,
+            oracle_event: OracleEvent {
+                oracle_nonces: vec![key; nb_digits as usize],
+                event_maturity_epoch: 1_700_000_000,
+                event_descriptor: EventDescriptor::DigitDecompositionEvent(
+                    DigitDecompositionEventDescriptor {
+                        base: 2,
+                        is_signed: false,
+                        unit: "usd/btc".to_string(),
+                        precision,
+                        nb_digits,
+                    },
+                ),
+                event_id: "btcusd".to_string(),
+            },
+        }
+    }
+
+    /// Sats the offer party is paid at each outcome of a numeric contract input.
+    fn offer_payouts(input: &ContractInput) -> impl Fn(u64) -> u64 {
+        let ContractDescriptor::Numerical(descriptor) = &input.contract_infos[0].contract_descriptor
+        else {
+            panic!("not a numeric contract");
+        };
+        let total = input.offer_collateral + input.accept_collateral;
+        let ranges = descriptor
+            .payout_function
+            .to_range_payouts(total, &descriptor.rounding_intervals)
+            .unwrap();
+        move |outcome| {
+            ranges
+                .iter()
+                .find(|r| r.start as u64 <= outcome && outcome < (r.start + r.count) as u64)
+                .unwrap()
+                .payout
+                .offer
+        }
+    }
+
+    /// Assert the offer party is paid the notional at `price`, a sat short at worst and at
+    /// most `tolerance` over.
+    fn assert_notional(sats: u64, price: u64, notional: u64, tolerance: f64) {
+        let value = sats as u128 * price as u128;
+        let target = notional as u128 * SATS_PER_BTC;
+        assert!((sats as u128 + 1) * price as u128 >= target, "{} sats at {}", sats, price);
+        assert!(value as f64 <= target as f64 * (1.0 + tolerance), "{} sats at {}", sats, price);
+    }
+
+    #[test]
+    fn payout_keeps_the_notional_between_floor_and_cap() {
+        let hedge = FiatHedge {
+            notional: 10_000,
+            reference_price: 50_000,
+            floor_price: 25_000,
+            cap_price: Some(200_000),
+        };
+        // Outcomes are prices in steps of $10.
+        let input = create_fiat_contract_input(&hedge, &announcement(1, 20), 50, 2).unwrap();
+        assert_eq!(input.offer_collateral, 20_000_000);
+        assert_eq!(input.accept_collateral, 20_000_000);
+
+        let payout = offer_payouts(&input);
+        for price in [25_000, 30_000, 42_370, 50_000, 77_770, 123_450, 200_000] {
+            assert_notional(payout(price / 10), price, hedge.notional, 0.001);
+        }
+        // Below the floor the offer party is paid everything, above the cap the notional at
+        // the cap.
+        assert_eq!(payout(1_000), 40_000_000);
+        assert_eq!(payout(50_000), 5_000_000);
+    }
+
+    #[test]
+    fn notional_holds_at_the_attested_price() {
+        let hedge = FiatHedge {
+            notional: 10_000,
+            reference_price: 50_000,
+            floor_price: 25_000,
+            cap_price: None,
+        };
+        // Outcomes are prices in steps of $100, so $50,050 is attested as $50,000.
+        let input = create_fiat_contract_input(&hedge, &announcement(2, 20), 200, 2).unwrap();
+        let payout = offer_payouts(&input);
+        assert_eq!(payout(50_050 / 100), payout(500));
+        for price in [25_000, 50_000, 1_000_000, 100_000_000] {
+            assert_notional(payout(price / 100), price, hedge.notional, 0.01);
+        }
+
+        let low_reference = FiatHedge { reference_price: 20_000, ..hedge };
+        assert!(create_fiat_contract_input(&low_reference, &announcement(2, 20), 200, 2).is_err());
+        assert!(create_fiat_contract_input(&hedge, &announcement(2, 10), 200, 2).is_err());
+    }
+}
@@ -1,8 +1,12 @@
 pub mod enumeration;
+pub mod fee_rate;
+pub mod fiat;
 
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
 use bitcoin::key::XOnlyPublicKey;
+use bitcoin::Script;
 use dlc_manager::contract::numerical_descriptor::NumericalDescriptor;
 use dlc_manager::{
     contract::{
@@ -11,11 +15,84 @@ use dlc_manager::{
     },
     payout_curve::{
         PayoutFunction, PayoutFunctionPiece, PayoutPoint, PolynomialPayoutCurvePiece,
-        RoundingInterval, RoundingIntervals,
+        RangePayout, RoundingInterval, RoundingIntervals,
     },
 };
 use dlc_trie::OracleNumericInfo;
 
+/// Smallest non-dust output for the P2WPKH payout addresses of a DDK wallet.
+pub const DEFAULT_DUST_LIMIT: u64 = 294;
+
+/// Smallest output value that is not dust for the type of `script_pubkey`.
+pub fn dust_limit(script_pubkey: &Script) -> u64 {
+    script_pubkey.minimal_non_dust().to_sat()
+}
+
+/// Weight of the funding transaction fields the parties share, from the DLC spec.
+const FUND_TX_BASE_WEIGHT: u64 = 214;
+/// Weight of the CET and refund transaction fields the parties share.
+const CET_BASE_WEIGHT: u64 = 500;
+/// Weight of an input without its script sig and witness.
+const TX_INPUT_BASE_WEIGHT: u64 = 164;
+/// Weight of an output without its script pubkey.
+const TX_OUTPUT_BASE_WEIGHT: u64 = 36;
+/// Length of the P2WPKH script pubkeys of the change and payout outputs of a DDK wallet.
+const P2WPKH_SCRIPT_LEN: u64 = 22;
+/// Witness length of a P2WPKH input.
+const P2WPKH_WITNESS_LEN: u64 = 107;
+
+/// What the offer party of a contract pays to fund it, in sats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TotalCost {
+    pub collateral: u64,
+    /// Share of the funding transaction fee.
+    pub funding_fee: u64,
+    /// Share of the CET or refund fee, locked in the funding output until the contract closes.
+    pub cet_fee_reserve: u64,
+}
+
+impl TotalCost {
+    /// The amount the wallet must be able to spend.
+    pub fn total(&self) -> u64 {
+        self.collateral + self.funding_fee + self.cet_fee_reserve
+    }
+}
+
+/// All-in cost of offering `contract_input` at `fee_rate` sats/vbyte, using the fee split of the
+/// DLC spec that the manager funds the contract with. Assumes the funding is a single P2WPKH
+/// input with a P2WPKH change output, as from a DDK wallet. Each extra input adds
+/// [`input_fee`] to the funding fee.
+pub fn estimate_total_cost(contract_input: &ContractInput, fee_rate: u64) -> TotalCost {
+    estimate_party_cost(contract_input.offer_collateral, fee_rate)
+}
+
+/// All-in cost for either party of a contract to put up `collateral` at `fee_rate` sats/vbyte.
+/// Both parties pay the same share of the fees. See [`estimate_total_cost`].
+pub fn estimate_party_cost(collateral: u64, fee_rate: u64) -> TotalCost {
+    let output_weight = TX_OUTPUT_BASE_WEIGHT + 4 * P2WPKH_SCRIPT_LEN;
+    let funding_weight = FUND_TX_BASE_WEIGHT / 2 + output_weight;
+    let cet_weight = CET_BASE_WEIGHT / 2 + output_weight;
+    TotalCost {
+        collateral,
+        funding_fee: weight_to_fee(funding_weight + input_weight(), fee_rate),
+        cet_fee_reserve: weight_to_fee(cet_weight, fee_rate),
+    }
+}
+
+/// Fee of one more P2WPKH funding input at `fee_rate` sats/vbyte.
+pub fn input_fee(fee_rate: u64) -> u64 {
+    weight_to_fee(input_weight(), fee_rate)
+}
+
+fn input_weight() -> u64 {
+    TX_INPUT_BASE_WEIGHT + P2WPKH_WITNESS_LEN
+}
+
+/// Fee of `weight` at `fee_rate`, rounding the virtual size up as rust-dlc does.
+fn weight_to_fee(weight: u64, fee_rate: u64) -> u64 {
+    weight.div_ceil(4) * fee_rate
+}
+
 pub fn generate_payout_curve(
     min_price: u64,
     max_price: u64,
@@ -23,6 +100,7 @@ pub fn generate_payout_curve(
     accept_collateral: u64,
     num_steps: u64,
     max_value: u64,
+    dust_limit: u64,
 ) -> anyhow::Result<PayoutFunction> {
     let total_collateral = offer_collateral + accept_collateral;
     let price_range = max_price - min_price;
@@ -49,21 +127,339 @@ pub fn generate_payout_curve(
         });
     }
 
-    let final_payout_piece = points[points.len() - 1].clone();
-
     // 20 digit oracle max value
-    let max_payout = PayoutPoint {
+    points.push(PayoutPoint {
         event_outcome: max_value,
         extra_precision: 0,
         outcome_payout: total_collateral,
+    });
+
+    payout_function_from_points(points, total_collateral, dust_limit)
+}
+
+/// Builds the payout function of a piecewise linear curve through `points`, in any order.
+/// Both parties derive the CETs of a numeric contract from its payout function, so the points
+/// are put in canonical order first and the same curve always builds the same function.
+pub fn payout_function_from_points(
+    points: Vec<PayoutPoint>,
+    total_collateral: u64,
+    dust_limit: u64,
+) -> anyhow::Result<PayoutFunction> {
+    let points = canonical_payout_points(points)?;
+    validate_payout_points(&points, total_collateral)?;
+    let points = clamp_dust(&points, total_collateral, dust_limit)?;
+    linear_payout_function(&points)
+}
+
+/// The payout function of lines between consecutive `points`, which must be in canonical order.
+fn linear_payout_function(points: &[PayoutPoint]) -> anyhow::Result<PayoutFunction> {
+    let pieces = points
+        .windows(2)
+        .map(|segment| {
+            Ok(PayoutFunctionPiece::PolynomialPayoutCurvePiece(
+                PolynomialPayoutCurvePiece::new(segment.to_vec())?,
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(PayoutFunction::new(pieces)?)
+}
+
+/// Builds a piecewise payout function from curve pieces that each cover a range of outcomes,
+/// such as a flat payout below a threshold and a linear one above it. The ranges, in any
+/// order, must cover every outcome from zero to `max_outcome` without gaps or overlaps, with
+/// adjacent ranges sharing their boundary outcome. Each piece must span exactly its range and
+/// pay the same as its neighbours at the shared boundaries, so the curve has no jumps.
+pub fn payout_function_from_segments(
+    mut segments: Vec<(RangeInclusive<u64>, PayoutFunctionPiece)>,
+    total_collateral: u64,
+    max_outcome: u64,
+) -> anyhow::Result<PayoutFunction> {
+    segments.sort_by_key(|(outcomes, _)| *outcomes.start());
+    let mut previous: Option<(u64, RangePayout)> = None;
+    for (outcomes, piece) in &segments {
+        let (first, last) = piece_endpoints(piece, total_collateral)?;
+        let (start, end) = (first.start as u64, (last.start + last.count - 1) as u64);
+        if (start, end) != (*outcomes.start(), *outcomes.end()) {
+            return Err(anyhow::anyhow!(
+                "Segment {:?} has a payout piece from outcome {} to {}.",
+                outcomes,
+                start,
+                end
+            ));
+        }
+
+        match &previous {
+            None if start != 0 => {
+                return Err(anyhow::anyhow!("Outcomes below {} have no payout.", start))
+            }
+            Some((previous_end, _)) if start > *previous_end => {
+                return Err(anyhow::anyhow!(
+                    "Segment {:?} starts after the segment before it ends at {}.",
+                    outcomes,
+                    previous_end
+                ))
+            }
+            Some((previous_end, _)) if start < *previous_end => {
+                return Err(anyhow::anyhow!(
+                    "Outcomes from {} to {} are in two segments.",
+                    start,
+                    previous_end
+                ))
+            }
+            Some((_, previous_last)) if previous_last.payout.offer != first.payout.offer => {
+                return Err(anyhow::anyhow!(
+                    "Outcome {} pays the offerer both {} and {}.",
+                    start,
+                    previous_last.payout.offer,
+                    first.payout.offer
+                ))
+            }
+            _ => {}
+        }
+        previous = Some((end, last));
+    }
+
+    match previous {
+        None => Err(anyhow::anyhow!("A payout function needs at least one segment.")),
+        Some((end, _)) if end != max_outcome => Err(anyhow::anyhow!(
+            "Outcomes from {} to {} have no payout.",
+            end + 1,
+            max_outcome
+        )),
+        Some(_) => Ok(PayoutFunction::new(
+            segments.into_iter().map(|(_, piece)| piece).collect(),
+        )?),
+    }
+}
+
+/// Payouts of the first and last outcomes of a single payout curve piece.
+fn piece_endpoints(
+    piece: &PayoutFunctionPiece,
+    total_collateral: u64,
+) -> anyhow::Result<(RangePayout, RangePayout)> {
+    let ranges = PayoutFunction::new(vec![piece.clone()])?
+        .to_range_payouts(total_collateral, &single_outcome_rounding())
+        .map_err(|e| anyhow::anyhow!("Invalid payout piece. {:?}", e))?;
+    match (ranges.first(), ranges.last()) {
+        (Some(first), Some(last)) => Ok((first.clone(), last.clone())),
+        _ => Err(anyhow::anyhow!("A payout piece pays no outcome.")),
+    }
+}
+
+fn single_outcome_rounding() -> RoundingIntervals {
+    RoundingIntervals {
+        intervals: vec![RoundingInterval {
+            begin_interval: 0,
+            rounding_mod: 1,
+        }],
+    }
+}
+
+/// Sorts payout points by outcome ascending and drops repeated points. Fails if two points at
+/// the same outcome have different payouts, as the curve would have two values there.
+pub fn canonical_payout_points(mut points: Vec<PayoutPoint>) -> anyhow::Result<Vec<PayoutPoint>> {
+    points.sort_by_key(|p| (p.event_outcome, p.extra_precision));
+    let mut canonical: Vec<PayoutPoint> = Vec::with_capacity(points.len());
+    for point in points {
+        match canonical.last() {
+            Some(last)
+                if last.event_outcome == point.event_outcome
+                    && last.extra_precision == point.extra_precision =>
+            {
+                if last.outcome_payout != point.outcome_payout {
+                    return Err(anyhow::anyhow!(
+                        "Outcome {} pays the offerer both {} and {}.",
+                        point.event_outcome,
+                        last.outcome_payout,
+                        point.outcome_payout
+                    ));
+                }
+            }
+            _ => canonical.push(point),
+        }
+    }
+    Ok(canonical)
+}
+
+/// Checks that no point of a piecewise linear curve pays the offerer more than the total
+/// collateral, so the accepter is paid the rest at every outcome. Outcomes between two valid
+/// points are interpolated between their payouts and are valid too.
+pub fn validate_payout_points(points: &[PayoutPoint], total_collateral: u64) -> anyhow::Result<()> {
+    match points.iter().find(|p| p.outcome_payout > total_collateral) {
+        Some(point) => Err(anyhow::anyhow!(
+            "Outcome {} pays the offerer {} but the total collateral is {}.",
+            point.event_outcome,
+            point.outcome_payout,
+            total_collateral
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks that both parties put up at least [DEFAULT_DUST_LIMIT] sats of collateral and
+/// that the fee rate is not zero. A contract outside these bounds cannot be funded or
+/// settled.
+pub fn validate_offer_terms(offer_collateral: u64, accept_collateral: u64, fee_rate: u64) -> anyhow::Result<()> {
+    if offer_collateral < DEFAULT_DUST_LIMIT {
+        return Err(anyhow::anyhow!(
+            "Offer collateral must be at least the dust limit of {} sats.",
+            DEFAULT_DUST_LIMIT
+        ));
+    }
+    if accept_collateral < DEFAULT_DUST_LIMIT {
+        return Err(anyhow::anyhow!(
+            "Accept collateral must be at least the dust limit of {} sats.",
+            DEFAULT_DUST_LIMIT
+        ));
+    }
+    if fee_rate == 0 {
+        return Err(anyhow::anyhow!("Fee rate must be at least 1 sat/vbyte."));
+    }
+    Ok(())
+}
+
+/// Checks the collateral and fee rate of `contract_input` with [validate_offer_terms] and
+/// its payouts with [validate_collateral].
+pub fn validate_contract_input(contract_input: &ContractInput) -> anyhow::Result<()> {
+    validate_offer_terms(
+        contract_input.offer_collateral,
+        contract_input.accept_collateral,
+        contract_input.fee_rate,
+    )?;
+    validate_collateral(contract_input)
+}
+
+/// Checks that no outcome of `contract_input` pays out more than the collateral of both
+/// parties. Numerical payout curves are evaluated at every outcome before they are capped at
+/// the total collateral, so every payout point and every piece of the curve is checked. The
+/// error names the first outcome that pays too much.
+pub fn validate_collateral(contract_input: &ContractInput) -> anyhow::Result<()> {
+    let total_collateral = contract_input.offer_collateral + contract_input.accept_collateral;
+    for contract_info in &contract_input.contract_infos {
+        match &contract_info.contract_descriptor {
+            ContractDescriptor::Enum(descriptor) => {
+                enumeration::validate_payouts(&descriptor.outcome_payouts, total_collateral)?
+            }
+            ContractDescriptor::Numerical(descriptor) => {
+                // Evaluated without a cap, so a payout above the total collateral shows up
+                // as such instead of being cut down to it.
+                let ranges = descriptor
+                    .payout_function
+                    .to_range_payouts(u64::MAX, &descriptor.rounding_intervals)
+                    .map_err(|e| anyhow::anyhow!("Invalid payout function. {:?}", e))?;
+                if let Some(range) = ranges.iter().find(|r| r.payout.offer > total_collateral) {
+                    return Err(anyhow::anyhow!(
+                        "Outcome {} pays the offerer {} but the total collateral is {}.",
+                        range.start,
+                        range.payout.offer,
+                        total_collateral
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reshapes an increasing, piecewise linear curve so that no outcome pays either party a
+/// non-zero amount below `dust_limit`. Outcomes that would pay the offerer dust pay them
+/// nothing and outcomes that would pay the accepter dust pay the offerer everything.
+fn clamp_dust(
+    points: &[PayoutPoint],
+    total_collateral: u64,
+    dust_limit: u64,
+) -> anyhow::Result<Vec<PayoutPoint>> {
+    if dust_limit == 0 {
+        return Ok(points.to_vec());
+    }
+    if total_collateral < 2 * dust_limit {
+        return Err(anyhow::anyhow!(
+            "Total collateral {} is too small for a dust limit of {}.",
+            total_collateral,
+            dust_limit
+        ));
+    }
+
+    let point = |event_outcome, outcome_payout| PayoutPoint {
+        event_outcome,
+        extra_precision: 0,
+        outcome_payout,
     };
+    // First outcome paying the offerer at least the dust limit.
+    let (low, low_payout) = crossing(points, dust_limit, true);
+    // Last outcome paying the accepter at least the dust limit.
+    let (high, high_payout) = crossing(points, total_collateral - dust_limit, false);
+    if high < low {
+        return Err(anyhow::anyhow!("No outcome pays both parties above the dust limit."));
+    }
+
+    let first = &points[0];
+    let last = &points[points.len() - 1];
+    let mut clamped = vec![point(first.event_outcome, 0)];
+    if low - 1 > first.event_outcome {
+        clamped.push(point(low - 1, 0));
+    }
+    clamped.push(point(low, low_payout));
+    clamped.extend(
+        points
+            .iter()
+            .filter(|p| p.event_outcome > low && p.event_outcome < high)
+            .map(|p| point(p.event_outcome, p.outcome_payout)),
+    );
+    if high > low {
+        clamped.push(point(high, high_payout));
+    }
+    clamped.push(point(high + 1, total_collateral));
+    if last.event_outcome > high + 1 {
+        clamped.push(point(last.event_outcome, total_collateral));
+    }
+    Ok(clamped)
+}
+
+/// Finds where an increasing, piecewise linear curve crosses `payout`. Returns the first
+/// outcome paying at least `payout` if `at_least`, otherwise the last outcome paying at most
+/// `payout`, along with the payout at that outcome rounded down. Rounding down keeps the
+/// payout on the required side of `payout` in both cases.
+fn crossing(points: &[PayoutPoint], payout: u64, at_least: bool) -> (u64, u64) {
+    for segment in points.windows(2) {
+        let (a, b) = (&segment[0], &segment[1]);
+        let crosses = if at_least {
+            a.outcome_payout < payout && b.outcome_payout >= payout
+        } else {
+            a.outcome_payout <= payout && b.outcome_payout > payout
+        };
+        if !crosses {
+            continue;
+        }
+
+        let run = (b.event_outcome - a.event_outcome) as u128;
+        let rise = (b.outcome_payout - a.outcome_payout) as u128;
+        let needed = (payout - a.outcome_payout) as u128 * run;
+        let offset = if at_least {
+            (needed + rise - 1) / rise
+        } else {
+            needed / rise
+        };
+        let value = a.outcome_payout as u128 + (offset * rise) / run;
+        return (a.event_outcome + offset as u64, value as u64);
+    }
+
+    let last = &points[points.len() - 1];
+    (last.event_outcome, last.outcome_payout)
+}
 
-    let payout_curve_pieces = PolynomialPayoutCurvePiece::new(points)?;
-    let upper_limit = PolynomialPayoutCurvePiece::new(vec![final_payout_piece, max_payout])?;
-    Ok(PayoutFunction::new(vec![
-        PayoutFunctionPiece::PolynomialPayoutCurvePiece(payout_curve_pieces),
-        PayoutFunctionPiece::PolynomialPayoutCurvePiece(upper_limit),
-    ])?)
+/// Digits of the numeric oracle events of the contracts built here.
+fn oracle_numeric_infos() -> OracleNumericInfo {
+    OracleNumericInfo {
+        base: 2,
+        nb_digits: vec![20],
+    }
+}
+
+/// Largest outcome of the numeric oracle events of the contracts built here.
+pub fn max_outcome() -> u64 {
+    let oracle_numeric_infos = oracle_numeric_infos();
+    oracle_numeric_infos.base.checked_pow(oracle_numeric_infos.nb_digits[0] as u32).unwrap() as u64 - 1
 }
 
 pub fn create_contract_input(
@@ -75,31 +471,46 @@ pub fn create_contract_input(
     fee_rate: u64,
     oracle_pubkey: String,
     event_id: String,
-) -> ContractInput {
-    let oracle_numeric_infos = OracleNumericInfo {
-        base: 2,
-        nb_digits: vec![20],
-    };
-
-    // Check the max value given the base and nb digits.
-    let max_value = oracle_numeric_infos.base.checked_pow(oracle_numeric_infos.nb_digits[0] as u32).unwrap() as u64 - 1;
-
+    dust_limit: Option<u64>,
+) -> anyhow::Result<ContractInput> {
     let payout_curve = generate_payout_curve(
         min_price,
         max_price,
         offer_collateral,
         accept_collateral,
         num_steps,
-        max_value,
-    )
-    .unwrap();
-    let rounding_intervals = RoundingIntervals {
-        intervals: vec![RoundingInterval {
-            begin_interval: 0,
-            rounding_mod: 1,
-        }],
-    };
-    
+        max_outcome(),
+        dust_limit.unwrap_or(DEFAULT_DUST_LIMIT),
+    )?;
+    numerical_contract_input(payout_curve, offer_collateral, accept_collateral, fee_rate, oracle_pubkey, event_id)
+}
+
+/// Contract input paying out by the curve of `segments`. See [payout_function_from_segments].
+/// The segments must cover the outcomes up to [max_outcome].
+pub fn create_contract_input_from_segments(
+    segments: Vec<(RangeInclusive<u64>, PayoutFunctionPiece)>,
+    offer_collateral: u64,
+    accept_collateral: u64,
+    fee_rate: u64,
+    oracle_pubkey: String,
+    event_id: String,
+) -> anyhow::Result<ContractInput> {
+    let payout_function =
+        payout_function_from_segments(segments, offer_collateral + accept_collateral, max_outcome())?;
+    numerical_contract_input(payout_function, offer_collateral, accept_collateral, fee_rate, oracle_pubkey, event_id)
+}
+
+fn numerical_contract_input(
+    payout_curve: PayoutFunction,
+    offer_collateral: u64,
+    accept_collateral: u64,
+    fee_rate: u64,
+    oracle_pubkey: String,
+    event_id: String,
+) -> anyhow::Result<ContractInput> {
+    let oracle_numeric_infos = oracle_numeric_infos();
+    let rounding_intervals = single_outcome_rounding();
+
     let contract_descriptor = ContractDescriptor::Numerical(NumericalDescriptor {
         payout_function: payout_curve,
         rounding_intervals,
@@ -117,31 +528,222 @@ pub fn create_contract_input(
         contract_descriptor,
         oracles,
     }];
-    ContractInput {
+    let contract_input = ContractInput {
         offer_collateral,
         accept_collateral,
         fee_rate,
         contract_infos,
-    }
+    };
+    validate_collateral(&contract_input)?;
+    Ok(contract_input)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{create_contract_input, generate_payout_curve};
+    use crate::{create_contract_input, create_contract_input_from_segments, estimate_total_cost, generate_payout_curve, input_fee, max_outcome, payout_function_from_points, payout_function_from_segments, validate_collateral, validate_contract_input, validate_offer_terms, validate_payout_points, DEFAULT_DUST_LIMIT};
+    use dlc_manager::contract::ContractDescriptor;
+    use dlc_manager::payout_curve::{PayoutFunction, PayoutFunctionPiece, PayoutPoint, PolynomialPayoutCurvePiece, RoundingInterval, RoundingIntervals};
 
     #[test]
     fn payout_curve() {
-        let curve = generate_payout_curve(13_000, 60_000, 50_000, 50_000, 10, 1045686);
+        let curve = generate_payout_curve(13_000, 60_000, 50_000, 50_000, 10, 1045686, DEFAULT_DUST_LIMIT);
         assert!(curve.is_ok())
     }
 
+    #[test]
+    fn no_sub_dust_payouts_at_curve_endpoints() {
+        let dust_limit = 1_000;
+        let (offer_collateral, accept_collateral) = (50_000, 50_000);
+        let total_collateral = offer_collateral + accept_collateral;
+        let curve = generate_payout_curve(0, 100_000, offer_collateral, accept_collateral, 3, 1_048_575, dust_limit).unwrap();
+        let rounding = RoundingIntervals {
+            intervals: vec![RoundingInterval {
+                begin_interval: 0,
+                rounding_mod: 1,
+            }],
+        };
+
+        let ranges = curve.to_range_payouts(total_collateral, &rounding).unwrap();
+        assert_eq!(ranges[0].payout.offer, 0);
+        assert_eq!(ranges[ranges.len() - 1].payout.accept, 0);
+        for range in ranges {
+            assert_eq!(range.payout.offer + range.payout.accept, total_collateral);
+            assert!(range.payout.offer == 0 || range.payout.offer >= dust_limit);
+            assert!(range.payout.accept == 0 || range.payout.accept >= dust_limit);
+        }
+    }
+
+    #[test]
+    fn collateral_below_dust_limit_is_rejected() {
+        let curve = generate_payout_curve(0, 100_000, 500, 500, 3, 1_048_575, 1_000);
+        assert!(curve.is_err())
+    }
+
+    #[test]
+    fn unbalanced_payout_points_are_rejected() {
+        let point = |event_outcome, outcome_payout| PayoutPoint {
+            event_outcome,
+            extra_precision: 0,
+            outcome_payout,
+        };
+        let balanced = vec![point(0, 0), point(50_000, 40_000), point(100_000, 100_000)];
+        assert!(validate_payout_points(&balanced, 100_000).is_ok());
+
+        let unbalanced = vec![point(0, 0), point(50_000, 40_000), point(100_000, 150_000)];
+        let error = validate_payout_points(&unbalanced, 100_000).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Outcome 100000 pays the offerer 150000 but the total collateral is 100000."
+        );
+    }
+
+    #[test]
+    fn payout_point_order_does_not_change_the_payout_function() {
+        let point = |event_outcome, outcome_payout| PayoutPoint {
+            event_outcome,
+            extra_precision: 0,
+            outcome_payout,
+        };
+        let sorted = vec![point(0, 0), point(50_000, 40_000), point(100_000, 100_000), point(1_048_575, 100_000)];
+        let shuffled = vec![
+            point(100_000, 100_000),
+            point(1_048_575, 100_000),
+            point(0, 0),
+            point(100_000, 100_000),
+            point(50_000, 40_000),
+        ];
+
+        let a = payout_function_from_points(sorted, 100_000, DEFAULT_DUST_LIMIT).unwrap();
+        let b = payout_function_from_points(shuffled, 100_000, DEFAULT_DUST_LIMIT).unwrap();
+        assert_eq!(serde_json::to_vec(&a).unwrap(), serde_json::to_vec(&b).unwrap());
+
+        // The oracle max value as the max price repeats the last point of the curve.
+        let curve = generate_payout_curve(0, 1_048_575, 50_000, 50_000, 3, 1_048_575, DEFAULT_DUST_LIMIT);
+        assert!(curve.is_ok());
+
+        let conflicting = vec![point(0, 0), point(50_000, 40_000), point(50_000, 60_000), point(100_000, 100_000)];
+        assert!(payout_function_from_points(conflicting, 100_000, DEFAULT_DUST_LIMIT).is_err());
+    }
+
     #[test]
     fn create_contract_input_test() {
         let oracle_pk = "0d829c1cc556aa59060df5a9543c5357199ace5db9bcd5a8ddd6ee2fc7b6d174".to_string();
         let event_id = "event".to_string();
-        let contract = create_contract_input(0, 100_000, 3, 50_000, 50_000, 2, oracle_pk, event_id);
+        let contract = create_contract_input(0, 100_000, 3, 50_000, 50_000, 2, oracle_pk, event_id, None).unwrap();
 
         let json = serde_json::to_string(&contract).unwrap();
         println!("{}", json)
     }
+
+    #[test]
+    fn total_cost_at_fee_rates() {
+        let oracle_pk = "0d829c1cc556aa59060df5a9543c5357199ace5db9bcd5a8ddd6ee2fc7b6d174".to_string();
+        let contract = create_contract_input(0, 100_000, 3, 50_000, 50_000, 2, oracle_pk, "event".to_string(), None).unwrap();
+
+        // 107 shared, 271 input and 124 change weight is 126 vbytes. The CET share is
+        // 250 shared and 124 payout weight, 94 vbytes.
+        let cost = estimate_total_cost(&contract, 1);
+        assert_eq!((cost.collateral, cost.funding_fee, cost.cet_fee_reserve), (50_000, 126, 94));
+        assert_eq!(cost.total(), 50_220);
+
+        let cost = estimate_total_cost(&contract, 10);
+        assert_eq!((cost.funding_fee, cost.cet_fee_reserve), (1_260, 940));
+        assert_eq!(input_fee(10), 680);
+    }
+
+    #[test]
+    fn collar_segments_are_continuous_at_the_thresholds() {
+        let point = |event_outcome, outcome_payout| PayoutPoint {
+            event_outcome,
+            extra_precision: 0,
+            outcome_payout,
+        };
+        let line = |from: PayoutPoint, to: PayoutPoint| {
+            PayoutFunctionPiece::PolynomialPayoutCurvePiece(PolynomialPayoutCurvePiece::new(vec![from, to]).unwrap())
+        };
+        let max = max_outcome();
+        // Flat below 40,000, linear to 60,000 and flat above.
+        let collar = || {
+            vec![
+                (60_000..=max, line(point(60_000, 80_000), point(max, 80_000))),
+                (0..=40_000, line(point(0, 20_000), point(40_000, 20_000))),
+                (40_000..=60_000, line(point(40_000, 20_000), point(60_000, 80_000))),
+            ]
+        };
+
+        let function = payout_function_from_segments(collar(), 100_000, max).unwrap();
+        let rounding = RoundingIntervals {
+            intervals: vec![RoundingInterval {
+                begin_interval: 0,
+                rounding_mod: 1,
+            }],
+        };
+        let ranges = function.to_range_payouts(100_000, &rounding).unwrap();
+        let payout_at = |outcome: usize| {
+            let range = ranges
+                .iter()
+                .find(|r| r.start <= outcome && outcome < r.start + r.count)
+                .unwrap();
+            range.payout.offer
+        };
+        assert_eq!(payout_at(0), 20_000);
+        assert_eq!(payout_at(39_999), 20_000);
+        assert_eq!(payout_at(40_000), 20_000);
+        assert_eq!(payout_at(40_001), 20_003);
+        assert_eq!(payout_at(50_000), 50_000);
+        assert_eq!(payout_at(60_000), 80_000);
+        assert_eq!(payout_at(60_001), 80_000);
+        assert_eq!(payout_at(max as usize), 80_000);
+        for window in ranges.windows(2) {
+            assert_eq!(window[0].start + window[0].count, window[1].start);
+        }
+
+        let oracle_pk = "0d829c1cc556aa59060df5a9543c5357199ace5db9bcd5a8ddd6ee2fc7b6d174".to_string();
+        let contract = create_contract_input_from_segments(collar(), 50_000, 50_000, 2, oracle_pk.clone(), "event".to_string()).unwrap();
+        assert!(validate_collateral(&contract).is_ok());
+
+        // A curve that rises above the total collateral is invalid from the first outcome
+        // that pays too much, not only at its payout points.
+        let mut over = create_contract_input_from_segments(collar(), 50_000, 50_000, 2, oracle_pk, "event".to_string()).unwrap();
+        if let ContractDescriptor::Numerical(descriptor) = &mut over.contract_infos[0].contract_descriptor {
+            descriptor.payout_function = PayoutFunction::new(vec![line(point(0, 0), point(200_000, 200_000))]).unwrap();
+        }
+        let error = validate_collateral(&over).unwrap_err();
+        assert_eq!(error.to_string(), "Outcome 100001 pays the offerer 100001 but the total collateral is 100000.");
+
+        let mut gap = collar();
+        gap[2] = (45_000..=60_000, line(point(45_000, 20_000), point(60_000, 80_000)));
+        let error = payout_function_from_segments(gap, 100_000, max).unwrap_err();
+        assert_eq!(error.to_string(), "Segment 45000..=60000 starts after the segment before it ends at 40000.");
+
+        let mut jump = collar();
+        jump[2] = (40_000..=60_000, line(point(40_000, 30_000), point(60_000, 80_000)));
+        let error = payout_function_from_segments(jump, 100_000, max).unwrap_err();
+        assert_eq!(error.to_string(), "Outcome 40000 pays the offerer both 20000 and 30000.");
+
+        let short = collar().into_iter().filter(|(outcomes, _)| *outcomes.start() < 60_000).collect();
+        assert!(payout_function_from_segments(short, 100_000, max).is_err());
+    }
+
+    #[test]
+    fn zero_collateral_and_fee_rate_are_rejected() {
+        let oracle_pk = "0d829c1cc556aa59060df5a9543c5357199ace5db9bcd5a8ddd6ee2fc7b6d174".to_string();
+        let mut contract = create_contract_input(0, 100_000, 3, 50_000, 50_000, 2, oracle_pk, "event".to_string(), None).unwrap();
+        assert!(validate_contract_input(&contract).is_ok());
+
+        let error = validate_offer_terms(0, 50_000, 2).unwrap_err();
+        assert_eq!(error.to_string(), "Offer collateral must be at least the dust limit of 294 sats.");
+        let error = validate_offer_terms(50_000, 0, 2).unwrap_err();
+        assert_eq!(error.to_string(), "Accept collateral must be at least the dust limit of 294 sats.");
+        let error = validate_offer_terms(50_000, 50_000, 0).unwrap_err();
+        assert_eq!(error.to_string(), "Fee rate must be at least 1 sat/vbyte.");
+        assert!(validate_offer_terms(DEFAULT_DUST_LIMIT - 1, 50_000, 2).is_err());
+        assert!(validate_offer_terms(DEFAULT_DUST_LIMIT, DEFAULT_DUST_LIMIT, 1).is_ok());
+
+        contract.fee_rate = 0;
+        assert!(validate_contract_input(&contract).is_err());
+        contract.fee_rate = 2;
+        contract.accept_collateral = 0;
+        assert!(validate_contract_input(&contract).is_err());
+    }
 }